@@ -0,0 +1,204 @@
+//! Local mirroring of documents that events link to (`[publish.assets]`).
+//!
+//! Files land in `<out_dir>/assets/<source dir>/<hash>-<name>`. The name is
+//! derived from the URL alone, so a document is downloaded once and later
+//! syncs only re-point records at the existing copy.
+
+use crate::config::LoadedSource;
+use crate::model::{Attachment, EventRecord};
+use crate::store::{EventQuery, EventStore};
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Mirrors document links of the source's stored events and rewrites their
+/// attachments to the mirror. Returns the years whose records changed, so
+/// their calendars can be rebuilt. Failed downloads keep the original link.
+pub fn mirror_source_assets(
+    store: &mut dyn EventStore,
+    source: &LoadedSource,
+    out_dir: &Path,
+) -> Result<BTreeSet<i32>> {
+    let assets = &source.config.publish.assets;
+    let mut changed_years = BTreeSet::new();
+    if !assets.mirror {
+        return Ok(changed_years);
+    }
+    let base_url = assets
+        .base_url
+        .as_deref()
+        .context("publish.assets.base_url missing")?
+        .trim_end_matches('/');
+    let dir_name = source.config.sanitized_source_dir_name();
+    let asset_dir = out_dir.join("assets").join(&dir_name);
+
+    let pending = store
+        .query(&EventQuery::for_source(&source.config.source.key))
+        .into_iter()
+        .filter(|record| needs_mirror(record, source))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for mut record in pending {
+        let mut added_source_url = false;
+        if let Some(url) = &record.source_url
+            && assets.is_asset_url(url)
+            && !record.attachments.iter().any(|a| &a.url == url)
+        {
+            record.attachments.push(Attachment {
+                url: url.clone(),
+                mirror_url: None,
+            });
+            added_source_url = true;
+        }
+
+        let mut changed = false;
+        for attachment in &mut record.attachments {
+            if attachment.mirror_url.is_some() || !assets.is_asset_url(&attachment.url) {
+                continue;
+            }
+            let file_name = asset_file_name(&attachment.url);
+            let path = asset_dir.join(&file_name);
+            if !path.exists() {
+                match download_asset(source, &attachment.url, &path) {
+                    Ok(bytes) => info!(
+                        source = %source.config.source.key,
+                        url = %attachment.url,
+                        bytes,
+                        "asset mirrored"
+                    ),
+                    Err(err) => {
+                        warn!(
+                            source = %source.config.source.key,
+                            url = %attachment.url,
+                            error = %format!("{err:#}"),
+                            "asset mirror failed; keeping original link"
+                        );
+                        continue;
+                    }
+                }
+            }
+            attachment.mirror_url = Some(format!("{base_url}/{dir_name}/{file_name}"));
+            changed = true;
+        }
+
+        // The source_url attachment only stays when it was actually mirrored.
+        if added_source_url
+            && record
+                .attachments
+                .last()
+                .is_some_and(|a| a.mirror_url.is_none())
+        {
+            record.attachments.pop();
+        }
+        if changed {
+            if let Some(year) = record.year_bucket() {
+                changed_years.insert(year);
+            }
+            store.upsert(record);
+        }
+    }
+
+    Ok(changed_years)
+}
+
+fn needs_mirror(record: &EventRecord, source: &LoadedSource) -> bool {
+    let assets = &source.config.publish.assets;
+    let source_url_pending = record.source_url.as_deref().is_some_and(|url| {
+        assets.is_asset_url(url) && !record.attachments.iter().any(|a| a.url == url)
+    });
+    source_url_pending
+        || record
+            .attachments
+            .iter()
+            .any(|a| a.mirror_url.is_none() && assets.is_asset_url(&a.url))
+}
+
+/// `<first 12 hex of sha256(url)>-<sanitized last path segment>`.
+pub fn asset_file_name(url: &str) -> String {
+    let digest = hex::encode(Sha256::digest(url.as_bytes()));
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let segment = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let segment = if segment.is_empty() {
+        "asset".to_string()
+    } else {
+        segment
+    };
+    format!("{}-{segment}", &digest[..12])
+}
+
+#[cfg(feature = "http")]
+fn download_asset(source: &LoadedSource, url: &str, path: &Path) -> Result<u64> {
+    use crate::fetch::{
+        SourceAuth, request_headers, robots_disallows, source_client, template_substitutions,
+        throttle,
+    };
+    use crate::network::check_request;
+    use std::io::Read;
+
+    // Asset links come from scraped pages, so they get the same checks as
+    // any page the source fetches.
+    check_request(source, url)?;
+    let max_bytes = source.config.publish.assets.max_bytes;
+    let headers = request_headers(source, &template_substitutions(source))?;
+    let client = source_client(
+        source,
+        &headers,
+        Some(std::time::Duration::from_secs(
+            source.config.fetch.timeout_secs,
+        )),
+    )?;
+    if let Some(rule) = robots_disallows(&client, &headers, source, url) {
+        anyhow::bail!("robots.txt disallows {url} ({rule})");
+    }
+    let mut request = client.get(url);
+    if let Some(auth) = SourceAuth::new(source, &headers)?
+        && let Some((name, value)) = auth.header_for(source, url, None)?
+    {
+        request = request.header(name, value);
+    }
+    throttle(source, url);
+    let response = request.send()?.error_for_status()?;
+    if let Some(length) = response.content_length()
+        && length > max_bytes
+    {
+        anyhow::bail!("asset is {length} bytes, over the {max_bytes} byte limit");
+    }
+
+    let mut body = Vec::new();
+    response.take(max_bytes + 1).read_to_end(&mut body)?;
+    if body.len() as u64 > max_bytes {
+        anyhow::bail!("asset exceeds the {max_bytes} byte limit");
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create asset dir {}", parent.display()))?;
+    }
+    let tmp_path = path.with_extension("part");
+    std::fs::write(&tmp_path, &body)
+        .with_context(|| format!("failed to write asset {}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("failed to write asset {}", path.display()))?;
+    Ok(body.len() as u64)
+}
+
+#[cfg(not(feature = "http"))]
+fn download_asset(_source: &LoadedSource, _url: &str, _path: &Path) -> Result<u64> {
+    anyhow::bail!("asset mirroring requires the `http` feature")
+}
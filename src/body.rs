@@ -0,0 +1,97 @@
+//! Document bodies, held in memory or spooled to a temp file.
+//!
+//! A response over `fetch.spool_threshold_bytes` is streamed into an
+//! unlinked temp file and mapped, so a large PDF or API dump is paged in by
+//! the OS as the parser reads it instead of sitting on the heap. Either way
+//! a [`Body`] reads as a byte slice.
+
+use memmap2::Mmap;
+use std::io::{self, Read, Write};
+use std::ops::Deref;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub enum Body {
+    Memory(Vec<u8>),
+    /// The first `len` bytes of a mapped temp file.
+    Spooled {
+        map: Arc<Mmap>,
+        len: usize,
+    },
+}
+
+impl Body {
+    /// Reads `reader` to the end. Up to `threshold` bytes stay in memory;
+    /// a longer body goes to a temp file.
+    pub fn read(mut reader: impl Read, threshold: u64) -> io::Result<Body> {
+        let mut head = Vec::new();
+        (&mut reader)
+            .take(threshold.saturating_add(1))
+            .read_to_end(&mut head)?;
+        if head.len() as u64 <= threshold {
+            return Ok(Body::Memory(head));
+        }
+        let mut file = tempfile::tempfile()?;
+        file.write_all(&head)?;
+        drop(head);
+        io::copy(&mut reader, &mut file)?;
+        // SAFETY: the file is unlinked and private to this process, and
+        // nothing writes to it once it is mapped.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Body::Spooled {
+            len: map.len(),
+            map: Arc::new(map),
+        })
+    }
+
+    /// The first `len` bytes.
+    pub fn truncated(self, len: usize) -> Body {
+        match self {
+            Body::Memory(mut bytes) => {
+                bytes.truncate(len);
+                Body::Memory(bytes)
+            }
+            Body::Spooled { map, len: old } => Body::Spooled {
+                map,
+                len: len.min(old),
+            },
+        }
+    }
+
+    pub fn is_spooled(&self) -> bool {
+        matches!(self, Body::Spooled { .. })
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            Body::Memory(bytes) => bytes,
+            Body::Spooled { map, len } => &map[..*len],
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Body::Memory(Vec::new())
+    }
+}
+
+impl Deref for Body {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for Body {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body::Memory(bytes)
+    }
+}
@@ -0,0 +1,118 @@
+//! Headless Chromium fetches for `fetch.mode = "browser"`.
+//!
+//! Some calendar pages build their event list client-side, so the HTML a
+//! plain GET returns has no events in it. Browser mode launches Chromium,
+//! opens `fetch.base_url` with the source's User-Agent, waits for
+//! `fetch.browser.wait_selector` to match, and hands the rendered DOM to the
+//! usual extraction as one document. Each fetch gets its own browser, so no
+//! cookies or storage carry over between runs.
+
+use crate::config::{LoadedSource, resolve_path};
+use crate::fetch::{
+    FetchedDocument, apply_templates, request_headers, template_substitutions, throttle,
+};
+use crate::network::check_request;
+use anyhow::{Context, Result, anyhow, bail};
+use chromiumoxide::browser::{Browser, BrowserConfig as ChromeConfig};
+use chromiumoxide::page::Page;
+use chrono::Utc;
+use futures::StreamExt;
+use reqwest::header::USER_AGENT;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// How often `wait_selector` is retried while the page renders.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub fn fetch_browser_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
+    let fetch = &source.config.fetch;
+    let substitutions = template_substitutions(source);
+    let base_url = fetch.base_url.as_ref().context("fetch.base_url missing")?;
+    let url = apply_templates(base_url, &substitutions);
+    let user_agent = request_headers(source, &substitutions)?
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    check_request(source, &url)?;
+    throttle(source, &url);
+    let started = Instant::now();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("failed to start the browser runtime")?;
+    let html = runtime
+        .block_on(render_page(source, &url, user_agent.as_deref()))
+        .with_context(|| format!("failed to render {url} in the browser"))?;
+    info!(
+        source = %source.config.source.key,
+        %url,
+        bytes = html.len(),
+        render_ms = started.elapsed().as_millis() as u64,
+        "rendered page in browser"
+    );
+
+    Ok(vec![FetchedDocument {
+        source_url: url,
+        body: html.into_bytes().into(),
+        page_index: 0,
+        fetched_at: Some(Utc::now()),
+        headers: BTreeMap::new(),
+    }])
+}
+
+async fn render_page(source: &LoadedSource, url: &str, user_agent: Option<&str>) -> Result<String> {
+    let config = &source.config.fetch.browser;
+    let timeout = Duration::from_secs(source.config.fetch.timeout_secs);
+    let mut builder = ChromeConfig::builder().request_timeout(timeout);
+    if let Some(executable) = &config.executable {
+        builder = builder.chrome_executable(resolve_path(&source.path, executable)?);
+    }
+    if !config.sandbox {
+        builder = builder.no_sandbox();
+    }
+    let chrome = builder
+        .build()
+        .map_err(|err| anyhow!("failed to configure chromium: {err}"))?;
+    let (mut browser, mut handler) = Browser::launch(chrome)
+        .await
+        .context("failed to launch chromium")?;
+    let events = tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+    let rendered = async {
+        let page = browser.new_page("about:blank").await?;
+        if let Some(user_agent) = user_agent {
+            page.set_user_agent(user_agent).await?;
+        }
+        page.goto(url).await?;
+        if let Some(selector) = &config.wait_selector {
+            wait_for_selector(&page, selector, timeout).await?;
+        }
+        page.content()
+            .await
+            .context("failed to read the rendered page")
+    }
+    .await;
+
+    let _ = browser.close().await;
+    let _ = browser.wait().await;
+    events.abort();
+    rendered
+}
+
+async fn wait_for_selector(page: &Page, selector: &str, timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if page.find_element(selector).await.is_ok() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "fetch.browser.wait_selector {selector} matched nothing within {}s",
+                timeout.as_secs()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
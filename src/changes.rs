@@ -0,0 +1,305 @@
+//! Per-event changes of a sync, and the outgoing change feed.
+//!
+//! Consumers that react to individual changes (invites, the feed) snapshot
+//! a source's events before the merge and diff afterwards, so the merge
+//! itself needs no hooks.
+//!
+//! With a `feed.toml` next to the sources directory, every change is
+//! appended as one JSON object per line to `changes.jsonl` in the output
+//! directory (or `path`), and optionally POSTed to `webhook_url`, one
+//! request per change. Each line looks like:
+//!
+//! ```json
+//! {"id": "3f2a...:2:event.updated", "type": "event.updated",
+//!  "occurred_at": "2030-03-01T12:00:00Z", "run_id": "20300301T120000Z-9c1e04d2",
+//!  "source_key": "us.fed.fomc",
+//!  "uid": "3f2a...", "sequence": 2, "title": "FOMC Meeting",
+//!  "start": "2030-03-18", "end": "2030-03-19", "all_day": true,
+//!  "status": "confirmed", "importance": 90, "categories": ["economics"],
+//!  "url": "https://..."}
+//! ```
+//!
+//! `type` is one of `event.created`, `event.updated`, `event.cancelled`,
+//! `event.expired` and `event.removed`; removed events only carry `id`,
+//! `type`, `occurred_at`, `run_id`, `source_key`, `uid`, `sequence` and
+//! `title`. `id` is unique per change, so it can be used to deduplicate;
+//! `run_id` matches the sync's logs, reports and run history.
+
+use crate::config::FeedConfig;
+use crate::model::{EventRecord, EventTimeSpec, Lifecycle};
+use crate::store::{EventQuery, EventStore};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+/// What a source's event looked like before a merge.
+#[derive(Debug, Clone)]
+pub struct SnapshotEntry {
+    pub uid: String,
+    pub sequence: u32,
+    pub lifecycle: Lifecycle,
+    pub title: String,
+}
+
+/// A source's events by store key.
+pub type SourceSnapshot = BTreeMap<String, SnapshotEntry>;
+
+pub fn snapshot_source(store: &dyn EventStore, source_key: &str) -> SourceSnapshot {
+    let query = EventQuery::for_source(source_key).with_cancelled(true);
+    store
+        .query(&query)
+        .into_iter()
+        .map(|event| {
+            (
+                event.store_key(),
+                SnapshotEntry {
+                    uid: event.uid.clone(),
+                    sequence: event.sequence,
+                    lifecycle: event.lifecycle,
+                    title: event.title.clone(),
+                },
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Cancelled,
+    Expired,
+    Removed,
+}
+
+impl ChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Created => "event.created",
+            ChangeKind::Updated => "event.updated",
+            ChangeKind::Cancelled => "event.cancelled",
+            ChangeKind::Expired => "event.expired",
+            ChangeKind::Removed => "event.removed",
+        }
+    }
+}
+
+/// Store keys of the source's events that changed since `before`. Events
+/// that arrive already cancelled, and edits to retired events, are not
+/// reported; a reinstated event is an update.
+pub fn diff_source(
+    store: &dyn EventStore,
+    source_key: &str,
+    before: &SourceSnapshot,
+) -> Vec<(ChangeKind, String)> {
+    let query = EventQuery::for_source(source_key).with_cancelled(true);
+    let mut changes = Vec::new();
+    let mut present = Vec::new();
+    for event in store.query(&query) {
+        let key = event.store_key();
+        let kind = match (before.get(&key), event.lifecycle) {
+            (None, Lifecycle::Active) => ChangeKind::Created,
+            (None, _) => continue,
+            (Some(previous), lifecycle) if previous.lifecycle != lifecycle => match lifecycle {
+                Lifecycle::Active => ChangeKind::Updated,
+                Lifecycle::Cancelled => ChangeKind::Cancelled,
+                Lifecycle::Expired => ChangeKind::Expired,
+            },
+            (Some(previous), Lifecycle::Active) if previous.sequence != event.sequence => {
+                ChangeKind::Updated
+            }
+            _ => {
+                present.push(key);
+                continue;
+            }
+        };
+        present.push(key.clone());
+        changes.push((kind, key));
+    }
+    present.sort();
+    for key in before.keys() {
+        if present.binary_search(key).is_err() {
+            changes.push((ChangeKind::Removed, key.clone()));
+        }
+    }
+    changes.sort_by(|a, b| a.1.cmp(&b.1));
+    changes
+}
+
+/// One line of the change feed; see the module docs for the schema.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChangeEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub occurred_at: DateTime<Utc>,
+    #[serde(default)]
+    pub run_id: String,
+    pub source_key: String,
+    pub uid: String,
+    pub sequence: u32,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_day: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub importance: Option<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl ChangeEvent {
+    fn new(kind: ChangeKind, run_id: &str, event: &EventRecord, now: DateTime<Utc>) -> Self {
+        let (start, end, all_day) = match &event.time {
+            EventTimeSpec::DateTime { start, end, .. } => (
+                Some(start.to_rfc3339()),
+                end.map(|end| end.to_rfc3339()),
+                Some(false),
+            ),
+            EventTimeSpec::Tbd { .. } => (None, None, None),
+            time => (
+                time.start_date().map(|date| date.to_string()),
+                time.end_date_exclusive().map(|date| date.to_string()),
+                Some(true),
+            ),
+        };
+        Self {
+            id: format!("{}:{}:{}", event.uid, event.sequence, kind.as_str()),
+            kind: kind.as_str().to_string(),
+            occurred_at: now,
+            run_id: run_id.to_string(),
+            source_key: event.source_key.clone(),
+            uid: event.uid.clone(),
+            sequence: event.sequence,
+            title: event.title.clone(),
+            start,
+            end,
+            all_day,
+            status: Some(event.status.clone()),
+            importance: event.importance,
+            categories: event.categories.clone(),
+            url: event.published_source_url().map(str::to_string),
+        }
+    }
+
+    fn removed(
+        run_id: &str,
+        source_key: &str,
+        previous: &SnapshotEntry,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let kind = ChangeKind::Removed.as_str();
+        Self {
+            id: format!("{}:{}:{kind}", previous.uid, previous.sequence),
+            kind: kind.to_string(),
+            occurred_at: now,
+            run_id: run_id.to_string(),
+            source_key: source_key.to_string(),
+            uid: previous.uid.clone(),
+            sequence: previous.sequence,
+            title: previous.title.clone(),
+            start: None,
+            end: None,
+            all_day: None,
+            status: None,
+            importance: None,
+            categories: Vec::new(),
+            url: None,
+        }
+    }
+}
+
+/// Feed entries for `changes`, skipping events below `feed.min_importance`.
+pub fn change_events(
+    feed: &FeedConfig,
+    run_id: &str,
+    store: &dyn EventStore,
+    source_key: &str,
+    before: &SourceSnapshot,
+    changes: &[(ChangeKind, String)],
+) -> Vec<ChangeEvent> {
+    let now = Utc::now();
+    changes
+        .iter()
+        .filter_map(|(kind, key)| match store.get(key) {
+            Some(event) => feed
+                .min_importance
+                .is_none_or(|min| event.importance.unwrap_or(0) >= min)
+                .then(|| ChangeEvent::new(*kind, run_id, event, now)),
+            None => before
+                .get(key)
+                .map(|previous| ChangeEvent::removed(run_id, source_key, previous, now)),
+        })
+        .collect()
+}
+
+/// Appends `events` to the feed file and POSTs them when a webhook is set.
+/// Webhook failures are logged, not returned: the feed file is the record.
+pub fn publish_changes(feed: &FeedConfig, out_dir: &Path, events: &[ChangeEvent]) -> Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    let path = out_dir.join(&feed.path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create feed dir {}", parent.display()))?;
+    }
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event)?);
+        lines.push('\n');
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .with_context(|| format!("failed to append to feed {}", path.display()))?;
+
+    if let Some(url) = &feed.webhook_url {
+        post_changes(feed, url, events);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "http")]
+fn post_changes(feed: &FeedConfig, url: &str, events: &[ChangeEvent]) {
+    let client = match reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(feed.timeout_secs))
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to build feed webhook client");
+            return;
+        }
+    };
+    for event in events {
+        let result = client
+            .post(url)
+            .json(event)
+            .send()
+            .and_then(|response| response.error_for_status());
+        if let Err(err) = result {
+            tracing::warn!(id = %event.id, error = %err, "feed webhook delivery failed");
+        }
+    }
+}
+
+#[cfg(not(feature = "http"))]
+fn post_changes(_feed: &FeedConfig, _url: &str, events: &[ChangeEvent]) {
+    tracing::warn!(
+        changes = events.len(),
+        "feed.webhook_url is set but rics was built without the `http` feature"
+    );
+}
@@ -0,0 +1,90 @@
+//! Decoding fetched bodies to text.
+//!
+//! Agency sites still serve ISO-8859-1, Windows-1252 and Shift_JIS pages,
+//! which a lossy UTF-8 decode turns into replacement characters. The
+//! encoding is picked, in order, from:
+//!
+//! 1. a byte order mark;
+//! 2. a UTF-16 `charset` in the `Content-Type` header, since UTF-16 text
+//!    can pass as UTF-8;
+//! 3. the body itself, when it is valid UTF-8: servers commonly declare
+//!    ISO-8859-1 for pages that are not, and ASCII decodes the same in both;
+//! 4. the `Content-Type` `charset`;
+//! 5. `<meta charset>`, `<meta http-equiv="Content-Type">` or an XML
+//!    declaration's `encoding` in the first 1024 bytes;
+//! 6. Windows-1252, the web's legacy default.
+
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use std::borrow::Cow;
+
+/// How far into a body `<meta>` and XML declarations are looked for, as in
+/// the HTML prescan.
+const PRESCAN_BYTES: usize = 1024;
+
+/// The encoding of `body`, given the `Content-Type` it was served with.
+pub fn detect_encoding(body: &[u8], content_type: Option<&str>) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(body) {
+        return encoding;
+    }
+    let declared = content_type.and_then(content_type_charset);
+    if let Some(encoding) = declared
+        && (encoding == UTF_16LE || encoding == UTF_16BE)
+    {
+        return encoding;
+    }
+    if std::str::from_utf8(body).is_ok() {
+        return UTF_8;
+    }
+    declared
+        .or_else(|| prescan_charset(body))
+        .unwrap_or(WINDOWS_1252)
+}
+
+/// `body` as text in its detected encoding; undecodable bytes become
+/// U+FFFD.
+pub fn decode_body<'a>(body: &'a [u8], content_type: Option<&str>) -> Cow<'a, str> {
+    let encoding = detect_encoding(body, content_type);
+    let (text, _, _) = encoding.decode(body);
+    text
+}
+
+/// The encoding named by the `charset` parameter of a `Content-Type` value.
+pub fn content_type_charset(content_type: &str) -> Option<&'static Encoding> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| Encoding::for_label(value.trim().trim_matches(['"', '\'']).as_bytes()))
+            .flatten()
+    })
+}
+
+/// The encoding declared in an XML declaration or `<meta>` tag near the
+/// start of `body`.
+fn prescan_charset(body: &[u8]) -> Option<&'static Encoding> {
+    let head = &body[..body.len().min(PRESCAN_BYTES)];
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+    if head.trim_start().starts_with("<?xml")
+        && let Some(end) = head.find("?>")
+    {
+        return attribute_value(&head[..end], "encoding=")
+            .and_then(|label| Encoding::for_label(label.as_bytes()));
+    }
+    head.match_indices("<meta")
+        .filter_map(|(start, _)| {
+            let tag = &head[start..];
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            attribute_value(tag, "charset=")
+        })
+        .find_map(|label| Encoding::for_label(label.as_bytes()))
+}
+
+/// The value following `key` in `tag`, quoted or not.
+fn attribute_value<'a>(tag: &'a str, key: &str) -> Option<&'a str> {
+    let rest = tag[tag.find(key)? + key.len()..].trim_start();
+    let rest = rest.trim_start_matches(['"', '\'']);
+    let end = rest
+        .find(|c: char| matches!(c, '"' | '\'' | ';' | '/' | '>') || c.is_whitespace())
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
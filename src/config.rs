@@ -1,13 +1,31 @@
+use crate::tenant::Tenant;
+use crate::timezones::derive_timezone;
 use anyhow::{Context, Result, anyhow, bail};
+use chrono::Datelike;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, warn};
 use walkdir::WalkDir;
 
 #[derive(Debug, Clone)]
 pub struct LoadedSource {
     pub path: PathBuf,
     pub config: SourceConfig,
+    /// Fetch state shared with the sources loaded alongside this one.
+    pub tenant: Arc<Tenant>,
+}
+
+impl LoadedSource {
+    /// SHA-256 of the config file on disk; `None` when the source was not
+    /// loaded from a readable file (e.g. previews).
+    pub fn config_hash(&self) -> Option<String> {
+        let bytes = std::fs::read(&self.path).ok()?;
+        Some(hex::encode(Sha256::digest(bytes)))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -24,10 +42,14 @@ pub struct SourceConfig {
     #[serde(default)]
     pub pagination: PaginationConfig,
     #[serde(default)]
+    pub preprocess: Vec<PreprocessConfig>,
+    #[serde(default)]
     pub extract: ExtractConfig,
     #[serde(default)]
     pub map: BTreeMap<String, FieldRule>,
     #[serde(default)]
+    pub values: BTreeMap<String, ValueRule>,
+    #[serde(default)]
     pub date: DateConfig,
     #[serde(default)]
     pub event: EventConfig,
@@ -37,9 +59,68 @@ pub struct SourceConfig {
     pub custom: CustomConfig,
     #[serde(default)]
     pub publish: PublishConfig,
+    #[serde(default)]
+    pub lifecycle: LifecycleConfig,
+    #[serde(default)]
+    pub enrich: EnrichConfig,
+    #[serde(default)]
+    pub categorize: CategorizeConfig,
+    #[serde(default)]
+    pub importance: ImportanceConfig,
+    #[serde(default)]
+    pub holidays: HolidaysConfig,
+    #[serde(default)]
+    pub date_rules: Vec<DateRuleConfig>,
+    #[serde(default)]
+    pub schedule: Vec<ScheduleConfig>,
+    #[serde(default)]
+    pub merge: MergeConfig,
+    #[serde(default)]
+    pub quarantine: QuarantineConfig,
+    #[serde(default)]
+    pub invites: InvitesConfig,
+    #[serde(default)]
+    pub uid: UidConfig,
 }
 
 impl SourceConfig {
+    /// Compiles every extraction regex within [`crate::regex_limits`], so
+    /// bad patterns fail at load time rather than mid-parse.
+    fn validate_regexes(&self) -> Result<()> {
+        let mut patterns = Vec::new();
+        if let Some(pattern) = &self.extract.record_regex {
+            patterns.push(("extract.record_regex".to_string(), pattern.as_str()));
+        }
+        for (field, rule) in &self.map {
+            if let Some(pattern) = &rule.regex {
+                patterns.push((format!("map.{field}.regex"), pattern.as_str()));
+            }
+            if let Some(pattern) = rule
+                .from
+                .as_deref()
+                .and_then(|from| from.strip_prefix("regex:"))
+            {
+                patterns.push((format!("map.{field}.from"), pattern));
+            }
+        }
+        for (index, split) in self.pdf.record_split.iter().enumerate() {
+            if split.strategy.eq_ignore_ascii_case("regex") {
+                patterns.push((
+                    format!("pdf.record_split[{index}].pattern"),
+                    split.pattern.as_str(),
+                ));
+            }
+        }
+        for (field, rule) in &self.pdf.fields {
+            patterns.push((format!("pdf.fields.{field}.pattern"), rule.pattern.as_str()));
+        }
+        for (name, pattern) in patterns {
+            crate::regex_limits::build(pattern, false)
+                .with_context(|| format!("{name} is invalid"))?;
+        }
+        Ok(())
+    }
+
     pub fn validate(&self) -> Result<()> {
         if self.source.key.trim().is_empty() {
             bail!("source.key must not be empty");
@@ -54,6 +135,19 @@ impl SourceConfig {
                     bail!("fetch.base_url is required for http mode");
                 }
             }
+            FetchMode::Browser => {
+                if self.fetch.base_url.is_none() {
+                    bail!("fetch.base_url is required for browser mode");
+                }
+                if self.pagination.enabled {
+                    bail!("pagination needs fetch.mode = \"http\"; browser mode renders one page");
+                }
+                if let Some(selector) = &self.fetch.browser.wait_selector
+                    && scraper::Selector::parse(selector).is_err()
+                {
+                    bail!("fetch.browser.wait_selector {selector} is not a valid CSS selector");
+                }
+            }
             FetchMode::File => {
                 if self.fetch.file_path.is_none() {
                     bail!("fetch.file_path is required for file mode");
@@ -64,18 +158,267 @@ impl SourceConfig {
                     bail!("fetch.inline_data is required for inline mode");
                 }
             }
+            FetchMode::Schedule => {
+                if self.schedule.is_empty() && self.date_rules.is_empty() {
+                    bail!("schedule mode needs at least one [[schedule]] or [[date_rules]] entry");
+                }
+            }
+            FetchMode::Replay => {
+                if self.fetch.replay_dir.is_none() {
+                    bail!("fetch.replay_dir is required for replay mode");
+                }
+            }
+            FetchMode::Ftp => {
+                let Some(ftp) = &self.fetch.ftp else {
+                    bail!("fetch.ftp is required for ftp mode");
+                };
+                if ftp.host.trim().is_empty() {
+                    bail!("fetch.ftp.host must not be empty");
+                }
+                validate_remote_path("fetch.ftp.path", &ftp.path)?;
+                if ftp.username.is_some() && ftp.password_env.is_none() {
+                    bail!("fetch.ftp.username needs fetch.ftp.password_env");
+                }
+            }
+            FetchMode::Sftp => {
+                let Some(sftp) = &self.fetch.sftp else {
+                    bail!("fetch.sftp is required for sftp mode");
+                };
+                if sftp.host.trim().is_empty() {
+                    bail!("fetch.sftp.host must not be empty");
+                }
+                validate_remote_path("fetch.sftp.path", &sftp.path)?;
+                if sftp.password_env.is_none() && sftp.private_key.is_none() {
+                    bail!("fetch.sftp needs password_env or private_key");
+                }
+                if let Some(fingerprint) = &sftp.host_key_sha256
+                    && hex::decode(fingerprint).map_or(true, |bytes| bytes.len() != 32)
+                {
+                    bail!("fetch.sftp.host_key_sha256 must be 64 hex digits");
+                }
+            }
+            FetchMode::Imap => {
+                let Some(imap) = &self.fetch.imap else {
+                    bail!("fetch.imap is required for imap mode");
+                };
+                if imap.host.trim().is_empty() || imap.username.trim().is_empty() {
+                    bail!("fetch.imap.host and fetch.imap.username must not be empty");
+                }
+            }
+        }
+
+        if let Some(status) = self
+            .fetch
+            .retry_statuses
+            .iter()
+            .find(|status| !(400..=599).contains(*status))
+        {
+            bail!("fetch.retry_statuses must be error statuses (400-599), not {status}");
+        }
+        if self.fetch.max_body_bytes == 0 {
+            bail!("fetch.max_body_bytes must be at least 1");
+        }
+        if self.fetch.rate_limit == Some(0) {
+            bail!("fetch.rate_limit must be at least 1 request per minute");
+        }
+        self.validate_regexes()?;
+
+        // A replayed source is an http source with its network swapped out,
+        // so it keeps its http-only settings.
+        let http_like = matches!(self.fetch.mode, FetchMode::Http | FetchMode::Replay);
+        if let Some(auth) = &self.fetch.auth {
+            if !http_like {
+                bail!("fetch.auth needs fetch.mode = \"http\"");
+            }
+            match auth.mode {
+                AuthMode::Basic if auth.username.is_none() && auth.username_env.is_none() => {
+                    bail!("fetch.auth mode basic needs username or username_env");
+                }
+                AuthMode::ApiKeyHeader if auth.header.is_none() => {
+                    bail!("fetch.auth mode api_key_header needs header");
+                }
+                AuthMode::Oauth2ClientCredentials
+                    if auth.token_url.is_none()
+                        || (auth.client_id.is_none() && auth.client_id_env.is_none()) =>
+                {
+                    bail!(
+                        "fetch.auth mode oauth2_client_credentials needs token_url and client_id or client_id_env"
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        if self.fetch.uses_session() && !http_like {
+            bail!("fetch.cookies and fetch.prefetch_urls need fetch.mode = \"http\"");
+        }
+        if let Some(iterate) = &self.fetch.iterate {
+            if !http_like {
+                bail!("fetch.iterate needs fetch.mode = \"http\"");
+            }
+            if self.pagination.enabled {
+                bail!("fetch.iterate and pagination cannot be combined");
+            }
+            let today = chrono::Utc::now().date_naive();
+            if iterate.from.apply(today, iterate.unit) > iterate.to.apply(today, iterate.unit) {
+                bail!("fetch.iterate.from must not be after fetch.iterate.to");
+            }
+            let periods = iterate.periods(today).len();
+            if periods > MAX_ITERATE_PERIODS {
+                bail!(
+                    "fetch.iterate spans {periods} periods; at most {MAX_ITERATE_PERIODS} are fetched"
+                );
+            }
+        }
+        for step in &self.preprocess {
+            match step.kind {
+                PreprocessKind::StripTags => {
+                    if step.tags.is_empty() {
+                        bail!("preprocess strip_tags needs at least one tag");
+                    }
+                    let tags = step.tags.join(", ");
+                    if scraper::Selector::parse(&tags).is_err() {
+                        bail!("preprocess strip_tags tags {tags} are not valid CSS selectors");
+                    }
+                }
+                PreprocessKind::Unpack => {
+                    if let Some(files) = &step.files
+                        && let Err(err) = glob::Pattern::new(files)
+                    {
+                        bail!("preprocess unpack files {files} is not a valid glob: {err}");
+                    }
+                }
+                PreprocessKind::Readability | PreprocessKind::SpreadsheetToCsv => {}
+            }
+        }
+        if self.pagination.concurrency == 0 {
+            bail!("pagination.concurrency must be at least 1");
+        }
+        if self.pagination.concurrency > 1
+            && self.pagination.strategy != PaginationStrategy::QueryParam
+        {
+            bail!(
+                "pagination.concurrency needs strategy = \"query_param\"; next_link and cursor pages depend on the one before"
+            );
+        }
+        if self.pagination.strategy == PaginationStrategy::Cursor
+            && self.pagination.cursor_jsonpath.is_none()
+        {
+            bail!("pagination.strategy = \"cursor\" needs pagination.cursor_jsonpath");
+        }
+        if self.pagination.next_selector.is_some()
+            && !matches!(
+                self.extract.format,
+                ExtractFormat::Html | ExtractFormat::Json
+            )
+        {
+            bail!(
+                "pagination.next_selector needs html or json extraction; text and pdf pages can only follow a Link header"
+            );
+        }
+        self.publish.validate()?;
+        let full = self.uid.algorithm.hex_len();
+        if !(MIN_UID_LENGTH..=full).contains(&self.uid.length) {
+            bail!("uid.length must be between {MIN_UID_LENGTH} and {full}");
+        }
+        if let Some(proxy) = self.fetch.proxy.as_deref()
+            && proxy != "none"
+        {
+            let url = url::Url::parse(proxy)
+                .with_context(|| format!("fetch.proxy {proxy} is not a valid URL"))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                bail!("fetch.proxy {proxy} must be an http:// or https:// URL");
+            }
+        }
+
+        if let (Some(min), Some(max)) = (self.publish.min_year, self.publish.max_year) {
+            let inverted = match (min, max) {
+                (YearBound::Absolute(min), YearBound::Absolute(max))
+                | (YearBound::Relative(min), YearBound::Relative(max)) => min > max,
+                _ => false,
+            };
+            if inverted {
+                bail!("publish.min_year must not be after publish.max_year");
+            }
+        }
+        if self.publish.assets.mirror && self.publish.assets.base_url.is_none() {
+            bail!("publish.assets.base_url is required when publish.assets.mirror is on");
         }
 
         if self.extract.format == ExtractFormat::Html
+            && self.fetch.mode != FetchMode::Schedule
             && self.map.is_empty()
             && !(self.custom.enabled && self.custom.parser.is_some())
         {
             bail!("map section must not be empty for html extraction");
         }
 
+        if self.extract.item_selector.is_some() && self.extract.format != ExtractFormat::Html {
+            bail!("extract.item_selector requires extract.format = \"html\"");
+        }
+
+        self.categorize.validate()?;
+        self.importance.validate()?;
+        self.holidays.extra_dates()?;
+        for (index, rule) in self.date_rules.iter().enumerate() {
+            rule.validate()
+                .with_context(|| format!("invalid date_rules[{index}]"))?;
+        }
+        self.merge.validate().context("invalid [merge]")?;
+        for recipient in &self.invites.recipients {
+            if !recipient.contains('@') {
+                bail!("invites.recipients entry {recipient:?} is not an email address");
+            }
+        }
+        if !(0.0..=1.0).contains(&self.quarantine.min_title_similarity) {
+            bail!("quarantine.min_title_similarity must be between 0 and 1");
+        }
+        for (index, entry) in self.schedule.iter().enumerate() {
+            entry
+                .validate()
+                .with_context(|| format!("invalid schedule[{index}]"))?;
+        }
+        if let Some(country) = &self.holidays.country
+            && self.holidays.annotate
+            && !crate::holidays::SUPPORTED_COUNTRIES
+                .contains(&country.to_ascii_uppercase().as_str())
+            && self.holidays.extra.is_empty()
+        {
+            bail!("holidays.country {country} has no built-in calendar; add holidays.extra dates");
+        }
+
+        if let Some(time) = &self.event.default_start_time {
+            chrono::NaiveTime::parse_from_str(time, "%H:%M")
+                .with_context(|| format!("event.default_start_time {time} must be HH:MM"))?;
+            let Some(zone) = self.default_start_timezone() else {
+                bail!(
+                    "event.default_start_time needs a zone: set event.default_start_timezone or source.timezone"
+                );
+            };
+            if zone.parse::<chrono_tz::Tz>().is_err() {
+                bail!("event.default_start_time zone {zone} is not an IANA time zone");
+            }
+        }
+
+        for (key, code) in &self.enrich.country_map {
+            let code = code.trim();
+            if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                bail!("enrich.country_map.{key} must be a two-letter country code, got {code:?}");
+            }
+        }
+
         Ok(())
     }
 
+    /// Zone that `event.default_start_time` is read in.
+    pub fn default_start_timezone(&self) -> Option<&str> {
+        self.event
+            .default_start_timezone
+            .as_deref()
+            .or(self.source.timezone.as_deref())
+            .or(self.date.assume_timezone.as_deref())
+    }
+
     pub fn sanitized_source_dir_name(&self) -> String {
         sanitize_for_path(&self.source.key)
     }
@@ -101,7 +444,7 @@ impl BundleConfig {
         if self.include.source_patterns.is_empty() {
             bail!("include.source_patterns must not be empty");
         }
-        Ok(())
+        self.publish.validate()
     }
 
     pub fn sanitized_bundle_dir_name(&self) -> String {
@@ -119,6 +462,10 @@ pub struct BundleMeta {
 pub struct BundleIncludeConfig {
     #[serde(default)]
     pub source_patterns: Vec<String>,
+    /// Only events carrying at least one of these categories; empty keeps
+    /// everything.
+    #[serde(default)]
+    pub categories: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -143,6 +490,32 @@ pub enum FetchMode {
     Http,
     File,
     Inline,
+    /// Messages from an IMAP folder; see [`ImapConfig`].
+    Imap,
+    /// Files on an FTP server; see [`FtpConfig`].
+    Ftp,
+    /// Files on an SSH server over SFTP; see [`SftpConfig`].
+    Sftp,
+    /// `base_url` as rendered by headless Chromium, for pages that build
+    /// their event list client-side; see [`BrowserConfig`].
+    Browser,
+    /// Nothing is fetched; events come only from `[[schedule]]` and
+    /// `[[date_rules]]` entries.
+    Schedule,
+    /// Responses saved by `rics sync --record`, read back from
+    /// `fetch.replay_dir` instead of the network.
+    Replay,
+}
+
+/// What happens to an http response over `fetch.max_body_bytes`.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizedBody {
+    /// Fail the fetch.
+    #[default]
+    Reject,
+    /// Keep the first `max_body_bytes` and parse those.
+    Truncate,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -165,228 +538,2197 @@ pub struct FetchConfig {
     pub timeout_secs: u64,
     #[serde(default = "default_retry_attempts")]
     pub retry_attempts: u8,
+    /// Wait before the first retry; it doubles with each further attempt,
+    /// and up to half of it is random.
     #[serde(default = "default_retry_backoff_ms")]
     pub retry_backoff_ms: u64,
+    /// Longest wait before a retry. A `Retry-After` asking for more fails
+    /// the request instead of stalling the sync.
+    #[serde(default = "default_retry_max_backoff_ms")]
+    pub retry_max_backoff_ms: u64,
+    /// Response statuses worth retrying; any other failure is final.
+    #[serde(default = "default_retry_statuses")]
+    pub retry_statuses: Vec<u16>,
+    /// Most bytes read from one http response. Bodies are streamed, so a
+    /// larger one is never held whole.
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: u64,
+    #[serde(default)]
+    pub oversized_body: OversizedBody,
+    /// Bodies over this many bytes are spooled to a temp file instead of
+    /// being held in memory.
+    #[serde(default = "default_spool_threshold_bytes")]
+    pub spool_threshold_bytes: u64,
+    /// Legacy shorthand for `fetch.identity.user_agent`.
     #[serde(default)]
     pub user_agent: Option<String>,
+    #[serde(default)]
+    pub identity: IdentityConfig,
+    /// Probe the document with HEAD (or a ranged GET) first and skip the run
+    /// when Content-Length/Last-Modified/ETag match the previous fetch. Only
+    /// applies to unpaginated http sources.
+    #[serde(default)]
+    pub preflight: bool,
+    /// Revalidate GETs against the HTTP cache next to the state file
+    /// (`If-None-Match`/`If-Modified-Since`) and reuse the body on a 304.
+    #[serde(default = "default_true")]
+    pub http_cache: bool,
+    /// Requests per minute to this source's host, shared with every other
+    /// source fetching from the same host in this process.
+    #[serde(default)]
+    pub rate_limit: Option<u32>,
+    /// Fetch each host's robots.txt and skip URLs it disallows for rics.
+    #[serde(default)]
+    pub respect_robots_txt: bool,
+    /// Keep cookies across this source's requests within one fetch. Each
+    /// fetch starts with an empty jar.
+    #[serde(default)]
+    pub cookies: bool,
+    /// Landing pages requested, in order, before the data URL in the same
+    /// cookie session, e.g. to obtain a session cookie. Implies `cookies`.
+    #[serde(default)]
+    pub prefetch_urls: Vec<String>,
+    /// Proxy URL (`http://` or `https://`) for every request of this
+    /// source, in place of `HTTPS_PROXY`/`HTTP_PROXY`; `"none"` connects
+    /// directly even when those are set.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    #[serde(default)]
+    pub imap: Option<ImapConfig>,
+    #[serde(default)]
+    pub ftp: Option<FtpConfig>,
+    #[serde(default)]
+    pub sftp: Option<SftpConfig>,
+    #[serde(default)]
+    pub browser: BrowserConfig,
+    /// Directory passed to `rics sync --record`, for replay mode. Relative
+    /// paths are resolved against the source config.
+    #[serde(default)]
+    pub replay_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub iterate: Option<IterateConfig>,
+    /// Skip the source in `rics sync` until this long after its last
+    /// successful fetch, e.g. `"6h"`; `--force` fetches anyway.
+    #[serde(default)]
+    pub min_interval: Option<FetchInterval>,
 }
 
-impl Default for FetchConfig {
-    fn default() -> Self {
-        Self {
-            mode: FetchMode::Http,
-            method: default_get(),
-            base_url: None,
-            file_path: None,
-            inline_data: None,
-            headers: BTreeMap::new(),
-            template_vars: BTreeMap::new(),
-            timeout_secs: default_timeout_secs(),
-            retry_attempts: default_retry_attempts(),
-            retry_backoff_ms: default_retry_backoff_ms(),
-            user_agent: None,
-        }
+impl FetchConfig {
+    /// Whether requests share a cookie session instead of the pooled client.
+    pub fn uses_session(&self) -> bool {
+        self.cookies || !self.prefetch_urls.is_empty()
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum PaginationStrategy {
-    #[default]
-    QueryParam,
-    NextLink,
-}
-
+/// How requests identify themselves (`[fetch.identity]`). Anything left
+/// unset falls back to the central defaults in `fetch`: a `rics/<version>`
+/// User-Agent and the operator contact from `RICS_CONTACT_EMAIL`.
 #[derive(Debug, Clone, Deserialize)]
-pub struct PaginationConfig {
+pub struct IdentityConfig {
+    /// Static or templated User-Agent; `{{contact}}`, `{{version}}` and the
+    /// fetch template vars are filled in.
     #[serde(default)]
-    pub enabled: bool,
+    pub user_agent: Option<String>,
+    /// User-Agents used in turn across fetches. Takes precedence over
+    /// `user_agent`.
     #[serde(default)]
-    pub strategy: PaginationStrategy,
-    #[serde(default = "default_page_param")]
-    pub page_param: String,
+    pub rotate: Vec<String>,
+    /// Contact address; defaults to `RICS_CONTACT_EMAIL`.
     #[serde(default)]
-    pub start_page: usize,
-    #[serde(default = "default_max_pages")]
-    pub max_pages: usize,
+    pub contact: Option<String>,
+    /// Send the contact as `From` and `X-Contact` headers.
     #[serde(default = "default_true")]
-    pub stop_when_no_results: bool,
-    #[serde(default)]
-    pub next_selector: Option<String>,
+    pub contact_headers: bool,
 }
 
-impl Default for PaginationConfig {
+impl Default for IdentityConfig {
     fn default() -> Self {
         Self {
-            enabled: false,
-            strategy: PaginationStrategy::QueryParam,
-            page_param: default_page_param(),
-            start_page: 0,
-            max_pages: default_max_pages(),
-            stop_when_no_results: true,
-            next_selector: None,
+            user_agent: None,
+            rotate: Vec::new(),
+            contact: None,
+            contact_headers: true,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum ExtractFormat {
-    #[default]
-    Html,
-    Json,
-    PdfText,
-    Text,
-}
-
+/// Credentials sent with every http request of the source (`[fetch.auth]`).
+/// Secrets are read from environment variables so they never live in the
+/// config file.
 #[derive(Debug, Clone, Deserialize)]
-pub struct ExtractConfig {
+pub struct AuthConfig {
+    pub mode: AuthMode,
+    /// `basic` user name; `username_env` wins when both are set.
     #[serde(default)]
-    pub format: ExtractFormat,
+    pub username: Option<String>,
     #[serde(default)]
-    pub root_selector: Option<String>,
+    pub username_env: Option<String>,
+    /// Environment variable holding the `basic` password, the `bearer`
+    /// token, the API key or the OAuth2 client secret.
+    pub secret_env: String,
+    /// Header that carries the key in `api_key_header` mode.
     #[serde(default)]
-    pub root_jsonpath: Option<String>,
+    pub header: Option<String>,
+    /// OAuth2 token endpoint for `oauth2_client_credentials`.
     #[serde(default)]
-    pub record_regex: Option<String>,
+    pub token_url: Option<String>,
+    /// OAuth2 client ID; `client_id_env` wins when both are set.
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub client_id_env: Option<String>,
+    /// Space-separated OAuth2 scopes to request.
+    #[serde(default)]
+    pub scope: Option<String>,
 }
 
-impl Default for ExtractConfig {
-    fn default() -> Self {
-        Self {
-            format: ExtractFormat::Html,
-            root_selector: None,
-            root_jsonpath: None,
-            record_regex: None,
-        }
-    }
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    /// `Authorization: Basic base64(username:secret)`.
+    Basic,
+    /// `Authorization: Bearer <secret>`.
+    Bearer,
+    /// `<header>: <secret>`.
+    ApiKeyHeader,
+    /// `Authorization: Bearer <token>` with a token from `token_url`,
+    /// obtained with the client-credentials grant and refreshed before it
+    /// expires or when it is rejected.
+    Oauth2ClientCredentials,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
-pub struct FieldRule {
+/// Mailbox to read for `mode = "imap"`. Each matching message becomes one
+/// document per usable part: the HTML or text body for `html`/`text`
+/// extraction, PDF attachments for `pdf_text`, JSON attachments for `json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImapConfig {
+    pub host: String,
+    #[serde(default = "default_imap_port")]
+    pub port: u16,
+    /// Disable only for local testing; credentials are sent in the clear.
+    #[serde(default = "default_true")]
+    pub tls: bool,
+    pub username: String,
+    /// Environment variable holding the password, so it never lives in the
+    /// config file.
+    #[serde(default = "default_imap_password_env")]
+    pub password_env: String,
+    #[serde(default = "default_imap_folder")]
+    pub folder: String,
+    /// Case-insensitive substring the sender's name or address must contain,
+    /// e.g. a domain.
     #[serde(default)]
     pub from: Option<String>,
-    #[serde(rename = "const", default)]
-    pub const_value: Option<String>,
-    #[serde(default)]
-    pub optional: bool,
-    #[serde(default)]
-    pub trim: bool,
+    /// Case-insensitive substring the subject must contain.
     #[serde(default)]
-    pub absolutize: bool,
+    pub subject: Option<String>,
+    /// Only messages received on or after this date.
     #[serde(default)]
-    pub regex: Option<String>,
+    pub since: Option<chrono::NaiveDate>,
+    /// Only messages received within the last N days; combined with `since`
+    /// the later bound wins.
     #[serde(default)]
-    pub capture: Option<usize>,
+    pub since_days: Option<u32>,
+    /// Newest N matching messages are fetched.
+    #[serde(default = "default_imap_max_messages")]
+    pub max_messages: usize,
 }
 
+/// Files to read for `mode = "ftp"` (`[fetch.ftp]`). Each file matching
+/// `path` becomes one document. Plain FTP only: credentials are sent in the
+/// clear.
 #[derive(Debug, Clone, Deserialize)]
-pub struct DateConfig {
-    #[serde(default = "default_primary_date")]
-    pub primary: String,
-    #[serde(default = "default_date_formats")]
-    pub formats: Vec<String>,
+pub struct FtpConfig {
+    pub host: String,
+    #[serde(default = "default_ftp_port")]
+    pub port: u16,
+    /// Logs in as `anonymous`, with the operator contact as password, when
+    /// unset.
     #[serde(default)]
-    pub assume_timezone: Option<String>,
-    #[serde(default = "default_true")]
-    pub allow_month_only: bool,
-    #[serde(default = "default_true")]
-    pub allow_year_only: bool,
+    pub username: Option<String>,
+    /// Environment variable holding the password; required with `username`.
+    #[serde(default)]
+    pub password_env: Option<String>,
+    /// Absolute path of the file. The file name may be a glob, e.g.
+    /// `/pub/agendas/*.pdf`, and the fetch template placeholders are filled
+    /// in.
+    pub path: String,
 }
 
-impl Default for DateConfig {
-    fn default() -> Self {
-        Self {
-            primary: default_primary_date(),
-            formats: default_date_formats(),
-            assume_timezone: None,
-            allow_month_only: true,
-            allow_year_only: true,
-        }
-    }
+/// Files to read for `mode = "sftp"` (`[fetch.sftp]`). Each file matching
+/// `path` becomes one document.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SftpConfig {
+    pub host: String,
+    #[serde(default = "default_sftp_port")]
+    pub port: u16,
+    pub username: String,
+    /// Environment variable holding the password.
+    #[serde(default)]
+    pub password_env: Option<String>,
+    /// Private key file, relative to the config file. Its passphrase, if
+    /// any, is read from the env var named by `password_env`.
+    #[serde(default)]
+    pub private_key: Option<PathBuf>,
+    /// Hex SHA-256 of the server's host key. Connections to a server with
+    /// another key are refused; when unset, the key is only logged.
+    #[serde(default)]
+    pub host_key_sha256: Option<String>,
+    /// Absolute path of the file, as for [`FtpConfig::path`].
+    pub path: String,
 }
 
+/// Page rendering for `mode = "browser"` (`[fetch.browser]`). The page gets
+/// `fetch.timeout_secs` to load and match `wait_selector`.
 #[derive(Debug, Clone, Deserialize)]
-pub struct EventConfig {
-    #[serde(default = "default_event_type")]
-    pub event_type: String,
-    #[serde(default)]
-    pub subtype: Option<String>,
-    #[serde(default = "default_status")]
-    pub status: String,
+pub struct BrowserConfig {
+    /// CSS selector that matches once the events have rendered; the DOM is
+    /// read as soon as the page has loaded when unset.
     #[serde(default)]
-    pub categories: Vec<String>,
+    pub wait_selector: Option<String>,
+    /// Chromium or Chrome binary, relative to the config file; found on
+    /// `PATH` and in the usual install locations when unset.
     #[serde(default)]
-    pub importance: Option<u8>,
+    pub executable: Option<PathBuf>,
+    /// Disable only where Chromium's sandbox cannot run, e.g. as root in a
+    /// container.
+    #[serde(default = "default_true")]
+    pub sandbox: bool,
 }
 
-impl Default for EventConfig {
+impl Default for BrowserConfig {
     fn default() -> Self {
         Self {
-            event_type: default_event_type(),
-            subtype: None,
-            status: default_status(),
-            categories: Vec::new(),
-            importance: None,
+            wait_selector: None,
+            executable: None,
+            sandbox: true,
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
-pub struct PdfConfig {
-    #[serde(default)]
-    pub page_range: Option<String>,
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            mode: FetchMode::Http,
+            method: default_get(),
+            base_url: None,
+            file_path: None,
+            inline_data: None,
+            headers: BTreeMap::new(),
+            template_vars: BTreeMap::new(),
+            timeout_secs: default_timeout_secs(),
+            retry_attempts: default_retry_attempts(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            retry_max_backoff_ms: default_retry_max_backoff_ms(),
+            retry_statuses: default_retry_statuses(),
+            max_body_bytes: default_max_body_bytes(),
+            oversized_body: OversizedBody::default(),
+            spool_threshold_bytes: default_spool_threshold_bytes(),
+            user_agent: None,
+            identity: IdentityConfig::default(),
+            preflight: false,
+            http_cache: true,
+            rate_limit: None,
+            respect_robots_txt: false,
+            cookies: false,
+            prefetch_urls: Vec::new(),
+            proxy: None,
+            auth: None,
+            imap: None,
+            ftp: None,
+            sftp: None,
+            browser: BrowserConfig::default(),
+            replay_dir: None,
+            iterate: None,
+            min_interval: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PaginationStrategy {
+    #[default]
+    QueryParam,
+    NextLink,
+    /// Each JSON response carries a token (`cursor_jsonpath`) that is sent
+    /// back as `cursor_param` to get the next page.
+    Cursor,
+}
+
+/// Most requests one `[fetch.iterate]` block may expand to.
+pub const MAX_ITERATE_PERIODS: usize = 400;
+
+/// `[fetch.iterate]`: one request per `unit` from `from` to `to`, for
+/// calendars that serve one period per page. The date placeholders of
+/// `base_url` (`{{year}}`, `{{month}}`, `{{year_month}}`, `{{today}}`,
+/// `{{iso_week}}`, with offsets) are filled from the first day of each
+/// period.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IterateConfig {
+    pub unit: IterateUnit,
+    /// First period, relative to today, e.g. `"-1 month"`.
+    #[serde(default)]
+    pub from: PeriodOffset,
+    /// Last period, relative to today, e.g. `"+6 months"`.
+    #[serde(default)]
+    pub to: PeriodOffset,
+}
+
+impl IterateConfig {
+    /// First days of the periods to fetch, oldest first.
+    pub fn periods(&self, today: chrono::NaiveDate) -> Vec<chrono::NaiveDate> {
+        let last = self.unit.period_start(self.to.apply(today, self.unit));
+        let mut period = self.unit.period_start(self.from.apply(today, self.unit));
+        let mut periods = Vec::new();
+        while period <= last && periods.len() <= MAX_ITERATE_PERIODS {
+            periods.push(period);
+            period = self.unit.shift(period, 1);
+        }
+        periods
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IterateUnit {
+    Day,
+    /// ISO weeks, starting on Monday.
+    Week,
+    Month,
+    Year,
+}
+
+impl IterateUnit {
+    /// The first day of the period containing `date`.
+    pub fn period_start(self, date: chrono::NaiveDate) -> chrono::NaiveDate {
+        match self {
+            IterateUnit::Day => date,
+            IterateUnit::Week => date.week(chrono::Weekday::Mon).first_day(),
+            IterateUnit::Month => date.with_day(1).unwrap_or(date),
+            IterateUnit::Year => date.with_ordinal(1).unwrap_or(date),
+        }
+    }
+
+    /// `date` moved by `count` of this unit, clamped to the calendar's range.
+    pub fn shift(self, date: chrono::NaiveDate, count: i32) -> chrono::NaiveDate {
+        let shifted = match self {
+            IterateUnit::Day => date.checked_add_signed(chrono::Duration::days(count.into())),
+            IterateUnit::Week => date.checked_add_signed(chrono::Duration::weeks(count.into())),
+            IterateUnit::Month | IterateUnit::Year => {
+                let months = if self == IterateUnit::Year {
+                    count.saturating_mul(12)
+                } else {
+                    count
+                };
+                let by = chrono::Months::new(months.unsigned_abs());
+                if months < 0 {
+                    date.checked_sub_months(by)
+                } else {
+                    date.checked_add_months(by)
+                }
+            }
+        };
+        shifted.unwrap_or(if count < 0 {
+            chrono::NaiveDate::MIN
+        } else {
+            chrono::NaiveDate::MAX
+        })
+    }
+}
+
+impl std::str::FromStr for IterateUnit {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        match raw.trim().to_ascii_lowercase().trim_end_matches('s') {
+            "day" => Ok(IterateUnit::Day),
+            "week" => Ok(IterateUnit::Week),
+            "month" => Ok(IterateUnit::Month),
+            "year" => Ok(IterateUnit::Year),
+            _ => bail!("unknown period unit {raw:?}; use day, week, month or year"),
+        }
+    }
+}
+
+/// A span of time written `"90s"`, `"30m"`, `"6h"`, `"1d"` or `"2w"`, or as
+/// a bare number of seconds.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "FetchIntervalValue")]
+pub struct FetchInterval {
+    pub seconds: u64,
+}
+
+impl FetchInterval {
+    pub fn duration(self) -> chrono::Duration {
+        chrono::Duration::seconds(self.seconds.min(i64::MAX as u64) as i64)
+    }
+}
+
+impl std::str::FromStr for FetchInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let trimmed = raw.trim();
+        let split = trimmed
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(trimmed.len());
+        let (count, unit) = trimmed.split_at(split);
+        let count = count
+            .parse::<u64>()
+            .map_err(|_| anyhow!("interval {raw:?} must look like \"6h\""))?;
+        let scale = match unit.trim() {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 3_600,
+            "d" => 86_400,
+            "w" => 604_800,
+            other => bail!("unknown interval unit {other:?} in {raw:?}; use s, m, h, d or w"),
+        };
+        Ok(FetchInterval {
+            seconds: count.saturating_mul(scale),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FetchIntervalValue {
+    Seconds(u64),
+    Text(String),
+}
+
+impl TryFrom<FetchIntervalValue> for FetchInterval {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FetchIntervalValue) -> Result<Self> {
+        match value {
+            FetchIntervalValue::Seconds(seconds) => Ok(FetchInterval { seconds }),
+            FetchIntervalValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+/// A signed number of periods from today, written `"-1 month"`,
+/// `"+6 months"` or as a bare count of the iteration's unit.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(try_from = "PeriodOffsetValue")]
+pub struct PeriodOffset {
+    pub count: i32,
+    /// Defaults to the iteration's unit.
+    pub unit: Option<IterateUnit>,
+}
+
+impl PeriodOffset {
+    /// `today` moved by this offset.
+    pub fn apply(self, today: chrono::NaiveDate, default_unit: IterateUnit) -> chrono::NaiveDate {
+        self.unit.unwrap_or(default_unit).shift(today, self.count)
+    }
+}
+
+impl std::str::FromStr for PeriodOffset {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let mut parts = raw.split_whitespace();
+        let count = parts
+            .next()
+            .and_then(|count| count.parse::<i32>().ok())
+            .ok_or_else(|| anyhow!("period offset {raw:?} must look like \"-1 month\""))?;
+        let unit = parts.next().map(str::parse).transpose()?;
+        if parts.next().is_some() {
+            bail!("period offset {raw:?} must look like \"-1 month\"");
+        }
+        Ok(PeriodOffset { count, unit })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PeriodOffsetValue {
+    Count(i32),
+    Text(String),
+}
+
+impl TryFrom<PeriodOffsetValue> for PeriodOffset {
+    type Error = anyhow::Error;
+
+    fn try_from(value: PeriodOffsetValue) -> Result<Self> {
+        match value {
+            PeriodOffsetValue::Count(count) => Ok(PeriodOffset { count, unit: None }),
+            PeriodOffsetValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaginationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub strategy: PaginationStrategy,
+    #[serde(default = "default_page_param")]
+    pub page_param: String,
+    #[serde(default)]
+    pub start_page: usize,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+    #[serde(default = "default_true")]
+    pub stop_when_no_results: bool,
+    /// What counts as no results. Sites that answer past-the-end pages with
+    /// a full HTML page (or repeat the last one) need `no_records` or
+    /// `no_new_records`, which parse each page as it arrives.
+    #[serde(default)]
+    pub stop_on: PaginationStop,
+    /// For `strategy = "next_link"`: where the next page's URL is, as CSS
+    /// (`a.next`, or `link[rel=next]@href`) for html or a JSON pointer/path
+    /// for json. Unset follows the `Link: <...>; rel="next"` header.
+    #[serde(default)]
+    pub next_selector: Option<String>,
+    /// For `strategy = "cursor"`: JSON pointer or path of the next-page
+    /// token, e.g. `/meta/nextCursor`. Pagination stops once it is absent,
+    /// null or empty.
+    #[serde(default)]
+    pub cursor_jsonpath: Option<String>,
+    /// Query parameter the token is sent back in.
+    #[serde(default = "default_cursor_param")]
+    pub cursor_param: String,
+    /// For `strategy = "query_param"`: pages requested at once. Pages still
+    /// come back in order, and each keeps to `fetch.rate_limit`.
+    #[serde(default = "default_page_concurrency")]
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PaginationStop {
+    /// The response body is empty.
+    #[default]
+    EmptyResponse,
+    /// The page maps to no records.
+    NoRecords,
+    /// Every record on the page has a UID an earlier page already had.
+    NoNewRecords,
+}
+
+fn default_cursor_param() -> String {
+    "cursor".to_string()
+}
+
+fn default_page_concurrency() -> usize {
+    1
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strategy: PaginationStrategy::QueryParam,
+            page_param: default_page_param(),
+            start_page: 0,
+            max_pages: default_max_pages(),
+            stop_when_no_results: true,
+            stop_on: PaginationStop::default(),
+            next_selector: None,
+            cursor_jsonpath: None,
+            cursor_param: default_cursor_param(),
+            concurrency: default_page_concurrency(),
+        }
+    }
+}
+
+/// One `[[preprocess]]` step; steps run in order on every fetched body
+/// before extraction (see [`crate::preprocess`]).
+#[derive(Debug, Clone, Deserialize)]
+pub struct PreprocessConfig {
+    pub kind: PreprocessKind,
+    /// `strip_tags`: elements removed along with their content.
+    #[serde(default = "default_strip_tags")]
+    pub tags: Vec<String>,
+    /// `unpack`: glob of the archive members to keep; every file by default.
+    #[serde(default)]
+    pub files: Option<String>,
+    /// `unpack`: the archive format; detected per document when unset.
+    #[serde(default)]
+    pub archive: Option<ArchiveFormat>,
+    /// `spreadsheet_to_csv`: the sheet to convert; the first by default.
+    #[serde(default)]
+    pub sheet: Option<String>,
+}
+
+fn default_strip_tags() -> Vec<String> {
+    ["script", "style", "noscript"].map(String::from).to_vec()
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreprocessKind {
+    StripTags,
+    Readability,
+    #[serde(alias = "unzip")]
+    Unpack,
+    SpreadsheetToCsv,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar")]
+    Tar,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractFormat {
+    #[default]
+    Html,
+    Json,
+    PdfText,
+    Text,
+    /// RSS or Atom; each item is a record with `title`, `link`, `pubDate`,
+    /// `description` and `guid` fields (see [`crate::feed`]).
+    Feed,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtractConfig {
+    #[serde(default)]
+    pub format: ExtractFormat,
+    #[serde(default)]
+    pub root_selector: Option<String>,
+    /// Selector for the records inside each `root_selector` node, e.g. the
+    /// releases listed on a day card. `css:` fields that match nothing in
+    /// a record are read from its root node instead.
+    #[serde(default)]
+    pub item_selector: Option<String>,
+    #[serde(default)]
+    pub root_jsonpath: Option<String>,
+    #[serde(default)]
+    pub record_regex: Option<String>,
+    /// Time each config regex may spend matching while parsing one fetch
+    /// of the source, in milliseconds.
+    #[serde(default = "default_regex_budget_ms")]
+    pub regex_budget_ms: u64,
+    /// Larger HTML and text documents are parsed only up to this many
+    /// bytes; larger JSON and PDF documents are skipped.
+    #[serde(default = "default_max_document_bytes")]
+    pub max_document_bytes: usize,
+    /// Records matched per document beyond this are dropped, so a selector
+    /// that matches every element cannot flood state.
+    #[serde(default = "default_max_nodes")]
+    pub max_nodes: usize,
+}
+
+impl Default for ExtractConfig {
+    fn default() -> Self {
+        Self {
+            format: ExtractFormat::Html,
+            root_selector: None,
+            item_selector: None,
+            root_jsonpath: None,
+            record_regex: None,
+            regex_budget_ms: default_regex_budget_ms(),
+            max_document_bytes: default_max_document_bytes(),
+            max_nodes: default_max_nodes(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct FieldRule {
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(rename = "const", default)]
+    pub const_value: Option<String>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub trim: bool,
+    #[serde(default)]
+    pub absolutize: bool,
+    #[serde(default)]
+    pub regex: Option<String>,
+    #[serde(default)]
+    pub capture: Option<usize>,
+}
+
+/// Typed numeric value read from a mapped field, e.g. `[values.actual]`.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ValueRule {
+    /// Mapped field holding the raw text; defaults to the value's own name.
+    #[serde(default)]
+    pub field: Option<String>,
+    /// Unit to record. When unset, a non-numeric prefix or suffix such as
+    /// `%`, `K`, `bn` or `$` is taken as the unit.
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Treat `,` as the decimal separator and `.` as a thousands separator.
+    #[serde(default)]
+    pub decimal_comma: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DateConfig {
+    #[serde(default = "default_primary_date")]
+    pub primary: String,
+    #[serde(default = "default_date_formats")]
+    pub formats: Vec<String>,
+    #[serde(default)]
+    pub assume_timezone: Option<String>,
+    #[serde(default = "default_true")]
+    pub allow_month_only: bool,
+    #[serde(default = "default_true")]
+    pub allow_year_only: bool,
+}
+
+impl Default for DateConfig {
+    fn default() -> Self {
+        Self {
+            primary: default_primary_date(),
+            formats: default_date_formats(),
+            assume_timezone: None,
+            allow_month_only: true,
+            allow_year_only: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventConfig {
+    #[serde(default = "default_event_type")]
+    pub event_type: String,
+    #[serde(default)]
+    pub subtype: Option<String>,
+    #[serde(default = "default_status")]
+    pub status: String,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub importance: Option<u8>,
+    /// Local `HH:MM` given to single-day, date-only events, for sources
+    /// that publish at a fixed time they never print.
+    #[serde(default)]
+    pub default_start_time: Option<String>,
+    /// Zone of `default_start_time`; defaults to `source.timezone`, then
+    /// `date.assume_timezone`.
+    #[serde(default)]
+    pub default_start_timezone: Option<String>,
+}
+
+impl Default for EventConfig {
+    fn default() -> Self {
+        Self {
+            event_type: default_event_type(),
+            subtype: None,
+            status: default_status(),
+            categories: Vec::new(),
+            importance: None,
+            default_start_time: None,
+            default_start_timezone: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct PdfConfig {
+    #[serde(default)]
+    pub page_range: Option<String>,
+    #[serde(default = "default_true")]
+    pub join_lines: bool,
+    #[serde(default = "default_true")]
+    pub normalize_whitespace: bool,
+    #[serde(default)]
+    pub record_split: Vec<PdfRecordSplit>,
+    #[serde(default)]
+    pub fields: BTreeMap<String, PdfFieldRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PdfRecordSplit {
+    #[serde(default = "default_split_strategy")]
+    pub strategy: String,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PdfFieldRule {
+    pub pattern: String,
+    #[serde(default = "default_capture")]
+    pub capture: usize,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct CustomConfig {
+    #[serde(default)]
+    pub parser: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub filter_field: Option<String>,
+    #[serde(default)]
+    pub filter_value: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct PublishConfig {
+    #[serde(default)]
+    pub mirror_dir: Option<PathBuf>,
+    #[serde(default = "default_true")]
+    pub mirror_source_subdir: bool,
+    #[serde(default)]
+    pub file_name_template: Option<String>,
+    #[serde(default)]
+    pub split_by_country: bool,
+    #[serde(default)]
+    pub time_basis: TimeBasis,
+    /// Append "Rescheduled from <date>" to DESCRIPTION when an event's
+    /// revision history shows it moved.
+    #[serde(default)]
+    pub describe_reschedules: bool,
+    /// Append a warning to DESCRIPTION for events flagged by
+    /// `[holidays] annotate`.
+    #[serde(default)]
+    pub describe_holidays: bool,
+    #[serde(default)]
+    pub assets: AssetsConfig,
+    /// Language whose `title.<lang>`/`description.<lang>` variants go into
+    /// SUMMARY/DESCRIPTION. Other variants are written as
+    /// `X-RICS-SUMMARY;LANGUAGE=<lang>` properties.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Further languages to publish as separate `<file>.<lang>.ics`
+    /// calendars.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Oldest year to write a calendar for: a year such as `2015`, or
+    /// relative to the current year such as `"current-1"`. Older events stay
+    /// in state but get no file.
+    #[serde(default)]
+    pub min_year: Option<YearBound>,
+    /// Newest year to write a calendar for, e.g. `"current+2"`.
+    #[serde(default)]
+    pub max_year: Option<YearBound>,
+    /// Maintain `-current`/`-next` aliases of the current and next year's
+    /// files (`name-2030.ics` -> `name-current.ics`), so subscriptions keep
+    /// working across the new year.
+    #[serde(default)]
+    pub aliases: Option<AliasMode>,
+    #[serde(default)]
+    pub x_properties: XPropertiesConfig,
+    /// Most events per calendar file; the rest move to
+    /// `<file>.overflow.ics`.
+    #[serde(default)]
+    pub max_events_per_file: Option<usize>,
+    /// Largest calendar file in bytes. Google Calendar silently ignores
+    /// subscribed files over about 1 MB.
+    #[serde(default)]
+    pub max_file_bytes: Option<u64>,
+    /// Which events move to the overflow file first.
+    #[serde(default)]
+    pub trim_order: TrimOrder,
+    /// Compressed copies written next to every calendar (`<file>.ics.gz`,
+    /// `<file>.ics.zst`), for static hosts that do not compress on the fly.
+    #[serde(default)]
+    pub compress: Vec<Compression>,
+    /// Rewrites of SUMMARY and DESCRIPTION text in written calendars, e.g.
+    /// a public bundle hiding what an internal source publishes. Stored
+    /// events keep the original text.
+    #[serde(default)]
+    pub redact: Vec<RedactRule>,
+    /// Named variants written next to every calendar as
+    /// `<file>.<profile>.ics` from the same events in the same pass, e.g. a
+    /// `public` profile without low-importance events or internal metadata.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, PublishProfile>,
+    #[serde(default)]
+    pub digest: Option<DigestConfig>,
+}
+
+/// Folds minor events into one all-day event per day or week that lists
+/// them in its DESCRIPTION (`[publish.digest]`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestConfig {
+    pub period: DigestPeriod,
+    /// Events scored above this stay individual; unscored events count as 0.
+    /// Unset folds every dated event.
+    #[serde(default)]
+    pub max_importance: Option<u8>,
+    /// Fewest events worth a digest; smaller groups stay individual.
+    #[serde(default = "default_digest_min_events")]
+    pub min_events: usize,
+    /// SUMMARY template with `{{date}}` (the period's first day, e.g.
+    /// `4 March`) and `{{count}}`.
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+fn default_digest_min_events() -> usize {
+    2
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPeriod {
+    Daily,
+    /// Weeks starting on Monday.
+    Weekly,
+}
+
+impl DigestConfig {
+    pub fn title_template(&self) -> &str {
+        match (&self.title, self.period) {
+            (Some(title), _) => title,
+            (None, DigestPeriod::Daily) => "{{date}}: {{count}} events",
+            (None, DigestPeriod::Weekly) => "Week of {{date}}: {{count}} events",
+        }
+    }
+}
+
+/// One `[publish.profiles.<name>]` variant. Unset fields keep the
+/// calendar's own settings.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct PublishProfile {
+    /// Leave out events scored below this; unscored events count as 0.
+    #[serde(default)]
+    pub min_importance: Option<u8>,
+    /// Leave out events with any of these categories.
+    #[serde(default)]
+    pub exclude_categories: Vec<String>,
+    #[serde(default)]
+    pub x_properties: Option<XPropertiesConfig>,
+    /// Applied after the calendar's own `redact` rules.
+    #[serde(default)]
+    pub redact: Vec<RedactRule>,
+}
+
+/// One `[[publish.redact]]` rule: a built-in pattern or a regex, and what
+/// matches become.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedactRule {
+    #[serde(default)]
+    pub builtin: Option<RedactBuiltin>,
+    /// Regex to replace; use `(?i)` for case-insensitive code names.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Replacement text; `$1` refers to a capture group of `pattern`.
+    #[serde(default = "default_redaction")]
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactBuiltin {
+    Email,
+    /// Numbers with separated groups, such as `(555) 123-4567` or
+    /// `+44 20 7946 0958`. Dates are left alone.
+    Phone,
+}
+
+impl RedactBuiltin {
+    fn pattern(self) -> &'static str {
+        match self {
+            Self::Email => r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+            Self::Phone => {
+                r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)|\b\d{2,4})[\s.-]?\d{3,4}[\s.-]\d{3,4}\b"
+            }
+        }
+    }
+}
+
+impl RedactRule {
+    pub fn pattern(&self) -> &str {
+        match (&self.pattern, self.builtin) {
+            (Some(pattern), _) => pattern,
+            (None, Some(builtin)) => builtin.pattern(),
+            (None, None) => "",
+        }
+    }
+}
+
+fn default_redaction() -> String {
+    "[redacted]".to_string()
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    Gzip,
+    /// Needs the `zstd` feature.
+    Zstd,
+}
+
+impl Compression {
+    pub const ALL: [Self; 2] = [Self::Gzip, Self::Zstd];
+
+    /// Suffix appended to the calendar's file name.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Zstd => "zst",
+        }
+    }
+
+    /// `Content-Encoding` the variant must be served with.
+    pub fn content_encoding(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TrimOrder {
+    /// Lowest `importance` first (unscored events count as 0), oldest first
+    /// among equals.
+    #[default]
+    LowestImportance,
+    Oldest,
+}
+
+impl PublishConfig {
+    /// The settings a profile's variant is rendered with.
+    pub fn with_profile(&self, profile: &PublishProfile) -> PublishConfig {
+        let mut publish = self.clone();
+        publish.profiles.clear();
+        if let Some(x_properties) = &profile.x_properties {
+            publish.x_properties = x_properties.clone();
+        }
+        publish.redact.extend(profile.redact.iter().cloned());
+        publish
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, profile) in &self.profiles {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            {
+                bail!(
+                    "publish.profiles.{name} must be named with lowercase letters, digits and hyphens"
+                );
+            }
+            if self
+                .languages
+                .iter()
+                .any(|language| language.eq_ignore_ascii_case(name))
+            {
+                bail!("publish.profiles.{name} would share its file names with a language variant");
+            }
+            self.with_profile(profile)
+                .validate()
+                .with_context(|| format!("publish.profiles.{name} is invalid"))?;
+        }
+        self.x_properties.validate()?;
+        if let Some(digest) = &self.digest
+            && digest.min_events == 0
+        {
+            bail!("publish.digest.min_events must be at least 1");
+        }
+        if cfg!(not(feature = "zstd")) && self.compress.contains(&Compression::Zstd) {
+            bail!("publish.compress = [\"zstd\"] needs rics built with the zstd feature");
+        }
+        for (index, rule) in self.redact.iter().enumerate() {
+            if rule.pattern.is_some() == rule.builtin.is_some() {
+                bail!("publish.redact[{index}] needs exactly one of builtin or pattern");
+            }
+            crate::regex_limits::build(rule.pattern(), false)
+                .with_context(|| format!("publish.redact[{index}].pattern is invalid"))?;
+        }
+        Ok(())
+    }
+
+    /// `text` with every `redact` rule applied in order. Patterns were
+    /// checked at load, so one that still fails to compile hides the whole
+    /// text rather than leaking it.
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for rule in &self.redact {
+            let Ok(regex) = crate::regex_limits::compile(rule.pattern()) else {
+                return Cow::Owned(rule.replacement.clone());
+            };
+            if let Cow::Owned(replaced) = regex.replace_all(&text, rule.replacement.as_str()) {
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+
+    /// Whether `year` is within `min_year`/`max_year` as of `current_year`.
+    pub fn publishes_year(&self, year: i32, current_year: i32) -> bool {
+        self.min_year
+            .is_none_or(|bound| year >= bound.resolve(current_year))
+            && self
+                .max_year
+                .is_none_or(|bound| year <= bound.resolve(current_year))
+    }
+}
+
+/// A publish year limit, absolute or relative to the current year.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "YearBoundValue")]
+pub enum YearBound {
+    Absolute(i32),
+    /// Offset from the current year; `current-1` is `Relative(-1)`.
+    Relative(i32),
+}
+
+impl YearBound {
+    pub fn resolve(self, current_year: i32) -> i32 {
+        match self {
+            YearBound::Absolute(year) => year,
+            YearBound::Relative(offset) => current_year + offset,
+        }
+    }
+}
+
+impl std::str::FromStr for YearBound {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let text = raw.trim().to_ascii_lowercase().replace(' ', "");
+        let Some(offset) = text.strip_prefix("current") else {
+            return text
+                .parse()
+                .map(YearBound::Absolute)
+                .map_err(|_| anyhow!("year bound {raw:?} must be a year or current[+-N]"));
+        };
+        if offset.is_empty() {
+            return Ok(YearBound::Relative(0));
+        }
+        let (sign, digits) = offset.split_at(1);
+        let value = digits
+            .parse::<i32>()
+            .map_err(|_| anyhow!("year bound {raw:?} must be a year or current[+-N]"))?;
+        match sign {
+            "+" => Ok(YearBound::Relative(value)),
+            "-" => Ok(YearBound::Relative(-value)),
+            _ => bail!("year bound {raw:?} must be a year or current[+-N]"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum YearBoundValue {
+    Year(i32),
+    Text(String),
+}
+
+impl TryFrom<YearBoundValue> for YearBound {
+    type Error = anyhow::Error;
+
+    fn try_from(value: YearBoundValue) -> Result<Self> {
+        match value {
+            YearBoundValue::Year(year) => Ok(YearBound::Absolute(year)),
+            YearBoundValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+/// Which metadata entries are published as X- properties
+/// (`[publish.x_properties]`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct XPropertiesConfig {
+    /// Metadata key to property name, e.g. `venue = "X-VENUE"`.
+    #[serde(default)]
+    pub map: BTreeMap<String, String>,
+    /// Keys never published; a trailing `*` matches a prefix.
+    #[serde(default)]
+    pub omit: Vec<String>,
+    /// Publish keys absent from `map` as `X-RICS-<KEY>`.
+    #[serde(default = "default_true")]
+    pub passthrough: bool,
+}
+
+impl Default for XPropertiesConfig {
+    fn default() -> Self {
+        Self {
+            map: BTreeMap::new(),
+            omit: Vec::new(),
+            passthrough: true,
+        }
+    }
+}
+
+impl XPropertiesConfig {
+    /// The property a metadata key is published as, if any.
+    pub fn property_for(&self, key: &str) -> Option<String> {
+        let omitted = self
+            .omit
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == pattern,
+            });
+        if omitted {
+            return None;
+        }
+        match self.map.get(key) {
+            Some(name) => Some(name.to_ascii_uppercase()),
+            None if self.passthrough => Some(format!("X-RICS-{}", x_name_part(key))),
+            None => None,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (key, name) in &self.map {
+            let valid = name.len() > 2
+                && name[..2].eq_ignore_ascii_case("x-")
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            if !valid {
+                bail!(
+                    "publish.x_properties.map.{key} = {name:?} must be an X- name of letters, digits and hyphens"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `key` as the tail of a property name: uppercase, other characters as `-`.
+fn x_name_part(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Mirroring of documents events link to (`[publish.assets]`), so ATTACH and
+/// URL keep working after an agency moves or deletes the originals.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetsConfig {
+    #[serde(default)]
+    pub mirror: bool,
+    /// Public URL that `<out_dir>/assets` is served from; mirrored links are
+    /// rewritten to `<base_url>/<source dir>/<file>`.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// File extensions treated as documents. `source_url` is mirrored too
+    /// when it points at one.
+    #[serde(default = "default_asset_extensions")]
+    pub extensions: Vec<String>,
+    #[serde(default = "default_asset_max_bytes")]
+    pub max_bytes: u64,
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self {
+            mirror: false,
+            base_url: None,
+            extensions: default_asset_extensions(),
+            max_bytes: default_asset_max_bytes(),
+        }
+    }
+}
+
+impl AssetsConfig {
+    /// Whether `url` looks like a document this config mirrors.
+    pub fn is_asset_url(&self, url: &str) -> bool {
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let Some((_, extension)) = path.rsplit_once('.') else {
+            return false;
+        };
+        !extension.contains('/')
+            && self
+                .extensions
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(extension))
+    }
+}
+
+/// How `publish.aliases` files point at the year file.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasMode {
+    /// A relative symlink; copies on platforms without symlinks.
+    Symlink,
+    /// A full copy, for servers that do not follow symlinks.
+    Copy,
+}
+
+/// How timed events are written to DTSTART/DTEND.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeBasis {
+    /// Always UTC (`...Z`).
+    #[default]
+    Utc,
+    /// `TZID=<zone>` with the source's original wall-clock time when known,
+    /// UTC otherwise. Zones are referenced by IANA name without VTIMEZONE
+    /// blocks, which mainstream clients resolve themselves.
+    Local,
+    /// Floating wall-clock time, with neither `Z` nor TZID, which clients
+    /// show unchanged in the viewer's zone; for events such as webinars
+    /// held "at 14:00 wherever you are". Uses the source's wall-clock time
+    /// when known, the UTC time otherwise.
+    Floating,
+}
+
+/// Per-field policies for combining a new candidate with the stored record,
+/// e.g. `[merge.fields] description = "longest"`. Unlisted fields take the
+/// new value.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct MergeConfig {
+    /// Keyed by `title`, `description`, `source_url`, `subtype`,
+    /// `jurisdiction`, `country`, `importance`, `categories` or
+    /// `metadata.<key>`.
+    #[serde(default)]
+    pub fields: BTreeMap<String, MergePolicy>,
+    /// How far ahead the source lists events, e.g. `"90d"`. Stored events
+    /// starting later are not cancelled for being missing from a run.
+    #[serde(default)]
+    pub cancellation_horizon: Option<Horizon>,
+}
+
+/// A span of days from today, written `"90d"`, `"12w"` or as a day count.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "HorizonValue")]
+pub struct Horizon {
+    pub days: u32,
+}
+
+impl Horizon {
+    /// The last date within the horizon.
+    pub fn end(self, today: chrono::NaiveDate) -> chrono::NaiveDate {
+        today + chrono::Duration::days(i64::from(self.days))
+    }
+}
+
+impl std::str::FromStr for Horizon {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let text = raw.trim().to_ascii_lowercase();
+        let (digits, per_unit) = match text.strip_suffix('w') {
+            Some(weeks) => (weeks, 7),
+            None => (text.strip_suffix('d').unwrap_or(&text), 1),
+        };
+        digits
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .and_then(|count| count.checked_mul(per_unit))
+            .map(|days| Horizon { days })
+            .ok_or_else(|| anyhow!("horizon {raw:?} must be a number of days (90d) or weeks (12w)"))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HorizonValue {
+    Days(u32),
+    Text(String),
+}
+
+impl TryFrom<HorizonValue> for Horizon {
+    type Error = anyhow::Error;
+
+    fn try_from(value: HorizonValue) -> Result<Self> {
+        match value {
+            HorizonValue::Days(days) => Ok(Horizon { days }),
+            HorizonValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+pub const MERGE_FIELDS: &[&str] = &[
+    "title",
+    "description",
+    "source_url",
+    "subtype",
+    "jurisdiction",
+    "country",
+    "importance",
+    "categories",
+];
+
+impl MergeConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (field, policy) in &self.fields {
+            let known = MERGE_FIELDS.contains(&field.as_str())
+                || field
+                    .strip_prefix("metadata.")
+                    .is_some_and(|key| !key.is_empty());
+            if !known {
+                bail!("unknown merge field {field}");
+            }
+            if field == "importance" && *policy == MergePolicy::Longest {
+                bail!("merge policy longest does not apply to importance");
+            }
+        }
+        if self
+            .cancellation_horizon
+            .is_some_and(|horizon| horizon.days == 0)
+        {
+            bail!("merge.cancellation_horizon must be at least 1 day");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    /// Take the candidate's value, even when empty.
+    #[default]
+    PreferNew,
+    /// Keep the stored value unless it is empty.
+    PreferExisting,
+    /// Take the candidate's value unless it is empty.
+    PreferNonEmpty,
+    /// Take whichever value is longer; ties go to the candidate.
+    Longest,
+}
+
+/// Thresholds past which an update is held for review instead of merged;
+/// see [`crate::quarantine`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuarantineConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_max_date_shift_days")]
+    pub max_date_shift_days: u32,
+    /// Edit-distance similarity (0–1) between old and new titles below which
+    /// the update is held.
+    #[serde(default = "default_min_title_similarity")]
+    pub min_title_similarity: f64,
+}
+
+impl Default for QuarantineConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_date_shift_days: default_max_date_shift_days(),
+            min_title_similarity: default_min_title_similarity(),
+        }
+    }
+}
+
+fn default_max_date_shift_days() -> u32 {
+    60
+}
+
+fn default_min_title_similarity() -> f64 {
+    0.5
+}
+
+/// How event UIDs are hashed. Changing either setting gives every event of
+/// the source a new UID, so subscribers see them as new events.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UidConfig {
+    #[serde(default)]
+    pub algorithm: UidAlgorithm,
+    /// Hex characters of the digest kept, up to the full digest
+    /// ([`UidAlgorithm::hex_len`]).
+    #[serde(default = "default_uid_length")]
+    pub length: usize,
+}
+
+impl Default for UidConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: UidAlgorithm::default(),
+            length: default_uid_length(),
+        }
+    }
+}
+
+fn default_uid_length() -> usize {
+    24
+}
+
+/// Shortest accepted `uid.length`.
+pub const MIN_UID_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UidAlgorithm {
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl UidAlgorithm {
+    /// Length of the full digest in hex characters.
+    pub fn hex_len(self) -> usize {
+        match self {
+            Self::Sha256 => 64,
+            Self::Sha384 => 96,
+            Self::Sha512 => 128,
+        }
+    }
+}
+
+/// Which hosts sources may fetch from, kept in `network.toml` next to the
+/// sources directory for deployments that run third-party configs; see
+/// [`crate::network`]. Host patterns are exact names, IP addresses, or
+/// `*.example.org` for every subdomain of `example.org`.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// When non-empty, only these hosts may be fetched.
+    #[serde(default)]
+    pub allow_hosts: Vec<String>,
+    /// Hosts never fetched, even when allowed.
+    #[serde(default)]
+    pub deny_hosts: Vec<String>,
+    /// Refuse loopback, private, link-local and other non-public addresses,
+    /// whether written in the URL or resolved from its host name.
+    #[serde(default)]
+    pub block_private_ips: bool,
+}
+
+impl NetworkConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (name, patterns) in [
+            ("allow_hosts", &self.allow_hosts),
+            ("deny_hosts", &self.deny_hosts),
+        ] {
+            for pattern in patterns {
+                let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+                let valid = !host.is_empty()
+                    && !host.contains(['*', '/'])
+                    && (!host.contains(':') || host.parse::<std::net::Ipv6Addr>().is_ok());
+                if !valid {
+                    bail!("{name} entry {pattern:?} must be a host name, an IP or *.domain");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `host` passes the allow and deny lists.
+    pub fn host_allowed(&self, host: &str) -> bool {
+        let host = crate::network::normalize_host(host);
+        let matches = |pattern: &String| match pattern.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(&domain.to_ascii_lowercase())
+                .is_some_and(|rest| rest.ends_with('.')),
+            None => host == crate::network::normalize_host(pattern),
+        };
+        (self.allow_hosts.is_empty() || self.allow_hosts.iter().any(matches))
+            && !self.deny_hosts.iter().any(matches)
+    }
+}
+
+/// A rewrite map from stable URLs to the generated calendar files, kept in
+/// `redirects.toml` next to the sources directory and rewritten on every
+/// build. Paths take the same `{{...}}` placeholders as
+/// `file_name_template`; `{{year}}` also maps `current` and `next`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RedirectsConfig {
+    pub format: RedirectFormat,
+    /// Where to write the map; relative paths are under the output dir.
+    /// Defaults to `redirects.nginx.conf`, `redirects.caddy` or
+    /// `_redirects` by format.
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+    /// URL path the output directory is served under.
+    #[serde(default = "default_redirect_base_path")]
+    pub base_path: String,
+    #[serde(default = "default_redirect_source_path")]
+    pub source_path: String,
+    #[serde(default = "default_redirect_bundle_path")]
+    pub bundle_path: String,
+    /// HTTP status; temporary by default because `current` moves yearly.
+    #[serde(default = "default_redirect_status")]
+    pub status: u16,
+}
+
+impl RedirectsConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (name, path) in [
+            ("source_path", &self.source_path),
+            ("bundle_path", &self.bundle_path),
+        ] {
+            if !path.contains("{{year}}") {
+                bail!("{name} must contain {{{{year}}}}");
+            }
+        }
+        if !(300..400).contains(&self.status) {
+            bail!("status must be a 3xx redirect code");
+        }
+        Ok(())
+    }
+
+    pub fn output_path(&self, out_dir: &Path) -> PathBuf {
+        let default = match self.format {
+            RedirectFormat::Nginx => "redirects.nginx.conf",
+            RedirectFormat::Caddy => "redirects.caddy",
+            RedirectFormat::Netlify => "_redirects",
+        };
+        out_dir.join(self.output.as_deref().unwrap_or(Path::new(default)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectFormat {
+    /// `location = ... { return ...; }` blocks to `include` in a server.
+    Nginx,
+    /// `redir` directives to `import` in a site block.
+    Caddy,
+    /// A `_redirects` file as read by Netlify and Cloudflare Pages.
+    Netlify,
+}
+
+fn default_redirect_base_path() -> String {
+    "/".to_string()
+}
+
+fn default_redirect_source_path() -> String {
+    "{{source_dir}}/{{year}}.ics".to_string()
+}
+
+fn default_redirect_bundle_path() -> String {
+    "bundles/{{bundle_dir}}/{{year}}.ics".to_string()
+}
+
+fn default_redirect_status() -> u16 {
+    302
+}
+
+/// Calendar invites (iTIP REQUEST/CANCEL emails) for important events,
+/// sent through the shared `smtp.toml`. Off while `recipients` is empty.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InvitesConfig {
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    /// Events below this importance (0-100) are not sent.
+    #[serde(default = "default_invite_min_importance")]
+    pub min_importance: u8,
+}
+
+impl Default for InvitesConfig {
+    fn default() -> Self {
+        Self {
+            recipients: Vec::new(),
+            min_importance: default_invite_min_importance(),
+        }
+    }
+}
+
+impl InvitesConfig {
+    pub fn enabled(&self) -> bool {
+        !self.recipients.is_empty()
+    }
+}
+
+fn default_invite_min_importance() -> u8 {
+    80
+}
+
+/// The append-only change feed in `feed.toml`; see [`crate::changes`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedConfig {
+    /// JSON Lines file, relative to the output dir unless absolute.
+    #[serde(default = "default_feed_path")]
+    pub path: PathBuf,
+    /// Each change is also POSTed here as JSON.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Leave out events below this importance.
+    #[serde(default)]
+    pub min_importance: Option<u8>,
+}
+
+fn default_feed_path() -> PathBuf {
+    PathBuf::from("changes.jsonl")
+}
+
+/// Outgoing mail server for invites, from `smtp.toml` next to the sources
+/// directory. Only implicit TLS (port 465) is supported; `tls = false` is
+/// meant for a local relay.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
     #[serde(default = "default_true")]
-    pub join_lines: bool,
+    pub tls: bool,
+    /// Sender and ORGANIZER address.
+    pub from: String,
+    /// Login for AUTH PLAIN; omit for relays that need none.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Environment variable holding the password.
+    #[serde(default = "default_smtp_password_env")]
+    pub password_env: String,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_smtp_port() -> u16 {
+    465
+}
+
+fn default_smtp_password_env() -> String {
+    "RICS_SMTP_PASSWORD".to_string()
+}
+
+/// When cancelled and expired events are dropped from state for good, and
+/// when TBD placeholders are retired.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct LifecycleConfig {
+    /// Days after cancellation (or after an expired event was last seen)
+    /// before the record is purged. Unset keeps records forever.
+    #[serde(default)]
+    pub delete_after_days: Option<u32>,
+    /// Retire TBD events first stored more than this many days ago, even if
+    /// the source still lists them. They come back once they get a date.
+    #[serde(default)]
+    pub tbd_max_age_days: Option<u32>,
+    /// Keep unlisted TBD events for this many syncs before retiring them,
+    /// instead of cancelling them on the first miss.
+    #[serde(default)]
+    pub tbd_missed_syncs: Option<u32>,
+    #[serde(default)]
+    pub tbd_action: TbdAction,
+}
+
+/// How a stale TBD event is retired.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TbdAction {
+    /// Kept in state until `delete_after_days`, like a past event.
+    #[default]
+    Expire,
+    Cancel,
+}
+
+/// Built-in enrichers run on parsed candidates; see [`crate::enrich`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnrichConfig {
+    /// Canonicalize `country` to ISO 3166-1 alpha-2 and infer it when unset.
     #[serde(default = "default_true")]
-    pub normalize_whitespace: bool,
+    pub infer_country: bool,
+    /// Extra names, keywords or codes mapped to a country code, e.g.
+    /// `"Euro Area" = "EU"`. Matched against country values, jurisdictions and
+    /// (as whole words) titles, ahead of the built-in table.
     #[serde(default)]
-    pub record_split: Vec<PdfRecordSplit>,
+    pub country_map: BTreeMap<String, String>,
+}
+
+impl Default for EnrichConfig {
+    fn default() -> Self {
+        Self {
+            infer_country: true,
+            country_map: BTreeMap::new(),
+        }
+    }
+}
+
+/// Keyword rules that add categories to events whose title or description
+/// matches. Sources carry their own `[categorize]`; rules that apply
+/// everywhere live in `categorize.toml` next to the sources directory.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct CategorizeConfig {
     #[serde(default)]
-    pub fields: BTreeMap<String, PdfFieldRule>,
+    pub rules: Vec<CategoryRule>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct PdfRecordSplit {
-    #[serde(default = "default_split_strategy")]
-    pub strategy: String,
+pub struct CategoryRule {
+    /// Regex, matched case-insensitively.
     pub pattern: String,
+    pub categories: Vec<String>,
+}
+
+impl CategorizeConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            crate::regex_limits::build(&rule.pattern, true)
+                .with_context(|| format!("categorize.rules[{index}] has invalid pattern"))?;
+            if rule.categories.iter().all(|c| c.trim().is_empty()) {
+                bail!("categorize.rules[{index}] must name at least one category");
+            }
+        }
+        Ok(())
+    }
 }
 
+/// Flags events scheduled on public holidays or weekends, which usually
+/// means the source misdated them.
 #[derive(Debug, Clone, Deserialize)]
-pub struct PdfFieldRule {
-    pub pattern: String,
-    #[serde(default = "default_capture")]
-    pub capture: usize,
+pub struct HolidaysConfig {
+    /// Write `holiday` / `weekend` metadata on affected events.
     #[serde(default)]
-    pub optional: bool,
+    pub annotate: bool,
+    #[serde(default = "default_true")]
+    pub weekends: bool,
+    /// Calendar to check against; defaults to each event's country.
+    #[serde(default)]
+    pub country: Option<String>,
+    /// Additional closures as `"YYYY-MM-DD" = "name"`.
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
-pub struct CustomConfig {
+impl Default for HolidaysConfig {
+    fn default() -> Self {
+        Self {
+            annotate: false,
+            weekends: true,
+            country: None,
+            extra: BTreeMap::new(),
+        }
+    }
+}
+
+impl HolidaysConfig {
+    pub fn extra_dates(&self) -> Result<BTreeMap<chrono::NaiveDate, String>> {
+        self.extra
+            .iter()
+            .map(|(date, name)| {
+                let parsed = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .with_context(|| format!("holidays.extra key {date} is not YYYY-MM-DD"))?;
+                Ok((parsed, name.clone()))
+            })
+            .collect()
+    }
+}
+
+/// An event whose date is given by a business-day rule rather than scraped,
+/// e.g. `rule = "5th business day of month"`; see [`crate::schedule`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct DateRuleConfig {
+    pub title: String,
+    pub rule: String,
+    /// Stable id prefix for generated events; defaults to the slugged title.
     #[serde(default)]
-    pub parser: Option<String>,
+    pub id: Option<String>,
     #[serde(default)]
-    pub enabled: bool,
+    pub description: Option<String>,
+    /// Local `HH:MM` in `source.timezone`; all-day when unset.
     #[serde(default)]
-    pub filter_field: Option<String>,
+    pub time: Option<String>,
+    /// Holiday calendar country; defaults to `source.default_country`.
     #[serde(default)]
-    pub filter_value: Option<String>,
+    pub calendar: Option<String>,
+    /// First month to generate, `YYYY-MM`; defaults to the current month.
+    #[serde(default)]
+    pub from: Option<String>,
+    /// How many months ahead to generate.
+    #[serde(default = "default_date_rule_months")]
+    pub months: u32,
+}
+
+impl DateRuleConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.title.trim().is_empty() {
+            bail!("title must not be empty");
+        }
+        self.rule.parse::<crate::schedule::BusinessDayRule>()?;
+        if let Some(time) = &self.time {
+            chrono::NaiveTime::parse_from_str(time, "%H:%M")
+                .with_context(|| format!("time {time} must be HH:MM"))?;
+        }
+        if let Some(from) = &self.from {
+            chrono::NaiveDate::parse_from_str(&format!("{from}-01"), "%Y-%m-%d")
+                .with_context(|| format!("from {from} must be YYYY-MM"))?;
+        }
+        Ok(())
+    }
+}
+
+fn default_date_rule_months() -> u32 {
+    12
+}
+
+/// A recurring event described directly in the config, either as explicit
+/// `dates` (e.g. a central bank's announced meeting days) or as a recurrence
+/// such as `every = "first friday of month"`; see [`crate::schedule`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleConfig {
+    pub title: String,
+    /// `YYYY-MM-DD` dates.
+    #[serde(default)]
+    pub dates: Vec<String>,
+    #[serde(default)]
+    pub every: Option<String>,
+    /// Stable id prefix for generated events; defaults to the slugged title.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Local `HH:MM` in `source.timezone`; all-day when unset.
+    #[serde(default)]
+    pub time: Option<String>,
+    /// Local `HH:MM` end time; requires `time`.
+    #[serde(default)]
+    pub end_time: Option<String>,
+    /// First month of a recurrence, `YYYY-MM`; defaults to the current month.
+    #[serde(default)]
+    pub from: Option<String>,
+    /// How many months of a recurrence to generate.
+    #[serde(default = "default_date_rule_months")]
+    pub months: u32,
+}
+
+impl ScheduleConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.title.trim().is_empty() {
+            bail!("title must not be empty");
+        }
+        match (&self.every, self.dates.is_empty()) {
+            (Some(_), false) => bail!("set either dates or every, not both"),
+            (None, true) => bail!("one of dates or every is required"),
+            (Some(every), true) => {
+                every.parse::<crate::schedule::Recurrence>()?;
+            }
+            (None, false) => {
+                for date in &self.dates {
+                    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .with_context(|| format!("date {date} must be YYYY-MM-DD"))?;
+                }
+            }
+        }
+        for time in [&self.time, &self.end_time].into_iter().flatten() {
+            chrono::NaiveTime::parse_from_str(time, "%H:%M")
+                .with_context(|| format!("time {time} must be HH:MM"))?;
+        }
+        if self.end_time.is_some() && self.time.is_none() {
+            bail!("end_time requires time");
+        }
+        if let Some(from) = &self.from {
+            chrono::NaiveDate::parse_from_str(&format!("{from}-01"), "%Y-%m-%d")
+                .with_context(|| format!("from {from} must be YYYY-MM"))?;
+        }
+        Ok(())
+    }
 }
 
+/// Declarative importance rules. The first rule whose conditions all hold
+/// decides; source rules are tried before the shared `importance.toml`.
 #[derive(Debug, Clone, Deserialize, Default)]
-pub struct PublishConfig {
+pub struct ImportanceConfig {
     #[serde(default)]
-    pub mirror_dir: Option<PathBuf>,
-    #[serde(default = "default_true")]
-    pub mirror_source_subdir: bool,
+    pub rules: Vec<ImportanceRule>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportanceRule {
+    /// Regex matched case-insensitively against the title.
     #[serde(default)]
-    pub file_name_template: Option<String>,
+    pub title: Option<String>,
     #[serde(default)]
-    pub split_by_country: bool,
+    pub event_type: Option<String>,
+    /// Source key or `prefix*` pattern, as in bundle `source_patterns`.
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    pub importance: u8,
+    /// When false the rule only fills in a missing importance.
+    #[serde(default = "default_true")]
+    pub overwrite: bool,
+}
+
+impl ImportanceConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            if let Some(title) = &rule.title {
+                crate::regex_limits::build(title, true)
+                    .with_context(|| format!("importance.rules[{index}] has invalid title"))?;
+            }
+            if rule.title.is_none()
+                && rule.event_type.is_none()
+                && rule.source.is_none()
+                && rule.country.is_none()
+                && rule.category.is_none()
+            {
+                bail!("importance.rules[{index}] must have at least one condition");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads the shared `categorize.toml` that sits next to `config_dir`, or an
+/// empty rule set when there is none.
+pub fn load_global_categorize(config_dir: &Path) -> Result<CategorizeConfig> {
+    let Some((path, config)) =
+        load_shared_config::<CategorizeConfig>(config_dir, "categorize.toml")?
+    else {
+        return Ok(CategorizeConfig::default());
+    };
+    config
+        .validate()
+        .with_context(|| format!("invalid categorize config {}", path.display()))?;
+    Ok(config)
+}
+
+/// Loads the shared `importance.toml` that sits next to `config_dir`.
+pub fn load_global_importance(config_dir: &Path) -> Result<ImportanceConfig> {
+    let Some((path, config)) =
+        load_shared_config::<ImportanceConfig>(config_dir, "importance.toml")?
+    else {
+        return Ok(ImportanceConfig::default());
+    };
+    config
+        .validate()
+        .with_context(|| format!("invalid importance config {}", path.display()))?;
+    Ok(config)
+}
+
+/// Loads the shared `redirects.toml` that sits next to `config_dir`, if any.
+pub fn load_global_redirects(config_dir: &Path) -> Result<Option<RedirectsConfig>> {
+    let Some((path, config)) = load_shared_config::<RedirectsConfig>(config_dir, "redirects.toml")?
+    else {
+        return Ok(None);
+    };
+    config
+        .validate()
+        .with_context(|| format!("invalid redirects config {}", path.display()))?;
+    Ok(Some(config))
+}
+
+/// Loads the shared `network.toml` that sits next to `config_dir`.
+pub fn load_global_network(config_dir: &Path) -> Result<NetworkConfig> {
+    let Some((path, config)) = load_shared_config::<NetworkConfig>(config_dir, "network.toml")?
+    else {
+        return Ok(NetworkConfig::default());
+    };
+    config
+        .validate()
+        .with_context(|| format!("invalid network config {}", path.display()))?;
+    Ok(config)
+}
+
+/// Loads the shared `feed.toml` that sits next to `config_dir`, if any.
+pub fn load_global_feed(config_dir: &Path) -> Result<Option<FeedConfig>> {
+    let Some((path, config)) = load_shared_config::<FeedConfig>(config_dir, "feed.toml")? else {
+        return Ok(None);
+    };
+    if let Some(url) = &config.webhook_url {
+        url::Url::parse(url)
+            .with_context(|| format!("invalid feed webhook_url in {}", path.display()))?;
+    }
+    Ok(Some(config))
+}
+
+/// Loads the shared `smtp.toml` that sits next to `config_dir`, if any.
+pub fn load_global_smtp(config_dir: &Path) -> Result<Option<SmtpConfig>> {
+    let Some((path, config)) = load_shared_config::<SmtpConfig>(config_dir, "smtp.toml")? else {
+        return Ok(None);
+    };
+    if config.host.trim().is_empty() || !config.from.contains('@') {
+        bail!(
+            "invalid smtp config {}: host and from address are required",
+            path.display()
+        );
+    }
+    Ok(Some(config))
+}
+
+/// Env var listing source config dirs, separated like `PATH`.
+pub const CONFIG_PATH_ENV: &str = "RICS_CONFIG_PATH";
+
+/// The directories in a `config_dir`, in override order. A `config_dir` may
+/// be a path list joined with the platform's `PATH` separator (see
+/// [`join_config_dirs`]); sources, bundles and shared configs from later
+/// directories replace earlier ones with the same key or file name.
+pub fn config_roots(config_dir: &Path) -> Vec<PathBuf> {
+    std::env::split_paths(config_dir.as_os_str())
+        .filter(|root| !root.as_os_str().is_empty())
+        .collect()
+}
+
+/// Joins several source config dirs into one `config_dir` path list.
+pub fn join_config_dirs(dirs: &[PathBuf]) -> Result<PathBuf> {
+    std::env::join_paths(dirs)
+        .map(PathBuf::from)
+        .context("config dir paths must not contain the path list separator")
+}
+
+/// The shared `file_name` next to the last config root that has one.
+fn load_shared_config<T: serde::de::DeserializeOwned>(
+    config_dir: &Path,
+    file_name: &str,
+) -> Result<Option<(PathBuf, T)>> {
+    let Some(path) = config_roots(config_dir)
+        .iter()
+        .rev()
+        .filter_map(|root| root.parent().map(|parent| parent.join(file_name)))
+        .find(|path| path.exists())
+    else {
+        return Ok(None);
+    };
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read shared config: {}", path.display()))?;
+    let config = toml::from_str(&text)
+        .with_context(|| format!("failed to parse toml in {}", path.display()))?;
+    Ok(Some((path, config)))
 }
 
+/// Loads every source under `config_dir` into a fresh [`Tenant`], so
+/// nothing fetched for them is shared with any other load.
 pub fn load_sources_from_dir(config_dir: &Path) -> Result<Vec<LoadedSource>> {
+    load_sources_for_tenant(config_dir, &Arc::default())
+}
+
+/// Loads every source under `config_dir` into `tenant`, which the caller
+/// keeps so successive runs over the dir share pooled connections,
+/// rate-limit slots and tokens. A tenant serves one config dir.
+pub fn load_sources_for_tenant(
+    config_dir: &Path,
+    tenant: &Arc<Tenant>,
+) -> Result<Vec<LoadedSource>> {
+    let roots = config_roots(config_dir);
+    if roots.is_empty() {
+        bail!("config dir does not exist: {}", config_dir.display());
+    }
+
+    tenant.set_network(load_global_network(config_dir)?);
+    let mut loaded: Vec<LoadedSource> = Vec::new();
+    for root in roots {
+        let sources = load_sources_from_root(&root, tenant)?;
+        for source in &sources {
+            if let Some(overridden) = loaded
+                .iter()
+                .find(|earlier| earlier.config.source.key == source.config.source.key)
+            {
+                debug!(
+                    source = %source.config.source.key,
+                    overridden = %overridden.path.display(),
+                    by = %source.path.display(),
+                    "source config overridden"
+                );
+            }
+        }
+        loaded.retain(|earlier| {
+            !sources
+                .iter()
+                .any(|source| source.config.source.key == earlier.config.source.key)
+        });
+        loaded.extend(sources);
+    }
+
+    loaded.sort_by(|a, b| a.config.source.key.cmp(&b.config.source.key));
+    Ok(loaded)
+}
+
+fn load_sources_from_root(config_dir: &Path, tenant: &Arc<Tenant>) -> Result<Vec<LoadedSource>> {
     if !config_dir.exists() {
         bail!("config dir does not exist: {}", config_dir.display());
     }
@@ -404,35 +2746,61 @@ pub fn load_sources_from_dir(config_dir: &Path) -> Result<Vec<LoadedSource>> {
 
         let text = std::fs::read_to_string(path)
             .with_context(|| format!("failed to read source config: {}", path.display()))?;
-        let config: SourceConfig = toml::from_str(&text)
-            .with_context(|| format!("failed to parse toml in {}", path.display()))?;
-        config
-            .validate()
+        let config = parse_source_config(&text)
             .with_context(|| format!("invalid source config {}", path.display()))?;
         loaded.push(LoadedSource {
             path: path.to_path_buf(),
             config,
+            tenant: Arc::clone(tenant),
         });
     }
-
-    loaded.sort_by(|a, b| a.config.source.key.cmp(&b.config.source.key));
     Ok(loaded)
 }
 
 pub fn load_source_file(config_path: &Path) -> Result<LoadedSource> {
     let text = std::fs::read_to_string(config_path)
         .with_context(|| format!("failed to read source config: {}", config_path.display()))?;
-    let config: SourceConfig = toml::from_str(&text)
-        .with_context(|| format!("failed to parse toml in {}", config_path.display()))?;
-    config
-        .validate()
+    let config = parse_source_config(&text)
         .with_context(|| format!("invalid source config {}", config_path.display()))?;
+    let config_dir = config_path.parent().unwrap_or(config_path);
+    let tenant = Arc::new(Tenant::default());
+    tenant.set_network(load_global_network(config_dir)?);
     Ok(LoadedSource {
         path: config_path.to_path_buf(),
         config,
+        tenant,
     })
 }
 
+/// Parses and validates a single source config from TOML text.
+pub fn parse_source_config(text: &str) -> Result<SourceConfig> {
+    let mut config: SourceConfig = toml::from_str(text).context("failed to parse source toml")?;
+    apply_default_timezone(&mut config.source);
+    config.validate()?;
+    Ok(config)
+}
+
+/// Fills an unset `source.timezone` from the jurisdiction or country; see
+/// [`crate::timezones`].
+fn apply_default_timezone(source: &mut SourceMeta) {
+    if source.timezone.is_some() {
+        return;
+    }
+    let Some((zone, code)) = derive_timezone(
+        source.jurisdiction.as_deref(),
+        source.default_country.as_deref(),
+    ) else {
+        return;
+    };
+    warn!(
+        source = %source.key,
+        code,
+        timezone = zone,
+        "source.timezone is unset; defaulting from jurisdiction"
+    );
+    source.timezone = Some(zone.to_string());
+}
+
 pub fn load_bundles_from_dir(bundle_dir: &Path) -> Result<Vec<LoadedBundle>> {
     if !bundle_dir.exists() {
         bail!("bundle dir does not exist: {}", bundle_dir.display());
@@ -502,6 +2870,18 @@ fn default_timeout_secs() -> u64 {
     20
 }
 
+fn default_regex_budget_ms() -> u64 {
+    2_000
+}
+
+fn default_max_document_bytes() -> usize {
+    16 << 20
+}
+
+fn default_max_nodes() -> usize {
+    5_000
+}
+
 fn default_retry_attempts() -> u8 {
     2
 }
@@ -510,6 +2890,64 @@ fn default_retry_backoff_ms() -> u64 {
     500
 }
 
+fn default_retry_max_backoff_ms() -> u64 {
+    30_000
+}
+
+fn default_retry_statuses() -> Vec<u16> {
+    vec![408, 425, 429, 500, 502, 503, 504]
+}
+
+fn default_max_body_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_spool_threshold_bytes() -> u64 {
+    8 * 1024 * 1024
+}
+
+fn default_ftp_port() -> u16 {
+    21
+}
+
+fn default_sftp_port() -> u16 {
+    22
+}
+
+/// Checks an ftp or sftp `path`: absolute, with a glob at most in the file
+/// name.
+fn validate_remote_path(field: &str, path: &str) -> Result<()> {
+    let Some((dir, name)) = path
+        .rsplit_once('/')
+        .filter(|(_, name)| path.starts_with('/') && !name.is_empty())
+    else {
+        bail!("{field} must be an absolute file path such as /pub/agenda.pdf, not {path}");
+    };
+    if dir.contains(['*', '?', '[']) {
+        bail!("{field} may only use a glob in its file name");
+    }
+    if let Err(err) = glob::Pattern::new(name) {
+        bail!("{field} file name {name} is not a valid glob: {err}");
+    }
+    Ok(())
+}
+
+fn default_imap_port() -> u16 {
+    993
+}
+
+fn default_imap_password_env() -> String {
+    "RICS_IMAP_PASSWORD".to_string()
+}
+
+fn default_imap_folder() -> String {
+    "INBOX".to_string()
+}
+
+fn default_imap_max_messages() -> usize {
+    20
+}
+
 fn default_page_param() -> String {
     "page".to_string()
 }
@@ -542,6 +2980,17 @@ fn default_status() -> String {
     "scheduled".to_string()
 }
 
+fn default_asset_extensions() -> Vec<String> {
+    ["pdf", "doc", "docx", "xls", "xlsx", "csv", "zip"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_asset_max_bytes() -> u64 {
+    25 * 1024 * 1024
+}
+
 fn default_split_strategy() -> String {
     "regex".to_string()
 }
@@ -0,0 +1,423 @@
+//! Long-running sync loop for `rics daemon`.
+//!
+//! Besides the periodic full sync, a sync of individual sources can be
+//! requested from outside: `POST /sync/<source_key>` (or `POST /sync` for
+//! every source) on the webhook listener, or a message on the MQTT trigger
+//! topic whose payload is the source key. Triggers that arrive while a sync
+//! is running are coalesced into the next one.
+//!
+//! After every sync the daemon refreshes `status.json` in the output
+//! directory; the listener also answers `GET /healthz` with the same
+//! summary, status 503 when any source failed or went stale.
+
+use crate::config::load_sources_from_dir;
+use crate::health::{HealthStatus, HealthTracker, write_health_status};
+use crate::model::State;
+use crate::pipeline::{SyncOptions, sync_sources};
+use crate::store::load_state;
+use crate::tenant::Tenant;
+use anyhow::{Context, Result, anyhow};
+use chrono::Utc;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{error, info, warn};
+
+/// How often the loop wakes up to check for shutdown when idle.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone)]
+pub struct DaemonOptions {
+    pub config_dir: PathBuf,
+    pub state_path: PathBuf,
+    pub out_dir: PathBuf,
+    /// Full sync cadence; `None` syncs only on triggers.
+    pub interval: Option<Duration>,
+    /// Address for the webhook listener, e.g. `127.0.0.1:8787`.
+    pub listen: Option<String>,
+    /// Bearer token required on webhook requests when set.
+    pub webhook_token: Option<String>,
+    /// Passed through to [`SyncOptions::jobs`].
+    pub jobs: usize,
+    /// A source without a successful sync for this long is reported stale.
+    pub stale_after: Duration,
+    #[cfg(feature = "mqtt")]
+    pub mqtt: Option<MqttTrigger>,
+}
+
+#[cfg(feature = "mqtt")]
+#[derive(Debug, Clone)]
+pub struct MqttTrigger {
+    pub host: String,
+    pub port: u16,
+    pub topic: String,
+    pub client_id: String,
+}
+
+/// A request to sync one source, or every source when `source` is `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncTrigger {
+    pub source: Option<String>,
+}
+
+/// Runs until `shutdown` is set. Sync failures are logged and the loop keeps
+/// going; only listener setup errors are returned.
+pub fn run_daemon(options: &DaemonOptions, shutdown: Arc<AtomicBool>) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut workers = Vec::new();
+    let mut health = DaemonHealth::new(options);
+
+    if let Some(addr) = &options.listen {
+        let server = Server::http(addr)
+            .map_err(|err| anyhow!("failed to bind webhook listener on {addr}: {err}"))?;
+        info!(addr = %addr, "webhook trigger listening");
+        let token = options.webhook_token.clone();
+        let sender = sender.clone();
+        let shutdown = Arc::clone(&shutdown);
+        let status = Arc::clone(&health.status);
+        workers.push(thread::spawn(move || {
+            serve_webhook(server, token.as_deref(), &sender, &status, &shutdown)
+        }));
+    }
+
+    #[cfg(feature = "mqtt")]
+    if let Some(mqtt) = &options.mqtt {
+        let mqtt = mqtt.clone();
+        let sender = sender.clone();
+        let shutdown = Arc::clone(&shutdown);
+        info!(host = %mqtt.host, port = mqtt.port, topic = %mqtt.topic, "mqtt trigger subscribing");
+        workers.push(thread::spawn(move || {
+            listen_mqtt(&mqtt, &sender, &shutdown)
+        }));
+    }
+    drop(sender);
+
+    // Kept across runs, so pooled connections and rate-limit budgets carry
+    // over from one sync to the next.
+    let tenant = Arc::new(Tenant::default());
+    run_loop(options, &tenant, &receiver, &mut health, &shutdown);
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+fn run_loop(
+    options: &DaemonOptions,
+    tenant: &Arc<Tenant>,
+    receiver: &Receiver<SyncTrigger>,
+    health: &mut DaemonHealth,
+    shutdown: &AtomicBool,
+) {
+    let mut next_full = options.interval.map(|_| Instant::now());
+
+    while !shutdown.load(Ordering::Relaxed) {
+        if let (Some(due), Some(interval)) = (next_full, options.interval)
+            && Instant::now() >= due
+        {
+            run_sync(options, tenant, None, health);
+            next_full = Some(Instant::now() + interval);
+            continue;
+        }
+
+        let wait = next_full
+            .map(|due| due.saturating_duration_since(Instant::now()))
+            .unwrap_or(POLL_INTERVAL)
+            .min(POLL_INTERVAL);
+        let first = match receiver.recv_timeout(wait) {
+            Ok(trigger) => trigger,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => {
+                // No trigger sources left; fall back to interval-only sleeping.
+                if options.interval.is_none() {
+                    return;
+                }
+                thread::sleep(wait);
+                continue;
+            }
+        };
+
+        let mut pending = vec![first];
+        pending.extend(receiver.try_iter());
+        let targets = coalesce(&pending);
+        if targets.contains(&None) {
+            run_sync(options, tenant, None, health);
+            if let Some(interval) = options.interval {
+                next_full = Some(Instant::now() + interval);
+            }
+        } else {
+            for source in targets {
+                run_sync(options, tenant, source, health);
+            }
+        }
+    }
+}
+
+/// Distinct targets in arrival order; a full-sync trigger subsumes the rest.
+fn coalesce(pending: &[SyncTrigger]) -> Vec<Option<String>> {
+    if pending.iter().any(|trigger| trigger.source.is_none()) {
+        return vec![None];
+    }
+    let mut seen = BTreeSet::new();
+    pending
+        .iter()
+        .filter(|trigger| seen.insert(trigger.source.clone()))
+        .map(|trigger| trigger.source.clone())
+        .collect()
+}
+
+fn run_sync(
+    options: &DaemonOptions,
+    tenant: &Arc<Tenant>,
+    source: Option<String>,
+    health: &mut DaemonHealth,
+) {
+    let label = source.clone().unwrap_or_else(|| "*".to_string());
+    let result = sync_sources(&SyncOptions {
+        config_dir: options.config_dir.clone(),
+        state_path: options.state_path.clone(),
+        out_dir: options.out_dir.clone(),
+        source: source.clone(),
+        jobs: options.jobs,
+        // A trigger naming one source asks for it now.
+        force: source.is_some(),
+        tenant: Arc::clone(tenant),
+        ..SyncOptions::default()
+    });
+    match result {
+        Ok(reports) => {
+            let changed = reports
+                .iter()
+                .map(|r| r.inserted + r.updated + r.cancelled)
+                .sum::<usize>();
+            health.tracker.record_success(
+                reports
+                    .iter()
+                    .filter(|r| r.error.is_none())
+                    .map(|r| r.source_key.as_str()),
+            );
+            for report in &reports {
+                if let Some(error) = &report.error {
+                    health
+                        .tracker
+                        .record_failure([report.source_key.as_str()], error);
+                }
+            }
+            info!(scope = %label, sources = reports.len(), changed, "daemon sync complete");
+        }
+        Err(err) => {
+            let message = format!("{err:#}");
+            // Without reports, every source the sync covered is blamed.
+            let scope = match source {
+                Some(key) => vec![key],
+                None => enabled_source_keys(options).unwrap_or_default(),
+            };
+            health
+                .tracker
+                .record_failure(scope.iter().map(String::as_str), &message);
+            error!(scope = %label, error = %message, "daemon sync failed");
+        }
+    }
+    health.publish(options);
+}
+
+/// Failures seen by this process plus the summary served on `/healthz`.
+struct DaemonHealth {
+    tracker: HealthTracker,
+    status: Arc<Mutex<HealthStatus>>,
+}
+
+impl DaemonHealth {
+    fn new(options: &DaemonOptions) -> Self {
+        let tracker = HealthTracker::default();
+        let status = tracker.status(&[], &State::default(), chrono::Duration::zero(), Utc::now());
+        let mut health = Self {
+            tracker,
+            status: Arc::new(Mutex::new(status)),
+        };
+        health.publish(options);
+        health
+    }
+
+    /// Recomputes the summary from state and writes `status.json`.
+    fn publish(&mut self, options: &DaemonOptions) {
+        let mut status = self
+            .status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let keys = enabled_source_keys(options).unwrap_or_else(|| {
+            status
+                .sources
+                .iter()
+                .map(|source| source.source_key.clone())
+                .collect()
+        });
+        let state = load_state(&options.state_path).unwrap_or_else(|err| {
+            warn!(error = %format!("{err:#}"), "failed to read state for health status");
+            State::default()
+        });
+        let stale_after =
+            chrono::Duration::from_std(options.stale_after).unwrap_or(chrono::Duration::MAX);
+        *status = self.tracker.status(&keys, &state, stale_after, Utc::now());
+        if let Err(err) = write_health_status(&options.out_dir, &status) {
+            warn!(error = %format!("{err:#}"), "failed to write health status");
+        }
+    }
+}
+
+fn enabled_source_keys(options: &DaemonOptions) -> Option<Vec<String>> {
+    let sources = load_sources_from_dir(&options.config_dir).ok()?;
+    Some(
+        sources
+            .into_iter()
+            .filter(|source| source.config.source.enabled)
+            .map(|source| source.config.source.key)
+            .collect(),
+    )
+}
+
+fn serve_webhook(
+    server: Server,
+    token: Option<&str>,
+    sender: &Sender<SyncTrigger>,
+    status: &Mutex<HealthStatus>,
+    shutdown: &AtomicBool,
+) {
+    while !shutdown.load(Ordering::Relaxed) {
+        let request = match server.recv_timeout(POLL_INTERVAL) {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(err) => {
+                warn!(error = %err, "webhook listener failed");
+                return;
+            }
+        };
+        let response = if request.url().split('?').next() == Some("/healthz") {
+            healthz_response(&request, status)
+        } else {
+            let (code, body) = handle_webhook(&request, token, sender);
+            Response::from_string(body).with_status_code(code)
+        };
+        if let Err(err) = request.respond(response) {
+            warn!(error = %err, "failed to answer webhook request");
+        }
+    }
+}
+
+/// The current health summary; unauthenticated so monitors need no token.
+fn healthz_response(
+    request: &Request,
+    status: &Mutex<HealthStatus>,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    if !matches!(request.method(), Method::Get | Method::Head) {
+        return Response::from_string("method not allowed\n").with_status_code(405);
+    }
+    let mut status = status
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+    status.refresh(Utc::now());
+    let body = serde_json::to_string_pretty(&status).unwrap_or_default();
+    Response::from_string(body)
+        .with_status_code(if status.healthy { 200 } else { 503 })
+        .with_header(Header::from_bytes("Content-Type", "application/json").expect("valid header"))
+}
+
+fn handle_webhook(
+    request: &Request,
+    token: Option<&str>,
+    sender: &Sender<SyncTrigger>,
+) -> (u16, &'static str) {
+    if *request.method() != Method::Post {
+        return (405, "method not allowed\n");
+    }
+    if let Some(token) = token
+        && !request
+            .headers()
+            .iter()
+            .any(|header| is_bearer(header, token))
+    {
+        return (401, "unauthorized\n");
+    }
+
+    let path = request.url().split('?').next().unwrap_or_default();
+    let source = match path.trim_end_matches('/') {
+        "/sync" => None,
+        other => match other.strip_prefix("/sync/") {
+            Some(key) if !key.is_empty() && !key.contains('/') => Some(key.to_string()),
+            _ => return (404, "not found\n"),
+        },
+    };
+
+    info!(
+        source = source.as_deref().unwrap_or("*"),
+        "webhook sync trigger"
+    );
+    match sender.send(SyncTrigger { source }) {
+        Ok(()) => (202, "accepted\n"),
+        Err(_) => (503, "shutting down\n"),
+    }
+}
+
+fn is_bearer(header: &Header, token: &str) -> bool {
+    header.field.equiv("Authorization")
+        && header
+            .value
+            .as_str()
+            .strip_prefix("Bearer ")
+            .is_some_and(|value| value.trim() == token)
+}
+
+#[cfg(feature = "mqtt")]
+fn listen_mqtt(mqtt: &MqttTrigger, sender: &Sender<SyncTrigger>, shutdown: &AtomicBool) {
+    use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+
+    let mut mqtt_options = MqttOptions::new(&mqtt.client_id, &mqtt.host, mqtt.port);
+    mqtt_options.set_keep_alive(Duration::from_secs(30));
+    let (client, mut connection) = Client::new(mqtt_options, 16);
+
+    while !shutdown.load(Ordering::Relaxed) {
+        match connection.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(Event::Incoming(Packet::ConnAck(_)))) => {
+                // Subscribe on every (re)connect; clean sessions drop subscriptions.
+                if let Err(err) = client.subscribe(&mqtt.topic, QoS::AtLeastOnce) {
+                    warn!(error = %err, topic = %mqtt.topic, "mqtt subscribe failed");
+                }
+            }
+            Ok(Ok(Event::Incoming(Packet::Publish(publish)))) => {
+                let payload = String::from_utf8_lossy(&publish.payload).trim().to_string();
+                let source = (!payload.is_empty() && payload != "*").then_some(payload);
+                info!(
+                    topic = %publish.topic,
+                    source = source.as_deref().unwrap_or("*"),
+                    "mqtt sync trigger"
+                );
+                if sender.send(SyncTrigger { source }).is_err() {
+                    break;
+                }
+            }
+            Ok(Ok(_)) | Err(rumqttc::RecvTimeoutError::Timeout) => {}
+            Ok(Err(err)) => {
+                warn!(error = %err, "mqtt connection error; retrying");
+                thread::sleep(Duration::from_secs(1));
+            }
+            Err(rumqttc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let _ = client.disconnect();
+}
+
+/// Reads the webhook bearer token from `env_var`, if set and non-empty.
+pub fn webhook_token_from_env(env_var: &str) -> Result<Option<String>> {
+    match std::env::var(env_var) {
+        Ok(value) if !value.trim().is_empty() => Ok(Some(value.trim().to_string())),
+        Ok(_) | Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("invalid {env_var}")),
+    }
+}
@@ -0,0 +1,176 @@
+//! Selector drift diagnostics for HTML sources.
+//!
+//! When a redesign leaves `root_selector` matching nothing, the records are
+//! usually still there under new markup: a run of structurally identical
+//! siblings that each contain a date. This module finds such runs and offers
+//! selectors for them.
+
+use crate::config::{ExtractFormat, load_sources_from_dir};
+use crate::fetch::fetch_source_documents;
+use crate::parser::{detect_date_in_text, excerpt};
+use anyhow::{Result, bail};
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Fewest identical siblings considered a record list.
+const MIN_SIBLINGS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct DiagnoseOptions {
+    pub config_dir: PathBuf,
+    pub source: String,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentDiagnosis {
+    pub source_url: String,
+    pub root_selector: Option<String>,
+    /// Nodes the configured `root_selector` matches today.
+    pub current_matches: usize,
+    pub suggestions: Vec<SelectorSuggestion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SelectorSuggestion {
+    pub selector: String,
+    /// Nodes the selector matches in the whole document.
+    pub matches: usize,
+    /// Matched siblings that contain a date.
+    pub dated: usize,
+    /// Text of the first match, shortened.
+    pub sample: String,
+}
+
+/// Fetches the source's documents and suggests root selectors for each.
+pub fn diagnose_source(options: &DiagnoseOptions) -> Result<Vec<DocumentDiagnosis>> {
+    let Some(source) = load_sources_from_dir(&options.config_dir)?
+        .into_iter()
+        .find(|s| s.config.source.key == options.source)
+    else {
+        bail!("no source configuration with key {}", options.source);
+    };
+    if source.config.extract.format != ExtractFormat::Html {
+        bail!(
+            "source {} does not use html extraction; selector suggestions only apply to html",
+            options.source
+        );
+    }
+
+    let root_selector = source.config.extract.root_selector.clone();
+    let parsed_root = root_selector
+        .as_deref()
+        .and_then(|selector| Selector::parse(selector).ok());
+
+    let mut diagnoses = Vec::new();
+    for doc in fetch_source_documents(&source)? {
+        let html = doc.text().into_owned();
+        let current_matches = parsed_root
+            .as_ref()
+            .map(|selector| Html::parse_document(&html).select(selector).count())
+            .unwrap_or_default();
+        diagnoses.push(DocumentDiagnosis {
+            source_url: doc.source_url,
+            root_selector: root_selector.clone(),
+            current_matches,
+            suggestions: suggest_root_selectors(&html, options.limit),
+        });
+    }
+    Ok(diagnoses)
+}
+
+/// Candidate root selectors for repeated, dated sibling elements, best first.
+pub fn suggest_root_selectors(html: &str, limit: usize) -> Vec<SelectorSuggestion> {
+    let doc = Html::parse_document(html);
+    let mut best: BTreeMap<String, SelectorSuggestion> = BTreeMap::new();
+
+    for parent in doc
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+    {
+        let mut groups: BTreeMap<String, Vec<ElementRef<'_>>> = BTreeMap::new();
+        for child in parent.children().filter_map(ElementRef::wrap) {
+            groups.entry(signature(child)).or_default().push(child);
+        }
+
+        for (child_signature, members) in groups {
+            if members.len() < MIN_SIBLINGS {
+                continue;
+            }
+            let dated = members.iter().filter(|member| has_date(member)).count();
+            if dated * 2 < members.len() {
+                continue;
+            }
+
+            let selector = qualify(parent, &child_signature);
+            let Ok(parsed) = Selector::parse(&selector) else {
+                continue;
+            };
+            let suggestion = SelectorSuggestion {
+                matches: doc.select(&parsed).count(),
+                dated,
+                sample: excerpt(&members[0].text().collect::<Vec<_>>().join(" ")),
+                selector: selector.clone(),
+            };
+            match best.get(&selector) {
+                Some(existing) if existing.dated >= suggestion.dated => {}
+                _ => {
+                    best.insert(selector, suggestion);
+                }
+            }
+        }
+    }
+
+    let mut suggestions = best.into_values().collect::<Vec<_>>();
+    suggestions.sort_by(|a, b| {
+        b.dated
+            .cmp(&a.dated)
+            .then_with(|| a.selector.len().cmp(&b.selector.len()))
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// `tag.class1.class2` with classes sorted; classes that look generated
+/// (containing digits) are left out because they rarely survive a rebuild.
+fn signature(element: ElementRef<'_>) -> String {
+    let mut classes = element
+        .value()
+        .classes()
+        .filter(|class| !class.chars().any(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>();
+    classes.sort_unstable();
+    let mut out = element.value().name().to_string();
+    for class in classes {
+        out.push('.');
+        out.push_str(class);
+    }
+    out
+}
+
+/// Classed children stand on their own; bare tags are anchored to their
+/// parent's id or classes so `li` does not match every list on the page.
+fn qualify(parent: ElementRef<'_>, child_signature: &str) -> String {
+    if child_signature.contains('.') {
+        return child_signature.to_string();
+    }
+    if let Some(id) = parent.value().id()
+        && !id.chars().any(|c| c.is_ascii_digit())
+    {
+        return format!("#{id} > {child_signature}");
+    }
+    let parent_signature = signature(parent);
+    if parent_signature.contains('.') {
+        return format!("{parent_signature} > {child_signature}");
+    }
+    child_signature.to_string()
+}
+
+fn has_date(element: &ElementRef<'_>) -> bool {
+    let time = Selector::parse("time").expect("time selector must be valid");
+    element.select(&time).next().is_some()
+        || detect_date_in_text(&element.text().collect::<Vec<_>>().join(" ")).is_some()
+}
@@ -0,0 +1,655 @@
+//! Built-in enrichment applied to parsed candidates before they are merged.
+//!
+//! Sources disagree on how they name countries: some set `default_country`,
+//! some only a `jurisdiction` like `US-TX-HOU`, some spell the country out in
+//! the title, and feeds mix `GBR`, `UK` and `United Kingdom`. The country
+//! enricher settles all of them on ISO 3166-1 alpha-2 codes so per-country
+//! calendars and bundle filters line up across sources.
+//!
+//! Category rules tag events by keyword so bundles can filter on topics
+//! without every source mapping categories itself, and importance rules
+//! re-rank whole families of events from one place. Holiday annotation
+//! flags events that land on a public holiday or weekend.
+
+use crate::config::{CategorizeConfig, ImportanceConfig, ImportanceRule, SourceConfig};
+use crate::holidays::{HolidayCalendar, is_weekend};
+use crate::model::{CandidateEvent, EventTimeSpec};
+use crate::regex_limits;
+use crate::store::matches_source_patterns;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// Runs every enabled enricher over the candidates in place.
+pub fn enrich_candidates(source: &SourceConfig, events: &mut [CandidateEvent]) -> Result<()> {
+    if source.enrich.infer_country {
+        for event in events.iter_mut() {
+            infer_country(source, event);
+        }
+    }
+    let categorizer = Categorizer::new(&source.categorize)?;
+    for event in events.iter_mut() {
+        categorizer.apply(event);
+    }
+    if source.holidays.annotate {
+        let extra = source.holidays.extra_dates()?;
+        for event in events.iter_mut() {
+            annotate_holiday(source, &extra, event);
+        }
+    }
+    Ok(())
+}
+
+/// Records `holiday` (and `weekend`, when enabled) metadata for events whose
+/// day falls on one. Only day-precise events are checked.
+pub fn annotate_holiday(
+    source: &SourceConfig,
+    extra: &BTreeMap<NaiveDate, String>,
+    event: &mut CandidateEvent,
+) {
+    let Some(date) = event_day(source, event) else {
+        return;
+    };
+    let country = source
+        .holidays
+        .country
+        .as_deref()
+        .or(event.country.as_deref())
+        .unwrap_or_default();
+    if let Some(calendar) = HolidayCalendar::new(country, extra.clone())
+        && let Some(name) = calendar.holiday(date)
+    {
+        event.metadata.insert(
+            "holiday".to_string(),
+            format!("{name} ({})", calendar.country()),
+        );
+    }
+    if source.holidays.weekends && is_weekend(date) {
+        event
+            .metadata
+            .insert("weekend".to_string(), date.format("%A").to_string());
+    }
+}
+
+/// The calendar day an event happens on where it is published.
+fn event_day(source: &SourceConfig, event: &CandidateEvent) -> Option<NaiveDate> {
+    match &event.time {
+        EventTimeSpec::Date { start, .. } => Some(*start),
+        EventTimeSpec::DateTime { start, local, .. } => {
+            if let Some(local) = local {
+                return Some(local.start.date());
+            }
+            let zone = event
+                .timezone
+                .as_deref()
+                .or(source.source.timezone.as_deref())
+                .and_then(|tz| tz.parse::<Tz>().ok());
+            Some(match zone {
+                Some(tz) => start.with_timezone(&tz).date_naive(),
+                None => start.date_naive(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Compiled `[categorize]` rules.
+#[derive(Debug, Clone, Default)]
+pub struct Categorizer {
+    rules: Vec<(Regex, Vec<String>)>,
+}
+
+impl Categorizer {
+    pub fn new(config: &CategorizeConfig) -> Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let regex = regex_limits::build(&rule.pattern, true)
+                    .with_context(|| format!("invalid categorize pattern {}", rule.pattern))?;
+                let categories = rule
+                    .categories
+                    .iter()
+                    .map(|category| category.trim().to_string())
+                    .filter(|category| !category.is_empty())
+                    .collect();
+                Ok((regex, categories))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Adds the categories of every rule matching the title or description.
+    /// Returns how many categories were new to the event.
+    pub fn apply(&self, event: &mut CandidateEvent) -> usize {
+        let mut added = 0;
+        for (regex, categories) in &self.rules {
+            let matched = regex.is_match(&event.title)
+                || event
+                    .description
+                    .as_deref()
+                    .is_some_and(|description| regex.is_match(description));
+            if !matched {
+                continue;
+            }
+            for category in categories {
+                if !event.categories.contains(category) {
+                    event.categories.push(category.clone());
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
+
+/// Compiled importance rules, in evaluation order.
+#[derive(Debug, Clone, Default)]
+pub struct ImportanceScorer {
+    rules: Vec<(Option<Regex>, ImportanceRule)>,
+}
+
+impl ImportanceScorer {
+    /// Rules from each config in turn; pass the source's own config first.
+    pub fn new(configs: &[&ImportanceConfig]) -> Result<Self> {
+        let rules = configs
+            .iter()
+            .flat_map(|config| config.rules.iter())
+            .map(|rule| {
+                let title = rule
+                    .title
+                    .as_deref()
+                    .map(|pattern| {
+                        regex_limits::build(pattern, true)
+                            .with_context(|| format!("invalid importance title pattern {pattern}"))
+                    })
+                    .transpose()?;
+                Ok((title, rule.clone()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Applies the first matching rule. Returns true when importance changed.
+    pub fn apply(&self, event: &mut CandidateEvent) -> bool {
+        let Some((_, rule)) = self
+            .rules
+            .iter()
+            .find(|(title, rule)| rule_matches(title.as_ref(), rule, event))
+        else {
+            return false;
+        };
+        if !rule.overwrite && event.importance.is_some() {
+            return false;
+        }
+        let changed = event.importance != Some(rule.importance);
+        event.importance = Some(rule.importance);
+        changed
+    }
+}
+
+fn rule_matches(title: Option<&Regex>, rule: &ImportanceRule, event: &CandidateEvent) -> bool {
+    title.is_none_or(|regex| regex.is_match(&event.title))
+        && rule
+            .event_type
+            .as_ref()
+            .is_none_or(|event_type| event.event_type.eq_ignore_ascii_case(event_type))
+        && rule.source.as_ref().is_none_or(|pattern| {
+            matches_source_patterns(&event.source_key, std::slice::from_ref(pattern))
+        })
+        && rule.country.as_ref().is_none_or(|country| {
+            event
+                .country
+                .as_deref()
+                .is_some_and(|value| value.eq_ignore_ascii_case(country))
+        })
+        && rule
+            .category
+            .as_ref()
+            .is_none_or(|category| event.categories.contains(category))
+}
+
+/// Sets `event.country` to an ISO 3166-1 alpha-2 code.
+///
+/// An existing value (including `default_country`, which the declarative
+/// parser fills in up front) is only canonicalized. Otherwise the country
+/// comes from the first of: the jurisdiction's leading segment, a
+/// `country_map` keyword in the title, a country named in the title, and
+/// `default_country`.
+pub fn infer_country(source: &SourceConfig, event: &mut CandidateEvent) {
+    if let Some(raw) = event.country.as_deref() {
+        if let Some(code) = resolve_country(source, raw)
+            && code != raw
+        {
+            event.country = Some(code);
+        }
+        return;
+    }
+
+    let inferred = event
+        .jurisdiction
+        .as_deref()
+        .and_then(|jurisdiction| jurisdiction.split('-').next())
+        .and_then(|segment| resolve_country(source, segment))
+        .map(|code| (code, "jurisdiction"))
+        .or_else(|| mapped_keyword(source, &event.title).map(|code| (code, "country_map")))
+        .or_else(|| country_named_in(&event.title).map(|code| (code, "title")))
+        .or_else(|| {
+            source
+                .source
+                .default_country
+                .as_deref()
+                .and_then(|raw| resolve_country(source, raw))
+                .map(|code| (code, "default_country"))
+        });
+
+    if let Some((code, from)) = inferred {
+        debug!(
+            source = %source.source.key,
+            title = %event.title,
+            country = %code,
+            from,
+            "country inferred"
+        );
+        event.country = Some(code);
+    }
+}
+
+/// Resolves a code, alpha-3 code, name or `country_map` key to an alpha-2
+/// code. `country_map` entries win over the built-in table.
+pub fn resolve_country(source: &SourceConfig, raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    if let Some(code) = source
+        .enrich
+        .country_map
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(raw))
+        .map(|(_, code)| code.trim().to_ascii_uppercase())
+    {
+        return Some(code);
+    }
+    country_index()
+        .get(&raw.to_lowercase())
+        .map(|code| code.to_string())
+}
+
+fn mapped_keyword(source: &SourceConfig, title: &str) -> Option<String> {
+    let lowered = title.to_lowercase();
+    source
+        .enrich
+        .country_map
+        .iter()
+        .find(|(key, _)| contains_word(&lowered, &key.to_lowercase()))
+        .map(|(_, code)| code.trim().to_ascii_uppercase())
+}
+
+/// The country a title names, when it names exactly one. Only full names and
+/// common aliases count; bare codes are too easy to confuse with words.
+fn country_named_in(title: &str) -> Option<String> {
+    let mut found: Vec<(&str, &str)> = COUNTRIES
+        .iter()
+        .map(|(code, _, name)| (*name, *code))
+        .chain(ALIASES.iter().copied())
+        .filter(|(name, _)| !AMBIGUOUS_NAMES.contains(name) && contains_word(title, name))
+        .collect();
+    // "Guinea" inside "Papua New Guinea" is not a second country.
+    let names = found.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    found.retain(|(name, _)| {
+        !names
+            .iter()
+            .any(|other| other.len() > name.len() && other.contains(name))
+    });
+    found.sort_by_key(|(_, code)| *code);
+    found.dedup_by_key(|(_, code)| *code);
+    match found.as_slice() {
+        [(_, code)] => Some(code.to_string()),
+        _ => None,
+    }
+}
+
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Lowercased alpha-2, alpha-3, names and aliases to alpha-2.
+fn country_index() -> &'static HashMap<String, &'static str> {
+    static INDEX: OnceLock<HashMap<String, &'static str>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let mut index = HashMap::new();
+        for (alpha2, alpha3, name) in COUNTRIES {
+            index.insert(alpha2.to_lowercase(), *alpha2);
+            index.insert(alpha3.to_lowercase(), *alpha2);
+            index.insert(name.to_lowercase(), *alpha2);
+        }
+        for (alias, alpha2) in ALIASES.iter().chain(CODE_ALIASES) {
+            index.insert(alias.to_lowercase(), *alpha2);
+        }
+        index
+    })
+}
+
+/// Names that are also common words or US states, skipped when scanning
+/// titles.
+const AMBIGUOUS_NAMES: &[&str] = &["Chad", "Georgia", "Jersey", "Jordan", "Turkey", "Wales"];
+
+/// Alternate spellings accepted both in titles and as field values.
+const ALIASES: &[(&str, &str)] = &[
+    ("United States", "US"),
+    ("U.S.", "US"),
+    ("USA", "US"),
+    ("United Kingdom", "GB"),
+    ("Britain", "GB"),
+    ("Great Britain", "GB"),
+    ("England", "GB"),
+    ("Scotland", "GB"),
+    ("Wales", "GB"),
+    ("Northern Ireland", "GB"),
+    ("Russia", "RU"),
+    ("South Korea", "KR"),
+    ("Korea", "KR"),
+    ("North Korea", "KP"),
+    ("Czech Republic", "CZ"),
+    ("Vietnam", "VN"),
+    ("Iran", "IR"),
+    ("Syria", "SY"),
+    ("Laos", "LA"),
+    ("Bolivia", "BO"),
+    ("Venezuela", "VE"),
+    ("Tanzania", "TZ"),
+    ("Moldova", "MD"),
+    ("Macedonia", "MK"),
+    ("Ivory Coast", "CI"),
+    ("Taiwan", "TW"),
+    ("Holland", "NL"),
+    ("Vatican", "VA"),
+    ("Kosovo", "XK"),
+    ("Palestine", "PS"),
+    ("Micronesia", "FM"),
+    ("Brunei", "BN"),
+    ("Cape Verde", "CV"),
+    ("Swaziland", "SZ"),
+    ("Burma", "MM"),
+    ("East Timor", "TL"),
+    ("Türkiye", "TR"),
+    ("Turkey", "TR"),
+];
+
+/// Non-standard codes seen in feeds (Eurostat uses `EL` and `UK`).
+const CODE_ALIASES: &[(&str, &str)] = &[("UK", "GB"), ("EL", "GR"), ("XKX", "XK")];
+
+/// ISO 3166-1 alpha-2, alpha-3 and short name, plus `XK` for Kosovo.
+const COUNTRIES: &[(&str, &str, &str)] = &[
+    ("AD", "AND", "Andorra"),
+    ("AE", "ARE", "United Arab Emirates"),
+    ("AF", "AFG", "Afghanistan"),
+    ("AG", "ATG", "Antigua and Barbuda"),
+    ("AI", "AIA", "Anguilla"),
+    ("AL", "ALB", "Albania"),
+    ("AM", "ARM", "Armenia"),
+    ("AO", "AGO", "Angola"),
+    ("AQ", "ATA", "Antarctica"),
+    ("AR", "ARG", "Argentina"),
+    ("AS", "ASM", "American Samoa"),
+    ("AT", "AUT", "Austria"),
+    ("AU", "AUS", "Australia"),
+    ("AW", "ABW", "Aruba"),
+    ("AX", "ALA", "Åland Islands"),
+    ("AZ", "AZE", "Azerbaijan"),
+    ("BA", "BIH", "Bosnia and Herzegovina"),
+    ("BB", "BRB", "Barbados"),
+    ("BD", "BGD", "Bangladesh"),
+    ("BE", "BEL", "Belgium"),
+    ("BF", "BFA", "Burkina Faso"),
+    ("BG", "BGR", "Bulgaria"),
+    ("BH", "BHR", "Bahrain"),
+    ("BI", "BDI", "Burundi"),
+    ("BJ", "BEN", "Benin"),
+    ("BL", "BLM", "Saint Barthélemy"),
+    ("BM", "BMU", "Bermuda"),
+    ("BN", "BRN", "Brunei Darussalam"),
+    ("BO", "BOL", "Bolivia, Plurinational State of"),
+    ("BQ", "BES", "Bonaire, Sint Eustatius and Saba"),
+    ("BR", "BRA", "Brazil"),
+    ("BS", "BHS", "Bahamas"),
+    ("BT", "BTN", "Bhutan"),
+    ("BV", "BVT", "Bouvet Island"),
+    ("BW", "BWA", "Botswana"),
+    ("BY", "BLR", "Belarus"),
+    ("BZ", "BLZ", "Belize"),
+    ("CA", "CAN", "Canada"),
+    ("CC", "CCK", "Cocos (Keeling) Islands"),
+    ("CD", "COD", "Congo, Democratic Republic of the"),
+    ("CF", "CAF", "Central African Republic"),
+    ("CG", "COG", "Congo"),
+    ("CH", "CHE", "Switzerland"),
+    ("CI", "CIV", "Côte d'Ivoire"),
+    ("CK", "COK", "Cook Islands"),
+    ("CL", "CHL", "Chile"),
+    ("CM", "CMR", "Cameroon"),
+    ("CN", "CHN", "China"),
+    ("CO", "COL", "Colombia"),
+    ("CR", "CRI", "Costa Rica"),
+    ("CU", "CUB", "Cuba"),
+    ("CV", "CPV", "Cabo Verde"),
+    ("CW", "CUW", "Curaçao"),
+    ("CX", "CXR", "Christmas Island"),
+    ("CY", "CYP", "Cyprus"),
+    ("CZ", "CZE", "Czechia"),
+    ("DE", "DEU", "Germany"),
+    ("DJ", "DJI", "Djibouti"),
+    ("DK", "DNK", "Denmark"),
+    ("DM", "DMA", "Dominica"),
+    ("DO", "DOM", "Dominican Republic"),
+    ("DZ", "DZA", "Algeria"),
+    ("EC", "ECU", "Ecuador"),
+    ("EE", "EST", "Estonia"),
+    ("EG", "EGY", "Egypt"),
+    ("EH", "ESH", "Western Sahara"),
+    ("ER", "ERI", "Eritrea"),
+    ("ES", "ESP", "Spain"),
+    ("ET", "ETH", "Ethiopia"),
+    ("FI", "FIN", "Finland"),
+    ("FJ", "FJI", "Fiji"),
+    ("FK", "FLK", "Falkland Islands (Malvinas)"),
+    ("FM", "FSM", "Micronesia, Federated States of"),
+    ("FO", "FRO", "Faroe Islands"),
+    ("FR", "FRA", "France"),
+    ("GA", "GAB", "Gabon"),
+    (
+        "GB",
+        "GBR",
+        "United Kingdom of Great Britain and Northern Ireland",
+    ),
+    ("GD", "GRD", "Grenada"),
+    ("GE", "GEO", "Georgia"),
+    ("GF", "GUF", "French Guiana"),
+    ("GG", "GGY", "Guernsey"),
+    ("GH", "GHA", "Ghana"),
+    ("GI", "GIB", "Gibraltar"),
+    ("GL", "GRL", "Greenland"),
+    ("GM", "GMB", "Gambia"),
+    ("GN", "GIN", "Guinea"),
+    ("GP", "GLP", "Guadeloupe"),
+    ("GQ", "GNQ", "Equatorial Guinea"),
+    ("GR", "GRC", "Greece"),
+    ("GS", "SGS", "South Georgia and the South Sandwich Islands"),
+    ("GT", "GTM", "Guatemala"),
+    ("GU", "GUM", "Guam"),
+    ("GW", "GNB", "Guinea-Bissau"),
+    ("GY", "GUY", "Guyana"),
+    ("HK", "HKG", "Hong Kong"),
+    ("HM", "HMD", "Heard Island and McDonald Islands"),
+    ("HN", "HND", "Honduras"),
+    ("HR", "HRV", "Croatia"),
+    ("HT", "HTI", "Haiti"),
+    ("HU", "HUN", "Hungary"),
+    ("ID", "IDN", "Indonesia"),
+    ("IE", "IRL", "Ireland"),
+    ("IL", "ISR", "Israel"),
+    ("IM", "IMN", "Isle of Man"),
+    ("IN", "IND", "India"),
+    ("IO", "IOT", "British Indian Ocean Territory"),
+    ("IQ", "IRQ", "Iraq"),
+    ("IR", "IRN", "Iran, Islamic Republic of"),
+    ("IS", "ISL", "Iceland"),
+    ("IT", "ITA", "Italy"),
+    ("JE", "JEY", "Jersey"),
+    ("JM", "JAM", "Jamaica"),
+    ("JO", "JOR", "Jordan"),
+    ("JP", "JPN", "Japan"),
+    ("KE", "KEN", "Kenya"),
+    ("KG", "KGZ", "Kyrgyzstan"),
+    ("KH", "KHM", "Cambodia"),
+    ("KI", "KIR", "Kiribati"),
+    ("KM", "COM", "Comoros"),
+    ("KN", "KNA", "Saint Kitts and Nevis"),
+    ("KP", "PRK", "Korea, Democratic People's Republic of"),
+    ("KR", "KOR", "Korea, Republic of"),
+    ("KW", "KWT", "Kuwait"),
+    ("KY", "CYM", "Cayman Islands"),
+    ("KZ", "KAZ", "Kazakhstan"),
+    ("LA", "LAO", "Lao People's Democratic Republic"),
+    ("LB", "LBN", "Lebanon"),
+    ("LC", "LCA", "Saint Lucia"),
+    ("LI", "LIE", "Liechtenstein"),
+    ("LK", "LKA", "Sri Lanka"),
+    ("LR", "LBR", "Liberia"),
+    ("LS", "LSO", "Lesotho"),
+    ("LT", "LTU", "Lithuania"),
+    ("LU", "LUX", "Luxembourg"),
+    ("LV", "LVA", "Latvia"),
+    ("LY", "LBY", "Libya"),
+    ("MA", "MAR", "Morocco"),
+    ("MC", "MCO", "Monaco"),
+    ("MD", "MDA", "Moldova, Republic of"),
+    ("ME", "MNE", "Montenegro"),
+    ("MF", "MAF", "Saint Martin (French part)"),
+    ("MG", "MDG", "Madagascar"),
+    ("MH", "MHL", "Marshall Islands"),
+    ("MK", "MKD", "North Macedonia"),
+    ("ML", "MLI", "Mali"),
+    ("MM", "MMR", "Myanmar"),
+    ("MN", "MNG", "Mongolia"),
+    ("MO", "MAC", "Macao"),
+    ("MP", "MNP", "Northern Mariana Islands"),
+    ("MQ", "MTQ", "Martinique"),
+    ("MR", "MRT", "Mauritania"),
+    ("MS", "MSR", "Montserrat"),
+    ("MT", "MLT", "Malta"),
+    ("MU", "MUS", "Mauritius"),
+    ("MV", "MDV", "Maldives"),
+    ("MW", "MWI", "Malawi"),
+    ("MX", "MEX", "Mexico"),
+    ("MY", "MYS", "Malaysia"),
+    ("MZ", "MOZ", "Mozambique"),
+    ("NA", "NAM", "Namibia"),
+    ("NC", "NCL", "New Caledonia"),
+    ("NE", "NER", "Niger"),
+    ("NF", "NFK", "Norfolk Island"),
+    ("NG", "NGA", "Nigeria"),
+    ("NI", "NIC", "Nicaragua"),
+    ("NL", "NLD", "Netherlands"),
+    ("NO", "NOR", "Norway"),
+    ("NP", "NPL", "Nepal"),
+    ("NR", "NRU", "Nauru"),
+    ("NU", "NIU", "Niue"),
+    ("NZ", "NZL", "New Zealand"),
+    ("OM", "OMN", "Oman"),
+    ("PA", "PAN", "Panama"),
+    ("PE", "PER", "Peru"),
+    ("PF", "PYF", "French Polynesia"),
+    ("PG", "PNG", "Papua New Guinea"),
+    ("PH", "PHL", "Philippines"),
+    ("PK", "PAK", "Pakistan"),
+    ("PL", "POL", "Poland"),
+    ("PM", "SPM", "Saint Pierre and Miquelon"),
+    ("PN", "PCN", "Pitcairn"),
+    ("PR", "PRI", "Puerto Rico"),
+    ("PS", "PSE", "Palestine, State of"),
+    ("PT", "PRT", "Portugal"),
+    ("PW", "PLW", "Palau"),
+    ("PY", "PRY", "Paraguay"),
+    ("QA", "QAT", "Qatar"),
+    ("RE", "REU", "Réunion"),
+    ("RO", "ROU", "Romania"),
+    ("RS", "SRB", "Serbia"),
+    ("RU", "RUS", "Russian Federation"),
+    ("RW", "RWA", "Rwanda"),
+    ("SA", "SAU", "Saudi Arabia"),
+    ("SB", "SLB", "Solomon Islands"),
+    ("SC", "SYC", "Seychelles"),
+    ("SD", "SDN", "Sudan"),
+    ("SE", "SWE", "Sweden"),
+    ("SG", "SGP", "Singapore"),
+    ("SH", "SHN", "Saint Helena, Ascension and Tristan da Cunha"),
+    ("SI", "SVN", "Slovenia"),
+    ("SJ", "SJM", "Svalbard and Jan Mayen"),
+    ("SK", "SVK", "Slovakia"),
+    ("SL", "SLE", "Sierra Leone"),
+    ("SM", "SMR", "San Marino"),
+    ("SN", "SEN", "Senegal"),
+    ("SO", "SOM", "Somalia"),
+    ("SR", "SUR", "Suriname"),
+    ("SS", "SSD", "South Sudan"),
+    ("ST", "STP", "Sao Tome and Principe"),
+    ("SV", "SLV", "El Salvador"),
+    ("SX", "SXM", "Sint Maarten (Dutch part)"),
+    ("SY", "SYR", "Syrian Arab Republic"),
+    ("SZ", "SWZ", "Eswatini"),
+    ("TC", "TCA", "Turks and Caicos Islands"),
+    ("TD", "TCD", "Chad"),
+    ("TF", "ATF", "French Southern Territories"),
+    ("TG", "TGO", "Togo"),
+    ("TH", "THA", "Thailand"),
+    ("TJ", "TJK", "Tajikistan"),
+    ("TK", "TKL", "Tokelau"),
+    ("TL", "TLS", "Timor-Leste"),
+    ("TM", "TKM", "Turkmenistan"),
+    ("TN", "TUN", "Tunisia"),
+    ("TO", "TON", "Tonga"),
+    ("TR", "TUR", "Türkiye"),
+    ("TT", "TTO", "Trinidad and Tobago"),
+    ("TV", "TUV", "Tuvalu"),
+    ("TW", "TWN", "Taiwan, Province of China"),
+    ("TZ", "TZA", "Tanzania, United Republic of"),
+    ("UA", "UKR", "Ukraine"),
+    ("UG", "UGA", "Uganda"),
+    ("UM", "UMI", "United States Minor Outlying Islands"),
+    ("US", "USA", "United States of America"),
+    ("UY", "URY", "Uruguay"),
+    ("UZ", "UZB", "Uzbekistan"),
+    ("VA", "VAT", "Holy See"),
+    ("VC", "VCT", "Saint Vincent and the Grenadines"),
+    ("VE", "VEN", "Venezuela, Bolivarian Republic of"),
+    ("VG", "VGB", "Virgin Islands (British)"),
+    ("VI", "VIR", "Virgin Islands (U.S.)"),
+    ("VN", "VNM", "Viet Nam"),
+    ("VU", "VUT", "Vanuatu"),
+    ("WF", "WLF", "Wallis and Futuna"),
+    ("WS", "WSM", "Samoa"),
+    ("XK", "XKX", "Kosovo"),
+    ("YE", "YEM", "Yemen"),
+    ("YT", "MYT", "Mayotte"),
+    ("ZA", "ZAF", "South Africa"),
+    ("ZM", "ZMB", "Zambia"),
+    ("ZW", "ZWE", "Zimbabwe"),
+];
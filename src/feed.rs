@@ -0,0 +1,110 @@
+//! RSS and Atom items for `extract.format = "feed"`.
+//!
+//! Every `<item>` (RSS 0.9x, 1.0 and 2.0) or `<entry>` (Atom) becomes one
+//! record. Atom names are mapped onto the RSS ones, so `title`, `link`,
+//! `pubDate`, `description` and `guid` mean the same in either, and
+//! `pubDate` is rewritten from RFC 2822 to RFC 3339. Any other child
+//! element with plain text, such as the RSS event module's
+//! `<ev:startdate>`, is kept under its local name (`startdate`).
+
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use roxmltree::{Document, Node};
+use std::collections::BTreeMap;
+
+/// One feed item: its fields, and its XML for excerpts and `--capture-raw`.
+#[derive(Debug, Clone)]
+pub struct FeedItem {
+    pub fields: BTreeMap<String, String>,
+    pub raw: String,
+}
+
+/// The items of an RSS or Atom document, in document order.
+pub fn feed_items(xml: &str) -> Result<Vec<FeedItem>> {
+    let doc = Document::parse(xml).context("failed to parse feed xml")?;
+    Ok(doc
+        .descendants()
+        .filter(|node| matches!(node.tag_name().name(), "item" | "entry"))
+        .map(|node| FeedItem {
+            fields: item_fields(node),
+            raw: xml[node.range()].to_string(),
+        })
+        .collect())
+}
+
+fn item_fields(item: Node<'_, '_>) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    let mut categories = Vec::new();
+    for child in item.children().filter(Node::is_element) {
+        let name = child.tag_name().name();
+        match name {
+            // Atom: the alternate link, or the first without a rel.
+            "link" if child.has_attribute("href") => {
+                let rel = child.attribute("rel").unwrap_or("alternate");
+                if rel == "alternate"
+                    && let Some(href) = child.attribute("href")
+                {
+                    fields.entry("link".to_string()).or_insert(href.to_string());
+                }
+            }
+            "category" => {
+                if let Some(term) = child.attribute("term").or_else(|| child.text()) {
+                    categories.push(term.trim().to_string());
+                }
+            }
+            // Atom's `<author><name>`; RSS authors are plain text.
+            "author" if child.children().any(|node| node.is_element()) => {
+                if let Some(author) = child_text(child, "name") {
+                    fields.insert("author".to_string(), author);
+                }
+            }
+            _ => {
+                let Some(text) = element_text(child) else {
+                    continue;
+                };
+                let key = match name {
+                    "id" => "guid",
+                    "summary" | "content" => "description",
+                    "published" => "pubDate",
+                    other => other,
+                };
+                // RSS `description` wins over Atom `content`, and Atom
+                // `published` over `updated`.
+                fields.entry(key.to_string()).or_insert(text);
+            }
+        }
+    }
+    if !fields.contains_key("pubDate")
+        && let Some(updated) = fields.get("updated").cloned()
+    {
+        fields.insert("pubDate".to_string(), updated);
+    }
+    if let Some(date) = fields.get_mut("pubDate")
+        && let Ok(parsed) = DateTime::parse_from_rfc2822(date)
+    {
+        *date = parsed.to_rfc3339();
+    }
+    if !categories.is_empty() {
+        fields.insert("categories".to_string(), categories.join(", "));
+    }
+    fields
+}
+
+/// Text of an element with no child elements; CDATA sections are included.
+fn element_text(node: Node<'_, '_>) -> Option<String> {
+    if node.children().any(|child| child.is_element()) {
+        return None;
+    }
+    let text = node
+        .children()
+        .filter_map(|child| child.text())
+        .collect::<String>();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn child_text(node: Node<'_, '_>, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.tag_name().name() == name)
+        .and_then(element_text)
+}
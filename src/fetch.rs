@@ -1,36 +1,489 @@
-use crate::config::{FetchMode, LoadedSource, PaginationStrategy, resolve_path};
+use crate::body::Body;
+use crate::charset::decode_body;
+#[cfg(feature = "http")]
+use crate::config::{AuthConfig, AuthMode, ExtractFormat, PaginationStop, PaginationStrategy};
+use crate::config::{FetchConfig, FetchMode, LoadedSource, OversizedBody, resolve_path};
+use crate::http_cache::HttpCache;
+use crate::model::ResourceValidators;
+#[cfg(feature = "http")]
+use crate::network::{check_request, check_url, restrict};
+#[cfg(feature = "http")]
+use crate::parser::parse_page_events;
+#[cfg(feature = "http")]
+use crate::pipeline::stable_uid;
+#[cfg(feature = "http")]
+use crate::robots::RobotsRules;
+#[cfg(feature = "http")]
+use crate::tenant::Tenant;
+#[cfg(feature = "http")]
+use crate::util::base64;
 use anyhow::{Context, Result, bail};
-use chrono::{Datelike, Utc};
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveDateTime, Utc};
 use chrono_tz::Tz;
 use glob::glob;
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
-use std::time::Duration;
+use regex::Regex;
+#[cfg(feature = "http")]
+use reqwest::StatusCode;
+#[cfg(feature = "http")]
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+#[cfg(feature = "http")]
+use reqwest::header::{
+    AUTHORIZATION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, FROM, HeaderMap, HeaderName,
+    HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LINK, RANGE, RETRY_AFTER,
+    USER_AGENT,
+};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+#[cfg(feature = "http")]
+use std::collections::HashSet;
+#[cfg(feature = "http")]
+use std::hash::{BuildHasher, RandomState};
+use std::io::Read;
+#[cfg(feature = "http")]
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::Ordering;
+#[cfg(feature = "http")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "http")]
+use tracing::Span;
 use tracing::{debug, info, warn};
+#[cfg(feature = "http")]
 use url::Url;
 
+/// Central User-Agent for sources without `[fetch.identity]`.
+pub const DEFAULT_USER_AGENT: &str = "rics/{{version}} (+https://github.com/sguzman/rics)";
+/// As [`DEFAULT_USER_AGENT`], used when an operator contact is known.
+pub const DEFAULT_USER_AGENT_WITH_CONTACT: &str =
+    "rics/{{version}} (+https://github.com/sguzman/rics; {{contact}})";
+/// Env var holding the operator contact sent with every request.
+pub const CONTACT_ENV: &str = "RICS_CONTACT_EMAIL";
+/// `fetch.proxy` value that bypasses the proxy environment variables.
+pub const NO_PROXY: &str = "none";
+
 #[derive(Debug, Clone)]
 pub struct FetchedDocument {
     pub source_url: String,
-    pub body: Vec<u8>,
+    pub body: Body,
     pub page_index: usize,
+    /// When the body was fetched; `None` for bodies supplied by the caller,
+    /// e.g. previews.
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Headers of the response that carried the body, by lowercase name;
+    /// empty for documents that were not fetched over http.
+    pub headers: BTreeMap<String, String>,
+}
+
+impl FetchedDocument {
+    /// The body as text, transcoded from its detected charset.
+    pub fn text(&self) -> Cow<'_, str> {
+        decode_body(
+            &self.body,
+            self.headers.get("content-type").map(String::as_str),
+        )
+    }
 }
 
 pub fn fetch_source_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
+    fetch_source_documents_with_cache(source, None)
+}
+
+/// As [`fetch_source_documents`], revalidating HTTP responses against
+/// `cache` unless the source sets `fetch.http_cache = false`.
+pub fn fetch_source_documents_with_cache(
+    source: &LoadedSource,
+    cache: Option<&HttpCache>,
+) -> Result<Vec<FetchedDocument>> {
+    #[cfg(not(feature = "http"))]
+    let _ = cache;
     match source.config.fetch.mode {
-        FetchMode::Http => fetch_http_documents(source),
+        #[cfg(feature = "http")]
+        FetchMode::Http => {
+            fetch_http_documents(source, cache.filter(|_| source.config.fetch.http_cache))
+        }
+        FetchMode::Replay => crate::recording::replay_documents(source),
+        #[cfg(not(feature = "http"))]
+        FetchMode::Http => bail!(
+            "source {} uses http fetch mode but rics was built without the `http` feature",
+            source.config.source.key
+        ),
         FetchMode::File => fetch_file_document(source),
         FetchMode::Inline => fetch_inline_document(source),
+        FetchMode::Ftp => crate::ftp::fetch_ftp_documents(source),
+        #[cfg(feature = "sftp")]
+        FetchMode::Sftp => crate::sftp::fetch_sftp_documents(source),
+        #[cfg(not(feature = "sftp"))]
+        FetchMode::Sftp => bail!(
+            "source {} uses sftp fetch mode but rics was built without the `sftp` feature",
+            source.config.source.key
+        ),
+        FetchMode::Schedule => Ok(Vec::new()),
+        #[cfg(feature = "imap")]
+        FetchMode::Imap => crate::imap::fetch_imap_documents(source),
+        #[cfg(not(feature = "imap"))]
+        FetchMode::Imap => bail!(
+            "source {} uses imap fetch mode but rics was built without the `imap` feature",
+            source.config.source.key
+        ),
+        #[cfg(feature = "browser")]
+        FetchMode::Browser => crate::browser::fetch_browser_documents(source),
+        #[cfg(not(feature = "browser"))]
+        FetchMode::Browser => bail!(
+            "source {} uses browser fetch mode but rics was built without the `browser` feature",
+            source.config.source.key
+        ),
+    }
+}
+
+/// Returns a client from the source tenant's pool keyed by default headers,
+/// timeout and proxy. Clients with the same settings share one connection
+/// pool, so keep-alive connections (and TLS sessions) are reused across
+/// paginated requests, sources and custom parsers hitting the same host;
+/// past `MAX_POOLED_CLIENTS` the least recently used is dropped. Requests
+/// go through the source's `fetch.proxy` when set, otherwise
+/// through `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY`. Clients of
+/// `api_key_header` sources refuse redirects to another origin, since
+/// reqwest forwards custom headers on every hop.
+#[cfg(feature = "http")]
+pub fn source_client(
+    source: &LoadedSource,
+    headers: &HeaderMap,
+    timeout: Option<Duration>,
+) -> Result<Client> {
+    pooled_client(
+        &source.tenant,
+        headers,
+        timeout,
+        source.config.fetch.proxy.as_deref(),
+        same_origin_redirects(source),
+    )
+}
+
+#[cfg(feature = "http")]
+fn same_origin_redirects(source: &LoadedSource) -> bool {
+    source
+        .config
+        .fetch
+        .auth
+        .as_ref()
+        .is_some_and(|auth| auth.mode == AuthMode::ApiKeyHeader)
+}
+
+/// Most clients a tenant pools. Header values rendered from date templates
+/// change from run to run, so a long-lived tenant would otherwise keep a
+/// client for every value it ever sent; the least recently used goes first.
+#[cfg(feature = "http")]
+const MAX_POOLED_CLIENTS: usize = 64;
+
+#[cfg(feature = "http")]
+fn pooled_client(
+    tenant: &Tenant,
+    headers: &HeaderMap,
+    timeout: Option<Duration>,
+    proxy: Option<&str>,
+    same_origin: bool,
+) -> Result<Client> {
+    let mut header_key = headers
+        .iter()
+        .map(|(name, value)| (name.as_str().to_string(), value.as_bytes().to_vec()))
+        .collect::<Vec<_>>();
+    header_key.sort();
+    let key = (header_key, timeout, proxy.map(str::to_string), same_origin);
+
+    let mut pool = tenant
+        .clients
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((client, used)) = pool.get_mut(&key) {
+        *used = Instant::now();
+        return Ok(client.clone());
     }
+
+    let mut builder = with_proxy(Client::builder().default_headers(headers.clone()), proxy)?;
+    builder = restrict(builder, &tenant.network(), same_origin);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    let client = builder.build().context("failed to build reqwest client")?;
+    if pool.len() >= MAX_POOLED_CLIENTS
+        && let Some(oldest) = pool
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(key, _)| key.clone())
+    {
+        pool.remove(&oldest);
+    }
+    pool.insert(key, (client.clone(), Instant::now()));
+    Ok(client)
 }
 
-fn fetch_http_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
+/// A client with its own cookie jar, for sources whose requests must share
+/// a session. Not pooled, so cookies never leak between sources or runs.
+#[cfg(feature = "http")]
+fn session_client(
+    source: &LoadedSource,
+    headers: &HeaderMap,
+    timeout: Option<Duration>,
+) -> Result<Client> {
+    let mut builder = Client::builder()
+        .default_headers(headers.clone())
+        .cookie_store(true);
+    builder = with_proxy(builder, source.config.fetch.proxy.as_deref())?;
+    builder = restrict(
+        builder,
+        &source.tenant.network(),
+        same_origin_redirects(source),
+    );
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    builder.build().context("failed to build reqwest client")
+}
+
+/// Routes every request through `proxy`; `"none"` also ignores the proxy
+/// environment variables, and `None` leaves them in charge.
+#[cfg(feature = "http")]
+fn with_proxy(builder: ClientBuilder, proxy: Option<&str>) -> Result<ClientBuilder> {
+    Ok(match proxy {
+        None => builder,
+        Some(NO_PROXY) => builder.no_proxy(),
+        Some(proxy) => builder.proxy(
+            reqwest::Proxy::all(proxy).with_context(|| format!("invalid fetch.proxy {proxy}"))?,
+        ),
+    })
+}
+
+/// Blocks until a request to `url`'s host fits within the source's
+/// `fetch.rate_limit`. Each call reserves the next free slot for its host,
+/// so concurrent sources of a tenant and successive pages share one budget
+/// per host.
+#[cfg(feature = "http")]
+pub(crate) fn throttle(source: &LoadedSource, url: &str) {
+    let per_minute = source.config.fetch.rate_limit;
+    let Some(per_minute) = per_minute.filter(|limit| *limit > 0) else {
+        return;
+    };
+    let Some(host) = Url::parse(url).ok().and_then(|url| {
+        url.host_str()
+            .map(|host| format!("{host}:{}", url.port_or_known_default().unwrap_or(0)))
+    }) else {
+        return;
+    };
+    let interval = Duration::from_secs(60) / per_minute;
+    let now = Instant::now();
+    let slot = {
+        let mut slots = source
+            .tenant
+            .slots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let slot = slots.get(&host).copied().unwrap_or(now).max(now);
+        slots.insert(host.clone(), slot + interval);
+        slot
+    };
+    if slot > now {
+        debug!(%host, wait_ms = (slot - now).as_millis() as u64, "rate limited");
+        std::thread::sleep(slot - now);
+    }
+}
+
+/// How long a host's robots.txt is reused before it is fetched again.
+#[cfg(feature = "http")]
+const ROBOTS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The robots.txt rule that disallows `url` when the source sets
+/// `fetch.respect_robots_txt`. Each host's robots.txt is fetched once per
+/// [`ROBOTS_TTL`] and shared by every source of the tenant. A missing
+/// robots.txt (4xx) allows everything; one that cannot be fetched (5xx,
+/// network error) disallows everything, as RFC 9309 asks.
+#[cfg(feature = "http")]
+pub(crate) fn robots_disallows(
+    client: &Client,
+    headers: &HeaderMap,
+    source: &LoadedSource,
+    url: &str,
+) -> Option<String> {
+    if !source.config.fetch.respect_robots_txt {
+        return None;
+    }
+    let parsed = Url::parse(url).ok()?;
+    let origin = parsed.origin().ascii_serialization();
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("rics")
+        .to_string();
+    let key = (origin.clone(), user_agent.clone());
+    let cached = source
+        .tenant
+        .robots
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(&key)
+        .filter(|(fetched, _)| fetched.elapsed() < ROBOTS_TTL)
+        .map(|(_, rules)| Arc::clone(rules));
+    let rules = match cached {
+        Some(rules) => rules,
+        None => {
+            let robots_url = format!("{origin}/robots.txt");
+            // The page fetch itself reports the refusal.
+            if check_request(source, &robots_url).is_err() {
+                return None;
+            }
+            throttle(source, &robots_url);
+            let rules = match client.get(&robots_url).send() {
+                Ok(resp) if resp.status().is_success() => {
+                    RobotsRules::parse(&resp.text().unwrap_or_default(), &user_agent)
+                }
+                Ok(resp) if resp.status().is_client_error() => RobotsRules::allow_all(),
+                Ok(resp) => {
+                    warn!(url = %robots_url, status = %resp.status(), "robots.txt unavailable; treating host as disallowed");
+                    RobotsRules::disallow_all()
+                }
+                Err(err) => {
+                    warn!(url = %robots_url, error = %err, "robots.txt unavailable; treating host as disallowed");
+                    RobotsRules::disallow_all()
+                }
+            };
+            let rules = Arc::new(rules);
+            source
+                .tenant
+                .robots
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(key, (Instant::now(), Arc::clone(&rules)));
+            rules
+        }
+    };
+
+    let path = match parsed.query() {
+        Some(query) => format!("{}?{query}", parsed.path()),
+        None => parsed.path().to_string(),
+    };
+    let disallowed = rules.check(&path).err()?;
+    warn!(
+        source = %source.config.source.key,
+        %url,
+        rule = %disallowed.rule,
+        "robots.txt disallows url; skipping"
+    );
+    Some(disallowed.rule)
+}
+
+/// Probes a single-document HTTP source with HEAD (falling back to a
+/// one-byte ranged GET) when `fetch.preflight` is set, returning the
+/// validators to compare against the previous run. `None` means the source
+/// does not use preflight, fetches several pages or periods, needs a
+/// cookie session, or the server gave nothing usable, so the caller should
+/// fetch normally.
+pub fn preflight_source(source: &LoadedSource) -> Option<ResourceValidators> {
+    if !source.config.fetch.preflight
+        || source.config.fetch.mode != FetchMode::Http
+        || source.config.pagination.enabled
+        || source.config.fetch.iterate.is_some()
+        || source.config.fetch.uses_session()
+    {
+        return None;
+    }
+
+    #[cfg(feature = "http")]
+    {
+        match probe_http_resource(source) {
+            Ok(validators) => validators.filter(|v| !v.is_empty()),
+            Err(err) => {
+                warn!(
+                    source = %source.config.source.key,
+                    error = %err,
+                    "preflight failed; fetching in full"
+                );
+                None
+            }
+        }
+    }
+    #[cfg(not(feature = "http"))]
+    {
+        None
+    }
+}
+
+#[cfg(feature = "http")]
+fn probe_http_resource(source: &LoadedSource) -> Result<Option<ResourceValidators>> {
     let substitutions = template_substitutions(source);
+    let headers = request_headers(source, &substitutions)?;
+    let client = source_client(
+        source,
+        &headers,
+        Some(Duration::from_secs(source.config.fetch.timeout_secs)),
+    )?;
+    let url = source
+        .config
+        .fetch
+        .base_url
+        .as_ref()
+        .context("fetch.base_url missing")?;
+    let url = apply_templates(url, &substitutions);
+    if robots_disallows(&client, &headers, source, &url).is_some() {
+        return Ok(None);
+    }
+    let auth = match SourceAuth::new(source, &headers)? {
+        Some(auth) => auth.header_for(source, &url, None)?,
+        None => None,
+    };
+    let with_auth = |request: RequestBuilder| match &auth {
+        Some((name, value)) => request.header(name, value),
+        None => request,
+    };
+
+    check_request(source, &url)?;
+    throttle(source, &url);
+    let head = with_auth(client.head(&url)).send()?;
+    if head.status().is_success() {
+        return Ok(Some(validators_from_headers(head.headers(), None)));
+    }
+
+    debug!(status = %head.status(), url = %url, "HEAD not supported; trying ranged GET");
+    throttle(source, &url);
+    let ranged = with_auth(client.get(&url))
+        .header(RANGE, "bytes=0-0")
+        .send()?;
+    if ranged.status() == StatusCode::PARTIAL_CONTENT {
+        let total = ranged
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit('/').next())
+            .and_then(|v| v.parse::<u64>().ok());
+        return Ok(Some(validators_from_headers(ranged.headers(), total)));
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "http")]
+fn validators_from_headers(headers: &HeaderMap, content_length: Option<u64>) -> ResourceValidators {
+    let text = |name| {
+        headers
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+            .map(str::to_string)
+    };
+    ResourceValidators {
+        content_length: content_length
+            .or_else(|| text(CONTENT_LENGTH).and_then(|v| v.parse::<u64>().ok())),
+        last_modified: text(LAST_MODIFIED),
+        etag: text(ETAG),
+    }
+}
+
+#[cfg(feature = "http")]
+pub(crate) fn request_headers(
+    source: &LoadedSource,
+    substitutions: &[(String, String)],
+) -> Result<HeaderMap> {
     let mut headers = HeaderMap::new();
     for (k, v) in &source.config.fetch.headers {
         let name = HeaderName::from_bytes(k.as_bytes())
             .with_context(|| format!("invalid header name {k}"))?;
-        let rendered = apply_templates(v, &substitutions);
+        let rendered = apply_templates(v, substitutions);
         let value = HeaderValue::from_str(&rendered)
             .with_context(|| format!("invalid header value for {k}"))?;
         headers.insert(name, value);
@@ -38,94 +491,746 @@ fn fetch_http_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
 
     ensure_default_headers(&mut headers);
 
-    if let Some(user_agent) = &source.config.fetch.user_agent {
-        let rendered = apply_templates(user_agent, &substitutions);
-        headers.insert(USER_AGENT, HeaderValue::from_str(&rendered)?);
+    // Computed once: each call advances the User-Agent rotation.
+    for (name, value) in &identity_headers(source, substitutions)? {
+        if !headers.contains_key(name) {
+            headers.insert(name, value.clone());
+        }
+    }
+
+    Ok(headers)
+}
+
+/// The `[fetch.auth]` credential of a source. It is added to each request
+/// rather than to the pooled client, and only to requests for the origin of
+/// `fetch.base_url`, so robots.txt, other hosts and redirect targets never
+/// see it.
+#[cfg(feature = "http")]
+pub(crate) struct SourceAuth {
+    origin: url::Origin,
+    credential: Credential,
+}
+
+#[cfg(feature = "http")]
+enum Credential {
+    Header(HeaderName, HeaderValue),
+    /// Client-credentials grant; tokens are requested with `token_client`
+    /// and cached in the source's tenant.
+    Oauth2 {
+        token_client: Client,
+        client_id: String,
+        secret: String,
+    },
+}
+
+#[cfg(feature = "http")]
+impl SourceAuth {
+    /// Reads the source's secrets from the environment. `headers` are the
+    /// source's request headers; the OAuth2 token request sends only their
+    /// identity headers.
+    pub(crate) fn new(source: &LoadedSource, headers: &HeaderMap) -> Result<Option<Self>> {
+        let fetch = &source.config.fetch;
+        let (Some(auth), Some(base_url)) = (&fetch.auth, &fetch.base_url) else {
+            return Ok(None);
+        };
+        let base_url = apply_templates(base_url, &template_substitutions(source));
+        let origin = Url::parse(&base_url)
+            .with_context(|| format!("invalid fetch.base_url {base_url}"))?
+            .origin();
+        let credential = Self::credential(source, auth, headers)
+            .with_context(|| format!("fetch.auth for source {}", source.config.source.key))?;
+        Ok(Some(Self { origin, credential }))
+    }
+
+    fn credential(
+        source: &LoadedSource,
+        auth: &AuthConfig,
+        headers: &HeaderMap,
+    ) -> Result<Credential> {
+        let env =
+            |var: &str| std::env::var(var).with_context(|| format!("env var {var} is not set"));
+        let secret = env(&auth.secret_env)?;
+        let (name, value) = match auth.mode {
+            AuthMode::Basic => {
+                let username = match &auth.username_env {
+                    Some(var) => env(var)?,
+                    None => auth.username.clone().unwrap_or_default(),
+                };
+                let credentials = base64(format!("{username}:{secret}").as_bytes());
+                (AUTHORIZATION, format!("Basic {credentials}"))
+            }
+            AuthMode::Bearer => (AUTHORIZATION, format!("Bearer {secret}")),
+            AuthMode::ApiKeyHeader => {
+                let header = auth.header.as_deref().unwrap_or_default();
+                let name = HeaderName::from_bytes(header.as_bytes())
+                    .with_context(|| format!("invalid fetch.auth.header {header}"))?;
+                (name, secret)
+            }
+            AuthMode::Oauth2ClientCredentials => {
+                let client_id = match &auth.client_id_env {
+                    Some(var) => env(var)?,
+                    None => auth.client_id.clone().unwrap_or_default(),
+                };
+                let identity = headers
+                    .iter()
+                    .filter(|(name, _)| {
+                        [USER_AGENT.as_str(), FROM.as_str(), "x-contact"].contains(&name.as_str())
+                    })
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect::<HeaderMap>();
+                let timeout = Some(Duration::from_secs(source.config.fetch.timeout_secs));
+                return Ok(Credential::Oauth2 {
+                    token_client: source_client(source, &identity, timeout)?,
+                    client_id,
+                    secret,
+                });
+            }
+        };
+        Ok(Credential::Header(name, sensitive(&value)?))
+    }
+
+    /// Whether a rejected header can be replaced by a fresh one.
+    pub(crate) fn refreshable(&self) -> bool {
+        matches!(self.credential, Credential::Oauth2 { .. })
+    }
+
+    /// The header to send with a request for `url`; `None` when `url` is
+    /// on another origin than the source. An OAuth2 token in `rejected` is
+    /// replaced by a new one.
+    pub(crate) fn header_for(
+        &self,
+        source: &LoadedSource,
+        url: &str,
+        rejected: Option<&HeaderValue>,
+    ) -> Result<Option<(HeaderName, HeaderValue)>> {
+        if Url::parse(url).map(|url| url.origin()).ok().as_ref() != Some(&self.origin) {
+            return Ok(None);
+        }
+        let header = match &self.credential {
+            Credential::Header(name, value) => (name.clone(), value.clone()),
+            Credential::Oauth2 {
+                token_client,
+                client_id,
+                secret,
+            } => {
+                let auth = source
+                    .config
+                    .fetch
+                    .auth
+                    .as_ref()
+                    .context("fetch.auth missing")?;
+                let rejected = rejected
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "));
+                let token = oauth2_token(
+                    &source.tenant,
+                    token_client,
+                    auth,
+                    client_id,
+                    secret,
+                    rejected,
+                )
+                .with_context(|| format!("fetch.auth for source {}", source.config.source.key))?;
+                (AUTHORIZATION, sensitive(&format!("Bearer {token}"))?)
+            }
+        };
+        Ok(Some(header))
+    }
+}
+
+/// `value` marked sensitive, which keeps it out of Debug output and out of
+/// HPACK tables.
+#[cfg(feature = "http")]
+fn sensitive(value: &str) -> Result<HeaderValue> {
+    let mut value =
+        HeaderValue::from_str(value).context("credential is not a valid header value")?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Tokens are refreshed this long before they expire, so one is never sent
+/// just as it lapses.
+#[cfg(feature = "http")]
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// An access token for the client-credentials grant, cached per token
+/// endpoint, client and scope so sources of a tenant sharing an issuer
+/// share a token. A cached token equal to `rejected` is replaced. While one
+/// source requests a token, the others of that issuer wait for it; no lock
+/// is held during the request.
+#[cfg(feature = "http")]
+fn oauth2_token(
+    tenant: &Tenant,
+    client: &Client,
+    auth: &AuthConfig,
+    client_id: &str,
+    secret: &str,
+    rejected: Option<&str>,
+) -> Result<String> {
+    let token_url = auth.token_url.as_deref().unwrap_or_default();
+    let key = (
+        token_url.to_string(),
+        client_id.to_string(),
+        auth.scope.clone(),
+    );
+    let slot = Arc::clone(
+        tenant
+            .tokens
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(key)
+            .or_default(),
+    );
+
+    let mut state = slot
+        .state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    loop {
+        if let Some((token, expires)) = &state.token
+            && Instant::now() + TOKEN_REFRESH_MARGIN < *expires
+            && rejected != Some(token.as_str())
+        {
+            return Ok(token.clone());
+        }
+        if !state.refreshing {
+            break;
+        }
+        state = slot
+            .ready
+            .wait(state)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
     }
+    state.refreshing = true;
+    drop(state);
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(source.config.fetch.timeout_secs))
-        .default_headers(headers)
-        .build()
-        .context("failed to build reqwest client")?;
+    let requested = request_oauth2_token(tenant, client, auth, client_id, secret);
+    let mut state = slot
+        .state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    state.refreshing = false;
+    if let Ok(token) = &requested {
+        state.token = Some(token.clone());
+    }
+    slot.ready.notify_all();
+    requested.map(|(token, _)| token)
+}
 
-    let base_url = source
+/// Requests a token from `auth.token_url`, returning it with its expiry.
+#[cfg(feature = "http")]
+fn request_oauth2_token(
+    tenant: &Tenant,
+    client: &Client,
+    auth: &AuthConfig,
+    client_id: &str,
+    secret: &str,
+) -> Result<(String, Instant)> {
+    #[derive(serde::Deserialize)]
+    struct TokenResponse {
+        access_token: String,
+        expires_in: Option<u64>,
+    }
+
+    let token_url = auth.token_url.as_deref().unwrap_or_default();
+    let mut form = url::form_urlencoded::Serializer::new(String::new());
+    form.append_pair("grant_type", "client_credentials");
+    if let Some(scope) = &auth.scope {
+        form.append_pair("scope", scope);
+    }
+    // RFC 6749 2.3.1: the client authenticates with HTTP Basic, its ID and
+    // secret form-encoded first.
+    let encode =
+        |value: &str| url::form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+    let credentials = base64(format!("{}:{}", encode(client_id), encode(secret)).as_bytes());
+    check_url(&tenant.network(), token_url)?;
+    let requested = Instant::now();
+    let response = client
+        .post(token_url)
+        .header(AUTHORIZATION, format!("Basic {credentials}"))
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(form.finish())
+        .send()
+        .with_context(|| format!("failed to request an OAuth2 token from {token_url}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("OAuth2 token endpoint {token_url} returned HTTP {status}");
+    }
+    let body: TokenResponse = response
+        .json()
+        .with_context(|| format!("invalid OAuth2 token response from {token_url}"))?;
+    let lifetime = Duration::from_secs(body.expires_in.unwrap_or(3600));
+    debug!(
+        token_url,
+        client_id,
+        expires_in_secs = lifetime.as_secs(),
+        "fetched OAuth2 access token"
+    );
+    Ok((body.access_token, requested + lifetime))
+}
+
+/// User-Agent plus `From`/`X-Contact` per the source's identity policy. Used
+/// on their own by the API-backed custom parsers.
+#[cfg(feature = "http")]
+pub(crate) fn identity_headers(
+    source: &LoadedSource,
+    substitutions: &[(String, String)],
+) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    let user_agent = user_agent_for(source, substitutions);
+    headers.insert(
+        USER_AGENT,
+        HeaderValue::from_str(&user_agent)
+            .with_context(|| format!("invalid user agent {user_agent}"))?,
+    );
+
+    if source.config.fetch.identity.contact_headers
+        && let Some(contact) = contact_for(source)
+    {
+        let value = HeaderValue::from_str(&contact)
+            .with_context(|| format!("invalid contact {contact}"))?;
+        headers.insert(FROM, value.clone());
+        headers.insert(HeaderName::from_static("x-contact"), value);
+    }
+
+    Ok(headers)
+}
+
+/// Operator contact: `fetch.identity.contact`, else `RICS_CONTACT_EMAIL`.
+pub fn contact_for(source: &LoadedSource) -> Option<String> {
+    source
+        .config
+        .fetch
+        .identity
+        .contact
+        .clone()
+        .or_else(|| std::env::var(CONTACT_ENV).ok())
+        .map(|contact| contact.trim().to_string())
+        .filter(|contact| !contact.is_empty())
+}
+
+/// Resolves the User-Agent for one fetch run: the next entry of
+/// `identity.rotate`, else `identity.user_agent` (or the legacy
+/// `fetch.user_agent`), else the central default.
+pub fn user_agent_for(source: &LoadedSource, substitutions: &[(String, String)]) -> String {
+    let fetch = &source.config.fetch;
+    let template = if !fetch.identity.rotate.is_empty() {
+        let next = source.tenant.rotation.fetch_add(1, Ordering::Relaxed);
+        fetch.identity.rotate[next % fetch.identity.rotate.len()].as_str()
+    } else if let Some(user_agent) = fetch.identity.user_agent.as_deref() {
+        user_agent
+    } else if let Some(user_agent) = fetch.user_agent.as_deref() {
+        user_agent
+    } else if contact_for(source).is_some() {
+        DEFAULT_USER_AGENT_WITH_CONTACT
+    } else {
+        DEFAULT_USER_AGENT
+    };
+    apply_templates(template, substitutions)
+}
+
+/// Why `page` is past the last page under `pagination.stop_on`, if it is.
+/// The UIDs of pages that do not stop pagination are added to `seen_uids`.
+#[cfg(feature = "http")]
+fn pagination_stop_reason(
+    source: &LoadedSource,
+    page: &FetchedDocument,
+    seen_uids: &mut HashSet<String>,
+) -> Result<Option<&'static str>> {
+    let pagination = &source.config.pagination;
+    if !pagination.stop_when_no_results {
+        return Ok(None);
+    }
+    if page.body.is_empty() {
+        return Ok(Some("response is empty"));
+    }
+    if pagination.stop_on == PaginationStop::EmptyResponse {
+        return Ok(None);
+    }
+    let events = parse_page_events(source, page)
+        .with_context(|| format!("failed to parse page {}", page.source_url))?;
+    if events.is_empty() {
+        return Ok(Some("page has no records"));
+    }
+    if pagination.stop_on == PaginationStop::NoNewRecords {
+        let mut new = false;
+        for event in &events {
+            new |= seen_uids.insert(stable_uid(event, &source.config.uid));
+        }
+        if !new {
+            return Ok(Some("page has no new records"));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "http")]
+fn fetch_http_documents(
+    source: &LoadedSource,
+    cache: Option<&HttpCache>,
+) -> Result<Vec<FetchedDocument>> {
+    let substitutions = template_substitutions(source);
+    let headers = request_headers(source, &substitutions)?;
+    let auth = SourceAuth::new(source, &headers)?;
+
+    let timeout = Some(Duration::from_secs(source.config.fetch.timeout_secs));
+    let client = if source.config.fetch.uses_session() {
+        session_client(source, &headers, timeout)?
+    } else {
+        source_client(source, &headers, timeout)?
+    };
+
+    for url in &source.config.fetch.prefetch_urls {
+        let url = apply_templates(url, &substitutions);
+        if robots_disallows(&client, &headers, source, &url).is_some() {
+            continue;
+        }
+        fetch_with_retries(&client, "GET", &url, source, auth.as_ref(), None)
+            .with_context(|| format!("prefetch of {url} failed"))?;
+        debug!(source = %source.config.source.key, %url, "prefetched");
+    }
+
+    let raw_base_url = source
         .config
         .fetch
         .base_url
         .as_ref()
         .context("fetch.base_url missing")?;
-    let base_url = apply_templates(base_url, &substitutions);
-
-    if source.config.pagination.enabled
-        && source.config.pagination.strategy == PaginationStrategy::NextLink
-    {
-        warn!(
-            source = %source.config.source.key,
-            "next_link pagination is declared but not fully implemented; using query-param style fallback"
-        );
-    }
+    let base_url = apply_templates(raw_base_url, &substitutions);
 
     let mut docs = Vec::new();
+    let mut disallowed = 0usize;
 
-    if source.config.pagination.enabled {
-        let start = source.config.pagination.start_page;
-        let end = start + source.config.pagination.max_pages;
-        for (index, page) in (start..end).enumerate() {
-            let page_url = build_paged_url(
-                &base_url,
-                &source.config.pagination.page_param,
-                page.to_string().as_str(),
-            )?;
-            let bytes = fetch_with_retries(
+    if source.config.pagination.enabled
+        && source.config.pagination.strategy != PaginationStrategy::QueryParam
+    {
+        let mut page_url = base_url.clone();
+        let mut visited = HashSet::new();
+        let mut seen_uids = HashSet::new();
+        for index in 0..source.config.pagination.max_pages {
+            if !visited.insert(page_url.clone()) {
+                warn!(
+                    source = %source.config.source.key,
+                    url = %page_url,
+                    "stopping pagination because the next page loops back"
+                );
+                break;
+            }
+            if robots_disallows(&client, &headers, source, &page_url).is_some() {
+                disallowed += 1;
+                break;
+            }
+            let page = fetch_with_retries(
                 &client,
                 &source.config.fetch.method,
                 &page_url,
-                source.config.fetch.retry_attempts,
-                source.config.fetch.retry_backoff_ms,
+                source,
+                auth.as_ref(),
+                cache,
             )?;
-
-            if bytes.is_empty() && source.config.pagination.stop_when_no_results {
+            let doc = FetchedDocument {
+                source_url: page_url,
+                body: page.body,
+                page_index: index,
+                fetched_at: Some(Utc::now()),
+                headers: page.headers,
+            };
+            if let Some(reason) = pagination_stop_reason(source, &doc, &mut seen_uids)? {
                 info!(
                     source = %source.config.source.key,
-                    page,
-                    "stopping pagination because response is empty"
+                    page = index,
+                    "stopping pagination because {reason}"
                 );
                 break;
             }
-
             info!(
                 source = %source.config.source.key,
-                page,
-                bytes = bytes.len(),
-                url = %page_url,
+                page = index,
+                bytes = doc.body.len(),
+                url = %doc.source_url,
                 "fetched page"
             );
 
+            let next = match source.config.pagination.strategy {
+                PaginationStrategy::Cursor => cursor_page_url(source, &base_url, &doc)?,
+                _ => next_page_url(source, &doc, page.next_link)?,
+            };
+            docs.push(doc);
+            match next {
+                Some(next) => page_url = next,
+                None => break,
+            }
+            if index + 1 == source.config.pagination.max_pages {
+                info!(
+                    source = %source.config.source.key,
+                    max_pages = source.config.pagination.max_pages,
+                    "stopping pagination at max_pages"
+                );
+            }
+        }
+    } else if source.config.pagination.enabled {
+        let pagination = &source.config.pagination;
+        let pages = (pagination.start_page..pagination.start_page + pagination.max_pages)
+            .enumerate()
+            .collect::<Vec<_>>();
+        let mut seen_uids = HashSet::new();
+        // Pages are fetched `concurrency` at a time but checked in order, so
+        // a stop on one page discards the rest of its batch.
+        'batches: for batch in pages.chunks(pagination.concurrency.max(1)) {
+            let mut page_urls = Vec::with_capacity(batch.len());
+            for &(index, page) in batch {
+                let page_url =
+                    build_paged_url(&base_url, &pagination.page_param, page.to_string().as_str())?;
+                if robots_disallows(&client, &headers, source, &page_url).is_some() {
+                    disallowed += 1;
+                    continue;
+                }
+                page_urls.push((index, page, page_url));
+            }
+            let span = Span::current();
+            let fetched = std::thread::scope(|scope| {
+                let handles = page_urls
+                    .iter()
+                    .map(|(_, _, page_url)| {
+                        let span = span.clone();
+                        let client = &client;
+                        let auth = auth.as_ref();
+                        scope.spawn(move || {
+                            span.in_scope(|| {
+                                fetch_with_retries(
+                                    client,
+                                    &source.config.fetch.method,
+                                    page_url,
+                                    source,
+                                    auth,
+                                    cache,
+                                )
+                            })
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            for ((index, page, page_url), fetched) in page_urls.into_iter().zip(fetched) {
+                let fetched = fetched?;
+                let doc = FetchedDocument {
+                    source_url: page_url,
+                    body: fetched.body,
+                    page_index: index,
+                    fetched_at: Some(Utc::now()),
+                    headers: fetched.headers,
+                };
+
+                if let Some(reason) = pagination_stop_reason(source, &doc, &mut seen_uids)? {
+                    info!(
+                        source = %source.config.source.key,
+                        page,
+                        "stopping pagination because {reason}"
+                    );
+                    break 'batches;
+                }
+
+                info!(
+                    source = %source.config.source.key,
+                    page,
+                    bytes = doc.body.len(),
+                    url = %doc.source_url,
+                    "fetched page"
+                );
+
+                docs.push(doc);
+            }
+        }
+    } else if let Some(iterate) = &source.config.fetch.iterate {
+        let mut seen = HashSet::new();
+        for period in iterate.periods(source_now(source).date()) {
+            let substitutions =
+                template_substitutions_at(source, period.and_time(chrono::NaiveTime::MIN));
+            let url = apply_templates(raw_base_url, &substitutions);
+            if !seen.insert(url.clone()) {
+                continue;
+            }
+            if robots_disallows(&client, &headers, source, &url).is_some() {
+                disallowed += 1;
+                continue;
+            }
+            let fetched = fetch_with_retries(
+                &client,
+                &source.config.fetch.method,
+                &url,
+                source,
+                auth.as_ref(),
+                cache,
+            )?;
+            debug!(source = %source.config.source.key, %url, %period, "fetched period");
             docs.push(FetchedDocument {
-                source_url: page_url,
-                body: bytes,
-                page_index: index,
+                source_url: url,
+                body: fetched.body,
+                page_index: docs.len(),
+                fetched_at: Some(Utc::now()),
+                headers: fetched.headers,
             });
         }
+    } else if robots_disallows(&client, &headers, source, &base_url).is_some() {
+        disallowed += 1;
     } else {
-        let bytes = fetch_with_retries(
+        let fetched = fetch_with_retries(
             &client,
             &source.config.fetch.method,
             &base_url,
-            source.config.fetch.retry_attempts,
-            source.config.fetch.retry_backoff_ms,
+            source,
+            auth.as_ref(),
+            cache,
         )?;
         docs.push(FetchedDocument {
             source_url: base_url,
-            body: bytes,
+            body: fetched.body,
             page_index: 0,
+            fetched_at: Some(Utc::now()),
+            headers: fetched.headers,
         });
     }
 
+    // Merging nothing would cancel every upcoming event of the source, so a
+    // source robots.txt shuts out entirely fails instead.
+    if docs.is_empty() && disallowed > 0 {
+        bail!(
+            "robots.txt disallows every url of source {}",
+            source.config.source.key
+        );
+    }
     Ok(docs)
 }
 
+/// A fetched response body, its headers and its `Link: <...>; rel="next"`
+/// target.
+#[cfg(feature = "http")]
+struct FetchedPage {
+    body: Body,
+    headers: BTreeMap<String, String>,
+    next_link: Option<String>,
+}
+
+/// The page after `page` for `strategy = "next_link"`, resolved against its
+/// URL: the first match of `pagination.next_selector` (CSS with an
+/// optional `@attr`, default `href`, for html; a JSON pointer or path for
+/// json), or the `Link` header's `rel="next"` (`next_link`) when no selector
+/// is set.
+#[cfg(feature = "http")]
+fn next_page_url(
+    source: &LoadedSource,
+    page: &FetchedDocument,
+    next_link: Option<String>,
+) -> Result<Option<String>> {
+    let page_url = page.source_url.as_str();
+    let next = match source.config.pagination.next_selector.as_deref() {
+        None => next_link,
+        Some(selector) => match source.config.extract.format {
+            ExtractFormat::Html => {
+                let (css, attr) = selector.rsplit_once('@').unwrap_or((selector, "href"));
+                let parsed = scraper::Selector::parse(css).map_err(|err| {
+                    anyhow::anyhow!("invalid pagination.next_selector {css}: {err}")
+                })?;
+                let html = scraper::Html::parse_document(&page.text());
+                html.select(&parsed)
+                    .find_map(|node| node.value().attr(attr))
+                    .map(str::to_string)
+            }
+            ExtractFormat::Json => {
+                let payload: serde_json::Value = serde_json::from_str(&page.text())
+                    .with_context(|| format!("failed to parse json page {page_url}"))?;
+                crate::parser::select_json_nodes(&payload, Some(selector))
+                    .into_iter()
+                    .find_map(|node| node.as_str())
+                    .map(str::to_string)
+            }
+            ExtractFormat::PdfText | ExtractFormat::Text | ExtractFormat::Feed => None,
+        },
+    };
+    let Some(next) = next
+        .map(|next| next.trim().to_string())
+        .filter(|n| !n.is_empty())
+    else {
+        return Ok(None);
+    };
+    let base = Url::parse(page_url).with_context(|| format!("invalid page url {page_url}"))?;
+    let next = base
+        .join(&next)
+        .with_context(|| format!("invalid next link {next} on {page_url}"))?;
+    Ok(Some(next.to_string()))
+}
+
+/// The page after `page` for `strategy = "cursor"`: `base_url` with the
+/// token at `pagination.cursor_jsonpath` in `cursor_param`.
+#[cfg(feature = "http")]
+fn cursor_page_url(
+    source: &LoadedSource,
+    base_url: &str,
+    page: &FetchedDocument,
+) -> Result<Option<String>> {
+    let pagination = &source.config.pagination;
+    let payload: serde_json::Value = serde_json::from_str(&page.text())
+        .with_context(|| format!("failed to parse json page {}", page.source_url))?;
+    let cursor = crate::parser::select_json_nodes(&payload, pagination.cursor_jsonpath.as_deref())
+        .into_iter()
+        .find_map(|node| match node {
+            serde_json::Value::String(token) => Some(token.clone()),
+            serde_json::Value::Number(token) => Some(token.to_string()),
+            _ => None,
+        })
+        .filter(|token| !token.is_empty());
+    cursor
+        .map(|token| build_paged_url(base_url, &pagination.cursor_param, &token))
+        .transpose()
+}
+
+/// Headers by lowercase name; repeated headers are joined with `, `.
+#[cfg(feature = "http")]
+fn header_map(headers: &HeaderMap) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::<String, String>::new();
+    for (name, value) in headers {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        map.entry(name.as_str().to_string())
+            .and_modify(|joined| {
+                joined.push_str(", ");
+                joined.push_str(value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+    map
+}
+
+/// The `rel="next"` target of a `Link` header (RFC 8288).
+#[cfg(feature = "http")]
+fn link_rel_next(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        let is_next = params.split(';').any(|param| {
+            param.split_once('=').is_some_and(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("rel")
+                    && value
+                        .trim()
+                        .trim_matches('"')
+                        .split_whitespace()
+                        .any(|rel| rel.eq_ignore_ascii_case("next"))
+            })
+        });
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        is_next.then(|| target.to_string())
+    })
+}
+
+#[cfg(feature = "http")]
 fn ensure_default_headers(headers: &mut HeaderMap) {
     insert_if_missing(
         headers,
@@ -139,6 +1244,7 @@ fn ensure_default_headers(headers: &mut HeaderMap) {
     insert_if_missing(headers, "upgrade-insecure-requests", "1");
 }
 
+#[cfg(feature = "http")]
 fn insert_if_missing(headers: &mut HeaderMap, name: &'static str, value: &'static str) {
     let header_name = HeaderName::from_static(name);
     if headers.contains_key(&header_name) {
@@ -147,15 +1253,27 @@ fn insert_if_missing(headers: &mut HeaderMap, name: &'static str, value: &'stati
     headers.insert(header_name, HeaderValue::from_static(value));
 }
 
-fn template_substitutions(source: &LoadedSource) -> Vec<(String, String)> {
+pub(crate) fn template_substitutions(source: &LoadedSource) -> Vec<(String, String)> {
+    template_substitutions_at(source, source_now(source))
+}
+
+/// The current wall-clock time in `source.timezone`, or UTC.
+fn source_now(source: &LoadedSource) -> NaiveDateTime {
     let now_utc = Utc::now();
-    let now_local = if let Some(tz_name) = source.config.source.timezone.as_deref()
+    if let Some(tz_name) = source.config.source.timezone.as_deref()
         && let Ok(tz) = tz_name.parse::<Tz>()
     {
         now_utc.with_timezone(&tz).naive_local()
     } else {
         now_utc.naive_utc()
-    };
+    }
+}
+
+/// Substitutions with the date placeholders filled from `now_local`.
+fn template_substitutions_at(
+    source: &LoadedSource,
+    now_local: NaiveDateTime,
+) -> Vec<(String, String)> {
     let year = now_local.year();
 
     let mut values = vec![
@@ -166,6 +1284,19 @@ fn template_substitutions(source: &LoadedSource) -> Vec<(String, String)> {
             "{{today_ymd}}".to_string(),
             now_local.date().format("%Y-%m-%d").to_string(),
         ),
+        (
+            "{{today}}".to_string(),
+            now_local.date().format("%Y-%m-%d").to_string(),
+        ),
+        ("{{month}}".to_string(), now_local.format("%m").to_string()),
+        (
+            "{{year_month}}".to_string(),
+            now_local.format("%Y-%m").to_string(),
+        ),
+        (
+            "{{iso_week}}".to_string(),
+            now_local.format("%V").to_string(),
+        ),
         (
             "{{current_month_short}}".to_string(),
             now_local.format("%b").to_string().to_ascii_lowercase(),
@@ -188,6 +1319,14 @@ fn template_substitutions(source: &LoadedSource) -> Vec<(String, String)> {
         ));
     }
 
+    values.push((
+        "{{version}}".to_string(),
+        env!("CARGO_PKG_VERSION").to_string(),
+    ));
+    if let Some(contact) = contact_for(source) {
+        values.push(("{{contact}}".to_string(), contact));
+    }
+
     for (key, value) in &source.config.fetch.template_vars {
         values.push((format!("{{{{{key}}}}}"), value.clone()));
     }
@@ -195,56 +1334,320 @@ fn template_substitutions(source: &LoadedSource) -> Vec<(String, String)> {
     values
 }
 
-fn apply_templates(input: &str, substitutions: &[(String, String)]) -> String {
+/// Fills `{{name}}` placeholders, then offset date placeholders such as
+/// `{{year+1}}`, `{{month-1}}`, `{{year_month+1}}`, `{{today+7}}` (days) and
+/// `{{iso_week+1}}`, counted from the `{{today}}` substitution. Each offset
+/// shifts only its own placeholder, so a month offset that crosses a year
+/// needs `{{year_month+N}}` rather than `{{year}}-{{month+N}}`.
+pub(crate) fn apply_templates(input: &str, substitutions: &[(String, String)]) -> String {
     let mut out = input.to_string();
     for (pattern, value) in substitutions {
         out = out.replace(pattern, value);
     }
+    let today = substitutions
+        .iter()
+        .find(|(pattern, _)| pattern == "{{today}}")
+        .and_then(|(_, value)| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok());
+    if let Some(today) = today
+        && out.contains("{{")
+    {
+        static OFFSET: OnceLock<Regex> = OnceLock::new();
+        let offset = OFFSET.get_or_init(|| {
+            Regex::new(r"\{\{(year_month|year|month|today|iso_week)([+-]\d{1,4})\}\}")
+                .expect("valid regex")
+        });
+        out = offset
+            .replace_all(&out, |caps: &regex::Captures<'_>| {
+                let by = caps[2].parse::<i32>().unwrap_or_default();
+                offset_date_placeholder(today, &caps[1], by).unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned();
+    }
     out
 }
 
+fn offset_date_placeholder(today: NaiveDate, name: &str, by: i32) -> Option<String> {
+    let days = |days: i64| today.checked_add_signed(chrono::Duration::days(days));
+    let months = || {
+        let months = Months::new(by.unsigned_abs());
+        if by < 0 {
+            today.checked_sub_months(months)
+        } else {
+            today.checked_add_months(months)
+        }
+    };
+    Some(match name {
+        "year" => (today.year() + by).to_string(),
+        "month" => months()?.format("%m").to_string(),
+        "year_month" => months()?.format("%Y-%m").to_string(),
+        "today" => days(i64::from(by))?.format("%Y-%m-%d").to_string(),
+        "iso_week" => days(7 * i64::from(by))?.format("%V").to_string(),
+        _ => return None,
+    })
+}
+
+/// GETs with a `cache` entry are sent as conditional requests; a 304
+/// returns the cached body.
+#[cfg(feature = "http")]
 fn fetch_with_retries(
     client: &Client,
     method: &str,
     url: &str,
-    retry_attempts: u8,
-    retry_backoff_ms: u64,
-) -> Result<Vec<u8>> {
-    let attempts = retry_attempts.max(1);
+    source: &LoadedSource,
+    auth: Option<&SourceAuth>,
+    cache: Option<&HttpCache>,
+) -> Result<FetchedPage> {
+    let fetch = &source.config.fetch;
+    let attempts = fetch.retry_attempts.max(1);
+    let cache = cache.filter(|_| method.eq_ignore_ascii_case("GET"));
+    let cached = cache.and_then(|cache| cache.validators(url));
+    check_request(source, url)?;
 
-    for attempt in 1..=attempts {
-        let request = match method.to_ascii_uppercase().as_str() {
+    // The request with the auth header it carries; `rejected` is a header
+    // the server answered with 401.
+    let build = |rejected: Option<&HeaderValue>| -> Result<(RequestBuilder, Option<HeaderValue>)> {
+        let mut request = match method.to_ascii_uppercase().as_str() {
             "GET" => client.get(url),
             "POST" => client.post(url),
             other => bail!("unsupported fetch method {other}"),
         };
+        let mut sent = None;
+        if let Some(auth) = auth
+            && let Some((name, value)) = auth.header_for(source, url, rejected)?
+        {
+            request = request.header(name, &value);
+            sent = Some(value);
+        }
+        throttle(source, url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        Ok((request, sent))
+    };
 
-        match request.send() {
+    for attempt in 1..=attempts {
+        let (request, sent) = build(None)?;
+        let mut response = request.send();
+        // A token can be revoked before it expires; a 401 gets one retry
+        // with a fresh one.
+        if let Ok(resp) = &response
+            && resp.status() == StatusCode::UNAUTHORIZED
+            && auth.is_some_and(SourceAuth::refreshable)
+            && let Some(sent) = &sent
+        {
+            debug!(%url, "token rejected; retrying with a fresh one");
+            response = build(Some(sent))?.0.send();
+        }
+
+        match response {
             Ok(resp) => {
+                let next_link = resp
+                    .headers()
+                    .get_all(LINK)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .find_map(link_rel_next);
+                let headers = header_map(resp.headers());
+                if resp.status() == StatusCode::NOT_MODIFIED
+                    && let Some(cache) = cache
+                    && cached.is_some()
+                {
+                    debug!(%url, "not modified; using cached body");
+                    return Ok(FetchedPage {
+                        body: cache.body(url)?,
+                        headers,
+                        next_link,
+                    });
+                }
                 if !resp.status().is_success() {
                     let status = resp.status();
-                    if attempt == attempts {
+                    if attempt == attempts || !fetch.retry_statuses.contains(&status.as_u16()) {
                         bail!("request to {url} failed with status {status}");
                     }
-                    warn!(%url, %status, attempt, "request failed; retrying");
+                    let max_wait = Duration::from_millis(fetch.retry_max_backoff_ms);
+                    let wait = match retry_after(resp.headers()) {
+                        Some(wait) if wait > max_wait => bail!(
+                            "request to {url} failed with status {status}; Retry-After of {}s exceeds fetch.retry_max_backoff_ms",
+                            wait.as_secs()
+                        ),
+                        Some(wait) => wait,
+                        None => retry_backoff(fetch, attempt),
+                    };
+                    warn!(
+                        %url,
+                        %status,
+                        attempt,
+                        wait_ms = wait.as_millis() as u64,
+                        "request failed; retrying"
+                    );
+                    std::thread::sleep(wait);
                 } else {
-                    return Ok(resp.bytes()?.to_vec());
+                    let header = |name| {
+                        resp.headers()
+                            .get(name)
+                            .and_then(|value: &HeaderValue| value.to_str().ok())
+                            .map(str::to_string)
+                    };
+                    let etag = header(ETAG);
+                    let last_modified = header(LAST_MODIFIED);
+                    let body = read_body(resp, url, fetch)?;
+                    if let Some(cache) = cache {
+                        cache.store(url, etag, last_modified, &body);
+                    }
+                    return Ok(FetchedPage {
+                        body,
+                        headers,
+                        next_link,
+                    });
                 }
             }
             Err(err) => {
                 if attempt == attempts {
                     return Err(err).with_context(|| format!("request to {url} failed"));
                 }
-                warn!(%url, attempt, error = %err, "request errored; retrying");
+                let wait = retry_backoff(fetch, attempt);
+                warn!(
+                    %url,
+                    attempt,
+                    error = %err,
+                    wait_ms = wait.as_millis() as u64,
+                    "request errored; retrying"
+                );
+                std::thread::sleep(wait);
             }
         }
-
-        std::thread::sleep(Duration::from_millis(retry_backoff_ms));
     }
 
     bail!("request to {url} failed after retries")
 }
 
+/// Streams `resp` into a [`Body`], stopping at `fetch.max_body_bytes`. An
+/// oversized body fails the fetch, or is cut off with
+/// `oversized_body = "truncate"`. Bodies over `fetch.spool_threshold_bytes`
+/// are spooled to a temp file.
+#[cfg(feature = "http")]
+fn read_body(resp: Response, url: &str, fetch: &FetchConfig) -> Result<Body> {
+    let limit = fetch.max_body_bytes;
+    if fetch.oversized_body == OversizedBody::Reject
+        && let Some(length) = resp.content_length()
+        && length > limit
+    {
+        bail!("response from {url} is {length} bytes, over fetch.max_body_bytes ({limit})");
+    }
+    read_capped(resp, url, fetch)
+}
+
+/// Reads `reader` to the end, or to `fetch.max_body_bytes` as described at
+/// [`read_body`].
+pub(crate) fn read_capped(reader: impl Read, url: &str, fetch: &FetchConfig) -> Result<Body> {
+    let limit = fetch.max_body_bytes;
+    let body = Body::read(
+        reader.take(limit.saturating_add(1)),
+        fetch.spool_threshold_bytes,
+    )
+    .with_context(|| format!("failed to read response from {url}"))?;
+    if body.len() as u64 > limit {
+        if fetch.oversized_body == OversizedBody::Reject {
+            bail!("response from {url} is over fetch.max_body_bytes ({limit})");
+        }
+        warn!(%url, limit, "response over fetch.max_body_bytes; truncated");
+        return Ok(body.truncated(limit as usize));
+    }
+    Ok(body)
+}
+
+/// `retry_backoff_ms` doubled for each attempt after the first, capped at
+/// `retry_max_backoff_ms`. The upper half is random, so sources that
+/// failed together do not retry in lockstep.
+#[cfg(feature = "http")]
+fn retry_backoff(fetch: &FetchConfig, attempt: u8) -> Duration {
+    let doublings = u32::from(attempt.saturating_sub(1)).min(16);
+    let wait = fetch
+        .retry_backoff_ms
+        .saturating_mul(1 << doublings)
+        .min(fetch.retry_max_backoff_ms);
+    let fixed = wait - wait / 2;
+    let jitter = RandomState::new().hash_one(Instant::now()) % (wait / 2 + 1);
+    Duration::from_millis(fixed + jitter)
+}
+
+/// The wait a `Retry-After` header asks for, given as seconds or as an
+/// HTTP date.
+#[cfg(feature = "http")]
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// The URL records of `source` take when they map none of their own, for
+/// sources whose records all come from one document. `None` when nothing is
+/// fetched; an error when it depends on the page, file or message.
+pub fn single_document_url(source: &LoadedSource) -> Result<Option<String>> {
+    let fetch = &source.config.fetch;
+    let substitutions = template_substitutions(source);
+    let key = &source.config.source.key;
+    match fetch.mode {
+        FetchMode::Schedule => Ok(None),
+        FetchMode::Inline => Ok(Some(format!("inline://{key}"))),
+        FetchMode::Http | FetchMode::Browser
+            if !source.config.pagination.enabled && fetch.iterate.is_none() =>
+        {
+            let base_url = fetch.base_url.as_ref().context("fetch.base_url missing")?;
+            Ok(Some(apply_templates(base_url, &substitutions)))
+        }
+        FetchMode::File => {
+            let file_path = fetch
+                .file_path
+                .as_ref()
+                .context("fetch.file_path missing for file mode")?;
+            let rendered = apply_templates(&file_path.to_string_lossy(), &substitutions);
+            let resolved = resolve_path(&source.path, std::path::Path::new(&rendered))?;
+            if has_glob_pattern(&resolved.to_string_lossy()) {
+                bail!("records of source {key} take the URL of the file they were read from");
+            }
+            Ok(Some(format!("file://{}", resolved.display())))
+        }
+        FetchMode::Ftp => {
+            let ftp = fetch
+                .ftp
+                .as_ref()
+                .context("fetch.ftp missing for ftp mode")?;
+            let path = apply_templates(&ftp.path, &substitutions);
+            if has_glob_pattern(&path) {
+                bail!("records of source {key} take the URL of the file they were read from");
+            }
+            Ok(Some(crate::ftp::ftp_url(ftp, &path)))
+        }
+        FetchMode::Sftp => {
+            let sftp = fetch
+                .sftp
+                .as_ref()
+                .context("fetch.sftp missing for sftp mode")?;
+            let path = apply_templates(&sftp.path, &substitutions);
+            if has_glob_pattern(&path) {
+                bail!("records of source {key} take the URL of the file they were read from");
+            }
+            Ok(Some(crate::sftp::sftp_url(sftp, &path)))
+        }
+        FetchMode::Http | FetchMode::Browser | FetchMode::Imap | FetchMode::Replay => {
+            bail!("records of source {key} take the URL of the page or message they were found in")
+        }
+    }
+}
+
 fn fetch_file_document(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
     let file_path = source
         .config
@@ -274,8 +1677,10 @@ fn fetch_file_document(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
                 .with_context(|| format!("failed to read file source {}", path.display()))?;
             docs.push(FetchedDocument {
                 source_url: format!("file://{}", path.display()),
-                body: bytes,
+                body: bytes.into(),
                 page_index: index,
+                fetched_at: Some(Utc::now()),
+                headers: BTreeMap::new(),
             });
         }
 
@@ -300,12 +1705,14 @@ fn fetch_file_document(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
 
     Ok(vec![FetchedDocument {
         source_url: format!("file://{}", resolved.display()),
-        body: bytes,
+        body: bytes.into(),
         page_index: 0,
+        fetched_at: Some(Utc::now()),
+        headers: BTreeMap::new(),
     }])
 }
 
-fn has_glob_pattern(path: &str) -> bool {
+pub(crate) fn has_glob_pattern(path: &str) -> bool {
     path.contains('*') || path.contains('?') || path.contains('[')
 }
 
@@ -327,11 +1734,14 @@ fn fetch_inline_document(source: &LoadedSource) -> Result<Vec<FetchedDocument>>
 
     Ok(vec![FetchedDocument {
         source_url: format!("inline://{}", source.config.source.key),
-        body: inline.into_bytes(),
+        body: inline.into_bytes().into(),
         page_index: 0,
+        fetched_at: Some(Utc::now()),
+        headers: BTreeMap::new(),
     }])
 }
 
+#[cfg(feature = "http")]
 fn build_paged_url(base_url: &str, param: &str, page: &str) -> Result<String> {
     let mut url = Url::parse(base_url).with_context(|| format!("invalid base_url {base_url}"))?;
 
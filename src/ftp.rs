@@ -0,0 +1,300 @@
+//! Minimal FTP client for `fetch.mode = "ftp"`.
+//!
+//! Only what the fetch mode needs is implemented: USER/PASS, binary mode,
+//! passive data connections (EPSV, falling back to PASV), NLST to expand a
+//! globbed file name and RETR. Data connections go to the control
+//! connection's peer address rather than the one PASV announces, which is
+//! often private behind NAT.
+
+use crate::config::{FtpConfig, LoadedSource};
+use crate::fetch::{
+    FetchedDocument, apply_templates, contact_for, has_glob_pattern, read_capped,
+    template_substitutions,
+};
+use crate::network::connect_checked;
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use glob::Pattern;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// Password sent for anonymous logins when no operator contact is set.
+const ANONYMOUS_PASSWORD: &str = "anonymous@";
+
+pub fn fetch_ftp_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
+    let fetch = &source.config.fetch;
+    let ftp = fetch.ftp.as_ref().context("fetch.ftp missing")?;
+    let path = apply_templates(&ftp.path, &template_substitutions(source));
+    let (username, password) = credentials(source, ftp)?;
+
+    let timeout = Duration::from_secs(fetch.timeout_secs);
+    let mut session = FtpSession::connect(source, ftp, timeout)?;
+    session.login(&username, &password)?;
+    session.expect("TYPE I", 2)?;
+
+    let (dir, name) = path.rsplit_once('/').unwrap_or(("", &path));
+    let files = if has_glob_pattern(name) {
+        let pattern =
+            Pattern::new(name).with_context(|| format!("invalid ftp file glob pattern {name}"))?;
+        let mut names = session
+            .list(if dir.is_empty() { "/" } else { dir })?
+            .into_iter()
+            .filter(|listed| pattern.matches(listed))
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        if names.is_empty() {
+            bail!("no files on ftp {} matched {path}", ftp.host);
+        }
+        names
+            .into_iter()
+            .map(|listed| format!("{dir}/{listed}"))
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+
+    let fetched_at = Utc::now();
+    let mut docs = Vec::with_capacity(files.len());
+    for file in files {
+        let source_url = ftp_url(ftp, &file);
+        let body = session
+            .retrieve(&file, |data| read_capped(data, &source_url, fetch))
+            .with_context(|| format!("failed to download {source_url}"))?;
+        debug!(url = %source_url, bytes = body.len(), "ftp file downloaded");
+        docs.push(FetchedDocument {
+            source_url,
+            body,
+            page_index: docs.len(),
+            fetched_at: Some(fetched_at),
+            headers: BTreeMap::new(),
+        });
+    }
+
+    // Best effort; the files are already in hand.
+    let _ = session.command("QUIT");
+    info!(
+        source = %source.config.source.key,
+        host = %ftp.host,
+        %path,
+        documents = docs.len(),
+        "ftp fetch complete"
+    );
+    Ok(docs)
+}
+
+/// `ftp://` URL of `path` on the configured server.
+pub fn ftp_url(ftp: &FtpConfig, path: &str) -> String {
+    if ftp.port == 21 {
+        format!("ftp://{}{path}", ftp.host)
+    } else {
+        format!("ftp://{}:{}{path}", ftp.host, ftp.port)
+    }
+}
+
+fn credentials(source: &LoadedSource, ftp: &FtpConfig) -> Result<(String, String)> {
+    let Some(username) = &ftp.username else {
+        let password = contact_for(source).unwrap_or_else(|| ANONYMOUS_PASSWORD.to_string());
+        return Ok(("anonymous".to_string(), password));
+    };
+    let env = ftp
+        .password_env
+        .as_ref()
+        .context("fetch.ftp.password_env missing")?;
+    let password = std::env::var(env).with_context(|| {
+        format!(
+            "ftp password env var {env} is not set for source {}",
+            source.config.source.key
+        )
+    })?;
+    Ok((username.clone(), password))
+}
+
+struct Reply {
+    code: u16,
+    text: String,
+}
+
+struct FtpSession {
+    control: BufReader<TcpStream>,
+    peer: SocketAddr,
+    timeout: Duration,
+}
+
+impl FtpSession {
+    fn connect(source: &LoadedSource, ftp: &FtpConfig, timeout: Duration) -> Result<Self> {
+        let tcp = connect_checked(source, &ftp.host, ftp.port, timeout)
+            .with_context(|| format!("failed to connect to ftp {}:{}", ftp.host, ftp.port))?;
+        tcp.set_read_timeout(Some(timeout))?;
+        tcp.set_write_timeout(Some(timeout))?;
+        let mut session = Self {
+            peer: tcp.peer_addr()?,
+            control: BufReader::new(tcp),
+            timeout,
+        };
+        let greeting = session.read_reply()?;
+        if greeting.code != 220 {
+            bail!(
+                "unexpected ftp greeting: {} {}",
+                greeting.code,
+                greeting.text
+            );
+        }
+        Ok(session)
+    }
+
+    fn login(&mut self, username: &str, password: &str) -> Result<()> {
+        let reply = self.command(&format!("USER {username}"))?;
+        let reply = match reply.code {
+            331 | 332 => self.command(&format!("PASS {password}"))?,
+            _ => reply,
+        };
+        if reply.code / 100 != 2 {
+            bail!("ftp login failed: {} {}", reply.code, reply.text);
+        }
+        Ok(())
+    }
+
+    /// File names in `dir`; empty when the server reports no such files.
+    fn list(&mut self, dir: &str) -> Result<Vec<String>> {
+        let listed = self.transfer(&format!("NLST {dir}"), |data| {
+            let mut raw = String::new();
+            data.read_to_string(&mut raw)
+                .context("failed to read ftp listing")?;
+            // Some servers list full paths, others bare names.
+            Ok(raw
+                .lines()
+                .filter_map(|line| line.trim().rsplit('/').next())
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect())
+        })?;
+        match listed {
+            Ok(names) => Ok(names),
+            Err(reply) if matches!(reply.code, 450 | 550) => Ok(Vec::new()),
+            Err(reply) => bail!("ftp NLST {dir} failed: {} {}", reply.code, reply.text),
+        }
+    }
+
+    fn retrieve<T>(
+        &mut self,
+        path: &str,
+        read: impl FnOnce(&mut TcpStream) -> Result<T>,
+    ) -> Result<T> {
+        match self.transfer(&format!("RETR {path}"), read)? {
+            Ok(value) => Ok(value),
+            Err(reply) => bail!("ftp RETR failed: {} {}", reply.code, reply.text),
+        }
+    }
+
+    /// Runs `command` over a fresh passive data connection, handing the
+    /// connection to `read` once the server starts sending. The inner error
+    /// is the server's reply when it refuses the command.
+    fn transfer<T>(
+        &mut self,
+        command: &str,
+        read: impl FnOnce(&mut TcpStream) -> Result<T>,
+    ) -> Result<Result<T, Reply>> {
+        let mut data = self.open_data()?;
+        let reply = self.command(command)?;
+        if reply.code / 100 != 1 {
+            return Ok(Err(reply));
+        }
+        let result = read(&mut data);
+        // A body cut short at `max_body_bytes` leaves data unsent, and the
+        // server reports the transfer as aborted once the connection closes.
+        let cut_short = result.is_ok() && data.read(&mut [0]).is_ok_and(|read| read > 0);
+        drop(data);
+        let done = self.read_reply()?;
+        let value = result?;
+        if done.code / 100 != 2 && !cut_short {
+            bail!("ftp transfer failed: {} {}", done.code, done.text);
+        }
+        Ok(Ok(value))
+    }
+
+    fn open_data(&mut self) -> Result<TcpStream> {
+        let reply = self.command("EPSV")?;
+        let port = if reply.code == 229 {
+            epsv_port(&reply.text)
+                .with_context(|| format!("malformed ftp EPSV reply: {}", reply.text))?
+        } else {
+            let reply = self.expect("PASV", 2)?;
+            pasv_port(&reply.text)
+                .with_context(|| format!("malformed ftp PASV reply: {}", reply.text))?
+        };
+        let addr = SocketAddr::new(self.peer.ip(), port);
+        let data = TcpStream::connect_timeout(&addr, self.timeout)
+            .with_context(|| format!("failed to open ftp data connection to {addr}"))?;
+        data.set_read_timeout(Some(self.timeout))?;
+        data.set_write_timeout(Some(self.timeout))?;
+        Ok(data)
+    }
+
+    /// Sends `command`, failing unless the reply code is in `class`xx.
+    fn expect(&mut self, command: &str, class: u16) -> Result<Reply> {
+        let reply = self.command(command)?;
+        if reply.code / 100 != class {
+            let verb = command.split_whitespace().next().unwrap_or(command);
+            bail!("ftp {verb} failed: {} {}", reply.code, reply.text);
+        }
+        Ok(reply)
+    }
+
+    fn command(&mut self, command: &str) -> Result<Reply> {
+        let stream = self.control.get_mut();
+        stream.write_all(format!("{command}\r\n").as_bytes())?;
+        stream.flush()?;
+        self.read_reply()
+    }
+
+    fn read_reply(&mut self) -> Result<Reply> {
+        let first = self.read_line()?;
+        let code = first
+            .get(..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .with_context(|| format!("malformed ftp reply: {first}"))?;
+        // `123-` opens a multi-line reply that ends at a line starting `123 `.
+        if first.as_bytes().get(3) == Some(&b'-') {
+            let last = format!("{code} ");
+            while !self.read_line()?.starts_with(&last) {}
+        }
+        Ok(Reply {
+            code,
+            text: first.get(4..).unwrap_or_default().to_string(),
+        })
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut buf = Vec::new();
+        let read = self.control.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            bail!("ftp server closed the connection");
+        }
+        Ok(String::from_utf8_lossy(&buf).trim_end().to_string())
+    }
+}
+
+/// Port from `229 Entering Extended Passive Mode (|||6446|)`.
+fn epsv_port(text: &str) -> Option<u16> {
+    let start = text.find("(|||")? + 4;
+    let end = start + text[start..].find('|')?;
+    text[start..end].parse().ok()
+}
+
+/// Port from `227 Entering Passive Mode (h1,h2,h3,h4,p1,p2)`.
+fn pasv_port(text: &str) -> Option<u16> {
+    let start = text.find('(')? + 1;
+    let end = start + text[start..].find(')')?;
+    let numbers = text[start..end]
+        .split(',')
+        .map(|part| part.trim().parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match numbers[..] {
+        [_, _, _, _, high, low] => Some(u16::from(high) << 8 | u16::from(low)),
+        _ => None,
+    }
+}
@@ -1,3 +1,4 @@
+use crate::model::SourceRunReport;
 use crate::pipeline::{SyncOptions, load_state_for_read, sync_sources};
 use anyhow::Result;
 use serde::Serialize;
@@ -22,6 +23,8 @@ pub struct HarnessReport {
     pub second_run_cancelled: usize,
     pub total_events: usize,
     pub ics_files: usize,
+    pub first_run_reports: Vec<SourceRunReport>,
+    pub second_run_reports: Vec<SourceRunReport>,
 }
 
 pub fn run_harness(options: &HarnessOptions) -> Result<HarnessReport> {
@@ -32,21 +35,14 @@ pub fn run_harness(options: &HarnessOptions) -> Result<HarnessReport> {
         std::fs::remove_file(&options.state_path)?;
     }
 
-    let first = sync_sources(&SyncOptions {
+    let sync = SyncOptions {
         config_dir: options.config_dir.clone(),
         state_path: options.state_path.clone(),
         out_dir: options.out_dir.clone(),
-        source: None,
-        dry_run: false,
-    })?;
-
-    let second = sync_sources(&SyncOptions {
-        config_dir: options.config_dir.clone(),
-        state_path: options.state_path.clone(),
-        out_dir: options.out_dir.clone(),
-        source: None,
-        dry_run: false,
-    })?;
+        ..SyncOptions::default()
+    };
+    let first = sync_sources(&sync)?;
+    let second = sync_sources(&sync)?;
 
     let state = load_state_for_read(&options.state_path)?;
 
@@ -70,5 +66,7 @@ pub fn run_harness(options: &HarnessOptions) -> Result<HarnessReport> {
         second_run_cancelled: second.iter().map(|r| r.cancelled).sum(),
         total_events: state.events.len(),
         ics_files,
+        first_run_reports: first,
+        second_run_reports: second,
     })
 }
@@ -0,0 +1,140 @@
+//! Per-source health for uptime monitors.
+//!
+//! `rics daemon` keeps a [`HealthStatus`] current after every sync, writes it
+//! to `status.json` in the output directory and serves it on `GET /healthz`.
+//! A source is unhealthy when its last sync failed or when it has not synced
+//! successfully within `stale_after`.
+
+use crate::model::State;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HealthStatus {
+    pub generated_at: DateTime<Utc>,
+    /// Every source is healthy.
+    pub healthy: bool,
+    pub stale_after_secs: u64,
+    pub sources: Vec<SourceHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SourceHealth {
+    pub source_key: String,
+    pub healthy: bool,
+    /// When the last sync that included this source finished.
+    pub last_success: Option<DateTime<Utc>>,
+    /// Failed syncs since the last success.
+    pub consecutive_failures: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// No success within `stale_after_secs`, or never synced.
+    pub stale: bool,
+}
+
+/// Sync outcomes the state file does not record: a failed sync saves
+/// nothing, so failures are only known to the process that saw them.
+#[derive(Debug, Clone, Default)]
+pub struct HealthTracker {
+    failures: BTreeMap<String, (u32, String)>,
+}
+
+impl HealthTracker {
+    pub fn record_success<'a>(&mut self, source_keys: impl IntoIterator<Item = &'a str>) {
+        for key in source_keys {
+            self.failures.remove(key);
+        }
+    }
+
+    /// Counts a failed sync against every source it covered.
+    pub fn record_failure<'a>(
+        &mut self,
+        source_keys: impl IntoIterator<Item = &'a str>,
+        error: &str,
+    ) {
+        for key in source_keys {
+            let entry = self
+                .failures
+                .entry(key.to_string())
+                .or_insert((0, String::new()));
+            entry.0 += 1;
+            entry.1 = error.to_string();
+        }
+    }
+
+    /// Health of `source_keys` as of `now`, taking successes from the run
+    /// history in `state`.
+    pub fn status(
+        &self,
+        source_keys: &[String],
+        state: &State,
+        stale_after: Duration,
+        now: DateTime<Utc>,
+    ) -> HealthStatus {
+        let sources = source_keys
+            .iter()
+            .map(|key| {
+                let last_success = state.runs.iter().rev().find_map(|run| {
+                    run.source(key)
+                        .map(|_| run.started_at + Duration::milliseconds(run.duration_ms as i64))
+                });
+                let (consecutive_failures, last_error) = self
+                    .failures
+                    .get(key)
+                    .map(|(count, error)| (*count, Some(error.clone())))
+                    .unwrap_or((0, None));
+                SourceHealth {
+                    source_key: key.clone(),
+                    healthy: false,
+                    last_success,
+                    consecutive_failures,
+                    last_error,
+                    stale: false,
+                }
+            })
+            .collect();
+        let mut status = HealthStatus {
+            generated_at: now,
+            healthy: false,
+            stale_after_secs: stale_after.num_seconds().max(0) as u64,
+            sources,
+        };
+        status.refresh(now);
+        status
+    }
+}
+
+impl HealthStatus {
+    /// Re-evaluates staleness as of `now`.
+    pub fn refresh(&mut self, now: DateTime<Utc>) {
+        let stale_after = Duration::seconds(self.stale_after_secs as i64);
+        for source in &mut self.sources {
+            source.stale = source
+                .last_success
+                .is_none_or(|success| now - success > stale_after);
+            source.healthy = !source.stale && source.consecutive_failures == 0;
+        }
+        self.healthy = self.sources.iter().all(|source| source.healthy);
+        self.generated_at = now;
+    }
+}
+
+/// `status.json` in the output directory.
+pub fn status_path(out_dir: &Path) -> PathBuf {
+    out_dir.join("status.json")
+}
+
+/// Writes `status` to `status.json`, replacing it atomically so monitors
+/// never read a partial file.
+pub fn write_health_status(out_dir: &Path, status: &HealthStatus) -> Result<()> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("failed to create output dir {}", out_dir.display()))?;
+    let path = status_path(out_dir);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(status)?)
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("failed to replace {}", path.display()))
+}
@@ -0,0 +1,235 @@
+//! Built-in public holiday calendars.
+//!
+//! Holidays are computed from rules (fixed dates, nth weekdays, Easter
+//! offsets, weekend substitution) rather than shipped as a date list, so
+//! they cover any year. Only national holidays are included; regional ones
+//! can be added per source as extra dates.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::BTreeMap;
+
+/// Countries with a built-in calendar, as ISO 3166-1 alpha-2 codes.
+pub const SUPPORTED_COUNTRIES: &[&str] = &["CA", "DE", "FR", "GB", "US"];
+
+/// A country's holidays plus any configured extra dates.
+#[derive(Debug, Clone)]
+pub struct HolidayCalendar {
+    country: String,
+    extra: BTreeMap<NaiveDate, String>,
+}
+
+impl HolidayCalendar {
+    /// `None` when the country has no built-in calendar and no extra dates
+    /// were given.
+    pub fn new(country: &str, extra: BTreeMap<NaiveDate, String>) -> Option<Self> {
+        let country = country.trim().to_ascii_uppercase();
+        if !SUPPORTED_COUNTRIES.contains(&country.as_str()) && extra.is_empty() {
+            return None;
+        }
+        Some(Self { country, extra })
+    }
+
+    pub fn country(&self) -> &str {
+        &self.country
+    }
+
+    /// The name of the holiday on `date`, if any. Substitute days are named
+    /// after the holiday they replace.
+    pub fn holiday(&self, date: NaiveDate) -> Option<String> {
+        if let Some(name) = self.extra.get(&date) {
+            return Some(name.clone());
+        }
+        // A New Year's Day observed on 31 December belongs to the next
+        // year's list.
+        [date.year(), date.year() + 1]
+            .into_iter()
+            .flat_map(|year| national_holidays(&self.country, year))
+            .find(|(day, _)| *day == date)
+            .map(|(_, name)| name)
+    }
+
+    /// Weekdays that are not holidays.
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && self.holiday(date).is_none()
+    }
+}
+
+pub fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// National holidays for `year`, including observed/substitute days.
+pub fn national_holidays(country: &str, year: i32) -> Vec<(NaiveDate, String)> {
+    let fixed = |month, day| NaiveDate::from_ymd_opt(year, month, day);
+    let easter = easter_sunday(year);
+    let from_easter = |days: i64| easter.map(|date| date + Duration::days(days));
+    let nth = |month, weekday, n| nth_weekday(year, month, weekday, n);
+    let last = |month, weekday| last_weekday(year, month, weekday);
+
+    let (rules, observance): (Vec<(Option<NaiveDate>, &str)>, Observance) = match country {
+        "US" => (
+            vec![
+                (fixed(1, 1), "New Year's Day"),
+                (nth(1, Weekday::Mon, 3), "Martin Luther King Jr. Day"),
+                (nth(2, Weekday::Mon, 3), "Washington's Birthday"),
+                (last(5, Weekday::Mon), "Memorial Day"),
+                (
+                    fixed(6, 19).filter(|_| year >= 2021),
+                    "Juneteenth National Independence Day",
+                ),
+                (fixed(7, 4), "Independence Day"),
+                (nth(9, Weekday::Mon, 1), "Labor Day"),
+                (nth(10, Weekday::Mon, 2), "Columbus Day"),
+                (fixed(11, 11), "Veterans Day"),
+                (nth(11, Weekday::Thu, 4), "Thanksgiving Day"),
+                (fixed(12, 25), "Christmas Day"),
+            ],
+            Observance::NearestWeekday,
+        ),
+        "GB" => (
+            vec![
+                (fixed(1, 1), "New Year's Day"),
+                (from_easter(-2), "Good Friday"),
+                (from_easter(1), "Easter Monday"),
+                (nth(5, Weekday::Mon, 1), "Early May bank holiday"),
+                (last(5, Weekday::Mon), "Spring bank holiday"),
+                (last(8, Weekday::Mon), "Summer bank holiday"),
+                (fixed(12, 25), "Christmas Day"),
+                (fixed(12, 26), "Boxing Day"),
+            ],
+            Observance::NextWorkingDay,
+        ),
+        "CA" => (
+            vec![
+                (fixed(1, 1), "New Year's Day"),
+                (from_easter(-2), "Good Friday"),
+                (
+                    fixed(5, 24).map(|date| previous_or_same(date, Weekday::Mon)),
+                    "Victoria Day",
+                ),
+                (fixed(7, 1), "Canada Day"),
+                (nth(9, Weekday::Mon, 1), "Labour Day"),
+                (nth(10, Weekday::Mon, 2), "Thanksgiving"),
+                (fixed(12, 25), "Christmas Day"),
+                (fixed(12, 26), "Boxing Day"),
+            ],
+            Observance::NextWorkingDay,
+        ),
+        "DE" => (
+            vec![
+                (fixed(1, 1), "Neujahr"),
+                (from_easter(-2), "Karfreitag"),
+                (from_easter(1), "Ostermontag"),
+                (fixed(5, 1), "Tag der Arbeit"),
+                (from_easter(39), "Christi Himmelfahrt"),
+                (from_easter(50), "Pfingstmontag"),
+                (fixed(10, 3), "Tag der Deutschen Einheit"),
+                (fixed(12, 25), "1. Weihnachtstag"),
+                (fixed(12, 26), "2. Weihnachtstag"),
+            ],
+            Observance::None,
+        ),
+        "FR" => (
+            vec![
+                (fixed(1, 1), "Jour de l'an"),
+                (from_easter(1), "Lundi de Pâques"),
+                (fixed(5, 1), "Fête du Travail"),
+                (fixed(5, 8), "Victoire 1945"),
+                (from_easter(39), "Ascension"),
+                (from_easter(50), "Lundi de Pentecôte"),
+                (fixed(7, 14), "Fête nationale"),
+                (fixed(8, 15), "Assomption"),
+                (fixed(11, 1), "Toussaint"),
+                (fixed(11, 11), "Armistice 1918"),
+                (fixed(12, 25), "Noël"),
+            ],
+            Observance::None,
+        ),
+        _ => return Vec::new(),
+    };
+
+    let mut holidays = rules
+        .into_iter()
+        .filter_map(|(date, name)| date.map(|date| (date, name.to_string())))
+        .collect::<Vec<_>>();
+    holidays.sort();
+    observance.apply(&mut holidays);
+    holidays
+}
+
+/// How a holiday that falls on a weekend is made up for.
+#[derive(Debug, Clone, Copy)]
+enum Observance {
+    None,
+    /// Saturday moves to Friday, Sunday to Monday (US federal).
+    NearestWeekday,
+    /// Moves to the next weekday that is not already a holiday (UK and
+    /// Canadian substitute days).
+    NextWorkingDay,
+}
+
+impl Observance {
+    fn apply(self, holidays: &mut Vec<(NaiveDate, String)>) {
+        let mut substitutes = Vec::new();
+        for (date, name) in holidays.iter() {
+            let observed = match (self, date.weekday()) {
+                (Observance::None, _) => continue,
+                (Observance::NearestWeekday, Weekday::Sat) => *date - Duration::days(1),
+                (Observance::NearestWeekday, Weekday::Sun) => *date + Duration::days(1),
+                (Observance::NextWorkingDay, Weekday::Sat | Weekday::Sun) => {
+                    let mut day = *date + Duration::days(1);
+                    while is_weekend(day)
+                        || holidays.iter().any(|(other, _)| *other == day)
+                        || substitutes.iter().any(|(other, _)| *other == day)
+                    {
+                        day += Duration::days(1);
+                    }
+                    day
+                }
+                _ => continue,
+            };
+            substitutes.push((observed, format!("{name} (observed)")));
+        }
+        holidays.extend(substitutes);
+        holidays.sort();
+    }
+}
+
+/// Gregorian Easter Sunday (anonymous Gregorian algorithm).
+pub fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// The `n`th (1-based) `weekday` of a month.
+pub fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+}
+
+pub fn last_weekday(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let last_day = NaiveDate::from_ymd_opt(next_year, next_month, 1)? - Duration::days(1);
+    Some(previous_or_same(last_day, weekday))
+}
+
+fn previous_or_same(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let back = (date.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+    date - Duration::days(i64::from(back))
+}
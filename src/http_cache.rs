@@ -0,0 +1,192 @@
+//! Conditional-request cache for HTTP fetches.
+//!
+//! Responses that carry an `ETag` or `Last-Modified` header are kept in
+//! `<state>.http-cache/` next to the state file, keyed by URL. The next fetch of the
+//! same URL sends `If-None-Match`/`If-Modified-Since`, and a `304 Not
+//! Modified` reuses the stored body, so static sources are not downloaded
+//! again on every sync. New bodies are held (in memory, or in their spool
+//! file) until [`HttpCache::save`], which the sync calls only after state
+//! was written.
+
+use crate::body::Body;
+use crate::store::write_atomic;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const INDEX_FILE: &str = "index.json";
+
+/// Validators of a cached response.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CachedResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    pub stored_at: DateTime<Utc>,
+}
+
+/// Shared by concurrently fetching sources, hence the locks.
+#[derive(Debug, Default)]
+pub struct HttpCache {
+    dir: PathBuf,
+    entries: Mutex<BTreeMap<String, CachedResponse>>,
+    /// Bodies fetched this run by URL, written on save.
+    pending: Mutex<BTreeMap<String, Body>>,
+    /// URLs evicted this run, dropped from the index on save.
+    evicted: Mutex<BTreeSet<String>>,
+}
+
+impl HttpCache {
+    /// `<state>.http-cache/` beside the state file. Each state file gets
+    /// its own, since [`HttpCache::save`] deletes bodies its index does not
+    /// know.
+    pub fn dir_for_state(state_path: &Path) -> PathBuf {
+        let mut dir = state_path.as_os_str().to_owned();
+        dir.push(".http-cache");
+        PathBuf::from(dir)
+    }
+
+    /// Opens the cache, empty when it does not exist yet.
+    pub fn open(dir: &Path) -> Result<Self> {
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            entries: Mutex::new(read_index(dir)?),
+            pending: Mutex::default(),
+            evicted: Mutex::default(),
+        })
+    }
+
+    /// Validators to send for `url`, if its body is still available.
+    pub fn validators(&self, url: &str) -> Option<CachedResponse> {
+        let cached = lock(&self.entries).get(url).cloned()?;
+        (lock(&self.pending).contains_key(url) || self.body_path(url).exists()).then_some(cached)
+    }
+
+    /// The cached body for `url`, after a 304.
+    pub fn body(&self, url: &str) -> Result<Body> {
+        if let Some(body) = lock(&self.pending).get(url) {
+            return Ok(body.clone());
+        }
+        let path = self.body_path(url);
+        std::fs::read(&path)
+            .map(Body::from)
+            .with_context(|| format!("failed to read cached body {}", path.display()))
+    }
+
+    /// Records a fresh response. Responses without validators evict the URL,
+    /// since they could never be revalidated.
+    pub fn store(
+        &self,
+        url: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        body: &Body,
+    ) {
+        let mut entries = lock(&self.entries);
+        let mut pending = lock(&self.pending);
+        let mut evicted = lock(&self.evicted);
+        if etag.is_none() && last_modified.is_none() {
+            entries.remove(url);
+            pending.remove(url);
+            evicted.insert(url.to_string());
+            return;
+        }
+        evicted.remove(url);
+        entries.insert(
+            url.to_string(),
+            CachedResponse {
+                etag,
+                last_modified,
+                stored_at: Utc::now(),
+            },
+        );
+        pending.insert(url.to_string(), body.clone());
+    }
+
+    /// Writes new bodies and the index, and deletes bodies no entry refers
+    /// to any more. The index is read again first and only this run's
+    /// changes are applied to it, so entries another run saved since
+    /// [`HttpCache::open`] survive; callers hold the state's
+    /// [`StateLock`](crate::store::StateLock) so that runs save in turn.
+    pub fn save(&self) -> Result<()> {
+        let mut entries = lock(&self.entries);
+        let mut pending = lock(&self.pending);
+        let mut evicted = lock(&self.evicted);
+        let mut merged = read_index(&self.dir)?;
+        for url in evicted.iter() {
+            merged.remove(url);
+        }
+        for url in pending.keys() {
+            if let Some(cached) = entries.get(url) {
+                merged.insert(url.clone(), cached.clone());
+            }
+        }
+        *entries = merged;
+        evicted.clear();
+        if entries.is_empty() && !self.dir.exists() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create http cache {}", self.dir.display()))?;
+        for (url, body) in pending.iter() {
+            let path = self.body_path(url);
+            write_atomic(&path, body)
+                .with_context(|| format!("failed to write cached body {}", path.display()))?;
+        }
+        pending.clear();
+
+        let index = self.dir.join(INDEX_FILE);
+        write_atomic(&index, serde_json::to_string_pretty(&*entries)?.as_bytes())
+            .with_context(|| format!("failed to write http cache {}", index.display()))?;
+
+        let live = entries
+            .keys()
+            .map(|url| body_file_name(url))
+            .collect::<HashSet<_>>();
+        for entry in std::fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to list http cache {}", self.dir.display()))?
+        {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            // `.tmp` files are writes a crash cut short.
+            if (name.ends_with(".body") && !live.contains(name)) || name.ends_with(".tmp") {
+                std::fs::remove_file(&path).with_context(|| {
+                    format!("failed to remove stale cached body {}", path.display())
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn body_path(&self, url: &str) -> PathBuf {
+        self.dir.join(body_file_name(url))
+    }
+}
+
+fn read_index(dir: &Path) -> Result<BTreeMap<String, CachedResponse>> {
+    let index = dir.join(INDEX_FILE);
+    if !index.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(&index)
+        .with_context(|| format!("failed to read http cache {}", index.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("failed to parse http cache {}", index.display()))
+}
+
+fn body_file_name(url: &str) -> String {
+    format!("{}.body", hex::encode(Sha256::digest(url.as_bytes())))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
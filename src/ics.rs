@@ -1,28 +1,266 @@
-use crate::config::SourceConfig;
-use crate::model::{EventRecord, EventTimeSpec};
+use crate::config::{Compression, PublishConfig, SourceConfig, TimeBasis};
+use crate::model::{EventRecord, EventTimeSpec, LocalTime, RecurrenceDate};
 use anyhow::{Context, Result};
-use chrono::{Datelike, Timelike, Utc};
+use chrono::{Datelike, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
 use std::path::Path;
 
+/// Writes a source's year calendar. Returns false when the file already had
+/// identical content and was left untouched.
 pub fn write_source_year_calendar(
     source: &SourceConfig,
     year: i32,
     events: &[&EventRecord],
     path: &Path,
-) -> Result<()> {
-    write_calendar_file(&format!("{} {}", source.source.name, year), events, path)
+) -> Result<bool> {
+    write_calendar_file(
+        &format!("{} {}", source.source.name, year),
+        events,
+        &source.publish,
+        path,
+    )
 }
 
 pub fn write_named_year_calendar(
     calendar_name: &str,
     year: i32,
     events: &[&EventRecord],
+    publish: &PublishConfig,
+    path: &Path,
+) -> Result<bool> {
+    write_calendar_file(&format!("{calendar_name} {year}"), events, publish, path)
+}
+
+pub fn render_source_year_calendar(
+    source: &SourceConfig,
+    year: i32,
+    events: &[&EventRecord],
+) -> String {
+    render_calendar(
+        &format!("{} {}", source.source.name, year),
+        events,
+        &source.publish,
+    )
+}
+
+pub fn render_named_year_calendar(
+    calendar_name: &str,
+    year: i32,
+    events: &[&EventRecord],
+    publish: &PublishConfig,
+) -> String {
+    render_calendar(&format!("{calendar_name} {year}"), events, publish)
+}
+
+/// Bytes `event` adds to a published calendar, so a file can be sized
+/// without rendering it whole.
+pub fn rendered_event_len(event: &EventRecord, publish: &PublishConfig) -> usize {
+    let mut lines = Vec::new();
+    append_event_lines(&mut lines, event, publish);
+    lines.iter().map(|line| line.len() + 2).sum()
+}
+
+/// Streams a source's year calendar to `out` without touching the output
+/// directory, e.g. for `rics build --stdout`.
+pub fn stream_source_year_calendar<W: Write>(
+    source: &SourceConfig,
+    year: i32,
+    events: &[&EventRecord],
+    out: &mut W,
+) -> io::Result<()> {
+    write_calendar(
+        out,
+        &format!("{} {}", source.source.name, year),
+        events,
+        &source.publish,
+    )?;
+    out.flush()
+}
+
+/// A single-event iTIP message (RFC 5546) to attach to an email. `method`
+/// is `REQUEST` or `CANCEL`; the sender is the ORGANIZER and every
+/// recipient an ATTENDEE.
+pub fn render_itip_invite(
+    event: &EventRecord,
+    method: &str,
+    organizer: &str,
+    attendees: &[String],
+    publish: &PublishConfig,
+) -> String {
+    let mut lines = Vec::new();
+    push_line(&mut lines, "BEGIN:VCALENDAR".to_string());
+    push_line(&mut lines, "VERSION:2.0".to_string());
+    push_line(
+        &mut lines,
+        "PRODID:-//rics//ICS Generator 1.0//EN".to_string(),
+    );
+    push_line(&mut lines, "CALSCALE:GREGORIAN".to_string());
+    push_line(&mut lines, format!("METHOD:{method}"));
+    append_event_lines(&mut lines, event, publish);
+    // Reopen the event to add the scheduling properties.
+    lines.pop();
+    push_line(&mut lines, format!("ORGANIZER:mailto:{organizer}"));
+    for attendee in attendees {
+        push_line(
+            &mut lines,
+            format!("ATTENDEE;ROLE=REQ-PARTICIPANT;RSVP=FALSE:mailto:{attendee}"),
+        );
+    }
+    push_line(&mut lines, "END:VEVENT".to_string());
+    push_line(&mut lines, "END:VCALENDAR".to_string());
+
+    let mut out = lines.join("\r\n");
+    out.push_str("\r\n");
+    out
+}
+
+/// Names of the compressed copies of `file_name` per `publish.compress`.
+pub fn compressed_variant_names(publish: &PublishConfig, file_name: &str) -> Vec<String> {
+    publish
+        .compress
+        .iter()
+        .map(|compression| format!("{file_name}.{}", compression.extension()))
+        .collect()
+}
+
+/// Writes the compressed copies of the calendar at `path` as `target` plus
+/// each extension. The output only depends on the calendar (gzip's mtime is
+/// left at 0), so unchanged calendars give unchanged copies.
+pub fn write_compressed_variants(
+    publish: &PublishConfig,
     path: &Path,
+    target: &Path,
 ) -> Result<()> {
-    write_calendar_file(&format!("{calendar_name} {year}"), events, path)
+    let content =
+        std::fs::read(path).with_context(|| format!("failed to read ics {}", path.display()))?;
+    for compression in &publish.compress {
+        let mut variant = target.as_os_str().to_owned();
+        variant.push(format!(".{}", compression.extension()));
+        let variant = std::path::PathBuf::from(variant);
+        let compressed = compress(*compression, &content)
+            .with_context(|| format!("failed to compress {}", path.display()))?;
+        std::fs::write(&variant, compressed)
+            .with_context(|| format!("failed to write {}", variant.display()))?;
+    }
+    Ok(())
+}
+
+fn compress(compression: Compression, content: &[u8]) -> Result<Vec<u8>> {
+    match compression {
+        Compression::Gzip => {
+            let mut encoder =
+                flate2::GzBuilder::new().write(Vec::new(), flate2::Compression::best());
+            encoder.write_all(content)?;
+            Ok(encoder.finish()?)
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(zstd::encode_all(content, 19)?),
+        #[cfg(not(feature = "zstd"))]
+        Compression::Zstd => anyhow::bail!("zstd compression needs the zstd feature"),
+    }
+}
+
+fn write_calendar_file(
+    calendar_name: &str,
+    events: &[&EventRecord],
+    publish: &PublishConfig,
+    path: &Path,
+) -> Result<bool> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create output dir {}", parent.display()))?;
+    }
+
+    // Stream into a sibling temp file while hashing, so memory stays flat no
+    // matter how many events the year holds, then only replace the real file
+    // when the content differs. Unchanged calendars keep their mtime for
+    // HTTP caching.
+    let tmp_path = path.with_extension("ics.tmp");
+    let file = File::create(&tmp_path)
+        .with_context(|| format!("failed to create ics {}", tmp_path.display()))?;
+    let mut writer = HashingWriter::new(BufWriter::new(file));
+    write_calendar(&mut writer, calendar_name, events, publish)
+        .and_then(|_| writer.flush())
+        .with_context(|| format!("failed to write ics {}", tmp_path.display()))?;
+    let digest = writer.finish();
+
+    if file_digest(path)? == Some(digest) {
+        std::fs::remove_file(&tmp_path)
+            .with_context(|| format!("failed to remove {}", tmp_path.display()))?;
+        return Ok(false);
+    }
+
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("failed to write ics {}", path.display()))?;
+    Ok(true)
+}
+
+/// SHA-256 of a file's content, or `None` when it does not exist.
+pub fn file_digest(path: &Path) -> Result<Option<[u8; 32]>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to open {}", path.display()));
+        }
+    };
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(Some(hasher.finalize().into()))
+}
+
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    fn finish(self) -> [u8; 32] {
+        self.hasher.finalize().into()
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
 }
 
-fn write_calendar_file(calendar_name: &str, events: &[&EventRecord], path: &Path) -> Result<()> {
+fn render_calendar(
+    calendar_name: &str,
+    events: &[&EventRecord],
+    publish: &PublishConfig,
+) -> String {
+    let mut out = Vec::new();
+    write_calendar(&mut out, calendar_name, events, publish).expect("writing to a Vec cannot fail");
+    String::from_utf8(out).expect("calendar output is valid UTF-8")
+}
+
+/// Writes the calendar one event at a time; only a single event's folded
+/// lines are held in memory.
+fn write_calendar<W: Write>(
+    out: &mut W,
+    calendar_name: &str,
+    events: &[&EventRecord],
+    publish: &PublishConfig,
+) -> io::Result<()> {
     let mut lines = Vec::new();
     push_line(&mut lines, "BEGIN:VCALENDAR".to_string());
     push_line(&mut lines, "VERSION:2.0".to_string());
@@ -37,25 +275,26 @@ fn write_calendar_file(calendar_name: &str, events: &[&EventRecord], path: &Path
         format!("X-WR-CALNAME:{}", escape_text(calendar_name)),
     );
     push_line(&mut lines, "X-WR-TIMEZONE:UTC".to_string());
+    write_lines(out, &mut lines)?;
 
     for event in events {
-        append_event_lines(&mut lines, event);
+        append_event_lines(&mut lines, event, publish);
+        write_lines(out, &mut lines)?;
     }
 
     push_line(&mut lines, "END:VCALENDAR".to_string());
+    write_lines(out, &mut lines)
+}
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .with_context(|| format!("failed to create output dir {}", parent.display()))?;
+fn write_lines<W: Write>(out: &mut W, lines: &mut Vec<String>) -> io::Result<()> {
+    for line in lines.drain(..) {
+        out.write_all(line.as_bytes())?;
+        out.write_all(b"\r\n")?;
     }
-
-    std::fs::write(path, lines.join("\r\n") + "\r\n")
-        .with_context(|| format!("failed to write ics {}", path.display()))?;
-
     Ok(())
 }
 
-fn append_event_lines(lines: &mut Vec<String>, event: &EventRecord) {
+fn append_event_lines(lines: &mut Vec<String>, event: &EventRecord, publish: &PublishConfig) {
     push_line(lines, "BEGIN:VEVENT".to_string());
     push_line(lines, format!("UID:{}", escape_text(&event.uid)));
     push_line(
@@ -69,13 +308,41 @@ fn append_event_lines(lines: &mut Vec<String>, event: &EventRecord) {
     );
     push_line(lines, format!("SEQUENCE:{}", event.sequence));
 
+    let floating = publish.time_basis == TimeBasis::Floating;
     match &event.time {
-        EventTimeSpec::DateTime { start, end } => {
-            push_line(lines, format!("DTSTART:{}", format_utc(*start)));
-            if let Some(end) = end {
-                push_line(lines, format!("DTEND:{}", format_utc(*end)));
+        EventTimeSpec::DateTime { start, end, local } if floating => {
+            let local = local.as_ref();
+            let start = local.map_or_else(|| floating_time(*start, None), |local| local.start);
+            push_line(lines, format!("DTSTART:{}", format_local(start)));
+            if let Some(end) = local
+                .and_then(|local| local.end)
+                .or_else(|| end.map(|end| floating_time(end, local)))
+            {
+                push_line(lines, format!("DTEND:{}", format_local(end)));
             }
         }
+        EventTimeSpec::DateTime { start, end, local } => match local {
+            Some(local) if publish.time_basis == TimeBasis::Local => {
+                push_line(
+                    lines,
+                    format!("DTSTART;TZID={}:{}", local.tzid, format_local(local.start)),
+                );
+                if let Some(local_end) = local.end {
+                    push_line(
+                        lines,
+                        format!("DTEND;TZID={}:{}", local.tzid, format_local(local_end)),
+                    );
+                } else if let Some(end) = end {
+                    push_line(lines, format!("DTEND:{}", format_utc(*end)));
+                }
+            }
+            _ => {
+                push_line(lines, format!("DTSTART:{}", format_utc(*start)));
+                if let Some(end) = end {
+                    push_line(lines, format!("DTEND:{}", format_utc(*end)));
+                }
+            }
+        },
         EventTimeSpec::Date { start, end } => {
             push_line(lines, format!("DTSTART;VALUE=DATE:{}", format_date(*start)));
             let exclusive_end = end.unwrap_or(*start).succ_opt().unwrap_or(*start);
@@ -116,15 +383,125 @@ fn append_event_lines(lines: &mut Vec<String>, event: &EventRecord) {
         }
     }
 
-    push_line(lines, format!("SUMMARY:{}", escape_text(&event.title)));
+    if let Some(recurrence_id) = &event.recurrence_id {
+        push_line(
+            lines,
+            format_recurrence_date("RECURRENCE-ID", recurrence_id, event, floating),
+        );
+    }
+    if let Some(rrule) = &event.rrule {
+        push_line(lines, format!("RRULE:{rrule}"));
+    }
+    for exdate in &event.exdates {
+        push_line(
+            lines,
+            format_recurrence_date("EXDATE", exdate, event, floating),
+        );
+    }
+    for related in &event.related_uids {
+        if event.supersedes.as_ref() == Some(related) {
+            continue;
+        }
+        push_line(
+            lines,
+            format!("RELATED-TO;RELTYPE=SIBLING:{}", escape_text(related)),
+        );
+    }
+    if let Some(supersedes) = &event.supersedes {
+        push_line(
+            lines,
+            format!(
+                "RELATED-TO;RELTYPE=X-RICS-SUPERSEDES:{}",
+                escape_text(supersedes)
+            ),
+        );
+    }
+
+    let language = publish.language.as_deref();
+    let language_param = language
+        .filter(|lang| event.translations.contains_key(*lang))
+        .map(|lang| format!(";LANGUAGE={lang}"))
+        .unwrap_or_default();
+    push_line(
+        lines,
+        format!(
+            "SUMMARY{language_param}:{}",
+            escape_text(&publish.redact(event.title_in(language)))
+        ),
+    );
 
-    if let Some(description) = &event.description {
-        push_line(lines, format!("DESCRIPTION:{}", escape_text(description)));
+    let reschedule_note = if publish.describe_reschedules {
+        event.rescheduled_from().map(|previous| {
+            let current_year = event.time.start_date().map(|date| date.year());
+            if current_year == Some(previous.year()) {
+                format!("Rescheduled from {}", previous.format("%-d %B"))
+            } else {
+                format!("Rescheduled from {}", previous.format("%-d %B %Y"))
+            }
+        })
+    } else {
+        None
+    };
+    let holiday_note = if publish.describe_holidays {
+        match (event.metadata.get("holiday"), event.metadata.get("weekend")) {
+            (Some(holiday), _) => {
+                Some(format!("Warning: scheduled on a public holiday, {holiday}"))
+            }
+            (None, Some(day)) => Some(format!("Warning: scheduled on a weekend ({day})")),
+            (None, None) => None,
+        }
+    } else {
+        None
+    };
+    let notes = [reschedule_note, holiday_note]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let notes = (!notes.is_empty()).then(|| notes.join("\n"));
+    let description = match (event.description_in(language), notes) {
+        (Some(description), Some(note)) => Some(format!("{description}\n\n{note}")),
+        (Some(description), None) => Some(description.to_string()),
+        (None, note) => note,
+    };
+    if let Some(description) = description {
+        push_line(
+            lines,
+            format!(
+                "DESCRIPTION{language_param}:{}",
+                escape_text(&publish.redact(&description))
+            ),
+        );
+    }
+    for (lang, text) in &event.translations {
+        if Some(lang.as_str()) == language {
+            continue;
+        }
+        if let Some(title) = &text.title {
+            push_line(
+                lines,
+                format!(
+                    "X-RICS-SUMMARY;LANGUAGE={lang}:{}",
+                    escape_text(&publish.redact(title))
+                ),
+            );
+        }
+        if let Some(description) = &text.description {
+            push_line(
+                lines,
+                format!(
+                    "X-RICS-DESCRIPTION;LANGUAGE={lang}:{}",
+                    escape_text(&publish.redact(description))
+                ),
+            );
+        }
     }
 
-    if let Some(url) = &event.source_url {
+    if let Some(url) = event.published_source_url() {
         push_line(lines, format!("URL:{}", escape_text(url)));
     }
+    for attachment in &event.attachments {
+        push_line(lines, format!("ATTACH:{}", attachment.published_url()));
+    }
 
     if !event.categories.is_empty() {
         let mut categories = event
@@ -170,6 +547,9 @@ fn append_event_lines(lines: &mut Vec<String>, event: &EventRecord) {
             event.time.precision().to_ascii_uppercase()
         ),
     );
+    if event.dead_link.is_some() {
+        push_line(lines, "X-RICS-LINK-STATUS:DEAD".to_string());
+    }
     push_line(
         lines,
         format!("X-RICS-REVISION-HASH:{}", event.revision_hash),
@@ -179,25 +559,14 @@ fn append_event_lines(lines: &mut Vec<String>, event: &EventRecord) {
         if key.is_empty() || value.is_empty() {
             continue;
         }
-        let x_key = format!("X-RICS-{}", sanitize_x_key(key));
-        push_line(lines, format!("{x_key}:{}", escape_text(value)));
+        if let Some(x_key) = publish.x_properties.property_for(key) {
+            push_line(lines, format!("{x_key}:{}", escape_text(value)));
+        }
     }
 
     push_line(lines, "END:VEVENT".to_string());
 }
 
-fn sanitize_x_key(raw: &str) -> String {
-    raw.chars()
-        .map(|c| {
-            if c.is_ascii_alphanumeric() {
-                c.to_ascii_uppercase()
-            } else {
-                '-'
-            }
-        })
-        .collect()
-}
-
 fn push_line(lines: &mut Vec<String>, line: String) {
     for folded in fold_line(&line) {
         lines.push(folded);
@@ -250,6 +619,10 @@ fn format_utc(value: chrono::DateTime<Utc>) -> String {
     )
 }
 
+fn format_local(value: chrono::NaiveDateTime) -> String {
+    value.format("%Y%m%dT%H%M%S").to_string()
+}
+
 fn format_date(value: chrono::NaiveDate) -> String {
     format!("{:04}{:02}{:02}", value.year(), value.month(), value.day())
 }
@@ -261,3 +634,35 @@ fn escape_text(value: &str) -> String {
         .replace(',', "\\,")
         .replace('\n', "\\n")
 }
+
+/// `value` as wall-clock time in `local`'s zone when known, in UTC
+/// otherwise.
+fn floating_time(value: chrono::DateTime<Utc>, local: Option<&LocalTime>) -> NaiveDateTime {
+    local
+        .and_then(|local| local.tzid.parse::<Tz>().ok())
+        .map_or_else(
+            || value.naive_utc(),
+            |tz| value.with_timezone(&tz).naive_local(),
+        )
+}
+
+/// A RECURRENCE-ID or EXDATE line; floating when `event`'s DTSTART is, so
+/// the two compare equal.
+fn format_recurrence_date(
+    property: &str,
+    value: &RecurrenceDate,
+    event: &EventRecord,
+    floating: bool,
+) -> String {
+    match value {
+        RecurrenceDate::DateTime(dt) if floating => {
+            let local = match &event.time {
+                EventTimeSpec::DateTime { local, .. } => local.as_ref(),
+                _ => None,
+            };
+            format!("{property}:{}", format_local(floating_time(*dt, local)))
+        }
+        RecurrenceDate::DateTime(dt) => format!("{property}:{}", format_utc(*dt)),
+        RecurrenceDate::Date(date) => format!("{property};VALUE=DATE:{}", format_date(*date)),
+    }
+}
@@ -0,0 +1,277 @@
+//! Minimal IMAP reader for `fetch.mode = "imap"`.
+//!
+//! Only what the fetch mode needs is implemented: LOGIN, EXAMINE (read-only,
+//! so `\Seen` flags are left alone), UID SEARCH and UID FETCH of whole
+//! messages. MIME decoding is handled by `mail-parser`.
+
+use crate::config::{ExtractFormat, ImapConfig, LoadedSource};
+use crate::fetch::FetchedDocument;
+use crate::network::connect_checked;
+use anyhow::{Context, Result, bail};
+use chrono::{Duration, NaiveDate, Utc};
+use mail_parser::{Message, MessageParser, MimeHeaders};
+use native_tls::TlsConnector;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use tracing::{debug, info};
+
+pub fn fetch_imap_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
+    let imap = source
+        .config
+        .fetch
+        .imap
+        .as_ref()
+        .context("fetch.imap missing")?;
+    let password = std::env::var(&imap.password_env).with_context(|| {
+        format!(
+            "imap password env var {} is not set for source {}",
+            imap.password_env, source.config.source.key
+        )
+    })?;
+
+    let timeout = std::time::Duration::from_secs(source.config.fetch.timeout_secs);
+    let tcp = connect_checked(source, &imap.host, imap.port, timeout)
+        .with_context(|| format!("failed to connect to imap {}:{}", imap.host, imap.port))?;
+    tcp.set_read_timeout(Some(timeout))?;
+    tcp.set_write_timeout(Some(timeout))?;
+
+    let messages = if imap.tls {
+        let connector = TlsConnector::new().context("failed to build tls connector")?;
+        let stream = connector
+            .connect(&imap.host, tcp)
+            .with_context(|| format!("tls handshake with {} failed", imap.host))?;
+        read_messages(ImapSession::new(stream)?, imap, &password)?
+    } else {
+        read_messages(ImapSession::new(tcp)?, imap, &password)?
+    };
+
+    let fetched_at = Utc::now();
+    let mut docs = Vec::new();
+    for (uid, raw) in messages {
+        let Some(message) = MessageParser::default().parse(&raw) else {
+            continue;
+        };
+        // SEARCH matching is up to the server and some are lax about it, so
+        // the filters are checked again on the parsed headers.
+        if !matches_filters(&message, imap) {
+            debug!(uid, folder = %imap.folder, "imap message does not match filters; skipped");
+            continue;
+        }
+        let source_url = format!("imap://{}/{};UID={uid}", imap.host, imap.folder);
+        for body in message_bodies(&message, source.config.extract.format) {
+            docs.push(FetchedDocument {
+                source_url: source_url.clone(),
+                body: body.into(),
+                page_index: docs.len(),
+                fetched_at: Some(fetched_at),
+                headers: BTreeMap::new(),
+            });
+        }
+    }
+
+    info!(
+        source = %source.config.source.key,
+        folder = %imap.folder,
+        documents = docs.len(),
+        "imap fetch complete"
+    );
+    Ok(docs)
+}
+
+fn read_messages<S: Read + Write>(
+    mut session: ImapSession<S>,
+    imap: &ImapConfig,
+    password: &str,
+) -> Result<Vec<(u32, Vec<u8>)>> {
+    session
+        .command(&format!(
+            "LOGIN {} {}",
+            quote(&imap.username),
+            quote(password)
+        ))
+        .context("imap login failed")?;
+    session
+        .command(&format!("EXAMINE {}", quote(&imap.folder)))
+        .with_context(|| format!("failed to open imap folder {}", imap.folder))?;
+
+    let mut criteria = Vec::new();
+    if let Some(since) = since_date(imap) {
+        criteria.push(format!("SINCE {}", since.format("%-d-%b-%Y")));
+    }
+    if let Some(from) = &imap.from {
+        criteria.push(format!("FROM {}", quote(from)));
+    }
+    if let Some(subject) = &imap.subject {
+        criteria.push(format!("SUBJECT {}", quote(subject)));
+    }
+    if criteria.is_empty() {
+        criteria.push("ALL".to_string());
+    }
+
+    let search = session.command(&format!("UID SEARCH {}", criteria.join(" ")))?;
+    let mut uids = search
+        .lines
+        .iter()
+        .filter_map(|line| line.strip_prefix("* SEARCH"))
+        .flat_map(|rest| rest.split_whitespace())
+        .filter_map(|uid| uid.parse::<u32>().ok())
+        .collect::<Vec<_>>();
+    uids.sort_unstable();
+    let skip = uids.len().saturating_sub(imap.max_messages);
+    let uids = &uids[skip..];
+    debug!(folder = %imap.folder, matched = uids.len(), "imap search complete");
+
+    let mut messages = Vec::new();
+    for uid in uids {
+        let response = session.command(&format!("UID FETCH {uid} BODY.PEEK[]"))?;
+        if let Some(raw) = response.literals.into_iter().next() {
+            messages.push((*uid, raw));
+        }
+    }
+
+    // Best effort; the messages are already in hand.
+    let _ = session.command("LOGOUT");
+    Ok(messages)
+}
+
+fn since_date(imap: &ImapConfig) -> Option<NaiveDate> {
+    let relative = imap
+        .since_days
+        .map(|days| Utc::now().date_naive() - Duration::days(i64::from(days)));
+    match (imap.since, relative) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Whether the sender and subject contain `from` and `subject`, ignoring case.
+/// The sender matches on either its display name or its address.
+fn matches_filters(message: &Message, imap: &ImapConfig) -> bool {
+    let contains =
+        |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+    if let Some(from) = &imap.from {
+        let sender_matches = message.from().is_some_and(|senders| {
+            senders.iter().any(|sender| {
+                sender.name().is_some_and(|name| contains(name, from))
+                    || sender
+                        .address()
+                        .is_some_and(|address| contains(address, from))
+            })
+        });
+        if !sender_matches {
+            return false;
+        }
+    }
+    if let Some(subject) = &imap.subject
+        && !message
+            .subject()
+            .is_some_and(|actual| contains(actual, subject))
+    {
+        return false;
+    }
+    true
+}
+
+/// Parts of a message that the source's extractor can read.
+fn message_bodies(message: &Message, format: ExtractFormat) -> Vec<Vec<u8>> {
+    match format {
+        ExtractFormat::Html => message
+            .body_html(0)
+            .map(|html| vec![html.into_owned().into_bytes()])
+            .unwrap_or_default(),
+        ExtractFormat::Text => message
+            .body_text(0)
+            .map(|text| vec![text.into_owned().into_bytes()])
+            .unwrap_or_default(),
+        ExtractFormat::PdfText | ExtractFormat::Json | ExtractFormat::Feed => {
+            // Suffix match, so `rss+xml` and `atom+xml` count as xml.
+            let (subtype, extension) = match format {
+                ExtractFormat::PdfText => ("pdf", ".pdf"),
+                ExtractFormat::Feed => ("xml", ".xml"),
+                _ => ("json", ".json"),
+            };
+            message
+                .attachments()
+                .filter(|part| {
+                    part.content_type().is_some_and(|ct| {
+                        ct.subtype()
+                            .is_some_and(|st| st.to_ascii_lowercase().ends_with(subtype))
+                    }) || part
+                        .attachment_name()
+                        .is_some_and(|name| name.to_ascii_lowercase().ends_with(extension))
+                })
+                .map(|part| part.contents().to_vec())
+                .collect()
+        }
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[derive(Default)]
+struct Response {
+    lines: Vec<String>,
+    literals: Vec<Vec<u8>>,
+}
+
+struct ImapSession<S: Read + Write> {
+    stream: BufReader<S>,
+    next_tag: u32,
+}
+
+impl<S: Read + Write> ImapSession<S> {
+    fn new(stream: S) -> Result<Self> {
+        let mut session = Self {
+            stream: BufReader::new(stream),
+            next_tag: 1,
+        };
+        let greeting = session.read_line()?;
+        if !greeting.starts_with("* OK") && !greeting.starts_with("* PREAUTH") {
+            bail!("unexpected imap greeting: {greeting}");
+        }
+        Ok(session)
+    }
+
+    fn command(&mut self, command: &str) -> Result<Response> {
+        let tag = format!("a{}", self.next_tag);
+        self.next_tag += 1;
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{tag} {command}\r\n").as_bytes())?;
+        stream.flush()?;
+
+        let mut response = Response::default();
+        loop {
+            let line = self.read_line()?;
+            if let Some(status) = line.strip_prefix(&format!("{tag} ")) {
+                if status.starts_with("OK") {
+                    return Ok(response);
+                }
+                let verb = command.split_whitespace().next().unwrap_or(command);
+                bail!("imap {verb} failed: {status}");
+            }
+            if let Some(size) = literal_size(&line) {
+                let mut literal = vec![0; size];
+                self.stream.read_exact(&mut literal)?;
+                response.literals.push(literal);
+            }
+            response.lines.push(line);
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String> {
+        let mut buf = Vec::new();
+        let read = self.stream.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            bail!("imap server closed the connection");
+        }
+        Ok(String::from_utf8_lossy(&buf).trim_end().to_string())
+    }
+}
+
+/// Size of a `{N}` literal announced at the end of a response line.
+fn literal_size(line: &str) -> Option<usize> {
+    let open = line.rfind('{')?;
+    line[open + 1..].strip_suffix('}')?.parse().ok()
+}
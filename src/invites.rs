@@ -0,0 +1,164 @@
+//! Calendar invites for important events.
+//!
+//! Sources with `[invites] recipients` send each qualifying insert or update
+//! as an iTIP `REQUEST` email and each cancellation as a `CANCEL`, so
+//! recipients get native invites that their mail client keeps current. Mail
+//! goes out only after state was saved.
+
+use crate::changes::ChangeKind;
+use crate::config::{SmtpConfig, SourceConfig};
+use crate::ics::render_itip_invite;
+use crate::model::EventRecord;
+use crate::store::EventStore;
+use crate::util::base64;
+use anyhow::Result;
+use chrono::Utc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InviteMethod {
+    Request,
+    Cancel,
+}
+
+impl InviteMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InviteMethod::Request => "REQUEST",
+            InviteMethod::Cancel => "CANCEL",
+        }
+    }
+}
+
+/// A rendered invite email.
+#[derive(Debug, Clone)]
+pub struct Invite {
+    pub method: InviteMethod,
+    pub uid: String,
+    pub recipients: Vec<String>,
+    /// Complete RFC 5322 message, CRLF line endings.
+    pub message: String,
+}
+
+/// Invites for the source's `changes`: a REQUEST for every new or updated
+/// event and a CANCEL for every cancellation. TBD events and events below
+/// `invites.min_importance` are skipped.
+pub fn pending_invites(
+    source: &SourceConfig,
+    store: &dyn EventStore,
+    changes: &[(ChangeKind, String)],
+    organizer: &str,
+) -> Vec<Invite> {
+    let config = &source.invites;
+    let mut invites = Vec::new();
+    for (kind, key) in changes {
+        let method = match kind {
+            ChangeKind::Created | ChangeKind::Updated => InviteMethod::Request,
+            ChangeKind::Cancelled => InviteMethod::Cancel,
+            ChangeKind::Expired | ChangeKind::Removed => continue,
+        };
+        let Some(event) = store.get(key) else {
+            continue;
+        };
+        if event.is_tbd() || event.importance.unwrap_or(0) < config.min_importance {
+            continue;
+        }
+        invites.push(Invite {
+            method,
+            uid: event.uid.clone(),
+            recipients: config.recipients.clone(),
+            message: invite_message(event, method, organizer, &config.recipients, source),
+        });
+    }
+    invites
+}
+
+/// A multipart email with a plain-text summary and the iTIP calendar part.
+pub fn invite_message(
+    event: &EventRecord,
+    method: InviteMethod,
+    organizer: &str,
+    recipients: &[String],
+    source: &SourceConfig,
+) -> String {
+    let ics = render_itip_invite(
+        event,
+        method.as_str(),
+        organizer,
+        recipients,
+        &source.publish,
+    );
+    let subject = match method {
+        InviteMethod::Request => format!("Invitation: {}", event.title),
+        InviteMethod::Cancel => format!("Cancelled: {}", event.title),
+    };
+    let when = event
+        .time
+        .start_date()
+        .map(|date| date.to_string())
+        .unwrap_or_default();
+    let mut text = format!("{}\r\n{when}\r\n", event.title);
+    if let Some(description) = &event.description {
+        text.push_str(&format!("\r\n{}\r\n", description.replace('\n', "\r\n")));
+    }
+    let boundary = format!(
+        "rics-{}",
+        &event.revision_hash[..16.min(event.revision_hash.len())]
+    );
+
+    [
+        format!("From: {organizer}"),
+        format!("To: {}", recipients.join(", ")),
+        format!("Subject: {}", encode_header(&subject)),
+        format!("Date: {}", Utc::now().to_rfc2822()),
+        format!(
+            "Message-ID: <{}-{}@rics>",
+            event.store_key().replace(['<', '>', ' '], "-"),
+            event.sequence
+        ),
+        "MIME-Version: 1.0".to_string(),
+        format!("Content-Type: multipart/alternative; boundary=\"{boundary}\""),
+        String::new(),
+        format!("--{boundary}"),
+        "Content-Type: text/plain; charset=UTF-8".to_string(),
+        "Content-Transfer-Encoding: 8bit".to_string(),
+        String::new(),
+        text,
+        format!("--{boundary}"),
+        format!(
+            "Content-Type: text/calendar; charset=UTF-8; method={}",
+            method.as_str()
+        ),
+        "Content-Transfer-Encoding: 8bit".to_string(),
+        String::new(),
+        ics,
+        format!("--{boundary}--"),
+        String::new(),
+    ]
+    .join("\r\n")
+}
+
+/// Sends `invites` over one SMTP session. Returns how many were accepted.
+pub fn send_invites(smtp: &SmtpConfig, invites: &[Invite]) -> Result<usize> {
+    #[cfg(feature = "email")]
+    {
+        crate::smtp::send_messages(
+            smtp,
+            invites
+                .iter()
+                .map(|invite| (invite.recipients.as_slice(), invite.message.as_str())),
+        )
+    }
+    #[cfg(not(feature = "email"))]
+    {
+        let _ = (smtp, invites);
+        anyhow::bail!("invites are configured but rics was built without the `email` feature")
+    }
+}
+
+/// RFC 2047 encoding for header values that are not plain ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    format!("=?UTF-8?B?{}?=", base64(value.as_bytes()))
+}
@@ -1,8 +1,56 @@
+pub mod assets;
+pub mod body;
+#[cfg(feature = "browser")]
+pub mod browser;
+pub mod changes;
+pub mod charset;
 pub mod config;
+#[cfg(feature = "daemon")]
+pub mod daemon;
+pub mod diagnose;
+pub mod enrich;
+pub mod feed;
 pub mod fetch;
+pub mod ftp;
 pub mod harness;
+pub mod health;
+pub mod http_cache;
+pub mod holidays;
 pub mod ics;
+#[cfg(feature = "imap")]
+pub mod imap;
+pub mod invites;
+#[cfg(feature = "http")]
+pub mod links;
+pub mod manifest;
 pub mod model;
+pub mod network;
+pub mod normalize;
 pub mod parser;
 pub mod pipeline;
+pub mod preprocess;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "publishers")]
+pub mod publishers;
+pub mod quarantine;
+pub mod recording;
+pub mod redirects;
+pub mod regex_limits;
+pub mod robots;
+pub mod schedule;
+pub mod schema;
+pub mod search;
+pub mod sftp;
+#[cfg(feature = "email")]
+pub mod smtp;
+pub mod staging;
+pub mod stats;
 pub mod store;
+pub mod tenant;
+pub mod timezones;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod util;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,218 @@
+//! Liveness checks of stored `source_url`s (`rics check-links`).
+//!
+//! Agencies move pages, so links published with events rot. Each distinct
+//! URL gets a HEAD request, retried as a GET for servers that refuse HEAD,
+//! within its source's `fetch.rate_limit`. Only 404 and 410 count as dead:
+//! timeouts, 5xx and auth walls say nothing about whether the page is gone,
+//! so they are reported as unreachable and never annotated.
+
+use crate::config::{LoadedSource, load_sources_from_dir};
+use crate::fetch::{request_headers, source_client, template_substitutions, throttle};
+use crate::model::DeadLink;
+use crate::network::check_request;
+use crate::store::{EventQuery, EventStore, JsonFileStore};
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use rayon::prelude::*;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone)]
+pub struct CheckLinksOptions {
+    pub config_dir: PathBuf,
+    pub state_path: PathBuf,
+    pub source: Option<String>,
+    pub jobs: usize,
+    /// Mark events whose link is dead (and unmark ones that answer again),
+    /// so calendars carry `X-RICS-LINK-STATUS:DEAD` after the next build.
+    pub annotate: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LinkReport {
+    /// Distinct URLs requested.
+    pub checked: usize,
+    pub dead: Vec<LinkProblem>,
+    /// URLs that failed without saying the page is gone.
+    pub unreachable: Vec<LinkProblem>,
+    /// Events whose dead-link mark was added or cleared.
+    pub annotated: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkProblem {
+    pub source_key: String,
+    pub url: String,
+    /// Final HTTP status, `None` when no response arrived.
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Store keys of the events that link to the URL.
+    pub store_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LinkState {
+    Alive,
+    Dead(u16),
+    Unreachable(Option<u16>, Option<String>),
+}
+
+/// Checks the `source_url` of every stored, not cancelled event of the
+/// enabled sources and optionally saves dead-link marks to state.
+pub fn check_links(options: &CheckLinksOptions) -> Result<LinkReport> {
+    let sources = load_sources_from_dir(&options.config_dir)?
+        .into_iter()
+        .filter(|source| {
+            source.config.source.enabled
+                && options
+                    .source
+                    .as_ref()
+                    .is_none_or(|key| source.config.source.key == *key)
+        })
+        .map(|source| (source.config.source.key.clone(), source))
+        .collect::<HashMap<_, _>>();
+    if sources.is_empty() {
+        bail!("no matching source configurations found");
+    }
+
+    // Only annotating writes state, so plain checks need not wait for a sync.
+    let mut store = if options.annotate {
+        JsonFileStore::open_locked(&options.state_path)?
+    } else {
+        JsonFileStore::open(&options.state_path)?
+    };
+    // One request per URL and source, since sources differ in headers,
+    // proxies and rate limits.
+    let mut links: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+    for event in store.query(&EventQuery::default()) {
+        let Some(url) = &event.source_url else {
+            continue;
+        };
+        if !sources.contains_key(&event.source_key)
+            || !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            continue;
+        }
+        links
+            .entry((event.source_key.clone(), url.clone()))
+            .or_default()
+            .push(event.store_key());
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.jobs.max(1))
+        .build()
+        .context("failed to build link check thread pool")?;
+    let states = pool.install(|| {
+        links
+            .par_iter()
+            .map(|((source_key, url), _)| check_link(&sources[source_key], url))
+            .collect::<Vec<_>>()
+    });
+
+    let now = Utc::now();
+    let mut report = LinkReport {
+        checked: links.len(),
+        ..LinkReport::default()
+    };
+    for (((source_key, url), store_keys), state) in links.into_iter().zip(states) {
+        let dead = match &state {
+            LinkState::Alive => None,
+            LinkState::Dead(status) => Some(DeadLink {
+                url: url.clone(),
+                status: *status,
+                checked_at: now,
+            }),
+            LinkState::Unreachable(..) => {
+                warn!(source = %source_key, url = %url, "link unreachable; leaving its mark as is");
+                report
+                    .unreachable
+                    .push(problem(source_key, url, store_keys, state));
+                continue;
+            }
+        };
+        if options.annotate {
+            for key in &store_keys {
+                let Some(mut record) = store.get(key).cloned() else {
+                    continue;
+                };
+                if record.dead_link.is_some() != dead.is_some() {
+                    record.dead_link = dead.clone();
+                    store.upsert(record);
+                    report.annotated += 1;
+                }
+            }
+        }
+        if dead.is_some() {
+            warn!(source = %source_key, url = %url, events = store_keys.len(), "dead link");
+            report
+                .dead
+                .push(problem(source_key, url, store_keys, state));
+        }
+    }
+
+    if options.annotate && report.annotated > 0 {
+        store.save()?;
+        info!(
+            annotated = report.annotated,
+            "dead-link marks saved; run `rics build` to republish"
+        );
+    }
+    Ok(report)
+}
+
+fn problem(
+    source_key: String,
+    url: String,
+    store_keys: Vec<String>,
+    state: LinkState,
+) -> LinkProblem {
+    let (status, error) = match state {
+        LinkState::Alive => (None, None),
+        LinkState::Dead(status) => (Some(status), None),
+        LinkState::Unreachable(status, error) => (status, error),
+    };
+    LinkProblem {
+        source_key,
+        url,
+        status,
+        error,
+        store_keys,
+    }
+}
+
+fn check_link(source: &LoadedSource, url: &str) -> LinkState {
+    request_link(source, url)
+        .unwrap_or_else(|err| LinkState::Unreachable(None, Some(format!("{err:#}"))))
+}
+
+fn request_link(source: &LoadedSource, url: &str) -> Result<LinkState> {
+    let client = source_client(
+        source,
+        &request_headers(source, &template_substitutions(source))?,
+        Some(Duration::from_secs(source.config.fetch.timeout_secs)),
+    )?;
+    let classify = |status: StatusCode| match status {
+        status if status.is_success() || status.is_redirection() => LinkState::Alive,
+        StatusCode::NOT_FOUND | StatusCode::GONE => LinkState::Dead(status.as_u16()),
+        status => LinkState::Unreachable(Some(status.as_u16()), None),
+    };
+
+    check_request(source, url)?;
+    throttle(source, url);
+    let head = client
+        .head(url)
+        .send()
+        .map(|response| classify(response.status()));
+    if let Ok(state @ (LinkState::Alive | LinkState::Dead(_))) = head {
+        return Ok(state);
+    }
+    // Some servers answer HEAD with 403, 405 or 501, or not at all.
+    throttle(source, url);
+    Ok(classify(client.get(url).send()?.status()))
+}
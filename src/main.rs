@@ -1,10 +1,20 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
+use rics::config::{CONFIG_PATH_ENV, join_config_dirs};
+use rics::diagnose::{DiagnoseOptions, diagnose_source};
 use rics::harness::{HarnessOptions, run_harness};
+#[cfg(feature = "http")]
+use rics::links::{CheckLinksOptions, check_links};
+use rics::model::SourceRunReport;
 use rics::pipeline::{
-    BuildOptions, PublishOptions, SyncOptions, ValidateOptions, build_calendars,
-    publish_existing_calendars, sync_sources, validate_configs,
+    BuildOptions, DEFAULT_SYNC_JOBS, MonitorOptions, PublishOptions, SyncOptions, UidOptions,
+    ValidateOptions, build_calendars, load_state_for_read, monitor_sources, predict_uid,
+    publish_existing_calendars, render_calendar_to, sync_sources, validate_configs,
 };
+use rics::quarantine::{Quarantine, apply_quarantined, reject_quarantined};
+use rics::schema::{candidate_event_schema, parse_candidate_events};
+use rics::search::open_search_index;
+use rics::stats::{EventStats, event_stats, stats_csv};
 use std::path::PathBuf;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
@@ -12,8 +22,11 @@ use tracing_subscriber::EnvFilter;
 #[derive(Parser, Debug)]
 #[command(name = "rics", about = "Config-driven calendar ICS generator")]
 struct Cli {
-    #[arg(long, default_value = "configs/sources")]
-    config_dir: PathBuf,
+    /// Source config dir; repeat to layer dirs, later ones overriding
+    /// sources with the same key. Defaults to `RICS_CONFIG_PATH`, then
+    /// `configs/sources`.
+    #[arg(long = "config-dir")]
+    config_dirs: Vec<PathBuf>,
 
     #[arg(long, default_value = "data/state/events.json")]
     state_path: PathBuf,
@@ -21,10 +34,28 @@ struct Cli {
     #[arg(long, default_value = "data/out")]
     out_dir: PathBuf,
 
+    /// `json` emits one object per line with the run and source span
+    /// fields, for log shippers.
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum StatsFormat {
+    Text,
+    Json,
+    Csv,
+}
+
 #[derive(Subcommand, Debug)]
 enum Commands {
     Sync {
@@ -32,12 +63,49 @@ enum Commands {
         source: Option<String>,
         #[arg(long, default_value_t = false)]
         dry_run: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        /// Print parser warnings (missing fields, unmatched selectors) per
+        /// source.
+        #[arg(long, default_value_t = false)]
+        show_warnings: bool,
+        /// Sources to fetch and parse concurrently.
+        #[arg(long, default_value_t = DEFAULT_SYNC_JOBS)]
+        jobs: usize,
+        /// Save each record's matched HTML/JSON/text under
+        /// `<out-dir>/debug/raw/<source>/<uid>.*`.
+        #[arg(long, default_value_t = false)]
+        capture_raw: bool,
+        /// Save every http response under this directory, for sources
+        /// with `fetch.mode = "replay"` and `fetch.replay_dir` set to it.
+        #[arg(long)]
+        record: Option<PathBuf>,
+        /// Fetch sources even when their `fetch.min_interval` has not
+        /// passed.
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Fetch and parse without merging, and report what a sync would
+    /// change per source. Fails after reporting when any source failed.
+    Monitor {
+        #[arg(long)]
+        source: Option<String>,
+        #[arg(long, default_value_t = DEFAULT_SYNC_JOBS)]
+        jobs: usize,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        /// Exit with status 1 when any source differs from state.
+        #[arg(long, default_value_t = false)]
+        exit_code: bool,
     },
     Build {
         #[arg(long)]
         source: Option<String>,
         #[arg(long)]
         year: Option<i32>,
+        /// Print the source's calendar for `--year` instead of writing files.
+        #[arg(long, requires_all = ["source", "year"])]
+        stdout: bool,
     },
     Publish {
         #[arg(long)]
@@ -49,49 +117,330 @@ enum Commands {
         #[arg(long)]
         source_file: Option<PathBuf>,
     },
+    /// Print the JSON Schema of candidate events, the format custom parsers
+    /// emit.
+    Schema,
+    /// Check a JSON array of candidate events (`-` reads stdin) against the
+    /// schema and report every problem by event index and field.
+    ValidateEvents {
+        file: PathBuf,
+    },
+    /// Print stored events (including provenance) whose UID starts with the
+    /// given prefix.
+    Show {
+        uid: String,
+    },
+    /// Print the UID a sync gives an event, for override patches and CalDAV
+    /// mappings. Without --id or --url the event is taken to have no URL of
+    /// its own.
+    Uid {
+        #[arg(long)]
+        source: String,
+        #[arg(long)]
+        title: String,
+        /// Start date (YYYY-MM-DD); omit for undated events.
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+        /// The event's source ID, when its config maps one.
+        #[arg(long)]
+        id: Option<String>,
+        /// The event's URL, when its config maps one.
+        #[arg(long)]
+        url: Option<String>,
+    },
     Harness,
+    /// Search stored events with the full-text index kept next to the
+    /// state file, or list likely cross-source duplicates.
+    Query {
+        /// Words that must all appear in the title, description or
+        /// metadata; the last one may be a prefix.
+        #[arg(long, required_unless_present = "duplicates")]
+        text: Option<String>,
+        #[arg(long)]
+        source: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Same-day events from different sources with similar titles.
+        #[arg(long, default_value_t = false, conflicts_with = "text")]
+        duplicates: bool,
+        #[arg(long, default_value_t = 0.8)]
+        min_similarity: f64,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Count stored events by source, year, month, category, importance,
+    /// precision and status, with each source's change over its latest
+    /// sync.
+    Stats {
+        #[arg(long)]
+        source: Option<String>,
+        #[arg(long, value_enum, default_value_t = StatsFormat::Text)]
+        format: StatsFormat,
+    },
+    /// Inspect the sync history kept in state.
+    Runs {
+        #[command(subcommand)]
+        action: RunsAction,
+    },
+    /// Review updates held back by a source's `[quarantine]` thresholds.
+    Quarantine {
+        #[command(subcommand)]
+        action: QuarantineAction,
+    },
+    /// Suggest root selectors for an html source whose markup changed, from
+    /// repeated sibling elements that contain dates.
+    SuggestSelectors {
+        source: String,
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Request every stored event's source URL and report dead links.
+    #[cfg(feature = "http")]
+    CheckLinks {
+        #[arg(long)]
+        source: Option<String>,
+        /// URLs to check concurrently.
+        #[arg(long, default_value_t = DEFAULT_SYNC_JOBS)]
+        jobs: usize,
+        /// Mark events with dead links in state, and clear marks of links
+        /// that answer again; run `rics build` afterwards.
+        #[arg(long, default_value_t = false)]
+        annotate: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        /// Exit with status 1 when any link is dead.
+        #[arg(long, default_value_t = false)]
+        exit_code: bool,
+    },
+    /// Browse sources, upcoming events, recent changes and per-source
+    /// health in the terminal; `s` syncs the selected source.
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Show a source as stale after this long without a successful
+        /// sync.
+        #[arg(long, default_value_t = 86_400)]
+        stale_after_secs: u64,
+    },
+    /// Keep running: sync every source on an interval and on inbound
+    /// webhook/MQTT triggers.
+    #[cfg(feature = "daemon")]
+    Daemon {
+        /// Seconds between full syncs; 0 disables periodic syncing.
+        #[arg(long, default_value_t = 3600)]
+        interval_secs: u64,
+        /// Webhook listener address, e.g. 127.0.0.1:8787.
+        #[arg(long)]
+        listen: Option<String>,
+        /// Env var holding the bearer token webhook requests must carry.
+        #[arg(long, default_value = "RICS_WEBHOOK_TOKEN")]
+        webhook_token_env: String,
+        /// Sources to fetch and parse concurrently per sync.
+        #[arg(long, default_value_t = DEFAULT_SYNC_JOBS)]
+        jobs: usize,
+        /// Report a source stale after this long without a successful sync.
+        /// Defaults to three intervals, or a day without periodic syncing.
+        #[arg(long)]
+        stale_after_secs: Option<u64>,
+        #[cfg(feature = "mqtt")]
+        #[arg(long)]
+        mqtt_host: Option<String>,
+        #[cfg(feature = "mqtt")]
+        #[arg(long, default_value_t = 1883)]
+        mqtt_port: u16,
+        #[cfg(feature = "mqtt")]
+        #[arg(long, default_value = "rics/sync")]
+        mqtt_topic: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum RunsAction {
+    /// List recent runs, newest first.
+    List {
+        /// Only runs that processed this source, with its counts.
+        #[arg(long)]
+        source: Option<String>,
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum QuarantineAction {
+    /// List pending changes.
+    List {
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Merge a held change into state; run `rics build` afterwards.
+    Apply { id: String },
+    /// Discard a held change; later syncs ignore the same revision.
+    Reject { id: String },
 }
 
 fn main() -> Result<()> {
-    init_tracing()?;
     let cli = Cli::parse();
+    init_tracing(cli.log_format)?;
+    let config_dir = resolve_config_dir(&cli.config_dirs)?;
 
     match cli.command {
-        Commands::Sync { source, dry_run } => {
+        Commands::Sync {
+            source,
+            dry_run,
+            json,
+            show_warnings,
+            jobs,
+            capture_raw,
+            record,
+            force,
+        } => {
             let reports = sync_sources(&SyncOptions {
-                config_dir: cli.config_dir,
+                config_dir,
                 state_path: cli.state_path,
                 out_dir: cli.out_dir,
                 source,
                 dry_run,
+                jobs,
+                capture_raw,
+                record_dir: record,
+                force,
+                tenant: Default::default(),
             })?;
 
-            for report in reports {
+            for report in &reports {
                 info!(
                     source = %report.source_key,
                     pages = report.pages_fetched,
                     parsed = report.records_parsed,
+                    skipped = report.skipped_records.len(),
+                    warnings = report.warnings.len(),
                     inserted = report.inserted,
                     updated = report.updated,
                     unchanged = report.unchanged,
                     cancelled = report.cancelled,
+                    expired = report.expired,
+                    purged = report.purged,
+                    quarantined = report.quarantined,
+                    invites = report.invites,
+                    fetch_ms = report.fetch_duration_ms,
+                    bytes = report.bytes_downloaded,
                     "source sync summary"
                 );
             }
+
+            if show_warnings {
+                for report in reports.iter().filter(|r| !r.warnings.is_empty()) {
+                    println!("{}:", report.source_key);
+                    for warning in &report.warnings {
+                        println!("  warning: {warning}");
+                    }
+                }
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            }
+
+            let (rolled_back, failed): (Vec<_>, Vec<_>) = reports
+                .iter()
+                .filter(|report| report.error.is_some())
+                .partition(|report| report.rolled_back);
+            let keys = |reports: Vec<&SourceRunReport>| {
+                reports
+                    .iter()
+                    .map(|report| report.source_key.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            match (failed.is_empty(), rolled_back.is_empty()) {
+                (true, true) => {}
+                (false, true) => bail!("fetching or parsing failed for {}", keys(failed)),
+                (true, false) => bail!(
+                    "merging failed for {}; their state changes were rolled back",
+                    keys(rolled_back)
+                ),
+                (false, false) => bail!(
+                    "fetching or parsing failed for {}; merging failed for {}, whose state changes were rolled back",
+                    keys(failed),
+                    keys(rolled_back)
+                ),
+            }
         }
-        Commands::Build { source, year } => {
-            build_calendars(&BuildOptions {
-                config_dir: cli.config_dir,
+        Commands::Monitor {
+            source,
+            jobs,
+            json,
+            exit_code,
+        } => {
+            let reports = monitor_sources(&MonitorOptions {
+                config_dir,
+                state_path: cli.state_path,
+                source,
+                jobs,
+                tenant: Default::default(),
+            })?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            } else {
+                for report in &reports {
+                    let status = if let Some(error) = &report.error {
+                        format!("failed: {error}")
+                    } else if report.not_modified {
+                        "not modified".to_string()
+                    } else if report.differs() {
+                        format!(
+                            "{} changes, {} quarantined",
+                            report.changes.len(),
+                            report.quarantined
+                        )
+                    } else {
+                        "unchanged".to_string()
+                    };
+                    println!("{}: {status}", report.source_key);
+                    for change in &report.changes {
+                        println!("  {} {}", change.kind, change.title);
+                    }
+                }
+            }
+            let failed = reports
+                .iter()
+                .filter(|report| report.error.is_some())
+                .map(|report| report.source_key.as_str())
+                .collect::<Vec<_>>();
+            if !failed.is_empty() {
+                bail!("monitor failed for {}", failed.join(", "));
+            }
+            if exit_code && reports.iter().any(|report| report.differs()) {
+                std::process::exit(1);
+            }
+        }
+        Commands::Build {
+            source,
+            year,
+            stdout,
+        } => {
+            let options = BuildOptions {
+                config_dir,
                 state_path: cli.state_path,
                 out_dir: cli.out_dir,
                 source,
                 year,
-            })?;
-            info!("build complete");
+            };
+            if stdout {
+                let events = render_calendar_to(&options, &mut std::io::stdout().lock())?;
+                info!(events, "calendar rendered");
+            } else {
+                build_calendars(&options)?;
+                info!("build complete");
+            }
         }
         Commands::Publish { source, year } => {
             let count = publish_existing_calendars(&PublishOptions {
-                config_dir: cli.config_dir,
+                config_dir,
                 out_dir: cli.out_dir,
                 source,
                 year,
@@ -100,34 +449,435 @@ fn main() -> Result<()> {
         }
         Commands::Validate { source_file } => {
             let messages = validate_configs(&ValidateOptions {
-                config_dir: Some(cli.config_dir),
+                config_dir: Some(config_dir),
                 source_file,
             })?;
             for line in messages {
                 println!("{line}");
             }
         }
+        Commands::Schema => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&candidate_event_schema())?
+            );
+        }
+        Commands::ValidateEvents { file } => {
+            let json = if file.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(&file)?
+            };
+            let events = parse_candidate_events(&json)?;
+            println!("{} candidate events are valid", events.len());
+        }
+        Commands::Show { uid } => {
+            let state = load_state_for_read(&cli.state_path)?;
+            let matches = state
+                .events
+                .iter()
+                .filter(|(key, event)| *key == &uid || event.uid.starts_with(&uid))
+                .map(|(_, event)| event)
+                .collect::<Vec<_>>();
+            if matches.is_empty() {
+                bail!("no event matching {uid} in {}", cli.state_path.display());
+            }
+            println!("{}", serde_json::to_string_pretty(&matches)?);
+        }
+        Commands::Uid {
+            source,
+            title,
+            date,
+            id,
+            url,
+        } => {
+            println!(
+                "{}",
+                predict_uid(&UidOptions {
+                    config_dir,
+                    source,
+                    title,
+                    date,
+                    id,
+                    url,
+                })?
+            );
+        }
         Commands::Harness => {
             let report = run_harness(&HarnessOptions {
-                config_dir: cli.config_dir,
+                config_dir,
                 state_path: cli.state_path,
                 out_dir: cli.out_dir,
             })?;
 
             println!("{}", serde_json::to_string_pretty(&report)?);
         }
+        Commands::Query {
+            text,
+            source,
+            limit,
+            duplicates,
+            min_similarity,
+            json,
+        } => {
+            let state = load_state_for_read(&cli.state_path)?;
+            let index = open_search_index(&cli.state_path, &state)?;
+            let in_source = |key: &String| {
+                source
+                    .as_ref()
+                    .is_none_or(|source| state.events[key].source_key == *source)
+            };
+            if duplicates {
+                let pairs = index
+                    .duplicates(&state, min_similarity)
+                    .into_iter()
+                    .filter(|pair| in_source(&pair.first) || in_source(&pair.second))
+                    .take(limit)
+                    .collect::<Vec<_>>();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&pairs)?);
+                    return Ok(());
+                }
+                for pair in pairs {
+                    let (first, second) = (&state.events[&pair.first], &state.events[&pair.second]);
+                    println!(
+                        "{:.2} {} {:?} <> {} {:?}",
+                        pair.similarity,
+                        first.source_key,
+                        first.title,
+                        second.source_key,
+                        second.title
+                    );
+                }
+                return Ok(());
+            }
+
+            let events = index
+                .search(text.as_deref().unwrap_or_default())
+                .into_iter()
+                .filter(|key| in_source(key))
+                .take(limit)
+                .map(|key| &state.events[&key])
+                .collect::<Vec<_>>();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&events)?);
+                return Ok(());
+            }
+            if events.is_empty() {
+                println!("no matching events");
+            }
+            for event in events {
+                println!(
+                    "{} {} {:?} ({})",
+                    event
+                        .time
+                        .start_date()
+                        .map(|date| date.to_string())
+                        .unwrap_or_else(|| "TBD".to_string()),
+                    event.source_key,
+                    event.title,
+                    event.uid
+                );
+            }
+        }
+        Commands::Stats { source, format } => {
+            let state = load_state_for_read(&cli.state_path)?;
+            let stats = event_stats(&state, source.as_deref());
+            match format {
+                StatsFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+                StatsFormat::Csv => print!("{}", stats_csv(&stats)),
+                StatsFormat::Text => print_stats(&stats),
+            }
+        }
+        Commands::Runs {
+            action:
+                RunsAction::List {
+                    source,
+                    limit,
+                    json,
+                },
+        } => {
+            let state = load_state_for_read(&cli.state_path)?;
+            let runs = state
+                .runs
+                .iter()
+                .rev()
+                .filter(|run| source.as_ref().is_none_or(|key| run.source(key).is_some()))
+                .take(limit)
+                .collect::<Vec<_>>();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&runs)?);
+                return Ok(());
+            }
+            if runs.is_empty() {
+                println!("no recorded runs");
+            }
+            for run in runs {
+                println!(
+                    "{} {}ms rics {} ({} sources)",
+                    run.started_at.to_rfc3339(),
+                    run.duration_ms,
+                    run.rics_version,
+                    run.sources.len()
+                );
+                if let Some(summary) = source.as_deref().and_then(|key| run.source(key)) {
+                    println!(
+                        "  {}: parsed {}, inserted {}, updated {}, unchanged {}, cancelled {}, {}ms{}",
+                        summary.source_key,
+                        summary.records_parsed,
+                        summary.inserted,
+                        summary.updated,
+                        summary.unchanged,
+                        summary.cancelled,
+                        summary.duration_ms,
+                        if summary.not_modified {
+                            " (not modified)"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+            }
+        }
+        Commands::Quarantine { action } => match action {
+            QuarantineAction::List { json } => {
+                let quarantine = Quarantine::open(&Quarantine::path_for_state(&cli.state_path))?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(quarantine.entries())?);
+                    return Ok(());
+                }
+                if quarantine.entries().is_empty() {
+                    println!("no quarantined changes");
+                }
+                for entry in quarantine.entries() {
+                    println!(
+                        "{} {} {:?} ({}) -> {:?} ({})",
+                        entry.id,
+                        entry.source_key,
+                        entry.previous_title,
+                        entry.previous_start.as_deref().unwrap_or("?"),
+                        entry.record.title,
+                        entry
+                            .record
+                            .time
+                            .start_date()
+                            .map(|date| date.to_string())
+                            .unwrap_or_else(|| "?".to_string()),
+                    );
+                    for reason in &entry.reasons {
+                        println!("  {reason}");
+                    }
+                }
+            }
+            QuarantineAction::Apply { id } => {
+                let entry = apply_quarantined(&cli.state_path, &id)?;
+                info!(id = %entry.id, uid = %entry.record.uid, "quarantined change applied; run `rics build` to republish");
+            }
+            QuarantineAction::Reject { id } => {
+                let entry = reject_quarantined(&cli.state_path, &id)?;
+                info!(id = %entry.id, uid = %entry.record.uid, "quarantined change rejected");
+            }
+        },
+        Commands::SuggestSelectors {
+            source,
+            limit,
+            json,
+        } => {
+            let diagnoses = diagnose_source(&DiagnoseOptions {
+                config_dir,
+                source,
+                limit,
+            })?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diagnoses)?);
+                return Ok(());
+            }
+            for diagnosis in &diagnoses {
+                println!(
+                    "{}: root_selector {} matches {} nodes",
+                    diagnosis.source_url,
+                    diagnosis.root_selector.as_deref().unwrap_or("(none)"),
+                    diagnosis.current_matches
+                );
+                if diagnosis.suggestions.is_empty() {
+                    println!("  no repeated dated elements found");
+                }
+                for suggestion in &diagnosis.suggestions {
+                    println!(
+                        "  {} ({} matches, {} dated): {}",
+                        suggestion.selector,
+                        suggestion.matches,
+                        suggestion.dated,
+                        suggestion.sample
+                    );
+                }
+            }
+        }
+        #[cfg(feature = "http")]
+        Commands::CheckLinks {
+            source,
+            jobs,
+            annotate,
+            json,
+            exit_code,
+        } => {
+            let report = check_links(&CheckLinksOptions {
+                config_dir,
+                state_path: cli.state_path,
+                source,
+                jobs,
+                annotate,
+            })?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!(
+                    "{} links checked: {} dead, {} unreachable",
+                    report.checked,
+                    report.dead.len(),
+                    report.unreachable.len()
+                );
+                for (label, problems) in
+                    [("dead", &report.dead), ("unreachable", &report.unreachable)]
+                {
+                    for problem in problems {
+                        let status = problem
+                            .status
+                            .map(|status| status.to_string())
+                            .or_else(|| problem.error.clone())
+                            .unwrap_or_default();
+                        println!(
+                            "  {label} {} {} ({status}; {} events)",
+                            problem.source_key,
+                            problem.url,
+                            problem.store_keys.len()
+                        );
+                    }
+                }
+            }
+            if exit_code && !report.dead.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        #[cfg(feature = "tui")]
+        Commands::Tui { stale_after_secs } => {
+            rics::tui::run_tui(rics::tui::TuiOptions {
+                config_dir,
+                state_path: cli.state_path,
+                out_dir: cli.out_dir,
+                stale_after: chrono::Duration::seconds(stale_after_secs as i64),
+            })?;
+        }
+        #[cfg(feature = "daemon")]
+        Commands::Daemon {
+            interval_secs,
+            listen,
+            webhook_token_env,
+            jobs,
+            stale_after_secs,
+            #[cfg(feature = "mqtt")]
+            mqtt_host,
+            #[cfg(feature = "mqtt")]
+            mqtt_port,
+            #[cfg(feature = "mqtt")]
+            mqtt_topic,
+        } => {
+            use rics::daemon::{DaemonOptions, run_daemon, webhook_token_from_env};
+
+            let options = DaemonOptions {
+                config_dir,
+                state_path: cli.state_path,
+                out_dir: cli.out_dir,
+                interval: (interval_secs > 0)
+                    .then(|| std::time::Duration::from_secs(interval_secs)),
+                listen,
+                webhook_token: webhook_token_from_env(&webhook_token_env)?,
+                jobs,
+                stale_after: std::time::Duration::from_secs(stale_after_secs.unwrap_or(
+                    if interval_secs > 0 {
+                        interval_secs.saturating_mul(3)
+                    } else {
+                        86_400
+                    },
+                )),
+                #[cfg(feature = "mqtt")]
+                mqtt: mqtt_host.map(|host| rics::daemon::MqttTrigger {
+                    host,
+                    port: mqtt_port,
+                    topic: mqtt_topic,
+                    client_id: format!("rics-{}", std::process::id()),
+                }),
+            };
+            info!(interval_secs, "daemon starting");
+            run_daemon(&options, Default::default())?;
+        }
     }
 
     Ok(())
 }
 
-fn init_tracing() -> Result<()> {
+/// `--config-dir` values, else `RICS_CONFIG_PATH`, else the default dir,
+/// as one path list.
+fn resolve_config_dir(dirs: &[PathBuf]) -> Result<PathBuf> {
+    if !dirs.is_empty() {
+        return join_config_dirs(dirs);
+    }
+    match std::env::var_os(CONFIG_PATH_ENV) {
+        Some(paths) if !paths.is_empty() => Ok(PathBuf::from(paths)),
+        _ => Ok(PathBuf::from("configs/sources")),
+    }
+}
+
+fn print_stats(stats: &EventStats) {
+    println!("{} events", stats.total);
+    for (heading, counts) in [
+        ("source", &stats.by_source),
+        ("year", &stats.by_year),
+        ("month", &stats.by_month),
+        ("category", &stats.by_category),
+        ("importance", &stats.by_importance),
+        ("precision", &stats.by_precision),
+        ("status", &stats.by_status),
+    ] {
+        println!("by {heading}:");
+        for (key, count) in counts {
+            println!("  {key}: {count}");
+        }
+    }
+    if !stats.trends.is_empty() {
+        println!("since previous run:");
+    }
+    for trend in &stats.trends {
+        println!(
+            "  {}: {} -> {} ({:+}; inserted {}, updated {}, cancelled {}, purged {}) at {}",
+            trend.source_key,
+            trend.previous,
+            trend.current,
+            trend.change(),
+            trend.inserted,
+            trend.updated,
+            trend.cancelled,
+            trend.purged,
+            trend.run_started_at.to_rfc3339()
+        );
+    }
+}
+
+fn init_tracing(format: LogFormat) -> Result<()> {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt()
+    let builder = tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_target(false)
-        .compact()
-        .try_init()
-        .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+        .with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => builder.compact().try_init(),
+        LogFormat::Json => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(true)
+            .try_init(),
+    }
+    .map_err(|err| anyhow::anyhow!(err.to_string()))?;
     Ok(())
 }
@@ -0,0 +1,106 @@
+//! Ownership records for generated calendar files.
+//!
+//! Output and mirror directories are often shared webroots, so stale-file
+//! cleanup must never guess from file names. Every directory rics writes
+//! calendars into carries a `.rics-manifest.json` listing the files each
+//! source or bundle produced, and only those are ever deleted. Files that
+//! predate the manifest are left alone.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+pub const MANIFEST_FILE: &str = ".rics-manifest.json";
+
+/// Files rics wrote into one directory, by owner (a source key, or
+/// `bundle:<key>` for bundles).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OutputManifest {
+    #[serde(default)]
+    pub owners: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl OutputManifest {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read manifest {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse manifest {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MANIFEST_FILE);
+        let serialized = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, serialized)
+            .with_context(|| format!("failed to write manifest {}", path.display()))
+    }
+
+    /// Whether any owner lists `file_name`.
+    pub fn owns(&self, file_name: &str) -> bool {
+        self.owners.values().any(|files| files.contains(file_name))
+    }
+}
+
+/// Records that `owner` produced `files` in `dir`, without removing
+/// anything. Used when only part of an owner's output was written.
+pub fn record_owned_files<'a>(
+    dir: &Path,
+    owner: &str,
+    files: impl IntoIterator<Item = &'a String>,
+) -> Result<()> {
+    let mut manifest = OutputManifest::load(dir)?;
+    let before = manifest.clone();
+    manifest
+        .owners
+        .entry(owner.to_string())
+        .or_default()
+        .extend(files.into_iter().cloned());
+    if manifest != before {
+        manifest.save(dir)?;
+    }
+    Ok(())
+}
+
+/// Makes `expected` the complete set of files `owner` has in `dir`: files
+/// the manifest lists for `owner` that are no longer expected are deleted,
+/// unless another owner also claims them. Returns the removed paths.
+pub fn reconcile_owned_files(
+    dir: &Path,
+    owner: &str,
+    expected: &HashSet<String>,
+) -> Result<Vec<PathBuf>> {
+    let mut manifest = OutputManifest::load(dir)?;
+    let before = manifest.clone();
+    let previous = manifest
+        .owners
+        .insert(owner.to_string(), expected.iter().cloned().collect())
+        .unwrap_or_default();
+
+    let mut removed = Vec::new();
+    for file_name in previous.difference(&manifest.owners[owner]) {
+        if manifest.owns(file_name) {
+            continue;
+        }
+        let path = dir.join(file_name);
+        // `symlink_metadata` so dangling alias links are removed too.
+        if path.symlink_metadata().is_ok() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("failed to remove stale file {}", path.display()))?;
+            warn!(file = %path.display(), owner, "removed stale calendar file");
+            removed.push(path);
+        }
+    }
+    if manifest.owners[owner].is_empty() {
+        manifest.owners.remove(owner);
+    }
+    if manifest != before {
+        manifest.save(dir)?;
+    }
+    Ok(removed)
+}
@@ -1,13 +1,18 @@
-use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum EventTimeSpec {
     DateTime {
         start: DateTime<Utc>,
         end: Option<DateTime<Utc>>,
+        /// Wall-clock time and zone as published by the source, when known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        local: Option<LocalTime>,
     },
     Date {
         start: NaiveDate,
@@ -29,7 +34,86 @@ pub enum EventTimeSpec {
     },
 }
 
+impl Default for EventTimeSpec {
+    fn default() -> Self {
+        EventTimeSpec::Tbd { note: None }
+    }
+}
+
+/// A single occurrence of a recurring series, used for EXDATE and
+/// RECURRENCE-ID values.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+#[serde(untagged)]
+pub enum RecurrenceDate {
+    DateTime(DateTime<Utc>),
+    Date(NaiveDate),
+}
+
+impl RecurrenceDate {
+    /// Parses `YYYY-MM-DD`, RFC 3339, or iCalendar basic format
+    /// (`YYYYMMDD` / `YYYYMMDDTHHMMSSZ`).
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+            return Some(RecurrenceDate::DateTime(dt.with_timezone(&Utc)));
+        }
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+            return Some(RecurrenceDate::DateTime(dt.and_utc()));
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d"))
+            .ok()
+            .map(RecurrenceDate::Date)
+    }
+
+    pub fn to_key(&self) -> String {
+        match self {
+            RecurrenceDate::DateTime(dt) => dt.format("%Y%m%dT%H%M%SZ").to_string(),
+            RecurrenceDate::Date(date) => date.format("%Y%m%d").to_string(),
+        }
+    }
+}
+
+/// Original local wall-clock time behind a UTC `DateTime` spec.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct LocalTime {
+    pub tzid: String,
+    pub start: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<NaiveDateTime>,
+}
+
+impl LocalTime {
+    /// Short label such as `14:30 CET`, falling back to the TZID when the
+    /// zone name is not a known IANA zone.
+    pub fn start_label(&self) -> String {
+        match self.tzid.parse::<Tz>() {
+            Ok(tz) => match self.start.and_local_timezone(tz).earliest() {
+                Some(dt) => dt.format("%H:%M %Z").to_string(),
+                None => format!("{} {}", self.start.format("%H:%M"), self.tzid),
+            },
+            Err(_) => format!("{} {}", self.start.format("%H:%M"), self.tzid),
+        }
+    }
+}
+
 impl EventTimeSpec {
+    /// A UTC instant with no known local wall-clock time.
+    pub fn utc(start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> Self {
+        EventTimeSpec::DateTime {
+            start,
+            end,
+            local: None,
+        }
+    }
+
+    pub fn local_time(&self) -> Option<&LocalTime> {
+        match self {
+            EventTimeSpec::DateTime { local, .. } => local.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn year_bucket(&self) -> Option<i32> {
         match self {
             EventTimeSpec::DateTime { start, .. } => Some(start.year()),
@@ -114,7 +198,9 @@ impl EventTimeSpec {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An event as a parser emits it, before UIDs and revisions are assigned.
+/// `schemas/candidate-event.schema.json` describes its JSON form.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct CandidateEvent {
     pub source_key: String,
     pub source_name: String,
@@ -133,9 +219,84 @@ pub struct CandidateEvent {
     pub importance: Option<u8>,
     pub confidence: Option<f32>,
     pub metadata: BTreeMap<String, String>,
+    /// RFC 5545 recurrence rule (without the `RRULE:` prefix) for a series.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rrule: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exdates: Vec<RecurrenceDate>,
+    /// Set on an exception instance that overrides one occurrence of a series.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence_id: Option<RecurrenceDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Typed numeric values such as `actual`/`forecast`/`previous`. The raw
+    /// strings stay in `metadata`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, NumericValue>,
+    /// Absolute URLs of documents the event links to (agendas, releases).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    /// Per-language variants from `title.<lang>` / `description.<lang>` map
+    /// rules, keyed by language tag.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, LocalizedText>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Title and description in one language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct LocalizedText {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A document linked from an event, emitted as `ATTACH`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Attachment {
+    pub url: String,
+    /// Public URL of our copy when `publish.assets.mirror` is on and the
+    /// download succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror_url: Option<String>,
+}
+
+impl Attachment {
+    /// The URL to publish: the mirror when there is one.
+    pub fn published_url(&self) -> &str {
+        self.mirror_url.as_deref().unwrap_or(&self.url)
+    }
+}
+
+/// A number with an optional unit, parsed from source text.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct NumericValue {
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub raw: String,
+}
+
+/// Where a candidate event was extracted from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct Provenance {
+    /// `declarative:<format>` or the custom parser key.
+    pub parser: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_index: Option<usize>,
+    /// Position of the record within its document (or within the custom
+    /// parser's output).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EventRecord {
     pub uid: String,
     pub source_key: String,
@@ -155,27 +316,410 @@ pub struct EventRecord {
     pub importance: Option<u8>,
     pub confidence: Option<f32>,
     pub metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rrule: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exdates: Vec<RecurrenceDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence_id: Option<RecurrenceDate>,
+    /// UIDs of events this one is linked to, emitted as `RELATED-TO`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_uids: Vec<String>,
+    /// UID of the cancelled event this one replaces, e.g. after a reschedule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supersedes: Option<String>,
+    /// Prior revisions, oldest first, capped at [`MAX_REVISION_HISTORY`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<EventRevision>,
+    /// Provenance of the document that produced the current revision.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, NumericValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, LocalizedText>,
+    /// Set by `rics check-links --annotate` while `source_url` is dead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_link: Option<DeadLink>,
+    #[serde(default)]
+    pub lifecycle: Lifecycle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancelled_at: Option<DateTime<Utc>>,
     pub sequence: u32,
     pub revision_hash: String,
     pub created_at: DateTime<Utc>,
     pub last_modified: DateTime<Utc>,
     pub last_seen_at: DateTime<Utc>,
+    /// Consecutive syncs the source ran without listing this event; only
+    /// tracked for TBD events under `lifecycle.tbd_missed_syncs`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub missed_syncs: u32,
+}
+
+/// A `source_url` that answered with a missing-page status when checked.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeadLink {
+    pub url: String,
+    pub status: u16,
+    pub checked_at: DateTime<Utc>,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+/// Where a stored event is in its life. `status` carries whatever the source
+/// published; this is what the pipeline acts on.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Lifecycle {
+    #[default]
+    Active,
+    /// Dropped from a source (or published as cancelled) before it happened.
+    /// Excluded from calendars.
+    Cancelled,
+    /// Dropped from a source after it happened. Still published.
+    Expired,
+}
+
+impl Lifecycle {
+    /// Lifecycle implied by a source-published status string.
+    pub fn from_status(status: &str) -> Self {
+        if status.eq_ignore_ascii_case("cancelled") || status.eq_ignore_ascii_case("canceled") {
+            Lifecycle::Cancelled
+        } else {
+            Lifecycle::Active
+        }
+    }
+}
+
+/// Number of prior revisions kept on each [`EventRecord`].
+pub const MAX_REVISION_HISTORY: usize = 10;
+
+/// Snapshot of the fields of an event that analysts care about when it
+/// changes: what it was called, when it was, and whether it was on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventRevision {
+    pub sequence: u32,
+    pub revision_hash: String,
+    pub title: String,
+    pub time: EventTimeSpec,
+    pub status: String,
+    pub recorded_at: DateTime<Utc>,
 }
 
 impl EventRecord {
+    /// Snapshots the current state into `history` before it is overwritten.
+    pub fn push_revision(&mut self, recorded_at: DateTime<Utc>) {
+        let revision = EventRevision {
+            sequence: self.sequence,
+            revision_hash: self.revision_hash.clone(),
+            title: self.title.clone(),
+            time: self.time.clone(),
+            status: self.status.clone(),
+            recorded_at,
+        };
+        self.history.push(revision);
+        if self.history.len() > MAX_REVISION_HISTORY {
+            let excess = self.history.len() - MAX_REVISION_HISTORY;
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Start date of the most recent revision that was scheduled for a
+    /// different day than the event is now.
+    pub fn rescheduled_from(&self) -> Option<NaiveDate> {
+        let current = self.time.start_date()?;
+        self.history
+            .iter()
+            .rev()
+            .filter_map(|revision| revision.time.start_date())
+            .find(|date| *date != current)
+    }
+
+    /// Key under which the record is stored in [`State::events`]. Exception
+    /// instances share their series UID, so they are keyed by UID and
+    /// recurrence id to keep SEQUENCE bumps scoped to the single instance.
+    pub fn store_key(&self) -> String {
+        store_key(&self.uid, self.recurrence_id.as_ref())
+    }
+
     pub fn year_bucket(&self) -> Option<i32> {
         self.time.year_bucket()
     }
 
+    /// Title in `language` when a variant exists, else the default title.
+    pub fn title_in(&self, language: Option<&str>) -> &str {
+        language
+            .and_then(|lang| self.translations.get(lang))
+            .and_then(|text| text.title.as_deref())
+            .unwrap_or(&self.title)
+    }
+
+    /// Description in `language` when a variant exists, else the default.
+    pub fn description_in(&self, language: Option<&str>) -> Option<&str> {
+        language
+            .and_then(|lang| self.translations.get(lang))
+            .and_then(|text| text.description.as_deref())
+            .or(self.description.as_deref())
+    }
+
+    /// `source_url`, pointed at our mirror when the link is itself a
+    /// mirrored document.
+    pub fn published_source_url(&self) -> Option<&str> {
+        let url = self.source_url.as_deref()?;
+        Some(
+            self.attachments
+                .iter()
+                .find(|attachment| attachment.url == url)
+                .map_or(url, Attachment::published_url),
+        )
+    }
+
+    /// Difference between two typed values, e.g. `surprise("actual",
+    /// "forecast")`. `None` when either is missing or their units differ.
+    pub fn surprise(&self, actual: &str, expected: &str) -> Option<f64> {
+        let actual = self.values.get(actual)?;
+        let expected = self.values.get(expected)?;
+        if actual.unit != expected.unit {
+            return None;
+        }
+        Some(actual.value - expected.value)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.lifecycle == Lifecycle::Cancelled
+    }
+
+    /// When the event left the active lifecycle, used for delete-after
+    /// policies. Expired events have no explicit timestamp, so the last time
+    /// they were seen stands in.
+    pub fn retired_at(&self) -> Option<DateTime<Utc>> {
+        match self.lifecycle {
+            Lifecycle::Active => None,
+            Lifecycle::Cancelled => Some(self.cancelled_at.unwrap_or(self.last_modified)),
+            Lifecycle::Expired => Some(self.last_seen_at),
+        }
+    }
+
     pub fn is_future_relative_to(&self, date: NaiveDate) -> bool {
         self.time.is_future_relative_to(date)
     }
+
+    pub fn is_tbd(&self) -> bool {
+        matches!(self.time, EventTimeSpec::Tbd { .. })
+    }
+}
+
+pub fn store_key(uid: &str, recurrence_id: Option<&RecurrenceDate>) -> String {
+    match recurrence_id {
+        Some(recurrence_id) => format!("{uid}#{}", recurrence_id.to_key()),
+        None => uid.to_string(),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     pub schema_version: u32,
     pub events: BTreeMap<String, EventRecord>,
+    /// Preflight validators from each source's last full fetch, by source key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fetch_validators: BTreeMap<String, ResourceValidators>,
+    /// Field fill rates from each source's last run that parsed records.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_coverage: BTreeMap<String, FieldCoverage>,
+    /// SHA-256 of each source's config file as of its last sync, by source
+    /// key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub config_hashes: BTreeMap<String, String>,
+    /// Start of the last run that fetched each source successfully, by
+    /// source key; read for `fetch.min_interval`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_fetched: BTreeMap<String, DateTime<Utc>>,
+    /// Completed syncs, oldest first, capped at [`MAX_RUN_HISTORY`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runs: Vec<RunRecord>,
+}
+
+/// Number of runs kept in [`State::runs`].
+pub const MAX_RUN_HISTORY: usize = 50;
+
+/// One completed sync.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Correlates this record with the run's logs, reports and change feed.
+    #[serde(default)]
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub rics_version: String,
+    pub sources: Vec<SourceRunSummary>,
+}
+
+impl RunRecord {
+    pub fn source(&self, source_key: &str) -> Option<&SourceRunSummary> {
+        self.sources
+            .iter()
+            .find(|source| source.source_key == source_key)
+    }
+}
+
+/// The counts from a [`SourceRunReport`] worth keeping in state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceRunSummary {
+    pub source_key: String,
+    /// SHA-256 of the source config file as it was for this run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_hash: Option<String>,
+    #[serde(default)]
+    pub not_modified: bool,
+    pub records_parsed: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub cancelled: usize,
+    pub unchanged: usize,
+    #[serde(default)]
+    pub expired: usize,
+    #[serde(default)]
+    pub purged: usize,
+    #[serde(default)]
+    pub quarantined: usize,
+    #[serde(default)]
+    pub warnings: usize,
+    pub duration_ms: u64,
+}
+
+impl SourceRunSummary {
+    pub fn from_report(
+        report: &SourceRunReport,
+        config_hash: Option<String>,
+        duration_ms: u64,
+    ) -> Self {
+        Self {
+            source_key: report.source_key.clone(),
+            config_hash,
+            not_modified: report.not_modified,
+            records_parsed: report.records_parsed,
+            inserted: report.inserted,
+            updated: report.updated,
+            cancelled: report.cancelled,
+            unchanged: report.unchanged,
+            expired: report.expired,
+            purged: report.purged,
+            quarantined: report.quarantined,
+            warnings: report.warnings.len(),
+            duration_ms,
+        }
+    }
+}
+
+/// HTTP validators used to tell whether a remote document changed without
+/// downloading it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResourceValidators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_length: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+impl ResourceValidators {
+    pub fn is_empty(&self) -> bool {
+        self.content_length.is_none() && self.last_modified.is_none() && self.etag.is_none()
+    }
+}
+
+/// Share of a run's parsed records that had each field filled. Mapped
+/// fields kept in `metadata` are tracked under their own names.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FieldCoverage {
+    pub records: usize,
+    /// Fill rate in `0.0..=1.0` by field.
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl FieldCoverage {
+    pub fn from_candidates(candidates: &[CandidateEvent]) -> Self {
+        let mut filled: BTreeMap<String, usize> = BTreeMap::new();
+        for candidate in candidates {
+            let document_url = candidate
+                .provenance
+                .as_ref()
+                .and_then(|p| p.document_url.as_deref());
+            let core = [
+                ("title", !candidate.title.trim().is_empty()),
+                ("date", candidate.time.year_bucket().is_some()),
+                (
+                    "time",
+                    matches!(candidate.time, EventTimeSpec::DateTime { .. }),
+                ),
+                (
+                    "url",
+                    candidate.source_url.is_some()
+                        && candidate.source_url.as_deref() != document_url,
+                ),
+                ("description", candidate.description.is_some()),
+                ("importance", candidate.importance.is_some()),
+                ("source_event_id", candidate.source_event_id.is_some()),
+            ];
+            for (field, present) in core {
+                *filled.entry(field.to_string()).or_default() += usize::from(present);
+            }
+            for (key, value) in &candidate.metadata {
+                // Derived annotations, not mapped fields.
+                if matches!(
+                    key.as_str(),
+                    "time_precision" | "base_url" | "holiday" | "weekend"
+                ) {
+                    continue;
+                }
+                *filled.entry(key.clone()).or_default() += usize::from(!value.is_empty());
+            }
+        }
+
+        let records = candidates.len();
+        let rates = filled
+            .into_iter()
+            .map(|(field, count)| (field, count as f64 / records.max(1) as f64))
+            .collect();
+        Self { records, rates }
+    }
+
+    /// Fields whose fill rate fell by at least `threshold` since `previous`,
+    /// as `(field, previous rate, current rate)`. Fields that disappeared
+    /// entirely count as 0.
+    pub fn drops_since(&self, previous: &FieldCoverage, threshold: f64) -> Vec<(String, f64, f64)> {
+        if previous.records == 0 || self.records == 0 {
+            return Vec::new();
+        }
+        previous
+            .rates
+            .iter()
+            .filter_map(|(field, before)| {
+                let now = self.rates.get(field).copied().unwrap_or(0.0);
+                (before - now >= threshold).then(|| (field.clone(), *before, now))
+            })
+            .collect()
+    }
+}
+
+impl State {
+    /// Derives `lifecycle` for records written before it existed, when
+    /// cancellation was only recorded in `status`.
+    pub fn backfill_lifecycle(&mut self) {
+        for event in self.events.values_mut() {
+            if event.lifecycle == Lifecycle::Active
+                && Lifecycle::from_status(&event.status) == Lifecycle::Cancelled
+            {
+                event.lifecycle = Lifecycle::Cancelled;
+                event.cancelled_at.get_or_insert(event.last_modified);
+            }
+        }
+    }
 }
 
 impl Default for State {
@@ -183,12 +727,20 @@ impl Default for State {
         Self {
             schema_version: 1,
             events: BTreeMap::new(),
+            fetch_validators: BTreeMap::new(),
+            field_coverage: BTreeMap::new(),
+            config_hashes: BTreeMap::new(),
+            last_fetched: BTreeMap::new(),
+            runs: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SourceRunReport {
+    /// The sync this report belongs to; see [`RunRecord::run_id`].
+    #[serde(default)]
+    pub run_id: String,
     pub source_key: String,
     pub pages_fetched: usize,
     pub records_parsed: usize,
@@ -196,4 +748,92 @@ pub struct SourceRunReport {
     pub updated: usize,
     pub cancelled: usize,
     pub unchanged: usize,
+    /// The preflight showed the source unchanged, so nothing was fetched.
+    #[serde(default)]
+    pub not_modified: bool,
+    /// The source config differs from the last sync's, so every year's
+    /// calendar was rebuilt.
+    #[serde(default)]
+    pub config_changed: bool,
+    #[serde(default)]
+    pub expired: usize,
+    #[serde(default)]
+    pub purged: usize,
+    /// Updates held for review instead of merged; see [`crate::quarantine`].
+    #[serde(default)]
+    pub quarantined: usize,
+    /// Invite emails queued for this source; see [`crate::invites`].
+    #[serde(default)]
+    pub invites: usize,
+    #[serde(default)]
+    pub fetch_duration_ms: u64,
+    #[serde(default)]
+    pub bytes_downloaded: u64,
+    #[serde(default)]
+    pub skipped_records: Vec<SkippedRecord>,
+    #[serde(default)]
+    pub warnings: Vec<ParseWarning>,
+    /// Fill rate by field for this run's parsed records.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_coverage: BTreeMap<String, f64>,
+    /// Why fetching, parsing, merging or publishing the source failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The error came from the merge or publish, whose state changes were
+    /// rolled back and counts reset. Otherwise the source failed before
+    /// anything was merged.
+    #[serde(default)]
+    pub rolled_back: bool,
+}
+
+/// A parsed record that did not become a candidate event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedRecord {
+    pub source_url: String,
+    pub reason: String,
+    pub excerpt: String,
+}
+
+/// A parser problem that did not fail the run but usually means the config
+/// is degrading against the upstream layout.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    /// Document the warning applies to; `None` for source-wide warnings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// Records affected; repeats within one document are folded together.
+    pub count: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseWarningKind {
+    /// The root selector, JSON path or record split produced no records.
+    EmptyNodeSet,
+    /// A non-optional field was missing from some records.
+    MissingField,
+    /// A field's selector matched in none of a document's records.
+    UnmatchedSelector,
+    /// The configured custom parser does not exist.
+    CustomParserMissing,
+    /// A field's fill rate fell sharply compared with the previous run.
+    CoverageDrop,
+    /// A document or its matched records exceeded `extract.max_document_bytes`
+    /// or `extract.max_nodes` and were cut off.
+    Truncated,
+    /// Different records got the same UID and were given numbered suffixes.
+    UidCollision,
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source_url {
+            Some(url) => write!(f, "{} ({url})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
 }
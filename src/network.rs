@@ -0,0 +1,302 @@
+//! Host policy for http fetches, from `network.toml`.
+//!
+//! Deployments that run configs written by third parties can restrict
+//! which hosts a `base_url` may point at and refuse non-public addresses,
+//! so a config cannot reach cloud metadata endpoints or internal services.
+//! URLs are checked before every request and on every redirect hop. With
+//! `block_private_ips`, host names are also resolved through a filter that
+//! drops non-public addresses, so a public name pointing inward is refused
+//! at connect time. Requests sent through a proxy are resolved by the
+//! proxy, so only their URLs are checked. The ftp, sftp and imap modes
+//! connect through [`connect_checked`].
+
+use crate::config::{LoadedSource, NetworkConfig};
+use anyhow::{Context as _, Result, bail};
+#[cfg(feature = "http")]
+use reqwest::blocking::ClientBuilder;
+#[cfg(feature = "http")]
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+#[cfg(feature = "http")]
+use reqwest::redirect::Policy;
+#[cfg(feature = "http")]
+use std::future::Future;
+#[cfg(feature = "http")]
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(feature = "http")]
+use std::pin::Pin;
+#[cfg(feature = "http")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "http")]
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use url::{Host, Url};
+
+/// Redirect hops followed before giving up, as reqwest does by default.
+#[cfg(feature = "http")]
+const MAX_REDIRECTS: usize = 10;
+
+/// Fails when the source's tenant may not fetch `url`.
+pub fn check_request(source: &LoadedSource, url: &str) -> Result<()> {
+    check_url(&source.tenant.network(), url)
+}
+
+/// Fails when `network` refuses `url`'s host or address.
+pub fn check_url(network: &NetworkConfig, url: &str) -> Result<()> {
+    if *network == NetworkConfig::default() {
+        return Ok(());
+    }
+    let parsed = Url::parse(url).map_err(|err| anyhow::anyhow!("invalid url {url}: {err}"))?;
+    let (Some(host), Some(name)) = (parsed.host(), parsed.host_str()) else {
+        bail!("{url} has no host");
+    };
+    if !network.host_allowed(name) {
+        bail!("{url} is refused by network.toml: host is not allowed");
+    }
+    let ip = match host {
+        Host::Ipv4(ip) => Some(IpAddr::V4(ip)),
+        Host::Ipv6(ip) => Some(IpAddr::V6(ip)),
+        Host::Domain(_) => None,
+    };
+    if network.block_private_ips
+        && let Some(ip) = ip
+        && !is_public(ip)
+    {
+        bail!("{url} is refused by network.toml: {ip} is not a public address");
+    }
+    Ok(())
+}
+
+/// `host` as the allow and deny lists compare it: lowercase, without a
+/// trailing dot or IPv6 brackets, and IPv6 literals in canonical form, so a
+/// URL host and a bare ftp/sftp/imap host match the same entries.
+pub(crate) fn normalize_host(host: &str) -> String {
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.');
+    match host.parse::<Ipv6Addr>() {
+        Ok(ip) => ip.to_string(),
+        Err(_) => host.to_ascii_lowercase(),
+    }
+}
+
+/// The addresses of `host:port` the source's tenant may connect to, for
+/// protocols that open their own sockets. Fails when the host is refused
+/// or, with `block_private_ips`, resolves only to non-public addresses.
+pub fn resolve_checked(source: &LoadedSource, host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+    let network = source.tenant.network();
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if !network.host_allowed(host) {
+        bail!("{host} is refused by network.toml: host is not allowed");
+    }
+    let resolved = (host, port)
+        .to_socket_addrs()
+        .with_context(|| format!("failed to resolve {host}:{port}"))?
+        .collect::<Vec<_>>();
+    if !network.block_private_ips {
+        return Ok(resolved);
+    }
+    let public = resolved
+        .into_iter()
+        .filter(|addr| is_public(addr.ip()))
+        .collect::<Vec<_>>();
+    if public.is_empty() {
+        bail!("{host} is refused by network.toml: it resolves only to non-public addresses");
+    }
+    Ok(public)
+}
+
+/// Connects to the first address of `host:port` that [`resolve_checked`]
+/// allows and that answers within `timeout`.
+pub fn connect_checked(
+    source: &LoadedSource,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<TcpStream> {
+    let mut last_err = None;
+    for addr in resolve_checked(source, host, port)? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(tcp) => return Ok(tcp),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    match last_err {
+        Some(err) => Err(err).with_context(|| format!("failed to connect to {host}:{port}")),
+        None => bail!("{host} did not resolve to any address"),
+    }
+}
+
+/// Whether `ip` is reachable on the public internet, as opposed to
+/// loopback, private, link-local, shared (CGNAT), benchmarking,
+/// documentation, multicast or otherwise reserved space. IPv6 addresses
+/// that carry an IPv4 address (mapped, IPv4-compatible, NAT64
+/// `64:ff9b::/96` and 6to4 `2002::/16`) are judged by that address;
+/// Teredo `2001::/32` tunnels to one that cannot be checked here, so it is
+/// never public.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && c == 0)
+                || (a == 198 && (18..20).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            if ip.is_loopback() || ip.is_unspecified() {
+                return false;
+            }
+            if let Some(embedded) = embedded_ipv4(ip) {
+                return is_public(IpAddr::V4(embedded));
+            }
+            let segments = ip.segments();
+            !(ip.is_multicast()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || segments[0] & 0xffc0 == 0xfec0
+                || segments[..2] == [0x2001, 0]
+                || segments[..2] == [0x2001, 0xdb8])
+        }
+    }
+}
+
+/// The IPv4 address an IPv4-mapped, IPv4-compatible, NAT64 or 6to4
+/// address routes to.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let [.., a, b, c, d] = ip.octets();
+    let low = Ipv4Addr::new(a, b, c, d);
+    match segments {
+        [0, 0, 0, 0, 0, 0xffff, ..] | [0, 0, 0, 0, 0, 0, ..] => Some(low),
+        [0x64, 0xff9b, 0, 0, 0, 0, ..] => Some(low),
+        [0x2002, high, low_half, ..] => {
+            let [a, b] = high.to_be_bytes();
+            let [c, d] = low_half.to_be_bytes();
+            Some(Ipv4Addr::new(a, b, c, d))
+        }
+        _ => None,
+    }
+}
+
+/// `builder` enforcing `network` on redirects and, with
+/// `block_private_ips`, on resolved addresses. With `same_origin`, a
+/// redirect away from the first request's origin is refused.
+#[cfg(feature = "http")]
+pub(crate) fn restrict(
+    builder: ClientBuilder,
+    network: &Arc<NetworkConfig>,
+    same_origin: bool,
+) -> ClientBuilder {
+    if **network == NetworkConfig::default() && !same_origin {
+        return builder;
+    }
+    let policy = Arc::clone(network);
+    let builder = builder.redirect(Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        if same_origin
+            && let Some(first) = attempt.previous().first()
+            && first.origin() != attempt.url().origin()
+        {
+            let refused = format!(
+                "refusing redirect to {}: it would carry the fetch.auth header to another origin",
+                attempt.url()
+            );
+            return attempt.error(refused);
+        }
+        match check_url(&policy, attempt.url().as_str()) {
+            Ok(()) => attempt.follow(),
+            Err(err) => attempt.error(err.to_string()),
+        }
+    }));
+    if network.block_private_ips {
+        builder.dns_resolver(Arc::new(PublicOnlyResolver))
+    } else {
+        builder
+    }
+}
+
+/// Resolves with the system resolver and keeps only public addresses.
+#[cfg(feature = "http")]
+struct PublicOnlyResolver;
+
+#[cfg(feature = "http")]
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let resolved = Lookup::spawn(host.clone()).await?;
+            let public = resolved
+                .into_iter()
+                .filter(|addr| is_public(addr.ip()))
+                .collect::<Vec<_>>();
+            if public.is_empty() {
+                let err = io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{host} resolves only to non-public addresses (network.toml)"),
+                );
+                return Err(err.into());
+            }
+            Ok(Box::new(public.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(feature = "http")]
+type LookupSlot = (Option<io::Result<Vec<SocketAddr>>>, Option<Waker>);
+
+/// A blocking `getaddrinfo` on its own thread, so a slow lookup does not
+/// stall other requests on the client's runtime.
+#[cfg(feature = "http")]
+struct Lookup {
+    slot: Arc<Mutex<LookupSlot>>,
+}
+
+#[cfg(feature = "http")]
+impl Lookup {
+    fn spawn(host: String) -> Self {
+        let slot: Arc<Mutex<LookupSlot>> = Arc::new(Mutex::new((None, None)));
+        let shared = Arc::clone(&slot);
+        std::thread::spawn(move || {
+            let resolved = (host.as_str(), 0).to_socket_addrs().map(Vec::from_iter);
+            let mut slot = shared
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            slot.0 = Some(resolved);
+            if let Some(waker) = slot.1.take() {
+                waker.wake();
+            }
+        });
+        Self { slot }
+    }
+}
+
+#[cfg(feature = "http")]
+impl Future for Lookup {
+    type Output = io::Result<Vec<SocketAddr>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self
+            .slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match slot.0.take() {
+            Some(resolved) => Poll::Ready(resolved),
+            None => {
+                slot.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
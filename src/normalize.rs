@@ -0,0 +1,147 @@
+//! Text normalization for extracted strings.
+//!
+//! The same upstream text often arrives as `A&amp;B` on one fetch and `A&B`
+//! on the next, or with a stray zero-width space or a decomposed accent.
+//! Each of those used to change the revision hash and republish an
+//! otherwise identical event, so every candidate passes through here before
+//! it is merged.
+
+use crate::model::CandidateEvent;
+use unicode_normalization::UnicodeNormalization;
+
+/// Decodes HTML entities, maps non-breaking spaces to plain spaces, drops
+/// zero-width and control characters (newlines and tabs are kept), applies
+/// NFC and trims the ends.
+pub fn normalize_str(raw: &str) -> String {
+    let decoded = decode_entities(raw);
+    let cleaned = decoded
+        .chars()
+        .filter_map(|c| match c {
+            '\u{a0}' | '\u{2007}' | '\u{202f}' => Some(' '),
+            '\n' | '\t' => Some(c),
+            '\u{ad}' | '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}' => None,
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .nfc()
+        .collect::<String>();
+    cleaned.trim().to_string()
+}
+
+/// Normalizes every free-text field of a candidate in place.
+pub fn normalize_candidate(event: &mut CandidateEvent) {
+    normalize_in_place(&mut event.title);
+    for value in [
+        &mut event.description,
+        &mut event.source_event_id,
+        &mut event.source_url,
+        &mut event.subtype,
+        &mut event.jurisdiction,
+        &mut event.country,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        normalize_in_place(value);
+    }
+    if event.description.as_deref() == Some("") {
+        event.description = None;
+    }
+    for value in event
+        .categories
+        .iter_mut()
+        .chain(event.attachments.iter_mut())
+        .chain(event.metadata.values_mut())
+    {
+        normalize_in_place(value);
+    }
+    for text in event.translations.values_mut() {
+        for value in [&mut text.title, &mut text.description]
+            .into_iter()
+            .flatten()
+        {
+            normalize_in_place(value);
+        }
+    }
+}
+
+fn normalize_in_place(value: &mut String) {
+    *value = normalize_str(value);
+}
+
+/// Decodes numeric references and the named entities that show up in feeds
+/// and attribute values. Unknown names are left as they are.
+fn decode_entities(raw: &str) -> String {
+    if !raw.contains('&') {
+        return raw.to_string();
+    }
+
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let decoded = tail
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&tail[1..end]).map(|c| (c, end)));
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ensp" | "emsp" | "thinsp" => ' ',
+        "shy" => '\u{ad}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "sbquo" => '‚',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bdquo" => '„',
+        "laquo" => '«',
+        "raquo" => '»',
+        "hellip" => '…',
+        "bull" => '•',
+        "middot" => '·',
+        "deg" => '°',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "cent" => '¢',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "sect" => '§',
+        "times" => '×',
+        "plusmn" => '±',
+        _ => return None,
+    };
+    Some(c)
+}
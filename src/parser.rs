@@ -1,14 +1,27 @@
-use crate::config::{DateConfig, ExtractFormat, FieldRule, LoadedSource, SourceConfig};
+use crate::config::{DateConfig, ExtractFormat, FieldRule, LoadedSource, SourceConfig, ValueRule};
+use crate::diagnose::suggest_root_selectors;
+use crate::enrich::enrich_candidates;
+use crate::feed::feed_items;
 use crate::fetch::FetchedDocument;
-use crate::model::{CandidateEvent, EventTimeSpec};
+use crate::model::{
+    CandidateEvent, EventTimeSpec, LocalTime, LocalizedText, NumericValue, ParseWarning,
+    ParseWarningKind, Provenance, RecurrenceDate, SkippedRecord,
+};
+use crate::normalize::normalize_candidate;
+use crate::preprocess::preprocess_documents;
+use crate::regex_limits;
+use crate::schedule::{date_rule_events, schedule_events};
+use crate::schema::check_candidates;
 use anyhow::{Context, Result, anyhow};
-use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use chrono_tz::Tz;
+use percent_encoding::percent_decode_str;
 use regex::Regex;
-use reqwest::blocking::Client;
 use scraper::{ElementRef, Html, Selector};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
 use tracing::{debug, info, warn};
 use url::Url;
 
@@ -18,10 +31,75 @@ pub trait CustomParser: Send + Sync {
     -> Result<Vec<CandidateEvent>>;
 }
 
+/// Events parsed from a source plus the diagnostics gathered along the way.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOutcome {
+    pub events: Vec<CandidateEvent>,
+    pub skipped: Vec<SkippedRecord>,
+    pub warnings: Vec<ParseWarning>,
+    /// Matched document text per declarative record, when parsed with
+    /// `capture_raw`.
+    pub fragments: Vec<RawFragment>,
+}
+
+/// The HTML node, JSON value or text chunk a declarative record was mapped
+/// from.
+#[derive(Debug, Clone)]
+pub struct RawFragment {
+    pub document_url: String,
+    pub page_index: usize,
+    pub record_index: usize,
+    /// `html`, `json`, `txt` or `xml`.
+    pub extension: &'static str,
+    pub content: String,
+}
+
 pub fn parse_source_events(
     source: &LoadedSource,
     docs: &[FetchedDocument],
 ) -> Result<Vec<CandidateEvent>> {
+    Ok(parse_source_documents(source, docs)?.events)
+}
+
+/// Like [`parse_source_events`], but keeps skipped records and parser
+/// warnings for the run report.
+pub fn parse_source_documents(
+    source: &LoadedSource,
+    docs: &[FetchedDocument],
+) -> Result<ParseOutcome> {
+    parse_source_documents_with_raw(source, docs, false)
+}
+
+/// Like [`parse_source_documents`], also keeping each declarative record's
+/// [`RawFragment`] when `capture_raw` is set.
+pub fn parse_source_documents_with_raw(
+    source: &LoadedSource,
+    docs: &[FetchedDocument],
+    capture_raw: bool,
+) -> Result<ParseOutcome> {
+    parse_documents(source, docs, capture_raw, true)
+}
+
+/// The records one fetched page maps to, without the events `[[date_rules]]`
+/// and `[[schedule]]` add, so pagination can tell when pages run out.
+pub fn parse_page_events(
+    source: &LoadedSource,
+    page: &FetchedDocument,
+) -> Result<Vec<CandidateEvent>> {
+    Ok(parse_documents(source, std::slice::from_ref(page), false, false)?.events)
+}
+
+fn parse_documents(
+    source: &LoadedSource,
+    docs: &[FetchedDocument],
+    capture_raw: bool,
+    with_rules: bool,
+) -> Result<ParseOutcome> {
+    let mut outcome = ParseOutcome::default();
+    let _regex_budget =
+        regex_limits::budget_scope(Duration::from_millis(source.config.extract.regex_budget_ms));
+    let docs = &*preprocess_documents(source, docs)?;
+
     if let Some(parser_key) = source
         .config
         .custom
@@ -30,71 +108,232 @@ pub fn parse_source_events(
         .filter(|_| source.config.custom.enabled)
     {
         if let Some(result) = run_custom_parser(parser_key, source, docs) {
-            let events = result?;
+            let mut events = result?;
+            apply_custom_provenance(parser_key, docs, &mut events);
             info!(
                 source = %source.config.source.key,
                 parser = %parser_key,
                 events = events.len(),
                 "custom parser produced events"
             );
-            return Ok(events);
+            outcome.events = events;
+            finish_candidates(&source.config, &mut outcome.events, with_rules)?;
+            return Ok(outcome);
         }
+        let hint = if cfg!(feature = "publishers") {
+            ""
+        } else {
+            " (rics was built without the `publishers` feature)"
+        };
         warn!(
             source = %source.config.source.key,
             parser = %parser_key,
-            "custom parser not found; falling back to declarative parser"
+            "custom parser not found{hint}; falling back to declarative parser"
         );
+        outcome.warnings.push(ParseWarning {
+            kind: ParseWarningKind::CustomParserMissing,
+            source_url: None,
+            field: None,
+            count: 1,
+            message: format!(
+                "custom parser {parser_key} not found{hint}; fell back to declarative parser"
+            ),
+        });
+    }
+
+    parse_declarative_events(source, docs, capture_raw, &mut outcome)?;
+    finish_candidates(&source.config, &mut outcome.events, with_rules)?;
+    Ok(outcome)
+}
+
+/// Adds rule-generated events when `with_rules` is set, then normalizes and
+/// enriches everything and checks the result against the candidate schema.
+fn finish_candidates(
+    source: &SourceConfig,
+    events: &mut Vec<CandidateEvent>,
+    with_rules: bool,
+) -> Result<()> {
+    if with_rules && !source.date_rules.is_empty() {
+        events.extend(date_rule_events(source, Utc::now().date_naive())?);
+    }
+    if with_rules && !source.schedule.is_empty() {
+        events.extend(schedule_events(source, Utc::now().date_naive())?);
+    }
+    events.iter_mut().for_each(normalize_candidate);
+    apply_default_start_time(source, events)?;
+    enrich_candidates(source, events)?;
+    check_candidates(events)
+        .with_context(|| format!("source {} produced invalid events", source.source.key))
+}
+
+/// Gives single-day, date-only events `event.default_start_time` in its
+/// zone. Multi-day spans keep their dates.
+fn apply_default_start_time(source: &SourceConfig, events: &mut [CandidateEvent]) -> Result<()> {
+    let Some(time) = &source.event.default_start_time else {
+        return Ok(());
+    };
+    let time = NaiveTime::parse_from_str(time, "%H:%M")
+        .with_context(|| format!("event.default_start_time {time} must be HH:MM"))?;
+    let timezone = source.default_start_timezone();
+    for event in events {
+        if let EventTimeSpec::Date { start, end } = event.time
+            && end.is_none_or(|end| end == start)
+        {
+            event.time = zoned_datetime(start.and_time(time), None, timezone)?;
+        }
     }
+    Ok(())
+}
 
-    parse_declarative_events(source, docs)
+/// Parses already-fetched document bodies against a source config given as
+/// TOML text. Nothing is fetched or written, so this works in parse-only
+/// builds such as the wasm config editor.
+pub fn preview_source_events(
+    config_toml: &str,
+    docs: &[FetchedDocument],
+) -> Result<Vec<CandidateEvent>> {
+    let config = crate::config::parse_source_config(config_toml)?;
+    let source = LoadedSource {
+        path: std::path::PathBuf::from(format!("{}.toml", config.sanitized_source_dir_name())),
+        config,
+        tenant: Default::default(),
+    };
+    parse_source_events(&source, docs)
 }
 
+#[cfg(feature = "publishers")]
 fn run_custom_parser(
     parser_key: &str,
     source: &LoadedSource,
     docs: &[FetchedDocument],
 ) -> Option<Result<Vec<CandidateEvent>>> {
-    let parser: Box<dyn CustomParser> = match parser_key {
-        "oecd_publications_v1" => Box::new(OecdPublicationsParser),
-        "rough_text_lines_v1" => Box::new(RoughTextLinesParser),
-        "econ_indicators_calendar_v1" => Box::new(EconIndicatorsCalendarParser),
-        "europe_elections_feed_v1" => Box::new(EuropeElectionsFeedParser),
-        "structured_calendar_feed_v1" => Box::new(StructuredCalendarFeedParser),
-        "us_state_elections_feed_v1" => Box::new(UsStateElectionsFeedParser),
-        "mlb_statsapi_schedule_v1" => Box::new(MlbStatsApiScheduleParser),
-        "nhl_schedule_api_v1" => Box::new(NhlScheduleApiParser),
-        "nba_full_schedule_v1" => Box::new(NbaFullScheduleParser),
-        "nfl_operations_schedule_v1" => Box::new(NflOperationsScheduleParser),
-        "mls_statsapi_schedule_v1" => Box::new(MlsStatsApiScheduleParser),
-        _ => return None,
-    };
+    let parser = crate::publishers::custom_parser(parser_key)?;
     Some(parser.parse(source, docs))
 }
 
+#[cfg(not(feature = "publishers"))]
+fn run_custom_parser(
+    _parser_key: &str,
+    _source: &LoadedSource,
+    _docs: &[FetchedDocument],
+) -> Option<Result<Vec<CandidateEvent>>> {
+    None
+}
+
 fn parse_declarative_events(
     source: &LoadedSource,
     docs: &[FetchedDocument],
-) -> Result<Vec<CandidateEvent>> {
-    let mut mapped_records = Vec::new();
+    capture_raw: bool,
+    outcome: &mut ParseOutcome,
+) -> Result<()> {
+    let extract = &source.config.extract;
+    let (parser, selector, extension) = match extract.format {
+        ExtractFormat::Html => (
+            "declarative:html",
+            match (&extract.root_selector, &extract.item_selector) {
+                (Some(root), Some(item)) => Some(format!("{root} {item}")),
+                (root, item) => root.clone().or_else(|| item.clone()),
+            },
+            "html",
+        ),
+        ExtractFormat::Json => ("declarative:json", extract.root_jsonpath.clone(), "json"),
+        ExtractFormat::PdfText => ("declarative:pdf_text", extract.record_regex.clone(), "txt"),
+        ExtractFormat::Text => ("declarative:text", extract.record_regex.clone(), "txt"),
+        ExtractFormat::Feed => ("declarative:feed", None, "xml"),
+    };
 
     for doc in docs {
-        let records = match source.config.extract.format {
-            ExtractFormat::Html => parse_html_document(&source.config, doc)?,
-            ExtractFormat::Json => parse_json_document(&source.config, doc)?,
-            ExtractFormat::PdfText => parse_text_document(&source.config, doc, true)?,
-            ExtractFormat::Text => parse_text_document(&source.config, doc, false)?,
+        let warnings = &mut outcome.warnings;
+        let Some(doc) = limit_document(&source.config, doc, warnings) else {
+            continue;
         };
-        mapped_records.extend(records);
+        let doc = doc.as_ref();
+        let records = match extract.format {
+            ExtractFormat::Html => parse_html_document(&source.config, doc, capture_raw, warnings)?,
+            ExtractFormat::Json => parse_json_document(&source.config, doc, warnings)?,
+            ExtractFormat::PdfText => parse_text_document(&source.config, doc, true, warnings)?,
+            ExtractFormat::Text => parse_text_document(&source.config, doc, false, warnings)?,
+            ExtractFormat::Feed => parse_feed_document(&source.config, doc, warnings)?,
+        };
+
+        for (record_index, mapped) in records.into_iter().enumerate() {
+            let source_url = mapped.source_url.clone();
+            let excerpt = excerpt(&mapped.raw_text);
+            let fragment = capture_raw.then(|| {
+                mapped
+                    .raw_html
+                    .clone()
+                    .unwrap_or_else(|| mapped.raw_text.clone())
+            });
+            match mapped_record_to_event(&source.config, mapped)? {
+                Some(mut event) => {
+                    if let Some(content) = fragment {
+                        outcome.fragments.push(RawFragment {
+                            document_url: doc.source_url.clone(),
+                            page_index: doc.page_index,
+                            record_index,
+                            extension,
+                            content,
+                        });
+                    }
+                    event.provenance = Some(Provenance {
+                        parser: parser.to_string(),
+                        document_url: Some(doc.source_url.clone()),
+                        page_index: Some(doc.page_index),
+                        record_index: Some(record_index),
+                        selector: selector.clone(),
+                        fetched_at: doc.fetched_at,
+                    });
+                    outcome.events.push(event);
+                }
+                None => outcome.skipped.push(SkippedRecord {
+                    source_url,
+                    reason: "missing title".to_string(),
+                    excerpt,
+                }),
+            }
+        }
     }
 
-    let mut events = Vec::new();
-    for mapped in mapped_records {
-        if let Some(event) = mapped_record_to_event(&source.config, mapped)? {
-            events.push(event);
+    Ok(())
+}
+
+/// Fills in provenance for custom parser output that did not set its own.
+/// Custom parsers may fetch further pages themselves, so the document is only
+/// recorded when there was exactly one.
+fn apply_custom_provenance(
+    parser_key: &str,
+    docs: &[FetchedDocument],
+    events: &mut [CandidateEvent],
+) {
+    let single_doc = match docs {
+        [doc] => Some(doc),
+        _ => None,
+    };
+    for (record_index, event) in events.iter_mut().enumerate() {
+        if event.provenance.is_some() {
+            continue;
         }
+        event.provenance = Some(Provenance {
+            parser: parser_key.to_string(),
+            document_url: single_doc.map(|doc| doc.source_url.clone()),
+            page_index: single_doc.map(|doc| doc.page_index),
+            record_index: Some(record_index),
+            selector: None,
+            fetched_at: single_doc.and_then(|doc| doc.fetched_at),
+        });
     }
+}
 
-    Ok(events)
+pub(crate) fn excerpt(raw: &str) -> String {
+    const LIMIT: usize = 160;
+    let collapsed = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= LIMIT {
+        return collapsed;
+    }
+    let mut out = collapsed.chars().take(LIMIT).collect::<String>();
+    out.push('…');
+    out
 }
 
 #[derive(Debug, Clone)]
@@ -103,17 +342,36 @@ struct MappedRecord {
     source_url: String,
     base_url: Option<String>,
     raw_text: String,
+    /// Outer HTML of the matched node, kept only for `capture_raw`.
+    raw_html: Option<String>,
 }
 
+/// Root node of an `extract.item_selector` record, and that selector.
+type ItemRoot<'a> = (ElementRef<'a>, &'a Selector);
+
 #[derive(Clone, Copy)]
 enum MappingCtx<'a> {
-    Html { node: ElementRef<'a>, doc: &'a Html },
-    Json { value: &'a Value },
+    Html {
+        node: ElementRef<'a>,
+        parent: Option<ItemRoot<'a>>,
+        doc: &'a Html,
+    },
+    Json {
+        value: &'a Value,
+    },
     Text,
+    Feed {
+        item: &'a BTreeMap<String, String>,
+    },
 }
 
-fn parse_html_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<Vec<MappedRecord>> {
-    let html_text = String::from_utf8_lossy(&doc.body).to_string();
+fn parse_html_document(
+    source: &SourceConfig,
+    doc: &FetchedDocument,
+    capture_raw: bool,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<MappedRecord>> {
+    let html_text = doc.text();
     let parsed = Html::parse_document(&html_text);
 
     let base_url = Url::parse(&doc.source_url)
@@ -126,7 +384,7 @@ fn parse_html_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<V
         })
         .or_else(|| source.configured_base_url());
 
-    let nodes: Vec<ElementRef<'_>> = if let Some(selector) = source.extract.root_selector.as_ref() {
+    let roots: Vec<ElementRef<'_>> = if let Some(selector) = source.extract.root_selector.as_ref() {
         let selector = Selector::parse(selector)
             .map_err(|err| anyhow!("invalid root_selector {selector}: {err:?}"))?;
         parsed.select(&selector).collect()
@@ -135,14 +393,40 @@ fn parse_html_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<V
             Selector::parse("body").map_err(|_| anyhow!("failed to parse body selector"))?;
         parsed.select(&selector).collect()
     };
+    let item_selector = source
+        .extract
+        .item_selector
+        .as_ref()
+        .map(|selector| {
+            Selector::parse(selector)
+                .map_err(|err| anyhow!("invalid item_selector {selector}: {err:?}"))
+        })
+        .transpose()?;
+    let mut nodes: Vec<(ElementRef<'_>, Option<ItemRoot<'_>>)> = match &item_selector {
+        Some(item) => roots
+            .iter()
+            .flat_map(|root| root.select(item).map(|node| (node, Some((*root, item)))))
+            .collect(),
+        None => roots.into_iter().map(|root| (root, None)).collect(),
+    };
 
     if nodes.is_empty() {
         warn!(source = %source.source.key, url = %doc.source_url, "no html nodes matched; skipping document");
+        let message = match suggest_root_selectors(&html_text, 1).first() {
+            Some(suggestion) => format!(
+                "no html nodes matched; {} looks like a record list ({} dated siblings)",
+                suggestion.selector, suggestion.dated
+            ),
+            None => "no html nodes matched".to_string(),
+        };
+        warnings.push(empty_node_set_warning(doc, &message));
         return Ok(Vec::new());
     }
+    limit_nodes(source, doc, &mut nodes, warnings);
 
     let mut out = Vec::new();
-    for node in nodes {
+    let mut missing = BTreeMap::new();
+    for (node, parent) in nodes {
         let raw_text = node.text().collect::<Vec<_>>().join(" ");
         let mut mapped = BTreeMap::new();
 
@@ -161,11 +445,15 @@ fn parse_html_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<V
                 let value = evaluate_field_rule(
                     field,
                     rule,
-                    MappingCtx::Html { node, doc: &parsed },
+                    MappingCtx::Html {
+                        node,
+                        parent,
+                        doc: &parsed,
+                    },
                     &mapped,
                     &raw_text,
                     base_url.as_deref(),
-                    &doc.source_url,
+                    doc,
                 )?;
 
                 if let Some(value) = value {
@@ -176,6 +464,7 @@ fn parse_html_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<V
                         field,
                         "missing non-optional field in html record"
                     );
+                    *missing.entry(field.as_str()).or_default() += 1;
                 }
             }
         }
@@ -185,18 +474,29 @@ fn parse_html_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<V
             source_url: doc.source_url.clone(),
             base_url: base_url.clone(),
             raw_text,
+            raw_html: capture_raw.then(|| node.html()),
         });
     }
 
+    push_missing_field_warnings(source, doc, out.len(), &missing, warnings);
     Ok(out)
 }
 
-fn parse_json_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<Vec<MappedRecord>> {
-    let payload: Value = serde_json::from_slice(&doc.body)
+fn parse_json_document(
+    source: &SourceConfig,
+    doc: &FetchedDocument,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<MappedRecord>> {
+    let payload: Value = serde_json::from_str(&doc.text())
         .with_context(|| format!("failed to parse json from {}", doc.source_url))?;
-    let nodes = select_json_nodes(&payload, source.extract.root_jsonpath.as_deref());
+    let mut nodes = select_json_nodes(&payload, source.extract.root_jsonpath.as_deref());
+    if nodes.is_empty() {
+        warnings.push(empty_node_set_warning(doc, "no json nodes matched"));
+    }
+    limit_nodes(source, doc, &mut nodes, warnings);
 
     let mut out = Vec::new();
+    let mut missing = BTreeMap::new();
     for node in nodes {
         let raw_text = node.to_string();
         let mut mapped = BTreeMap::new();
@@ -218,10 +518,12 @@ fn parse_json_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<V
                     &mapped,
                     &raw_text,
                     None,
-                    &doc.source_url,
+                    doc,
                 )?;
                 if let Some(value) = value {
                     mapped.insert(field.clone(), value);
+                } else if !rule.optional {
+                    *missing.entry(field.as_str()).or_default() += 1;
                 }
             }
         }
@@ -231,9 +533,73 @@ fn parse_json_document(source: &SourceConfig, doc: &FetchedDocument) -> Result<V
             source_url: doc.source_url.clone(),
             base_url: None,
             raw_text,
+            raw_html: None,
+        });
+    }
+
+    push_missing_field_warnings(source, doc, out.len(), &missing, warnings);
+    Ok(out)
+}
+
+/// Maps each RSS item or Atom entry. Rules read the item's fields by name
+/// (`from = "link"`); without rules, `guid` becomes `id` and `pubDate`
+/// becomes `date`.
+fn parse_feed_document(
+    source: &SourceConfig,
+    doc: &FetchedDocument,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Vec<MappedRecord>> {
+    let xml = doc.text();
+    let mut items = feed_items(&xml)
+        .with_context(|| format!("failed to parse feed from {}", doc.source_url))?;
+    if items.is_empty() {
+        warnings.push(empty_node_set_warning(doc, "no feed items found"));
+    }
+    limit_nodes(source, doc, &mut items, warnings);
+
+    let mut out = Vec::new();
+    let mut missing = BTreeMap::new();
+    for item in items {
+        let mut mapped = BTreeMap::new();
+
+        if source.map.is_empty() {
+            mapped = item.fields;
+            for (from, to) in [("guid", "id"), ("pubDate", "date")] {
+                if !mapped.contains_key(to)
+                    && let Some(value) = mapped.remove(from)
+                {
+                    mapped.insert(to.to_string(), value);
+                }
+            }
+        } else {
+            for (field, rule) in &source.map {
+                let value = evaluate_field_rule(
+                    field,
+                    rule,
+                    MappingCtx::Feed { item: &item.fields },
+                    &mapped,
+                    &item.raw,
+                    Some(&doc.source_url),
+                    doc,
+                )?;
+                if let Some(value) = value {
+                    mapped.insert(field.clone(), value);
+                } else if !rule.optional {
+                    *missing.entry(field.as_str()).or_default() += 1;
+                }
+            }
+        }
+
+        out.push(MappedRecord {
+            fields: mapped,
+            source_url: doc.source_url.clone(),
+            base_url: Some(doc.source_url.clone()),
+            raw_text: item.raw,
+            raw_html: None,
         });
     }
 
+    push_missing_field_warnings(source, doc, out.len(), &missing, warnings);
     Ok(out)
 }
 
@@ -241,21 +607,12 @@ fn parse_text_document(
     source: &SourceConfig,
     doc: &FetchedDocument,
     from_pdf: bool,
+    warnings: &mut Vec<ParseWarning>,
 ) -> Result<Vec<MappedRecord>> {
     let raw_text = if from_pdf {
-        match pdf_extract::extract_text_from_mem(&doc.body) {
-            Ok(text) => text,
-            Err(err) => {
-                warn!(
-                    source = %source.source.key,
-                    error = %err,
-                    "pdf text extraction failed; falling back to utf8 decode"
-                );
-                String::from_utf8_lossy(&doc.body).to_string()
-            }
-        }
+        extract_pdf_text(source, doc)?
     } else {
-        String::from_utf8_lossy(&doc.body).to_string()
+        doc.text().into_owned()
     };
 
     let processed = normalize_text(
@@ -263,14 +620,13 @@ fn parse_text_document(
         source.pdf.normalize_whitespace,
         source.pdf.join_lines,
     );
-    let chunks = split_text_records(source, &processed)?;
+    let mut chunks = split_text_records(source, &processed)?;
+    chunks.retain(|chunk| !chunk.trim().is_empty());
+    limit_nodes(source, doc, &mut chunks, warnings);
 
     let mut out = Vec::new();
+    let mut missing = BTreeMap::new();
     for chunk in chunks {
-        if chunk.trim().is_empty() {
-            continue;
-        }
-
         let mut mapped = BTreeMap::new();
 
         if source.map.is_empty() {
@@ -279,17 +635,12 @@ fn parse_text_document(
             }
         } else {
             for (field, rule) in &source.map {
-                let value = evaluate_field_rule(
-                    field,
-                    rule,
-                    MappingCtx::Text,
-                    &mapped,
-                    &chunk,
-                    None,
-                    &doc.source_url,
-                )?;
+                let value =
+                    evaluate_field_rule(field, rule, MappingCtx::Text, &mapped, &chunk, None, doc)?;
                 if let Some(value) = value {
                     mapped.insert(field.clone(), value);
+                } else if !rule.optional {
+                    *missing.entry(field.as_str()).or_default() += 1;
                 }
             }
         }
@@ -306,6 +657,7 @@ fn parse_text_document(
                     field,
                     "missing non-optional pdf field"
                 );
+                *missing.entry(field.as_str()).or_default() += 1;
             }
         }
 
@@ -314,12 +666,170 @@ fn parse_text_document(
             source_url: doc.source_url.clone(),
             base_url: None,
             raw_text: chunk,
+            raw_html: None,
         });
     }
 
+    if out.is_empty() {
+        warnings.push(empty_node_set_warning(doc, "no text records found"));
+    }
+    push_missing_field_warnings(source, doc, out.len(), &missing, warnings);
     Ok(out)
 }
 
+/// Applies `extract.max_document_bytes`: HTML and text bodies are cut at the
+/// limit, JSON and PDF bodies, which cannot be parsed in part, are skipped.
+fn limit_document<'a>(
+    source: &SourceConfig,
+    doc: &'a FetchedDocument,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<Cow<'a, FetchedDocument>> {
+    let limit = source.extract.max_document_bytes;
+    if doc.body.len() <= limit {
+        return Some(Cow::Borrowed(doc));
+    }
+    let partial = matches!(
+        source.extract.format,
+        ExtractFormat::Html | ExtractFormat::Text
+    );
+    warn!(
+        source = %source.source.key,
+        url = %doc.source_url,
+        bytes = doc.body.len(),
+        limit,
+        "document exceeds extract.max_document_bytes"
+    );
+    let message = if partial {
+        format!(
+            "document is {} bytes; parsed only the first {limit} (extract.max_document_bytes)",
+            doc.body.len()
+        )
+    } else {
+        format!(
+            "document is {} bytes, over extract.max_document_bytes = {limit}; skipped",
+            doc.body.len()
+        )
+    };
+    warnings.push(truncated_warning(doc, 1, message));
+    partial.then(|| {
+        Cow::Owned(FetchedDocument {
+            source_url: doc.source_url.clone(),
+            body: doc.body.clone().truncated(limit),
+            page_index: doc.page_index,
+            fetched_at: doc.fetched_at,
+            headers: doc.headers.clone(),
+        })
+    })
+}
+
+/// Drops records matched beyond `extract.max_nodes`.
+fn limit_nodes<T>(
+    source: &SourceConfig,
+    doc: &FetchedDocument,
+    nodes: &mut Vec<T>,
+    warnings: &mut Vec<ParseWarning>,
+) {
+    let limit = source.extract.max_nodes;
+    if nodes.len() <= limit {
+        return;
+    }
+    let dropped = nodes.len() - limit;
+    warn!(
+        source = %source.source.key,
+        url = %doc.source_url,
+        matched = nodes.len(),
+        limit,
+        "matched records exceed extract.max_nodes; dropping the rest"
+    );
+    warnings.push(truncated_warning(
+        doc,
+        dropped,
+        format!(
+            "{} records matched; kept the first {limit} (extract.max_nodes)",
+            nodes.len()
+        ),
+    ));
+    nodes.truncate(limit);
+}
+
+fn truncated_warning(doc: &FetchedDocument, count: usize, message: String) -> ParseWarning {
+    ParseWarning {
+        kind: ParseWarningKind::Truncated,
+        source_url: Some(doc.source_url.clone()),
+        field: None,
+        count,
+        message,
+    }
+}
+
+fn empty_node_set_warning(doc: &FetchedDocument, message: &str) -> ParseWarning {
+    ParseWarning {
+        kind: ParseWarningKind::EmptyNodeSet,
+        source_url: Some(doc.source_url.clone()),
+        field: None,
+        count: 1,
+        message: message.to_string(),
+    }
+}
+
+/// Folds per-record misses of non-optional fields into one warning per
+/// field. A selector that missed in every record is reported as unmatched.
+fn push_missing_field_warnings(
+    source: &SourceConfig,
+    doc: &FetchedDocument,
+    records: usize,
+    missing: &BTreeMap<&str, usize>,
+    warnings: &mut Vec<ParseWarning>,
+) {
+    for (field, count) in missing {
+        let selector = source
+            .map
+            .get(*field)
+            .and_then(|rule| rule.from.as_deref())
+            .filter(|from| from.starts_with("css:") || from.starts_with("json:"));
+        let (kind, message) = match selector {
+            Some(selector) if *count == records => (
+                ParseWarningKind::UnmatchedSelector,
+                format!("selector {selector} for {field} matched in none of {records} records"),
+            ),
+            _ => (
+                ParseWarningKind::MissingField,
+                format!("{field} missing in {count} of {records} records"),
+            ),
+        };
+        warnings.push(ParseWarning {
+            kind,
+            source_url: Some(doc.source_url.clone()),
+            field: Some((*field).to_string()),
+            count: *count,
+            message,
+        });
+    }
+}
+
+#[cfg(feature = "pdf")]
+fn extract_pdf_text(source: &SourceConfig, doc: &FetchedDocument) -> Result<String> {
+    Ok(match pdf_extract::extract_text_from_mem(&doc.body) {
+        Ok(text) => text,
+        Err(err) => {
+            warn!(
+                source = %source.source.key,
+                error = %err,
+                "pdf text extraction failed; falling back to utf8 decode"
+            );
+            doc.text().into_owned()
+        }
+    })
+}
+
+#[cfg(not(feature = "pdf"))]
+fn extract_pdf_text(source: &SourceConfig, _doc: &FetchedDocument) -> Result<String> {
+    anyhow::bail!(
+        "source {} uses pdf_text extraction but rics was built without the `pdf` feature",
+        source.source.key
+    )
+}
+
 fn normalize_text(text: &str, normalize_ws: bool, join_lines: bool) -> String {
     let mut working = text.replace("\r\n", "\n");
     if normalize_ws {
@@ -338,35 +848,37 @@ fn normalize_text(text: &str, normalize_ws: bool, join_lines: bool) -> String {
 
 fn split_text_records(source: &SourceConfig, text: &str) -> Result<Vec<String>> {
     if let Some(regex) = source.extract.record_regex.as_ref() {
-        let re = Regex::new(regex).with_context(|| format!("invalid record_regex {regex}"))?;
-        let mut rows = Vec::new();
-        for caps in re.captures_iter(text) {
-            if let Some(m) = caps.get(1).or_else(|| caps.get(0)) {
-                rows.push(m.as_str().trim().to_string());
-            }
-        }
+        let re = regex_limits::compile(regex).context("invalid extract.record_regex")?;
+        let rows = regex_limits::run(&re, text, |re, text| {
+            re.captures_iter(text)
+                .filter_map(|caps| caps.get(1).or_else(|| caps.get(0)))
+                .map(|m| m.as_str().trim().to_string())
+                .collect::<Vec<_>>()
+        })?;
         if !rows.is_empty() {
             return Ok(rows);
         }
     }
 
-    if let Some(split) = source.pdf.record_split.first() {
-        if split.strategy.eq_ignore_ascii_case("regex") {
-            let re = Regex::new(&split.pattern)
-                .with_context(|| format!("invalid pdf.record_split pattern {}", split.pattern))?;
-            let starts: Vec<usize> = re.find_iter(text).map(|m| m.start()).collect();
-            if starts.len() > 1 {
-                let mut rows = Vec::new();
-                for (idx, start) in starts.iter().enumerate() {
-                    let end = if idx + 1 < starts.len() {
-                        starts[idx + 1]
-                    } else {
-                        text.len()
-                    };
-                    rows.push(text[*start..end].trim().to_string());
-                }
-                return Ok(rows);
+    if let Some(split) = source.pdf.record_split.first()
+        && split.strategy.eq_ignore_ascii_case("regex")
+    {
+        let re =
+            regex_limits::compile(&split.pattern).context("invalid pdf.record_split pattern")?;
+        let starts: Vec<usize> = regex_limits::run(&re, text, |re, text| {
+            re.find_iter(text).map(|m| m.start()).collect()
+        })?;
+        if starts.len() > 1 {
+            let mut rows = Vec::new();
+            for (idx, start) in starts.iter().enumerate() {
+                let end = if idx + 1 < starts.len() {
+                    starts[idx + 1]
+                } else {
+                    text.len()
+                };
+                rows.push(text[*start..end].trim().to_string());
             }
+            return Ok(rows);
         }
     }
 
@@ -388,7 +900,7 @@ fn split_text_records(source: &SourceConfig, text: &str) -> Result<Vec<String>>
         .collect())
 }
 
-fn parse_pipe_record(line: &str) -> Option<BTreeMap<String, String>> {
+pub(crate) fn parse_pipe_record(line: &str) -> Option<BTreeMap<String, String>> {
     let parts = line
         .split('|')
         .map(str::trim)
@@ -415,19 +927,19 @@ fn evaluate_field_rule(
     existing: &BTreeMap<String, String>,
     raw_text: &str,
     base_url: Option<&str>,
-    source_url: &str,
+    document: &FetchedDocument,
 ) -> Result<Option<String>> {
     let mut value = if let Some(const_value) = &rule.const_value {
         Some(const_value.clone())
     } else {
         let from = rule.from.as_deref().unwrap_or(field_name);
-        evaluate_from_expression(from, ctx, existing, raw_text, source_url)?
+        evaluate_from_expression(from, ctx, existing, raw_text, document)?
     };
 
-    if let Some(pattern) = &rule.regex {
-        if let Some(v) = value.take() {
-            value = extract_with_regex(&v, pattern, rule.capture.unwrap_or(1))?;
-        }
+    if let Some(pattern) = &rule.regex
+        && let Some(v) = value.take()
+    {
+        value = extract_with_regex(&v, pattern, rule.capture.unwrap_or(1))?;
     }
 
     if rule.trim {
@@ -450,22 +962,25 @@ fn evaluate_from_expression(
     ctx: MappingCtx<'_>,
     existing: &BTreeMap<String, String>,
     raw_text: &str,
-    source_url: &str,
+    document: &FetchedDocument,
 ) -> Result<Option<String>> {
     if let Some(key) = expr.strip_prefix("field:") {
         return Ok(existing.get(key).cloned());
     }
     if expr == "source_url" {
-        return Ok(Some(source_url.to_string()));
+        return Ok(Some(document.source_url.clone()));
+    }
+    if let Some(header) = expr.strip_prefix("header:") {
+        return Ok(header_value(document, header));
     }
     if let Some(pattern) = expr.strip_prefix("regex:") {
         return extract_with_regex(raw_text, pattern, 1);
     }
 
     match ctx {
-        MappingCtx::Html { node, doc } => {
+        MappingCtx::Html { node, parent, doc } => {
             if let Some(css) = expr.strip_prefix("css:") {
-                return Ok(extract_css_value(node, doc, css));
+                return Ok(extract_css_value(node, parent, doc, css));
             }
         }
         MappingCtx::Json { value } => {
@@ -475,12 +990,24 @@ fn evaluate_from_expression(
             }
         }
         MappingCtx::Text => {}
+        MappingCtx::Feed { item } => {
+            if !existing.contains_key(expr) {
+                return Ok(item.get(expr).cloned());
+            }
+        }
     }
 
     Ok(existing.get(expr).cloned())
 }
 
-fn extract_css_value(node: ElementRef<'_>, doc: &Html, expression: &str) -> Option<String> {
+/// The first match in `node`, else in its item root, else anywhere in
+/// `doc`. Matches inside other items are never borrowed.
+fn extract_css_value(
+    node: ElementRef<'_>,
+    parent: Option<ItemRoot<'_>>,
+    doc: &Html,
+    expression: &str,
+) -> Option<String> {
     let (selector_text, attr) = split_selector_attr(expression);
     let selector = Selector::parse(selector_text).ok()?;
 
@@ -488,16 +1015,66 @@ fn extract_css_value(node: ElementRef<'_>, doc: &Html, expression: &str) -> Opti
         return Some(element_attr_or_text(el, attr));
     }
 
+    let outside_items = |el: &ElementRef<'_>| {
+        parent.is_none_or(|(_, item)| {
+            !el.ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| item.matches(&ancestor))
+        })
+    };
+    if let Some((root, _)) = parent
+        && let Some(el) = root.select(&selector).find(outside_items)
+    {
+        return Some(element_attr_or_text(el, attr));
+    }
     doc.select(&selector)
-        .next()
+        .find(outside_items)
         .map(|el| element_attr_or_text(el, attr))
 }
 
-fn split_selector_attr(expression: &str) -> (&str, Option<&str>) {
-    if let Some((selector, attr)) = expression.rsplit_once('@') {
-        if !attr.is_empty() && !attr.contains(' ') {
-            return (selector, Some(attr));
+/// `header:<name>`: a response header of the document, or with `@param`
+/// one of its parameters, e.g. `content-disposition@filename`. HTTP dates
+/// are rewritten to RFC 3339 so they parse as event times.
+fn header_value(document: &FetchedDocument, expression: &str) -> Option<String> {
+    let (name, param) = split_selector_attr(expression);
+    let value = document.headers.get(&name.trim().to_ascii_lowercase())?;
+    if let Some(param) = param {
+        return header_param(value, param);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.with_timezone(&Utc).to_rfc3339());
+    }
+    Some(value.clone())
+}
+
+/// A parameter of a header value. An RFC 5987 `name*` form, as in
+/// `filename*=UTF-8''R%C3%A9union.pdf`, wins over the plain one.
+fn header_param(value: &str, name: &str) -> Option<String> {
+    let mut plain = None;
+    for param in value.split(';').skip(1) {
+        let Some((key, raw)) = param.split_once('=') else {
+            continue;
+        };
+        let (key, raw) = (key.trim(), raw.trim());
+        if let Some(key) = key.strip_suffix('*')
+            && key.eq_ignore_ascii_case(name)
+            && let Some((_, encoded)) = raw.split_once("''")
+        {
+            return Some(percent_decode_str(encoded).decode_utf8_lossy().into_owned());
         }
+        if key.eq_ignore_ascii_case(name) {
+            plain = Some(raw.trim_matches('"').to_string());
+        }
+    }
+    plain
+}
+
+fn split_selector_attr(expression: &str) -> (&str, Option<&str>) {
+    if let Some((selector, attr)) = expression.rsplit_once('@')
+        && !attr.is_empty()
+        && !attr.contains(' ')
+    {
+        return (selector, Some(attr));
     }
     (expression, None)
 }
@@ -540,7 +1117,7 @@ fn first_html_attr(node: &ElementRef<'_>, selector: &str, attr: &str) -> Option<
         .and_then(|el| el.value().attr(attr).map(ToString::to_string))
 }
 
-fn select_json_nodes<'a>(root: &'a Value, path: Option<&str>) -> Vec<&'a Value> {
+pub(crate) fn select_json_nodes<'a>(root: &'a Value, path: Option<&str>) -> Vec<&'a Value> {
     match path {
         None => match root {
             Value::Array(items) => items.iter().collect(),
@@ -666,11 +1243,35 @@ fn mapped_record_to_event(
     source: &SourceConfig,
     mapped: MappedRecord,
 ) -> Result<Option<CandidateEvent>> {
+    let mut translations: BTreeMap<String, LocalizedText> = BTreeMap::new();
+    for (key, value) in &mapped.fields {
+        if let Some(lang) = key.strip_prefix("title.") {
+            translations.entry(lang.to_string()).or_default().title = Some(value.clone());
+        } else if let Some(lang) = key.strip_prefix("description.") {
+            translations
+                .entry(lang.to_string())
+                .or_default()
+                .description = Some(value.clone());
+        }
+    }
+    // Variants stand in for a missing default, preferring the published
+    // language.
+    let variant = |pick: fn(&LocalizedText) -> &Option<String>| {
+        source
+            .publish
+            .language
+            .as_deref()
+            .and_then(|lang| translations.get(lang))
+            .and_then(|text| pick(text).clone())
+            .or_else(|| translations.values().find_map(|text| pick(text).clone()))
+    };
+
     let title = mapped
         .fields
         .get("title")
         .cloned()
-        .or_else(|| mapped.fields.get("name").cloned());
+        .or_else(|| mapped.fields.get("name").cloned())
+        .or_else(|| variant(|text| &text.title));
 
     let Some(title) = title else {
         debug!(
@@ -754,7 +1355,8 @@ fn mapped_record_to_event(
         .fields
         .get("description")
         .cloned()
-        .or_else(|| mapped.fields.get("summary").cloned());
+        .or_else(|| mapped.fields.get("summary").cloned())
+        .or_else(|| variant(|text| &text.description));
 
     let importance = mapped
         .fields
@@ -767,8 +1369,50 @@ fn mapped_record_to_event(
         .get("confidence")
         .and_then(|v| v.parse::<f32>().ok());
 
+    let rrule = mapped
+        .fields
+        .get("rrule")
+        .map(|v| v.trim().trim_start_matches("RRULE:").to_string())
+        .filter(|v| !v.is_empty());
+    let exdates = mapped
+        .fields
+        .get("exdates")
+        .map(|v| v.split(',').filter_map(RecurrenceDate::parse).collect())
+        .unwrap_or_default();
+    let recurrence_id = mapped
+        .fields
+        .get("recurrence_id")
+        .and_then(|v| RecurrenceDate::parse(v));
+
+    let attachments = mapped
+        .fields
+        .get("attachments")
+        .map(|v| {
+            v.split([',', ' ', '\n'])
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(|url| absolutize_url(mapped.base_url.as_deref(), url))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut values = BTreeMap::new();
+    for (name, rule) in &source.values {
+        let field = rule.field.as_deref().unwrap_or(name);
+        if let Some(value) = mapped
+            .fields
+            .get(field)
+            .and_then(|raw| parse_numeric_value(raw, rule))
+        {
+            values.insert(name.clone(), value);
+        }
+    }
+
     let mut metadata = BTreeMap::new();
     for (k, v) in &mapped.fields {
+        if k.starts_with("title.") || k.starts_with("description.") {
+            continue;
+        }
         if [
             "title",
             "name",
@@ -787,6 +1431,12 @@ fn mapped_record_to_event(
             "link",
             "importance",
             "confidence",
+            "rrule",
+            "exdates",
+            "recurrence_id",
+            "attachments",
+            "country",
+            "jurisdiction",
         ]
         .contains(&k.as_str())
         {
@@ -812,15 +1462,83 @@ fn mapped_record_to_event(
         event_type,
         subtype,
         categories: categories.into_iter().collect(),
-        jurisdiction: source.source.jurisdiction.clone(),
-        country: source.source.default_country.clone(),
+        jurisdiction: mapped
+            .fields
+            .get("jurisdiction")
+            .cloned()
+            .or_else(|| source.source.jurisdiction.clone()),
+        country: mapped
+            .fields
+            .get("country")
+            .cloned()
+            .or_else(|| source.source.default_country.clone()),
         importance,
         confidence,
         metadata,
+        rrule,
+        exdates,
+        recurrence_id,
+        provenance: None,
+        values,
+        attachments,
+        translations,
     }))
 }
 
-fn parse_event_time(
+fn parse_numeric_value(raw: &str, rule: &ValueRule) -> Option<NumericValue> {
+    let trimmed = raw.trim();
+    let chars = trimmed.char_indices().collect::<Vec<_>>();
+    let first_digit = chars.iter().position(|(_, c)| c.is_ascii_digit())?;
+
+    let mut start = first_digit;
+    if start > 0 && matches!(chars[start - 1].1, '-' | '+' | '\u{2212}') {
+        start -= 1;
+    }
+    let mut end = first_digit;
+    while end < chars.len() {
+        let c = chars[end].1;
+        let is_group_space = matches!(c, ' ' | '\u{a0}' | '\u{202f}')
+            && chars
+                .get(end + 1)
+                .is_some_and(|(_, next)| next.is_ascii_digit());
+        if c.is_ascii_digit() || c == '.' || c == ',' || is_group_space {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+
+    let byte_start = chars[start].0;
+    let byte_end = chars.get(end).map(|(i, _)| *i).unwrap_or(trimmed.len());
+    let mut number = trimmed[byte_start..byte_end]
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\u{202f}')
+        .map(|c| if c == '\u{2212}' { '-' } else { c })
+        .collect::<String>();
+    if rule.decimal_comma {
+        number = number.replace('.', "").replace(',', ".");
+    } else {
+        number = number.replace(',', "");
+    }
+    let value = number.trim_end_matches('.').parse::<f64>().ok()?;
+
+    let unit = rule.unit.clone().or_else(|| {
+        let suffix = trimmed[byte_end..].trim();
+        let prefix = trimmed[..byte_start].trim();
+        [suffix, prefix]
+            .into_iter()
+            .find(|part| !part.is_empty())
+            .map(str::to_string)
+    });
+
+    Some(NumericValue {
+        value,
+        unit,
+        raw: trimmed.to_string(),
+    })
+}
+
+pub(crate) fn parse_event_time(
     start_raw: &str,
     end_raw: Option<&str>,
     date_cfg: &DateConfig,
@@ -835,20 +1553,14 @@ fn parse_event_time(
         let end = end_raw
             .and_then(|s| DateTime::parse_from_rfc3339(s.trim()).ok())
             .map(|d| d.with_timezone(&Utc));
-        return Ok(EventTimeSpec::DateTime {
-            start: dt.with_timezone(&Utc),
-            end,
-        });
+        return Ok(EventTimeSpec::utc(dt.with_timezone(&Utc), end));
     }
 
     for format in &date_cfg.formats {
         if let Ok(dt) = NaiveDateTime::parse_from_str(start_raw, format) {
-            let start = localize_datetime(dt, timezone)?;
-            let end = end_raw
-                .and_then(|raw| NaiveDateTime::parse_from_str(raw.trim(), format).ok())
-                .map(|value| localize_datetime(value, timezone))
-                .transpose()?;
-            return Ok(EventTimeSpec::DateTime { start, end });
+            let local_end =
+                end_raw.and_then(|raw| NaiveDateTime::parse_from_str(raw.trim(), format).ok());
+            return zoned_datetime(dt, local_end, timezone);
         }
 
         if let Ok(date) = NaiveDate::parse_from_str(start_raw, format) {
@@ -878,17 +1590,36 @@ fn parse_event_time(
     })
 }
 
+/// Builds a `DateTime` spec from local wall-clock values, keeping the
+/// original local time and TZID when the timezone is known.
+pub(crate) fn zoned_datetime(
+    local_start: NaiveDateTime,
+    local_end: Option<NaiveDateTime>,
+    timezone: Option<&str>,
+) -> Result<EventTimeSpec> {
+    let start = localize_datetime(local_start, timezone)?;
+    let end = local_end
+        .map(|value| localize_datetime(value, timezone))
+        .transpose()?;
+    let local = timezone
+        .filter(|tz_name| tz_name.parse::<Tz>().is_ok())
+        .map(|tz_name| LocalTime {
+            tzid: tz_name.to_string(),
+            start: local_start,
+            end: local_end,
+        });
+    Ok(EventTimeSpec::DateTime { start, end, local })
+}
+
 fn localize_datetime(value: NaiveDateTime, timezone: Option<&str>) -> Result<DateTime<Utc>> {
     if let Some(tz_name) = timezone
         && let Ok(tz) = tz_name.parse::<Tz>()
-    {
-        if let Some(dt) = tz
+        && let Some(dt) = tz
             .from_local_datetime(&value)
             .earliest()
             .or_else(|| tz.from_local_datetime(&value).latest())
-        {
-            return Ok(dt.with_timezone(&Utc));
-        }
+    {
+        return Ok(dt.with_timezone(&Utc));
     }
 
     Ok(Utc.from_utc_datetime(&value))
@@ -912,7 +1643,7 @@ fn parse_quarter_year(value: &str) -> Option<(u8, i32)> {
     Some((q, year))
 }
 
-fn detect_date_in_text(text: &str) -> Option<String> {
+pub(crate) fn detect_date_in_text(text: &str) -> Option<String> {
     let patterns = [
         r"\b\d{4}-\d{2}-\d{2}\b",
         r"\b\d{4}/\d{2}/\d{2}\b",
@@ -930,17 +1661,14 @@ fn detect_date_in_text(text: &str) -> Option<String> {
 }
 
 fn extract_with_regex(input: &str, pattern: &str, capture: usize) -> Result<Option<String>> {
-    let regex = Regex::new(pattern).with_context(|| format!("invalid regex pattern {pattern}"))?;
-    let Some(caps) = regex.captures(input) else {
-        return Ok(None);
-    };
-    let Some(value) = caps.get(capture) else {
-        return Ok(None);
-    };
-    Ok(Some(value.as_str().trim().to_string()))
+    let regex = regex_limits::compile(pattern)?;
+    regex_limits::run(&regex, input, |regex, input| {
+        let caps = regex.captures(input)?;
+        Some(caps.get(capture)?.as_str().trim().to_string())
+    })
 }
 
-fn absolutize_url(base_url: Option<&str>, value: &str) -> String {
+pub(crate) fn absolutize_url(base_url: Option<&str>, value: &str) -> String {
     if value.starts_with("http://") || value.starts_with("https://") {
         return value.to_string();
     }
@@ -955,1487 +1683,6 @@ fn absolutize_url(base_url: Option<&str>, value: &str) -> String {
     value.to_string()
 }
 
-struct OecdPublicationsParser;
-
-impl CustomParser for OecdPublicationsParser {
-    fn key(&self) -> &'static str {
-        "oecd_publications_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        if docs.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let mut events = Vec::new();
-        let current_year = Utc::now().year();
-        let mut seen_ids = HashSet::new();
-        let first_doc_url = Url::parse(&docs[0].source_url)
-            .with_context(|| format!("invalid source url {}", docs[0].source_url))?;
-        let mut query_pairs: BTreeMap<String, String> = first_doc_url
-            .query_pairs()
-            .map(|(k, v)| (k.into_owned(), v.into_owned()))
-            .collect();
-        let facet_tags = query_pairs.get("facetTags").cloned().unwrap_or_else(|| {
-            "oecd-languages:en,oecd-search-config-pillars:publications".to_string()
-        });
-        query_pairs.insert(
-            "facetTags".to_string(),
-            ensure_facet_tags(&facet_tags).to_string(),
-        );
-
-        let client = Client::builder()
-            .user_agent(
-                source
-                    .config
-                    .fetch
-                    .user_agent
-                    .clone()
-                    .unwrap_or_else(|| "rics/0.1 (+https://example.invalid)".to_string()),
-            )
-            .build()
-            .context("failed to build OECD API client")?;
-
-        let page_size = source
-            .config
-            .fetch
-            .headers
-            .get("x-oecd-page-size")
-            .and_then(|v| v.parse::<usize>().ok())
-            .unwrap_or(50);
-        let max_pages = 200usize;
-        let mut total = usize::MAX;
-        let mut page = 0usize;
-
-        while page < max_pages && page * page_size < total {
-            let mut params = query_pairs.clone();
-            params.insert("siteName".to_string(), "oecd".to_string());
-            params.insert("page".to_string(), page.to_string());
-            params.insert("pageSize".to_string(), page_size.to_string());
-            params
-                .entry("orderBy".to_string())
-                .or_insert_with(|| "mostRecent".to_string());
-            params
-                .entry("minPublicationYear".to_string())
-                .or_insert_with(|| current_year.to_string());
-            params
-                .entry("maxPublicationYear".to_string())
-                .or_insert_with(|| current_year.to_string());
-
-            let response = client
-                .get("https://api.oecd.org/webcms/search/faceted-search")
-                .query(&params)
-                .send()
-                .with_context(|| format!("failed to query OECD API page {page}"))?;
-            if !response.status().is_success() {
-                return Err(anyhow!(
-                    "OECD API returned {} for page {}",
-                    response.status(),
-                    page
-                ));
-            }
-            let payload = response
-                .json::<Value>()
-                .context("failed to decode OECD API JSON")?;
-
-            total = payload.get("total").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-
-            let Some(results) = payload.get("results").and_then(|v| v.as_array()) else {
-                break;
-            };
-
-            for result in results {
-                let tag_ids = result
-                    .get("tags")
-                    .and_then(Value::as_array)
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|tag| tag.get("id").and_then(Value::as_str))
-                            .map(ToString::to_string)
-                            .collect::<Vec<_>>()
-                    })
-                    .unwrap_or_default();
-                let is_publication = tag_ids
-                    .iter()
-                    .any(|id| id.starts_with("oecd-content-types:publications/"));
-                if !is_publication {
-                    continue;
-                }
-
-                let title = result
-                    .get("title")
-                    .and_then(|v| v.as_str())
-                    .map(str::trim)
-                    .filter(|v| !v.is_empty())
-                    .map(ToString::to_string);
-                let Some(title) = title else {
-                    continue;
-                };
-
-                let url = result
-                    .get("url")
-                    .and_then(|v| v.as_str())
-                    .map(|v| absolutize_url(Some("https://www.oecd.org"), v))
-                    .unwrap_or_default();
-                if url.is_empty() {
-                    continue;
-                }
-                if !seen_ids.insert(url.clone()) {
-                    continue;
-                }
-
-                let date_text = result
-                    .get("publicationDateTime")
-                    .and_then(|v| v.as_str())
-                    .or_else(|| result.get("startDateTime").and_then(|v| v.as_str()))
-                    .or_else(|| result.get("endDateTime").and_then(|v| v.as_str()));
-                let Some(date_text) = date_text else {
-                    continue;
-                };
-
-                let time = parse_event_time(
-                    date_text,
-                    None,
-                    &source.config.date,
-                    source.config.source.timezone.as_deref(),
-                )?;
-                if !matches_year_or_next(time.year_bucket(), current_year) {
-                    continue;
-                }
-
-                let description = result
-                    .get("description")
-                    .and_then(|v| v.as_str())
-                    .map(str::trim)
-                    .filter(|v| !v.is_empty())
-                    .map(ToString::to_string);
-
-                let tags = tag_ids.join(",");
-
-                events.push(CandidateEvent {
-                    source_key: source.config.source.key.clone(),
-                    source_name: source.config.source.name.clone(),
-                    source_event_id: Some(url.clone()),
-                    source_url: Some(url),
-                    title,
-                    description,
-                    time,
-                    timezone: source.config.source.timezone.clone(),
-                    status: source.config.event.status.clone(),
-                    event_type: source.config.event.event_type.clone(),
-                    subtype: source.config.event.subtype.clone(),
-                    categories: {
-                        let mut x = source.config.event.categories.clone();
-                        if !x.contains(&"publishing".to_string()) {
-                            x.push("publishing".to_string());
-                        }
-                        x
-                    },
-                    jurisdiction: source.config.source.jurisdiction.clone(),
-                    country: source.config.source.default_country.clone(),
-                    importance: source.config.event.importance,
-                    confidence: Some(0.95),
-                    metadata: BTreeMap::from([
-                        ("custom_parser".to_string(), self.key().to_string()),
-                        ("api_total".to_string(), total.to_string()),
-                        ("api_tags".to_string(), tags),
-                    ]),
-                });
-            }
-
-            page += 1;
-        }
-
-        info!(
-            source = %source.config.source.key,
-            events = events.len(),
-            "oecd parser extracted dated publication events"
-        );
-
-        Ok(events)
-    }
-}
-
-fn ensure_facet_tags(tags: &str) -> String {
-    let mut values = tags
-        .split(',')
-        .map(str::trim)
-        .filter(|v| !v.is_empty())
-        .map(ToString::to_string)
-        .collect::<Vec<_>>();
-
-    if !values.iter().any(|v| v == "oecd-languages:en") {
-        values.push("oecd-languages:en".to_string());
-    }
-    if !values
-        .iter()
-        .any(|v| v == "oecd-search-config-pillars:publications")
-    {
-        values.push("oecd-search-config-pillars:publications".to_string());
-    }
-    values.join(",")
-}
-
-fn matches_year_or_next(year: Option<i32>, current_year: i32) -> bool {
-    match year {
-        Some(y) => y == current_year || y == current_year + 1,
-        None => false,
-    }
-}
-
-struct RoughTextLinesParser;
-
-impl CustomParser for RoughTextLinesParser {
-    fn key(&self) -> &'static str {
-        "rough_text_lines_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        let mut events = Vec::new();
-
-        for doc in docs {
-            let payload = String::from_utf8_lossy(&doc.body);
-            for line in payload.lines() {
-                let line = line.trim();
-                if line.is_empty() || line.starts_with('#') {
-                    continue;
-                }
-
-                let Some(map) = parse_pipe_record(line) else {
-                    continue;
-                };
-
-                let Some(title) = map.get("title").cloned() else {
-                    continue;
-                };
-
-                let time = if let Some(date) = map.get("date") {
-                    parse_event_time(
-                        date,
-                        None,
-                        &source.config.date,
-                        source.config.source.timezone.as_deref(),
-                    )?
-                } else {
-                    EventTimeSpec::Tbd { note: None }
-                };
-
-                events.push(CandidateEvent {
-                    source_key: source.config.source.key.clone(),
-                    source_name: source.config.source.name.clone(),
-                    source_event_id: map.get("url").cloned(),
-                    source_url: map.get("url").cloned(),
-                    title,
-                    description: None,
-                    time,
-                    timezone: source.config.source.timezone.clone(),
-                    status: source.config.event.status.clone(),
-                    event_type: source.config.event.event_type.clone(),
-                    subtype: source.config.event.subtype.clone(),
-                    categories: source.config.event.categories.clone(),
-                    jurisdiction: source.config.source.jurisdiction.clone(),
-                    country: source.config.source.default_country.clone(),
-                    importance: source.config.event.importance,
-                    confidence: Some(0.5),
-                    metadata: BTreeMap::from([(
-                        "custom_parser".to_string(),
-                        self.key().to_string(),
-                    )]),
-                });
-            }
-        }
-
-        Ok(events)
-    }
-}
-
-struct EconIndicatorsCalendarParser;
-
-impl CustomParser for EconIndicatorsCalendarParser {
-    fn key(&self) -> &'static str {
-        "econ_indicators_calendar_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        let mut events = Vec::new();
-        let day_header = Regex::new(
-            r"^(Monday|Tuesday|Wednesday|Thursday|Friday|Saturday|Sunday)\s+([A-Za-z]+)\s+(\d{1,2})\s+(\d{4})",
-        )
-        .expect("day header regex must compile");
-        let time_line =
-            Regex::new(r"^(\d{1,2}:\d{2}\s*[AP]M)$").expect("time line regex must compile");
-        let split_columns = Regex::new(r"\s{2,}").expect("split columns regex must compile");
-        let default_country = source
-            .config
-            .fetch
-            .template_vars
-            .get("country")
-            .cloned()
-            .or_else(|| source.config.source.default_country.clone())
-            .unwrap_or_else(|| "XX".to_string())
-            .to_ascii_uppercase();
-        let country_line_re = Regex::new(r"^[A-Z]{2,3}$").expect("country-line regex must compile");
-        let noise_lines = [
-            "news",
-            "markets",
-            "indicators",
-            "countries",
-            "forecasts",
-            "interest rate",
-            "inflation rate",
-            "unemployment rate",
-            "gdp growth",
-            "gdp per capita",
-            "current account",
-            "gold reserves",
-            "government debt",
-            "crude oil production",
-            "gasoline prices",
-            "credit rating",
-            "more indicators",
-            "actual",
-            "previous",
-            "consensus",
-            "forecast",
-        ];
-
-        for doc in docs {
-            let payload = String::from_utf8_lossy(&doc.body);
-            let mut active_date: Option<NaiveDate> = None;
-            let mut active_time: Option<String> = None;
-            let mut waiting_for_country = false;
-            let mut active_country: Option<String> = None;
-
-            for raw in payload.lines() {
-                let line = raw.trim();
-                if line.is_empty() {
-                    continue;
-                }
-                let line_lower = line.to_ascii_lowercase();
-                if noise_lines.contains(&line_lower.as_str()) || line_lower.starts_with("utc ") {
-                    continue;
-                }
-
-                if let Some(caps) = day_header.captures(line) {
-                    let month = caps.get(2).map(|m| m.as_str()).unwrap_or_default();
-                    let day = caps.get(3).map(|m| m.as_str()).unwrap_or_default();
-                    let year = caps.get(4).map(|m| m.as_str()).unwrap_or_default();
-                    let date_str = format!("{month} {day} {year}");
-                    active_date = NaiveDate::parse_from_str(&date_str, "%B %d %Y")
-                        .ok()
-                        .or_else(|| NaiveDate::parse_from_str(&date_str, "%b %d %Y").ok());
-                    active_time = None;
-                    active_country = None;
-                    waiting_for_country = false;
-                    continue;
-                }
-
-                if let Some(caps) = time_line.captures(line) {
-                    active_time = caps.get(1).map(|m| m.as_str().to_string());
-                    waiting_for_country = true;
-                    continue;
-                }
-
-                if waiting_for_country {
-                    if country_line_re.is_match(line) {
-                        active_country = Some(line.to_ascii_uppercase());
-                        waiting_for_country = false;
-                        continue;
-                    }
-
-                    // If country row is missing, continue with this line as payload.
-                    waiting_for_country = false;
-                }
-
-                if country_line_re.is_match(line) {
-                    // Standalone country marker; don't emit as event title.
-                    active_country = Some(line.to_ascii_uppercase());
-                    continue;
-                }
-
-                let Some(date) = active_date else {
-                    continue;
-                };
-                let Some(time_text) = active_time.as_deref() else {
-                    continue;
-                };
-                let country = active_country
-                    .clone()
-                    .unwrap_or_else(|| default_country.clone());
-
-                let Some(start) =
-                    combine_date_time(date, time_text, source.config.source.timezone.as_deref())?
-                else {
-                    continue;
-                };
-
-                let columns = split_columns
-                    .split(line)
-                    .map(str::trim)
-                    .filter(|v| !v.is_empty())
-                    .collect::<Vec<_>>();
-                if columns.is_empty() {
-                    continue;
-                }
-
-                let title = columns[0].to_string();
-                if title.len() < 4 || !title.chars().any(|c| c.is_ascii_alphabetic()) {
-                    continue;
-                }
-                let actual = columns.get(1).map(|v| v.to_string());
-                let previous = columns.get(2).map(|v| v.to_string());
-                let consensus = columns.get(3).map(|v| v.to_string());
-                let forecast = columns.get(4).map(|v| v.to_string());
-
-                let mut metadata = BTreeMap::new();
-                metadata.insert("country".to_string(), country.clone());
-                metadata.insert("custom_parser".to_string(), self.key().to_string());
-                if let Some(value) = &actual {
-                    metadata.insert("actual".to_string(), value.clone());
-                }
-                if let Some(value) = &previous {
-                    metadata.insert("previous".to_string(), value.clone());
-                }
-                if let Some(value) = &consensus {
-                    metadata.insert("consensus".to_string(), value.clone());
-                }
-                if let Some(value) = &forecast {
-                    metadata.insert("forecast".to_string(), value.clone());
-                }
-
-                let id = format!(
-                    "{}|{}|{}|{}",
-                    country,
-                    date.format("%Y-%m-%d"),
-                    time_text,
-                    title
-                );
-
-                let description = build_econ_description(actual, previous, consensus, forecast);
-
-                events.push(CandidateEvent {
-                    source_key: source.config.source.key.clone(),
-                    source_name: source.config.source.name.clone(),
-                    source_event_id: Some(id),
-                    source_url: Some(doc.source_url.clone()),
-                    title,
-                    description,
-                    time: EventTimeSpec::DateTime { start, end: None },
-                    timezone: source.config.source.timezone.clone(),
-                    status: source.config.event.status.clone(),
-                    event_type: source.config.event.event_type.clone(),
-                    subtype: source.config.event.subtype.clone(),
-                    categories: source.config.event.categories.clone(),
-                    jurisdiction: source.config.source.jurisdiction.clone(),
-                    country: Some(country),
-                    importance: source.config.event.importance,
-                    confidence: Some(0.9),
-                    metadata,
-                });
-            }
-        }
-
-        Ok(events)
-    }
-}
-
-struct EuropeElectionsFeedParser;
-
-impl CustomParser for EuropeElectionsFeedParser {
-    fn key(&self) -> &'static str {
-        "europe_elections_feed_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        parse_structured_elections_feed(self.key(), source, docs, None, None)
-    }
-}
-
-struct StructuredCalendarFeedParser;
-
-impl CustomParser for StructuredCalendarFeedParser {
-    fn key(&self) -> &'static str {
-        "structured_calendar_feed_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        parse_structured_elections_feed(
-            self.key(),
-            source,
-            docs,
-            source.config.custom.filter_field.as_deref(),
-            source.config.custom.filter_value.as_deref(),
-        )
-    }
-}
-
-struct UsStateElectionsFeedParser;
-
-impl CustomParser for UsStateElectionsFeedParser {
-    fn key(&self) -> &'static str {
-        "us_state_elections_feed_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        parse_structured_elections_feed(
-            self.key(),
-            source,
-            docs,
-            Some("state"),
-            source
-                .config
-                .source
-                .default_country
-                .as_deref()
-                .or(source.config.source.jurisdiction.as_deref()),
-        )
-    }
-}
-
-struct MlbStatsApiScheduleParser;
-
-impl CustomParser for MlbStatsApiScheduleParser {
-    fn key(&self) -> &'static str {
-        "mlb_statsapi_schedule_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        let mut events = Vec::new();
-
-        for doc in docs {
-            let payload: Value = serde_json::from_slice(&doc.body)
-                .with_context(|| format!("failed to parse mlb schedule json from {}", doc.source_url))?;
-            let Some(dates) = payload.get("dates").and_then(Value::as_array) else {
-                continue;
-            };
-
-            for date in dates {
-                let Some(games) = date.get("games").and_then(Value::as_array) else {
-                    continue;
-                };
-
-                for game in games {
-                    let Some(game_pk) = game.get("gamePk").and_then(Value::as_i64) else {
-                        continue;
-                    };
-                    let Some(game_date_raw) = game.get("gameDate").and_then(Value::as_str) else {
-                        continue;
-                    };
-                    let Ok(start) = DateTime::parse_from_rfc3339(game_date_raw).map(|dt| dt.with_timezone(&Utc)) else {
-                        continue;
-                    };
-
-                    let away_name = game
-                        .pointer("/teams/away/team/name")
-                        .and_then(Value::as_str)
-                        .unwrap_or("Away");
-                    let home_name = game
-                        .pointer("/teams/home/team/name")
-                        .and_then(Value::as_str)
-                        .unwrap_or("Home");
-                    let venue = game
-                        .pointer("/venue/name")
-                        .and_then(Value::as_str)
-                        .unwrap_or("Unknown venue");
-                    let phase = game
-                        .get("seriesDescription")
-                        .and_then(Value::as_str)
-                        .unwrap_or("MLB");
-                    let game_type = game
-                        .get("gameType")
-                        .and_then(Value::as_str)
-                        .unwrap_or("R");
-                    let subtype = match game_type {
-                        "S" => "preseason_game",
-                        "R" => "regular_season_game",
-                        "F" | "D" | "L" | "W" => "playoff_game",
-                        _ => "baseball_game",
-                    };
-                    let title = format!("MLB: {} at {}", away_name, home_name);
-                    let status = game
-                        .pointer("/status/detailedState")
-                        .and_then(Value::as_str)
-                        .unwrap_or("Scheduled");
-
-                    let mut metadata = BTreeMap::new();
-                    metadata.insert("league".to_string(), "mlb".to_string());
-                    metadata.insert("away_team".to_string(), away_name.to_string());
-                    metadata.insert("home_team".to_string(), home_name.to_string());
-                    metadata.insert("venue".to_string(), venue.to_string());
-                    metadata.insert("phase".to_string(), phase.to_string());
-                    metadata.insert("status_detail".to_string(), status.to_string());
-                    metadata.insert("custom_parser".to_string(), self.key().to_string());
-                    if let Some(day_night) = game.get("dayNight").and_then(Value::as_str) {
-                        metadata.insert("day_night".to_string(), day_night.to_string());
-                    }
-
-                    let description = format!(
-                        "{} at {} from {}. {}. Status: {}.",
-                        away_name, home_name, venue, phase, status
-                    );
-
-                    events.push(CandidateEvent {
-                        source_key: source.config.source.key.clone(),
-                        source_name: source.config.source.name.clone(),
-                        source_event_id: Some(game_pk.to_string()),
-                        source_url: Some(doc.source_url.clone()),
-                        title,
-                        description: Some(description),
-                        time: EventTimeSpec::DateTime { start, end: None },
-                        timezone: source.config.source.timezone.clone(),
-                        status: source.config.event.status.clone(),
-                        event_type: source.config.event.event_type.clone(),
-                        subtype: Some(subtype.to_string()),
-                        categories: source.config.event.categories.clone(),
-                        jurisdiction: source.config.source.jurisdiction.clone(),
-                        country: source.config.source.default_country.clone(),
-                        importance: source.config.event.importance,
-                        confidence: Some(0.98),
-                        metadata,
-                    });
-                }
-            }
-        }
-
-        Ok(events)
-    }
-}
-
-struct NhlScheduleApiParser;
-
-impl CustomParser for NhlScheduleApiParser {
-    fn key(&self) -> &'static str {
-        "nhl_schedule_api_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        let Some(doc) = docs.first() else {
-            return Ok(Vec::new());
-        };
-
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(source.config.fetch.timeout_secs.max(30)))
-            .build()
-            .context("failed to build nhl api client")?;
-
-        let mut events = Vec::new();
-        let mut seen_urls = HashSet::new();
-        let mut next_url = Some(doc.source_url.clone());
-
-        while let Some(url) = next_url.take() {
-            if !seen_urls.insert(url.clone()) {
-                break;
-            }
-
-            let payload: Value = client
-                .get(&url)
-                .send()
-                .with_context(|| format!("failed to fetch nhl schedule json from {url}"))?
-                .error_for_status()
-                .with_context(|| format!("nhl schedule api returned error for {url}"))?
-                .json()
-                .with_context(|| format!("failed to decode nhl schedule json from {url}"))?;
-
-            if let Some(weeks) = payload.get("gameWeek").and_then(Value::as_array) {
-                for week in weeks {
-                    let Some(games) = week.get("games").and_then(Value::as_array) else {
-                        continue;
-                    };
-
-                    for game in games {
-                        let Some(game_id) = game.get("id").and_then(Value::as_i64) else {
-                            continue;
-                        };
-                        let Some(start_raw) = game.get("startTimeUTC").and_then(Value::as_str) else {
-                            continue;
-                        };
-                        let Ok(start) = DateTime::parse_from_rfc3339(start_raw).map(|dt| dt.with_timezone(&Utc)) else {
-                            continue;
-                        };
-
-                        let away_place = game
-                            .pointer("/awayTeam/placeName/default")
-                            .and_then(Value::as_str)
-                            .unwrap_or("Away");
-                        let away_name = game
-                            .pointer("/awayTeam/commonName/default")
-                            .and_then(Value::as_str)
-                            .unwrap_or("Team");
-                        let home_place = game
-                            .pointer("/homeTeam/placeName/default")
-                            .and_then(Value::as_str)
-                            .unwrap_or("Home");
-                        let home_name = game
-                            .pointer("/homeTeam/commonName/default")
-                            .and_then(Value::as_str)
-                            .unwrap_or("Team");
-                        let venue = game
-                            .pointer("/venue/default")
-                            .and_then(Value::as_str)
-                            .unwrap_or("Unknown venue");
-                        let title = format!(
-                            "NHL: {} {} at {} {}",
-                            away_place, away_name, home_place, home_name
-                        );
-                        let game_type = game.get("gameType").and_then(Value::as_i64).unwrap_or(2);
-                        let subtype = match game_type {
-                            1 => "preseason_game",
-                            2 => "regular_season_game",
-                            3 => "playoff_game",
-                            _ => "hockey_game",
-                        };
-                        let special_event = game.get("specialEvent").and_then(Value::as_str);
-                        let description = if let Some(event_name) = special_event {
-                            format!(
-                                "{} {} at {} {} from {}. Special event: {}.",
-                                away_place, away_name, home_place, home_name, venue, event_name
-                            )
-                        } else {
-                            format!(
-                                "{} {} at {} {} from {}.",
-                                away_place, away_name, home_place, home_name, venue
-                            )
-                        };
-
-                        let mut metadata = BTreeMap::new();
-                        metadata.insert("league".to_string(), "nhl".to_string());
-                        metadata.insert(
-                            "away_team".to_string(),
-                            format!("{} {}", away_place, away_name),
-                        );
-                        metadata.insert(
-                            "home_team".to_string(),
-                            format!("{} {}", home_place, home_name),
-                        );
-                        metadata.insert("venue".to_string(), venue.to_string());
-                        metadata.insert("custom_parser".to_string(), self.key().to_string());
-                        if let Some(state) = game.get("gameScheduleState").and_then(Value::as_str) {
-                            metadata.insert("schedule_state".to_string(), state.to_string());
-                        }
-
-                        events.push(CandidateEvent {
-                            source_key: source.config.source.key.clone(),
-                            source_name: source.config.source.name.clone(),
-                            source_event_id: Some(game_id.to_string()),
-                            source_url: Some(url.clone()),
-                            title,
-                            description: Some(description),
-                            time: EventTimeSpec::DateTime { start, end: None },
-                            timezone: source.config.source.timezone.clone(),
-                            status: source.config.event.status.clone(),
-                            event_type: source.config.event.event_type.clone(),
-                            subtype: Some(subtype.to_string()),
-                            categories: source.config.event.categories.clone(),
-                            jurisdiction: source.config.source.jurisdiction.clone(),
-                            country: source.config.source.default_country.clone(),
-                            importance: source.config.event.importance,
-                            confidence: Some(0.98),
-                            metadata,
-                        });
-                    }
-                }
-            }
-
-            next_url = payload
-                .get("nextStartDate")
-                .and_then(Value::as_str)
-                .map(|date| format!("https://api-web.nhle.com/v1/schedule/{date}"));
-        }
-
-        Ok(events)
-    }
-}
-
-struct NbaFullScheduleParser;
-
-impl CustomParser for NbaFullScheduleParser {
-    fn key(&self) -> &'static str {
-        "nba_full_schedule_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        let mut events = Vec::new();
-
-        for doc in docs {
-            let payload: Value = serde_json::from_slice(&doc.body)
-                .with_context(|| format!("failed to parse nba schedule json from {}", doc.source_url))?;
-            let Some(months) = payload.get("lscd").and_then(Value::as_array) else {
-                continue;
-            };
-
-            for month in months {
-                let Some(games) = month.pointer("/mscd/g").and_then(Value::as_array) else {
-                    continue;
-                };
-
-                for game in games {
-                    let Some(game_id) = game.get("gid").and_then(Value::as_str) else {
-                        continue;
-                    };
-                    let Some(date_raw) = game.get("gdte").and_then(Value::as_str) else {
-                        continue;
-                    };
-                    let Some(utc_time_raw) = game.get("utctm").and_then(Value::as_str) else {
-                        continue;
-                    };
-                    let Ok(start) = DateTime::parse_from_rfc3339(&format!("{date_raw}T{utc_time_raw}:00Z"))
-                        .map(|dt| dt.with_timezone(&Utc)) else {
-                        continue;
-                    };
-
-                    let away_city = game.pointer("/v/tc").and_then(Value::as_str).unwrap_or("Away");
-                    let away_name = game.pointer("/v/tn").and_then(Value::as_str).unwrap_or("Team");
-                    let home_city = game.pointer("/h/tc").and_then(Value::as_str).unwrap_or("Home");
-                    let home_name = game.pointer("/h/tn").and_then(Value::as_str).unwrap_or("Team");
-                    let venue = game.get("an").and_then(Value::as_str).unwrap_or("Unknown venue");
-                    let title = format!("NBA: {} {} at {} {}", away_city, away_name, home_city, home_name);
-                    let status_code = game.get("st").and_then(Value::as_str).unwrap_or("1");
-                    let subtype = match status_code {
-                        "1" | "2" | "3" => "regular_season_game",
-                        _ => "basketball_game",
-                    };
-
-                    let mut metadata = BTreeMap::new();
-                    metadata.insert("league".to_string(), "nba".to_string());
-                    metadata.insert("away_team".to_string(), format!("{} {}", away_city, away_name));
-                    metadata.insert("home_team".to_string(), format!("{} {}", home_city, home_name));
-                    metadata.insert("venue".to_string(), venue.to_string());
-                    metadata.insert("custom_parser".to_string(), self.key().to_string());
-                    if let Some(pretty) = game.get("stt").and_then(Value::as_str) {
-                        metadata.insert("tipoff_label".to_string(), pretty.to_string());
-                    }
-                    if let Some(broadcasts) = game.pointer("/bd/b").and_then(Value::as_array) {
-                        let names = broadcasts
-                            .iter()
-                            .filter_map(|b| b.get("disp").and_then(Value::as_str))
-                            .collect::<Vec<_>>();
-                        if !names.is_empty() {
-                            metadata.insert("broadcasts".to_string(), names.join(" | "));
-                        }
-                    }
-
-                    let description = format!(
-                        "{} {} at {} {} from {}.",
-                        away_city, away_name, home_city, home_name, venue
-                    );
-
-                    events.push(CandidateEvent {
-                        source_key: source.config.source.key.clone(),
-                        source_name: source.config.source.name.clone(),
-                        source_event_id: Some(game_id.to_string()),
-                        source_url: Some(doc.source_url.clone()),
-                        title,
-                        description: Some(description),
-                        time: EventTimeSpec::DateTime { start, end: None },
-                        timezone: source.config.source.timezone.clone(),
-                        status: source.config.event.status.clone(),
-                        event_type: source.config.event.event_type.clone(),
-                        subtype: Some(subtype.to_string()),
-                        categories: source.config.event.categories.clone(),
-                        jurisdiction: source.config.source.jurisdiction.clone(),
-                        country: source.config.source.default_country.clone(),
-                        importance: source.config.event.importance,
-                        confidence: Some(0.97),
-                        metadata,
-                    });
-                }
-            }
-        }
-
-        Ok(events)
-    }
-}
-
-struct NflOperationsScheduleParser;
-
-impl CustomParser for NflOperationsScheduleParser {
-    fn key(&self) -> &'static str {
-        "nfl_operations_schedule_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        let mut events = Vec::new();
-
-        for doc in docs {
-            let html_text = String::from_utf8_lossy(&doc.body).to_string();
-            let parsed = Html::parse_document(&html_text);
-            let details_sel = Selector::parse("details.week-section")
-                .map_err(|_| anyhow!("failed to parse nfl week selector"))?;
-            let summary_sel =
-                Selector::parse("summary").map_err(|_| anyhow!("failed to parse summary selector"))?;
-            let date_sel = Selector::parse("div.game-date")
-                .map_err(|_| anyhow!("failed to parse game-date selector"))?;
-            let table_sel = Selector::parse("table.game-table")
-                .map_err(|_| anyhow!("failed to parse table selector"))?;
-            let tr_sel = Selector::parse("tr").map_err(|_| anyhow!("failed to parse tr selector"))?;
-            let td_sel = Selector::parse("td").map_err(|_| anyhow!("failed to parse td selector"))?;
-
-            for section in parsed.select(&details_sel) {
-                let summary_text = section
-                    .select(&summary_sel)
-                    .next()
-                    .map(|s| s.text().collect::<Vec<_>>().join(" "))
-                    .unwrap_or_default();
-                let week_label = summary_text.split('(').next().unwrap_or("").trim().to_string();
-                let week_range = summary_text
-                    .split('(')
-                    .nth(1)
-                    .map(|v| v.trim_end_matches(')').trim().to_string());
-
-                let date_nodes = section.select(&date_sel).collect::<Vec<_>>();
-                let table_nodes = section.select(&table_sel).collect::<Vec<_>>();
-                for (date_node, table_node) in date_nodes.into_iter().zip(table_nodes.into_iter()) {
-                    let date_label = date_node.text().collect::<Vec<_>>().join(" ").trim().to_string();
-                    for row in table_node.select(&tr_sel) {
-                        let cols = row
-                            .select(&td_sel)
-                            .map(|td| td.text().collect::<Vec<_>>().join(" ").trim().to_string())
-                            .filter(|v| !v.is_empty())
-                            .collect::<Vec<_>>();
-                        if cols.len() < 3 {
-                            continue;
-                        }
-
-                        let matchup = cols[0].clone();
-                        let kickoff = cols[1].clone();
-                        let network = cols[2].clone();
-                        if matchup == "TBD" {
-                            continue;
-                        }
-
-                        let (title, metadata_matchup) = normalize_nfl_matchup(&matchup);
-                        let time = if date_label == "Date TBD" || kickoff == "TBD" {
-                            EventTimeSpec::Tbd {
-                                note: Some(format!(
-                                    "{} {} {}",
-                                    week_label,
-                                    week_range.clone().unwrap_or_default(),
-                                    matchup
-                                )),
-                            }
-                        } else if let Some(start) = parse_nfl_datetime(&date_label, &kickoff)? {
-                            EventTimeSpec::DateTime { start, end: None }
-                        } else {
-                            EventTimeSpec::Tbd {
-                                note: Some(format!("{date_label} {kickoff}")),
-                            }
-                        };
-
-                        let mut metadata = BTreeMap::new();
-                        metadata.insert("league".to_string(), "nfl".to_string());
-                        metadata.insert("week".to_string(), week_label.clone());
-                        metadata.insert("network".to_string(), network.clone());
-                        metadata.insert("matchup".to_string(), matchup.clone());
-                        metadata.insert("custom_parser".to_string(), self.key().to_string());
-                        if let Some(range) = &week_range {
-                            metadata.insert("week_range".to_string(), range.clone());
-                        }
-                        for (k, v) in metadata_matchup {
-                            metadata.insert(k, v);
-                        }
-
-                        let description = format!(
-                            "{}. {} on {}. {}.",
-                            matchup, week_label, network, date_label
-                        );
-
-                        events.push(CandidateEvent {
-                            source_key: source.config.source.key.clone(),
-                            source_name: source.config.source.name.clone(),
-                            source_event_id: Some(format!("{}|{}|{}", week_label, date_label, matchup)),
-                            source_url: Some(doc.source_url.clone()),
-                            title,
-                            description: Some(description),
-                            time,
-                            timezone: source.config.source.timezone.clone(),
-                            status: source.config.event.status.clone(),
-                            event_type: source.config.event.event_type.clone(),
-                            subtype: Some("regular_season_game".to_string()),
-                            categories: source.config.event.categories.clone(),
-                            jurisdiction: source.config.source.jurisdiction.clone(),
-                            country: source.config.source.default_country.clone(),
-                            importance: source.config.event.importance,
-                            confidence: Some(0.98),
-                            metadata,
-                        });
-                    }
-                }
-            }
-        }
-
-        Ok(events)
-    }
-}
-
-struct MlsStatsApiScheduleParser;
-
-impl CustomParser for MlsStatsApiScheduleParser {
-    fn key(&self) -> &'static str {
-        "mls_statsapi_schedule_v1"
-    }
-
-    fn parse(
-        &self,
-        source: &LoadedSource,
-        docs: &[FetchedDocument],
-    ) -> Result<Vec<CandidateEvent>> {
-        let Some(doc) = docs.first() else {
-            return Ok(Vec::new());
-        };
-
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(source.config.fetch.timeout_secs.max(30)))
-            .build()
-            .context("failed to build mls api client")?;
-
-        let mut events = Vec::new();
-        let mut next_page_token: Option<String> = None;
-
-        loop {
-            let mut request = client.get(&doc.source_url);
-            if let Some(token) = &next_page_token {
-                request = request.query(&[("page_token", token.as_str())]);
-            }
-
-            let payload: Value = request
-                .send()
-                .with_context(|| format!("failed to fetch mls schedule json from {}", doc.source_url))?
-                .error_for_status()
-                .with_context(|| format!("mls schedule api returned error for {}", doc.source_url))?
-                .json()
-                .with_context(|| format!("failed to decode mls schedule json from {}", doc.source_url))?;
-
-            let Some(schedule) = payload.get("schedule").and_then(Value::as_array) else {
-                break;
-            };
-
-            for game in schedule {
-                let Some(game_id) = game.get("match_id").and_then(Value::as_str) else {
-                    continue;
-                };
-                let Some(start_raw) = game.get("planned_kickoff_time").and_then(Value::as_str) else {
-                    continue;
-                };
-                let Ok(start) =
-                    DateTime::parse_from_rfc3339(start_raw).map(|dt| dt.with_timezone(&Utc))
-                else {
-                    continue;
-                };
-
-                let away_name = game
-                    .get("away_team_name")
-                    .and_then(Value::as_str)
-                    .unwrap_or("Away");
-                let home_name = game
-                    .get("home_team_name")
-                    .and_then(Value::as_str)
-                    .unwrap_or("Home");
-                let venue = game
-                    .get("stadium_name")
-                    .and_then(Value::as_str)
-                    .unwrap_or("Unknown venue");
-                let title = format!("MLS: {} at {}", away_name, home_name);
-                let competition = game
-                    .get("competition_name")
-                    .and_then(Value::as_str)
-                    .unwrap_or("MLS");
-                let match_type = game
-                    .get("match_type")
-                    .and_then(Value::as_str)
-                    .unwrap_or("Regular season");
-                let status = game
-                    .get("match_date_time_status")
-                    .and_then(Value::as_str)
-                    .unwrap_or("Scheduled");
-
-                let mut metadata = BTreeMap::new();
-                metadata.insert("league".to_string(), "mls".to_string());
-                metadata.insert("away_team".to_string(), away_name.to_string());
-                metadata.insert("home_team".to_string(), home_name.to_string());
-                metadata.insert("venue".to_string(), venue.to_string());
-                metadata.insert("competition".to_string(), competition.to_string());
-                metadata.insert("match_type".to_string(), match_type.to_string());
-                metadata.insert("status_detail".to_string(), status.to_string());
-                metadata.insert("custom_parser".to_string(), self.key().to_string());
-                if let Some(sub_league) = game.get("sub_league").and_then(Value::as_str) {
-                    metadata.insert("sub_league".to_string(), sub_league.to_string());
-                }
-                if let Some(match_day) = game.get("match_day").and_then(Value::as_i64) {
-                    metadata.insert("match_day".to_string(), match_day.to_string());
-                }
-                if let Some(city) = game.get("stadium_city").and_then(Value::as_str) {
-                    metadata.insert("stadium_city".to_string(), city.to_string());
-                }
-                if let Some(code) = game
-                    .get("home_team_three_letter_code")
-                    .and_then(Value::as_str)
-                {
-                    metadata.insert("home_code".to_string(), code.to_string());
-                }
-                if let Some(code) = game
-                    .get("away_team_three_letter_code")
-                    .and_then(Value::as_str)
-                {
-                    metadata.insert("away_code".to_string(), code.to_string());
-                }
-                if let Some(neutral) = game.get("neutral_venue").and_then(Value::as_bool) {
-                    metadata.insert("neutral_venue".to_string(), neutral.to_string());
-                }
-                if let Some(token) = game.get("competition_label").and_then(Value::as_str) {
-                    metadata.insert("competition_label".to_string(), token.to_string());
-                }
-
-                let description = format!(
-                    "{} at {} from {}. {}. Status: {}.",
-                    away_name, home_name, venue, match_type, status
-                );
-
-                events.push(CandidateEvent {
-                    source_key: source.config.source.key.clone(),
-                    source_name: source.config.source.name.clone(),
-                    source_event_id: Some(game_id.to_string()),
-                    source_url: Some(doc.source_url.clone()),
-                    title,
-                    description: Some(description),
-                    time: EventTimeSpec::DateTime { start, end: None },
-                    timezone: source.config.source.timezone.clone(),
-                    status: source.config.event.status.clone(),
-                    event_type: source.config.event.event_type.clone(),
-                    subtype: Some("regular_season_game".to_string()),
-                    categories: source.config.event.categories.clone(),
-                    jurisdiction: source.config.source.jurisdiction.clone(),
-                    country: source.config.source.default_country.clone(),
-                    importance: source.config.event.importance,
-                    confidence: Some(0.98),
-                    metadata,
-                });
-            }
-
-            next_page_token = payload
-                .get("next_page_token")
-                .and_then(Value::as_str)
-                .map(str::to_owned);
-            if next_page_token.is_none() {
-                break;
-            }
-        }
-
-        Ok(events)
-    }
-}
-
-fn normalize_nfl_matchup(matchup: &str) -> (String, BTreeMap<String, String>) {
-    let mut metadata = BTreeMap::new();
-    if let Some((away, home)) = matchup.split_once(" at ") {
-        metadata.insert("away_team".to_string(), away.to_string());
-        metadata.insert("home_team".to_string(), home.to_string());
-        return (format!("NFL: {} at {}", away, home), metadata);
-    }
-    if let Some((away, rest)) = matchup.split_once(" vs ") {
-        metadata.insert("away_team".to_string(), away.to_string());
-        metadata.insert("home_team".to_string(), rest.to_string());
-        metadata.insert("neutral_site".to_string(), "true".to_string());
-        return (format!("NFL: {} vs {}", away, rest), metadata);
-    }
-    (format!("NFL: {}", matchup), metadata)
-}
-
-fn parse_nfl_datetime(date_label: &str, kickoff: &str) -> Result<Option<DateTime<Utc>>> {
-    let normalized_date = date_label
-        .replace("Sept.", "Sep.")
-        .replace("Sept ", "Sep ");
-    let clean_time = kickoff.trim().trim_end_matches('*');
-    let Some((hour_text, rest)) = clean_time.split_once(':') else {
-        return Ok(None);
-    };
-    let minute_digits = rest.chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
-    let suffix = rest.chars().skip_while(|c| c.is_ascii_digit()).collect::<String>();
-    let mut hour: u32 = match hour_text.parse() {
-        Ok(value) => value,
-        Err(_) => return Ok(None),
-    };
-    let minute: u32 = match minute_digits.parse() {
-        Ok(value) => value,
-        Err(_) => return Ok(None),
-    };
-    let lower_suffix = suffix.to_ascii_lowercase();
-    if lower_suffix.starts_with('p') && hour != 12 {
-        hour += 12;
-    }
-    if lower_suffix.starts_with('a') && hour == 12 {
-        hour = 0;
-    }
-
-    let date = NaiveDate::parse_from_str(&normalized_date, "%A, %b. %e, %Y")
-        .or_else(|_| NaiveDate::parse_from_str(&normalized_date, "%A, %b %e, %Y"))
-        .map_err(|err| anyhow!("failed to parse nfl date '{date_label}': {err}"))?;
-    let naive = date
-        .and_hms_opt(hour, minute, 0)
-        .ok_or_else(|| anyhow!("invalid nfl time {clean_time}"))?;
-    let eastern: Tz = chrono_tz::US::Eastern;
-    let local = eastern
-        .from_local_datetime(&naive)
-        .single()
-        .ok_or_else(|| anyhow!("ambiguous nfl local datetime {naive}"))?;
-    Ok(Some(local.with_timezone(&Utc)))
-}
-
-fn parse_structured_elections_feed(
-    parser_key: &str,
-    source: &LoadedSource,
-    docs: &[FetchedDocument],
-    filter_field: Option<&str>,
-    filter_value: Option<&str>,
-) -> Result<Vec<CandidateEvent>> {
-    let mut events = Vec::new();
-    let normalized_filter = filter_value.map(|value| value.to_ascii_uppercase());
-
-    for doc in docs {
-        let payload = String::from_utf8_lossy(&doc.body);
-        for raw_line in payload.lines() {
-            let line = raw_line.trim();
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            let parts = line
-                .split('|')
-                .map(str::trim)
-                .filter(|part| !part.is_empty())
-                .collect::<Vec<_>>();
-            if parts.len() < 2 {
-                continue;
-            }
-
-            let start_raw = parts[0];
-            let mut title = parts[1].to_string();
-            if !title
-                .to_ascii_lowercase()
-                .starts_with(&source.config.source.name.to_ascii_lowercase())
-            {
-                title = format!("{}: {title}", source.config.source.name);
-            }
-
-            let mut fields = BTreeMap::new();
-            for part in &parts[2..] {
-                let Some((key, value)) = part.split_once('=') else {
-                    continue;
-                };
-                fields.insert(key.trim().to_string(), value.trim().to_string());
-            }
-
-            if let (Some(field), Some(expected)) = (filter_field, normalized_filter.as_deref()) {
-                let actual = fields
-                    .get(field)
-                    .map(|value| value.to_ascii_uppercase())
-                    .unwrap_or_default();
-                if actual != expected {
-                    continue;
-                }
-            }
-
-            let time = if start_raw.eq_ignore_ascii_case("tbd") {
-                EventTimeSpec::Tbd {
-                    note: fields
-                        .get("tbd")
-                        .cloned()
-                        .or_else(|| Some("Date not yet confirmed".to_string())),
-                }
-            } else {
-                parse_event_time(
-                    start_raw,
-                    fields.get("end").map(String::as_str),
-                    &source.config.date,
-                    source.config.source.timezone.as_deref(),
-                )?
-            };
-
-            let subtype = fields
-                .get("subtype")
-                .cloned()
-                .or_else(|| source.config.event.subtype.clone());
-            let source_url = fields
-                .get("source_url")
-                .cloned()
-                .or_else(|| fields.get("official_url").cloned())
-                .or_else(|| Some(doc.source_url.clone()));
-            let source_event_id = fields.get("source_event_id").cloned().or_else(|| {
-                Some(format!(
-                    "{}|{}|{}",
-                    source.config.source.key, start_raw, title
-                ))
-            });
-            let status = fields
-                .get("status")
-                .cloned()
-                .unwrap_or_else(|| source.config.event.status.clone());
-            let confidence = fields
-                .get("confidence")
-                .and_then(|v| v.parse::<f32>().ok())
-                .or(Some(0.95));
-            let importance = fields
-                .get("importance")
-                .and_then(|v| v.parse::<u8>().ok())
-                .or(source.config.event.importance);
-            let description = fields.get("description").cloned();
-
-            let mut metadata = BTreeMap::new();
-            for (key, value) in &fields {
-                if ["end", "status", "subtype", "importance", "confidence", "description"]
-                    .contains(&key.as_str())
-                {
-                    continue;
-                }
-                metadata.insert(key.clone(), value.clone());
-            }
-            metadata.insert("custom_parser".to_string(), parser_key.to_string());
-            metadata.entry("country".to_string()).or_insert_with(|| {
-                source
-                    .config
-                    .source
-                    .default_country
-                    .clone()
-                    .unwrap_or_default()
-            });
-            metadata
-                .entry("source_class".to_string())
-                .or_insert_with(|| "curated".to_string());
-
-            let mut categories = source.config.event.categories.clone();
-            categories.push(source.config.source.domain.clone());
-            if let Some(country) = source.config.source.default_country.as_deref() {
-                categories.push(country.to_ascii_lowercase());
-            }
-            categories.sort();
-            categories.dedup();
-
-            events.push(CandidateEvent {
-                source_key: source.config.source.key.clone(),
-                source_name: source.config.source.name.clone(),
-                source_event_id,
-                source_url,
-                title,
-                description,
-                time,
-                timezone: source.config.source.timezone.clone(),
-                status,
-                event_type: source.config.event.event_type.clone(),
-                subtype,
-                categories,
-                jurisdiction: source.config.source.jurisdiction.clone(),
-                country: source.config.source.default_country.clone(),
-                importance,
-                confidence,
-                metadata,
-            });
-        }
-    }
-
-    Ok(events)
-}
-
-fn combine_date_time(
-    date: NaiveDate,
-    time_text: &str,
-    timezone: Option<&str>,
-) -> Result<Option<DateTime<Utc>>> {
-    let time = NaiveDateTime::parse_from_str(
-        &format!("{} {}", date.format("%Y-%m-%d"), time_text.replace(" ", "")),
-        "%Y-%m-%d %I:%M%p",
-    )
-    .ok()
-    .or_else(|| {
-        NaiveDateTime::parse_from_str(
-            &format!("{} {}", date.format("%Y-%m-%d"), time_text),
-            "%Y-%m-%d %I:%M %p",
-        )
-        .ok()
-    });
-
-    let Some(naive) = time else {
-        return Ok(None);
-    };
-
-    if let Some(tz_name) = timezone
-        && let Ok(tz) = tz_name.parse::<Tz>()
-        && let Some(dt) = tz
-            .from_local_datetime(&naive)
-            .earliest()
-            .or_else(|| tz.from_local_datetime(&naive).latest())
-    {
-        return Ok(Some(dt.with_timezone(&Utc)));
-    }
-
-    Ok(Some(Utc.from_utc_datetime(&naive)))
-}
-
-fn build_econ_description(
-    actual: Option<String>,
-    previous: Option<String>,
-    consensus: Option<String>,
-    forecast: Option<String>,
-) -> Option<String> {
-    let mut lines = Vec::new();
-    if let Some(v) = actual {
-        lines.push(format!("Actual: {v}"));
-    }
-    if let Some(v) = previous {
-        lines.push(format!("Previous: {v}"));
-    }
-    if let Some(v) = consensus {
-        lines.push(format!("Consensus: {v}"));
-    }
-    if let Some(v) = forecast {
-        lines.push(format!("Forecast: {v}"));
-    }
-
-    if lines.is_empty() {
-        None
-    } else {
-        Some(lines.join("\n"))
-    }
-}
-
 trait SourceConfigHelpers {
     fn configured_base_url(&self) -> Option<String>;
 }
@@ -1,18 +1,57 @@
+use crate::assets::mirror_source_assets;
+use crate::changes::{
+    ChangeEvent, ChangeKind, change_events, diff_source, publish_changes, snapshot_source,
+};
 use crate::config::{
-    LoadedBundle, LoadedSource, load_bundles_from_dir, load_source_file, load_sources_from_dir,
+    AliasMode, Compression, DigestPeriod, FeedConfig, FetchMode, ImportanceConfig, LifecycleConfig,
+    LoadedBundle, LoadedSource, MergeConfig, MergePolicy, PublishConfig, SmtpConfig, TbdAction,
+    TrimOrder, UidAlgorithm, UidConfig, config_roots, load_bundles_from_dir,
+    load_global_categorize, load_global_feed, load_global_importance, load_global_redirects,
+    load_global_smtp, load_source_file, load_sources_for_tenant, load_sources_from_dir,
+};
+use crate::enrich::{Categorizer, ImportanceScorer};
+use crate::fetch::{
+    FetchedDocument, fetch_source_documents_with_cache, preflight_source, single_document_url,
+};
+use crate::http_cache::HttpCache;
+use crate::ics::{
+    compressed_variant_names, file_digest, render_named_year_calendar, rendered_event_len,
+    stream_source_year_calendar, write_compressed_variants, write_named_year_calendar,
+    write_source_year_calendar,
+};
+use crate::invites::{Invite, pending_invites, send_invites};
+use crate::manifest::{reconcile_owned_files, record_owned_files};
+use crate::model::{
+    Attachment, CandidateEvent, EventRecord, EventTimeSpec, FieldCoverage, Lifecycle,
+    LocalizedText, ParseWarning, ParseWarningKind, RecurrenceDate, ResourceValidators, RunRecord,
+    SourceRunReport, SourceRunSummary, State, store_key,
 };
-use crate::fetch::fetch_source_documents;
-use crate::ics::{write_named_year_calendar, write_source_year_calendar};
-use crate::model::{CandidateEvent, EventRecord, SourceRunReport, State};
-use crate::parser::parse_source_events;
-use crate::store::{load_state, save_state};
+use crate::parser::{ParseOutcome, RawFragment, parse_source_documents_with_raw};
+use crate::quarantine::{Quarantine, QuarantineEntry, suspicious_changes};
+use crate::recording::record_documents;
+use crate::redirects::write_redirect_map;
+use crate::search::refresh_search_index;
+use crate::staging::Staging;
+use crate::store::{EventQuery, EventStore, JsonFileStore, StateLock, load_state};
+use crate::tenant::Tenant;
 use anyhow::{Context, Result, bail};
-use chrono::Utc;
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use rayon::prelude::*;
 use serde::Serialize;
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha384, Sha512};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use tracing::{info, warn};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
+use tracing::{Span, debug, info, info_span, warn};
+
+/// `--jobs` default: enough to overlap slow hosts without hammering any.
+pub const DEFAULT_SYNC_JOBS: usize = 4;
+
+/// Fall in a field's fill rate (absolute, `0.0..=1.0`) between runs that
+/// is reported as a coverage drop.
+const COVERAGE_DROP_THRESHOLD: f64 = 0.25;
 
 #[derive(Debug, Clone)]
 pub struct SyncOptions {
@@ -21,6 +60,78 @@ pub struct SyncOptions {
     pub out_dir: PathBuf,
     pub source: Option<String>,
     pub dry_run: bool,
+    /// Sources fetched and parsed at once; 1 syncs one after another.
+    pub jobs: usize,
+    /// Save each record's matched document text under `debug/raw/` in the
+    /// output directory, keyed by UID.
+    pub capture_raw: bool,
+    /// Save every http response under this directory for
+    /// `fetch.mode = "replay"`.
+    pub record_dir: Option<PathBuf>,
+    /// Fetch sources whose `fetch.min_interval` has not passed yet.
+    pub force: bool,
+    /// Fetch state of the config dir's sources; keep it across runs to
+    /// reuse connections, rate-limit slots and tokens.
+    pub tenant: Arc<Tenant>,
+}
+
+/// The CLI's defaults: `configs/sources`, `data/state/events.json` and
+/// `data/out`, with [`DEFAULT_SYNC_JOBS`].
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            config_dir: PathBuf::from("configs/sources"),
+            state_path: PathBuf::from("data/state/events.json"),
+            out_dir: PathBuf::from("data/out"),
+            source: None,
+            dry_run: false,
+            jobs: DEFAULT_SYNC_JOBS,
+            capture_raw: false,
+            record_dir: None,
+            force: false,
+            tenant: Arc::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MonitorOptions {
+    pub config_dir: PathBuf,
+    pub state_path: PathBuf,
+    pub source: Option<String>,
+    pub jobs: usize,
+    /// Fetch state of the config dir's sources, as in [`SyncOptions::tenant`].
+    pub tenant: Arc<Tenant>,
+}
+
+/// What a sync of one source would change, from `rics monitor`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MonitorReport {
+    pub source_key: String,
+    /// The preflight showed the source unchanged, so nothing was fetched.
+    pub not_modified: bool,
+    pub records_parsed: usize,
+    pub changes: Vec<MonitorChange>,
+    /// Updates a sync would hold for review instead of merging.
+    pub quarantined: usize,
+    /// Why fetching, parsing or merging the source failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl MonitorReport {
+    pub fn differs(&self) -> bool {
+        !self.changes.is_empty() || self.quarantined > 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorChange {
+    /// `event.created`, `event.updated` or `event.cancelled`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub store_key: String,
+    pub title: String,
 }
 
 #[derive(Debug, Clone)]
@@ -46,8 +157,26 @@ pub struct ValidateOptions {
     pub source_file: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone)]
+pub struct UidOptions {
+    pub config_dir: PathBuf,
+    pub source: String,
+    pub title: String,
+    pub date: Option<NaiveDate>,
+    pub id: Option<String>,
+    pub url: Option<String>,
+}
+
+/// When `source` may be fetched again under `fetch.min_interval`; `None`
+/// without an interval or a previous fetch.
+fn next_fetch_due(store: &JsonFileStore, source: &LoadedSource) -> Option<DateTime<Utc>> {
+    let interval = source.config.fetch.min_interval?;
+    let last = store.last_fetched(&source.config.source.key)?;
+    Some(last + interval.duration())
+}
+
 pub fn sync_sources(options: &SyncOptions) -> Result<Vec<SourceRunReport>> {
-    let mut sources = load_sources_from_dir(&options.config_dir)?;
+    let mut sources = load_sources_for_tenant(&options.config_dir, &options.tenant)?;
     if let Some(filter) = &options.source {
         sources.retain(|s| s.config.source.key == *filter);
     }
@@ -55,63 +184,744 @@ pub fn sync_sources(options: &SyncOptions) -> Result<Vec<SourceRunReport>> {
         bail!("no matching source configurations found");
     }
 
-    let mut state = load_state(&options.state_path)?;
+    let categorizer = Categorizer::new(&load_global_categorize(&options.config_dir)?)?;
+    let global_importance = load_global_importance(&options.config_dir)?;
+    let smtp = load_global_smtp(&options.config_dir)?;
+    let feed = load_global_feed(&options.config_dir)?;
+    let mut invites = Vec::new();
+    let mut feed_events = Vec::new();
+    // A dry run writes nothing, so it need not wait for another run.
+    let mut store = if options.dry_run {
+        JsonFileStore::open(&options.state_path)?
+    } else {
+        JsonFileStore::open_locked(&options.state_path)?
+    };
+    let mut quarantine = Quarantine::open(&Quarantine::path_for_state(&options.state_path))?;
+    let http_cache = HttpCache::open(&HttpCache::dir_for_state(&options.state_path))?;
     let mut reports = Vec::new();
+    let run_started_at = Utc::now();
+    let run_timer = Instant::now();
+    let run_id = new_run_id(run_started_at);
+    let _run_span = info_span!("sync", run_id = %run_id).entered();
+    let mut summaries = Vec::new();
+
+    let sources = sources
+        .into_iter()
+        .filter(|source| {
+            if !source.config.source.enabled {
+                info!(source = %source.config.source.key, "source disabled; skipping");
+            }
+            source.config.source.enabled
+        })
+        .filter(|source| {
+            let due = (!options.force)
+                .then(|| next_fetch_due(&store, source))
+                .flatten()
+                .filter(|due| run_started_at < *due);
+            if let Some(due) = due {
+                info!(
+                    source = %source.config.source.key,
+                    next_due = %due.to_rfc3339(),
+                    "fetched within fetch.min_interval; skipping"
+                );
+            }
+            due.is_none()
+        })
+        .collect::<Vec<_>>();
+    let spans = sources
+        .iter()
+        .map(|source| {
+            info_span!(
+                "source",
+                source = %source.config.source.key,
+                span_id = %source_span_id(&run_id, &source.config.source.key)
+            )
+        })
+        .collect::<Vec<_>>();
+    // Sources are fetched concurrently, but merged one at a time and in
+    // config order as their fetches complete, so state only ever holds
+    // finished merges and each is saved before the next.
+    let fetch_jobs = plan_fetches(&store, &sources, spans);
+    fetch_sources(
+        &http_cache,
+        &sources,
+        &fetch_jobs,
+        options.jobs,
+        options.capture_raw,
+        options.record_dir.as_deref(),
+        |index, fetched| {
+            let source = &sources[index];
+            let _source_span = fetch_jobs[index].span.enter();
+            let merge_started = Instant::now();
+            // A source that cannot be fetched or parsed leaves its state as it
+            // was; the others are still merged.
+            let fetched = match fetched {
+                Ok(fetched) => fetched,
+                Err(err) => {
+                    let error = format!("{err:#}");
+                    warn!(
+                        source = %source.config.source.key,
+                        error = %error,
+                        "source fetch failed; keeping its state"
+                    );
+                    let report = SourceRunReport {
+                        run_id: run_id.clone(),
+                        source_key: source.config.source.key.clone(),
+                        error: Some(error),
+                        ..SourceRunReport::default()
+                    };
+                    summaries.push(SourceRunSummary::from_report(
+                        &report,
+                        None,
+                        merge_started.elapsed().as_millis() as u64,
+                    ));
+                    reports.push(report);
+                    return Ok(());
+                }
+            };
+            let FetchedSource {
+                config_hash,
+                config_changed,
+                validators,
+                fetch_duration,
+                elapsed,
+                documents,
+            } = fetched;
+            let Some((docs, parsed)) = documents else {
+                store.set_last_fetched(&source.config.source.key, run_started_at);
+                let report = SourceRunReport {
+                    run_id: run_id.clone(),
+                    source_key: source.config.source.key.clone(),
+                    not_modified: true,
+                    fetch_duration_ms: fetch_duration.as_millis() as u64,
+                    ..SourceRunReport::default()
+                };
+                summaries.push(SourceRunSummary::from_report(
+                    &report,
+                    config_hash,
+                    report.fetch_duration_ms,
+                ));
+                reports.push(report);
+                if !options.dry_run {
+                    save_progress(&store, &quarantine, &http_cache)?;
+                }
+                return Ok(());
+            };
+            let mut candidates = parsed.events;
+            enrich_candidates(source, &categorizer, &global_importance, &mut candidates)?;
+            if options.capture_raw {
+                write_raw_fragments(&options.out_dir, source, &candidates, &parsed.fragments)?;
+            }
+            let coverage = FieldCoverage::from_candidates(&candidates);
+
+            let mut report = SourceRunReport {
+                run_id: run_id.clone(),
+                source_key: source.config.source.key.clone(),
+                pages_fetched: docs.len(),
+                records_parsed: candidates.len(),
+                fetch_duration_ms: fetch_duration.as_millis() as u64,
+                bytes_downloaded: docs.iter().map(|doc| doc.body.len() as u64).sum(),
+                skipped_records: parsed.skipped,
+                warnings: parsed.warnings,
+                field_coverage: coverage.rates.clone(),
+                ..SourceRunReport::default()
+            };
+            // Everything below changes only this source's slice of state, so a
+            // failure rolls it back and the run goes on with the next source.
+            let checkpoint = store.checkpoint(&source.config.source.key);
+            let quarantine_checkpoint = quarantine.clone();
+            let fetched_report = report.clone();
+            check_field_coverage(&mut store, source, coverage, &mut report);
+            let run = SourceRun {
+                options,
+                run_id: &run_id,
+                feed: feed.as_ref(),
+                smtp: smtp.as_ref(),
+            };
+            match merge_and_publish(
+                &mut store,
+                &mut quarantine,
+                &run,
+                source,
+                candidates,
+                config_changed,
+                &mut report,
+            ) {
+                Ok(published) => {
+                    feed_events.extend(published.feed_events);
+                    invites.extend(published.invites);
+                    if let Some(validators) = validators {
+                        store.set_fetch_validators(&source.config.source.key, validators);
+                    }
+                    report.config_changed = config_changed;
+                    if let Some(hash) = &config_hash {
+                        store.set_config_hash(&source.config.source.key, hash.clone());
+                    }
+                    store.set_last_fetched(&source.config.source.key, run_started_at);
+                }
+                Err(err) => {
+                    let error = format!("{err:#}");
+                    warn!(
+                        source = %source.config.source.key,
+                        error = %error,
+                        "source sync failed; rolling back its state"
+                    );
+                    store.rollback(checkpoint);
+                    quarantine = quarantine_checkpoint;
+                    report = SourceRunReport {
+                        error: Some(error),
+                        rolled_back: true,
+                        ..fetched_report
+                    };
+                    // Calendars may have been committed before the failure.
+                    if !options.dry_run
+                        && let Err(err) =
+                            rebuild_source_calendars(&store, source, &options.out_dir, None, None)
+                    {
+                        warn!(
+                            source = %source.config.source.key,
+                            error = %format!("{err:#}"),
+                            "failed to restore calendars from rolled-back state"
+                        );
+                    }
+                }
+            }
 
-    for source in sources {
-        if !source.config.source.enabled {
-            info!(source = %source.config.source.key, "source disabled; skipping");
-            continue;
+            summaries.push(SourceRunSummary::from_report(
+                &report,
+                config_hash,
+                (elapsed + merge_started.elapsed()).as_millis() as u64,
+            ));
+            reports.push(report);
+            // Saved per source, so a crash later in the run keeps this merge.
+            if !options.dry_run {
+                save_progress(&store, &quarantine, &http_cache)?;
+            }
+            Ok(())
+        },
+    )?;
+
+    if !options.dry_run {
+        rebuild_bundles(
+            &store,
+            &load_optional_bundles(&options.config_dir)?,
+            &options.out_dir,
+            None,
+        )?;
+        write_redirect_map(&options.config_dir, &store, &options.out_dir)?;
+        store.record_run(RunRecord {
+            run_id: run_id.clone(),
+            started_at: run_started_at,
+            duration_ms: run_timer.elapsed().as_millis() as u64,
+            rics_version: env!("CARGO_PKG_VERSION").to_string(),
+            sources: summaries,
+        });
+        store.save()?;
+        refresh_search_index(&options.state_path, store.state())?;
+        info!(state = %options.state_path.display(), "state written");
+        if let Some(feed) = &feed {
+            publish_changes(feed, &options.out_dir, &feed_events)?;
         }
+        // State is already saved, so a mail failure must not fail the sync.
+        if let Some(smtp) = &smtp
+            && !invites.is_empty()
+        {
+            match send_invites(smtp, &invites) {
+                Ok(sent) => info!(sent, queued = invites.len(), "invites sent"),
+                Err(err) => {
+                    warn!(error = %format!("{err:#}"), queued = invites.len(), "invites not sent")
+                }
+            }
+        }
+    } else {
+        info!("dry run enabled; state and calendars not persisted");
+    }
 
-        info!(source = %source.config.source.key, "sync start");
-        let docs = fetch_source_documents(&source)
-            .with_context(|| format!("fetch failed for source {}", source.config.source.key))?;
-        let candidates = parse_source_events(&source, &docs)
-            .with_context(|| format!("parse failed for source {}", source.config.source.key))?;
+    Ok(reports)
+}
 
-        let mut report = SourceRunReport {
-            source_key: source.config.source.key.clone(),
-            pages_fetched: docs.len(),
-            records_parsed: candidates.len(),
-            ..SourceRunReport::default()
-        };
+/// Writes what a sync has merged so far.
+fn save_progress(
+    store: &JsonFileStore,
+    quarantine: &Quarantine,
+    http_cache: &HttpCache,
+) -> Result<()> {
+    store.save()?;
+    quarantine.save()?;
+    http_cache.save()
+}
 
-        let changed_years = merge_source_events(&mut state, &source, candidates, &mut report)?;
+/// What a run shares with each source's [`merge_and_publish`].
+struct SourceRun<'a> {
+    options: &'a SyncOptions,
+    run_id: &'a str,
+    feed: Option<&'a FeedConfig>,
+    smtp: Option<&'a SmtpConfig>,
+}
 
-        info!(
-            source = %source.config.source.key,
-            inserted = report.inserted,
-            updated = report.updated,
-            unchanged = report.unchanged,
-            cancelled = report.cancelled,
-            changed_years = ?changed_years,
-            "sync merge complete"
-        );
+/// Change feed entries and invites of one source, kept apart until its
+/// merge and publish succeeded.
+#[derive(Default)]
+struct PublishedSource {
+    feed_events: Vec<ChangeEvent>,
+    invites: Vec<Invite>,
+}
 
-        if !options.dry_run {
-            rebuild_source_calendars(&state, &source, &options.out_dir, None, Some(changed_years))?;
+/// Merges one source's candidates into state and rewrites its calendars.
+fn merge_and_publish(
+    store: &mut JsonFileStore,
+    quarantine: &mut Quarantine,
+    run: &SourceRun<'_>,
+    source: &LoadedSource,
+    candidates: Vec<CandidateEvent>,
+    config_changed: bool,
+    report: &mut SourceRunReport,
+) -> Result<PublishedSource> {
+    let mut published = PublishedSource::default();
+    let before = (source.config.invites.enabled() || run.feed.is_some())
+        .then(|| snapshot_source(store, &source.config.source.key));
+    let changed_years = merge_source_events(store, quarantine, source, candidates, report)?;
+    if let Some(before) = &before {
+        let changes = diff_source(store, &source.config.source.key, before);
+        if let Some(feed) = run.feed {
+            published.feed_events = change_events(
+                feed,
+                run.run_id,
+                store,
+                &source.config.source.key,
+                before,
+                &changes,
+            );
+        }
+        match run.smtp {
+            Some(smtp) if source.config.invites.enabled() => {
+                published.invites = pending_invites(&source.config, store, &changes, &smtp.from);
+                report.invites = published.invites.len();
+            }
+            None if source.config.invites.enabled() => warn!(
+                source = %source.config.source.key,
+                "invites configured but no smtp.toml found; not sending"
+            ),
+            _ => {}
         }
-
-        reports.push(report);
     }
 
-    if !options.dry_run {
-        rebuild_bundles(
-            &state,
-            &load_optional_bundles(&options.config_dir)?,
-            &options.out_dir,
+    info!(
+        source = %source.config.source.key,
+        inserted = report.inserted,
+        updated = report.updated,
+        unchanged = report.unchanged,
+        cancelled = report.cancelled,
+        changed_years = ?changed_years,
+        "sync merge complete"
+    );
+
+    if !run.options.dry_run {
+        let mut changed_years = changed_years;
+        changed_years.extend(mirror_source_assets(store, source, &run.options.out_dir)?);
+        if config_changed {
+            info!(
+                source = %source.config.source.key,
+                "source config changed; rebuilding every year"
+            );
+        }
+        rebuild_source_calendars(
+            store,
+            source,
+            &run.options.out_dir,
             None,
+            (!config_changed).then_some(changed_years),
         )?;
-        save_state(&options.state_path, &state)?;
-        info!(state = %options.state_path.display(), "state written");
-    } else {
-        info!("dry run enabled; state and calendars not persisted");
     }
+    Ok(published)
+}
 
+/// Fetches and parses like a sync, then merges into a scratch copy of state
+/// to see what would change. Nothing is written except the HTTP cache, so
+/// this is cheap enough to decide frequently whether a sync is worthwhile.
+/// Expiry and purges follow from the clock, not upstream, and are left out.
+pub fn monitor_sources(options: &MonitorOptions) -> Result<Vec<MonitorReport>> {
+    let mut sources = load_sources_for_tenant(&options.config_dir, &options.tenant)?;
+    sources.retain(|source| {
+        source.config.source.enabled
+            && options
+                .source
+                .as_ref()
+                .is_none_or(|key| source.config.source.key == *key)
+    });
+    if sources.is_empty() {
+        bail!("no matching source configurations found");
+    }
+
+    let categorizer = Categorizer::new(&load_global_categorize(&options.config_dir)?)?;
+    let global_importance = load_global_importance(&options.config_dir)?;
+    let mut store = JsonFileStore::open(&options.state_path)?;
+    let mut quarantine = Quarantine::open(&Quarantine::path_for_state(&options.state_path))?;
+    let http_cache = HttpCache::open(&HttpCache::dir_for_state(&options.state_path))?;
+    let spans = sources
+        .iter()
+        .map(|source| info_span!("source", source = %source.config.source.key))
+        .collect::<Vec<_>>();
+    let fetch_jobs = plan_fetches(&store, &sources, spans);
+    let mut reports = Vec::new();
+    fetch_sources(
+        &http_cache,
+        &sources,
+        &fetch_jobs,
+        options.jobs,
+        false,
+        None,
+        |index, fetched| {
+            let source = &sources[index];
+            let _source_span = fetch_jobs[index].span.enter();
+            let source_key = &source.config.source.key;
+            // A failing source is reported and the others still checked,
+            // as a sync would.
+            let report = fetched
+                .and_then(|fetched| {
+                    monitor_source(
+                        &mut store,
+                        &mut quarantine,
+                        source,
+                        &categorizer,
+                        &global_importance,
+                        fetched,
+                    )
+                })
+                .unwrap_or_else(|err| {
+                    let error = format!("{err:#}");
+                    warn!(source = %source_key, error = %error, "monitor check failed");
+                    MonitorReport {
+                        source_key: source_key.clone(),
+                        error: Some(error),
+                        ..MonitorReport::default()
+                    }
+                });
+            reports.push(report);
+            Ok(())
+        },
+    )?;
+    // The HTTP cache is shared with syncs of the same state, so it is saved
+    // under their lock; the fetches above ran without it.
+    let _lock = StateLock::acquire(&options.state_path)?;
+    http_cache.save()?;
     Ok(reports)
 }
 
+/// Merges one fetched source into the scratch `store` and lists what
+/// changed.
+fn monitor_source(
+    store: &mut JsonFileStore,
+    quarantine: &mut Quarantine,
+    source: &LoadedSource,
+    categorizer: &Categorizer,
+    global_importance: &ImportanceConfig,
+    fetched: FetchedSource,
+) -> Result<MonitorReport> {
+    let source_key = &source.config.source.key;
+    let Some((_, parsed)) = fetched.documents else {
+        return Ok(MonitorReport {
+            source_key: source_key.clone(),
+            not_modified: true,
+            ..MonitorReport::default()
+        });
+    };
+    let mut candidates = parsed.events;
+    enrich_candidates(source, categorizer, global_importance, &mut candidates)?;
+    let records_parsed = candidates.len();
+
+    let before = snapshot_source(store, source_key);
+    let mut scratch = SourceRunReport::default();
+    merge_source_events(store, quarantine, source, candidates, &mut scratch)?;
+    let changes = diff_source(store, source_key, &before)
+        .into_iter()
+        .filter(|(kind, _)| {
+            matches!(
+                kind,
+                ChangeKind::Created | ChangeKind::Updated | ChangeKind::Cancelled
+            )
+        })
+        .map(|(kind, key)| MonitorChange {
+            kind: kind.as_str().to_string(),
+            title: store
+                .get(&key)
+                .map(|event| event.title.clone())
+                .unwrap_or_default(),
+            store_key: key,
+        })
+        .collect::<Vec<_>>();
+    info!(
+        changes = changes.len(),
+        quarantined = scratch.quarantined,
+        "monitor check complete"
+    );
+    Ok(MonitorReport {
+        source_key: source_key.clone(),
+        not_modified: false,
+        records_parsed,
+        changes,
+        quarantined: scratch.quarantined,
+        error: None,
+    })
+}
+
+/// A source's span and what fetching it needs from state, read up front so
+/// merges can write to state while other sources are still fetching.
+struct FetchJob {
+    span: Span,
+    config_hash: Option<String>,
+    validators: Option<ResourceValidators>,
+}
+
+fn plan_fetches(
+    store: &JsonFileStore,
+    sources: &[LoadedSource],
+    spans: Vec<Span>,
+) -> Vec<FetchJob> {
+    sources
+        .iter()
+        .zip(spans)
+        .map(|(source, span)| {
+            let key = &source.config.source.key;
+            FetchJob {
+                span,
+                config_hash: store.config_hash(key).map(str::to_string),
+                validators: store.fetch_validators(key).cloned(),
+            }
+        })
+        .collect()
+}
+
+/// Fetches and parses `sources` at most `jobs` at a time, each inside its
+/// span, and hands each result to `merge` with its index, in `sources`
+/// order. Workers take sources in order and results that finish early wait
+/// in a reorder buffer, so only sources fetched ahead of a slow one are
+/// held in memory. An error from `merge` stops the run once the fetches in
+/// flight finish.
+fn fetch_sources(
+    http_cache: &HttpCache,
+    sources: &[LoadedSource],
+    fetch_jobs: &[FetchJob],
+    jobs: usize,
+    capture_raw: bool,
+    record_dir: Option<&Path>,
+    mut merge: impl FnMut(usize, Result<FetchedSource>) -> Result<()>,
+) -> Result<()> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, sources.len().max(1)) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(source) = sources.get(index) else {
+                        break;
+                    };
+                    let job = &fetch_jobs[index];
+                    let fetched = job.span.in_scope(|| {
+                        fetch_source(job, http_cache, source, capture_raw, record_dir)
+                    });
+                    // The merge stopped; leave the remaining sources.
+                    if sender.send((index, fetched)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut merged = 0;
+        for (index, fetched) in receiver {
+            pending.insert(index, fetched);
+            while let Some(fetched) = pending.remove(&merged) {
+                merge(merged, fetched)?;
+                merged += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Applies category rules, then importance rules, so importance rules can
+/// key on inferred countries and rule-assigned categories.
+fn enrich_candidates(
+    source: &LoadedSource,
+    categorizer: &Categorizer,
+    global_importance: &ImportanceConfig,
+    candidates: &mut [CandidateEvent],
+) -> Result<()> {
+    if !categorizer.is_empty() {
+        candidates.iter_mut().for_each(|candidate| {
+            categorizer.apply(candidate);
+        });
+    }
+    let scorer = ImportanceScorer::new(&[&source.config.importance, global_importance])?;
+    if !scorer.is_empty() {
+        let mut rescored = 0usize;
+        for candidate in candidates.iter_mut() {
+            if scorer.apply(candidate) {
+                rescored += 1;
+            }
+        }
+        debug!(
+            source = %source.config.source.key,
+            rescored,
+            "importance rules applied"
+        );
+    }
+    Ok(())
+}
+
+/// A source's fetch and parse, done concurrently with other sources.
+struct FetchedSource {
+    config_hash: Option<String>,
+    config_changed: bool,
+    validators: Option<ResourceValidators>,
+    fetch_duration: Duration,
+    /// Fetch plus parse.
+    elapsed: Duration,
+    /// `None` when the preflight showed the source unchanged.
+    documents: Option<(Vec<FetchedDocument>, ParseOutcome)>,
+}
+
+/// Fetches and parses one source without touching state, so several can
+/// run at once.
+fn fetch_source(
+    job: &FetchJob,
+    http_cache: &HttpCache,
+    source: &LoadedSource,
+    capture_raw: bool,
+    record_dir: Option<&Path>,
+) -> Result<FetchedSource> {
+    info!("sync start");
+    let started = Instant::now();
+    let config_hash = source.config_hash();
+    // A changed config can change every parsed event, so nothing from
+    // the previous run may be reused.
+    let config_changed = config_hash
+        .as_deref()
+        .is_some_and(|hash| job.config_hash.as_deref() != Some(hash));
+    let validators = preflight_source(source);
+    // A recording has to hold every response, so it never skips a fetch.
+    if !config_changed
+        && record_dir.is_none()
+        && let Some(current) = &validators
+        && job.validators.as_ref() == Some(current)
+    {
+        info!(
+            source = %source.config.source.key,
+            "preflight shows source unchanged; skipping fetch"
+        );
+        return Ok(FetchedSource {
+            config_hash,
+            config_changed,
+            validators,
+            fetch_duration: started.elapsed(),
+            elapsed: started.elapsed(),
+            documents: None,
+        });
+    }
+
+    let docs = fetch_source_documents_with_cache(source, Some(http_cache))
+        .with_context(|| format!("fetch failed for source {}", source.config.source.key))?;
+    if let Some(dir) = record_dir
+        && source.config.fetch.mode == FetchMode::Http
+    {
+        record_documents(dir, source, &docs)?;
+    }
+    let fetch_duration = started.elapsed();
+    let parsed = parse_source_documents_with_raw(source, &docs, capture_raw)
+        .with_context(|| format!("parse failed for source {}", source.config.source.key))?;
+    Ok(FetchedSource {
+        config_hash,
+        config_changed,
+        validators,
+        fetch_duration,
+        elapsed: started.elapsed(),
+        documents: Some((docs, parsed)),
+    })
+}
+
+/// Writes the fragment each candidate was parsed from to
+/// `debug/raw/<source dir>/<uid>.<ext>`. Events without a fragment (custom
+/// parsers, date rules, schedules) are skipped.
+fn write_raw_fragments(
+    out_dir: &Path,
+    source: &LoadedSource,
+    candidates: &[CandidateEvent],
+    fragments: &[RawFragment],
+) -> Result<()> {
+    let by_record = fragments
+        .iter()
+        .map(|fragment| {
+            (
+                (
+                    fragment.document_url.as_str(),
+                    fragment.page_index,
+                    fragment.record_index,
+                ),
+                fragment,
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let dir = out_dir
+        .join("debug")
+        .join("raw")
+        .join(source.config.sanitized_source_dir_name());
+    let (uids, _) = assign_uids(candidates, &source.config.uid)?;
+    let mut written = 0usize;
+    for (candidate, uid) in candidates.iter().zip(uids) {
+        let Some(fragment) = candidate.provenance.as_ref().and_then(|provenance| {
+            by_record.get(&(
+                provenance.document_url.as_deref()?,
+                provenance.page_index?,
+                provenance.record_index?,
+            ))
+        }) else {
+            continue;
+        };
+        if written == 0 {
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let path = dir.join(format!("{uid}.{}", fragment.extension));
+        std::fs::write(&path, &fragment.content)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        written += 1;
+    }
+    debug!(
+        source = %source.config.source.key,
+        written,
+        dir = %dir.display(),
+        "captured raw record fragments"
+    );
+    Ok(())
+}
+
+/// A sortable, practically unique ID for one sync, e.g.
+/// `20300301T120000Z-3f2a9c1e`.
+fn new_run_id(started_at: DateTime<Utc>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        started_at
+            .timestamp_nanos_opt()
+            .unwrap_or_default()
+            .to_le_bytes(),
+    );
+    hasher.update(std::process::id().to_le_bytes());
+    let digest = hex::encode(hasher.finalize());
+    format!("{}-{}", started_at.format("%Y%m%dT%H%M%SZ"), &digest[..8])
+}
+
+/// Identifies one source's part of a run in the logs.
+fn source_span_id(run_id: &str, source_key: &str) -> String {
+    let digest = hex::encode(Sha256::digest(format!("{run_id}\0{source_key}")));
+    digest[..16].to_string()
+}
+
 pub fn build_calendars(options: &BuildOptions) -> Result<()> {
     let mut sources = load_sources_from_dir(&options.config_dir)?;
     if let Some(filter) = &options.source {
@@ -121,20 +931,51 @@ pub fn build_calendars(options: &BuildOptions) -> Result<()> {
         bail!("no matching source configurations found");
     }
 
-    let state = load_state(&options.state_path)?;
-    for source in sources {
-        rebuild_source_calendars(&state, &source, &options.out_dir, options.year, None)?;
-    }
+    let store = JsonFileStore::open(&options.state_path)?;
+    sources.par_iter().try_for_each(|source| {
+        rebuild_source_calendars(&store, source, &options.out_dir, options.year, None)
+    })?;
     rebuild_bundles(
-        &state,
+        &store,
         &load_optional_bundles(&options.config_dir)?,
         &options.out_dir,
         options.year,
     )?;
+    write_redirect_map(&options.config_dir, &store, &options.out_dir)?;
 
     Ok(())
 }
 
+/// Renders one source's calendar for `options.year` to `out` instead of
+/// the output directory. With `split_by_country`, all countries go into
+/// the one calendar. Returns the number of events written.
+pub fn render_calendar_to(options: &BuildOptions, out: &mut impl std::io::Write) -> Result<usize> {
+    let (Some(key), Some(year)) = (&options.source, options.year) else {
+        bail!("rendering to stdout needs both a source and a year");
+    };
+    let Some(source) = load_sources_from_dir(&options.config_dir)?
+        .into_iter()
+        .find(|source| source.config.source.key == *key)
+    else {
+        bail!("no source configuration with key {key}");
+    };
+
+    let store = JsonFileStore::open(&options.state_path)?;
+    let query = EventQuery::for_source(key).with_year(Some(year));
+    let (events, digests) = fold_digest_events(
+        key,
+        &source.config.uid,
+        store.query(&query),
+        &source.config.publish,
+    );
+    let mut events: Vec<&EventRecord> = events;
+    events.extend(&digests);
+    events.sort_by_key(|event| event_sort_key(event));
+    stream_source_year_calendar(&source.config, year, &events, out)
+        .context("failed to write calendar")?;
+    Ok(events.len())
+}
+
 pub fn publish_existing_calendars(options: &PublishOptions) -> Result<usize> {
     let mut sources = load_sources_from_dir(&options.config_dir)?;
     if let Some(filter) = &options.source {
@@ -174,18 +1015,21 @@ pub fn publish_existing_calendars(options: &PublishOptions) -> Result<usize> {
         std::fs::create_dir_all(&mirror_dir)
             .with_context(|| format!("failed to create mirror dir {}", mirror_dir.display()))?;
 
+        let mut copied = Vec::new();
         for entry in std::fs::read_dir(&source_out_dir)? {
             let entry = entry?;
             let src_path = entry.path();
-            if src_path.extension().and_then(|s| s.to_str()) != Some("ics") {
-                continue;
-            }
             let Some(file_name) = src_path.file_name().and_then(|s| s.to_str()) else {
                 continue;
             };
+            let (calendar_name, compression) = split_compressed_name(file_name);
+            if !calendar_name.ends_with(".ics") {
+                continue;
+            }
 
             if let Some(filter_year) = options.year
-                && extract_year_from_any_ics_filename(file_name, &file_prefix) != Some(filter_year)
+                && extract_year_from_any_ics_filename(calendar_name, &file_prefix)
+                    != Some(filter_year)
             {
                 continue;
             }
@@ -199,13 +1043,16 @@ pub fn publish_existing_calendars(options: &PublishOptions) -> Result<usize> {
                 )
             })?;
             published += 1;
+            copied.push(file_name.to_string());
             info!(
                 source = %source.config.source.key,
                 src = %src_path.display(),
                 dst = %dst_path.display(),
+                content_encoding = compression.map(Compression::content_encoding),
                 "published existing calendar file"
             );
         }
+        record_owned_files(&mirror_dir, &source.config.source.key, &copied)?;
     }
 
     for bundle in load_optional_bundles(&options.config_dir)? {
@@ -227,17 +1074,20 @@ pub fn publish_existing_calendars(options: &PublishOptions) -> Result<usize> {
         std::fs::create_dir_all(&mirror_dir)
             .with_context(|| format!("failed to create mirror dir {}", mirror_dir.display()))?;
 
+        let mut copied = Vec::new();
         for entry in std::fs::read_dir(&bundle_out_dir)? {
             let entry = entry?;
             let src_path = entry.path();
-            if src_path.extension().and_then(|s| s.to_str()) != Some("ics") {
-                continue;
-            }
             let Some(file_name) = src_path.file_name().and_then(|s| s.to_str()) else {
                 continue;
             };
+            let (calendar_name, _) = split_compressed_name(file_name);
+            if !calendar_name.ends_with(".ics") {
+                continue;
+            }
             if let Some(filter_year) = options.year
-                && extract_year_from_any_ics_filename(file_name, &file_prefix) != Some(filter_year)
+                && extract_year_from_any_ics_filename(calendar_name, &file_prefix)
+                    != Some(filter_year)
             {
                 continue;
             }
@@ -251,7 +1101,10 @@ pub fn publish_existing_calendars(options: &PublishOptions) -> Result<usize> {
                 )
             })?;
             published += 1;
+            copied.push(file_name.to_string());
         }
+        let owner = format!("bundle:{}", bundle.config.bundle.key);
+        record_owned_files(&mirror_dir, &owner, &copied)?;
     }
 
     Ok(published)
@@ -286,6 +1139,23 @@ pub fn validate_configs(options: &ValidateOptions) -> Result<Vec<String>> {
                 bundle.path.display()
             ));
         }
+        let categorize = load_global_categorize(dir)?;
+        if !categorize.rules.is_empty() {
+            messages.push(format!(
+                "OK: {} global categorize rules",
+                categorize.rules.len()
+            ));
+        }
+        let importance = load_global_importance(dir)?;
+        if !importance.rules.is_empty() {
+            messages.push(format!(
+                "OK: {} global importance rules",
+                importance.rules.len()
+            ));
+        }
+        if load_global_redirects(dir)?.is_some() {
+            messages.push("OK: redirects.toml".to_string());
+        }
         return Ok(messages);
     }
 
@@ -296,22 +1166,32 @@ pub fn load_state_for_read(path: &Path) -> Result<State> {
     load_state(path)
 }
 
-fn bundle_config_dir(source_config_dir: &Path) -> Option<PathBuf> {
-    source_config_dir.parent().map(|parent| parent.join("bundles"))
-}
-
-fn load_optional_bundles(source_config_dir: &Path) -> Result<Vec<LoadedBundle>> {
-    let Some(bundle_dir) = bundle_config_dir(source_config_dir) else {
-        return Ok(Vec::new());
-    };
-    if !bundle_dir.exists() {
-        return Ok(Vec::new());
+/// Bundles from the `bundles` dir next to each config root; later roots
+/// replace earlier bundles with the same key.
+pub(crate) fn load_optional_bundles(source_config_dir: &Path) -> Result<Vec<LoadedBundle>> {
+    let mut loaded: Vec<LoadedBundle> = Vec::new();
+    for root in config_roots(source_config_dir) {
+        let Some(bundle_dir) = root.parent().map(|parent| parent.join("bundles")) else {
+            continue;
+        };
+        if !bundle_dir.exists() {
+            continue;
+        }
+        let bundles = load_bundles_from_dir(&bundle_dir)?;
+        loaded.retain(|earlier| {
+            !bundles
+                .iter()
+                .any(|bundle| bundle.config.bundle.key == earlier.config.bundle.key)
+        });
+        loaded.extend(bundles);
     }
-    load_bundles_from_dir(&bundle_dir)
+    loaded.sort_by(|a, b| a.config.bundle.key.cmp(&b.config.bundle.key));
+    Ok(loaded)
 }
 
 fn merge_source_events(
-    state: &mut State,
+    store: &mut dyn EventStore,
+    quarantine: &mut Quarantine,
     source: &LoadedSource,
     candidates: Vec<CandidateEvent>,
     report: &mut SourceRunReport,
@@ -319,78 +1199,416 @@ fn merge_source_events(
     let now = Utc::now();
     let today = now.date_naive();
     let source_key = source.config.source.key.as_str();
+    let lifecycle_config = &source.config.lifecycle;
 
-    let mut seen_uids = HashSet::new();
+    let mut seen_keys = HashSet::new();
     let mut changed_years = BTreeSet::new();
+    let mut inserted = Vec::new();
 
-    for mut candidate in candidates {
+    let mut candidates = candidates;
+    for candidate in &mut candidates {
         candidate.categories.sort();
         candidate.categories.dedup();
-
-        let uid = stable_uid(&candidate);
+    }
+    let (uids, collisions) = assign_uids(&candidates, &source.config.uid)?;
+    for collision in &collisions {
+        warn!(source = %source_key, "{}", collision.message);
+    }
+    report.warnings.extend(collisions);
+
+    for (mut candidate, uid) in candidates.into_iter().zip(uids) {
+        let key = store_key(&uid, candidate.recurrence_id.as_ref());
+        seen_keys.insert(key.clone());
+        let existing = store.get(&key).cloned();
+        if let Some(existing) = &existing {
+            apply_merge_policies(&mut candidate, existing, &source.config.merge);
+        }
         let revision_hash = revision_hash(&candidate)?;
         let year_bucket = candidate.time.year_bucket();
-        seen_uids.insert(uid.clone());
 
-        if let Some(existing) = state.events.get_mut(&uid) {
-            if existing.revision_hash != revision_hash {
-                let created_at = existing.created_at;
+        if let Some(mut existing) = existing {
+            // An overdue placeholder stays retired until it gets a date.
+            if matches!(candidate.time, EventTimeSpec::Tbd { .. })
+                && tbd_overdue(&existing, lifecycle_config, now)
+            {
+                store.touch(&key, now);
+                report.unchanged += 1;
+                continue;
+            }
+            let lifecycle = Lifecycle::from_status(&candidate.status);
+            if existing.revision_hash != revision_hash || existing.lifecycle != lifecycle {
+                if quarantine.is_rejected(&key, &revision_hash) {
+                    store.touch(&key, now);
+                    report.unchanged += 1;
+                    continue;
+                }
                 let new_sequence = existing.sequence.saturating_add(1);
-                *existing = candidate_to_record(
+                existing.push_revision(now);
+                let mut record = candidate_to_record(
                     candidate,
                     uid,
                     revision_hash,
                     new_sequence,
-                    created_at,
+                    existing.created_at,
                     now,
                 );
+                let reasons = if source.config.quarantine.enabled {
+                    suspicious_changes(&existing, &record, &source.config.quarantine)
+                } else {
+                    Vec::new()
+                };
+                record.related_uids = existing.related_uids;
+                record.supersedes = existing.supersedes;
+                record.history = existing.history;
+                record.dead_link = existing
+                    .dead_link
+                    .filter(|dead| record.source_url.as_deref() == Some(dead.url.as_str()));
+                if record.is_cancelled() {
+                    record.cancelled_at = existing.cancelled_at.or(record.cancelled_at);
+                }
+                if !reasons.is_empty() {
+                    warn!(
+                        source = %source_key,
+                        uid = %record.uid,
+                        reasons = ?reasons,
+                        "update quarantined for review"
+                    );
+                    quarantine.hold(QuarantineEntry {
+                        id: record.revision_hash.chars().take(12).collect(),
+                        source_key: source_key.to_string(),
+                        store_key: key.clone(),
+                        quarantined_at: now,
+                        reasons,
+                        previous_title: existing.title,
+                        previous_start: existing.time.start_date().map(|date| date.to_string()),
+                        record,
+                    });
+                    store.touch(&key, now);
+                    report.quarantined += 1;
+                    continue;
+                }
+                store.upsert(record);
                 report.updated += 1;
                 if let Some(year) = year_bucket {
                     changed_years.insert(year);
                 }
             } else {
-                existing.last_seen_at = now;
+                store.touch(&key, now);
+                quarantine.release(&key);
                 report.unchanged += 1;
             }
         } else {
-            let record = candidate_to_record(candidate, uid.clone(), revision_hash, 0, now, now);
+            let record = candidate_to_record(candidate, uid, revision_hash, 0, now, now);
             if let Some(year) = record.year_bucket() {
                 changed_years.insert(year);
             }
-            state.events.insert(uid, record);
+            inserted.push((record.store_key(), normalized_title(&record.title)));
+            store.upsert(record);
             report.inserted += 1;
         }
     }
 
-    for event in state
-        .events
-        .values_mut()
-        .filter(|event| event.source_key == source_key)
-    {
-        if seen_uids.contains(&event.uid) {
+    // Events past what the source lists can't be missing from it.
+    let after_horizon = source
+        .config
+        .merge
+        .cancellation_horizon
+        .map(|horizon| horizon.end(today) + chrono::Duration::days(1));
+    let unseen = store
+        .query(&EventQuery::for_source(source_key))
+        .into_iter()
+        .filter(|event| !seen_keys.contains(&event.store_key()))
+        .filter(|event| {
+            event.is_tbd() || after_horizon.is_none_or(|after| !event.is_future_relative_to(after))
+        })
+        .map(|event| {
+            (
+                event.store_key(),
+                normalized_title(&event.title),
+                event.year_bucket(),
+                event.is_future_relative_to(today),
+                event.is_tbd() && event.lifecycle == Lifecycle::Active,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut cancelled = Vec::new();
+    for (key, title, year_bucket, is_future, active_tbd) in unseen {
+        if active_tbd && let Some(limit) = lifecycle_config.tbd_missed_syncs {
+            if record_missed_sync(store, &key) >= limit {
+                retire_tbd(store, &key, lifecycle_config.tbd_action, now, report);
+            }
             continue;
         }
-        if !event.is_future_relative_to(today) {
+        if !is_future {
+            if store.mark_expired(&key) {
+                report.expired += 1;
+            }
             continue;
         }
-        if event.status.eq_ignore_ascii_case("cancelled") {
+        if !store.mark_cancelled(&key, now) {
             continue;
         }
-
-        event.status = "cancelled".to_string();
-        event.sequence = event.sequence.saturating_add(1);
-        event.last_modified = now;
-        event.last_seen_at = now;
         report.cancelled += 1;
+        cancelled.push((key, title));
 
-        if let Some(year) = event.year_bucket() {
+        if let Some(year) = year_bucket {
             changed_years.insert(year);
         }
     }
 
+    link_superseded_events(store, &cancelled, &inserted);
+
+    if lifecycle_config.tbd_max_age_days.is_some() {
+        let overdue = store
+            .query(&EventQuery::for_source(source_key))
+            .into_iter()
+            .filter(|event| {
+                event.lifecycle == Lifecycle::Active && tbd_overdue(event, lifecycle_config, now)
+            })
+            .map(EventRecord::store_key)
+            .collect::<Vec<_>>();
+        for key in overdue {
+            retire_tbd(store, &key, lifecycle_config.tbd_action, now, report);
+        }
+    }
+
+    if let Some(days) = source.config.lifecycle.delete_after_days {
+        let cutoff = now - chrono::Duration::days(i64::from(days));
+        let to_purge = store
+            .query(&EventQuery::for_source(source_key).with_cancelled(true))
+            .into_iter()
+            .filter(|event| event.retired_at().is_some_and(|at| at <= cutoff))
+            .map(|event| (event.store_key(), event.year_bucket()))
+            .collect::<Vec<_>>();
+        for (key, year_bucket) in to_purge {
+            if store.remove(&key).is_none() {
+                continue;
+            }
+            report.purged += 1;
+            if let Some(year) = year_bucket {
+                changed_years.insert(year);
+            }
+        }
+    }
+
     Ok(changed_years)
 }
 
+/// A TBD event older than `lifecycle.tbd_max_age_days`.
+fn tbd_overdue(event: &EventRecord, lifecycle: &LifecycleConfig, now: DateTime<Utc>) -> bool {
+    event.is_tbd()
+        && lifecycle
+            .tbd_max_age_days
+            .is_some_and(|days| event.created_at <= now - chrono::Duration::days(i64::from(days)))
+}
+
+/// Counts one more sync without the event and returns the new count.
+fn record_missed_sync(store: &mut dyn EventStore, key: &str) -> u32 {
+    let Some(mut event) = store.get(key).cloned() else {
+        return 0;
+    };
+    event.missed_syncs = event.missed_syncs.saturating_add(1);
+    let missed = event.missed_syncs;
+    store.upsert(event);
+    missed
+}
+
+fn retire_tbd(
+    store: &mut dyn EventStore,
+    key: &str,
+    action: TbdAction,
+    now: DateTime<Utc>,
+    report: &mut SourceRunReport,
+) {
+    let retired = match action {
+        TbdAction::Expire => store.mark_expired(key),
+        TbdAction::Cancel => store.mark_cancelled(key, now),
+    };
+    if retired {
+        debug!(key, action = ?action, "retired stale tbd event");
+        match action {
+            TbdAction::Expire => report.expired += 1,
+            TbdAction::Cancel => report.cancelled += 1,
+        }
+    }
+}
+
+/// Keeps stored values over the candidate's for fields whose `[merge]`
+/// policy says so. Runs before hashing, so a field kept this way does not
+/// count as a change.
+fn apply_merge_policies(
+    candidate: &mut CandidateEvent,
+    existing: &EventRecord,
+    merge: &MergeConfig,
+) {
+    let text = |value: &String| value.chars().count();
+    let optional_text = |value: &Option<String>| value.as_deref().map_or(0, |v| v.chars().count());
+    for (field, policy) in &merge.fields {
+        let policy = *policy;
+        match field.as_str() {
+            "title" => merge_field(&mut candidate.title, &existing.title, policy, text),
+            "description" => merge_field(
+                &mut candidate.description,
+                &existing.description,
+                policy,
+                optional_text,
+            ),
+            "source_url" => merge_field(
+                &mut candidate.source_url,
+                &existing.source_url,
+                policy,
+                optional_text,
+            ),
+            "subtype" => merge_field(
+                &mut candidate.subtype,
+                &existing.subtype,
+                policy,
+                optional_text,
+            ),
+            "jurisdiction" => merge_field(
+                &mut candidate.jurisdiction,
+                &existing.jurisdiction,
+                policy,
+                optional_text,
+            ),
+            "country" => merge_field(
+                &mut candidate.country,
+                &existing.country,
+                policy,
+                optional_text,
+            ),
+            "importance" => merge_field(
+                &mut candidate.importance,
+                &existing.importance,
+                policy,
+                |value| usize::from(value.is_some()),
+            ),
+            "categories" => merge_field(
+                &mut candidate.categories,
+                &existing.categories,
+                policy,
+                Vec::len,
+            ),
+            other => {
+                let Some(name) = other.strip_prefix("metadata.") else {
+                    continue;
+                };
+                let mut value = candidate.metadata.remove(name);
+                merge_field(
+                    &mut value,
+                    &existing.metadata.get(name).cloned(),
+                    policy,
+                    optional_text,
+                );
+                if let Some(value) = value {
+                    candidate.metadata.insert(name.to_string(), value);
+                }
+            }
+        }
+    }
+}
+
+/// Replaces `new` with `existing` when `policy` prefers it; `size` is zero
+/// for empty values and orders values for [`MergePolicy::Longest`].
+fn merge_field<T: Clone>(
+    new: &mut T,
+    existing: &T,
+    policy: MergePolicy,
+    size: impl Fn(&T) -> usize,
+) {
+    let keep_existing = match policy {
+        MergePolicy::PreferNew => false,
+        MergePolicy::PreferExisting => size(existing) > 0,
+        MergePolicy::PreferNonEmpty => size(new) == 0 && size(existing) > 0,
+        MergePolicy::Longest => size(existing) > size(new),
+    };
+    if keep_existing {
+        *new = existing.clone();
+    }
+}
+
+/// Pairs events cancelled in this run with events inserted in the same run
+/// under the same normalized title, treating the new event as the
+/// rescheduled replacement of the old one.
+fn link_superseded_events(
+    store: &mut dyn EventStore,
+    cancelled: &[(String, String)],
+    inserted: &[(String, String)],
+) {
+    let mut claimed = HashSet::new();
+    for (old_key, title) in cancelled {
+        if title.is_empty() {
+            continue;
+        }
+        let Some((new_key, _)) = inserted
+            .iter()
+            .find(|(key, candidate)| candidate == title && !claimed.contains(key))
+        else {
+            continue;
+        };
+        let (Some(mut old), Some(mut new)) =
+            (store.get(old_key).cloned(), store.get(new_key).cloned())
+        else {
+            continue;
+        };
+        claimed.insert(new_key.clone());
+
+        if !old.related_uids.contains(&new.uid) {
+            old.related_uids.push(new.uid.clone());
+        }
+        if !new.related_uids.contains(&old.uid) {
+            new.related_uids.push(old.uid.clone());
+        }
+        new.supersedes = Some(old.uid.clone());
+        debug!(old_uid = %old.uid, new_uid = %new.uid, "linked rescheduled event");
+        store.upsert(old);
+        store.upsert(new);
+    }
+}
+
+pub(crate) fn normalized_title(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Converts parsed candidates into fresh records the way a first sync would,
+/// without touching any store. Used for previews of unsaved configs.
+pub fn preview_records(candidates: Vec<CandidateEvent>) -> Result<Vec<EventRecord>> {
+    preview_records_with_uids(candidates, &UidConfig::default())
+}
+
+/// As [`preview_records`], hashing UIDs per the source's `[uid]` settings.
+pub fn preview_records_with_uids(
+    candidates: Vec<CandidateEvent>,
+    uid_config: &UidConfig,
+) -> Result<Vec<EventRecord>> {
+    let now = Utc::now();
+    let mut candidates = candidates;
+    for candidate in &mut candidates {
+        candidate.categories.sort();
+        candidate.categories.dedup();
+    }
+    let (uids, _) = assign_uids(&candidates, uid_config)?;
+    let mut records = Vec::with_capacity(candidates.len());
+    for (candidate, uid) in candidates.into_iter().zip(uids) {
+        let revision_hash = revision_hash(&candidate)?;
+        records.push(candidate_to_record(
+            candidate,
+            uid,
+            revision_hash,
+            0,
+            now,
+            now,
+        ));
+    }
+    Ok(records)
+}
+
 fn candidate_to_record(
     candidate: CandidateEvent,
     uid: String,
@@ -399,6 +1617,7 @@ fn candidate_to_record(
     created_at: chrono::DateTime<Utc>,
     now: chrono::DateTime<Utc>,
 ) -> EventRecord {
+    let lifecycle = Lifecycle::from_status(&candidate.status);
     EventRecord {
         uid,
         source_key: candidate.source_key,
@@ -418,11 +1637,32 @@ fn candidate_to_record(
         importance: candidate.importance,
         confidence: candidate.confidence,
         metadata: candidate.metadata,
+        rrule: candidate.rrule,
+        exdates: candidate.exdates,
+        recurrence_id: candidate.recurrence_id,
+        related_uids: Vec::new(),
+        supersedes: None,
+        history: Vec::new(),
+        provenance: candidate.provenance,
+        values: candidate.values,
+        attachments: candidate
+            .attachments
+            .into_iter()
+            .map(|url| Attachment {
+                url,
+                mirror_url: None,
+            })
+            .collect(),
+        translations: candidate.translations,
+        dead_link: None,
+        lifecycle,
+        cancelled_at: (lifecycle == Lifecycle::Cancelled).then_some(now),
         sequence,
         revision_hash,
         created_at,
         last_modified: now,
         last_seen_at: now,
+        missed_syncs: 0,
     }
 }
 
@@ -439,6 +1679,18 @@ struct RevisionMaterial<'a> {
     subtype: &'a Option<String>,
     categories: &'a [String],
     metadata: &'a BTreeMap<String, String>,
+    // Recurrence fields, attachments and translations are omitted when unset
+    // so that hashes of events without them match the ones already stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rrule: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exdates: Vec<&'a RecurrenceDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recurrence_id: Option<&'a RecurrenceDate>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    attachments: &'a [String],
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    translations: &'a BTreeMap<String, LocalizedText>,
 }
 
 fn revision_hash(candidate: &CandidateEvent) -> Result<String> {
@@ -454,6 +1706,11 @@ fn revision_hash(candidate: &CandidateEvent) -> Result<String> {
         subtype: &candidate.subtype,
         categories: &candidate.categories,
         metadata: &candidate.metadata,
+        rrule: candidate.rrule.as_deref(),
+        exdates: candidate.exdates.iter().collect(),
+        recurrence_id: candidate.recurrence_id.as_ref(),
+        attachments: &candidate.attachments,
+        translations: &candidate.translations,
     };
 
     let json = serde_json::to_vec(&material)?;
@@ -461,60 +1718,205 @@ fn revision_hash(candidate: &CandidateEvent) -> Result<String> {
     Ok(hex::encode(digest))
 }
 
-fn stable_uid(candidate: &CandidateEvent) -> String {
-    let identity = if let Some(source_event_id) = &candidate.source_event_id {
-        format!("{}::{}", candidate.source_key, source_event_id)
-    } else if let Some(url) = &candidate.source_url {
-        format!("{}::{}", candidate.source_key, url)
+pub(crate) fn stable_uid(candidate: &CandidateEvent, config: &UidConfig) -> String {
+    uid_for(
+        &UidIdentity {
+            source_key: &candidate.source_key,
+            source_event_id: candidate.source_event_id.as_deref(),
+            source_url: candidate.source_url.as_deref(),
+            title: &candidate.title,
+            year: candidate.time.year_bucket(),
+        },
+        config,
+    )
+}
+
+/// What an event's UID is derived from: its ID, else its URL (the document's
+/// when the record has none), else its title and year.
+#[derive(Debug, Clone, Copy)]
+pub struct UidIdentity<'a> {
+    pub source_key: &'a str,
+    pub source_event_id: Option<&'a str>,
+    pub source_url: Option<&'a str>,
+    pub title: &'a str,
+    pub year: Option<i32>,
+}
+
+/// The UID a sync assigns to an event with `identity`, before any collision
+/// suffix.
+pub fn uid_for(identity: &UidIdentity<'_>, config: &UidConfig) -> String {
+    let material = if let Some(source_event_id) = identity.source_event_id {
+        format!("{}::{}", identity.source_key, source_event_id)
+    } else if let Some(url) = identity.source_url {
+        format!("{}::{}", identity.source_key, url)
     } else {
         format!(
             "{}::{}::{}",
-            candidate.source_key,
-            candidate.title.to_lowercase(),
-            candidate
-                .time
-                .year_bucket()
+            identity.source_key,
+            identity.title.to_lowercase(),
+            identity
+                .year
                 .map(|y| y.to_string())
                 .unwrap_or_else(|| "undated".to_string())
         )
     };
 
-    let digest = Sha256::digest(identity.as_bytes());
-    let short = &hex::encode(digest)[..24];
+    let digest = match config.algorithm {
+        UidAlgorithm::Sha256 => hex::encode(Sha256::digest(material.as_bytes())),
+        UidAlgorithm::Sha384 => hex::encode(Sha384::digest(material.as_bytes())),
+        UidAlgorithm::Sha512 => hex::encode(Sha512::digest(material.as_bytes())),
+    };
+    let short = &digest[..config.length.min(digest.len())];
     format!("{short}@rics.local")
 }
 
+/// Predicts the UID a sync gives an event of `options.source`, e.g. for
+/// override patches. Without `id` or `url` the record is assumed to map
+/// neither and take its document's URL. Collision suffixes (see
+/// [`assign_uids`]) depend on the other records and are not predicted.
+pub fn predict_uid(options: &UidOptions) -> Result<String> {
+    let Some(source) = load_sources_from_dir(&options.config_dir)?
+        .into_iter()
+        .find(|s| s.config.source.key == options.source)
+    else {
+        bail!("no source configuration with key {}", options.source);
+    };
+    let url = match (&options.id, &options.url) {
+        (None, None) => single_document_url(&source)
+            .context("pass --url with the event's URL, or --id with its source ID")?,
+        _ => options.url.clone(),
+    };
+    Ok(uid_for(
+        &UidIdentity {
+            source_key: &source.config.source.key,
+            source_event_id: options.id.as_deref(),
+            source_url: url.as_deref(),
+            title: &options.title,
+            year: options.date.map(|date| date.year()),
+        },
+        &source.config.uid,
+    ))
+}
+
+/// UIDs for one source's candidates, in order. Distinct candidates whose
+/// [`stable_uid`] and recurrence collide (e.g. repeated generic titles
+/// without an ID or URL) are told apart by a `-2`, `-3`, ... suffix in the
+/// order they were parsed, so the first keeps its UID across runs. Exact
+/// repeats of one record keep the shared UID. Each collision is returned as
+/// a warning.
+fn assign_uids(
+    candidates: &[CandidateEvent],
+    config: &UidConfig,
+) -> Result<(Vec<String>, Vec<ParseWarning>)> {
+    // Per store key: the revision hashes seen so far, in order.
+    let mut variants: HashMap<String, Vec<String>> = HashMap::new();
+    let mut uids = Vec::with_capacity(candidates.len());
+    let mut collided = Vec::new();
+    for candidate in candidates {
+        let uid = stable_uid(candidate, config);
+        let key = store_key(&uid, candidate.recurrence_id.as_ref());
+        let hash = revision_hash(candidate)?;
+        let seen = variants.entry(key.clone()).or_default();
+        let index = match seen.iter().position(|seen| *seen == hash) {
+            Some(index) => index,
+            None => {
+                seen.push(hash);
+                seen.len() - 1
+            }
+        };
+        if index == 0 {
+            uids.push(uid);
+            continue;
+        }
+        if index == 1 && !collided.iter().any(|(seen, _, _)| *seen == key) {
+            collided.push((key, uid.clone(), candidate.title.clone()));
+        }
+        uids.push(uid.replacen('@', &format!("-{}@", index + 1), 1));
+    }
+    let warnings = collided
+        .into_iter()
+        .map(|(key, uid, title)| {
+            let count = variants[&key].len();
+            ParseWarning {
+                kind: ParseWarningKind::UidCollision,
+                source_url: None,
+                field: None,
+                count,
+                message: format!(
+                    "{count} different records of \"{title}\" share uid {uid}; \
+                     all but the first were given a numbered suffix. \
+                     Map an id or url to keep them stable"
+                ),
+            }
+        })
+        .collect();
+    Ok((uids, warnings))
+}
+
+/// Warns about fields whose fill rate dropped sharply since the previous
+/// run, then records this run as the new baseline. Runs that parsed nothing
+/// keep the old baseline; the empty node set is warned about already.
+fn check_field_coverage(
+    store: &mut JsonFileStore,
+    source: &LoadedSource,
+    coverage: FieldCoverage,
+    report: &mut SourceRunReport,
+) {
+    if coverage.records == 0 {
+        return;
+    }
+    let key = &source.config.source.key;
+    if let Some(previous) = store.field_coverage(key) {
+        for (field, before, now) in coverage.drops_since(previous, COVERAGE_DROP_THRESHOLD) {
+            warn!(
+                source = %key,
+                field = %field,
+                previous = before,
+                current = now,
+                "field coverage dropped"
+            );
+            report.warnings.push(ParseWarning {
+                kind: ParseWarningKind::CoverageDrop,
+                source_url: None,
+                message: format!(
+                    "{field} filled in {:.0}% of records, down from {:.0}%",
+                    now * 100.0,
+                    before * 100.0
+                ),
+                field: Some(field),
+                count: coverage.records,
+            });
+        }
+    }
+    store.set_field_coverage(key, coverage);
+}
+
 fn rebuild_source_calendars(
-    state: &State,
+    store: &dyn EventStore,
     source: &LoadedSource,
     out_dir: &Path,
     year_filter: Option<i32>,
     changed_years: Option<BTreeSet<i32>>,
 ) -> Result<()> {
+    // Aliases may need to move to a new year even when nothing changed.
     if let Some(changed) = &changed_years
         && changed.is_empty()
+        && source.config.publish.aliases.is_none()
     {
         return Ok(());
     }
 
+    let current_year = Utc::now().year();
     let mut by_year: HashMap<i32, Vec<&EventRecord>> = HashMap::new();
-    for event in state.events.values().filter(|event| {
-        event.source_key == source.config.source.key
-            && !event.status.eq_ignore_ascii_case("cancelled")
-    }) {
-        if let Some(year) = event.year_bucket() {
+    let query = EventQuery::for_source(&source.config.source.key).with_year(year_filter);
+    for event in store.query(&query) {
+        if let Some(year) = event.year_bucket()
+            && source.config.publish.publishes_year(year, current_year)
+        {
             by_year.entry(year).or_default().push(event);
         }
     }
 
-    if let Some(year) = year_filter {
-        by_year.retain(|y, _| *y == year);
-    }
-
-    if let Some(changed) = &changed_years {
-        by_year.retain(|year, _| changed.contains(year));
-    }
-
     let source_dir = out_dir
         .join("sources")
         .join(source.config.sanitized_source_dir_name());
@@ -533,128 +1935,183 @@ fn rebuild_source_calendars(
             .with_context(|| format!("failed to create mirror dir {}", mirror_dir.display()))?;
     }
 
-    let mut expected_files = HashSet::new();
-
-    if source.config.publish.split_by_country {
-        let mut by_country_year: HashMap<(String, i32), Vec<&EventRecord>> = HashMap::new();
-        for (year, events) in by_year {
-            for event in events {
-                let country = event
+    // Each (country, year) file is independent, so they are rendered and
+    // written in parallel. Unchanged years are still named so cleanup knows
+    // the full set of files this source owns.
+    let mut groups: HashMap<(Option<String>, i32), Vec<&EventRecord>> = HashMap::new();
+    for (year, events) in by_year {
+        for event in events {
+            let country = source.config.publish.split_by_country.then(|| {
+                event
                     .country
                     .clone()
                     .unwrap_or_else(|| "xx".to_string())
-                    .to_ascii_lowercase();
-                by_country_year
-                    .entry((country, year))
-                    .or_default()
-                    .push(event);
-            }
+                    .to_ascii_lowercase()
+            });
+            groups.entry((country, year)).or_default().push(event);
         }
+    }
 
-        for ((country, year), mut events) in by_country_year {
-            events.sort_by(|a, b| {
-                let a_key = event_sort_key(a);
-                let b_key = event_sort_key(b);
-                a_key.cmp(&b_key)
-            });
-            let file_name = source_ics_filename(source, &file_prefix, year, Some(&country));
-            expected_files.insert(file_name.clone());
-            let path = source_dir.join(&file_name);
-            write_source_year_calendar(&source.config, year, &events, &path)?;
-            if let Some(mirror_dir) = &mirror_source_dir {
-                let mirror_path = mirror_dir.join(&file_name);
-                std::fs::copy(&path, &mirror_path).with_context(|| {
-                    format!(
-                        "failed to publish mirrored calendar {}",
-                        mirror_path.display()
-                    )
-                })?;
-                info!(
-                    source = %source.config.source.key,
+    let staging = Staging::new(
+        &source_dir,
+        &out_dir.join(".staging/sources").join(&file_prefix),
+    )?;
+    let year_files = groups
+        .into_par_iter()
+        .map(|((country, year), events)| -> Result<(i32, Vec<String>)> {
+            let publish = &source.config.publish;
+            let file_name = source_ics_filename(source, &file_prefix, year, country.as_deref());
+            let mut file_names = vec![file_name.clone()];
+            file_names.extend(language_variant_filenames(
+                &source.config.publish,
+                &file_name,
+            ));
+            file_names.extend(profile_variant_filenames(publish, &file_name));
+            if changed_years
+                .as_ref()
+                .is_some_and(|changed| !changed.contains(&year))
+            {
+                let overflow_name = overflow_filename(&file_name);
+                if source_dir.join(&overflow_name).exists() {
+                    file_names.push(overflow_name);
+                }
+                return Ok((
                     year,
-                    country = %country,
-                    mirror = %mirror_path.display(),
-                    "calendar file mirrored"
-                );
+                    with_compressed_variants(publish, &staging, &source_dir, false, file_names)?,
+                ));
             }
-            info!(
+            let (events, digests) = fold_digest_events(
+                &source.config.source.key,
+                &source.config.uid,
+                events,
+                publish,
+            );
+            let mut events: Vec<&EventRecord> = events;
+            events.extend(&digests);
+            events.sort_by_key(|event| event_sort_key(event));
+            let (events, overflow) = fit_calendar_budget(
+                &source.config.source.name,
+                year,
+                events,
+                &source.config.publish,
+            );
+            debug!(
                 source = %source.config.source.key,
                 year,
-                country = %country,
+                country = country.as_deref().unwrap_or_default(),
                 events = events.len(),
-                file = %path.display(),
-                "calendar file rebuilt"
+                "rendering calendar file"
             );
-        }
-    } else {
-        for (year, mut events) in by_year {
-            events.sort_by(|a, b| {
-                let a_key = event_sort_key(a);
-                let b_key = event_sort_key(b);
-                a_key.cmp(&b_key)
-            });
-            let file_name = source_ics_filename(source, &file_prefix, year, None);
-            expected_files.insert(file_name.clone());
-            let path = source_dir.join(&file_name);
-            write_source_year_calendar(&source.config, year, &events, &path)?;
-            if let Some(mirror_dir) = &mirror_source_dir {
-                let mirror_path = mirror_dir.join(&file_name);
-                std::fs::copy(&path, &mirror_path).with_context(|| {
-                    format!(
-                        "failed to publish mirrored calendar {}",
-                        mirror_path.display()
-                    )
-                })?;
+            write_source_year_calendar(&source.config, year, &events, &staging.path(&file_name))?;
+            if !overflow.is_empty() {
+                let overflow_name = overflow_filename(&file_name);
+                write_named_year_calendar(
+                    &format!("{} (overflow)", source.config.source.name),
+                    year,
+                    &overflow,
+                    &source.config.publish,
+                    &staging.path(&overflow_name),
+                )?;
+                file_names.push(overflow_name);
+            }
+
+            // Extra languages get their own file next to the default one,
+            // e.g. `name-2030.fr.ics`.
+            for language in &publish.languages {
+                if publish.language.as_ref() == Some(language) {
+                    continue;
+                }
+                let localized = PublishConfig {
+                    language: Some(language.clone()),
+                    ..publish.clone()
+                };
+                write_named_year_calendar(
+                    &source.config.source.name,
+                    year,
+                    &events,
+                    &localized,
+                    &staging.path(&language_variant_filename(&file_name, language)),
+                )?;
+            }
+            write_profile_variants(
+                &source.config.source.name,
+                year,
+                &events,
+                publish,
+                &staging,
+                &file_name,
+            )?;
+            Ok((
+                year,
+                with_compressed_variants(publish, &staging, &source_dir, true, file_names)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut expected_files = year_files
+        .iter()
+        .flat_map(|(_, names)| names.iter().cloned())
+        .collect::<HashSet<_>>();
+
+    // Nothing reaches the live directory until every file was rendered.
+    let committed = staging.commit()?;
+    if let Some(mirror_dir) = &mirror_source_dir {
+        for name in &committed {
+            let mirror_path = mirror_dir.join(name);
+            if mirror_calendar_file(&source_dir.join(name), &mirror_path)? {
                 info!(
                     source = %source.config.source.key,
-                    year,
                     mirror = %mirror_path.display(),
                     "calendar file mirrored"
                 );
             }
-            info!(
-                source = %source.config.source.key,
-                year,
-                events = events.len(),
-                file = %path.display(),
-                "calendar file rebuilt"
-            );
         }
     }
 
-    if source_dir.exists() {
-        cleanup_stale_calendar_files(&source_dir, &expected_files, &file_prefix)?;
+    if let Some(mode) = source.config.publish.aliases {
+        let dirs = std::iter::once(&source_dir)
+            .chain(mirror_source_dir.as_ref())
+            .collect::<Vec<_>>();
+        expected_files.extend(publish_year_aliases(
+            &dirs,
+            &year_files,
+            current_year,
+            mode,
+        )?);
     }
-    if let Some(mirror_dir) = &mirror_source_dir
-        && mirror_dir.exists()
-    {
-        cleanup_stale_calendar_files(mirror_dir, &expected_files, &file_prefix)?;
+
+    let owner = source.config.source.key.as_str();
+    for dir in std::iter::once(&source_dir).chain(mirror_source_dir.as_ref()) {
+        // A single-year build only knows about that year's files.
+        if year_filter.is_some() {
+            record_owned_files(dir, owner, &expected_files)?;
+        } else {
+            reconcile_owned_files(dir, owner, &expected_files)?;
+        }
     }
 
     Ok(())
 }
 
 fn rebuild_bundles(
-    state: &State,
+    store: &dyn EventStore,
     bundles: &[LoadedBundle],
     out_dir: &Path,
     year_filter: Option<i32>,
 ) -> Result<()> {
+    let current_year = Utc::now().year();
     for bundle in bundles {
         let mut by_year: HashMap<i32, Vec<&EventRecord>> = HashMap::new();
-        for event in state.events.values().filter(|event| {
-            !event.status.eq_ignore_ascii_case("cancelled")
-                && matches_bundle_patterns(&event.source_key, &bundle.config.include.source_patterns)
-        }) {
-            if let Some(year) = event.year_bucket() {
+        let query = EventQuery::for_patterns(&bundle.config.include.source_patterns)
+            .with_year(year_filter)
+            .with_categories(&bundle.config.include.categories);
+        for event in store.query(&query) {
+            if let Some(year) = event.year_bucket()
+                && bundle.config.publish.publishes_year(year, current_year)
+            {
                 by_year.entry(year).or_default().push(event);
             }
         }
 
-        if let Some(year) = year_filter {
-            by_year.retain(|y, _| *y == year);
-        }
-
         let bundle_dir = out_dir
             .join("bundles")
             .join(bundle.config.sanitized_bundle_dir_name());
@@ -674,85 +2131,502 @@ fn rebuild_bundles(
                 .with_context(|| format!("failed to create mirror dir {}", mirror_dir.display()))?;
         }
 
-        let mut expected_files = HashSet::new();
-        for (year, mut events) in by_year {
-            events.sort_by(|a, b| event_sort_key(a).cmp(&event_sort_key(b)));
-            let file_name = bundle_ics_filename(bundle, &file_prefix, year);
-            expected_files.insert(file_name.clone());
-            let path = bundle_dir.join(&file_name);
-            write_named_year_calendar(&bundle.config.bundle.name, year, &events, &path)?;
-            if let Some(mirror_dir) = &mirror_bundle_dir {
-                let mirror_path = mirror_dir.join(&file_name);
-                std::fs::copy(&path, &mirror_path).with_context(|| {
-                    format!(
-                        "failed to publish mirrored calendar {}",
-                        mirror_path.display()
-                    )
-                })?;
+        let staging = Staging::new(
+            &bundle_dir,
+            &out_dir.join(".staging/bundles").join(&file_prefix),
+        )?;
+        let year_files = by_year
+            .into_par_iter()
+            .map(|(year, events)| -> Result<(i32, Vec<String>)> {
+                let (events, digests) = fold_digest_events(
+                    &format!("bundle:{}", bundle.config.bundle.key),
+                    &UidConfig::default(),
+                    events,
+                    &bundle.config.publish,
+                );
+                let mut events: Vec<&EventRecord> = events;
+                events.extend(&digests);
+                events.sort_by_key(|event| event_sort_key(event));
+                let file_name = bundle_ics_filename(bundle, &file_prefix, year);
+                let (events, overflow) = fit_calendar_budget(
+                    &bundle.config.bundle.name,
+                    year,
+                    events,
+                    &bundle.config.publish,
+                );
+                write_named_year_calendar(
+                    &bundle.config.bundle.name,
+                    year,
+                    &events,
+                    &bundle.config.publish,
+                    &staging.path(&file_name),
+                )?;
+                write_profile_variants(
+                    &bundle.config.bundle.name,
+                    year,
+                    &events,
+                    &bundle.config.publish,
+                    &staging,
+                    &file_name,
+                )?;
+                let mut file_names = profile_variant_filenames(&bundle.config.publish, &file_name);
+                file_names.insert(0, file_name);
+                if !overflow.is_empty() {
+                    let overflow_name = overflow_filename(&file_names[0]);
+                    write_named_year_calendar(
+                        &format!("{} (overflow)", bundle.config.bundle.name),
+                        year,
+                        &overflow,
+                        &bundle.config.publish,
+                        &staging.path(&overflow_name),
+                    )?;
+                    file_names.push(overflow_name);
+                }
+                Ok((
+                    year,
+                    with_compressed_variants(
+                        &bundle.config.publish,
+                        &staging,
+                        &bundle_dir,
+                        true,
+                        file_names,
+                    )?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let committed = staging.commit()?;
+        if let Some(mirror_dir) = &mirror_bundle_dir {
+            for name in &committed {
+                mirror_calendar_file(&bundle_dir.join(name), &mirror_dir.join(name))?;
             }
         }
+        let mut expected_files = year_files
+            .iter()
+            .flat_map(|(_, names)| names.iter().cloned())
+            .collect::<HashSet<_>>();
+        if let Some(mode) = bundle.config.publish.aliases {
+            let dirs = std::iter::once(&bundle_dir)
+                .chain(mirror_bundle_dir.as_ref())
+                .collect::<Vec<_>>();
+            expected_files.extend(publish_year_aliases(
+                &dirs,
+                &year_files,
+                current_year,
+                mode,
+            )?);
+        }
 
-        cleanup_stale_calendar_files(&bundle_dir, &expected_files, &file_prefix)?;
-        if let Some(mirror_dir) = &mirror_bundle_dir
-            && mirror_dir.exists()
-        {
-            cleanup_stale_calendar_files(mirror_dir, &expected_files, &file_prefix)?;
+        let owner = format!("bundle:{}", bundle.config.bundle.key);
+        for dir in std::iter::once(&bundle_dir).chain(mirror_bundle_dir.as_ref()) {
+            if year_filter.is_some() {
+                record_owned_files(dir, &owner, &expected_files)?;
+            } else {
+                reconcile_owned_files(dir, &owner, &expected_files)?;
+            }
         }
     }
 
     Ok(())
 }
 
-fn matches_bundle_patterns(source_key: &str, patterns: &[String]) -> bool {
-    patterns
-        .iter()
-        .any(|pattern| source_key_matches_pattern(source_key, pattern))
+/// Copies a rebuilt calendar to its mirror location unless the mirror already
+/// has identical content. Returns whether the mirror was written.
+fn mirror_calendar_file(path: &Path, mirror_path: &Path) -> Result<bool> {
+    if file_digest(mirror_path)? == file_digest(path)? {
+        return Ok(false);
+    }
+    // Copy next to the target and rename, so the mirror is never partial.
+    let tmp_path = mirror_path.with_extension("ics.tmp");
+    std::fs::copy(path, &tmp_path)
+        .and_then(|_| std::fs::rename(&tmp_path, mirror_path))
+        .with_context(|| {
+            format!(
+                "failed to publish mirrored calendar {}",
+                mirror_path.display()
+            )
+        })?;
+    Ok(true)
 }
 
-fn source_key_matches_pattern(source_key: &str, pattern: &str) -> bool {
-    if let Some(prefix) = pattern.strip_suffix('*') {
-        source_key.starts_with(prefix)
-    } else {
-        source_key == pattern
+fn ics_filename(file_prefix: &str, year: i32) -> String {
+    format!("{file_prefix}-{year}.ics")
+}
+
+/// Points `-current`/`-next` aliases in each of `dirs` at the current and
+/// next year's files and returns the alias names. Years without a file get
+/// no alias, so a stale one is cleaned up like any other unexpected file.
+fn publish_year_aliases(
+    dirs: &[&PathBuf],
+    year_files: &[(i32, Vec<String>)],
+    current_year: i32,
+    mode: AliasMode,
+) -> Result<Vec<String>> {
+    let mut aliases = Vec::new();
+    for (year, names) in year_files {
+        let label = match year - current_year {
+            0 => "current",
+            1 => "next",
+            _ => continue,
+        };
+        for name in names {
+            let Some(alias) = alias_filename(name, *year, label) else {
+                continue;
+            };
+            for dir in dirs {
+                if dir.join(name).exists() && write_alias(dir, name, &alias, mode)? {
+                    info!(alias = %dir.join(&alias).display(), target = %name, "calendar alias updated");
+                }
+            }
+            aliases.push(alias);
+        }
     }
+    Ok(aliases)
 }
 
-fn cleanup_stale_calendar_files(
-    source_dir: &Path,
-    expected_files: &HashSet<String>,
-    file_prefix: &str,
-) -> Result<()> {
-    for entry in std::fs::read_dir(source_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().and_then(|v| v.to_str()) != Some("ics") {
-            continue;
+/// `name-2030.fr.ics` -> `name-current.fr.ics`; `None` when the year is not
+/// part of the file name.
+fn alias_filename(file_name: &str, year: i32, label: &str) -> Option<String> {
+    let year = year.to_string();
+    let index = file_name.rfind(&year)?;
+    Some(format!(
+        "{}{label}{}",
+        &file_name[..index],
+        &file_name[index + year.len()..]
+    ))
+}
+
+/// Replaces `dir/alias` with a link to or copy of `dir/target` unless it
+/// already matches. Returns whether anything was written.
+fn write_alias(dir: &Path, target: &str, alias: &str, mode: AliasMode) -> Result<bool> {
+    let alias_path = dir.join(alias);
+    let tmp_path = dir.join(format!(".{alias}.tmp"));
+    let _ = std::fs::remove_file(&tmp_path);
+    #[cfg(unix)]
+    if mode == AliasMode::Symlink {
+        if std::fs::read_link(&alias_path).is_ok_and(|current| current == Path::new(target)) {
+            return Ok(false);
         }
-        let Some(file_name) = path.file_name().and_then(|s| s.to_str()) else {
-            continue;
+        std::os::unix::fs::symlink(target, &tmp_path)
+            .and_then(|_| std::fs::rename(&tmp_path, &alias_path))
+            .with_context(|| format!("failed to link alias {}", alias_path.display()))?;
+        return Ok(true);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    let target_path = dir.join(target);
+    let is_copy = std::fs::symlink_metadata(&alias_path).is_ok_and(|meta| meta.is_file());
+    if is_copy && file_digest(&alias_path)? == file_digest(&target_path)? {
+        return Ok(false);
+    }
+    std::fs::copy(&target_path, &tmp_path)
+        .and_then(|_| std::fs::rename(&tmp_path, &alias_path))
+        .with_context(|| format!("failed to copy alias {}", alias_path.display()))?;
+    Ok(true)
+}
+
+/// Splits a year's events, in calendar order, into those within
+/// `publish.max_events_per_file` and `publish.max_file_bytes` and the
+/// overflow, moving events out in `publish.trim_order`.
+fn fit_calendar_budget<'a>(
+    calendar_name: &str,
+    year: i32,
+    events: Vec<&'a EventRecord>,
+    publish: &PublishConfig,
+) -> (Vec<&'a EventRecord>, Vec<&'a EventRecord>) {
+    if publish.max_events_per_file.is_none() && publish.max_file_bytes.is_none() {
+        return (events, Vec::new());
+    }
+    // Indices of `events`, first to move out first.
+    let mut order = (0..events.len()).collect::<Vec<_>>();
+    match publish.trim_order {
+        TrimOrder::LowestImportance => order.sort_by_key(|&index| {
+            (
+                events[index].importance.unwrap_or(0),
+                event_sort_key(events[index]),
+            )
+        }),
+        TrimOrder::Oldest => order.sort_by_key(|&index| event_sort_key(events[index])),
+    }
+    let mut dropped = publish
+        .max_events_per_file
+        .map_or(0, |max| events.len().saturating_sub(max));
+    if let Some(max_bytes) = publish.max_file_bytes {
+        // A calendar is its header and footer plus each event's lines, so
+        // one render per event gives the size of any subset.
+        let sizes = events
+            .iter()
+            .map(|event| rendered_event_len(event, publish))
+            .collect::<Vec<_>>();
+        let mut size = render_named_year_calendar(calendar_name, year, &[], publish).len()
+            + order[dropped..]
+                .iter()
+                .map(|&index| sizes[index])
+                .sum::<usize>();
+        while size as u64 > max_bytes && dropped < events.len() {
+            size -= sizes[order[dropped]];
+            dropped += 1;
+        }
+    }
+    if dropped == 0 {
+        return (events, Vec::new());
+    }
+    let moved = order[..dropped].iter().copied().collect::<HashSet<_>>();
+    let (overflow, kept): (Vec<_>, Vec<_>) = events
+        .into_iter()
+        .enumerate()
+        .partition(|(index, _)| moved.contains(index));
+    let kept = kept.into_iter().map(|(_, event)| event).collect::<Vec<_>>();
+    let overflow = overflow
+        .into_iter()
+        .map(|(_, event)| event)
+        .collect::<Vec<_>>();
+    warn!(
+        calendar = %calendar_name,
+        year,
+        kept = kept.len(),
+        overflow = overflow.len(),
+        "calendar over its publish budget; moved events to the overflow file"
+    );
+    (kept, overflow)
+}
+
+/// Splits off the events `publish.digest` folds and builds one all-day
+/// digest per day or week in their place. Periods with fewer than
+/// `min_events` such events keep them individual.
+fn fold_digest_events<'a>(
+    owner_key: &str,
+    uid_config: &UidConfig,
+    events: Vec<&'a EventRecord>,
+    publish: &PublishConfig,
+) -> (Vec<&'a EventRecord>, Vec<EventRecord>) {
+    let Some(digest) = &publish.digest else {
+        return (events, Vec::new());
+    };
+    let mut kept = Vec::new();
+    let mut periods: BTreeMap<NaiveDate, Vec<&EventRecord>> = BTreeMap::new();
+    for event in events {
+        let day = match &event.time {
+            EventTimeSpec::DateTime {
+                local: Some(local), ..
+            } => Some(local.start.date()),
+            EventTimeSpec::DateTime { start, .. } => Some(start.date_naive()),
+            EventTimeSpec::Date { start, .. } => Some(*start),
+            _ => None,
         };
-        if is_legacy_year_only_filename(file_name) || !expected_files.contains(file_name) {
-            std::fs::remove_file(&path)
-                .with_context(|| format!("failed to remove stale file {}", path.display()))?;
-            let marker = extract_year_from_any_ics_filename(file_name, file_prefix)
-                .map(|y| y.to_string())
-                .unwrap_or_else(|| "unknown-year".to_string());
-            warn!(
-                file = %path.display(),
-                marker = %marker,
-                "removed stale calendar file"
-            );
+        let minor = digest
+            .max_importance
+            .is_none_or(|max| event.importance.unwrap_or(0) <= max);
+        match day.filter(|_| minor) {
+            Some(day) => {
+                let first = match digest.period {
+                    DigestPeriod::Daily => day,
+                    DigestPeriod::Weekly => {
+                        day - chrono::Days::new(u64::from(day.weekday().num_days_from_monday()))
+                    }
+                };
+                periods.entry(first).or_default().push(event);
+            }
+            None => kept.push(event),
+        }
+    }
+
+    let mut digests = Vec::new();
+    for (first, mut members) in periods {
+        if members.len() < digest.min_events {
+            kept.extend(members);
+            continue;
+        }
+        members.sort_by_key(|event| event_sort_key(event));
+        let lines = members
+            .iter()
+            .map(|event| {
+                let when = match &event.time {
+                    EventTimeSpec::DateTime {
+                        local: Some(local), ..
+                    } => format!(
+                        "{} {}",
+                        local.start.format("%a %-d %b"),
+                        local.start_label()
+                    ),
+                    EventTimeSpec::DateTime { start, .. } => {
+                        start.format("%a %-d %b %H:%M UTC").to_string()
+                    }
+                    time => time
+                        .start_date()
+                        .map(|date| date.format("%a %-d %b").to_string())
+                        .unwrap_or_default(),
+                };
+                format!("{when}: {}", event.title)
+            })
+            .collect::<Vec<_>>();
+        let title = digest
+            .title_template()
+            .replace("{{date}}", &first.format("%-d %B").to_string())
+            .replace("{{count}}", &members.len().to_string());
+        let mut categories = members
+            .iter()
+            .flat_map(|event| event.categories.iter().cloned())
+            .collect::<BTreeSet<_>>();
+        categories.insert("digest".to_string());
+        let key = format!("digest:{first}");
+        digests.push(EventRecord {
+            uid: uid_for(
+                &UidIdentity {
+                    source_key: owner_key,
+                    source_event_id: Some(&key),
+                    source_url: None,
+                    title: &title,
+                    year: Some(first.year()),
+                },
+                uid_config,
+            ),
+            source_key: owner_key.to_string(),
+            title,
+            description: Some(lines.join("\n")),
+            time: EventTimeSpec::Date {
+                start: first,
+                end: None,
+            },
+            status: members[0].status.clone(),
+            event_type: "digest".to_string(),
+            categories: categories.into_iter().collect(),
+            importance: members.iter().filter_map(|event| event.importance).max(),
+            // Stable timestamps keep an unchanged digest byte-identical.
+            sequence: members
+                .iter()
+                .map(|event| event.sequence)
+                .max()
+                .unwrap_or(0),
+            created_at: members
+                .iter()
+                .map(|event| event.created_at)
+                .min()
+                .unwrap_or_default(),
+            last_modified: members
+                .iter()
+                .map(|event| event.last_modified)
+                .max()
+                .unwrap_or_default(),
+            ..EventRecord::default()
+        });
+    }
+    (kept, digests)
+}
+
+/// `file_names` plus their compressed copies per `publish.compress`, staged
+/// in `staging`. Copies of rebuilt calendars are always rewritten; for an
+/// unchanged year (`rebuilt` false) only copies missing from `dir` are, e.g.
+/// when `compress` was just enabled.
+fn with_compressed_variants(
+    publish: &PublishConfig,
+    staging: &Staging,
+    dir: &Path,
+    rebuilt: bool,
+    mut file_names: Vec<String>,
+) -> Result<Vec<String>> {
+    let mut variants = Vec::new();
+    for name in &file_names {
+        let names = compressed_variant_names(publish, name);
+        if rebuilt {
+            write_compressed_variants(publish, &staging.path(name), &staging.path(name))?;
+        } else if dir.join(name).exists() && names.iter().any(|variant| !dir.join(variant).exists())
+        {
+            write_compressed_variants(publish, &dir.join(name), &staging.path(name))?;
         }
+        variants.extend(names);
     }
+    file_names.extend(variants);
+    Ok(file_names)
+}
+
+/// Splits `name.ics.gz` into `name.ics` and its compression.
+fn split_compressed_name(file_name: &str) -> (&str, Option<Compression>) {
+    Compression::ALL
+        .into_iter()
+        .find_map(|compression| {
+            let stem = file_name.strip_suffix(compression.extension())?;
+            Some((stem.strip_suffix('.')?, Some(compression)))
+        })
+        .unwrap_or((file_name, None))
+}
 
+/// `name-2030.ics` -> `name-2030.overflow.ics`.
+fn overflow_filename(file_name: &str) -> String {
+    let stem = file_name.strip_suffix(".ics").unwrap_or(file_name);
+    format!("{stem}.overflow.ics")
+}
+
+/// Names of the extra-language files published next to `file_name`.
+fn language_variant_filenames(publish: &PublishConfig, file_name: &str) -> Vec<String> {
+    publish
+        .languages
+        .iter()
+        .filter(|language| publish.language.as_ref() != Some(*language))
+        .map(|language| language_variant_filename(file_name, language))
+        .collect()
+}
+
+fn language_variant_filename(file_name: &str, language: &str) -> String {
+    let stem = file_name.strip_suffix(".ics").unwrap_or(file_name);
+    format!("{stem}.{}.ics", sanitize_language_tag(language))
+}
+
+/// `name-2030.ics` -> `name-2030.public.ics` for each `publish.profiles`.
+fn profile_variant_filenames(publish: &PublishConfig, file_name: &str) -> Vec<String> {
+    let stem = file_name.strip_suffix(".ics").unwrap_or(file_name);
+    publish
+        .profiles
+        .keys()
+        .map(|profile| format!("{stem}.{profile}.ics"))
+        .collect()
+}
+
+/// Writes the `publish.profiles` variants of a rebuilt calendar next to
+/// `file_name`, each with the events its profile keeps.
+fn write_profile_variants(
+    calendar_name: &str,
+    year: i32,
+    events: &[&EventRecord],
+    publish: &PublishConfig,
+    staging: &Staging,
+    file_name: &str,
+) -> Result<()> {
+    let names = profile_variant_filenames(publish, file_name);
+    for (profile, name) in publish.profiles.values().zip(names) {
+        let kept = events
+            .iter()
+            .copied()
+            .filter(|event| {
+                profile
+                    .min_importance
+                    .is_none_or(|min| event.importance.unwrap_or(0) >= min)
+                    && !event
+                        .categories
+                        .iter()
+                        .any(|category| profile.exclude_categories.contains(category))
+            })
+            .collect::<Vec<_>>();
+        write_named_year_calendar(
+            calendar_name,
+            year,
+            &kept,
+            &publish.with_profile(profile),
+            &staging.path(&name),
+        )?;
+    }
     Ok(())
 }
 
-fn ics_filename(file_prefix: &str, year: i32) -> String {
-    format!("{file_prefix}-{year}.ics")
+fn sanitize_language_tag(language: &str) -> String {
+    language
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>()
+        .to_ascii_lowercase()
 }
 
-fn source_ics_filename(
+pub(crate) fn source_ics_filename(
     source: &LoadedSource,
     file_prefix: &str,
     year: i32,
@@ -786,7 +2660,7 @@ fn source_ics_filename(
     }
 }
 
-fn bundle_ics_filename(bundle: &LoadedBundle, file_prefix: &str, year: i32) -> String {
+pub(crate) fn bundle_ics_filename(bundle: &LoadedBundle, file_prefix: &str, year: i32) -> String {
     let Some(template) = bundle.config.publish.file_name_template.as_deref() else {
         return ics_filename(file_prefix, year);
     };
@@ -810,13 +2684,6 @@ fn parse_year_from_filename(file_name: &str, file_prefix: &str) -> Option<i32> {
     year.parse::<i32>().ok()
 }
 
-fn is_legacy_year_only_filename(file_name: &str) -> bool {
-    file_name
-        .strip_suffix(".ics")
-        .and_then(|stem| stem.parse::<i32>().ok())
-        .is_some()
-}
-
 fn extract_year_from_any_ics_filename(file_name: &str, file_prefix: &str) -> Option<i32> {
     parse_year_from_filename(file_name, file_prefix).or_else(|| {
         let stem = file_name.strip_suffix(".ics")?;
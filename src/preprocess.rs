@@ -0,0 +1,395 @@
+//! Preprocessors applied to fetched bodies before extraction.
+//!
+//! A source lists `[[preprocess]]` steps to tame messy inputs without a
+//! custom parser: strip `<script>` and other noise from HTML, keep only a
+//! page's main content, unpack a zip or tar archive into one document per
+//! member, or turn an XLS/XLSX/ODS workbook into CSV text for
+//! `format = "text"`.
+//! Steps run in order, each on the documents the previous one produced.
+//! Bodies are preprocessed at parse time, so captured and recorded
+//! responses keep the bytes the server sent.
+
+#[cfg(feature = "archives")]
+use crate::config::ArchiveFormat;
+use crate::config::{LoadedSource, PreprocessConfig, PreprocessKind};
+use crate::fetch::FetchedDocument;
+#[cfg(feature = "archives")]
+use crate::fetch::read_capped;
+use anyhow::{Context, Result, anyhow, bail};
+use scraper::{ElementRef, Html, Selector};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use tracing::debug;
+
+pub trait Preprocessor: Send + Sync {
+    fn key(&self) -> &'static str;
+    /// The documents `doc` becomes; one for most steps, any number for
+    /// archives.
+    fn process(&self, source: &LoadedSource, doc: FetchedDocument) -> Result<Vec<FetchedDocument>>;
+}
+
+/// `docs` after the source's `[[preprocess]]` steps; borrowed when it has
+/// none.
+pub fn preprocess_documents<'a>(
+    source: &LoadedSource,
+    docs: &'a [FetchedDocument],
+) -> Result<Cow<'a, [FetchedDocument]>> {
+    if source.config.preprocess.is_empty() {
+        return Ok(Cow::Borrowed(docs));
+    }
+    let mut docs = docs.to_vec();
+    for step in &source.config.preprocess {
+        let preprocessor = preprocessor(step)?;
+        let before = docs.len();
+        docs = docs
+            .into_iter()
+            .map(|doc| {
+                let url = doc.source_url.clone();
+                preprocessor
+                    .process(source, doc)
+                    .with_context(|| format!("preprocess {} failed on {url}", preprocessor.key()))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        debug!(
+            source = %source.config.source.key,
+            step = preprocessor.key(),
+            before,
+            after = docs.len(),
+            "documents preprocessed"
+        );
+    }
+    Ok(Cow::Owned(docs))
+}
+
+fn preprocessor(step: &PreprocessConfig) -> Result<Box<dyn Preprocessor>> {
+    Ok(match step.kind {
+        PreprocessKind::StripTags => {
+            let tags = step.tags.join(", ");
+            let selector = Selector::parse(&tags)
+                .map_err(|err| anyhow!("preprocess strip_tags tags {tags} are not valid: {err}"))?;
+            Box::new(StripTags { selector })
+        }
+        PreprocessKind::Readability => Box::new(Readability),
+        #[cfg(feature = "archives")]
+        PreprocessKind::Unpack => Box::new(Unpack {
+            files: step
+                .files
+                .as_deref()
+                .map(glob::Pattern::new)
+                .transpose()
+                .context("invalid preprocess unpack files glob")?,
+            archive: step.archive,
+        }),
+        #[cfg(not(feature = "archives"))]
+        PreprocessKind::Unpack => {
+            bail!("preprocess unpack needs rics built with the `archives` feature")
+        }
+        #[cfg(feature = "spreadsheet")]
+        PreprocessKind::SpreadsheetToCsv => Box::new(SpreadsheetToCsv {
+            sheet: step.sheet.clone(),
+        }),
+        #[cfg(not(feature = "spreadsheet"))]
+        PreprocessKind::SpreadsheetToCsv => {
+            bail!("preprocess spreadsheet_to_csv needs rics built with the `spreadsheet` feature")
+        }
+    })
+}
+
+/// `doc` with a new body. The body is UTF-8 now, so the content type is
+/// replaced too.
+fn with_body(doc: FetchedDocument, body: String, content_type: &str) -> FetchedDocument {
+    let mut headers = doc.headers;
+    headers.insert("content-type".to_string(), content_type.to_string());
+    FetchedDocument {
+        body: body.into_bytes().into(),
+        headers,
+        ..doc
+    }
+}
+
+/// Removes the elements matching `tags`, content and all.
+struct StripTags {
+    selector: Selector,
+}
+
+impl Preprocessor for StripTags {
+    fn key(&self) -> &'static str {
+        "strip_tags"
+    }
+
+    fn process(
+        &self,
+        _source: &LoadedSource,
+        doc: FetchedDocument,
+    ) -> Result<Vec<FetchedDocument>> {
+        let mut html = Html::parse_document(&doc.text());
+        let matched = html
+            .select(&self.selector)
+            .map(|element| element.id())
+            .collect::<Vec<_>>();
+        for id in matched {
+            if let Some(mut node) = html.tree.get_mut(id) {
+                node.detach();
+            }
+        }
+        Ok(vec![with_body(
+            doc,
+            html.html(),
+            "text/html; charset=utf-8",
+        )])
+    }
+}
+
+/// Keeps only the page's main content: its `<article>`, `<main>` or
+/// `role="main"` element when it has one, otherwise the element holding
+/// the most paragraph, list and table text.
+struct Readability;
+
+impl Preprocessor for Readability {
+    fn key(&self) -> &'static str {
+        "readability"
+    }
+
+    fn process(
+        &self,
+        _source: &LoadedSource,
+        doc: FetchedDocument,
+    ) -> Result<Vec<FetchedDocument>> {
+        let html = Html::parse_document(&doc.text());
+        let Some(content) = main_content(&html) else {
+            return Ok(vec![doc]);
+        };
+        let body = format!("<html><body>{}</body></html>", content.html());
+        Ok(vec![with_body(doc, body, "text/html; charset=utf-8")])
+    }
+}
+
+fn main_content(html: &Html) -> Option<ElementRef<'_>> {
+    let landmark = Selector::parse("article, main, [role=main]").expect("valid selector");
+    if let Some(element) = html.select(&landmark).next() {
+        return Some(element);
+    }
+
+    // Each block scores its text, less link text so navigation loses, in
+    // full for the element around its list or table and in half for the
+    // one around that.
+    let blocks = Selector::parse("p, li, tr, dt, dd, pre, blockquote").expect("valid selector");
+    let links = Selector::parse("a").expect("valid selector");
+    let mut scores = HashMap::new();
+    for block in html.select(&blocks) {
+        let text = block.text().map(str::trim).map(str::len).sum::<usize>();
+        let linked = block
+            .select(&links)
+            .flat_map(|link| link.text())
+            .map(str::trim)
+            .map(str::len)
+            .sum::<usize>();
+        let score = text.saturating_sub(linked) as f64;
+        let ancestors = block
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .filter(|ancestor| !is_list_or_table_part(ancestor));
+        for (ancestor, weight) in ancestors.zip([1.0, 0.5]) {
+            *scores.entry(ancestor.id()).or_insert(0.0) += score * weight;
+        }
+    }
+    // Ties go to the outermost element.
+    let (best, _) = scores
+        .into_iter()
+        .filter(|(_, score)| *score > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))?;
+    html.tree.get(best).and_then(ElementRef::wrap)
+}
+
+fn is_list_or_table_part(element: &ElementRef<'_>) -> bool {
+    matches!(
+        element.value().name(),
+        "ul" | "ol" | "li" | "dl" | "table" | "thead" | "tbody" | "tfoot" | "tr" | "td" | "th"
+    )
+}
+
+/// One document per archive member, named `<url>#<member path>`.
+/// Documents that are not archives pass through unchanged unless the
+/// format is configured.
+#[cfg(feature = "archives")]
+struct Unpack {
+    files: Option<glob::Pattern>,
+    archive: Option<ArchiveFormat>,
+}
+
+#[cfg(feature = "archives")]
+impl Preprocessor for Unpack {
+    fn key(&self) -> &'static str {
+        "unpack"
+    }
+
+    fn process(&self, source: &LoadedSource, doc: FetchedDocument) -> Result<Vec<FetchedDocument>> {
+        let Some(archive) = self.archive.or_else(|| archive_format(&doc)) else {
+            return Ok(vec![doc]);
+        };
+        let mut members = Vec::new();
+        // Each member is held to the same cap as a response body.
+        let mut add = |name: &str, data: &mut dyn std::io::Read| -> Result<()> {
+            if self
+                .files
+                .as_ref()
+                .is_some_and(|files| !files.matches(name))
+            {
+                return Ok(());
+            }
+            let source_url = format!("{}#{name}", doc.source_url);
+            let body = read_capped(data, &source_url, &source.config.fetch)?;
+            members.push(FetchedDocument {
+                source_url,
+                body,
+                page_index: doc.page_index,
+                fetched_at: doc.fetched_at,
+                headers: Default::default(),
+            });
+            Ok(())
+        };
+        let body = std::io::Cursor::new(doc.body.as_slice());
+        match archive {
+            ArchiveFormat::Zip => {
+                let mut zip = zip::ZipArchive::new(body).context("body is not a zip archive")?;
+                for index in 0..zip.len() {
+                    let mut member = zip.by_index(index)?;
+                    if member.is_file() {
+                        let name = member.name().to_string();
+                        add(&name, &mut member)?;
+                    }
+                }
+            }
+            ArchiveFormat::Tar => tar_members(body, &mut add)?,
+            ArchiveFormat::TarGz => tar_members(flate2::read::GzDecoder::new(body), &mut add)?,
+        }
+        if members.is_empty() {
+            bail!("no archive members matched preprocess unpack files");
+        }
+        Ok(members)
+    }
+}
+
+#[cfg(feature = "archives")]
+fn tar_members(
+    data: impl std::io::Read,
+    add: &mut dyn FnMut(&str, &mut dyn std::io::Read) -> Result<()>,
+) -> Result<()> {
+    let mut tar = tar::Archive::new(data);
+    for entry in tar.entries().context("body is not a tar archive")? {
+        let mut entry = entry.context("body is not a tar archive")?;
+        if entry.header().entry_type().is_file() {
+            let name = entry.path()?.to_string_lossy().into_owned();
+            add(&name, &mut entry)?;
+        }
+    }
+    Ok(())
+}
+
+/// The archive `doc` holds, from its content type or, failing that, its
+/// leading bytes; `None` for anything else, including plain gzip.
+#[cfg(feature = "archives")]
+fn archive_format(doc: &FetchedDocument) -> Option<ArchiveFormat> {
+    use std::io::Read;
+
+    let content_type = doc
+        .headers
+        .get("content-type")
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase());
+    match content_type.as_deref() {
+        Some("application/zip" | "application/x-zip-compressed") => {
+            return Some(ArchiveFormat::Zip);
+        }
+        Some("application/x-tar") => return Some(ArchiveFormat::Tar),
+        _ => {}
+    }
+    let is_tar = |head: &[u8]| head.get(257..262) == Some(b"ustar");
+    let body = doc.body.as_slice();
+    if body.starts_with(b"PK\x03\x04") || body.starts_with(b"PK\x05\x06") {
+        Some(ArchiveFormat::Zip)
+    } else if is_tar(body) {
+        Some(ArchiveFormat::Tar)
+    } else if body.starts_with(&[0x1f, 0x8b]) {
+        let mut head = Vec::new();
+        flate2::read::GzDecoder::new(body)
+            .take(262)
+            .read_to_end(&mut head)
+            .ok()?;
+        is_tar(&head).then_some(ArchiveFormat::TarGz)
+    } else {
+        None
+    }
+}
+
+/// The sheet as CSV, one line per row. Date cells become `YYYY-MM-DD`, or
+/// `YYYY-MM-DDTHH:MM:SS` when they carry a time.
+#[cfg(feature = "spreadsheet")]
+struct SpreadsheetToCsv {
+    sheet: Option<String>,
+}
+
+#[cfg(feature = "spreadsheet")]
+impl Preprocessor for SpreadsheetToCsv {
+    fn key(&self) -> &'static str {
+        "spreadsheet_to_csv"
+    }
+
+    fn process(
+        &self,
+        _source: &LoadedSource,
+        doc: FetchedDocument,
+    ) -> Result<Vec<FetchedDocument>> {
+        use calamine::Reader;
+
+        let mut workbook =
+            calamine::open_workbook_auto_from_rs(std::io::Cursor::new(doc.body.as_slice()))
+                .context("body is not an xls, xlsx or ods workbook")?;
+        let sheet = match &self.sheet {
+            Some(sheet) => sheet.clone(),
+            None => workbook
+                .sheet_names()
+                .into_iter()
+                .next()
+                .context("workbook has no sheets")?,
+        };
+        let range = workbook
+            .worksheet_range(&sheet)
+            .with_context(|| format!("failed to read sheet {sheet}"))?;
+        let mut csv = String::new();
+        for row in range.rows() {
+            let cells = row.iter().map(csv_cell).collect::<Vec<_>>();
+            csv.push_str(&cells.join(","));
+            csv.push('\n');
+        }
+        Ok(vec![with_body(doc, csv, "text/csv; charset=utf-8")])
+    }
+}
+
+#[cfg(feature = "spreadsheet")]
+fn csv_cell(cell: &calamine::Data) -> String {
+    use calamine::{Data, DataType};
+
+    let text = match cell {
+        Data::DateTime(_) | Data::DateTimeIso(_) => cell
+            .as_datetime()
+            .map(|at| {
+                let format = if at.time() == chrono::NaiveTime::MIN {
+                    "%Y-%m-%d"
+                } else {
+                    "%Y-%m-%dT%H:%M:%S"
+                };
+                at.format(format).to_string()
+            })
+            .unwrap_or_else(|| cell.to_string()),
+        _ => cell.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
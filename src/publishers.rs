@@ -0,0 +1,1556 @@
+//! Parsers for specific publishers' calendars and APIs, selected with
+//! `custom.parser` (the `publishers` feature). Sources without a custom
+//! parser only need the declarative parser in [`crate::parser`].
+
+use crate::config::LoadedSource;
+use crate::fetch::FetchedDocument;
+#[cfg(feature = "http")]
+use crate::fetch::{identity_headers, source_client, template_substitutions, throttle};
+use crate::model::{CandidateEvent, EventTimeSpec};
+#[cfg(feature = "http")]
+use crate::network::check_request;
+#[cfg(feature = "http")]
+use crate::parser::absolutize_url;
+use crate::parser::{CustomParser, parse_event_time, parse_pipe_record};
+use anyhow::{Context, Result, anyhow};
+#[cfg(feature = "http")]
+use chrono::Datelike;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde_json::Value;
+use std::collections::BTreeMap;
+#[cfg(feature = "http")]
+use std::collections::HashSet;
+#[cfg(feature = "http")]
+use tracing::info;
+#[cfg(feature = "http")]
+use url::Url;
+
+/// The custom parser registered under `key`, if any.
+pub(crate) fn custom_parser(key: &str) -> Option<Box<dyn CustomParser>> {
+    let parser: Box<dyn CustomParser> = match key {
+        #[cfg(feature = "http")]
+        "oecd_publications_v1" => Box::new(OecdPublicationsParser),
+        "rough_text_lines_v1" => Box::new(RoughTextLinesParser),
+        "econ_indicators_calendar_v1" => Box::new(EconIndicatorsCalendarParser),
+        "europe_elections_feed_v1" => Box::new(EuropeElectionsFeedParser),
+        "structured_calendar_feed_v1" => Box::new(StructuredCalendarFeedParser),
+        "us_state_elections_feed_v1" => Box::new(UsStateElectionsFeedParser),
+        "mlb_statsapi_schedule_v1" => Box::new(MlbStatsApiScheduleParser),
+        #[cfg(feature = "http")]
+        "nhl_schedule_api_v1" => Box::new(NhlScheduleApiParser),
+        "nba_full_schedule_v1" => Box::new(NbaFullScheduleParser),
+        "nfl_operations_schedule_v1" => Box::new(NflOperationsScheduleParser),
+        #[cfg(feature = "http")]
+        "mls_statsapi_schedule_v1" => Box::new(MlsStatsApiScheduleParser),
+        _ => return None,
+    };
+    Some(parser)
+}
+
+#[cfg(feature = "http")]
+struct OecdPublicationsParser;
+
+#[cfg(feature = "http")]
+impl CustomParser for OecdPublicationsParser {
+    fn key(&self) -> &'static str {
+        "oecd_publications_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        if docs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut events = Vec::new();
+        let current_year = Utc::now().year();
+        let mut seen_ids = HashSet::new();
+        let first_doc_url = Url::parse(&docs[0].source_url)
+            .with_context(|| format!("invalid source url {}", docs[0].source_url))?;
+        let mut query_pairs: BTreeMap<String, String> = first_doc_url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        let facet_tags = query_pairs.get("facetTags").cloned().unwrap_or_else(|| {
+            "oecd-languages:en,oecd-search-config-pillars:publications".to_string()
+        });
+        query_pairs.insert(
+            "facetTags".to_string(),
+            ensure_facet_tags(&facet_tags).to_string(),
+        );
+
+        let headers = identity_headers(source, &template_substitutions(source))?;
+        let client =
+            source_client(source, &headers, None).context("failed to build OECD API client")?;
+
+        let page_size = source
+            .config
+            .fetch
+            .headers
+            .get("x-oecd-page-size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(50);
+        let max_pages = 200usize;
+        let mut total = usize::MAX;
+        let mut page = 0usize;
+
+        while page < max_pages && page * page_size < total {
+            let mut params = query_pairs.clone();
+            params.insert("siteName".to_string(), "oecd".to_string());
+            params.insert("page".to_string(), page.to_string());
+            params.insert("pageSize".to_string(), page_size.to_string());
+            params
+                .entry("orderBy".to_string())
+                .or_insert_with(|| "mostRecent".to_string());
+            params
+                .entry("minPublicationYear".to_string())
+                .or_insert_with(|| current_year.to_string());
+            params
+                .entry("maxPublicationYear".to_string())
+                .or_insert_with(|| current_year.to_string());
+
+            let url = "https://api.oecd.org/webcms/search/faceted-search";
+            check_request(source, url)?;
+            throttle(source, url);
+            let response = client
+                .get(url)
+                .query(&params)
+                .send()
+                .with_context(|| format!("failed to query OECD API page {page}"))?;
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "OECD API returned {} for page {}",
+                    response.status(),
+                    page
+                ));
+            }
+            let payload = response
+                .json::<Value>()
+                .context("failed to decode OECD API JSON")?;
+
+            total = payload.get("total").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+
+            let Some(results) = payload.get("results").and_then(|v| v.as_array()) else {
+                break;
+            };
+
+            for result in results {
+                let tag_ids = result
+                    .get("tags")
+                    .and_then(Value::as_array)
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|tag| tag.get("id").and_then(Value::as_str))
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let is_publication = tag_ids
+                    .iter()
+                    .any(|id| id.starts_with("oecd-content-types:publications/"));
+                if !is_publication {
+                    continue;
+                }
+
+                let title = result
+                    .get("title")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(ToString::to_string);
+                let Some(title) = title else {
+                    continue;
+                };
+
+                let url = result
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .map(|v| absolutize_url(Some("https://www.oecd.org"), v))
+                    .unwrap_or_default();
+                if url.is_empty() {
+                    continue;
+                }
+                if !seen_ids.insert(url.clone()) {
+                    continue;
+                }
+
+                let date_text = result
+                    .get("publicationDateTime")
+                    .and_then(|v| v.as_str())
+                    .or_else(|| result.get("startDateTime").and_then(|v| v.as_str()))
+                    .or_else(|| result.get("endDateTime").and_then(|v| v.as_str()));
+                let Some(date_text) = date_text else {
+                    continue;
+                };
+
+                let time = parse_event_time(
+                    date_text,
+                    None,
+                    &source.config.date,
+                    source.config.source.timezone.as_deref(),
+                )?;
+                if !matches_year_or_next(time.year_bucket(), current_year) {
+                    continue;
+                }
+
+                let description = result
+                    .get("description")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(ToString::to_string);
+
+                let tags = tag_ids.join(",");
+
+                events.push(CandidateEvent {
+                    source_key: source.config.source.key.clone(),
+                    source_name: source.config.source.name.clone(),
+                    source_event_id: Some(url.clone()),
+                    source_url: Some(url),
+                    title,
+                    description,
+                    time,
+                    timezone: source.config.source.timezone.clone(),
+                    status: source.config.event.status.clone(),
+                    event_type: source.config.event.event_type.clone(),
+                    subtype: source.config.event.subtype.clone(),
+                    categories: {
+                        let mut x = source.config.event.categories.clone();
+                        if !x.contains(&"publishing".to_string()) {
+                            x.push("publishing".to_string());
+                        }
+                        x
+                    },
+                    jurisdiction: source.config.source.jurisdiction.clone(),
+                    country: source.config.source.default_country.clone(),
+                    importance: source.config.event.importance,
+                    confidence: Some(0.95),
+                    metadata: BTreeMap::from([
+                        ("custom_parser".to_string(), self.key().to_string()),
+                        ("api_total".to_string(), total.to_string()),
+                        ("api_tags".to_string(), tags),
+                    ]),
+                    ..CandidateEvent::default()
+                });
+            }
+
+            page += 1;
+        }
+
+        info!(
+            source = %source.config.source.key,
+            events = events.len(),
+            "oecd parser extracted dated publication events"
+        );
+
+        Ok(events)
+    }
+}
+
+#[cfg(feature = "http")]
+fn ensure_facet_tags(tags: &str) -> String {
+    let mut values = tags
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if !values.iter().any(|v| v == "oecd-languages:en") {
+        values.push("oecd-languages:en".to_string());
+    }
+    if !values
+        .iter()
+        .any(|v| v == "oecd-search-config-pillars:publications")
+    {
+        values.push("oecd-search-config-pillars:publications".to_string());
+    }
+    values.join(",")
+}
+
+#[cfg(feature = "http")]
+fn matches_year_or_next(year: Option<i32>, current_year: i32) -> bool {
+    match year {
+        Some(y) => y == current_year || y == current_year + 1,
+        None => false,
+    }
+}
+
+struct RoughTextLinesParser;
+
+impl CustomParser for RoughTextLinesParser {
+    fn key(&self) -> &'static str {
+        "rough_text_lines_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        let mut events = Vec::new();
+
+        for doc in docs {
+            let payload = doc.text();
+            for line in payload.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                let Some(map) = parse_pipe_record(line) else {
+                    continue;
+                };
+
+                let Some(title) = map.get("title").cloned() else {
+                    continue;
+                };
+
+                let time = if let Some(date) = map.get("date") {
+                    parse_event_time(
+                        date,
+                        None,
+                        &source.config.date,
+                        source.config.source.timezone.as_deref(),
+                    )?
+                } else {
+                    EventTimeSpec::Tbd { note: None }
+                };
+
+                events.push(CandidateEvent {
+                    source_key: source.config.source.key.clone(),
+                    source_name: source.config.source.name.clone(),
+                    source_event_id: map.get("url").cloned(),
+                    source_url: map.get("url").cloned(),
+                    title,
+                    description: None,
+                    time,
+                    timezone: source.config.source.timezone.clone(),
+                    status: source.config.event.status.clone(),
+                    event_type: source.config.event.event_type.clone(),
+                    subtype: source.config.event.subtype.clone(),
+                    categories: source.config.event.categories.clone(),
+                    jurisdiction: source.config.source.jurisdiction.clone(),
+                    country: source.config.source.default_country.clone(),
+                    importance: source.config.event.importance,
+                    confidence: Some(0.5),
+                    metadata: BTreeMap::from([(
+                        "custom_parser".to_string(),
+                        self.key().to_string(),
+                    )]),
+                    ..CandidateEvent::default()
+                });
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+struct EconIndicatorsCalendarParser;
+
+impl CustomParser for EconIndicatorsCalendarParser {
+    fn key(&self) -> &'static str {
+        "econ_indicators_calendar_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        let mut events = Vec::new();
+        let day_header = Regex::new(
+            r"^(Monday|Tuesday|Wednesday|Thursday|Friday|Saturday|Sunday)\s+([A-Za-z]+)\s+(\d{1,2})\s+(\d{4})",
+        )
+        .expect("day header regex must compile");
+        let time_line =
+            Regex::new(r"^(\d{1,2}:\d{2}\s*[AP]M)$").expect("time line regex must compile");
+        let split_columns = Regex::new(r"\s{2,}").expect("split columns regex must compile");
+        let default_country = source
+            .config
+            .fetch
+            .template_vars
+            .get("country")
+            .cloned()
+            .or_else(|| source.config.source.default_country.clone())
+            .unwrap_or_else(|| "XX".to_string())
+            .to_ascii_uppercase();
+        let country_line_re = Regex::new(r"^[A-Z]{2,3}$").expect("country-line regex must compile");
+        let noise_lines = [
+            "news",
+            "markets",
+            "indicators",
+            "countries",
+            "forecasts",
+            "interest rate",
+            "inflation rate",
+            "unemployment rate",
+            "gdp growth",
+            "gdp per capita",
+            "current account",
+            "gold reserves",
+            "government debt",
+            "crude oil production",
+            "gasoline prices",
+            "credit rating",
+            "more indicators",
+            "actual",
+            "previous",
+            "consensus",
+            "forecast",
+        ];
+
+        for doc in docs {
+            let payload = doc.text();
+            let mut active_date: Option<NaiveDate> = None;
+            let mut active_time: Option<String> = None;
+            let mut waiting_for_country = false;
+            let mut active_country: Option<String> = None;
+
+            for raw in payload.lines() {
+                let line = raw.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let line_lower = line.to_ascii_lowercase();
+                if noise_lines.contains(&line_lower.as_str()) || line_lower.starts_with("utc ") {
+                    continue;
+                }
+
+                if let Some(caps) = day_header.captures(line) {
+                    let month = caps.get(2).map(|m| m.as_str()).unwrap_or_default();
+                    let day = caps.get(3).map(|m| m.as_str()).unwrap_or_default();
+                    let year = caps.get(4).map(|m| m.as_str()).unwrap_or_default();
+                    let date_str = format!("{month} {day} {year}");
+                    active_date = NaiveDate::parse_from_str(&date_str, "%B %d %Y")
+                        .ok()
+                        .or_else(|| NaiveDate::parse_from_str(&date_str, "%b %d %Y").ok());
+                    active_time = None;
+                    active_country = None;
+                    waiting_for_country = false;
+                    continue;
+                }
+
+                if let Some(caps) = time_line.captures(line) {
+                    active_time = caps.get(1).map(|m| m.as_str().to_string());
+                    waiting_for_country = true;
+                    continue;
+                }
+
+                if waiting_for_country {
+                    if country_line_re.is_match(line) {
+                        active_country = Some(line.to_ascii_uppercase());
+                        waiting_for_country = false;
+                        continue;
+                    }
+
+                    // If country row is missing, continue with this line as payload.
+                    waiting_for_country = false;
+                }
+
+                if country_line_re.is_match(line) {
+                    // Standalone country marker; don't emit as event title.
+                    active_country = Some(line.to_ascii_uppercase());
+                    continue;
+                }
+
+                let Some(date) = active_date else {
+                    continue;
+                };
+                let Some(time_text) = active_time.as_deref() else {
+                    continue;
+                };
+                let country = active_country
+                    .clone()
+                    .unwrap_or_else(|| default_country.clone());
+
+                let Some(start) =
+                    combine_date_time(date, time_text, source.config.source.timezone.as_deref())?
+                else {
+                    continue;
+                };
+
+                let columns = split_columns
+                    .split(line)
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .collect::<Vec<_>>();
+                if columns.is_empty() {
+                    continue;
+                }
+
+                let title = columns[0].to_string();
+                if title.len() < 4 || !title.chars().any(|c| c.is_ascii_alphabetic()) {
+                    continue;
+                }
+                let actual = columns.get(1).map(|v| v.to_string());
+                let previous = columns.get(2).map(|v| v.to_string());
+                let consensus = columns.get(3).map(|v| v.to_string());
+                let forecast = columns.get(4).map(|v| v.to_string());
+
+                let mut metadata = BTreeMap::new();
+                metadata.insert("country".to_string(), country.clone());
+                metadata.insert("custom_parser".to_string(), self.key().to_string());
+                if let Some(value) = &actual {
+                    metadata.insert("actual".to_string(), value.clone());
+                }
+                if let Some(value) = &previous {
+                    metadata.insert("previous".to_string(), value.clone());
+                }
+                if let Some(value) = &consensus {
+                    metadata.insert("consensus".to_string(), value.clone());
+                }
+                if let Some(value) = &forecast {
+                    metadata.insert("forecast".to_string(), value.clone());
+                }
+
+                let id = format!(
+                    "{}|{}|{}|{}",
+                    country,
+                    date.format("%Y-%m-%d"),
+                    time_text,
+                    title
+                );
+
+                let description = build_econ_description(actual, previous, consensus, forecast);
+
+                events.push(CandidateEvent {
+                    source_key: source.config.source.key.clone(),
+                    source_name: source.config.source.name.clone(),
+                    source_event_id: Some(id),
+                    source_url: Some(doc.source_url.clone()),
+                    title,
+                    description,
+                    time: EventTimeSpec::utc(start, None),
+                    timezone: source.config.source.timezone.clone(),
+                    status: source.config.event.status.clone(),
+                    event_type: source.config.event.event_type.clone(),
+                    subtype: source.config.event.subtype.clone(),
+                    categories: source.config.event.categories.clone(),
+                    jurisdiction: source.config.source.jurisdiction.clone(),
+                    country: Some(country),
+                    importance: source.config.event.importance,
+                    confidence: Some(0.9),
+                    metadata,
+                    ..CandidateEvent::default()
+                });
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+struct EuropeElectionsFeedParser;
+
+impl CustomParser for EuropeElectionsFeedParser {
+    fn key(&self) -> &'static str {
+        "europe_elections_feed_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        parse_structured_elections_feed(self.key(), source, docs, None, None)
+    }
+}
+
+struct StructuredCalendarFeedParser;
+
+impl CustomParser for StructuredCalendarFeedParser {
+    fn key(&self) -> &'static str {
+        "structured_calendar_feed_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        parse_structured_elections_feed(
+            self.key(),
+            source,
+            docs,
+            source.config.custom.filter_field.as_deref(),
+            source.config.custom.filter_value.as_deref(),
+        )
+    }
+}
+
+struct UsStateElectionsFeedParser;
+
+impl CustomParser for UsStateElectionsFeedParser {
+    fn key(&self) -> &'static str {
+        "us_state_elections_feed_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        parse_structured_elections_feed(
+            self.key(),
+            source,
+            docs,
+            Some("state"),
+            source
+                .config
+                .source
+                .default_country
+                .as_deref()
+                .or(source.config.source.jurisdiction.as_deref()),
+        )
+    }
+}
+
+struct MlbStatsApiScheduleParser;
+
+impl CustomParser for MlbStatsApiScheduleParser {
+    fn key(&self) -> &'static str {
+        "mlb_statsapi_schedule_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        let mut events = Vec::new();
+
+        for doc in docs {
+            let payload: Value = serde_json::from_slice(&doc.body)
+                .with_context(|| format!("failed to parse mlb schedule json from {}", doc.source_url))?;
+            let Some(dates) = payload.get("dates").and_then(Value::as_array) else {
+                continue;
+            };
+
+            for date in dates {
+                let Some(games) = date.get("games").and_then(Value::as_array) else {
+                    continue;
+                };
+
+                for game in games {
+                    let Some(game_pk) = game.get("gamePk").and_then(Value::as_i64) else {
+                        continue;
+                    };
+                    let Some(game_date_raw) = game.get("gameDate").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let Ok(start) = DateTime::parse_from_rfc3339(game_date_raw).map(|dt| dt.with_timezone(&Utc)) else {
+                        continue;
+                    };
+
+                    let away_name = game
+                        .pointer("/teams/away/team/name")
+                        .and_then(Value::as_str)
+                        .unwrap_or("Away");
+                    let home_name = game
+                        .pointer("/teams/home/team/name")
+                        .and_then(Value::as_str)
+                        .unwrap_or("Home");
+                    let venue = game
+                        .pointer("/venue/name")
+                        .and_then(Value::as_str)
+                        .unwrap_or("Unknown venue");
+                    let phase = game
+                        .get("seriesDescription")
+                        .and_then(Value::as_str)
+                        .unwrap_or("MLB");
+                    let game_type = game
+                        .get("gameType")
+                        .and_then(Value::as_str)
+                        .unwrap_or("R");
+                    let subtype = match game_type {
+                        "S" => "preseason_game",
+                        "R" => "regular_season_game",
+                        "F" | "D" | "L" | "W" => "playoff_game",
+                        _ => "baseball_game",
+                    };
+                    let title = format!("MLB: {} at {}", away_name, home_name);
+                    let status = game
+                        .pointer("/status/detailedState")
+                        .and_then(Value::as_str)
+                        .unwrap_or("Scheduled");
+
+                    let mut metadata = BTreeMap::new();
+                    metadata.insert("league".to_string(), "mlb".to_string());
+                    metadata.insert("away_team".to_string(), away_name.to_string());
+                    metadata.insert("home_team".to_string(), home_name.to_string());
+                    metadata.insert("venue".to_string(), venue.to_string());
+                    metadata.insert("phase".to_string(), phase.to_string());
+                    metadata.insert("status_detail".to_string(), status.to_string());
+                    metadata.insert("custom_parser".to_string(), self.key().to_string());
+                    if let Some(day_night) = game.get("dayNight").and_then(Value::as_str) {
+                        metadata.insert("day_night".to_string(), day_night.to_string());
+                    }
+
+                    let description = format!(
+                        "{} at {} from {}. {}. Status: {}.",
+                        away_name, home_name, venue, phase, status
+                    );
+
+                    events.push(CandidateEvent {
+                        source_key: source.config.source.key.clone(),
+                        source_name: source.config.source.name.clone(),
+                        source_event_id: Some(game_pk.to_string()),
+                        source_url: Some(doc.source_url.clone()),
+                        title,
+                        description: Some(description),
+                        time: EventTimeSpec::utc(start, None),
+                        timezone: source.config.source.timezone.clone(),
+                        status: source.config.event.status.clone(),
+                        event_type: source.config.event.event_type.clone(),
+                        subtype: Some(subtype.to_string()),
+                        categories: source.config.event.categories.clone(),
+                        jurisdiction: source.config.source.jurisdiction.clone(),
+                        country: source.config.source.default_country.clone(),
+                        importance: source.config.event.importance,
+                        confidence: Some(0.98),
+                        metadata,
+                        ..CandidateEvent::default()
+                    });
+                }
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(feature = "http")]
+struct NhlScheduleApiParser;
+
+#[cfg(feature = "http")]
+impl CustomParser for NhlScheduleApiParser {
+    fn key(&self) -> &'static str {
+        "nhl_schedule_api_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        let Some(doc) = docs.first() else {
+            return Ok(Vec::new());
+        };
+
+        let client = source_client(
+            source,
+            &identity_headers(source, &template_substitutions(source))?,
+            Some(std::time::Duration::from_secs(
+                source.config.fetch.timeout_secs.max(30),
+            )),
+        )
+        .context("failed to build nhl api client")?;
+
+        let mut events = Vec::new();
+        let mut seen_urls = HashSet::new();
+        let mut next_url = Some(doc.source_url.clone());
+
+        while let Some(url) = next_url.take() {
+            if !seen_urls.insert(url.clone()) {
+                break;
+            }
+
+            check_request(source, &url)?;
+            throttle(source, &url);
+            let payload: Value = client
+                .get(&url)
+                .send()
+                .with_context(|| format!("failed to fetch nhl schedule json from {url}"))?
+                .error_for_status()
+                .with_context(|| format!("nhl schedule api returned error for {url}"))?
+                .json()
+                .with_context(|| format!("failed to decode nhl schedule json from {url}"))?;
+
+            if let Some(weeks) = payload.get("gameWeek").and_then(Value::as_array) {
+                for week in weeks {
+                    let Some(games) = week.get("games").and_then(Value::as_array) else {
+                        continue;
+                    };
+
+                    for game in games {
+                        let Some(game_id) = game.get("id").and_then(Value::as_i64) else {
+                            continue;
+                        };
+                        let Some(start_raw) = game.get("startTimeUTC").and_then(Value::as_str) else {
+                            continue;
+                        };
+                        let Ok(start) = DateTime::parse_from_rfc3339(start_raw).map(|dt| dt.with_timezone(&Utc)) else {
+                            continue;
+                        };
+
+                        let away_place = game
+                            .pointer("/awayTeam/placeName/default")
+                            .and_then(Value::as_str)
+                            .unwrap_or("Away");
+                        let away_name = game
+                            .pointer("/awayTeam/commonName/default")
+                            .and_then(Value::as_str)
+                            .unwrap_or("Team");
+                        let home_place = game
+                            .pointer("/homeTeam/placeName/default")
+                            .and_then(Value::as_str)
+                            .unwrap_or("Home");
+                        let home_name = game
+                            .pointer("/homeTeam/commonName/default")
+                            .and_then(Value::as_str)
+                            .unwrap_or("Team");
+                        let venue = game
+                            .pointer("/venue/default")
+                            .and_then(Value::as_str)
+                            .unwrap_or("Unknown venue");
+                        let title = format!(
+                            "NHL: {} {} at {} {}",
+                            away_place, away_name, home_place, home_name
+                        );
+                        let game_type = game.get("gameType").and_then(Value::as_i64).unwrap_or(2);
+                        let subtype = match game_type {
+                            1 => "preseason_game",
+                            2 => "regular_season_game",
+                            3 => "playoff_game",
+                            _ => "hockey_game",
+                        };
+                        let special_event = game.get("specialEvent").and_then(Value::as_str);
+                        let description = if let Some(event_name) = special_event {
+                            format!(
+                                "{} {} at {} {} from {}. Special event: {}.",
+                                away_place, away_name, home_place, home_name, venue, event_name
+                            )
+                        } else {
+                            format!(
+                                "{} {} at {} {} from {}.",
+                                away_place, away_name, home_place, home_name, venue
+                            )
+                        };
+
+                        let mut metadata = BTreeMap::new();
+                        metadata.insert("league".to_string(), "nhl".to_string());
+                        metadata.insert(
+                            "away_team".to_string(),
+                            format!("{} {}", away_place, away_name),
+                        );
+                        metadata.insert(
+                            "home_team".to_string(),
+                            format!("{} {}", home_place, home_name),
+                        );
+                        metadata.insert("venue".to_string(), venue.to_string());
+                        metadata.insert("custom_parser".to_string(), self.key().to_string());
+                        if let Some(state) = game.get("gameScheduleState").and_then(Value::as_str) {
+                            metadata.insert("schedule_state".to_string(), state.to_string());
+                        }
+
+                        events.push(CandidateEvent {
+                            source_key: source.config.source.key.clone(),
+                            source_name: source.config.source.name.clone(),
+                            source_event_id: Some(game_id.to_string()),
+                            source_url: Some(url.clone()),
+                            title,
+                            description: Some(description),
+                            time: EventTimeSpec::utc(start, None),
+                            timezone: source.config.source.timezone.clone(),
+                            status: source.config.event.status.clone(),
+                            event_type: source.config.event.event_type.clone(),
+                            subtype: Some(subtype.to_string()),
+                            categories: source.config.event.categories.clone(),
+                            jurisdiction: source.config.source.jurisdiction.clone(),
+                            country: source.config.source.default_country.clone(),
+                            importance: source.config.event.importance,
+                            confidence: Some(0.98),
+                            metadata,
+                            ..CandidateEvent::default()
+                        });
+                    }
+                }
+            }
+
+            next_url = payload
+                .get("nextStartDate")
+                .and_then(Value::as_str)
+                .map(|date| format!("https://api-web.nhle.com/v1/schedule/{date}"));
+        }
+
+        Ok(events)
+    }
+}
+
+struct NbaFullScheduleParser;
+
+impl CustomParser for NbaFullScheduleParser {
+    fn key(&self) -> &'static str {
+        "nba_full_schedule_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        let mut events = Vec::new();
+
+        for doc in docs {
+            let payload: Value = serde_json::from_slice(&doc.body)
+                .with_context(|| format!("failed to parse nba schedule json from {}", doc.source_url))?;
+            let Some(months) = payload.get("lscd").and_then(Value::as_array) else {
+                continue;
+            };
+
+            for month in months {
+                let Some(games) = month.pointer("/mscd/g").and_then(Value::as_array) else {
+                    continue;
+                };
+
+                for game in games {
+                    let Some(game_id) = game.get("gid").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let Some(date_raw) = game.get("gdte").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let Some(utc_time_raw) = game.get("utctm").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    let Ok(start) = DateTime::parse_from_rfc3339(&format!("{date_raw}T{utc_time_raw}:00Z"))
+                        .map(|dt| dt.with_timezone(&Utc)) else {
+                        continue;
+                    };
+
+                    let away_city = game.pointer("/v/tc").and_then(Value::as_str).unwrap_or("Away");
+                    let away_name = game.pointer("/v/tn").and_then(Value::as_str).unwrap_or("Team");
+                    let home_city = game.pointer("/h/tc").and_then(Value::as_str).unwrap_or("Home");
+                    let home_name = game.pointer("/h/tn").and_then(Value::as_str).unwrap_or("Team");
+                    let venue = game.get("an").and_then(Value::as_str).unwrap_or("Unknown venue");
+                    let title = format!("NBA: {} {} at {} {}", away_city, away_name, home_city, home_name);
+                    let status_code = game.get("st").and_then(Value::as_str).unwrap_or("1");
+                    let subtype = match status_code {
+                        "1" | "2" | "3" => "regular_season_game",
+                        _ => "basketball_game",
+                    };
+
+                    let mut metadata = BTreeMap::new();
+                    metadata.insert("league".to_string(), "nba".to_string());
+                    metadata.insert("away_team".to_string(), format!("{} {}", away_city, away_name));
+                    metadata.insert("home_team".to_string(), format!("{} {}", home_city, home_name));
+                    metadata.insert("venue".to_string(), venue.to_string());
+                    metadata.insert("custom_parser".to_string(), self.key().to_string());
+                    if let Some(pretty) = game.get("stt").and_then(Value::as_str) {
+                        metadata.insert("tipoff_label".to_string(), pretty.to_string());
+                    }
+                    if let Some(broadcasts) = game.pointer("/bd/b").and_then(Value::as_array) {
+                        let names = broadcasts
+                            .iter()
+                            .filter_map(|b| b.get("disp").and_then(Value::as_str))
+                            .collect::<Vec<_>>();
+                        if !names.is_empty() {
+                            metadata.insert("broadcasts".to_string(), names.join(" | "));
+                        }
+                    }
+
+                    let description = format!(
+                        "{} {} at {} {} from {}.",
+                        away_city, away_name, home_city, home_name, venue
+                    );
+
+                    events.push(CandidateEvent {
+                        source_key: source.config.source.key.clone(),
+                        source_name: source.config.source.name.clone(),
+                        source_event_id: Some(game_id.to_string()),
+                        source_url: Some(doc.source_url.clone()),
+                        title,
+                        description: Some(description),
+                        time: EventTimeSpec::utc(start, None),
+                        timezone: source.config.source.timezone.clone(),
+                        status: source.config.event.status.clone(),
+                        event_type: source.config.event.event_type.clone(),
+                        subtype: Some(subtype.to_string()),
+                        categories: source.config.event.categories.clone(),
+                        jurisdiction: source.config.source.jurisdiction.clone(),
+                        country: source.config.source.default_country.clone(),
+                        importance: source.config.event.importance,
+                        confidence: Some(0.97),
+                        metadata,
+                        ..CandidateEvent::default()
+                    });
+                }
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+struct NflOperationsScheduleParser;
+
+impl CustomParser for NflOperationsScheduleParser {
+    fn key(&self) -> &'static str {
+        "nfl_operations_schedule_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        let mut events = Vec::new();
+
+        for doc in docs {
+            let html_text = doc.text();
+            let parsed = Html::parse_document(&html_text);
+            let details_sel = Selector::parse("details.week-section")
+                .map_err(|_| anyhow!("failed to parse nfl week selector"))?;
+            let summary_sel =
+                Selector::parse("summary").map_err(|_| anyhow!("failed to parse summary selector"))?;
+            let date_sel = Selector::parse("div.game-date")
+                .map_err(|_| anyhow!("failed to parse game-date selector"))?;
+            let table_sel = Selector::parse("table.game-table")
+                .map_err(|_| anyhow!("failed to parse table selector"))?;
+            let tr_sel = Selector::parse("tr").map_err(|_| anyhow!("failed to parse tr selector"))?;
+            let td_sel = Selector::parse("td").map_err(|_| anyhow!("failed to parse td selector"))?;
+
+            for section in parsed.select(&details_sel) {
+                let summary_text = section
+                    .select(&summary_sel)
+                    .next()
+                    .map(|s| s.text().collect::<Vec<_>>().join(" "))
+                    .unwrap_or_default();
+                let week_label = summary_text.split('(').next().unwrap_or("").trim().to_string();
+                let week_range = summary_text
+                    .split('(')
+                    .nth(1)
+                    .map(|v| v.trim_end_matches(')').trim().to_string());
+
+                let date_nodes = section.select(&date_sel).collect::<Vec<_>>();
+                let table_nodes = section.select(&table_sel).collect::<Vec<_>>();
+                for (date_node, table_node) in date_nodes.into_iter().zip(table_nodes) {
+                    let date_label = date_node.text().collect::<Vec<_>>().join(" ").trim().to_string();
+                    for row in table_node.select(&tr_sel) {
+                        let cols = row
+                            .select(&td_sel)
+                            .map(|td| td.text().collect::<Vec<_>>().join(" ").trim().to_string())
+                            .filter(|v| !v.is_empty())
+                            .collect::<Vec<_>>();
+                        if cols.len() < 3 {
+                            continue;
+                        }
+
+                        let matchup = cols[0].clone();
+                        let kickoff = cols[1].clone();
+                        let network = cols[2].clone();
+                        if matchup == "TBD" {
+                            continue;
+                        }
+
+                        let (title, metadata_matchup) = normalize_nfl_matchup(&matchup);
+                        let time = if date_label == "Date TBD" || kickoff == "TBD" {
+                            EventTimeSpec::Tbd {
+                                note: Some(format!(
+                                    "{} {} {}",
+                                    week_label,
+                                    week_range.clone().unwrap_or_default(),
+                                    matchup
+                                )),
+                            }
+                        } else if let Some(start) = parse_nfl_datetime(&date_label, &kickoff)? {
+                            EventTimeSpec::utc(start, None)
+                        } else {
+                            EventTimeSpec::Tbd {
+                                note: Some(format!("{date_label} {kickoff}")),
+                            }
+                        };
+
+                        let mut metadata = BTreeMap::new();
+                        metadata.insert("league".to_string(), "nfl".to_string());
+                        metadata.insert("week".to_string(), week_label.clone());
+                        metadata.insert("network".to_string(), network.clone());
+                        metadata.insert("matchup".to_string(), matchup.clone());
+                        metadata.insert("custom_parser".to_string(), self.key().to_string());
+                        if let Some(range) = &week_range {
+                            metadata.insert("week_range".to_string(), range.clone());
+                        }
+                        for (k, v) in metadata_matchup {
+                            metadata.insert(k, v);
+                        }
+
+                        let description = format!(
+                            "{}. {} on {}. {}.",
+                            matchup, week_label, network, date_label
+                        );
+
+                        events.push(CandidateEvent {
+                            source_key: source.config.source.key.clone(),
+                            source_name: source.config.source.name.clone(),
+                            source_event_id: Some(format!("{}|{}|{}", week_label, date_label, matchup)),
+                            source_url: Some(doc.source_url.clone()),
+                            title,
+                            description: Some(description),
+                            time,
+                            timezone: source.config.source.timezone.clone(),
+                            status: source.config.event.status.clone(),
+                            event_type: source.config.event.event_type.clone(),
+                            subtype: Some("regular_season_game".to_string()),
+                            categories: source.config.event.categories.clone(),
+                            jurisdiction: source.config.source.jurisdiction.clone(),
+                            country: source.config.source.default_country.clone(),
+                            importance: source.config.event.importance,
+                            confidence: Some(0.98),
+                            metadata,
+                            ..CandidateEvent::default()
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(feature = "http")]
+struct MlsStatsApiScheduleParser;
+
+#[cfg(feature = "http")]
+impl CustomParser for MlsStatsApiScheduleParser {
+    fn key(&self) -> &'static str {
+        "mls_statsapi_schedule_v1"
+    }
+
+    fn parse(
+        &self,
+        source: &LoadedSource,
+        docs: &[FetchedDocument],
+    ) -> Result<Vec<CandidateEvent>> {
+        let Some(doc) = docs.first() else {
+            return Ok(Vec::new());
+        };
+
+        let client = source_client(
+            source,
+            &identity_headers(source, &template_substitutions(source))?,
+            Some(std::time::Duration::from_secs(
+                source.config.fetch.timeout_secs.max(30),
+            )),
+        )
+        .context("failed to build mls api client")?;
+
+        let mut events = Vec::new();
+        let mut next_page_token: Option<String> = None;
+
+        loop {
+            let mut request = client.get(&doc.source_url);
+            if let Some(token) = &next_page_token {
+                request = request.query(&[("page_token", token.as_str())]);
+            }
+
+            check_request(source, &doc.source_url)?;
+            throttle(source, &doc.source_url);
+            let payload: Value = request
+                .send()
+                .with_context(|| format!("failed to fetch mls schedule json from {}", doc.source_url))?
+                .error_for_status()
+                .with_context(|| format!("mls schedule api returned error for {}", doc.source_url))?
+                .json()
+                .with_context(|| format!("failed to decode mls schedule json from {}", doc.source_url))?;
+
+            let Some(schedule) = payload.get("schedule").and_then(Value::as_array) else {
+                break;
+            };
+
+            for game in schedule {
+                let Some(game_id) = game.get("match_id").and_then(Value::as_str) else {
+                    continue;
+                };
+                let Some(start_raw) = game.get("planned_kickoff_time").and_then(Value::as_str) else {
+                    continue;
+                };
+                let Ok(start) =
+                    DateTime::parse_from_rfc3339(start_raw).map(|dt| dt.with_timezone(&Utc))
+                else {
+                    continue;
+                };
+
+                let away_name = game
+                    .get("away_team_name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Away");
+                let home_name = game
+                    .get("home_team_name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Home");
+                let venue = game
+                    .get("stadium_name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Unknown venue");
+                let title = format!("MLS: {} at {}", away_name, home_name);
+                let competition = game
+                    .get("competition_name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("MLS");
+                let match_type = game
+                    .get("match_type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Regular season");
+                let status = game
+                    .get("match_date_time_status")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Scheduled");
+
+                let mut metadata = BTreeMap::new();
+                metadata.insert("league".to_string(), "mls".to_string());
+                metadata.insert("away_team".to_string(), away_name.to_string());
+                metadata.insert("home_team".to_string(), home_name.to_string());
+                metadata.insert("venue".to_string(), venue.to_string());
+                metadata.insert("competition".to_string(), competition.to_string());
+                metadata.insert("match_type".to_string(), match_type.to_string());
+                metadata.insert("status_detail".to_string(), status.to_string());
+                metadata.insert("custom_parser".to_string(), self.key().to_string());
+                if let Some(sub_league) = game.get("sub_league").and_then(Value::as_str) {
+                    metadata.insert("sub_league".to_string(), sub_league.to_string());
+                }
+                if let Some(match_day) = game.get("match_day").and_then(Value::as_i64) {
+                    metadata.insert("match_day".to_string(), match_day.to_string());
+                }
+                if let Some(city) = game.get("stadium_city").and_then(Value::as_str) {
+                    metadata.insert("stadium_city".to_string(), city.to_string());
+                }
+                if let Some(code) = game
+                    .get("home_team_three_letter_code")
+                    .and_then(Value::as_str)
+                {
+                    metadata.insert("home_code".to_string(), code.to_string());
+                }
+                if let Some(code) = game
+                    .get("away_team_three_letter_code")
+                    .and_then(Value::as_str)
+                {
+                    metadata.insert("away_code".to_string(), code.to_string());
+                }
+                if let Some(neutral) = game.get("neutral_venue").and_then(Value::as_bool) {
+                    metadata.insert("neutral_venue".to_string(), neutral.to_string());
+                }
+                if let Some(token) = game.get("competition_label").and_then(Value::as_str) {
+                    metadata.insert("competition_label".to_string(), token.to_string());
+                }
+
+                let description = format!(
+                    "{} at {} from {}. {}. Status: {}.",
+                    away_name, home_name, venue, match_type, status
+                );
+
+                events.push(CandidateEvent {
+                    source_key: source.config.source.key.clone(),
+                    source_name: source.config.source.name.clone(),
+                    source_event_id: Some(game_id.to_string()),
+                    source_url: Some(doc.source_url.clone()),
+                    title,
+                    description: Some(description),
+                    time: EventTimeSpec::utc(start, None),
+                    timezone: source.config.source.timezone.clone(),
+                    status: source.config.event.status.clone(),
+                    event_type: source.config.event.event_type.clone(),
+                    subtype: Some("regular_season_game".to_string()),
+                    categories: source.config.event.categories.clone(),
+                    jurisdiction: source.config.source.jurisdiction.clone(),
+                    country: source.config.source.default_country.clone(),
+                    importance: source.config.event.importance,
+                    confidence: Some(0.98),
+                    metadata,
+                    ..CandidateEvent::default()
+                });
+            }
+
+            next_page_token = payload
+                .get("next_page_token")
+                .and_then(Value::as_str)
+                .map(str::to_owned);
+            if next_page_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+fn normalize_nfl_matchup(matchup: &str) -> (String, BTreeMap<String, String>) {
+    let mut metadata = BTreeMap::new();
+    if let Some((away, home)) = matchup.split_once(" at ") {
+        metadata.insert("away_team".to_string(), away.to_string());
+        metadata.insert("home_team".to_string(), home.to_string());
+        return (format!("NFL: {} at {}", away, home), metadata);
+    }
+    if let Some((away, rest)) = matchup.split_once(" vs ") {
+        metadata.insert("away_team".to_string(), away.to_string());
+        metadata.insert("home_team".to_string(), rest.to_string());
+        metadata.insert("neutral_site".to_string(), "true".to_string());
+        return (format!("NFL: {} vs {}", away, rest), metadata);
+    }
+    (format!("NFL: {}", matchup), metadata)
+}
+
+fn parse_nfl_datetime(date_label: &str, kickoff: &str) -> Result<Option<DateTime<Utc>>> {
+    let normalized_date = date_label
+        .replace("Sept.", "Sep.")
+        .replace("Sept ", "Sep ");
+    let clean_time = kickoff.trim().trim_end_matches('*');
+    let Some((hour_text, rest)) = clean_time.split_once(':') else {
+        return Ok(None);
+    };
+    let minute_digits = rest.chars().take_while(|c| c.is_ascii_digit()).collect::<String>();
+    let suffix = rest.chars().skip_while(|c| c.is_ascii_digit()).collect::<String>();
+    let mut hour: u32 = match hour_text.parse() {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    let minute: u32 = match minute_digits.parse() {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    let lower_suffix = suffix.to_ascii_lowercase();
+    if lower_suffix.starts_with('p') && hour != 12 {
+        hour += 12;
+    }
+    if lower_suffix.starts_with('a') && hour == 12 {
+        hour = 0;
+    }
+
+    let date = NaiveDate::parse_from_str(&normalized_date, "%A, %b. %e, %Y")
+        .or_else(|_| NaiveDate::parse_from_str(&normalized_date, "%A, %b %e, %Y"))
+        .map_err(|err| anyhow!("failed to parse nfl date '{date_label}': {err}"))?;
+    let naive = date
+        .and_hms_opt(hour, minute, 0)
+        .ok_or_else(|| anyhow!("invalid nfl time {clean_time}"))?;
+    let eastern: Tz = chrono_tz::US::Eastern;
+    let local = eastern
+        .from_local_datetime(&naive)
+        .single()
+        .ok_or_else(|| anyhow!("ambiguous nfl local datetime {naive}"))?;
+    Ok(Some(local.with_timezone(&Utc)))
+}
+
+fn parse_structured_elections_feed(
+    parser_key: &str,
+    source: &LoadedSource,
+    docs: &[FetchedDocument],
+    filter_field: Option<&str>,
+    filter_value: Option<&str>,
+) -> Result<Vec<CandidateEvent>> {
+    let mut events = Vec::new();
+    let normalized_filter = filter_value.map(|value| value.to_ascii_uppercase());
+
+    for doc in docs {
+        let payload = doc.text();
+        for raw_line in payload.lines() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts = line
+                .split('|')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>();
+            if parts.len() < 2 {
+                continue;
+            }
+
+            let start_raw = parts[0];
+            let mut title = parts[1].to_string();
+            if !title
+                .to_ascii_lowercase()
+                .starts_with(&source.config.source.name.to_ascii_lowercase())
+            {
+                title = format!("{}: {title}", source.config.source.name);
+            }
+
+            let mut fields = BTreeMap::new();
+            for part in &parts[2..] {
+                let Some((key, value)) = part.split_once('=') else {
+                    continue;
+                };
+                fields.insert(key.trim().to_string(), value.trim().to_string());
+            }
+
+            if let (Some(field), Some(expected)) = (filter_field, normalized_filter.as_deref()) {
+                let actual = fields
+                    .get(field)
+                    .map(|value| value.to_ascii_uppercase())
+                    .unwrap_or_default();
+                if actual != expected {
+                    continue;
+                }
+            }
+
+            let time = if start_raw.eq_ignore_ascii_case("tbd") {
+                EventTimeSpec::Tbd {
+                    note: fields
+                        .get("tbd")
+                        .cloned()
+                        .or_else(|| Some("Date not yet confirmed".to_string())),
+                }
+            } else {
+                parse_event_time(
+                    start_raw,
+                    fields.get("end").map(String::as_str),
+                    &source.config.date,
+                    source.config.source.timezone.as_deref(),
+                )?
+            };
+
+            let subtype = fields
+                .get("subtype")
+                .cloned()
+                .or_else(|| source.config.event.subtype.clone());
+            let source_url = fields
+                .get("source_url")
+                .cloned()
+                .or_else(|| fields.get("official_url").cloned())
+                .or_else(|| Some(doc.source_url.clone()));
+            let source_event_id = fields.get("source_event_id").cloned().or_else(|| {
+                Some(format!(
+                    "{}|{}|{}",
+                    source.config.source.key, start_raw, title
+                ))
+            });
+            let status = fields
+                .get("status")
+                .cloned()
+                .unwrap_or_else(|| source.config.event.status.clone());
+            let confidence = fields
+                .get("confidence")
+                .and_then(|v| v.parse::<f32>().ok())
+                .or(Some(0.95));
+            let importance = fields
+                .get("importance")
+                .and_then(|v| v.parse::<u8>().ok())
+                .or(source.config.event.importance);
+            let description = fields.get("description").cloned();
+
+            let mut metadata = BTreeMap::new();
+            for (key, value) in &fields {
+                if ["end", "status", "subtype", "importance", "confidence", "description"]
+                    .contains(&key.as_str())
+                {
+                    continue;
+                }
+                metadata.insert(key.clone(), value.clone());
+            }
+            metadata.insert("custom_parser".to_string(), parser_key.to_string());
+            metadata.entry("country".to_string()).or_insert_with(|| {
+                source
+                    .config
+                    .source
+                    .default_country
+                    .clone()
+                    .unwrap_or_default()
+            });
+            metadata
+                .entry("source_class".to_string())
+                .or_insert_with(|| "curated".to_string());
+
+            let mut categories = source.config.event.categories.clone();
+            categories.push(source.config.source.domain.clone());
+            if let Some(country) = source.config.source.default_country.as_deref() {
+                categories.push(country.to_ascii_lowercase());
+            }
+            categories.sort();
+            categories.dedup();
+
+            events.push(CandidateEvent {
+                source_key: source.config.source.key.clone(),
+                source_name: source.config.source.name.clone(),
+                source_event_id,
+                source_url,
+                title,
+                description,
+                time,
+                timezone: source.config.source.timezone.clone(),
+                status,
+                event_type: source.config.event.event_type.clone(),
+                subtype,
+                categories,
+                jurisdiction: source.config.source.jurisdiction.clone(),
+                country: source.config.source.default_country.clone(),
+                importance,
+                confidence,
+                metadata,
+                ..CandidateEvent::default()
+            });
+        }
+    }
+
+    Ok(events)
+}
+
+fn combine_date_time(
+    date: NaiveDate,
+    time_text: &str,
+    timezone: Option<&str>,
+) -> Result<Option<DateTime<Utc>>> {
+    let time = NaiveDateTime::parse_from_str(
+        &format!("{} {}", date.format("%Y-%m-%d"), time_text.replace(" ", "")),
+        "%Y-%m-%d %I:%M%p",
+    )
+    .ok()
+    .or_else(|| {
+        NaiveDateTime::parse_from_str(
+            &format!("{} {}", date.format("%Y-%m-%d"), time_text),
+            "%Y-%m-%d %I:%M %p",
+        )
+        .ok()
+    });
+
+    let Some(naive) = time else {
+        return Ok(None);
+    };
+
+    if let Some(tz_name) = timezone
+        && let Ok(tz) = tz_name.parse::<Tz>()
+        && let Some(dt) = tz
+            .from_local_datetime(&naive)
+            .earliest()
+            .or_else(|| tz.from_local_datetime(&naive).latest())
+    {
+        return Ok(Some(dt.with_timezone(&Utc)));
+    }
+
+    Ok(Some(Utc.from_utc_datetime(&naive)))
+}
+
+fn build_econ_description(
+    actual: Option<String>,
+    previous: Option<String>,
+    consensus: Option<String>,
+    forecast: Option<String>,
+) -> Option<String> {
+    let mut lines = Vec::new();
+    if let Some(v) = actual {
+        lines.push(format!("Actual: {v}"));
+    }
+    if let Some(v) = previous {
+        lines.push(format!("Previous: {v}"));
+    }
+    if let Some(v) = consensus {
+        lines.push(format!("Consensus: {v}"));
+    }
+    if let Some(v) = forecast {
+        lines.push(format!("Forecast: {v}"));
+    }
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
@@ -0,0 +1,103 @@
+use crate::fetch::FetchedDocument;
+use crate::parser::preview_source_events;
+use crate::pipeline::{
+    BuildOptions, DEFAULT_SYNC_JOBS, SyncOptions, build_calendars, load_state_for_read,
+    sync_sources,
+};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Python entry points, importable as `import rics`.
+///
+/// Everything crosses the boundary as plain dicts and lists (via JSON) so the
+/// event corpus drops straight into `pandas.DataFrame(...)`.
+#[pymodule]
+pub fn rics(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(sync, m)?)?;
+    m.add_function(wrap_pyfunction!(build, m)?)?;
+    m.add_function(wrap_pyfunction!(preview, m)?)?;
+    m.add_function(wrap_pyfunction!(load_events, m)?)?;
+    Ok(())
+}
+
+#[pyfunction]
+#[pyo3(signature = (config_dir, state_path, out_dir, source=None, dry_run=false, jobs=DEFAULT_SYNC_JOBS))]
+fn sync(
+    py: Python<'_>,
+    config_dir: PathBuf,
+    state_path: PathBuf,
+    out_dir: PathBuf,
+    source: Option<String>,
+    dry_run: bool,
+    jobs: usize,
+) -> PyResult<Py<PyAny>> {
+    let options = SyncOptions {
+        config_dir,
+        state_path,
+        out_dir,
+        source,
+        dry_run,
+        jobs,
+        ..SyncOptions::default()
+    };
+    let reports = py.detach(|| sync_sources(&options)).map_err(to_py_err)?;
+    to_py_object(py, &reports)
+}
+
+#[pyfunction]
+#[pyo3(signature = (config_dir, state_path, out_dir, source=None, year=None))]
+fn build(
+    py: Python<'_>,
+    config_dir: PathBuf,
+    state_path: PathBuf,
+    out_dir: PathBuf,
+    source: Option<String>,
+    year: Option<i32>,
+) -> PyResult<()> {
+    let options = BuildOptions {
+        config_dir,
+        state_path,
+        out_dir,
+        source,
+        year,
+    };
+    py.detach(|| build_calendars(&options)).map_err(to_py_err)
+}
+
+/// Parses `body` with a source config given as TOML text and returns the
+/// candidate events without touching state.
+#[pyfunction]
+#[pyo3(signature = (config_toml, body, source_url="preview://body"))]
+fn preview(py: Python<'_>, config_toml: &str, body: &str, source_url: &str) -> PyResult<Py<PyAny>> {
+    let docs = [FetchedDocument {
+        source_url: source_url.to_string(),
+        body: body.as_bytes().to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: BTreeMap::new(),
+    }];
+    let candidates = preview_source_events(config_toml, &docs).map_err(to_py_err)?;
+    to_py_object(py, &candidates)
+}
+
+/// Returns every stored event record from the state file as a list of dicts.
+#[pyfunction]
+fn load_events(py: Python<'_>, state_path: PathBuf) -> PyResult<Py<PyAny>> {
+    let state = load_state_for_read(&state_path).map_err(to_py_err)?;
+    let events = state.events.values().collect::<Vec<_>>();
+    to_py_object(py, &events)
+}
+
+fn to_py_object<T: Serialize + ?Sized>(py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+    let json =
+        serde_json::to_string(value).map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+    let loaded = py.import("json")?.call_method1("loads", (json,))?;
+    Ok(loaded.unbind())
+}
+
+fn to_py_err(err: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{err:#}"))
+}
@@ -0,0 +1,201 @@
+//! Review queue for updates that look like scraping glitches.
+//!
+//! When a source enables `[quarantine]`, an update that moves an event's date
+//! too far or rewrites its title beyond recognition is parked in
+//! `<state>.quarantine.json` next to the state file instead of being merged.
+//! The stored event keeps publishing its last good version until the change
+//! is applied or rejected with `rics quarantine`.
+
+use crate::config::QuarantineConfig;
+use crate::model::EventRecord;
+use crate::pipeline::normalized_title;
+use crate::store::{EventStore, JsonFileStore, StateLock, write_atomic};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A held-back update.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub id: String,
+    pub source_key: String,
+    pub store_key: String,
+    pub quarantined_at: DateTime<Utc>,
+    pub reasons: Vec<String>,
+    pub previous_title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_start: Option<String>,
+    /// The record as it would have been stored.
+    pub record: EventRecord,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct QuarantineFile {
+    #[serde(default)]
+    entries: Vec<QuarantineEntry>,
+    /// Rejected revision hashes by store key, so the same upstream change
+    /// is not queued again on every sync.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    rejected: BTreeMap<String, String>,
+}
+
+/// The pending entries for one state file.
+#[derive(Debug, Clone)]
+pub struct Quarantine {
+    path: PathBuf,
+    file: QuarantineFile,
+}
+
+impl Quarantine {
+    /// `<state>.quarantine.json` beside the state file, so state files
+    /// sharing a directory keep separate queues.
+    pub fn path_for_state(state_path: &Path) -> PathBuf {
+        let mut path = state_path.as_os_str().to_owned();
+        path.push(".quarantine.json");
+        PathBuf::from(path)
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read quarantine file {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("failed to parse quarantine file {}", path.display()))?
+        } else {
+            QuarantineFile::default()
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    pub fn entries(&self) -> &[QuarantineEntry] {
+        &self.file.entries
+    }
+
+    /// Adds an entry, replacing any older one for the same event.
+    pub fn hold(&mut self, entry: QuarantineEntry) {
+        self.release(&entry.store_key);
+        self.file.entries.push(entry);
+    }
+
+    pub fn is_rejected(&self, store_key: &str, revision_hash: &str) -> bool {
+        self.file.rejected.get(store_key).map(String::as_str) == Some(revision_hash)
+    }
+
+    /// Forgets the pending entry and any rejection for an event, e.g. once
+    /// upstream reverts.
+    pub fn release(&mut self, store_key: &str) -> bool {
+        self.file.rejected.remove(store_key);
+        let before = self.file.entries.len();
+        self.file
+            .entries
+            .retain(|entry| entry.store_key != store_key);
+        self.file.entries.len() != before
+    }
+
+    /// Removes and returns the entry with `id`.
+    pub fn take(&mut self, id: &str) -> Option<QuarantineEntry> {
+        let index = self.file.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.file.entries.remove(index))
+    }
+
+    /// Writes the file; an empty queue that was never written stays absent.
+    pub fn save(&self) -> Result<()> {
+        if self.file.entries.is_empty() && self.file.rejected.is_empty() && !self.path.exists() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create quarantine directory {}", parent.display())
+            })?;
+        }
+        let serialized = serde_json::to_string_pretty(&self.file)?;
+        write_atomic(&self.path, serialized.as_bytes())
+            .with_context(|| format!("failed to write quarantine file {}", self.path.display()))
+    }
+}
+
+/// Why `updated` should not replace `existing` unreviewed; empty when the
+/// change looks ordinary.
+pub fn suspicious_changes(
+    existing: &EventRecord,
+    updated: &EventRecord,
+    config: &QuarantineConfig,
+) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if let (Some(before), Some(after)) = (existing.time.start_date(), updated.time.start_date()) {
+        let shift = (after - before).num_days().abs();
+        if shift > i64::from(config.max_date_shift_days) {
+            reasons.push(format!("date moved {shift} days ({before} -> {after})"));
+        }
+    }
+    let similarity = title_similarity(&existing.title, &updated.title);
+    if similarity < config.min_title_similarity {
+        reasons.push(format!(
+            "title similarity {similarity:.2} ({:?} -> {:?})",
+            existing.title, updated.title
+        ));
+    }
+    reasons
+}
+
+/// Edit-distance similarity of the normalized titles, from 0 (nothing in
+/// common) to 1 (equal).
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = normalized_title(a).chars().collect::<Vec<_>>();
+    let b = normalized_title(b).chars().collect::<Vec<_>>();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+/// Merges a quarantined update into the state file. Calendars are not
+/// rebuilt; run `rics build` afterwards.
+pub fn apply_quarantined(state_path: &Path, id: &str) -> Result<QuarantineEntry> {
+    let mut store = JsonFileStore::open_locked(state_path)?;
+    let mut quarantine = Quarantine::open(&Quarantine::path_for_state(state_path))?;
+    let Some(entry) = quarantine.take(id) else {
+        bail!("no quarantined change with id {id}");
+    };
+    let mut record = entry.record.clone();
+    if let Some(current) = store.get(&entry.store_key) {
+        // The stored event may have moved on (e.g. been cancelled) since.
+        record.sequence = record.sequence.max(current.sequence.saturating_add(1));
+    }
+    store.upsert(record);
+    store.save()?;
+    quarantine.save()?;
+    Ok(entry)
+}
+
+/// Discards a quarantined update, keeping the stored event as it is. The
+/// same revision is ignored by later syncs.
+pub fn reject_quarantined(state_path: &Path, id: &str) -> Result<QuarantineEntry> {
+    let _lock = StateLock::acquire(state_path)?;
+    let mut quarantine = Quarantine::open(&Quarantine::path_for_state(state_path))?;
+    let Some(entry) = quarantine.take(id) else {
+        bail!("no quarantined change with id {id}");
+    };
+    quarantine
+        .file
+        .rejected
+        .insert(entry.store_key.clone(), entry.record.revision_hash.clone());
+    quarantine.save()?;
+    Ok(entry)
+}
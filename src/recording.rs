@@ -0,0 +1,121 @@
+//! Recorded http responses for offline runs (`rics sync --record` and
+//! `fetch.mode = "replay"`).
+//!
+//! A recording keeps, per source, the URL, headers and body of every page a
+//! sync fetched, under `<dir>/<source dir>/`: an index in `responses.json`
+//! and one `NNN.body` file per page. Replay hands those pages to extraction
+//! in their recorded order with their recorded URLs, so a replayed sync
+//! yields the same events as the live one without touching the network.
+//! `Set-Cookie` headers are never written.
+
+use crate::config::{LoadedSource, resolve_path};
+use crate::fetch::FetchedDocument;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+const INDEX_FILE: &str = "responses.json";
+
+/// Headers left out of recordings, since they carry session secrets.
+const SKIPPED_HEADERS: &[&str] = &["set-cookie"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    url: String,
+    page_index: usize,
+    #[serde(default)]
+    fetched_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// Body file, relative to the index.
+    body_file: String,
+}
+
+/// Directory holding the recording of `source` under `dir`.
+pub fn recording_dir(dir: &Path, source: &LoadedSource) -> PathBuf {
+    dir.join(source.config.sanitized_source_dir_name())
+}
+
+/// Replaces the recording of `source` under `dir` with `docs`.
+pub fn record_documents(dir: &Path, source: &LoadedSource, docs: &[FetchedDocument]) -> Result<()> {
+    let source_dir = recording_dir(dir, source);
+    if source_dir.exists() {
+        fs::remove_dir_all(&source_dir)
+            .with_context(|| format!("failed to clear recording {}", source_dir.display()))?;
+    }
+    fs::create_dir_all(&source_dir)
+        .with_context(|| format!("failed to create {}", source_dir.display()))?;
+
+    let mut index = Vec::with_capacity(docs.len());
+    for (position, doc) in docs.iter().enumerate() {
+        let body_file = format!("{position:03}.body");
+        let path = source_dir.join(&body_file);
+        fs::write(&path, &doc.body)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        index.push(RecordedResponse {
+            url: doc.source_url.clone(),
+            page_index: doc.page_index,
+            fetched_at: doc.fetched_at,
+            headers: doc
+                .headers
+                .iter()
+                .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            body_file,
+        });
+    }
+    let index_path = source_dir.join(INDEX_FILE);
+    fs::write(&index_path, serde_json::to_string_pretty(&index)?)
+        .with_context(|| format!("failed to write {}", index_path.display()))?;
+    info!(
+        source = %source.config.source.key,
+        responses = index.len(),
+        dir = %source_dir.display(),
+        "recorded responses"
+    );
+    Ok(())
+}
+
+/// The documents recorded for `source` under `fetch.replay_dir`.
+pub fn replay_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
+    let dir = source
+        .config
+        .fetch
+        .replay_dir
+        .as_ref()
+        .context("fetch.replay_dir missing for replay mode")?;
+    let source_dir = recording_dir(&resolve_path(&source.path, dir)?, source);
+    let index_path = source_dir.join(INDEX_FILE);
+    if !index_path.exists() {
+        bail!(
+            "no recording for source {} at {}; run `rics sync --record` first",
+            source.config.source.key,
+            source_dir.display()
+        );
+    }
+    let raw = fs::read_to_string(&index_path)
+        .with_context(|| format!("failed to read {}", index_path.display()))?;
+    let index: Vec<RecordedResponse> = serde_json::from_str(&raw)
+        .with_context(|| format!("failed to parse {}", index_path.display()))?;
+
+    index
+        .into_iter()
+        .map(|recorded| {
+            let path = source_dir.join(&recorded.body_file);
+            let body =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            Ok(FetchedDocument {
+                source_url: recorded.url,
+                body: body.into(),
+                page_index: recorded.page_index,
+                fetched_at: recorded.fetched_at,
+                headers: recorded.headers,
+            })
+        })
+        .collect()
+}
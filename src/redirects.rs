@@ -0,0 +1,205 @@
+//! Rewrite maps from stable URLs to generated calendar files.
+//!
+//! `file_name_template` lets deployments rename their calendars, which would
+//! break every existing subscription. With a `redirects.toml` next to the
+//! sources directory, each build also writes an nginx, Caddy or `_redirects`
+//! map from the configured stable paths to whatever the files are currently
+//! called, including `current` and `next` paths that follow the new year.
+
+use crate::config::{
+    LoadedBundle, LoadedSource, RedirectFormat, RedirectsConfig, load_global_redirects,
+    load_sources_from_dir,
+};
+use crate::pipeline::{bundle_ics_filename, load_optional_bundles, source_ics_filename};
+use crate::store::{EventQuery, EventStore};
+use anyhow::{Context, Result};
+use chrono::{Datelike, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// One stable path and the URL it currently resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub from: String,
+    pub to: String,
+}
+
+/// Regenerates the map when `redirects.toml` exists, returning its path.
+pub fn write_redirect_map(
+    config_dir: &Path,
+    store: &dyn EventStore,
+    out_dir: &Path,
+) -> Result<Option<PathBuf>> {
+    let Some(config) = load_global_redirects(config_dir)? else {
+        return Ok(None);
+    };
+    let sources = load_sources_from_dir(config_dir)?;
+    let bundles = load_optional_bundles(config_dir)?;
+    let redirects = redirect_entries(&config, &sources, &bundles, store, Utc::now().year());
+
+    let path = config.output_path(out_dir);
+    let content = render_redirects(&config, &redirects);
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create redirect dir {}", parent.display()))?;
+        }
+        std::fs::write(&path, content)
+            .with_context(|| format!("failed to write redirect map {}", path.display()))?;
+        info!(file = %path.display(), redirects = redirects.len(), "redirect map written");
+    }
+    Ok(Some(path))
+}
+
+/// Redirects for every published source and bundle year, plus `current`
+/// and `next` when those years have a file.
+pub fn redirect_entries(
+    config: &RedirectsConfig,
+    sources: &[LoadedSource],
+    bundles: &[LoadedBundle],
+    store: &dyn EventStore,
+    current_year: i32,
+) -> Vec<Redirect> {
+    let mut redirects = BTreeMap::new();
+    let mut add = |from: String, to: String| {
+        let from = url_path(&config.base_path, &from);
+        let to = url_path(&config.base_path, &to);
+        if let Some(previous) = redirects.get(&from)
+            && *previous != to
+        {
+            warn!(path = %from, kept = %previous, dropped = %to, "conflicting redirect");
+            return;
+        }
+        redirects.insert(from, to);
+    };
+
+    for source in sources.iter().filter(|source| source.config.source.enabled) {
+        let publish = &source.config.publish;
+        let file_prefix = source.config.sanitized_source_dir_name();
+        let mut files = BTreeSet::new();
+        for event in store.query(&EventQuery::for_source(&source.config.source.key)) {
+            if let Some(year) = event.year_bucket()
+                && publish.publishes_year(year, current_year)
+            {
+                let country = publish.split_by_country.then(|| {
+                    event
+                        .country
+                        .as_deref()
+                        .unwrap_or("xx")
+                        .to_ascii_lowercase()
+                });
+                files.insert((country, year));
+            }
+        }
+        for (country, year) in files {
+            let target = format!(
+                "sources/{file_prefix}/{}",
+                source_ics_filename(source, &file_prefix, year, country.as_deref())
+            );
+            let country = country.or_else(|| source.config.source.default_country.clone());
+            for label in year_labels(year, current_year) {
+                let mut vars = vec![
+                    ("year", label.clone()),
+                    ("source_key", source.config.source.key.clone()),
+                    ("source_dir", file_prefix.clone()),
+                ];
+                if let Some(country) = &country {
+                    vars.push(("country", country.to_ascii_lowercase()));
+                    vars.push(("country_upper", country.to_ascii_uppercase()));
+                }
+                let from = fill_placeholders(&config.source_path, &vars);
+                let from = fill_placeholders(
+                    &from,
+                    &source
+                        .config
+                        .fetch
+                        .template_vars
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), value.clone()))
+                        .collect::<Vec<_>>(),
+                );
+                add(from, target.clone());
+            }
+        }
+    }
+
+    for bundle in bundles {
+        let publish = &bundle.config.publish;
+        let file_prefix = bundle.config.sanitized_bundle_dir_name();
+        let query = EventQuery::for_patterns(&bundle.config.include.source_patterns)
+            .with_categories(&bundle.config.include.categories);
+        let years = store
+            .query(&query)
+            .into_iter()
+            .filter_map(|event| event.year_bucket())
+            .filter(|year| publish.publishes_year(*year, current_year))
+            .collect::<BTreeSet<_>>();
+        for year in years {
+            let target = format!(
+                "bundles/{file_prefix}/{}",
+                bundle_ics_filename(bundle, &file_prefix, year)
+            );
+            for label in year_labels(year, current_year) {
+                let vars = [
+                    ("year", label),
+                    ("bundle_key", bundle.config.bundle.key.clone()),
+                    ("bundle_dir", file_prefix.clone()),
+                ];
+                add(
+                    fill_placeholders(&config.bundle_path, &vars),
+                    target.clone(),
+                );
+            }
+        }
+    }
+
+    redirects
+        .into_iter()
+        .map(|(from, to)| Redirect { from, to })
+        .collect()
+}
+
+/// The map in the configured server's syntax.
+pub fn render_redirects(config: &RedirectsConfig, redirects: &[Redirect]) -> String {
+    let status = config.status;
+    let mut out = String::from("# Generated by rics on every build; do not edit.\n");
+    for Redirect { from, to } in redirects {
+        let line = match config.format {
+            RedirectFormat::Nginx => format!("location = {from} {{ return {status} {to}; }}"),
+            RedirectFormat::Caddy => format!("redir {from} {to} {status}"),
+            RedirectFormat::Netlify => format!("{from} {to} {status}"),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+fn year_labels(year: i32, current_year: i32) -> Vec<String> {
+    let mut labels = vec![year.to_string()];
+    match year - current_year {
+        0 => labels.push("current".to_string()),
+        1 => labels.push("next".to_string()),
+        _ => {}
+    }
+    labels
+}
+
+fn fill_placeholders(template: &str, vars: &[(&str, String)]) -> String {
+    let mut out = template.to_string();
+    for (key, value) in vars {
+        out = out.replace(&format!("{{{{{key}}}}}"), value);
+    }
+    out
+}
+
+fn url_path(base_path: &str, path: &str) -> String {
+    let base = base_path.trim_matches('/');
+    let path = path.trim_start_matches('/');
+    if base.is_empty() {
+        format!("/{path}")
+    } else {
+        format!("/{base}/{path}")
+    }
+}
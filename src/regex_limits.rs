@@ -0,0 +1,164 @@
+//! Limits for regexes from configs.
+//!
+//! Config regexes run against untrusted documents that can be several
+//! megabytes. The `regex` crate never backtracks, so one match is linear in
+//! its input, but a pattern can still compile to a huge program
+//! (`\w{500}{500}`), and a pattern applied to every record of a large
+//! document can dominate a sync. So patterns are length-, size- and
+//! nesting-limited when compiled, inputs are capped, and the time each
+//! pattern spends while parsing one source is budgeted
+//! (`extract.regex_budget_ms`).
+
+use anyhow::{Result, anyhow, bail};
+use regex::{Regex, RegexBuilder};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Longest accepted pattern, in bytes.
+pub const MAX_PATTERN_LEN: usize = 2048;
+/// Largest compiled program (and lazy DFA cache) per pattern, in bytes.
+pub const COMPILED_SIZE_LIMIT: usize = 1 << 20;
+/// Deepest accepted nesting of groups and repetitions.
+pub const NEST_LIMIT: u32 = 32;
+/// Longest input a pattern is run against, in bytes.
+pub const MAX_INPUT_BYTES: usize = 16 << 20;
+/// Compiled patterns [`compile`] keeps.
+pub const CACHED_PATTERNS: usize = 256;
+
+thread_local! {
+    static BUDGET: Cell<Option<Duration>> = const { Cell::new(None) };
+    static SPENT: RefCell<HashMap<String, Duration>> = RefCell::new(HashMap::new());
+}
+
+/// Compiles a config pattern within the limits above.
+pub fn build(pattern: &str, case_insensitive: bool) -> Result<Regex> {
+    if pattern.len() > MAX_PATTERN_LEN {
+        bail!(
+            "regex is {} bytes long; patterns are limited to {MAX_PATTERN_LEN}",
+            pattern.len()
+        );
+    }
+    RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .size_limit(COMPILED_SIZE_LIMIT)
+        .dfa_size_limit(COMPILED_SIZE_LIMIT)
+        .nest_limit(NEST_LIMIT)
+        .build()
+        .map_err(|err| match err {
+            regex::Error::CompiledTooBig(limit) => anyhow!(
+                "regex {pattern} is too complex: it compiles to more than {limit} bytes; \
+                 reduce counted repetitions such as {{n}}"
+            ),
+            err => anyhow!("invalid regex {pattern}: {err}"),
+        })
+}
+
+/// [`build`] with case-sensitive matching, cached per pattern since parsers
+/// apply the same pattern to every record. The cache keeps the
+/// [`CACHED_PATTERNS`] most recently used patterns, so a long-running
+/// process whose configs keep changing does not hold every pattern it has
+/// ever seen.
+pub fn compile(pattern: &str) -> Result<Regex> {
+    /// Compiled patterns and when each was last used.
+    #[derive(Default)]
+    struct Cache {
+        patterns: HashMap<String, (Regex, u64)>,
+        clock: u64,
+    }
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+    let lock = || {
+        CACHE
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    };
+    {
+        let cache = &mut *lock();
+        cache.clock += 1;
+        if let Some((regex, used)) = cache.patterns.get_mut(pattern) {
+            *used = cache.clock;
+            return Ok(regex.clone());
+        }
+    }
+    let regex = build(pattern, false)?;
+    let cache = &mut *lock();
+    if cache.patterns.len() >= CACHED_PATTERNS
+        && let Some(oldest) = cache
+            .patterns
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(pattern, _)| pattern.clone())
+    {
+        cache.patterns.remove(&oldest);
+    }
+    cache.clock += 1;
+    cache
+        .patterns
+        .insert(pattern.to_string(), (regex.clone(), cache.clock));
+    Ok(regex)
+}
+
+/// Runs `matcher` with `regex` over `haystack`, charging the time to the
+/// pattern's budget when a [`BudgetScope`] is active on this thread.
+pub fn run<T>(regex: &Regex, haystack: &str, matcher: impl FnOnce(&Regex, &str) -> T) -> Result<T> {
+    if haystack.len() > MAX_INPUT_BYTES {
+        bail!(
+            "input of {} bytes exceeds the {MAX_INPUT_BYTES} byte limit for regex {}",
+            haystack.len(),
+            regex.as_str()
+        );
+    }
+    let started = now();
+    let out = matcher(regex, haystack);
+    let Some((budget, started)) = BUDGET.get().zip(started) else {
+        return Ok(out);
+    };
+    let spent = SPENT.with_borrow_mut(|spent| {
+        let total = spent.entry(regex.as_str().to_string()).or_default();
+        *total += started.elapsed();
+        *total
+    });
+    if spent > budget {
+        bail!(
+            "regex {} ran for {} ms, over its {} ms budget (extract.regex_budget_ms)",
+            regex.as_str(),
+            spent.as_millis(),
+            budget.as_millis()
+        );
+    }
+    Ok(out)
+}
+
+/// The clock [`run`] charges time by. `Instant::now` panics on
+/// wasm32-unknown-unknown, so patterns run unbudgeted there.
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now() -> Option<Instant> {
+    None
+}
+
+/// Budgets every pattern [`run`] on this thread at `budget` until dropped.
+pub struct BudgetScope {
+    previous: Option<Duration>,
+}
+
+pub fn budget_scope(budget: Duration) -> BudgetScope {
+    SPENT.with_borrow_mut(HashMap::clear);
+    BudgetScope {
+        previous: BUDGET.replace(Some(budget)),
+    }
+}
+
+impl Drop for BudgetScope {
+    fn drop(&mut self) {
+        SPENT.with_borrow_mut(HashMap::clear);
+        BUDGET.set(self.previous);
+    }
+}
@@ -0,0 +1,134 @@
+//! robots.txt rules (RFC 9309) for `fetch.respect_robots_txt`.
+//!
+//! Only the group that applies to one user agent is kept: the group naming
+//! its product token, or `*` when none does. The longest matching rule
+//! wins and `Allow` wins ties; `*` and a trailing `$` are supported in
+//! paths.
+
+/// Whether the rule allows, and its path pattern.
+type Rule = (bool, String);
+
+/// `Allow`/`Disallow` rules of the group that applies to one user agent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotsRules {
+    rules: Vec<Rule>,
+}
+
+/// Why a path is disallowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disallowed {
+    /// The matching `Disallow` pattern, `/` for a disallow-all fallback.
+    pub rule: String,
+}
+
+impl RobotsRules {
+    /// Allows everything, e.g. when the host has no robots.txt.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Disallows everything, for a robots.txt that could not be fetched.
+    pub fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".to_string())],
+        }
+    }
+
+    /// Rules in `content` for `user_agent`, matched on its product token
+    /// (`rics` for `rics/0.1 (+https://...)`).
+    pub fn parse(content: &str, user_agent: &str) -> Self {
+        // Consecutive User-agent lines share the rules that follow them.
+        let mut groups: Vec<(Vec<String>, Vec<Rule>)> = Vec::new();
+        let mut in_rules = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match field.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules || groups.is_empty() {
+                        groups.push((Vec::new(), Vec::new()));
+                        in_rules = false;
+                    }
+                    if let Some((agents, _)) = groups.last_mut() {
+                        agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                field @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    // An empty Disallow allows everything.
+                    if let Some((_, rules)) = groups.last_mut()
+                        && !value.is_empty()
+                    {
+                        rules.push((field == "allow", value.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let token = product_token(user_agent);
+        let rules_for = |agent: &str| {
+            groups
+                .iter()
+                .filter(|(agents, _)| agents.iter().any(|name| name == agent))
+                .flat_map(|(_, rules)| rules.iter().cloned())
+                .collect::<Vec<_>>()
+        };
+        let named = groups.iter().any(|(agents, _)| agents.contains(&token));
+        Self {
+            rules: rules_for(if named { &token } else { "*" }),
+        }
+    }
+
+    /// Checks `path` (path plus query) against the rules.
+    pub fn check(&self, path: &str) -> Result<(), Disallowed> {
+        let best = self
+            .rules
+            .iter()
+            .filter(|(_, pattern)| matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow));
+        match best {
+            Some((false, pattern)) => Err(Disallowed {
+                rule: pattern.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn product_token(user_agent: &str) -> String {
+    user_agent
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Prefix match with `*` matching any run of characters and a trailing `$`
+/// anchoring the end.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    for (index, part) in parts.iter().enumerate() {
+        let last = index + 1 == parts.len();
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
@@ -0,0 +1,502 @@
+//! Events generated from schedule descriptions instead of scraped dates.
+//!
+//! Some agencies only publish their calendar as a rule ("released on the
+//! fifth business day of each month"). A `[[date_rules]]` entry turns such a
+//! rule into concrete, holiday-aware dates over a horizon, and the resulting
+//! candidates merge like any other. `[[schedule]]` entries do the same for
+//! plain recurrences and announced date lists; a source with
+//! `fetch.mode = "schedule"` consists of nothing else.
+
+use crate::config::SourceConfig;
+use crate::holidays::{HolidayCalendar, is_weekend, last_weekday, nth_weekday};
+use crate::model::{CandidateEvent, EventTimeSpec, Provenance};
+use crate::parser::zoned_datetime;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// The span a rule repeats over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    fn months(self) -> u32 {
+        match self {
+            Period::Month => 1,
+            Period::Quarter => 3,
+            Period::Year => 12,
+        }
+    }
+
+    /// First day of the period containing `date`.
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        let month0 = date.month0() - date.month0() % self.months();
+        NaiveDate::from_ymd_opt(date.year(), month0 + 1, 1).expect("period start is a valid date")
+    }
+
+    /// Last day of the period starting at `start`.
+    pub fn end_of(self, start: NaiveDate) -> NaiveDate {
+        add_months(start, self.months()) - Duration::days(1)
+    }
+
+    /// Stable label used in generated event ids, e.g. `2030-03`, `2030-Q1`.
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            Period::Month => start.format("%Y-%m").to_string(),
+            Period::Quarter => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+            Period::Year => start.year().to_string(),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "month" => Ok(Period::Month),
+            "quarter" => Ok(Period::Quarter),
+            "year" => Ok(Period::Year),
+            other => bail!("unknown period {other}"),
+        }
+    }
+}
+
+/// A business-day rule, parsed from text such as:
+///
+/// - `5th business day of month`
+/// - `last business day of quarter`
+/// - `T+2 business days after quarter end`
+/// - `3 business days after month start`
+/// - `2 business days before year end`
+///
+/// "After" and "before" count strictly from the anchor day, so `T+1 after
+/// month end` is the first business day of the next month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusinessDayRule {
+    pub period: Period,
+    pub kind: RuleKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// The nth business day counted from the period start.
+    Nth(u32),
+    Last,
+    AfterStart(u32),
+    AfterEnd(u32),
+    BeforeEnd(u32),
+}
+
+impl FromStr for BusinessDayRule {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let text = raw.trim().to_ascii_lowercase();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let period = r"(?:the )?(month|quarter|year)";
+        let nth = Regex::new(&format!(
+            r"^(\d+)(?:st|nd|rd|th)? business day of (?:each |every )?{period}$"
+        ))?;
+        let last = Regex::new(&format!(
+            r"^last business day of (?:each |every )?{period}$"
+        ))?;
+        let offset = Regex::new(&format!(
+            r"^(?:t ?\+ ?)?(\d+) business days? (after|before) {period}[ -](start|end)$"
+        ))?;
+
+        if let Some(caps) = nth.captures(&text) {
+            let n = caps[1].parse::<u32>()?;
+            if n == 0 {
+                bail!("business day rule {raw:?} must count from 1");
+            }
+            return Ok(Self {
+                period: caps[2].parse()?,
+                kind: RuleKind::Nth(n),
+            });
+        }
+        if let Some(caps) = last.captures(&text) {
+            return Ok(Self {
+                period: caps[1].parse()?,
+                kind: RuleKind::Last,
+            });
+        }
+        if let Some(caps) = offset.captures(&text) {
+            let n = caps[1].parse::<u32>()?;
+            let kind = match (&caps[2], &caps[4]) {
+                ("after", "start") => RuleKind::AfterStart(n),
+                ("after", "end") => RuleKind::AfterEnd(n),
+                ("before", "end") => RuleKind::BeforeEnd(n),
+                _ => bail!("business day rule {raw:?}: only `before ... end` is supported"),
+            };
+            return Ok(Self {
+                period: caps[3].parse()?,
+                kind,
+            });
+        }
+        Err(anyhow!("unrecognized business day rule {raw:?}"))
+    }
+}
+
+impl BusinessDayRule {
+    /// The rule's date for the period starting at `period_start`.
+    pub fn date_in(
+        &self,
+        period_start: NaiveDate,
+        calendar: Option<&HolidayCalendar>,
+    ) -> NaiveDate {
+        let is_business_day = |date: NaiveDate| match calendar {
+            Some(calendar) => calendar.is_business_day(date),
+            None => !is_weekend(date),
+        };
+        let period_end = self.period.end_of(period_start);
+        match self.kind {
+            RuleKind::Nth(n) => step(period_start - Duration::days(1), n, 1, is_business_day),
+            RuleKind::Last => step(period_end + Duration::days(1), 1, -1, is_business_day),
+            RuleKind::AfterStart(n) => step(period_start, n, 1, is_business_day),
+            RuleKind::AfterEnd(n) => step(period_end, n, 1, is_business_day),
+            RuleKind::BeforeEnd(n) => step(period_end, n, -1, is_business_day),
+        }
+    }
+}
+
+/// Moves `n` business days from `from` (exclusive) in `direction`.
+fn step(
+    from: NaiveDate,
+    n: u32,
+    direction: i64,
+    is_business_day: impl Fn(NaiveDate) -> bool,
+) -> NaiveDate {
+    let mut day = from;
+    let mut remaining = n;
+    while remaining > 0 {
+        day += Duration::days(direction);
+        if is_business_day(day) {
+            remaining -= 1;
+        }
+    }
+    day
+}
+
+fn add_months(date: NaiveDate, months: u32) -> NaiveDate {
+    let total = date.year() * 12 + date.month0() as i32 + months as i32;
+    NaiveDate::from_ymd_opt(total.div_euclid(12), total.rem_euclid(12) as u32 + 1, 1)
+        .expect("first of month is a valid date")
+}
+
+/// A calendar recurrence, parsed from text such as:
+///
+/// - `every friday` / `weekly on friday`
+/// - `first friday of month` / `every 2nd tuesday`
+/// - `last wednesday of the month`
+/// - `15th of month` (clamped to the month's last day)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recurrence {
+    Weekly(Weekday),
+    /// The nth weekday of each month; `None` is the last one.
+    MonthlyWeekday {
+        weekday: Weekday,
+        nth: Option<u8>,
+    },
+    MonthlyDay(u32),
+}
+
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let text = raw.trim().to_ascii_lowercase();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let text = text
+            .strip_prefix("every ")
+            .or_else(|| text.strip_prefix("each "))
+            .unwrap_or(&text);
+        let weekday = r"((?:mon|tues|wednes|thurs|fri|satur|sun)day)";
+        let of_month = r"(?: of (?:the |each |every )?month)";
+        let weekly = Regex::new(&format!(r"^(?:weekly on )?{weekday}s?$"))?;
+        let monthly_weekday = Regex::new(&format!(
+            r"^(first|second|third|fourth|fifth|last|[1-5](?:st|nd|rd|th)) {weekday}{of_month}?$"
+        ))?;
+        let monthly_day = Regex::new(&format!(
+            r"^(?:day )?(\d{{1,2}})(?:st|nd|rd|th)?{of_month}$"
+        ))?;
+
+        let parse_weekday = |name: &str| {
+            name.parse::<Weekday>()
+                .map_err(|_| anyhow!("unknown weekday {name}"))
+        };
+        if let Some(caps) = weekly.captures(text) {
+            return Ok(Recurrence::Weekly(parse_weekday(&caps[1])?));
+        }
+        if let Some(caps) = monthly_weekday.captures(text) {
+            let nth = match &caps[1] {
+                "last" => None,
+                "first" => Some(1),
+                "second" => Some(2),
+                "third" => Some(3),
+                "fourth" => Some(4),
+                "fifth" => Some(5),
+                ordinal => Some(ordinal[..1].parse()?),
+            };
+            return Ok(Recurrence::MonthlyWeekday {
+                weekday: parse_weekday(&caps[2])?,
+                nth,
+            });
+        }
+        if let Some(caps) = monthly_day.captures(text) {
+            let day = caps[1].parse::<u32>()?;
+            if !(1..=31).contains(&day) {
+                bail!("recurrence {raw:?}: day must be between 1 and 31");
+            }
+            return Ok(Recurrence::MonthlyDay(day));
+        }
+        Err(anyhow!("unrecognized recurrence {raw:?}"))
+    }
+}
+
+impl Recurrence {
+    /// Occurrences from `start` up to (excluding) `end`, each with the key
+    /// its event id is built from: the month for monthly recurrences, the
+    /// date for weekly ones. Months without a fifth weekday are skipped.
+    pub fn occurrences(&self, start: NaiveDate, end: NaiveDate) -> Vec<(String, NaiveDate)> {
+        let mut out = Vec::new();
+        match *self {
+            Recurrence::Weekly(weekday) => {
+                let ahead = (weekday.num_days_from_monday() + 7
+                    - start.weekday().num_days_from_monday())
+                    % 7;
+                let mut date = start + Duration::days(i64::from(ahead));
+                while date < end {
+                    out.push((date.to_string(), date));
+                    date += Duration::weeks(1);
+                }
+            }
+            Recurrence::MonthlyWeekday { .. } | Recurrence::MonthlyDay(_) => {
+                let mut month = Period::Month.start_of(start);
+                while month < end {
+                    if let Some(date) = self.date_in_month(month)
+                        && date >= start
+                        && date < end
+                    {
+                        out.push((Period::Month.label(month), date));
+                    }
+                    month = add_months(month, 1);
+                }
+            }
+        }
+        out
+    }
+
+    fn date_in_month(&self, month: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            Recurrence::Weekly(_) => None,
+            Recurrence::MonthlyWeekday {
+                weekday,
+                nth: Some(n),
+            } => nth_weekday(month.year(), month.month(), weekday, n),
+            Recurrence::MonthlyWeekday { weekday, nth: None } => {
+                last_weekday(month.year(), month.month(), weekday)
+            }
+            Recurrence::MonthlyDay(day) => {
+                month.with_day(day.min(Period::Month.end_of(month).day()))
+            }
+        }
+    }
+}
+
+/// Candidates for every `[[date_rules]]` entry, starting from the period
+/// that contains `today` unless the entry sets `from`.
+pub fn date_rule_events(source: &SourceConfig, today: NaiveDate) -> Result<Vec<CandidateEvent>> {
+    let mut events = Vec::new();
+    for (index, rule_config) in source.date_rules.iter().enumerate() {
+        let rule = rule_config
+            .rule
+            .parse::<BusinessDayRule>()
+            .with_context(|| format!("date_rules[{index}]"))?;
+        let country = rule_config
+            .calendar
+            .as_deref()
+            .or(source.source.default_country.as_deref());
+        let extra = source.holidays.extra_dates()?;
+        let calendar = country.and_then(|country| HolidayCalendar::new(country, extra));
+        let time = parse_time(rule_config.time.as_deref())
+            .with_context(|| format!("date_rules[{index}].time must be HH:MM"))?;
+        let first = parse_from(rule_config.from.as_deref(), today)
+            .with_context(|| format!("date_rules[{index}].from must be YYYY-MM"))?;
+        let template = Template {
+            title: &rule_config.title,
+            id: rule_config.id.as_deref(),
+            description: rule_config.description.as_deref(),
+            index,
+            parser: "date_rule",
+            rule: &rule_config.rule,
+        };
+
+        let horizon_end = add_months(first.with_day(1).unwrap_or(first), rule_config.months);
+        let mut period_start = rule.period.start_of(first);
+        while period_start < horizon_end {
+            let date = rule.date_in(period_start, calendar.as_ref());
+            // Keyed by period, not date, so a holiday shift moves the event
+            // instead of creating a second one.
+            events.push(generated_event(
+                source,
+                &template,
+                &rule.period.label(period_start),
+                date,
+                time.map(|time| (time, None)),
+            )?);
+            period_start = add_months(period_start, rule.period.months());
+        }
+    }
+    Ok(events)
+}
+
+/// Candidates for every `[[schedule]]` entry. Explicit dates are used as
+/// given; recurrences run from the month containing `today` unless the
+/// entry sets `from`.
+pub fn schedule_events(source: &SourceConfig, today: NaiveDate) -> Result<Vec<CandidateEvent>> {
+    let mut events = Vec::new();
+    for (index, entry) in source.schedule.iter().enumerate() {
+        let start_time = parse_time(entry.time.as_deref())
+            .with_context(|| format!("schedule[{index}].time must be HH:MM"))?;
+        let end_time = parse_time(entry.end_time.as_deref())
+            .with_context(|| format!("schedule[{index}].end_time must be HH:MM"))?;
+        let occurrences = match &entry.every {
+            Some(every) => {
+                let recurrence = every
+                    .parse::<Recurrence>()
+                    .with_context(|| format!("schedule[{index}]"))?;
+                let first = parse_from(entry.from.as_deref(), today)
+                    .with_context(|| format!("schedule[{index}].from must be YYYY-MM"))?;
+                let first = first.with_day(1).unwrap_or(first);
+                recurrence.occurrences(first, add_months(first, entry.months))
+            }
+            None => entry
+                .dates
+                .iter()
+                .map(|value| {
+                    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").with_context(|| {
+                        format!("schedule[{index}] date {value} must be YYYY-MM-DD")
+                    })?;
+                    Ok((date.to_string(), date))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        };
+        let template = Template {
+            title: &entry.title,
+            id: entry.id.as_deref(),
+            description: entry.description.as_deref(),
+            index,
+            parser: "schedule",
+            rule: entry.every.as_deref().unwrap_or("dates"),
+        };
+        for (key, date) in occurrences {
+            events.push(generated_event(
+                source,
+                &template,
+                &key,
+                date,
+                start_time.map(|time| (time, end_time)),
+            )?);
+        }
+    }
+    Ok(events)
+}
+
+fn parse_time(value: Option<&str>) -> Result<Option<NaiveTime>> {
+    value
+        .map(|value| NaiveTime::parse_from_str(value, "%H:%M").map_err(Into::into))
+        .transpose()
+}
+
+fn parse_from(value: Option<&str>, today: NaiveDate) -> Result<NaiveDate> {
+    match value {
+        Some(from) => Ok(NaiveDate::parse_from_str(
+            &format!("{from}-01"),
+            "%Y-%m-%d",
+        )?),
+        None => Ok(today),
+    }
+}
+
+/// The config entry a generated event comes from.
+struct Template<'a> {
+    title: &'a str,
+    id: Option<&'a str>,
+    description: Option<&'a str>,
+    index: usize,
+    /// Provenance parser name, also the metadata key `rule` is stored under.
+    parser: &'static str,
+    rule: &'a str,
+}
+
+fn generated_event(
+    source: &SourceConfig,
+    template: &Template,
+    key: &str,
+    date: NaiveDate,
+    time: Option<(NaiveTime, Option<NaiveTime>)>,
+) -> Result<CandidateEvent> {
+    let spec = match time {
+        Some((start, end)) => zoned_datetime(
+            date.and_time(start),
+            end.map(|end| date.and_time(end)),
+            source.source.timezone.as_deref(),
+        )?,
+        None => EventTimeSpec::Date {
+            start: date,
+            end: None,
+        },
+    };
+    let slug = template
+        .id
+        .map(str::to_string)
+        .unwrap_or_else(|| slugify(template.title));
+    let mut categories = source.event.categories.clone();
+    categories.push(source.source.domain.clone());
+
+    Ok(CandidateEvent {
+        source_key: source.source.key.clone(),
+        source_name: source.source.name.clone(),
+        source_event_id: Some(format!("{slug}:{key}")),
+        title: template.title.to_string(),
+        description: template.description.map(str::to_string),
+        timezone: source.source.timezone.clone(),
+        status: source.event.status.clone(),
+        event_type: source.event.event_type.clone(),
+        subtype: source.event.subtype.clone(),
+        categories,
+        jurisdiction: source.source.jurisdiction.clone(),
+        country: source.source.default_country.clone(),
+        importance: source.event.importance,
+        metadata: BTreeMap::from([
+            (template.parser.to_string(), template.rule.to_string()),
+            ("time_precision".to_string(), spec.precision().to_string()),
+        ]),
+        provenance: Some(Provenance {
+            parser: template.parser.to_string(),
+            record_index: Some(template.index),
+            ..Provenance::default()
+        }),
+        time: spec,
+        ..CandidateEvent::default()
+    })
+}
+
+fn slugify(value: &str) -> String {
+    let mut out = String::new();
+    for c in value.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_matches('-').to_string()
+}
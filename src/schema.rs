@@ -0,0 +1,157 @@
+//! JSON Schema and validation for candidate events.
+//!
+//! Parsers that live outside this crate hand their events over as JSON.
+//! [`parse_candidate_events`] checks such output before it gets anywhere
+//! near a merge: deserialization errors name the event and field path, and
+//! events that deserialize but could not be published correctly (blank
+//! titles, ends before starts, month 13) are all reported at once. Every
+//! source's parsed events go through the same [`check_candidates`].
+
+use crate::model::{CandidateEvent, EventTimeSpec};
+use anyhow::{Result, anyhow, bail};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::fmt;
+
+/// `$id` of the published schema.
+pub const CANDIDATE_EVENT_SCHEMA_ID: &str =
+    "https://github.com/sguzman/rics/blob/main/schemas/candidate-event.schema.json";
+
+/// JSON Schema of one candidate event, as published in
+/// `schemas/candidate-event.schema.json`.
+pub fn candidate_event_schema() -> serde_json::Value {
+    let mut schema = schemars::schema_for!(CandidateEvent);
+    schema.insert("$id".to_string(), CANDIDATE_EVENT_SCHEMA_ID.into());
+    schema.to_value()
+}
+
+/// A problem with one event of a parser's output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CandidateProblem {
+    /// Position of the event in the output.
+    pub index: usize,
+    /// Field path, e.g. `time.end`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for CandidateProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}].{}: {}", self.index, self.path, self.message)
+    }
+}
+
+/// Parses a JSON array of candidate events, failing with every problem
+/// [`check_candidate`] finds.
+pub fn parse_candidate_events(json: &str) -> Result<Vec<CandidateEvent>> {
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    let events: Vec<CandidateEvent> =
+        serde_path_to_error::deserialize(deserializer).map_err(|err| {
+            anyhow!(
+                "candidate events do not match the schema at {}: {}",
+                err.path(),
+                err.inner()
+            )
+        })?;
+    check_candidates(&events)?;
+    Ok(events)
+}
+
+/// Fails with every problem [`check_candidate`] finds in `events`.
+pub fn check_candidates(events: &[CandidateEvent]) -> Result<()> {
+    let problems = events
+        .iter()
+        .enumerate()
+        .flat_map(|(index, event)| check_candidate(index, event))
+        .collect::<Vec<_>>();
+    if !problems.is_empty() {
+        let lines = problems
+            .iter()
+            .map(|problem| format!("  {problem}"))
+            .collect::<Vec<_>>();
+        bail!(
+            "{} problems in candidate events:\n{}",
+            problems.len(),
+            lines.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Checks what the schema cannot express.
+pub fn check_candidate(index: usize, event: &CandidateEvent) -> Vec<CandidateProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |path: &str, message: String| {
+        problems.push(CandidateProblem {
+            index,
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if event.title.trim().is_empty() {
+        problem("title", "must not be empty".to_string());
+    }
+    match &event.time {
+        EventTimeSpec::DateTime { start, end, local } => {
+            if end.is_some_and(|end| end < *start) {
+                problem("time.end", "is before time.start".to_string());
+            }
+            if let Some(local) = local
+                && local.tzid.parse::<Tz>().is_err()
+            {
+                problem(
+                    "time.local.tzid",
+                    format!("{} is not an IANA time zone", local.tzid),
+                );
+            }
+        }
+        EventTimeSpec::Date { start, end } if end.is_some_and(|end| end < *start) => {
+            problem("time.end", "is before time.start".to_string());
+        }
+        EventTimeSpec::Month { month, .. } if !(1..=12).contains(month) => {
+            problem("time.month", format!("{month} is not between 1 and 12"));
+        }
+        EventTimeSpec::Quarter { quarter, .. } if !(1..=4).contains(quarter) => {
+            problem("time.quarter", format!("{quarter} is not between 1 and 4"));
+        }
+        _ => {}
+    }
+    if let Some(timezone) = &event.timezone
+        && timezone.parse::<Tz>().is_err()
+    {
+        problem("timezone", format!("{timezone} is not an IANA time zone"));
+    }
+    if let Some(importance) = event.importance
+        && importance > 100
+    {
+        problem(
+            "importance",
+            format!("{importance} is not between 0 and 100"),
+        );
+    }
+    if let Some(confidence) = event.confidence
+        && !(0.0..=1.0).contains(&confidence)
+    {
+        problem("confidence", format!("{confidence} is not between 0 and 1"));
+    }
+    if let Some(url) = &event.source_url
+        && url::Url::parse(url).is_err()
+    {
+        problem("source_url", format!("{url} is not an absolute URL"));
+    }
+    for (position, url) in event.attachments.iter().enumerate() {
+        if url::Url::parse(url).is_err() {
+            problem(
+                &format!("attachments[{position}]"),
+                format!("{url} is not an absolute URL"),
+            );
+        }
+    }
+    if let Some(rrule) = &event.rrule
+        && rrule.to_ascii_uppercase().starts_with("RRULE:")
+    {
+        problem("rrule", "must not include the RRULE: prefix".to_string());
+    }
+    problems
+}
@@ -0,0 +1,279 @@
+//! Inverted index for full-text queries over stored events.
+//!
+//! The index lives in `<state>.search-index.json` next to the state file.
+//! The first `rics query --text` builds it; from then on every sync
+//! refreshes it, and queries re-index whatever changed since (e.g. after
+//! `rics quarantine apply`), comparing revision hashes so only modified
+//! events are tokenized again. The file is only written under the
+//! [`StateLock`].
+
+use crate::model::{EventRecord, State};
+use crate::quarantine::title_similarity;
+use crate::store::{StateLock, write_atomic};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Tokens of one indexed event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct IndexedEvent {
+    revision_hash: String,
+    title_tokens: BTreeSet<String>,
+    /// Description, metadata and translation tokens not in the title.
+    other_tokens: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    path: PathBuf,
+    events: BTreeMap<String, IndexedEvent>,
+    /// Store keys by token; rebuilt in memory from `events` on open.
+    postings: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Two events that look like the same occurrence.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateCandidate {
+    pub first: String,
+    pub second: String,
+    pub similarity: f64,
+}
+
+impl SearchIndex {
+    /// `<state>.search-index.json` beside the state file, so state files
+    /// sharing a directory keep separate indexes.
+    pub fn path_for_state(state_path: &Path) -> PathBuf {
+        let mut path = state_path.as_os_str().to_owned();
+        path.push(".search-index.json");
+        PathBuf::from(path)
+    }
+
+    /// Opens the index, empty when the file does not exist yet.
+    pub fn open(path: &Path) -> Result<Self> {
+        let events: BTreeMap<String, IndexedEvent> = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read search index {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("failed to parse search index {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+        let mut index = Self {
+            path: path.to_path_buf(),
+            events: BTreeMap::new(),
+            postings: BTreeMap::new(),
+        };
+        for (key, event) in events {
+            index.insert(key, event);
+        }
+        Ok(index)
+    }
+
+    /// Brings the index in line with `state`. Returns how many events were
+    /// added, re-indexed or dropped.
+    pub fn refresh(&mut self, state: &State) -> usize {
+        let stale = self
+            .events
+            .keys()
+            .filter(|key| !state.events.contains_key(*key))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut changed = stale.len();
+        for key in stale {
+            self.remove(&key);
+        }
+        for (key, record) in &state.events {
+            if self
+                .events
+                .get(key)
+                .is_some_and(|indexed| indexed.revision_hash == record.revision_hash)
+            {
+                continue;
+            }
+            self.remove(key);
+            self.insert(key.clone(), index_event(record));
+            changed += 1;
+        }
+        changed
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create search index directory {}",
+                    parent.display()
+                )
+            })?;
+        }
+        let serialized = serde_json::to_string(&self.events)?;
+        write_atomic(&self.path, serialized.as_bytes())
+            .with_context(|| format!("failed to write search index {}", self.path.display()))
+    }
+
+    /// Store keys of events containing every word of `text`; the last word
+    /// also matches as a prefix. Title matches rank first.
+    pub fn search(&self, text: &str) -> Vec<String> {
+        let words = words(text);
+        let Some((last, rest)) = words.split_last() else {
+            return Vec::new();
+        };
+
+        let mut matches: Option<BTreeSet<String>> = None;
+        for word in rest {
+            let keys = self.postings.get(word).cloned().unwrap_or_default();
+            matches = Some(match matches {
+                Some(current) => current.intersection(&keys).cloned().collect(),
+                None => keys,
+            });
+        }
+        let prefixed = self
+            .postings
+            .range(last.clone()..)
+            .take_while(|(token, _)| token.starts_with(last.as_str()))
+            .flat_map(|(_, keys)| keys.iter().cloned())
+            .collect::<BTreeSet<_>>();
+        let matches = match matches {
+            Some(current) => current.intersection(&prefixed).cloned().collect(),
+            None => prefixed,
+        };
+
+        let mut ranked = matches
+            .into_iter()
+            .map(|key| {
+                let title_hits = self.events[&key]
+                    .title_tokens
+                    .iter()
+                    .filter(|token| rest.contains(token) || token.starts_with(last.as_str()))
+                    .count();
+                (std::cmp::Reverse(title_hits), key)
+            })
+            .collect::<Vec<_>>();
+        ranked.sort();
+        ranked.into_iter().map(|(_, key)| key).collect()
+    }
+
+    /// Pairs of events from different sources on the same day whose titles
+    /// are at least `min_similarity` alike. Only events sharing a title word
+    /// are compared, so this stays cheap on large states.
+    pub fn duplicates(&self, state: &State, min_similarity: f64) -> Vec<DuplicateCandidate> {
+        let mut out = Vec::new();
+        for (key, indexed) in &self.events {
+            let Some(event) = state.events.get(key) else {
+                continue;
+            };
+            let Some(day) = event.time.start_date() else {
+                continue;
+            };
+            let candidates = indexed
+                .title_tokens
+                .iter()
+                .filter_map(|token| self.postings.get(token))
+                .flatten()
+                .filter(|other| other.as_str() > key.as_str())
+                .collect::<BTreeSet<_>>();
+            for other_key in candidates {
+                let Some(other) = state.events.get(other_key) else {
+                    continue;
+                };
+                if other.source_key == event.source_key || other.time.start_date() != Some(day) {
+                    continue;
+                }
+                let similarity = title_similarity(&event.title, &other.title);
+                if similarity >= min_similarity {
+                    out.push(DuplicateCandidate {
+                        first: key.clone(),
+                        second: other_key.clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+        out.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        out
+    }
+
+    fn insert(&mut self, key: String, event: IndexedEvent) {
+        for token in event.title_tokens.iter().chain(&event.other_tokens) {
+            self.postings
+                .entry(token.clone())
+                .or_default()
+                .insert(key.clone());
+        }
+        self.events.insert(key, event);
+    }
+
+    fn remove(&mut self, key: &str) {
+        let Some(event) = self.events.remove(key) else {
+            return;
+        };
+        for token in event.title_tokens.iter().chain(&event.other_tokens) {
+            if let Some(keys) = self.postings.get_mut(token) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.postings.remove(token);
+                }
+            }
+        }
+    }
+}
+
+fn index_event(record: &EventRecord) -> IndexedEvent {
+    let title_tokens = tokenize(&record.title);
+    let mut other = record.description.iter().cloned().collect::<Vec<_>>();
+    other.extend(record.metadata.values().cloned());
+    for text in record.translations.values() {
+        other.extend(text.title.iter().chain(&text.description).cloned());
+    }
+    let other_tokens = tokenize(&other.join(" "))
+        .into_iter()
+        .filter(|token| !title_tokens.contains(token))
+        .collect();
+    IndexedEvent {
+        revision_hash: record.revision_hash.clone(),
+        title_tokens,
+        other_tokens,
+    }
+}
+
+/// Opens the index next to `state_path`, creating or catching it up with
+/// `state` first. The caught-up index is only saved when the state lock is
+/// free; during a sync it stays in memory and the sync refreshes the file.
+pub fn open_search_index(state_path: &Path, state: &State) -> Result<SearchIndex> {
+    let path = SearchIndex::path_for_state(state_path);
+    let existed = path.exists();
+    let mut index = SearchIndex::open(&path)?;
+    if (index.refresh(state) > 0 || !existed)
+        && let Some(_lock) = StateLock::try_acquire(state_path)?
+    {
+        index.save()?;
+    }
+    Ok(index)
+}
+
+/// Refreshes the index next to `state_path` after a sync. Does nothing
+/// until a query has created the index. The caller holds the state lock.
+pub fn refresh_search_index(state_path: &Path, state: &State) -> Result<()> {
+    let path = SearchIndex::path_for_state(state_path);
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut index = SearchIndex::open(&path)?;
+    if index.refresh(state) > 0 {
+        index.save()?;
+    }
+    Ok(())
+}
+
+/// Lowercased alphanumeric words of `text`.
+pub fn tokenize(text: &str) -> BTreeSet<String> {
+    words(text).into_iter().collect()
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
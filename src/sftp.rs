@@ -0,0 +1,168 @@
+//! `fetch.mode = "sftp"`: files over SSH, through libssh2 (the `sftp`
+//! feature).
+//!
+//! Logs in with a password or a private key, lists the directory when the
+//! file name is a glob and downloads each match. The server's host key is
+//! checked against `host_key_sha256` when set, and logged otherwise so it
+//! can be pinned.
+
+use crate::config::SftpConfig;
+#[cfg(feature = "sftp")]
+use crate::config::{LoadedSource, resolve_path};
+#[cfg(feature = "sftp")]
+use crate::fetch::{
+    FetchedDocument, apply_templates, has_glob_pattern, read_capped, template_substitutions,
+};
+#[cfg(feature = "sftp")]
+use crate::network::connect_checked;
+#[cfg(feature = "sftp")]
+use anyhow::{Context, Result, bail};
+#[cfg(feature = "sftp")]
+use chrono::Utc;
+#[cfg(feature = "sftp")]
+use glob::Pattern;
+#[cfg(feature = "sftp")]
+use ssh2::{HashType, Session};
+#[cfg(feature = "sftp")]
+use std::collections::BTreeMap;
+#[cfg(feature = "sftp")]
+use std::path::Path;
+#[cfg(feature = "sftp")]
+use std::time::Duration;
+#[cfg(feature = "sftp")]
+use tracing::{debug, info, warn};
+
+#[cfg(feature = "sftp")]
+pub fn fetch_sftp_documents(source: &LoadedSource) -> Result<Vec<FetchedDocument>> {
+    let fetch = &source.config.fetch;
+    let sftp = fetch.sftp.as_ref().context("fetch.sftp missing")?;
+    let path = apply_templates(&sftp.path, &template_substitutions(source));
+
+    let timeout = Duration::from_secs(fetch.timeout_secs);
+    let tcp = connect_checked(source, &sftp.host, sftp.port, timeout)
+        .with_context(|| format!("failed to connect to sftp {}:{}", sftp.host, sftp.port))?;
+    let mut session = Session::new().context("failed to start ssh session")?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX));
+    session
+        .handshake()
+        .with_context(|| format!("ssh handshake with {} failed", sftp.host))?;
+    check_host_key(&session, sftp)?;
+    authenticate(&session, source, sftp)?;
+    let client = session.sftp().context("failed to open sftp channel")?;
+
+    let (dir, name) = path.rsplit_once('/').unwrap_or(("", &path));
+    let files = if has_glob_pattern(name) {
+        let pattern =
+            Pattern::new(name).with_context(|| format!("invalid sftp file glob pattern {name}"))?;
+        let listing = client
+            .readdir(Path::new(if dir.is_empty() { "/" } else { dir }))
+            .with_context(|| format!("failed to list sftp directory {dir}"))?;
+        let mut names = listing
+            .into_iter()
+            .filter(|(_, stat)| stat.is_file())
+            .filter_map(|(listed, _)| Some(listed.file_name()?.to_str()?.to_string()))
+            .filter(|listed| pattern.matches(listed))
+            .collect::<Vec<_>>();
+        names.sort();
+        if names.is_empty() {
+            bail!("no files on sftp {} matched {path}", sftp.host);
+        }
+        names
+            .into_iter()
+            .map(|listed| format!("{dir}/{listed}"))
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+
+    let fetched_at = Utc::now();
+    let mut docs = Vec::with_capacity(files.len());
+    for file in files {
+        let source_url = sftp_url(sftp, &file);
+        let remote = client
+            .open(Path::new(&file))
+            .with_context(|| format!("failed to open {source_url}"))?;
+        let body = read_capped(remote, &source_url, fetch)?;
+        debug!(url = %source_url, bytes = body.len(), "sftp file downloaded");
+        docs.push(FetchedDocument {
+            source_url,
+            body,
+            page_index: docs.len(),
+            fetched_at: Some(fetched_at),
+            headers: BTreeMap::new(),
+        });
+    }
+
+    info!(
+        source = %source.config.source.key,
+        host = %sftp.host,
+        %path,
+        documents = docs.len(),
+        "sftp fetch complete"
+    );
+    Ok(docs)
+}
+
+/// `sftp://` URL of `path` on the configured server.
+pub fn sftp_url(sftp: &SftpConfig, path: &str) -> String {
+    if sftp.port == 22 {
+        format!("sftp://{}{path}", sftp.host)
+    } else {
+        format!("sftp://{}:{}{path}", sftp.host, sftp.port)
+    }
+}
+
+#[cfg(feature = "sftp")]
+fn check_host_key(session: &Session, sftp: &SftpConfig) -> Result<()> {
+    let fingerprint = session
+        .host_key_hash(HashType::Sha256)
+        .map(hex::encode)
+        .context("ssh server sent no host key")?;
+    match &sftp.host_key_sha256 {
+        Some(expected) if !expected.eq_ignore_ascii_case(&fingerprint) => bail!(
+            "host key of {} is {fingerprint}, not fetch.sftp.host_key_sha256 {expected}",
+            sftp.host
+        ),
+        Some(_) => Ok(()),
+        None => {
+            warn!(
+                host = %sftp.host,
+                host_key_sha256 = %fingerprint,
+                "sftp host key not pinned; set fetch.sftp.host_key_sha256"
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "sftp")]
+fn authenticate(session: &Session, source: &LoadedSource, sftp: &SftpConfig) -> Result<()> {
+    let secret = match &sftp.password_env {
+        Some(env) => Some(std::env::var(env).with_context(|| {
+            format!(
+                "sftp password env var {env} is not set for source {}",
+                source.config.source.key
+            )
+        })?),
+        None => None,
+    };
+    match &sftp.private_key {
+        Some(key) => {
+            let key = resolve_path(&source.path, key)?;
+            session
+                .userauth_pubkey_file(&sftp.username, None, &key, secret.as_deref())
+                .with_context(|| format!("sftp key login as {} failed", sftp.username))?;
+        }
+        None => {
+            let password = secret.context("fetch.sftp.password_env missing")?;
+            session
+                .userauth_password(&sftp.username, &password)
+                .with_context(|| format!("sftp login as {} failed", sftp.username))?;
+        }
+    }
+    if !session.authenticated() {
+        bail!("sftp login as {} was not accepted", sftp.username);
+    }
+    Ok(())
+}
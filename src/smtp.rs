@@ -0,0 +1,131 @@
+//! Minimal SMTP client for sending invites.
+//!
+//! Only what invites need is implemented: EHLO, AUTH PLAIN, MAIL/RCPT/DATA
+//! and QUIT, over implicit TLS or, for a local relay, plain TCP.
+
+use crate::config::SmtpConfig;
+use crate::util::base64;
+use anyhow::{Context, Result, bail};
+use native_tls::TlsConnector;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use tracing::{debug, warn};
+
+/// Sends each `(recipients, message)` pair in one session. A message the
+/// server rejects is logged and skipped; connection and login failures
+/// abort. Returns how many messages were accepted.
+pub fn send_messages<'a>(
+    config: &SmtpConfig,
+    messages: impl IntoIterator<Item = (&'a [String], &'a str)>,
+) -> Result<usize> {
+    let tcp = TcpStream::connect((config.host.as_str(), config.port))
+        .with_context(|| format!("failed to connect to smtp {}:{}", config.host, config.port))?;
+    let timeout = std::time::Duration::from_secs(config.timeout_secs);
+    tcp.set_read_timeout(Some(timeout))?;
+    tcp.set_write_timeout(Some(timeout))?;
+
+    if config.tls {
+        let connector = TlsConnector::new().context("failed to build tls connector")?;
+        let stream = connector
+            .connect(&config.host, tcp)
+            .with_context(|| format!("tls handshake with {} failed", config.host))?;
+        Session::new(stream).run(config, messages)
+    } else {
+        Session::new(tcp).run(config, messages)
+    }
+}
+
+struct Session<S: Read + Write> {
+    reader: BufReader<S>,
+}
+
+impl<S: Read + Write> Session<S> {
+    fn new(stream: S) -> Self {
+        Self {
+            reader: BufReader::new(stream),
+        }
+    }
+
+    fn run<'a>(
+        mut self,
+        config: &SmtpConfig,
+        messages: impl IntoIterator<Item = (&'a [String], &'a str)>,
+    ) -> Result<usize> {
+        self.expect(220).context("smtp greeting")?;
+        self.command("EHLO rics", 250)?;
+        if let Some(username) = &config.username {
+            let password = std::env::var(&config.password_env).with_context(|| {
+                format!("smtp password env var {} is not set", config.password_env)
+            })?;
+            let token = base64(format!("\0{username}\0{password}").as_bytes());
+            self.command(&format!("AUTH PLAIN {token}"), 235)
+                .context("smtp login failed")?;
+        }
+
+        let mut sent = 0;
+        for (recipients, message) in messages {
+            match self.send(&config.from, recipients, message) {
+                Ok(()) => sent += 1,
+                Err(err) => {
+                    warn!(error = %format!("{err:#}"), "smtp server rejected invite");
+                    self.command("RSET", 250)?;
+                }
+            }
+        }
+        self.command("QUIT", 221)?;
+        Ok(sent)
+    }
+
+    fn send(&mut self, from: &str, recipients: &[String], message: &str) -> Result<()> {
+        self.command(&format!("MAIL FROM:<{from}>"), 250)?;
+        for recipient in recipients {
+            self.command(&format!("RCPT TO:<{recipient}>"), 250)?;
+        }
+        self.command("DATA", 354)?;
+        // Dot-stuff lines that start with a period.
+        let mut body = message.replace("\r\n.", "\r\n..");
+        if body.starts_with('.') {
+            body.insert(0, '.');
+        }
+        if !body.ends_with("\r\n") {
+            body.push_str("\r\n");
+        }
+        body.push_str(".\r\n");
+        self.reader.get_mut().write_all(body.as_bytes())?;
+        self.expect(250).context("message not accepted")
+    }
+
+    fn command(&mut self, line: &str, code: u16) -> Result<()> {
+        let shown = if line.starts_with("AUTH") {
+            "AUTH PLAIN ***"
+        } else {
+            line
+        };
+        debug!(command = shown, "smtp");
+        write!(self.reader.get_mut(), "{line}\r\n")?;
+        self.expect(code)
+            .with_context(|| format!("smtp command {shown} failed"))
+    }
+
+    /// Reads a possibly multi-line reply and checks its code. 251 (user not
+    /// local, will forward) counts as 250.
+    fn expect(&mut self, code: u16) -> Result<()> {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                bail!("smtp connection closed");
+            }
+            let reply = line
+                .get(..3)
+                .and_then(|digits| digits.parse::<u16>().ok())
+                .with_context(|| format!("malformed smtp reply {:?}", line.trim_end()))?;
+            if line.as_bytes().get(3) == Some(&b'-') {
+                continue;
+            }
+            if reply == code || (code == 250 && reply == 251) {
+                return Ok(());
+            }
+            bail!("smtp replied {}", line.trim_end());
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Staged calendar rebuilds.
+//!
+//! A rebuild renders every file into a private staging directory first and
+//! only moves them over the live files once all of them were written, so a
+//! failed or slow rebuild never leaves subscribers with a mix of old and new
+//! years, and mirrors are copied from the committed set.
+
+use crate::ics::file_digest;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Files staged for one live directory. Dropping it without committing
+/// discards everything staged.
+#[derive(Debug)]
+pub struct Staging {
+    live_dir: PathBuf,
+    staging_dir: PathBuf,
+    committed: bool,
+}
+
+impl Staging {
+    /// Starts an empty staging area, removing leftovers of an earlier run
+    /// that failed.
+    pub fn new(live_dir: &Path, staging_dir: &Path) -> Result<Self> {
+        if staging_dir.exists() {
+            std::fs::remove_dir_all(staging_dir).with_context(|| {
+                format!("failed to clear staging dir {}", staging_dir.display())
+            })?;
+        }
+        std::fs::create_dir_all(staging_dir)
+            .with_context(|| format!("failed to create staging dir {}", staging_dir.display()))?;
+        Ok(Self {
+            live_dir: live_dir.to_path_buf(),
+            staging_dir: staging_dir.to_path_buf(),
+            committed: false,
+        })
+    }
+
+    /// Where to write `file_name` before it is committed.
+    pub fn path(&self, file_name: &str) -> PathBuf {
+        self.staging_dir.join(file_name)
+    }
+
+    /// Moves every staged file over its live counterpart with a rename.
+    /// Files identical to the live copy are dropped instead, so unchanged
+    /// calendars keep their mtime. Returns the staged file names.
+    pub fn commit(mut self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.staging_dir)? {
+            let entry = entry?;
+            if let Some(name) = entry.file_name().to_str()
+                && entry.file_type()?.is_file()
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+
+        for name in &names {
+            let staged = self.staging_dir.join(name);
+            let live = self.live_dir.join(name);
+            if file_digest(&live)? == file_digest(&staged)? {
+                debug!(file = %live.display(), "calendar file unchanged");
+                continue;
+            }
+            std::fs::rename(&staged, &live)
+                .with_context(|| format!("failed to commit {}", live.display()))?;
+            info!(file = %live.display(), "calendar file rebuilt");
+        }
+
+        self.committed = true;
+        std::fs::remove_dir_all(&self.staging_dir).with_context(|| {
+            format!(
+                "failed to remove staging dir {}",
+                self.staging_dir.display()
+            )
+        })?;
+        Ok(names)
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_dir_all(&self.staging_dir);
+        }
+    }
+}
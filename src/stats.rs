@@ -0,0 +1,158 @@
+//! Aggregate counts over stored events, for `rics stats`.
+//!
+//! Events are counted by source, year, month, category, importance band,
+//! time precision and status. Each source's trend compares its event count
+//! now with the count before the latest sync that processed it, worked out
+//! from that run's inserted and purged counts.
+
+use crate::model::{EventTimeSpec, State};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EventStats {
+    pub total: usize,
+    pub by_source: BTreeMap<String, usize>,
+    /// Undated events are counted under `tbd`.
+    pub by_year: BTreeMap<String, usize>,
+    /// `YYYY-MM`, for events dated to a month or finer.
+    pub by_month: BTreeMap<String, usize>,
+    /// Events with several categories count once under each.
+    pub by_category: BTreeMap<String, usize>,
+    /// Bands of ten, e.g. `80-89`, and `unset`.
+    pub by_importance: BTreeMap<String, usize>,
+    pub by_precision: BTreeMap<String, usize>,
+    pub by_status: BTreeMap<String, usize>,
+    pub trends: Vec<SourceTrend>,
+}
+
+/// A source's events before and after its latest sync.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceTrend {
+    pub source_key: String,
+    pub run_started_at: DateTime<Utc>,
+    pub previous: usize,
+    pub current: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub cancelled: usize,
+    pub purged: usize,
+}
+
+impl SourceTrend {
+    pub fn change(&self) -> i64 {
+        self.current as i64 - self.previous as i64
+    }
+}
+
+/// Counts over the events in `state`, or only `source`'s.
+pub fn event_stats(state: &State, source: Option<&str>) -> EventStats {
+    let mut stats = EventStats::default();
+    let events = state
+        .events
+        .values()
+        .filter(|event| source.is_none_or(|key| event.source_key == key));
+    for event in events {
+        stats.total += 1;
+        bump(&mut stats.by_source, &event.source_key);
+        let year = event
+            .year_bucket()
+            .map_or_else(|| "tbd".to_string(), |year| year.to_string());
+        bump(&mut stats.by_year, &year);
+        if matches!(
+            event.time,
+            EventTimeSpec::DateTime { .. }
+                | EventTimeSpec::Date { .. }
+                | EventTimeSpec::Month { .. }
+        ) && let Some(start) = event.time.start_date()
+        {
+            bump(&mut stats.by_month, &start.format("%Y-%m").to_string());
+        }
+        if event.categories.is_empty() {
+            bump(&mut stats.by_category, "uncategorized");
+        }
+        for category in &event.categories {
+            bump(&mut stats.by_category, category);
+        }
+        bump(&mut stats.by_importance, &importance_band(event.importance));
+        bump(&mut stats.by_precision, event.time.precision());
+        bump(&mut stats.by_status, &event.status);
+    }
+
+    for (source_key, &current) in &stats.by_source {
+        let Some((run, summary)) = state
+            .runs
+            .iter()
+            .rev()
+            .find_map(|run| run.source(source_key).map(|summary| (run, summary)))
+        else {
+            continue;
+        };
+        stats.trends.push(SourceTrend {
+            source_key: source_key.clone(),
+            run_started_at: run.started_at,
+            previous: (current + summary.purged).saturating_sub(summary.inserted),
+            current,
+            inserted: summary.inserted,
+            updated: summary.updated,
+            cancelled: summary.cancelled,
+            purged: summary.purged,
+        });
+    }
+    stats
+}
+
+/// `stats` as `dimension,key,count` rows. Trends are rows of the
+/// `change_since_previous_run` dimension, keyed by source.
+pub fn stats_csv(stats: &EventStats) -> String {
+    let mut out = String::from("dimension,key,count\n");
+    let mut row = |dimension: &str, key: &str, count: i64| {
+        out.push_str(&format!("{dimension},{},{count}\n", csv_field(key)));
+    };
+    row("total", "", stats.total as i64);
+    for (dimension, counts) in [
+        ("source", &stats.by_source),
+        ("year", &stats.by_year),
+        ("month", &stats.by_month),
+        ("category", &stats.by_category),
+        ("importance", &stats.by_importance),
+        ("precision", &stats.by_precision),
+        ("status", &stats.by_status),
+    ] {
+        for (key, count) in counts {
+            row(dimension, key, *count as i64);
+        }
+    }
+    for trend in &stats.trends {
+        row(
+            "change_since_previous_run",
+            &trend.source_key,
+            trend.change(),
+        );
+    }
+    out
+}
+
+fn bump(counts: &mut BTreeMap<String, usize>, key: &str) {
+    *counts.entry(key.to_string()).or_default() += 1;
+}
+
+fn importance_band(importance: Option<u8>) -> String {
+    match importance {
+        None => "unset".to_string(),
+        Some(value) if value >= 90 => "90-100".to_string(),
+        Some(value) => {
+            let low = value / 10 * 10;
+            format!("{low}-{}", low + 9)
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
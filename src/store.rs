@@ -1,6 +1,384 @@
-use crate::model::State;
+use crate::model::{
+    EventRecord, FieldCoverage, Lifecycle, MAX_RUN_HISTORY, ResourceValidators, RunRecord, State,
+};
 use anyhow::{Context, Result};
-use std::path::Path;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::info;
+
+/// Filters applied when reading events back out of an [`EventStore`].
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    pub source_key: Option<String>,
+    pub source_patterns: Vec<String>,
+    pub year: Option<i32>,
+    pub include_cancelled: bool,
+    /// Match events carrying any of these categories; empty matches all.
+    pub categories: Vec<String>,
+}
+
+impl EventQuery {
+    pub fn for_source(source_key: &str) -> Self {
+        Self {
+            source_key: Some(source_key.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn for_patterns(patterns: &[String]) -> Self {
+        Self {
+            source_patterns: patterns.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_year(mut self, year: Option<i32>) -> Self {
+        self.year = year;
+        self
+    }
+
+    pub fn with_cancelled(mut self, include_cancelled: bool) -> Self {
+        self.include_cancelled = include_cancelled;
+        self
+    }
+
+    pub fn with_categories(mut self, categories: &[String]) -> Self {
+        self.categories = categories.to_vec();
+        self
+    }
+
+    pub fn matches(&self, event: &EventRecord) -> bool {
+        if let Some(key) = &self.source_key
+            && event.source_key != *key
+        {
+            return false;
+        }
+        if !self.source_patterns.is_empty()
+            && !matches_source_patterns(&event.source_key, &self.source_patterns)
+        {
+            return false;
+        }
+        if let Some(year) = self.year
+            && event.year_bucket() != Some(year)
+        {
+            return false;
+        }
+        if !self.include_cancelled && event.is_cancelled() {
+            return false;
+        }
+        if !self.categories.is_empty()
+            && !event
+                .categories
+                .iter()
+                .any(|category| self.categories.contains(category))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Storage backend for canonical event records.
+///
+/// The pipeline only talks to state through this trait so that backends other
+/// than the JSON state file can be slotted in without touching merge logic.
+///
+/// Records are addressed by [`EventRecord::store_key`], which is the UID for
+/// standalone events and series masters, and UID plus recurrence id for
+/// exception instances.
+///
+/// Stores must be `Sync` because calendar files are rendered from them in
+/// parallel.
+pub trait EventStore: Sync {
+    fn get(&self, key: &str) -> Option<&EventRecord>;
+
+    /// Inserts or replaces a record, returning the previous version if any.
+    fn upsert(&mut self, record: EventRecord) -> Option<EventRecord>;
+
+    /// Records that an unchanged event was seen again.
+    fn touch(&mut self, key: &str, seen_at: DateTime<Utc>) -> bool;
+
+    /// Marks an event cancelled and bumps its sequence. Returns false when the
+    /// event is unknown or already cancelled.
+    fn mark_cancelled(&mut self, key: &str, now: DateTime<Utc>) -> bool;
+
+    /// Marks a past event that is no longer published as expired. Returns
+    /// false when the event is unknown or not active.
+    fn mark_expired(&mut self, key: &str) -> bool;
+
+    fn remove(&mut self, key: &str) -> Option<EventRecord>;
+
+    fn query(&self, query: &EventQuery) -> Vec<&EventRecord>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl EventStore for State {
+    fn get(&self, key: &str) -> Option<&EventRecord> {
+        self.events.get(key)
+    }
+
+    fn upsert(&mut self, record: EventRecord) -> Option<EventRecord> {
+        self.events.insert(record.store_key(), record)
+    }
+
+    fn touch(&mut self, key: &str, seen_at: DateTime<Utc>) -> bool {
+        let Some(event) = self.events.get_mut(key) else {
+            return false;
+        };
+        event.last_seen_at = seen_at;
+        event.missed_syncs = 0;
+        true
+    }
+
+    fn mark_cancelled(&mut self, key: &str, now: DateTime<Utc>) -> bool {
+        let Some(event) = self.events.get_mut(key) else {
+            return false;
+        };
+        if event.is_cancelled() {
+            return false;
+        }
+
+        event.push_revision(now);
+        event.lifecycle = Lifecycle::Cancelled;
+        event.cancelled_at = Some(now);
+        event.status = "cancelled".to_string();
+        event.sequence = event.sequence.saturating_add(1);
+        event.last_modified = now;
+        event.last_seen_at = now;
+        true
+    }
+
+    fn mark_expired(&mut self, key: &str) -> bool {
+        let Some(event) = self.events.get_mut(key) else {
+            return false;
+        };
+        if event.lifecycle != Lifecycle::Active {
+            return false;
+        }
+        event.lifecycle = Lifecycle::Expired;
+        true
+    }
+
+    fn remove(&mut self, key: &str) -> Option<EventRecord> {
+        self.events.remove(key)
+    }
+
+    fn query(&self, query: &EventQuery) -> Vec<&EventRecord> {
+        self.events
+            .values()
+            .filter(|event| query.matches(event))
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.events.len()
+    }
+}
+
+/// [`EventStore`] backed by the pretty-printed JSON state file.
+#[derive(Debug, Clone)]
+pub struct JsonFileStore {
+    path: PathBuf,
+    state: State,
+    _lock: Option<Arc<StateLock>>,
+}
+
+impl JsonFileStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            state: load_state(path)?,
+            _lock: None,
+        })
+    }
+
+    /// Opens the store for writing: takes the [`StateLock`] first, so state
+    /// is read only once no other run can still change it.
+    pub fn open_locked(path: &Path) -> Result<Self> {
+        let lock = StateLock::acquire(path)?;
+        Ok(Self {
+            _lock: Some(Arc::new(lock)),
+            ..Self::open(path)?
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn fetch_validators(&self, source_key: &str) -> Option<&ResourceValidators> {
+        self.state.fetch_validators.get(source_key)
+    }
+
+    pub fn set_fetch_validators(&mut self, source_key: &str, validators: ResourceValidators) {
+        self.state
+            .fetch_validators
+            .insert(source_key.to_string(), validators);
+    }
+
+    pub fn field_coverage(&self, source_key: &str) -> Option<&FieldCoverage> {
+        self.state.field_coverage.get(source_key)
+    }
+
+    pub fn set_field_coverage(&mut self, source_key: &str, coverage: FieldCoverage) {
+        self.state
+            .field_coverage
+            .insert(source_key.to_string(), coverage);
+    }
+
+    pub fn config_hash(&self, source_key: &str) -> Option<&str> {
+        self.state.config_hashes.get(source_key).map(String::as_str)
+    }
+
+    pub fn set_config_hash(&mut self, source_key: &str, hash: String) {
+        self.state
+            .config_hashes
+            .insert(source_key.to_string(), hash);
+    }
+
+    pub fn last_fetched(&self, source_key: &str) -> Option<DateTime<Utc>> {
+        self.state.last_fetched.get(source_key).copied()
+    }
+
+    pub fn set_last_fetched(&mut self, source_key: &str, at: DateTime<Utc>) {
+        self.state.last_fetched.insert(source_key.to_string(), at);
+    }
+
+    /// Captures everything a sync of `source_key` can change, so a failed
+    /// sync of one source can be undone without touching the others.
+    pub fn checkpoint(&self, source_key: &str) -> SourceCheckpoint {
+        SourceCheckpoint {
+            source_key: source_key.to_string(),
+            events: self
+                .state
+                .events
+                .iter()
+                .filter(|(_, event)| event.source_key == source_key)
+                .map(|(key, event)| (key.clone(), event.clone()))
+                .collect(),
+            fetch_validators: self.state.fetch_validators.get(source_key).cloned(),
+            field_coverage: self.state.field_coverage.get(source_key).cloned(),
+            config_hash: self.state.config_hashes.get(source_key).cloned(),
+        }
+    }
+
+    /// Puts the source's events and bookkeeping back as they were at
+    /// `checkpoint`.
+    pub fn rollback(&mut self, checkpoint: SourceCheckpoint) {
+        let SourceCheckpoint {
+            source_key,
+            events,
+            fetch_validators,
+            field_coverage,
+            config_hash,
+        } = checkpoint;
+        self.state
+            .events
+            .retain(|_, event| event.source_key != source_key);
+        self.state.events.extend(events);
+        restore_entry(
+            &mut self.state.fetch_validators,
+            &source_key,
+            fetch_validators,
+        );
+        restore_entry(&mut self.state.field_coverage, &source_key, field_coverage);
+        restore_entry(&mut self.state.config_hashes, &source_key, config_hash);
+    }
+
+    /// Appends a completed run, dropping the oldest beyond
+    /// [`MAX_RUN_HISTORY`].
+    pub fn record_run(&mut self, run: RunRecord) {
+        self.state.runs.push(run);
+        let excess = self.state.runs.len().saturating_sub(MAX_RUN_HISTORY);
+        self.state.runs.drain(..excess);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_state(&self.path, &self.state)
+    }
+}
+
+/// One source's slice of state, from [`JsonFileStore::checkpoint`].
+#[derive(Debug, Clone)]
+pub struct SourceCheckpoint {
+    source_key: String,
+    events: BTreeMap<String, EventRecord>,
+    fetch_validators: Option<ResourceValidators>,
+    field_coverage: Option<FieldCoverage>,
+    config_hash: Option<String>,
+}
+
+fn restore_entry<V>(map: &mut BTreeMap<String, V>, key: &str, value: Option<V>) {
+    match value {
+        Some(value) => {
+            map.insert(key.to_string(), value);
+        }
+        None => {
+            map.remove(key);
+        }
+    }
+}
+
+impl EventStore for JsonFileStore {
+    fn get(&self, key: &str) -> Option<&EventRecord> {
+        self.state.get(key)
+    }
+
+    fn upsert(&mut self, record: EventRecord) -> Option<EventRecord> {
+        self.state.upsert(record)
+    }
+
+    fn touch(&mut self, key: &str, seen_at: DateTime<Utc>) -> bool {
+        self.state.touch(key, seen_at)
+    }
+
+    fn mark_cancelled(&mut self, key: &str, now: DateTime<Utc>) -> bool {
+        self.state.mark_cancelled(key, now)
+    }
+
+    fn mark_expired(&mut self, key: &str) -> bool {
+        self.state.mark_expired(key)
+    }
+
+    fn remove(&mut self, key: &str) -> Option<EventRecord> {
+        self.state.remove(key)
+    }
+
+    fn query(&self, query: &EventQuery) -> Vec<&EventRecord> {
+        self.state.query(query)
+    }
+
+    fn len(&self) -> usize {
+        self.state.len()
+    }
+}
+
+pub fn matches_source_patterns(source_key: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| source_key_matches_pattern(source_key, pattern))
+}
+
+fn source_key_matches_pattern(source_key: &str, pattern: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        source_key.starts_with(prefix)
+    } else {
+        source_key == pattern
+    }
+}
 
 pub fn load_state(path: &Path) -> Result<State> {
     if !path.exists() {
@@ -9,8 +387,9 @@ pub fn load_state(path: &Path) -> Result<State> {
 
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read state file {}", path.display()))?;
-    let state = serde_json::from_str(&content)
+    let mut state: State = serde_json::from_str(&content)
         .with_context(|| format!("failed to parse state file {}", path.display()))?;
+    state.backfill_lifecycle();
     Ok(state)
 }
 
@@ -21,7 +400,96 @@ pub fn save_state(path: &Path, state: &State) -> Result<()> {
     }
 
     let serialized = serde_json::to_string_pretty(state)?;
-    std::fs::write(path, serialized)
-        .with_context(|| format!("failed to write state file {}", path.display()))?;
+    write_atomic(path, serialized.as_bytes())
+        .with_context(|| format!("failed to write state file {}", path.display()))
+}
+
+/// Writes `contents` next to `path`, flushes it to disk and renames it into
+/// place, so a crash or power loss mid-write leaves the previous file
+/// intact rather than an empty or partial one.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file =
+        File::create(&tmp).with_context(|| format!("failed to create {}", tmp.display()))?;
+    file.write_all(contents)
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("failed to write {}", tmp.display()))?;
+    drop(file);
+    std::fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))?;
+    // The rename is only durable once the directory entry is flushed too.
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .with_context(|| format!("failed to sync directory {}", dir.display()))?;
+    }
     Ok(())
 }
+
+/// An exclusive advisory lock on `<state>.lock`, held by commands that
+/// write state so that concurrent runs against one state file take turns
+/// instead of overwriting each other's merges. The operating system
+/// releases it when the process exits, however it exits.
+#[derive(Debug)]
+pub struct StateLock {
+    _file: File,
+}
+
+impl StateLock {
+    /// Takes the lock for the state file at `state_path`, waiting for
+    /// another run to finish when it already holds it.
+    pub fn acquire(state_path: &Path) -> Result<Self> {
+        let path = Self::path_for_state(state_path);
+        let file = open_lock_file(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                info!(lock = %path.display(), "state is locked by another run; waiting");
+                file.lock()
+                    .with_context(|| format!("failed to lock {}", path.display()))?;
+            }
+            Err(TryLockError::Error(err)) => {
+                return Err(err).with_context(|| format!("failed to lock {}", path.display()));
+            }
+        }
+        Ok(Self { _file: file })
+    }
+
+    /// Takes the lock, or returns `None` when another run holds it.
+    pub fn try_acquire(state_path: &Path) -> Result<Option<Self>> {
+        let path = Self::path_for_state(state_path);
+        let file = open_lock_file(&path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => {
+                Err(err).with_context(|| format!("failed to lock {}", path.display()))
+            }
+        }
+    }
+
+    pub fn path_for_state(state_path: &Path) -> PathBuf {
+        let mut path = state_path.as_os_str().to_owned();
+        path.push(".lock");
+        PathBuf::from(path)
+    }
+}
+
+fn open_lock_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create state directory {}", parent.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("failed to open state lock {}", path.display()))
+}
@@ -0,0 +1,103 @@
+//! Fetch state shared by the sources of one tenant.
+//!
+//! Pooled http clients, per-host rate-limit slots, robots.txt rules, OAuth2
+//! tokens, the `network.toml` host policy and the User-Agent rotation live
+//! in a [`Tenant`] owned by whoever runs the pipeline, instead of in
+//! process-wide statics. Pipelines over different config dirs and state
+//! paths can then run side by side in one process (e.g. a service hosting
+//! calendars for several teams) without sharing connections, rate-limit
+//! budgets or credentials, and a caller that keeps its tenant across runs
+//! (the daemon, the TUI) keeps them warm.
+
+use crate::config::NetworkConfig;
+#[cfg(feature = "http")]
+use crate::robots::RobotsRules;
+#[cfg(feature = "http")]
+use reqwest::blocking::Client;
+#[cfg(feature = "http")]
+use std::collections::HashMap;
+#[cfg(feature = "http")]
+use std::sync::Condvar;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "http")]
+use std::time::{Duration, Instant};
+
+/// Default headers, timeout, proxy and same-origin redirect policy of a
+/// pooled client.
+#[cfg(feature = "http")]
+pub(crate) type ClientKey = (
+    Vec<(String, Vec<u8>)>,
+    Option<Duration>,
+    Option<String>,
+    bool,
+);
+/// Origin and User-Agent of a robots.txt.
+#[cfg(feature = "http")]
+pub(crate) type RobotsKey = (String, String);
+/// Token endpoint, client ID and scope of an OAuth2 token.
+#[cfg(feature = "http")]
+pub(crate) type TokenKey = (String, String, Option<String>);
+
+/// The OAuth2 token of one issuer. `refreshing` is set while a token is
+/// being requested, so other sources wait on `ready` instead of requesting
+/// their own.
+#[cfg(feature = "http")]
+#[derive(Debug, Default)]
+pub(crate) struct TokenSlot {
+    pub(crate) state: Mutex<TokenState>,
+    pub(crate) ready: Condvar,
+}
+
+#[cfg(feature = "http")]
+#[derive(Debug, Default)]
+pub(crate) struct TokenState {
+    /// The token and when it expires.
+    pub(crate) token: Option<(String, Instant)>,
+    pub(crate) refreshing: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Tenant {
+    /// Pooled clients and when each was last handed out.
+    #[cfg(feature = "http")]
+    pub(crate) clients: Mutex<HashMap<ClientKey, (Client, Instant)>>,
+    /// Next free request slot per `host:port`.
+    #[cfg(feature = "http")]
+    pub(crate) slots: Mutex<HashMap<String, Instant>>,
+    #[cfg(feature = "http")]
+    pub(crate) robots: Mutex<HashMap<RobotsKey, (Instant, Arc<RobotsRules>)>>,
+    #[cfg(feature = "http")]
+    pub(crate) tokens: Mutex<HashMap<TokenKey, Arc<TokenSlot>>>,
+    network: Mutex<Arc<NetworkConfig>>,
+    pub(crate) rotation: AtomicUsize,
+}
+
+impl Tenant {
+    /// The hosts this tenant's sources may fetch from.
+    pub fn network(&self) -> Arc<NetworkConfig> {
+        Arc::clone(
+            &self
+                .network
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+
+    /// Replaces the host policy, dropping pooled clients built under a
+    /// different one.
+    pub(crate) fn set_network(&self, network: NetworkConfig) {
+        let mut current = self
+            .network
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if **current != network {
+            *current = Arc::new(network);
+            #[cfg(feature = "http")]
+            self.clients
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clear();
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Default timezones for jurisdictions.
+//!
+//! Sources without `source.timezone` read local times as UTC, which is
+//! rarely what a publisher meant. When the timezone is unset, one is derived
+//! from `jurisdiction`, then `default_country`: ISO 3166-1 country codes
+//! (`FR`) and ISO 3166-2 subdivisions (`US-CA`). Longer codes fall back to
+//! their prefix, so `US-TX-HOU` resolves through `US-TX`. Countries that
+//! span several zones map to their capital's or most populous zone, which is
+//! why a derived zone is logged as a warning.
+
+/// ISO 3166-2 subdivisions, checked before countries.
+pub const SUBDIVISIONS: &[(&str, &str)] = &[
+    ("US-AK", "America/Anchorage"),
+    ("US-AL", "America/Chicago"),
+    ("US-AR", "America/Chicago"),
+    ("US-AZ", "America/Phoenix"),
+    ("US-CA", "America/Los_Angeles"),
+    ("US-CO", "America/Denver"),
+    ("US-CT", "America/New_York"),
+    ("US-DC", "America/New_York"),
+    ("US-DE", "America/New_York"),
+    ("US-FL", "America/New_York"),
+    ("US-GA", "America/New_York"),
+    ("US-HI", "Pacific/Honolulu"),
+    ("US-IA", "America/Chicago"),
+    ("US-ID", "America/Boise"),
+    ("US-IL", "America/Chicago"),
+    ("US-IN", "America/Indiana/Indianapolis"),
+    ("US-KS", "America/Chicago"),
+    ("US-KY", "America/New_York"),
+    ("US-LA", "America/Chicago"),
+    ("US-MA", "America/New_York"),
+    ("US-MD", "America/New_York"),
+    ("US-ME", "America/New_York"),
+    ("US-MI", "America/Detroit"),
+    ("US-MN", "America/Chicago"),
+    ("US-MO", "America/Chicago"),
+    ("US-MS", "America/Chicago"),
+    ("US-MT", "America/Denver"),
+    ("US-NC", "America/New_York"),
+    ("US-ND", "America/Chicago"),
+    ("US-NE", "America/Chicago"),
+    ("US-NH", "America/New_York"),
+    ("US-NJ", "America/New_York"),
+    ("US-NM", "America/Denver"),
+    ("US-NV", "America/Los_Angeles"),
+    ("US-NY", "America/New_York"),
+    ("US-OH", "America/New_York"),
+    ("US-OK", "America/Chicago"),
+    ("US-OR", "America/Los_Angeles"),
+    ("US-PA", "America/New_York"),
+    ("US-PR", "America/Puerto_Rico"),
+    ("US-RI", "America/New_York"),
+    ("US-SC", "America/New_York"),
+    ("US-SD", "America/Chicago"),
+    ("US-TN", "America/Chicago"),
+    ("US-TX", "America/Chicago"),
+    ("US-UT", "America/Denver"),
+    ("US-VA", "America/New_York"),
+    ("US-VT", "America/New_York"),
+    ("US-WA", "America/Los_Angeles"),
+    ("US-WI", "America/Chicago"),
+    ("US-WV", "America/New_York"),
+    ("US-WY", "America/Denver"),
+    ("CA-AB", "America/Edmonton"),
+    ("CA-BC", "America/Vancouver"),
+    ("CA-MB", "America/Winnipeg"),
+    ("CA-NB", "America/Moncton"),
+    ("CA-NL", "America/St_Johns"),
+    ("CA-NS", "America/Halifax"),
+    ("CA-ON", "America/Toronto"),
+    ("CA-QC", "America/Toronto"),
+    ("CA-SK", "America/Regina"),
+    ("AU-NSW", "Australia/Sydney"),
+    ("AU-QLD", "Australia/Brisbane"),
+    ("AU-SA", "Australia/Adelaide"),
+    ("AU-VIC", "Australia/Melbourne"),
+    ("AU-WA", "Australia/Perth"),
+];
+
+/// ISO 3166-1 alpha-2 countries.
+pub const COUNTRIES: &[(&str, &str)] = &[
+    ("AD", "Europe/Andorra"),
+    ("AL", "Europe/Tirane"),
+    ("AM", "Asia/Yerevan"),
+    ("AR", "America/Argentina/Buenos_Aires"),
+    ("AT", "Europe/Vienna"),
+    ("AU", "Australia/Sydney"),
+    ("BA", "Europe/Sarajevo"),
+    ("BE", "Europe/Brussels"),
+    ("BG", "Europe/Sofia"),
+    ("BR", "America/Sao_Paulo"),
+    ("BY", "Europe/Minsk"),
+    ("CA", "America/Toronto"),
+    ("CH", "Europe/Zurich"),
+    ("CL", "America/Santiago"),
+    ("CN", "Asia/Shanghai"),
+    ("CO", "America/Bogota"),
+    ("CY", "Asia/Nicosia"),
+    ("CZ", "Europe/Prague"),
+    ("DE", "Europe/Berlin"),
+    ("DK", "Europe/Copenhagen"),
+    ("EE", "Europe/Tallinn"),
+    ("ES", "Europe/Madrid"),
+    ("FI", "Europe/Helsinki"),
+    ("FR", "Europe/Paris"),
+    ("GB", "Europe/London"),
+    ("GE", "Asia/Tbilisi"),
+    ("GR", "Europe/Athens"),
+    ("HR", "Europe/Zagreb"),
+    ("HU", "Europe/Budapest"),
+    ("IE", "Europe/Dublin"),
+    ("IL", "Asia/Jerusalem"),
+    ("IN", "Asia/Kolkata"),
+    ("IS", "Atlantic/Reykjavik"),
+    ("IT", "Europe/Rome"),
+    ("JP", "Asia/Tokyo"),
+    ("KR", "Asia/Seoul"),
+    ("LI", "Europe/Vaduz"),
+    ("LT", "Europe/Vilnius"),
+    ("LU", "Europe/Luxembourg"),
+    ("LV", "Europe/Riga"),
+    ("MC", "Europe/Monaco"),
+    ("MD", "Europe/Chisinau"),
+    ("ME", "Europe/Podgorica"),
+    ("MK", "Europe/Skopje"),
+    ("MT", "Europe/Malta"),
+    ("MX", "America/Mexico_City"),
+    ("NL", "Europe/Amsterdam"),
+    ("NO", "Europe/Oslo"),
+    ("NZ", "Pacific/Auckland"),
+    ("PL", "Europe/Warsaw"),
+    ("PT", "Europe/Lisbon"),
+    ("RO", "Europe/Bucharest"),
+    ("RS", "Europe/Belgrade"),
+    ("RU", "Europe/Moscow"),
+    ("SE", "Europe/Stockholm"),
+    ("SG", "Asia/Singapore"),
+    ("SI", "Europe/Ljubljana"),
+    ("SK", "Europe/Bratislava"),
+    ("SM", "Europe/San_Marino"),
+    ("TR", "Europe/Istanbul"),
+    ("UA", "Europe/Kyiv"),
+    ("US", "America/New_York"),
+    ("VA", "Europe/Vatican"),
+    ("XK", "Europe/Belgrade"),
+    ("ZA", "Africa/Johannesburg"),
+];
+
+/// The default zone for a country or subdivision code, trying shorter
+/// prefixes of hyphenated codes.
+pub fn timezone_for_code(code: &str) -> Option<&'static str> {
+    let mut code = code.trim().to_ascii_uppercase();
+    loop {
+        let table = if code.contains('-') {
+            SUBDIVISIONS
+        } else {
+            COUNTRIES
+        };
+        if let Some((_, zone)) = table.iter().find(|(key, _)| *key == code) {
+            return Some(zone);
+        }
+        let (prefix, _) = code.rsplit_once('-')?;
+        code = prefix.to_string();
+    }
+}
+
+/// The zone derived from `jurisdiction`, then `default_country`, with the
+/// code it came from.
+pub fn derive_timezone<'a>(
+    jurisdiction: Option<&'a str>,
+    default_country: Option<&'a str>,
+) -> Option<(&'static str, &'a str)> {
+    [jurisdiction, default_country]
+        .into_iter()
+        .flatten()
+        .find_map(|code| timezone_for_code(code).map(|zone| (zone, code)))
+}
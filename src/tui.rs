@@ -0,0 +1,441 @@
+//! Terminal UI for `rics tui`.
+//!
+//! Browses the configured sources with, for the selected one, its upcoming
+//! events, its most recently changed events and its health over the run
+//! history. The state file is only read; the one write path is `s`, which
+//! syncs the selected source through the usual pipeline and reloads.
+
+use crate::config::load_sources_from_dir;
+use crate::health::{HealthTracker, SourceHealth};
+use crate::model::{EventRecord, State};
+use crate::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use crate::tenant::Tenant;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table, Tabs};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Events listed per pane.
+const PANE_ROWS: usize = 200;
+
+const HELP: &str = "↑/↓ source  ←/→ pane  s sync  r reload  q quit";
+
+#[derive(Debug, Clone)]
+pub struct TuiOptions {
+    pub config_dir: PathBuf,
+    pub state_path: PathBuf,
+    pub out_dir: PathBuf,
+    /// A source is shown as stale after this long without a successful
+    /// sync.
+    pub stale_after: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pane {
+    #[default]
+    Upcoming,
+    Changes,
+    Health,
+}
+
+impl Pane {
+    const ALL: [Pane; 3] = [Pane::Upcoming, Pane::Changes, Pane::Health];
+
+    fn title(self) -> &'static str {
+        match self {
+            Pane::Upcoming => "Upcoming",
+            Pane::Changes => "Recent changes",
+            Pane::Health => "Health",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|pane| *pane == self).unwrap_or(0)
+    }
+}
+
+/// What a key press asks the caller to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Sync,
+    Reload,
+}
+
+struct SourceEntry {
+    key: String,
+    name: String,
+    enabled: bool,
+}
+
+pub struct App {
+    options: TuiOptions,
+    /// Kept across syncs, so connections and rate limits carry over.
+    tenant: Arc<Tenant>,
+    sources: Vec<SourceEntry>,
+    state: State,
+    health: HealthTracker,
+    selected: ListState,
+    pub pane: Pane,
+    /// Outcome of the last sync or reload, shown in the status line.
+    pub message: String,
+}
+
+impl App {
+    pub fn new(options: TuiOptions) -> Result<Self> {
+        let mut app = Self {
+            options,
+            tenant: Arc::default(),
+            sources: Vec::new(),
+            state: State::default(),
+            health: HealthTracker::default(),
+            selected: ListState::default(),
+            pane: Pane::default(),
+            message: String::new(),
+        };
+        app.reload()?;
+        app.selected.select((!app.sources.is_empty()).then_some(0));
+        Ok(app)
+    }
+
+    /// Re-reads the source configs and the state file.
+    pub fn reload(&mut self) -> Result<()> {
+        let mut sources = load_sources_from_dir(&self.options.config_dir)?
+            .into_iter()
+            .map(|source| SourceEntry {
+                key: source.config.source.key,
+                name: source.config.source.name,
+                enabled: source.config.source.enabled,
+            })
+            .collect::<Vec<_>>();
+        sources.sort_by(|a, b| a.key.cmp(&b.key));
+        self.sources = sources;
+        self.state = load_state_for_read(&self.options.state_path)?;
+        if let Some(selected) = self.selected.selected() {
+            let last = self.sources.len().saturating_sub(1);
+            self.selected.select(Some(selected.min(last)));
+        }
+        self.message = format!(
+            "{} sources, {} events",
+            self.sources.len(),
+            self.state.events.len()
+        );
+        Ok(())
+    }
+
+    pub fn selected_source(&self) -> Option<&str> {
+        self.selected
+            .selected()
+            .and_then(|index| self.sources.get(index))
+            .map(|source| source.key.as_str())
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<Action> {
+        let panes = Pane::ALL.len();
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+            KeyCode::Char('s') => return Some(Action::Sync),
+            KeyCode::Char('r') => return Some(Action::Reload),
+            KeyCode::Down | KeyCode::Char('j') => self.selected.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+            KeyCode::Right | KeyCode::Tab | KeyCode::Char('l') => {
+                self.pane = Pane::ALL[(self.pane.index() + 1) % panes];
+            }
+            KeyCode::Left | KeyCode::BackTab | KeyCode::Char('h') => {
+                self.pane = Pane::ALL[(self.pane.index() + panes - 1) % panes];
+            }
+            KeyCode::Char(digit @ '1'..='3') => {
+                self.pane = Pane::ALL[digit as usize - '1' as usize];
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Syncs the selected source and reloads state. Failures are kept for
+    /// the health pane and reported in the status line rather than
+    /// returned.
+    pub fn sync_selected(&mut self) {
+        let Some(key) = self.selected_source().map(str::to_string) else {
+            return;
+        };
+        let reports = sync_sources(&SyncOptions {
+            config_dir: self.options.config_dir.clone(),
+            state_path: self.options.state_path.clone(),
+            out_dir: self.options.out_dir.clone(),
+            source: Some(key.clone()),
+            jobs: 1,
+            force: true,
+            tenant: Arc::clone(&self.tenant),
+            ..SyncOptions::default()
+        });
+        let error = match reports {
+            Ok(reports) => reports.into_iter().find_map(|report| report.error),
+            Err(err) => Some(format!("{err:#}")),
+        };
+        match &error {
+            Some(error) => self.health.record_failure([key.as_str()], error),
+            None => self.health.record_success([key.as_str()]),
+        }
+        let reloaded = self.reload();
+        self.message = match (error, reloaded) {
+            (Some(error), _) => format!("sync of {key} failed: {error}"),
+            (None, Err(err)) => format!("synced {key}, but reloading failed: {err:#}"),
+            (None, Ok(())) => format!("synced {key}"),
+        };
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Min(0)]).areas(main);
+
+        let health = self.health_status(Utc::now());
+        let items = self
+            .sources
+            .iter()
+            .map(|source| {
+                let healthy = health
+                    .iter()
+                    .find(|entry| entry.source_key == source.key)
+                    .is_some_and(|entry| entry.healthy);
+                let marker = match (source.enabled, healthy) {
+                    (false, _) => "-",
+                    (true, true) => "✓",
+                    (true, false) => "!",
+                };
+                ListItem::new(format!("{marker} {}", source.key))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(Block::bordered().title("Sources"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.selected);
+
+        let [tabs, body] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(right);
+        let titles = Pane::ALL.iter().map(|pane| pane.title());
+        let heading = self
+            .selected
+            .selected()
+            .and_then(|index| self.sources.get(index))
+            .map(|source| source.name.clone())
+            .unwrap_or_default();
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(self.pane.index())
+                .block(Block::bordered().title(heading)),
+            tabs,
+        );
+        match self.pane {
+            Pane::Upcoming => self.render_upcoming(frame, body),
+            Pane::Changes => self.render_changes(frame, body),
+            Pane::Health => self.render_health(frame, body, &health),
+        }
+
+        frame.render_widget(
+            Paragraph::new(format!("{}  |  {HELP}", self.message)),
+            status,
+        );
+    }
+
+    fn source_events(&self) -> impl Iterator<Item = &EventRecord> {
+        let key = self.selected_source().unwrap_or_default().to_string();
+        self.state
+            .events
+            .values()
+            .filter(move |event| event.source_key == key)
+    }
+
+    fn render_upcoming(&self, frame: &mut Frame, area: Rect) {
+        let today = Utc::now().date_naive();
+        let mut events = self
+            .source_events()
+            .filter_map(|event| Some((event.time.start_date()?, event)))
+            .filter(|(date, _)| *date >= today)
+            .collect::<Vec<_>>();
+        events.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.title.cmp(&b.1.title)));
+        let rows = events.into_iter().take(PANE_ROWS).map(|(date, event)| {
+            Row::new([date.to_string(), event.status.clone(), event.title.clone()])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Min(0),
+            ],
+        )
+        .header(Row::new(["date", "status", "title"]).style(bold()))
+        .block(Block::bordered());
+        frame.render_widget(table, area);
+    }
+
+    fn render_changes(&self, frame: &mut Frame, area: Rect) {
+        let mut events = self.source_events().collect::<Vec<_>>();
+        events.sort_by_key(|event| std::cmp::Reverse(event.last_modified));
+        let rows = events.into_iter().take(PANE_ROWS).map(|event| {
+            let change = if event.cancelled_at.is_some() {
+                "cancelled"
+            } else if event.sequence == 0 {
+                "new"
+            } else {
+                "updated"
+            };
+            Row::new([
+                event.last_modified.format("%Y-%m-%d %H:%M").to_string(),
+                change.to_string(),
+                event.title.clone(),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Length(10),
+                Constraint::Min(0),
+            ],
+        )
+        .header(Row::new(["modified", "change", "title"]).style(bold()))
+        .block(Block::bordered());
+        frame.render_widget(table, area);
+    }
+
+    fn render_health(&self, frame: &mut Frame, area: Rect, health: &[SourceHealth]) {
+        let Some(key) = self.selected_source() else {
+            frame.render_widget(Block::bordered(), area);
+            return;
+        };
+        let [summary, runs] =
+            Layout::vertical([Constraint::Length(6), Constraint::Min(0)]).areas(area);
+
+        let mut lines = Vec::new();
+        if let Some(entry) = health.iter().find(|entry| entry.source_key == key) {
+            lines.push(Line::from(format!(
+                "status: {}",
+                if entry.healthy {
+                    "healthy"
+                } else if entry.consecutive_failures > 0 {
+                    "failing"
+                } else {
+                    "stale"
+                }
+            )));
+            lines.push(Line::from(format!(
+                "last success: {}",
+                entry
+                    .last_success
+                    .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                    .unwrap_or_else(|| "never".to_string())
+            )));
+            lines.push(Line::from(format!(
+                "failures since: {}",
+                entry.consecutive_failures
+            )));
+            if let Some(error) = &entry.last_error {
+                lines.push(Line::from(format!("last error: {error}")));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered()), summary);
+
+        let rows = self.state.runs.iter().rev().filter_map(|run| {
+            let summary = run.source(key)?;
+            Some(Row::new([
+                run.started_at.format("%Y-%m-%d %H:%M").to_string(),
+                summary.records_parsed.to_string(),
+                summary.inserted.to_string(),
+                summary.updated.to_string(),
+                summary.cancelled.to_string(),
+                summary.warnings.to_string(),
+                format!("{}ms", summary.duration_ms),
+            ]))
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(16),
+                Constraint::Length(7),
+                Constraint::Length(7),
+                Constraint::Length(7),
+                Constraint::Length(9),
+                Constraint::Length(8),
+                Constraint::Min(0),
+            ],
+        )
+        .header(
+            Row::new([
+                "run",
+                "parsed",
+                "new",
+                "updated",
+                "cancelled",
+                "warnings",
+                "time",
+            ])
+            .style(bold()),
+        )
+        .block(Block::bordered().title("Runs"));
+        frame.render_widget(table, runs);
+    }
+
+    fn health_status(&self, now: DateTime<Utc>) -> Vec<SourceHealth> {
+        let keys = self
+            .sources
+            .iter()
+            .map(|source| source.key.clone())
+            .collect::<Vec<_>>();
+        self.health
+            .status(&keys, &self.state, self.options.stale_after, now)
+            .sources
+    }
+}
+
+fn bold() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
+}
+
+/// Runs the UI until `q`, restoring the terminal on the way out.
+pub fn run_tui(options: TuiOptions) -> Result<()> {
+    let mut app = App::new(options)?;
+    let mut terminal = ratatui::try_init().context("failed to set up the terminal")?;
+    let result = event_loop(&mut app, &mut terminal);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(app: &mut App, terminal: &mut ratatui::DefaultTerminal) -> Result<()> {
+    loop {
+        terminal.draw(|frame| app.render(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match app.handle_key(key.code) {
+            Some(Action::Quit) => return Ok(()),
+            Some(Action::Sync) => {
+                if let Some(source) = app.selected_source() {
+                    app.message = format!("syncing {source}...");
+                    terminal.draw(|frame| app.render(frame))?;
+                }
+                app.sync_selected();
+                // Sync logs go to stderr, over the UI.
+                terminal.clear()?;
+            }
+            Some(Action::Reload) => {
+                if let Err(err) = app.reload() {
+                    app.message = format!("reload failed: {err:#}");
+                }
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::fetch::FetchedDocument;
+use crate::ics::render_named_year_calendar;
+use crate::parser::preview_source_events;
+use crate::pipeline::preview_records_with_uids;
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+/// Parses `body` with the source config in `config_toml` and returns the
+/// candidate events as a JSON array.
+#[wasm_bindgen(js_name = previewEvents)]
+pub fn preview_events(config_toml: &str, body: &str, source_url: &str) -> Result<String, JsError> {
+    let candidates = preview_source_events(config_toml, &[preview_document(body, source_url)])
+        .map_err(|err| JsError::new(&format!("{err:#}")))?;
+    serde_json::to_string(&candidates).map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Parses `body` and renders the events falling in `year` as an ICS calendar.
+#[wasm_bindgen(js_name = previewCalendar)]
+pub fn preview_calendar(
+    config_toml: &str,
+    body: &str,
+    source_url: &str,
+    year: i32,
+) -> Result<String, JsError> {
+    let candidates = preview_source_events(config_toml, &[preview_document(body, source_url)])
+        .map_err(|err| JsError::new(&format!("{err:#}")))?;
+    let calendar_name = candidates
+        .first()
+        .map(|event| event.source_name.clone())
+        .unwrap_or_else(|| "rics preview".to_string());
+    let config = crate::config::parse_source_config(config_toml)
+        .map_err(|err| JsError::new(&format!("{err:#}")))?;
+    let records = preview_records_with_uids(candidates, &config.uid)
+        .map_err(|err| JsError::new(&format!("{err:#}")))?;
+    let in_year = records
+        .iter()
+        .filter(|event| event.year_bucket() == Some(year))
+        .collect::<Vec<_>>();
+    Ok(render_named_year_calendar(
+        &calendar_name,
+        year,
+        &in_year,
+        &config.publish,
+    ))
+}
+
+fn preview_document(body: &str, source_url: &str) -> FetchedDocument {
+    FetchedDocument {
+        source_url: source_url.to_string(),
+        body: body.as_bytes().to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: BTreeMap::new(),
+    }
+}
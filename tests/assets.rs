@@ -0,0 +1,186 @@
+#![cfg(feature = "http")]
+
+use anyhow::Result;
+use rics::assets::asset_file_name;
+use rics::pipeline::{SyncOptions, sync_sources};
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::tempdir;
+use tiny_http::{Response, Server};
+
+const BODY: &str = r#"<html><body>
+<article class="item"><span class="title">Board meeting</span><time>2030-03-01</time>
+  <a class="doc" href="/docs/agenda.pdf">Agenda</a></article>
+<article class="item"><span class="title">Annual report</span><time>2030-06-15</time>
+  <a class="link" href="/docs/annual%20report.pdf">Report</a></article>
+</body></html>"#;
+
+#[test]
+fn sync_mirrors_linked_documents_and_rewrites_attach_and_url() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let document_gets = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&document_gets);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let body = if request.url().starts_with("/docs/") {
+                counter.fetch_add(1, Ordering::SeqCst);
+                format!("%PDF-1.4 {}", request.url())
+            } else {
+                BODY.to_string()
+            };
+            let _ = request.respond(Response::from_string(body));
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("board.toml"),
+        format!(
+            r#"[source]
+key = "test.assets"
+name = "Assets Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/meetings.html"
+retry_attempts = 1
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.attachments]
+from = "css:a.doc@href"
+optional = true
+absolutize = true
+
+[map.url]
+from = "css:a.link@href"
+optional = true
+absolutize = true
+
+[publish.assets]
+mirror = true
+base_url = "https://cdn.example.org/assets/"
+"#
+        ),
+    )?;
+
+    let out_dir = root.path().join("out");
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: out_dir.clone(),
+        ..SyncOptions::default()
+    };
+    sync_sources(&options)?;
+
+    let agenda_url = format!("http://127.0.0.1:{port}/docs/agenda.pdf");
+    let report_url = format!("http://127.0.0.1:{port}/docs/annual%20report.pdf");
+    let agenda_file = asset_file_name(&agenda_url);
+    let report_file = asset_file_name(&report_url);
+    assert!(report_file.ends_with("-annual_20report.pdf"));
+    let mirrored = fs::read_to_string(out_dir.join("assets/test-assets").join(&agenda_file))?;
+    assert_eq!(mirrored, "%PDF-1.4 /docs/agenda.pdf");
+
+    let ics = fs::read_to_string(out_dir.join("sources/test-assets/test-assets-2030.ics"))?
+        .replace("\r\n ", "");
+    assert!(ics.contains(&format!(
+        "ATTACH:https://cdn.example.org/assets/test-assets/{agenda_file}"
+    )));
+    assert!(ics.contains(&format!(
+        "URL:https://cdn.example.org/assets/test-assets/{report_file}"
+    )));
+    assert!(!ics.contains(&format!("ATTACH:{agenda_url}")));
+
+    sync_sources(&options)?;
+    assert_eq!(document_gets.load(Ordering::SeqCst), 2);
+    let state = fs::read_to_string(root.path().join("state/events.json"))?;
+    assert!(state.contains("cdn.example.org"));
+
+    Ok(())
+}
+
+#[test]
+fn asset_downloads_follow_the_network_policy() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let document_gets = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&document_gets);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let _ = request.respond(Response::from_string("%PDF-1.4"));
+        }
+    });
+
+    let sync = |host: &str, network: &str| -> Result<String> {
+        let root = tempdir()?;
+        let config_dir = root.path().join("sources");
+        fs::create_dir_all(&config_dir)?;
+        fs::write(root.path().join("network.toml"), network)?;
+        fs::write(
+            config_dir.join("board.toml"),
+            format!(
+                r#"[source]
+key = "test.guarded"
+name = "Guarded Assets"
+domain = "publishing"
+
+[fetch]
+mode = "inline"
+inline_data = '<article><span class="title">Board meeting</span><time>2030-03-01</time><a href="http://{host}:{port}/docs/agenda.pdf">Agenda</a></article>'
+
+[extract]
+root_selector = "article"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.attachments]
+from = "css:a@href"
+
+[publish.assets]
+mirror = true
+base_url = "https://cdn.example.org/assets/"
+"#
+            ),
+        )?;
+        let out_dir = root.path().join("out");
+        sync_sources(&SyncOptions {
+            config_dir,
+            state_path: root.path().join("state/events.json"),
+            out_dir: out_dir.clone(),
+            jobs: 1,
+            ..SyncOptions::default()
+        })?;
+        Ok(
+            fs::read_to_string(out_dir.join("sources/test-guarded/test-guarded-2030.ics"))?
+                .replace("\r\n ", ""),
+        )
+    };
+
+    // Refused links are kept as they are, without a request.
+    let ics = sync("localhost", "deny_hosts = [\"localhost\"]")?;
+    assert!(ics.contains(&format!("ATTACH:http://localhost:{port}/docs/agenda.pdf")));
+    let ics = sync("127.0.0.1", "block_private_ips = true")?;
+    assert!(ics.contains(&format!("ATTACH:http://127.0.0.1:{port}/docs/agenda.pdf")));
+    assert_eq!(document_gets.load(Ordering::SeqCst), 0);
+
+    let ics = sync("127.0.0.1", "")?;
+    assert!(ics.contains("ATTACH:https://cdn.example.org/assets/"));
+    assert_eq!(document_gets.load(Ordering::SeqCst), 1);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use rics::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn capture_raw_saves_each_records_matched_html_by_uid() -> Result<()> {
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("board.toml"),
+        r#"[source]
+key = "test.capture"
+name = "Capture Fixture"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = """<html><body>
+<article class="item"><span class="title">Board meeting</span><time>2030-03-01</time><a href="https://example.org/m/1">Minutes</a></article>
+<article class="item"><span class="title">Budget hearing</span><time>2030-04-02</time><a href="https://example.org/m/2">Minutes</a></article>
+</body></html>"""
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+"#,
+    )?;
+
+    let out_dir = root.path().join("out");
+    let mut options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: out_dir.clone(),
+        ..SyncOptions::default()
+    };
+    sync_sources(&options)?;
+    assert!(!out_dir.join("debug").exists());
+
+    options.capture_raw = true;
+    sync_sources(&options)?;
+    let raw_dir = out_dir.join("debug/raw/test-capture");
+    for event in load_state_for_read(&options.state_path)?.events.values() {
+        let fragment = fs::read_to_string(raw_dir.join(format!("{}.html", event.uid)))?;
+        assert!(fragment.starts_with("<article class=\"item\">"));
+        assert!(fragment.contains(&format!("<span class=\"title\">{}</span>", event.title)));
+    }
+    assert_eq!(fs::read_dir(&raw_dir)?.count(), 2);
+
+    Ok(())
+}
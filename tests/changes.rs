@@ -0,0 +1,157 @@
+mod common;
+
+use anyhow::Result;
+use common::{sync_options, write_json_source};
+use rics::changes::ChangeEvent;
+use rics::pipeline::sync_sources;
+use rics::store::load_state;
+use std::fs;
+use std::path::Path;
+
+fn read_feed(path: &Path) -> Result<Vec<ChangeEvent>> {
+    fs::read_to_string(path)?
+        .lines()
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+#[test]
+fn feed_appends_one_line_per_change() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        root.path().join("feed.toml"),
+        "path = \"feed/changes.jsonl\"\n",
+    )?;
+    let options = sync_options(root.path());
+    let feed_path = options.out_dir.join("feed/changes.jsonl");
+
+    write_json_source(
+        &config_dir.join("council.toml"),
+        "changes.council",
+        "Council",
+        "government",
+        r#"[{"id": "a", "title": "Budget hearing", "date": "2030-05-01"},
+            {"id": "b", "title": "Zoning session", "date": "2030-05-02"}]"#,
+        "",
+    )?;
+    sync_sources(&options)?;
+    let feed = read_feed(&feed_path)?;
+    assert_eq!(
+        feed.iter().map(|e| e.kind.as_str()).collect::<Vec<_>>(),
+        vec!["event.created", "event.created"]
+    );
+    let hearing = feed
+        .iter()
+        .find(|e| e.title == "Budget hearing")
+        .expect("hearing in feed");
+    assert_eq!(hearing.start.as_deref(), Some("2030-05-01"));
+    assert_eq!(hearing.end.as_deref(), Some("2030-05-02"));
+    assert_eq!(hearing.all_day, Some(true));
+
+    // A sync without changes appends nothing.
+    sync_sources(&options)?;
+    assert_eq!(read_feed(&feed_path)?.len(), 2);
+
+    write_json_source(
+        &config_dir.join("council.toml"),
+        "changes.council",
+        "Council",
+        "government",
+        r#"[{"id": "a", "title": "Budget hearing", "date": "2030-05-08"}]"#,
+        "",
+    )?;
+    sync_sources(&options)?;
+    let feed = read_feed(&feed_path)?;
+    let latest = feed[2..]
+        .iter()
+        .map(|e| (e.kind.as_str(), e.title.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(latest.len(), 2);
+    assert!(latest.contains(&("event.updated", "Budget hearing")));
+    assert!(latest.contains(&("event.cancelled", "Zoning session")));
+    let ids = feed
+        .iter()
+        .map(|e| e.id.as_str())
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(ids.len(), feed.len());
+
+    Ok(())
+}
+
+#[test]
+fn reports_feed_and_run_history_share_the_run_id() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(root.path().join("feed.toml"), "")?;
+    write_json_source(
+        &config_dir.join("council.toml"),
+        "changes.council",
+        "Council",
+        "government",
+        r#"[{"id": "a", "title": "Budget hearing", "date": "2030-05-01"}]"#,
+        "",
+    )?;
+    let options = sync_options(root.path());
+
+    let first = sync_sources(&options)?;
+    let second = sync_sources(&options)?;
+    let run_id = &first[0].run_id;
+    assert!(!run_id.is_empty());
+    assert_ne!(run_id, &second[0].run_id);
+
+    let feed = read_feed(&options.out_dir.join("changes.jsonl"))?;
+    assert_eq!(feed.len(), 1);
+    assert_eq!(&feed[0].run_id, run_id);
+    let state = load_state(&options.state_path)?;
+    assert_eq!(
+        state.runs.iter().map(|run| &run.run_id).collect::<Vec<_>>(),
+        vec![run_id, &second[0].run_id]
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn feed_posts_each_change_to_the_webhook() -> Result<()> {
+    let server = tiny_http::Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let receiver = std::thread::spawn(move || -> Result<Vec<ChangeEvent>> {
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let mut request = server.recv()?;
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body)?;
+            received.push(serde_json::from_str(&body)?);
+            request.respond(tiny_http::Response::empty(204))?;
+        }
+        Ok(received)
+    });
+
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        root.path().join("feed.toml"),
+        format!("webhook_url = \"http://127.0.0.1:{port}/hooks/rics\"\n"),
+    )?;
+    write_json_source(
+        &config_dir.join("council.toml"),
+        "changes.council",
+        "Council",
+        "government",
+        r#"[{"id": "a", "title": "Budget hearing", "date": "2030-05-01"},
+            {"id": "b", "title": "Zoning session", "date": "2030-05-02"}]"#,
+        "",
+    )?;
+    let options = sync_options(root.path());
+    sync_sources(&options)?;
+
+    let received = receiver.join().expect("receiver thread")?;
+    assert_eq!(received, read_feed(&options.out_dir.join("changes.jsonl"))?);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use encoding_rs::{SHIFT_JIS, UTF_8, UTF_16LE, WINDOWS_1252};
+use rics::charset::{decode_body, detect_encoding};
+use rics::fetch::FetchedDocument;
+use rics::parser::preview_source_events;
+use std::collections::BTreeMap;
+
+#[test]
+fn the_content_type_charset_decodes_latin_1_pages() {
+    let body = b"<p>Conf\xe9rence de presse</p>";
+    assert_eq!(
+        decode_body(body, Some("text/html; charset=ISO-8859-1")),
+        "<p>Conférence de presse</p>"
+    );
+    // Without any declaration, legacy pages fall back to Windows-1252.
+    assert_eq!(detect_encoding(body, None), WINDOWS_1252);
+}
+
+#[test]
+fn meta_and_xml_declarations_name_the_charset_when_headers_do_not() {
+    let (title, _, _) = SHIFT_JIS.encode("東京都議会");
+    let html = [
+        br#"<html><head><meta http-equiv="Content-Type" content="text/html; charset=Shift_JIS">"#
+            .as_slice(),
+        &title,
+        b"</head></html>",
+    ]
+    .concat();
+    assert_eq!(detect_encoding(&html, Some("text/html")), SHIFT_JIS);
+    assert!(decode_body(&html, None).contains("東京都議会"));
+
+    let xml =
+        b"<?xml version='1.0' encoding='windows-1252'?><rss><title>\x93Budget\x94</title></rss>";
+    assert!(decode_body(xml, None).contains("\u{201c}Budget\u{201d}"));
+}
+
+#[test]
+fn utf8_bodies_win_over_a_wrong_declaration_but_not_over_a_bom() {
+    let body = "<meta charset=\"iso-8859-1\"><p>Café</p>".as_bytes();
+    assert_eq!(
+        detect_encoding(body, Some("text/html; charset=iso-8859-1")),
+        UTF_8
+    );
+
+    let utf16 = [0xFF, 0xFE]
+        .into_iter()
+        .chain("Budget".encode_utf16().flat_map(u16::to_le_bytes))
+        .collect::<Vec<u8>>();
+    assert_eq!(
+        detect_encoding(&utf16, Some("text/html; charset=utf-8")),
+        UTF_16LE
+    );
+    assert_eq!(decode_body(&utf16, None), "Budget");
+}
+
+#[test]
+fn html_extraction_reads_windows_1252_pages() -> Result<()> {
+    let config = r#"[source]
+key = "test.legacy"
+name = "Legacy"
+domain = "government"
+
+[fetch]
+base_url = "https://agency.example/agenda"
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#;
+    let doc = FetchedDocument {
+        source_url: "https://agency.example/agenda".to_string(),
+        body: b"<ul><li><span class=\"title\">R\xe9union du conseil \x96 budget</span><time>2030-03-01</time></li></ul>".to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: BTreeMap::from([(
+            "content-type".to_string(),
+            "text/html; charset=windows-1252".to_string(),
+        )]),
+    };
+    let events = preview_source_events(config, &[doc])?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].title, "Réunion du conseil – budget");
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! Fixtures shared by the integration tests. Each test binary uses only
+//! some of them.
+#![allow(dead_code)]
+
+use anyhow::Result;
+use rics::pipeline::SyncOptions;
+use std::fs;
+use std::path::Path;
+use tiny_http::{Request, Response, Server};
+
+/// Writes an inline json source to `path`. `rows` is a JSON array whose
+/// objects map `id` (optional), `title` and `date`; `extra` is TOML
+/// appended after those mappings, e.g. more `[map.*]` tables.
+pub fn write_json_source(
+    path: &Path,
+    key: &str,
+    name: &str,
+    domain: &str,
+    rows: &str,
+    extra: &str,
+) -> Result<()> {
+    fs::write(
+        path,
+        format!(
+            r#"[source]
+key = "{key}"
+name = "{name}"
+domain = "{domain}"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": {rows}}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+optional = true
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+{extra}
+"#
+        ),
+    )?;
+    Ok(())
+}
+
+/// An inline text source with one `date | title` line.
+pub fn text_source(key: &str, title: &str) -> String {
+    format!(
+        r#"[source]
+key = "{key}"
+name = "{title}"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = "2030-05-01 | {title}"
+
+[extract]
+format = "text"
+"#
+    )
+}
+
+/// Sync options for configs in `<root>/sources`, with the state and output
+/// under `root` too.
+pub fn sync_options(root: &Path) -> SyncOptions {
+    SyncOptions {
+        config_dir: root.join("sources"),
+        state_path: root.join("state/events.json"),
+        out_dir: root.join("out"),
+        ..SyncOptions::default()
+    }
+}
+
+/// Serves one `<article class="item">` titled `Notice <request url>` per
+/// request on a local port, which it returns. `handle` sees every request
+/// first and may answer it with its own body instead.
+pub fn serve_notices(
+    handle: impl Fn(&Request) -> Option<String> + Send + 'static,
+) -> Result<u16> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let body = handle(&request).unwrap_or_else(|| {
+                format!(
+                    r#"<article class="item"><span class="title">Notice {}</span><time>2030-03-01</time></article>"#,
+                    request.url()
+                )
+            });
+            let _ = request.respond(Response::from_string(body));
+        }
+    });
+    Ok(port)
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use anyhow::Result;
+use common::text_source;
+use rics::config::{
+    join_config_dirs, load_global_categorize, load_sources_from_dir, parse_source_config,
+};
+use rics::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use std::fs;
+
+#[test]
+fn later_config_dirs_override_earlier_ones_by_source_key() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let base = root.path().join("base/sources");
+    let local = root.path().join("local/sources");
+    fs::create_dir_all(&base)?;
+    fs::create_dir_all(&local)?;
+    fs::write(base.join("a.toml"), text_source("roots.a", "Shared hearing"))?;
+    fs::write(base.join("b.toml"), text_source("roots.b", "Shared session"))?;
+    fs::write(local.join("b-private.toml"), text_source("roots.b", "Private session"))?;
+    fs::write(local.join("c.toml"), text_source("roots.c", "Private forum"))?;
+    fs::write(
+        root.path().join("base/categorize.toml"),
+        "[[rules]]\npattern = \"hearing\"\ncategories = [\"hearings\"]\n",
+    )?;
+
+    let config_dir = join_config_dirs(&[base.clone(), local.clone()])?;
+    let sources = load_sources_from_dir(&config_dir)?;
+    assert_eq!(
+        sources
+            .iter()
+            .map(|source| (
+                source.config.source.key.as_str(),
+                source.path.starts_with(&local)
+            ))
+            .collect::<Vec<_>>(),
+        vec![("roots.a", false), ("roots.b", true), ("roots.c", true)]
+    );
+    // Shared configs are found next to any root.
+    assert_eq!(load_global_categorize(&config_dir)?.rules.len(), 1);
+
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+    sync_sources(&options)?;
+    let mut titles = load_state_for_read(&options.state_path)?
+        .events
+        .into_values()
+        .map(|event| event.title)
+        .collect::<Vec<_>>();
+    titles.sort();
+    assert_eq!(
+        titles,
+        vec!["Private forum", "Private session", "Shared hearing"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn browser_mode_renders_one_page_and_checks_its_wait_selector() -> Result<()> {
+    let config = |selector: &str, extra: &str| {
+        format!(
+            r#"[source]
+key = "rendered.calendar"
+name = "Rendered Calendar"
+domain = "government"
+
+[fetch]
+mode = "browser"
+base_url = "https://example.invalid/calendar"
+
+[fetch.browser]
+wait_selector = "{selector}"
+
+[extract]
+root_selector = ".event"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+{extra}"#
+        )
+    };
+
+    let parsed = parse_source_config(&config(".event", ""))?;
+    assert_eq!(
+        parsed.fetch.browser.wait_selector.as_deref(),
+        Some(".event")
+    );
+    assert!(parsed.fetch.browser.sandbox);
+
+    let err =
+        parse_source_config(&config(".event", "\n[pagination]\nenabled = true\n")).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("browser mode renders one page"),
+        "{err:#}"
+    );
+    let err = parse_source_config(&config("div[", "")).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("not a valid CSS selector"),
+        "{err:#}"
+    );
+
+    #[cfg(not(feature = "browser"))]
+    {
+        let root = tempfile::tempdir()?;
+        fs::write(root.path().join("rendered.toml"), config(".event", ""))?;
+        let sources = load_sources_from_dir(root.path())?;
+        let err = rics::fetch::fetch_source_documents(&sources[0]).unwrap_err();
+        assert!(format!("{err:#}").contains("`browser` feature"), "{err:#}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+#![cfg(feature = "daemon")]
+
+mod common;
+
+use anyhow::Result;
+use common::text_source;
+use rics::daemon::{DaemonOptions, run_daemon};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn post(addr: &str, path: &str, token: Option<&str>) -> Result<u16> {
+    let mut stream = TcpStream::connect(addr)?;
+    let auth = token
+        .map(|token| format!("Authorization: Bearer {token}\r\n"))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "POST {path} HTTP/1.1\r\nHost: {addr}\r\n{auth}Content-Length: 0\r\nConnection: close\r\n\r\n"
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0);
+    Ok(status)
+}
+
+#[test]
+fn webhook_triggers_sync_of_named_source_only() -> Result<()> {
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("a.toml"),
+        text_source("test.daemon.a", "Alpha summit"),
+    )?;
+    fs::write(
+        config_dir.join("b.toml"),
+        text_source("test.daemon.b", "Beta summit"),
+    )?;
+    let state_path = root.path().join("state/events.json");
+
+    let addr = {
+        let probe = TcpListener::bind("127.0.0.1:0")?;
+        probe.local_addr()?.to_string()
+    };
+    let options = DaemonOptions {
+        config_dir,
+        state_path: state_path.clone(),
+        out_dir: root.path().join("out"),
+        interval: None,
+        listen: Some(addr.clone()),
+        webhook_token: Some("s3cret".to_string()),
+        jobs: 4,
+        stale_after: Duration::from_secs(3600),
+        #[cfg(feature = "mqtt")]
+        mqtt: None,
+    };
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let daemon = {
+        let shutdown = Arc::clone(&shutdown);
+        std::thread::spawn(move || run_daemon(&options, shutdown))
+    };
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let unauthorized = loop {
+        match post(&addr, "/sync/test.daemon.b", None) {
+            Ok(status) => break status,
+            Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(50)),
+            Err(err) => return Err(err),
+        }
+    };
+    assert_eq!(unauthorized, 401);
+    assert_eq!(post(&addr, "/calendars", Some("s3cret"))?, 404);
+    assert_eq!(post(&addr, "/sync/test.daemon.b", Some("s3cret"))?, 202);
+
+    while !state_path.exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    shutdown.store(true, Ordering::Relaxed);
+    daemon.join().expect("daemon thread")?;
+
+    let state = fs::read_to_string(&state_path)?;
+    assert!(state.contains("Beta summit"));
+    assert!(!state.contains("Alpha summit"));
+
+    Ok(())
+}
+
+fn get(addr: &str, path: &str) -> Result<(u16, String)> {
+    let mut stream = TcpStream::connect(addr)?;
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0);
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default();
+    Ok((status, body))
+}
+
+#[test]
+fn healthz_reports_sources_without_a_successful_sync() -> Result<()> {
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("a.toml"),
+        text_source("test.health.a", "Alpha summit"),
+    )?;
+    fs::write(
+        config_dir.join("b.toml"),
+        text_source("test.health.b", "Beta summit"),
+    )?;
+    let out_dir = root.path().join("out");
+    let status_path = out_dir.join("status.json");
+
+    let addr = {
+        let probe = TcpListener::bind("127.0.0.1:0")?;
+        probe.local_addr()?.to_string()
+    };
+    let options = DaemonOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: out_dir.clone(),
+        interval: None,
+        listen: Some(addr.clone()),
+        webhook_token: None,
+        jobs: 4,
+        stale_after: Duration::from_secs(3600),
+        #[cfg(feature = "mqtt")]
+        mqtt: None,
+    };
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let daemon = {
+        let shutdown = Arc::clone(&shutdown);
+        std::thread::spawn(move || run_daemon(&options, shutdown))
+    };
+    let source_health = |body: &str, key: &str| -> Result<serde_json::Value> {
+        let status: serde_json::Value = serde_json::from_str(body)?;
+        Ok(status["sources"]
+            .as_array()
+            .and_then(|sources| sources.iter().find(|s| s["source_key"] == key))
+            .cloned()
+            .unwrap_or_default())
+    };
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let (code, body) = loop {
+        match get(&addr, "/healthz") {
+            Ok(response) => break response,
+            Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(50)),
+            Err(err) => return Err(err),
+        }
+    };
+    assert_eq!(code, 503);
+    assert_eq!(source_health(&body, "test.health.a")?["stale"], true);
+
+    assert_eq!(post(&addr, "/sync/test.health.b", None)?, 202);
+    let body = loop {
+        let body = fs::read_to_string(&status_path).unwrap_or_default();
+        if body.contains("\"last_success\": \"") || Instant::now() >= deadline {
+            break body;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert_eq!(source_health(&body, "test.health.b")?["healthy"], true);
+    assert_eq!(source_health(&body, "test.health.a")?["healthy"], false);
+    assert_eq!(get(&addr, "/healthz")?.0, 503);
+
+    assert_eq!(post(&addr, "/sync", None)?, 202);
+    let code = loop {
+        let (code, _) = get(&addr, "/healthz")?;
+        if code == 200 || Instant::now() >= deadline {
+            break code;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert_eq!(code, 200);
+
+    shutdown.store(true, Ordering::Relaxed);
+    daemon.join().expect("daemon thread")?;
+    Ok(())
+}
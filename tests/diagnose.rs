@@ -0,0 +1,68 @@
+use anyhow::Result;
+use rics::diagnose::{DiagnoseOptions, diagnose_source, suggest_root_selectors};
+use std::fs;
+use tempfile::tempdir;
+
+const REDESIGNED: &str = r#"<html><body>
+<nav><ul><li>Home</li><li>About</li><li>Contact</li></ul></nav>
+<section class="listing">
+  <div class="card card-x91"><h3>Budget hearing</h3><span>2030-03-04</span></div>
+  <div class="card card-x92"><h3>Annual report</h3><span>2030-04-15</span></div>
+  <div class="card card-x93"><h3>Rate decision</h3><span>May 6, 2030</span></div>
+</section>
+<ul id="archive">
+  <li><time datetime="2029-01-01">Jan</time> Old release</li>
+  <li><time datetime="2029-02-01">Feb</time> Older release</li>
+  <li><time datetime="2029-03-01">Mar</time> Oldest release</li>
+  <li>No date here</li>
+</ul>
+</body></html>"#;
+
+#[test]
+fn suggests_repeated_dated_siblings_and_skips_navigation() {
+    let suggestions = suggest_root_selectors(REDESIGNED, 5);
+    let selectors = suggestions
+        .iter()
+        .map(|s| s.selector.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(selectors, vec!["div.card", "#archive > li"]);
+    assert_eq!(suggestions[0].dated, 3);
+    assert_eq!(suggestions[0].sample, "Budget hearing 2030-03-04");
+    assert_eq!(suggestions[1].matches, 4);
+}
+
+#[test]
+fn diagnose_reports_current_matches_and_suggestions() -> Result<()> {
+    let root = tempdir()?;
+    fs::write(root.path().join("redesigned.html"), REDESIGNED)?;
+    fs::write(
+        root.path().join("agency.toml"),
+        r#"[source]
+key = "test.drift"
+name = "Drift Fixture"
+domain = "publishing"
+
+[fetch]
+mode = "file"
+file_path = "redesigned.html"
+
+[extract]
+root_selector = "article.event"
+
+[map.title]
+from = "css:h3"
+"#,
+    )?;
+
+    let diagnoses = diagnose_source(&DiagnoseOptions {
+        config_dir: root.path().to_path_buf(),
+        source: "test.drift".to_string(),
+        limit: 1,
+    })?;
+    assert_eq!(diagnoses.len(), 1);
+    assert_eq!(diagnoses[0].current_matches, 0);
+    assert_eq!(diagnoses[0].suggestions.len(), 1);
+    assert_eq!(diagnoses[0].suggestions[0].selector, "div.card");
+
+    Ok(())
+}
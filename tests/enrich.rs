@@ -0,0 +1,291 @@
+use anyhow::Result;
+use rics::fetch::FetchedDocument;
+use rics::parser::preview_source_events;
+
+fn countries(config: &str, rows: serde_json::Value) -> Result<Vec<Option<String>>> {
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://enrich".to_string(),
+            body: serde_json::to_vec(&serde_json::json!({ "rows": rows }))?.into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    Ok(candidates.into_iter().map(|event| event.country).collect())
+}
+
+const CONFIG: &str = r#"[source]
+key = "enrich.country"
+name = "Enrich Country"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[map.country]
+from = "json:/country"
+
+[map.jurisdiction]
+from = "json:/jurisdiction"
+
+[enrich.country_map]
+"Euro Area" = "EU"
+"#;
+
+#[test]
+fn country_is_canonicalized_and_inferred_in_priority_order() -> Result<()> {
+    let rows = serde_json::json!([
+        {"title": "GDP", "date": "2030-01-02", "country": "GBR"},
+        {"title": "CPI", "date": "2030-01-02", "country": "UK"},
+        {"title": "HICP", "date": "2030-01-02", "country": "euro area"},
+        {"title": "Council meeting", "date": "2030-01-02", "jurisdiction": "US-TX-HOU"},
+        {"title": "Germany factory orders", "date": "2030-01-02"},
+        {"title": "Euro Area flash PMI", "date": "2030-01-02"},
+        {"title": "Papua New Guinea budget", "date": "2030-01-02"},
+        {"title": "France and Spain summit", "date": "2030-01-02"},
+        {"title": "Georgia primary", "date": "2030-01-02"},
+        {"title": "Unknown", "date": "2030-01-02", "country": "Atlantis"}
+    ]);
+    let got = countries(CONFIG, rows)?;
+    let expected = [
+        Some("GB"),
+        Some("GB"),
+        Some("EU"),
+        Some("US"),
+        Some("DE"),
+        Some("EU"),
+        Some("PG"),
+        None,
+        None,
+        Some("Atlantis"),
+    ];
+    assert_eq!(
+        got.iter().map(Option::as_deref).collect::<Vec<_>>(),
+        expected
+    );
+
+    Ok(())
+}
+
+#[test]
+fn default_country_fills_gaps_and_inference_can_be_disabled() -> Result<()> {
+    let rows = serde_json::json!([
+        {"title": "Japan trade balance", "date": "2030-01-02"},
+        {"title": "Board meeting", "date": "2030-01-02"}
+    ]);
+    let with_default = CONFIG.replace(
+        "domain = \"economics\"",
+        "domain = \"economics\"\ndefault_country = \"usa\"",
+    );
+    assert_eq!(
+        countries(&with_default, rows.clone())?,
+        vec![Some("US".to_string()), Some("US".to_string())]
+    );
+
+    let disabled = format!("{CONFIG}\n[enrich]\ninfer_country = false\n")
+        .replace("[enrich.country_map]\n\"Euro Area\" = \"EU\"\n", "");
+    assert_eq!(countries(&disabled, rows)?, vec![None, None]);
+
+    Ok(())
+}
+
+#[test]
+fn country_map_values_must_be_two_letter_codes() {
+    let bad = CONFIG.replace("\"EU\"", "\"Europe\"");
+    let err = rics::config::parse_source_config(&bad).unwrap_err();
+    assert!(err.to_string().contains("enrich.country_map.Euro Area"));
+}
+
+#[test]
+fn category_rules_tag_events_and_filter_bundles() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let configs = root.path().join("configs");
+    std::fs::create_dir_all(configs.join("sources"))?;
+    std::fs::create_dir_all(configs.join("bundles"))?;
+    std::fs::write(
+        configs.join("categorize.toml"),
+        r#"[[rules]]
+pattern = "CPI|inflation"
+categories = ["inflation"]
+"#,
+    )?;
+    std::fs::write(
+        configs.join("bundles/prices.toml"),
+        r#"[bundle]
+key = "prices"
+name = "Prices"
+
+[include]
+source_patterns = ["enrich.*"]
+categories = ["inflation"]
+
+[publish]
+file_name_template = "prices-{{year}}.ics"
+"#,
+    )?;
+    std::fs::write(
+        configs.join("sources/releases.toml"),
+        r#"[source]
+key = "enrich.releases"
+name = "Releases"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = """{"rows": [
+  {"id": "cpi", "title": "Consumer Price Index (cpi)", "date": "2030-02-12"},
+  {"id": "retail", "title": "Retail sales", "date": "2030-02-14"},
+  {"id": "minutes", "title": "Minutes", "summary": "Inflation outlook", "date": "2030-02-20"}
+]}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.description]
+from = "json:/summary"
+
+[map.date]
+from = "json:/date"
+
+[[categorize.rules]]
+pattern = "^retail"
+categories = ["consumption", " "]
+"#,
+    )?;
+
+    let out_dir = root.path().join("out");
+    rics::pipeline::sync_sources(&rics::pipeline::SyncOptions {
+        config_dir: configs.join("sources"),
+        state_path: root.path().join("state/events.json"),
+        out_dir: out_dir.clone(),
+        ..rics::pipeline::SyncOptions::default()
+    })?;
+
+    let state = rics::pipeline::load_state_for_read(&root.path().join("state/events.json"))?;
+    let mut tagged = state
+        .events
+        .values()
+        .map(|event| (event.title.as_str(), event.categories.join(",")))
+        .collect::<Vec<_>>();
+    tagged.sort();
+    assert_eq!(
+        tagged,
+        vec![
+            (
+                "Consumer Price Index (cpi)",
+                "economics,inflation".to_string()
+            ),
+            ("Minutes", "economics,inflation".to_string()),
+            ("Retail sales", "consumption,economics".to_string()),
+        ]
+    );
+
+    let bundle = std::fs::read_to_string(out_dir.join("bundles/prices/prices-2030.ics"))?;
+    assert_eq!(bundle.matches("BEGIN:VEVENT").count(), 2);
+    assert!(!bundle.contains("Retail sales"));
+    assert!(bundle.contains("CATEGORIES:economics,inflation"));
+
+    Ok(())
+}
+
+#[test]
+fn importance_rules_prefer_source_rules_then_shared_ones() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let configs = root.path().join("configs");
+    std::fs::create_dir_all(configs.join("sources"))?;
+    std::fs::write(
+        configs.join("importance.toml"),
+        r#"[[rules]]
+title = "weekly"
+importance = 1
+
+[[rules]]
+source = "enrich.*"
+importance = 3
+overwrite = false
+"#,
+    )?;
+    std::fs::write(
+        configs.join("sources/releases.toml"),
+        r#"[source]
+key = "enrich.importance"
+name = "Importance"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = """{"rows": [
+  {"id": "cpi", "title": "CPI weekly preview", "date": "2030-02-12", "importance": "4"},
+  {"id": "claims", "title": "Weekly jobless claims", "date": "2030-02-13", "importance": "4"},
+  {"id": "minutes", "title": "Minutes", "date": "2030-02-20"},
+  {"id": "gdp", "title": "GDP", "date": "2030-02-21", "importance": "4"}
+]}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[map.importance]
+from = "json:/importance"
+
+[[importance.rules]]
+title = "^cpi"
+importance = 5
+"#,
+    )?;
+
+    rics::pipeline::sync_sources(&rics::pipeline::SyncOptions {
+        config_dir: configs.join("sources"),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..rics::pipeline::SyncOptions::default()
+    })?;
+
+    let state = rics::pipeline::load_state_for_read(&root.path().join("state/events.json"))?;
+    let mut ranked = state
+        .events
+        .values()
+        .map(|event| (event.title.as_str(), event.importance))
+        .collect::<Vec<_>>();
+    ranked.sort();
+    assert_eq!(
+        ranked,
+        vec![
+            ("CPI weekly preview", Some(5)),
+            ("GDP", Some(4)),
+            ("Minutes", Some(3)),
+            ("Weekly jobless claims", Some(1)),
+        ]
+    );
+
+    Ok(())
+}
@@ -13,7 +13,7 @@ fn europe_source_pack_validates_and_has_unique_country_keys() -> Result<()> {
     let sources = load_sources_from_dir(&root.join("configs/sources/elections/europe"))?;
     let bundles = load_bundles_from_dir(&root.join("configs/bundles"))?;
 
-    assert_eq!(bundles.len(), 1);
+    assert_eq!(bundles.len(), 9);
     assert_eq!(bundles[0].config.bundle.key, "europe.elections");
     assert!(sources.len() >= 45);
 
@@ -35,8 +35,7 @@ fn parser_supports_exact_month_year_and_tbd_and_builds_bundle() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports.len(), 2);
@@ -105,8 +104,7 @@ fn election_updates_increment_sequence() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     let file = env.data_dir.join("aa.txt");
@@ -119,7 +117,7 @@ fn election_updates_increment_sequence() -> Result<()> {
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
         source: Some("elections.aa".to_string()),
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports[0].updated, 1);
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use rics::fetch::FetchedDocument;
+use rics::model::EventTimeSpec;
+use rics::parser::preview_source_events;
+
+const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:ev="http://purl.org/rss/1.0/modules/event/">
+<channel>
+  <title>Releases</title>
+  <item>
+    <title>CPI release</title>
+    <link>https://stats.example/releases/cpi</link>
+    <guid>release-cpi-2030-03</guid>
+    <pubDate>Tue, 12 Mar 2030 13:30:00 GMT</pubDate>
+    <description><![CDATA[Consumer prices for <b>February</b>.]]></description>
+    <category>prices</category>
+    <ev:startdate>2030-03-14</ev:startdate>
+  </item>
+</channel>
+</rss>"#;
+
+const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Board calendar</title>
+  <entry>
+    <title>Board meeting</title>
+    <link rel="self" href="https://agency.example/feed/1"/>
+    <link href="https://agency.example/meetings/1"/>
+    <id>urn:uuid:1</id>
+    <updated>2030-04-02T09:00:00Z</updated>
+    <summary>Monthly board meeting.</summary>
+    <author><name>Clerk</name></author>
+    <category term="board"/>
+  </entry>
+</feed>"#;
+
+fn config(map: &str) -> String {
+    format!(
+        r#"[source]
+key = "feed.releases"
+name = "Feed Releases"
+domain = "economics"
+
+[fetch]
+mode = "http"
+base_url = "https://stats.example/feed.xml"
+
+[extract]
+format = "feed"
+{map}"#
+    )
+}
+
+fn doc(body: &str) -> FetchedDocument {
+    FetchedDocument {
+        source_url: "https://stats.example/feed.xml".to_string(),
+        body: body.as_bytes().to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    }
+}
+
+#[test]
+fn rss_and_atom_items_map_without_rules() -> Result<()> {
+    let events = preview_source_events(&config(""), &[doc(RSS)])?;
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.title, "CPI release");
+    assert_eq!(
+        event.source_url.as_deref(),
+        Some("https://stats.example/releases/cpi")
+    );
+    assert_eq!(
+        event.source_event_id.as_deref(),
+        Some("release-cpi-2030-03")
+    );
+    assert_eq!(
+        event.description.as_deref(),
+        Some("Consumer prices for <b>February</b>.")
+    );
+    assert!(event.categories.contains(&"prices".to_string()));
+    assert!(matches!(event.time, EventTimeSpec::DateTime { .. }));
+    assert_eq!(
+        event.time.start_date().map(|d| d.to_string()).as_deref(),
+        Some("2030-03-12")
+    );
+
+    let events = preview_source_events(&config(""), &[doc(ATOM)])?;
+    let event = &events[0];
+    assert_eq!(event.title, "Board meeting");
+    assert_eq!(
+        event.source_url.as_deref(),
+        Some("https://agency.example/meetings/1")
+    );
+    assert_eq!(event.source_event_id.as_deref(), Some("urn:uuid:1"));
+    assert_eq!(event.description.as_deref(), Some("Monthly board meeting."));
+    assert_eq!(
+        event.metadata.get("author").map(String::as_str),
+        Some("Clerk")
+    );
+    assert!(event.categories.contains(&"board".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn map_rules_read_item_fields_by_name() -> Result<()> {
+    let map = r#"
+[map.title]
+from = "title"
+
+[map.date]
+from = "startdate"
+
+[map.url]
+from = "link"
+
+[map.id]
+from = "guid"
+"#;
+    let events = preview_source_events(&config(map), &[doc(RSS)])?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0].time,
+        EventTimeSpec::Date {
+            start: chrono::NaiveDate::from_ymd_opt(2030, 3, 14).unwrap(),
+            end: None,
+        }
+    );
+    assert_eq!(
+        events[0].source_url.as_deref(),
+        Some("https://stats.example/releases/cpi")
+    );
+
+    let err = preview_source_events(&config(""), &[doc("<rss><channel>")]).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("failed to parse feed"),
+        "{err:#}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,2101 @@
+#![cfg(feature = "http")]
+
+mod common;
+
+use anyhow::Result;
+use common::{serve_notices, sync_options, write_json_source};
+use rics::config::{load_sources_for_tenant, load_sources_from_dir};
+use rics::links::{CheckLinksOptions, check_links};
+use rics::pipeline::{BuildOptions, SyncOptions, render_calendar_to, sync_sources};
+use rics::robots::RobotsRules;
+use rics::tenant::Tenant;
+use std::collections::HashSet;
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+use tiny_http::{Method, Response, Server};
+
+#[test]
+fn rate_limit_spaces_requests_to_one_host_across_sources() -> Result<()> {
+    let port = serve_notices(|_| None)?;
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for name in ["north", "south"] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.rate_limit.{name}"
+name = "Rate Limit {name}"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{name}"
+retry_attempts = 1
+rate_limit = 600
+
+[pagination]
+enabled = true
+page_param = "page"
+max_pages = 3
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+"#
+            ),
+        )?;
+    }
+
+    let started = Instant::now();
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    })?;
+    assert_eq!(reports.iter().map(|r| r.pages_fetched).sum::<usize>(), 6);
+    // Six requests to one host at 600/min are at least five 100ms gaps apart,
+    // even though both sources fetch at once.
+    assert!(started.elapsed() >= Duration::from_millis(500));
+
+    Ok(())
+}
+
+#[test]
+fn pipelines_over_different_config_dirs_do_not_share_rate_limits() -> Result<()> {
+    let port = serve_notices(|_| None)?;
+
+    let root = tempdir()?;
+    let tenants = ["east", "west"]
+        .into_iter()
+        .map(|team| -> Result<SyncOptions> {
+            let config_dir = root.path().join(team).join("sources");
+            fs::create_dir_all(&config_dir)?;
+            fs::write(
+                config_dir.join("board.toml"),
+                format!(
+                    r#"[source]
+key = "test.tenant.board"
+name = "{team} board"
+domain = "government"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{team}"
+retry_attempts = 1
+rate_limit = 1
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+                ),
+            )?;
+            Ok(SyncOptions {
+                config_dir,
+                state_path: root.path().join(team).join("state/events.json"),
+                out_dir: root.path().join(team).join("out"),
+                jobs: 1,
+                ..SyncOptions::default()
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let started = Instant::now();
+    let reports = std::thread::scope(|scope| {
+        let handles = tenants
+            .iter()
+            .map(|options| scope.spawn(|| sync_sources(options)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("sync thread"))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    // At one request per minute, a budget shared across the two pipelines
+    // would hold one of them back for a minute.
+    assert!(started.elapsed() < Duration::from_secs(30));
+    for (team, reports) in ["east", "west"].into_iter().zip(&reports) {
+        assert_eq!(reports[0].inserted, 1);
+        let state = fs::read_to_string(root.path().join(team).join("state/events.json"))?;
+        assert!(state.contains(&format!("Notice /{team}")));
+    }
+
+    // Sources share the tenant their caller passes, and nothing else.
+    let config_dir = &tenants[0].config_dir;
+    let tenant = Arc::new(Tenant::default());
+    let first = load_sources_for_tenant(config_dir, &tenant)?;
+    let second = load_sources_for_tenant(config_dir, &tenant)?;
+    assert!(Arc::ptr_eq(&first[0].tenant, &second[0].tenant));
+    let fresh = load_sources_from_dir(config_dir)?;
+    assert!(!Arc::ptr_eq(&first[0].tenant, &fresh[0].tenant));
+
+    Ok(())
+}
+
+#[test]
+fn sources_with_the_same_client_settings_share_keep_alive_connections() -> Result<()> {
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&peers);
+    let port = serve_notices(move |request| {
+        let peer = request.remote_addr().map(|addr| addr.port());
+        seen.lock().unwrap().push((request.url().to_string(), peer));
+        None
+    })?;
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for (name, timeout) in [("alpha", 30), ("beta", 30), ("gamma", 7)] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.pool.{name}"
+name = "{name} board"
+domain = "government"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{name}"
+retry_attempts = 1
+timeout_secs = {timeout}
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+            ),
+        )?;
+    }
+    sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..SyncOptions::default()
+    })?;
+
+    let peers = peers.lock().unwrap();
+    let peer = |url: &str| {
+        peers
+            .iter()
+            .find(|(seen, _)| seen == url)
+            .and_then(|(_, peer)| *peer)
+            .expect("request served")
+    };
+    assert_eq!(peers.len(), 3);
+    // One pooled client, so beta reuses the connection alpha opened.
+    assert_eq!(peer("/alpha"), peer("/beta"));
+    // A different timeout is a different client with its own pool.
+    assert_ne!(peer("/alpha"), peer("/gamma"));
+
+    Ok(())
+}
+
+#[test]
+fn sources_are_merged_and_saved_while_later_sources_still_fetch() -> Result<()> {
+    let root = tempdir()?;
+    let options = sync_options(root.path());
+    let state_path = options.state_path.clone();
+    let saw_first_merge = Arc::new(AtomicBool::new(false));
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let seen = Arc::clone(&saw_first_merge);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            // Holds the slow source until the fast one is in the saved state.
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while Instant::now() < deadline {
+                if fs::read_to_string(&state_path).is_ok_and(|state| state.contains("Quick notice"))
+                {
+                    seen.store(true, Ordering::SeqCst);
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            let _ = request.respond(Response::from_string(
+                r#"<article class="item"><span class="title">Slow notice</span><time>2030-03-02</time></article>"#,
+            ));
+        }
+    });
+
+    fs::create_dir_all(&options.config_dir)?;
+    write_json_source(
+        &options.config_dir.join("a-quick.toml"),
+        "test.stream.quick",
+        "Quick",
+        "government",
+        r#"[{"title": "Quick notice", "date": "2030-03-01"}]"#,
+        "",
+    )?;
+    fs::write(
+        options.config_dir.join("b-slow.toml"),
+        format!(
+            r#"[source]
+key = "test.stream.slow"
+name = "Slow"
+domain = "government"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/slow"
+retry_attempts = 1
+timeout_secs = 30
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+
+    let reports = sync_sources(&SyncOptions { jobs: 2, ..options })?;
+    assert!(reports.iter().all(|report| report.inserted == 1));
+    assert!(saw_first_merge.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[test]
+fn robots_rules_pick_the_named_group_and_the_longest_match() {
+    let robots = "User-agent: *\nDisallow: /\n\nUser-agent: Googlebot\nUser-agent: rics\nDisallow: /events\nAllow: /events/public\nDisallow: /*.pdf$\n";
+    let rules = RobotsRules::parse(robots, "rics/0.1 (+https://github.com/sguzman/rics)");
+    assert!(rules.check("/calendar").is_ok());
+    assert_eq!(rules.check("/events/2030").unwrap_err().rule, "/events");
+    assert!(rules.check("/events/public/2030").is_ok());
+    assert_eq!(rules.check("/docs/agenda.pdf").unwrap_err().rule, "/*.pdf$");
+    assert!(rules.check("/docs/agenda.pdf?v=2").is_ok());
+
+    let other = RobotsRules::parse(robots, "curl/8.0");
+    assert_eq!(other.check("/calendar").unwrap_err().rule, "/");
+}
+
+#[test]
+fn respect_robots_txt_skips_disallowed_pages() -> Result<()> {
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&requested);
+    let port = serve_notices(move |request| {
+        let url = request.url().to_string();
+        log.lock().unwrap().push(url.clone());
+        (url == "/robots.txt").then(|| {
+            "User-agent: rics\nDisallow: /events?page=1\nDisallow: /private\n".to_string()
+        })
+    })?;
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for (name, path, pages) in [
+        ("events", "events", "true"),
+        ("private", "private", "false"),
+    ] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.robots.{name}"
+name = "Robots {name}"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{path}"
+retry_attempts = 1
+respect_robots_txt = true
+
+[pagination]
+enabled = {pages}
+page_param = "page"
+max_pages = 3
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+"#
+            ),
+        )?;
+    }
+
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        source: Some("test.robots.events".to_string()),
+        ..SyncOptions::default()
+    };
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].pages_fetched, 2);
+    assert_eq!(
+        *requested.lock().unwrap(),
+        vec!["/robots.txt", "/events?page=0", "/events?page=2"]
+    );
+
+    // A source whose only url is disallowed fails rather than merging nothing.
+    let reports = sync_sources(&SyncOptions {
+        source: Some("test.robots.private".to_string()),
+        ..options
+    })?;
+    let err = reports[0].error.as_deref().unwrap_or_default();
+    assert!(err.contains("robots.txt disallows every url"), "{err}");
+    assert!(!requested.lock().unwrap().contains(&"/private".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn prefetch_urls_share_a_cookie_session_with_the_data_url() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let has_session = request.headers().iter().any(|header| {
+                header.field.equiv("Cookie") && header.value.as_str().contains("session=abc")
+            });
+            let response = match request.url() {
+                "/start" => Response::from_string("welcome").with_header(
+                    "Set-Cookie: session=abc; Path=/"
+                        .parse::<tiny_http::Header>()
+                        .expect("header"),
+                ),
+                "/data" if has_session => Response::from_string(
+                    r#"<article class="item"><span class="title">Open house</span><time>2030-03-01</time></article>"#,
+                ),
+                _ => Response::from_string("no session").with_status_code(403),
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("session.toml"),
+        format!(
+            r#"[source]
+key = "test.session"
+name = "Session Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/data"
+prefetch_urls = ["http://127.0.0.1:{port}/start"]
+retry_attempts = 1
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    })?;
+    assert_eq!(reports[0].records_parsed, 1);
+    assert_eq!(reports[0].inserted, 1);
+
+    Ok(())
+}
+
+#[test]
+fn fetch_auth_is_sent_on_every_page_from_the_environment() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let authorized = request.headers().iter().any(|header| {
+                header.field.equiv("Authorization") && header.value.as_str() == "Bearer t0k3n"
+            });
+            let response = if authorized {
+                Response::from_string(format!(
+                    r#"<article class="item"><span class="title">Filing {}</span><time>2030-03-01</time></article>"#,
+                    request.url()
+                ))
+            } else {
+                Response::from_string("unauthorized").with_status_code(401)
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("filings.toml"),
+        format!(
+            r#"[source]
+key = "test.auth"
+name = "Auth Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/filings"
+retry_attempts = 1
+
+[fetch.auth]
+mode = "bearer"
+secret_env = "RICS_TEST_FETCH_TOKEN"
+
+[pagination]
+enabled = true
+page_param = "page"
+max_pages = 2
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+
+    let reports = sync_sources(&options)?;
+    let err = reports[0].error.as_deref().unwrap_or_default();
+    assert!(
+        err.contains("env var RICS_TEST_FETCH_TOKEN is not set"),
+        "{err}"
+    );
+
+    unsafe { std::env::set_var("RICS_TEST_FETCH_TOKEN", "t0k3n") };
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].pages_fetched, 2);
+    assert_eq!(reports[0].inserted, 2);
+
+    Ok(())
+}
+
+#[test]
+fn fetch_auth_is_only_sent_to_the_sources_origin() -> Result<()> {
+    type Seen = Arc<Mutex<Vec<(String, Option<String>, Option<String>)>>>;
+    let serve = |redirect_to: Option<u16>| -> Result<(u16, Seen)> {
+        let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+        let port = server.server_addr().to_ip().expect("ip listener").port();
+        let seen = Seen::default();
+        let log = Arc::clone(&seen);
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let header = |name: &'static str| {
+                    request
+                        .headers()
+                        .iter()
+                        .find(|h| h.field.equiv(name))
+                        .map(|h| h.value.as_str().to_string())
+                };
+                let entry = (
+                    request.url().to_string(),
+                    header("X-Api-Key"),
+                    header("Authorization"),
+                );
+                log.lock().unwrap().push(entry);
+                let response = match (redirect_to, request.url()) {
+                    (_, "/robots.txt") => Response::from_string("").with_status_code(404),
+                    (Some(other), url) if url != "/start" => Response::from_string("moved")
+                        .with_status_code(302)
+                        .with_header(
+                            format!("Location: http://127.0.0.1:{other}{url}")
+                                .parse::<tiny_http::Header>()
+                                .expect("header"),
+                        ),
+                    _ => Response::from_string(
+                        r#"<article class="item"><span class="title">Filing</span><time>2030-03-01</time></article>"#,
+                    ),
+                };
+                let _ = request.respond(response);
+            }
+        });
+        Ok((port, seen))
+    };
+    let (elsewhere, elsewhere_seen) = serve(None)?;
+    let (port, seen) = serve(Some(elsewhere))?;
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for (name, auth) in [
+        (
+            "key",
+            r#"mode = "api_key_header"
+header = "X-Api-Key""#,
+        ),
+        ("bearer", r#"mode = "bearer""#),
+    ] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.origin.{name}"
+name = "Origin Fixture {name}"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{name}"
+prefetch_urls = ["http://127.0.0.1:{elsewhere}/start"]
+respect_robots_txt = true
+retry_attempts = 1
+
+[fetch.auth]
+{auth}
+secret_env = "RICS_TEST_ORIGIN_SECRET"
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+            ),
+        )?;
+    }
+    unsafe { std::env::set_var("RICS_TEST_ORIGIN_SECRET", "0r1g1n") };
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..SyncOptions::default()
+    })?;
+
+    let report = |key: &str| {
+        reports
+            .iter()
+            .find(|report| report.source_key == key)
+            .expect("source report")
+    };
+    // The api key would follow the redirect, so the redirect is refused.
+    let err = report("test.origin.key")
+        .error
+        .as_deref()
+        .unwrap_or_default();
+    assert!(err.contains("refusing redirect"), "{err}");
+    // reqwest drops Authorization itself, so the bearer source follows it.
+    assert_eq!(report("test.origin.bearer").inserted, 1);
+
+    let seen = seen.lock().unwrap();
+    let sent = |url: &str| {
+        seen.iter()
+            .find(|(seen, _, _)| seen == url)
+            .map(|(_, key, bearer)| (key.clone(), bearer.clone()))
+    };
+    assert_eq!(sent("/key"), Some((Some("0r1g1n".to_string()), None)));
+    assert_eq!(
+        sent("/bearer"),
+        Some((None, Some("Bearer 0r1g1n".to_string())))
+    );
+    assert!(
+        seen.iter()
+            .filter(|(url, _, _)| url == "/robots.txt")
+            .all(|(_, key, bearer)| key.is_none() && bearer.is_none())
+    );
+    let elsewhere_seen = elsewhere_seen.lock().unwrap();
+    assert!(elsewhere_seen.iter().any(|(url, _, _)| url == "/bearer"));
+    assert!(!elsewhere_seen.iter().any(|(url, _, _)| url == "/key"));
+    assert!(
+        elsewhere_seen
+            .iter()
+            .all(|(_, key, bearer)| key.is_none() && bearer.is_none())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn oauth2_tokens_are_shared_by_sources_with_the_same_issuer() -> Result<()> {
+    let token_requests = Arc::new(AtomicUsize::new(0));
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let counter = Arc::clone(&token_requests);
+    std::thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let authorization = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Authorization"))
+                .map(|header| header.value.as_str().to_string());
+            let response = if request.url() == "/token" {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+                // base64("rics-client:s3cret")
+                if authorization.as_deref() == Some("Basic cmljcy1jbGllbnQ6czNjcmV0")
+                    && body == "grant_type=client_credentials&scope=read%3Aevents"
+                {
+                    Response::from_string(
+                        r#"{"access_token":"issued-token","token_type":"Bearer","expires_in":3600}"#,
+                    )
+                } else {
+                    Response::from_string("bad client").with_status_code(401)
+                }
+            } else if authorization.as_deref() == Some("Bearer issued-token") {
+                Response::from_string(format!(
+                    r#"<article class="item"><span class="title">Event {}</span><time>2030-03-01</time></article>"#,
+                    request.url()
+                ))
+            } else {
+                Response::from_string("unauthorized").with_status_code(401)
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for name in ["first", "second"] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.oauth.{name}"
+name = "OAuth Fixture {name}"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{name}"
+retry_attempts = 1
+
+[fetch.auth]
+mode = "oauth2_client_credentials"
+token_url = "http://127.0.0.1:{port}/token"
+client_id = "rics-client"
+secret_env = "RICS_TEST_OAUTH_SECRET"
+scope = "read:events"
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+"#
+            ),
+        )?;
+    }
+    unsafe { std::env::set_var("RICS_TEST_OAUTH_SECRET", "s3cret") };
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports.len(), 2);
+    assert!(reports.iter().all(|report| report.inserted == 1));
+    assert_eq!(token_requests.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[test]
+fn oauth2_tokens_are_refreshed_when_they_expire_or_are_rejected() -> Result<()> {
+    type Pages = Arc<Mutex<Vec<(String, String, Option<u16>)>>>;
+    let pages = Pages::default();
+    let issued = Arc::new(Mutex::new(Vec::<String>::new()));
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let (log, tokens) = (Arc::clone(&pages), Arc::clone(&issued));
+    std::thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let response = if request.url() == "/token" {
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+                let scope = body.rsplit('=').next().unwrap_or_default().to_string();
+                let mut tokens = tokens.lock().unwrap();
+                let n = tokens.iter().filter(|t| t.starts_with(&scope)).count() + 1;
+                tokens.push(format!("{scope}-{n}"));
+                // Inside the refresh margin, so a short token is used once.
+                let expires_in = if scope == "short" { 60 } else { 3600 };
+                Response::from_string(format!(
+                    r#"{{"access_token":"{scope}-{n}","expires_in":{expires_in}}}"#
+                ))
+            } else {
+                let token = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Authorization"))
+                    .map(|header| header.value.as_str().trim_start_matches("Bearer "))
+                    .unwrap_or_default()
+                    .to_string();
+                let peer = request.remote_addr().map(|addr| addr.port());
+                log.lock()
+                    .unwrap()
+                    .push((request.url().to_string(), token.clone(), peer));
+                // The first long-lived token is revoked before it expires.
+                if token == "long-1" || !tokens.lock().unwrap().contains(&token) {
+                    Response::from_string("unauthorized").with_status_code(401)
+                } else {
+                    Response::from_string(format!(
+                        r#"<article class="item"><span class="title">Event {}</span><time>2030-03-01</time></article>"#,
+                        request.url()
+                    ))
+                }
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for (name, scope, pages) in [("expiring", "short", 3), ("revoked", "long", 1)] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.refresh.{name}"
+name = "Refresh Fixture {name}"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{name}"
+retry_attempts = 1
+
+[fetch.auth]
+mode = "oauth2_client_credentials"
+token_url = "http://127.0.0.1:{port}/token"
+client_id = "rics-client"
+secret_env = "RICS_TEST_REFRESH_SECRET"
+scope = "{scope}"
+
+[pagination]
+enabled = true
+page_param = "page"
+max_pages = {pages}
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+            ),
+        )?;
+    }
+    unsafe { std::env::set_var("RICS_TEST_REFRESH_SECRET", "s3cret") };
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..SyncOptions::default()
+    })?;
+    for report in &reports {
+        assert_eq!(report.error, None);
+    }
+
+    let pages = pages.lock().unwrap();
+    let sent = |prefix: &str| {
+        pages
+            .iter()
+            .filter(|(url, _, _)| url.starts_with(prefix))
+            .map(|(_, token, _)| token.as_str())
+            .collect::<Vec<_>>()
+    };
+    // Each page of the expiring source gets a fresh token...
+    assert_eq!(sent("/expiring"), ["short-1", "short-2", "short-3"]);
+    // ...over one pooled connection, since tokens are not part of the client.
+    let peers = pages
+        .iter()
+        .filter(|(url, _, _)| url.starts_with("/expiring"))
+        .map(|(_, _, peer)| *peer)
+        .collect::<HashSet<_>>();
+    assert_eq!(peers.len(), 1);
+    // A rejected token is replaced and the request sent again.
+    assert_eq!(sent("/revoked"), ["long-1", "long-2"]);
+
+    Ok(())
+}
+
+#[test]
+fn fetch_proxy_routes_the_sources_requests_through_it() -> Result<()> {
+    let proxied = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&proxied);
+    let port = serve_notices(move |request| {
+        // A forward proxy receives the absolute URL in the request line.
+        seen.lock().unwrap().push(request.url().to_string());
+        None
+    })?;
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("notices.toml"),
+        format!(
+            r#"[source]
+key = "test.proxy"
+name = "Proxy Fixture"
+domain = "government"
+
+[fetch]
+base_url = "http://notices.example.invalid/list"
+proxy = "http://127.0.0.1:{port}"
+retry_attempts = 1
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+    let options = SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].inserted, 1);
+    assert_eq!(
+        *proxied.lock().unwrap(),
+        ["http://notices.example.invalid/list"]
+    );
+
+    let config = fs::read_to_string(config_dir.join("notices.toml"))?;
+    fs::write(
+        config_dir.join("notices.toml"),
+        config.replace(
+            &format!("http://127.0.0.1:{port}"),
+            "socks5://127.0.0.1:1080",
+        ),
+    )?;
+    let err = sync_sources(&options).unwrap_err();
+    assert!(format!("{err:#}").contains("must be an http:// or https:// URL"));
+
+    Ok(())
+}
+
+#[test]
+fn next_link_pagination_follows_links_until_they_loop() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&requested);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let url = request.url().to_string();
+            seen.lock().unwrap().push(url.clone());
+            let response = match url.as_str() {
+                // HTML pages link onward with a relative href; the last one
+                // links back to the first.
+                "/events" | "/events/p2" | "/events/p3" => {
+                    let next = match url.as_str() {
+                        "/events" => "events/p2",
+                        "/events/p2" => "/events/p3",
+                        _ => "/events",
+                    };
+                    Response::from_string(format!(
+                        r#"<article class="item"><span class="title">Event {url}</span><time>2030-03-01</time><a class="next" href="{next}">Next</a></article>"#
+                    ))
+                }
+                // Feed pages link onward in the Link header only.
+                _ => {
+                    let page = url
+                        .trim_start_matches("/feed?page=")
+                        .parse::<u32>()
+                        .unwrap_or(1);
+                    let link = format!("</feed?page={}>; rel=\"next\"", page + 1);
+                    Response::from_string(format!(
+                        r#"<article class="item"><span class="title">Feed {page}</span><time>2030-03-01</time></article>"#
+                    ))
+                    .with_header(tiny_http::Header::from_bytes("Link", link).unwrap())
+                }
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for (name, url, next_selector) in [
+        ("events", "/events", "next_selector = \"a.next\""),
+        ("feed", "/feed?page=1", ""),
+    ] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.next.{name}"
+name = "Next Link Fixture {name}"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}{url}"
+retry_attempts = 1
+
+[pagination]
+enabled = true
+strategy = "next_link"
+max_pages = 4
+{next_selector}
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+absolutize = true
+"#
+            ),
+        )?;
+    }
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..SyncOptions::default()
+    };
+
+    let reports = sync_sources(&options)?;
+    let pages = |key: &str| {
+        reports
+            .iter()
+            .find(|report| report.source_key == key)
+            .map(|report| report.pages_fetched)
+    };
+    assert_eq!(pages("test.next.events"), Some(3));
+    assert_eq!(pages("test.next.feed"), Some(4));
+    let requested = requested.lock().unwrap();
+    assert_eq!(
+        requested
+            .iter()
+            .filter(|url| url.starts_with("/events"))
+            .count(),
+        3
+    );
+    assert!(!requested.contains(&"/feed?page=5".to_string()));
+
+    Ok(())
+}
+
+#[test]
+fn cursor_pagination_sends_each_token_back_until_it_is_absent() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&requested);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let url = request.url().to_string();
+            seen.lock().unwrap().push(url.clone());
+            let (item, next) = match url.as_str() {
+                "/api/events?limit=1" => ("first", r#""c2""#),
+                "/api/events?limit=1&after=c2" => ("second", r#""c3""#),
+                _ => ("third", "null"),
+            };
+            let body = format!(
+                r#"{{"data": [{{"id": "{item}", "title": "Event {item}", "date": "2030-03-01"}}], "meta": {{"nextCursor": {next}}}}}"#
+            );
+            let _ = request.respond(Response::from_string(body));
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("api.toml"),
+        format!(
+            r#"[source]
+key = "test.cursor"
+name = "Cursor Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/api/events?limit=1"
+retry_attempts = 1
+
+[pagination]
+enabled = true
+strategy = "cursor"
+cursor_jsonpath = "/meta/nextCursor"
+cursor_param = "after"
+max_pages = 10
+
+[extract]
+format = "json"
+root_jsonpath = "/data"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+"#
+        ),
+    )?;
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..SyncOptions::default()
+    })?;
+
+    assert_eq!(reports[0].pages_fetched, 3);
+    assert_eq!(reports[0].inserted, 3);
+    assert_eq!(
+        *requested.lock().unwrap(),
+        [
+            "/api/events?limit=1",
+            "/api/events?limit=1&after=c2",
+            "/api/events?limit=1&after=c3",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn pagination_stops_on_pages_without_records_or_new_records() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&requested);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let url = request.url().to_string();
+            seen.lock().unwrap().push(url.clone());
+            // Past the end, /listing serves an empty list and /archive
+            // repeats its last page; neither sends an empty body.
+            let items: &[&str] = match url.as_str() {
+                "/listing?page=1" => &["Listing one", "Listing two"],
+                "/listing?page=2" => &["Listing three"],
+                "/archive?page=1" => &["Archive one"],
+                url if url.starts_with("/archive") => &["Archive two"],
+                _ => &[],
+            };
+            let items = items
+                .iter()
+                .map(|title| {
+                    format!(r#"<li><a class="title" href="/events/{title}">{title}</a><time>2030-03-01</time></li>"#)
+                })
+                .collect::<String>();
+            let body = format!("<html><body><h1>Events</h1><ul>{items}</ul></body></html>");
+            let _ = request.respond(Response::from_string(body));
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for (name, stop_on) in [("listing", "no_records"), ("archive", "no_new_records")] {
+        fs::write(
+            config_dir.join(format!("{name}.toml")),
+            format!(
+                r#"[source]
+key = "test.{name}"
+name = "{name}"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{name}"
+retry_attempts = 1
+
+[pagination]
+enabled = true
+start_page = 1
+max_pages = 10
+stop_on = "{stop_on}"
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+absolutize = true
+"#
+            ),
+        )?;
+    }
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..SyncOptions::default()
+    })?;
+
+    let report = |key: &str| {
+        reports
+            .iter()
+            .find(|report| report.source_key == key)
+            .expect("report")
+    };
+    assert_eq!(report("test.listing").pages_fetched, 2);
+    assert_eq!(report("test.listing").inserted, 3);
+    assert_eq!(report("test.archive").pages_fetched, 2);
+    assert_eq!(report("test.archive").inserted, 2);
+    let requested = requested.lock().unwrap();
+    assert_eq!(
+        requested
+            .iter()
+            .filter(|url| url.starts_with("/listing"))
+            .count(),
+        3
+    );
+    assert_eq!(
+        requested
+            .iter()
+            .filter(|url| url.starts_with("/archive"))
+            .count(),
+        3
+    );
+
+    Ok(())
+}
+
+#[test]
+fn check_links_flags_missing_pages_and_marks_their_events() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let restored = Arc::new(AtomicBool::new(false));
+    let gone_is_back = Arc::clone(&restored);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let head = *request.method() == Method::Head;
+            let status = match request.url() {
+                "/events/gone" if !gone_is_back.load(Ordering::SeqCst) => 404,
+                "/events/no-head" if head => 405,
+                "/events/broken" => 500,
+                _ => 200,
+            };
+            let items = ["live", "gone", "no-head", "broken"]
+                .iter()
+                .map(|name| {
+                    format!(r#"<li><a class="title" href="http://127.0.0.1:{port}/events/{name}">{name}</a><time>2030-03-01</time></li>"#)
+                })
+                .collect::<String>();
+            let _ = request.respond(
+                Response::from_string(format!("<ul>{items}</ul>")).with_status_code(status),
+            );
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("links.toml"),
+        format!(
+            r#"[source]
+key = "test.links"
+name = "Links"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/list"
+retry_attempts = 1
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+"#
+        ),
+    )?;
+    let state_path = root.path().join("state/events.json");
+    let out_dir = root.path().join("out");
+    sync_sources(&SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: state_path.clone(),
+        out_dir: out_dir.clone(),
+        ..SyncOptions::default()
+    })?;
+
+    let options = CheckLinksOptions {
+        config_dir: config_dir.clone(),
+        state_path: state_path.clone(),
+        source: None,
+        jobs: 4,
+        annotate: true,
+    };
+    let report = check_links(&options)?;
+    assert_eq!(report.checked, 4);
+    let urls = |problems: &[rics::links::LinkProblem]| {
+        problems
+            .iter()
+            .map(|problem| (problem.url.clone(), problem.status))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        urls(&report.dead),
+        [(format!("http://127.0.0.1:{port}/events/gone"), Some(404))]
+    );
+    assert_eq!(
+        urls(&report.unreachable),
+        [(format!("http://127.0.0.1:{port}/events/broken"), Some(500))]
+    );
+    assert_eq!(report.annotated, 1);
+
+    let build = BuildOptions {
+        config_dir: config_dir.clone(),
+        state_path: state_path.clone(),
+        out_dir,
+        source: Some("test.links".to_string()),
+        year: Some(2030),
+    };
+    let mut calendar = Vec::new();
+    render_calendar_to(&build, &mut calendar)?;
+    let calendar = String::from_utf8(calendar)?;
+    assert_eq!(calendar.matches("X-RICS-LINK-STATUS:DEAD").count(), 1);
+
+    // Marks survive a sync and clear once the page is back.
+    sync_sources(&SyncOptions {
+        config_dir,
+        state_path,
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    })?;
+    let mut calendar = Vec::new();
+    render_calendar_to(&build, &mut calendar)?;
+    assert!(String::from_utf8(calendar)?.contains("X-RICS-LINK-STATUS:DEAD"));
+    restored.store(true, Ordering::SeqCst);
+    let report = check_links(&options)?;
+    assert!(report.dead.is_empty());
+    assert_eq!(report.annotated, 1);
+    let mut calendar = Vec::new();
+    render_calendar_to(&build, &mut calendar)?;
+    assert!(!String::from_utf8(calendar)?.contains("X-RICS-LINK-STATUS"));
+
+    Ok(())
+}
+
+#[test]
+fn recorded_responses_replay_the_same_events_offline() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let live = std::thread::spawn(move || {
+        let request = server.recv().expect("one request");
+        let body = r#"<ul><li><span class="title">Launch</span><time>2030-05-01</time></li><li><span class="title">Review</span><time>2030-05-02</time></li></ul>"#;
+        let _ = request.respond(
+            Response::from_string(body)
+                .with_header(
+                    "Content-Type: text/html; charset=utf-8"
+                        .parse::<tiny_http::Header>()
+                        .unwrap(),
+                )
+                .with_header(
+                    "Set-Cookie: session=secret"
+                        .parse::<tiny_http::Header>()
+                        .unwrap(),
+                ),
+        );
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let config = |fetch: &str| {
+        format!(
+            r#"[source]
+key = "test.replay"
+name = "Replay"
+domain = "publishing"
+
+[fetch]
+{fetch}
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        )
+    };
+    fs::write(
+        config_dir.join("replay.toml"),
+        config(&format!(
+            "base_url = \"http://127.0.0.1:{port}/list\"\nretry_attempts = 1"
+        )),
+    )?;
+    let recordings = root.path().join("recordings");
+    let options = |state: &str| SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join(state),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..SyncOptions::default()
+    };
+    sync_sources(&SyncOptions {
+        record_dir: Some(recordings.clone()),
+        force: false,
+        ..options("live.json")
+    })?;
+    live.join().expect("server thread");
+
+    let index = fs::read_to_string(recordings.join("test-replay/responses.json"))?;
+    assert!(index.contains(&format!("http://127.0.0.1:{port}/list")));
+    assert!(index.contains("text/html; charset=utf-8"));
+    assert!(!index.contains("secret"));
+
+    // The server is gone; replay must not need it.
+    fs::write(
+        config_dir.join("replay.toml"),
+        config(&format!(
+            "mode = \"replay\"\nreplay_dir = \"../recordings\"\nbase_url = \"http://127.0.0.1:{port}/list\""
+        )),
+    )?;
+    sync_sources(&options("replayed.json"))?;
+
+    let events = |state: &str| -> Result<Vec<(String, String, Option<String>)>> {
+        Ok(rics::store::load_state(&root.path().join(state))?
+            .events
+            .into_iter()
+            .map(|(key, record)| (key, record.title, record.source_url))
+            .collect())
+    };
+    let live_events = events("live.json")?;
+    assert_eq!(live_events.len(), 2);
+    assert_eq!(events("replayed.json")?, live_events);
+    Ok(())
+}
+
+#[test]
+fn pagination_concurrency_fetches_pages_in_bounded_batches_and_keeps_their_order() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let requested = Arc::new(AtomicUsize::new(0));
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    {
+        let (requested, in_flight, peak) = (
+            Arc::clone(&requested),
+            Arc::clone(&in_flight),
+            Arc::clone(&peak),
+        );
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let (requested, in_flight, peak) = (
+                    Arc::clone(&requested),
+                    Arc::clone(&in_flight),
+                    Arc::clone(&peak),
+                );
+                std::thread::spawn(move || {
+                    requested.fetch_add(1, Ordering::SeqCst);
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    let page = request
+                        .url()
+                        .rsplit_once("page=")
+                        .and_then(|(_, page)| page.parse::<usize>().ok())
+                        .unwrap_or_default();
+                    // Later pages answer first, so order can't come from timing.
+                    std::thread::sleep(Duration::from_millis(400 - 50 * (page as u64 % 3)));
+                    let body = if page < 5 {
+                        format!(
+                            r#"<li><span class="title">Page {page}</span><time>2030-03-0{}</time></li>"#,
+                            page + 1
+                        )
+                    } else {
+                        String::new()
+                    };
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let _ = request.respond(Response::from_string(body));
+                });
+            }
+        });
+    }
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let path = config_dir.join("pages.toml");
+    let config = |strategy: &str| {
+        format!(
+            r#"[source]
+key = "test.pages"
+name = "Pages"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/list"
+retry_attempts = 1
+
+[pagination]
+enabled = true
+strategy = "{strategy}"
+max_pages = 9
+concurrency = 3
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        )
+    };
+    fs::write(&path, config("query_param"))?;
+    let source = rics::config::load_source_file(&path)?;
+    let docs = rics::fetch::fetch_source_documents(&source)?;
+
+    assert_eq!(
+        docs.iter().map(|doc| doc.page_index).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+    assert!(docs[4].source_url.ends_with("page=4"));
+    // Page 5 came back empty, so the third batch was never requested.
+    assert_eq!(requested.load(Ordering::SeqCst), 6);
+    assert_eq!(peak.load(Ordering::SeqCst), 3);
+
+    fs::write(&path, config("next_link"))?;
+    let err = rics::config::load_source_file(&path).unwrap_err();
+    assert!(format!("{err:#}").contains("query_param"), "{err:#}");
+    Ok(())
+}
+
+#[test]
+fn retries_honor_retry_after_and_only_retry_listed_statuses() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let requests = Arc::new(Mutex::new(Vec::<String>::new()));
+    {
+        let requests = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let path = request.url().to_string();
+                let seen = {
+                    let mut requests = requests.lock().unwrap();
+                    requests.push(path.clone());
+                    requests.iter().filter(|seen| **seen == path).count()
+                };
+                let response = match path.as_str() {
+                    "/busy" if seen == 1 => Response::from_string("slow down")
+                        .with_status_code(429)
+                        .with_header(tiny_http::Header::from_bytes("Retry-After", "1").unwrap()),
+                    "/busy" => Response::from_string(
+                        r#"<li><span class="title">Hearing</span><time>2030-03-01</time></li>"#,
+                    ),
+                    "/stalled" => Response::from_string("maintenance")
+                        .with_status_code(503)
+                        .with_header(tiny_http::Header::from_bytes("Retry-After", "3600").unwrap()),
+                    _ => Response::from_string("gone").with_status_code(404),
+                };
+                let _ = request.respond(response);
+            }
+        });
+    }
+
+    let root = tempdir()?;
+    let path = root.path().join("retry.toml");
+    let load = |page: &str| -> Result<_> {
+        fs::write(
+            &path,
+            format!(
+                r#"[source]
+key = "test.retry"
+name = "Retry"
+domain = "government"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{page}"
+retry_attempts = 3
+retry_backoff_ms = 20000
+retry_max_backoff_ms = 5000
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+            ),
+        )?;
+        rics::config::load_source_file(&path)
+    };
+
+    // Retry-After wins over the (capped) exponential backoff.
+    let started = Instant::now();
+    let docs = rics::fetch::fetch_source_documents(&load("busy")?)?;
+    let waited = started.elapsed();
+    assert_eq!(docs.len(), 1);
+    assert!(waited >= Duration::from_millis(900), "{waited:?}");
+    assert!(waited < Duration::from_millis(2500), "{waited:?}");
+
+    // A 404 is not worth retrying, and an hour-long Retry-After is past the cap.
+    let started = Instant::now();
+    let err = rics::fetch::fetch_source_documents(&load("missing")?).unwrap_err();
+    assert!(format!("{err:#}").contains("404"), "{err:#}");
+    let err = rics::fetch::fetch_source_documents(&load("stalled")?).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("exceeds fetch.retry_max_backoff_ms"),
+        "{err:#}"
+    );
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.iter().filter(|path| *path == "/busy").count(), 2);
+    assert_eq!(
+        requests.iter().filter(|path| *path == "/missing").count(),
+        1
+    );
+    assert_eq!(
+        requests.iter().filter(|path| *path == "/stalled").count(),
+        1
+    );
+    drop(requests);
+
+    fs::write(
+        &path,
+        fs::read_to_string(&path)?.replace(
+            "retry_max_backoff_ms = 5000",
+            "retry_max_backoff_ms = 5000\nretry_statuses = [503, 302]",
+        ),
+    )?;
+    let err = rics::config::load_source_file(&path).unwrap_err();
+    assert!(format!("{err:#}").contains("not 302"), "{err:#}");
+    Ok(())
+}
+
+#[test]
+fn oversized_responses_are_rejected_truncated_or_spooled() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let page = format!(
+                "2030-03-01 | Board meeting\n{}",
+                "Minutes to follow.\n".repeat(200)
+            );
+            let response = if request.url() == "/chunked" {
+                // No Content-Length, so the limit is only found by reading.
+                Response::new(200.into(), Vec::new(), page.as_bytes(), None, None)
+            } else {
+                Response::new(
+                    200.into(),
+                    Vec::new(),
+                    page.as_bytes(),
+                    Some(page.len()),
+                    None,
+                )
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    let root = tempdir()?;
+    let path = root.path().join("large.toml");
+    let load = |page: &str, oversized: &str, spool_threshold: u64| -> Result<_> {
+        fs::write(
+            &path,
+            format!(
+                r#"[source]
+key = "test.large"
+name = "Large"
+domain = "government"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/{page}"
+retry_attempts = 1
+max_body_bytes = 100
+oversized_body = "{oversized}"
+spool_threshold_bytes = {spool_threshold}
+
+[extract]
+format = "text"
+record_regex = "(?m)^(\\d{{4}}-\\d{{2}}-\\d{{2}} \\| .+)$"
+"#
+            ),
+        )?;
+        rics::config::load_source_file(&path)
+    };
+
+    for page in ["sized", "chunked"] {
+        let err = rics::fetch::fetch_source_documents(&load(page, "reject", 1000)?).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("over fetch.max_body_bytes (100)"),
+            "{page}: {err:#}"
+        );
+
+        // Bodies past the spool threshold are read back from a temp file.
+        for spool_threshold in [1000, 50] {
+            let source = load(page, "truncate", spool_threshold)?;
+            let docs = rics::fetch::fetch_source_documents(&source)?;
+            assert_eq!(docs[0].body.len(), 100, "{page}");
+            assert_eq!(docs[0].body.is_spooled(), spool_threshold < 100, "{page}");
+            let events = rics::parser::parse_source_events(&source, &docs)?;
+            assert_eq!(events.len(), 1, "{page}");
+            assert_eq!(events[0].title, "Board meeting");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn date_placeholders_resolve_with_offsets_at_sync_time() -> Result<()> {
+    use chrono::{Datelike, Days, Months, Utc};
+
+    let root = tempdir()?;
+    let path = root.path().join("templated.toml");
+    fs::write(
+        &path,
+        r#"[source]
+key = "test.templated"
+name = "Templated"
+domain = "economics"
+timezone = "UTC"
+
+[fetch]
+mode = "inline"
+inline_data = "{{year}} {{year+1}} {{year-1}} {{month}} {{month+1}} {{today}} {{today+7}} {{iso_week}} {{iso_week-1}} {{year+x}}"
+
+[extract]
+format = "text"
+"#,
+    )?;
+    let source = rics::config::load_source_file(&path)?;
+    let docs = rics::fetch::fetch_source_documents(&source)?;
+
+    let today = Utc::now().date_naive();
+    let expected = [
+        today.year().to_string(),
+        (today.year() + 1).to_string(),
+        (today.year() - 1).to_string(),
+        today.format("%m").to_string(),
+        (today + Months::new(1)).format("%m").to_string(),
+        today.format("%Y-%m-%d").to_string(),
+        (today + Days::new(7)).format("%Y-%m-%d").to_string(),
+        today.format("%V").to_string(),
+        (today - Days::new(7)).format("%V").to_string(),
+        "{{year+x}}".to_string(),
+    ]
+    .join(" ");
+    assert_eq!(docs[0].text(), expected);
+    Ok(())
+}
+
+#[test]
+fn fetch_iterate_requests_one_page_per_period() -> Result<()> {
+    use chrono::{Datelike, Months, NaiveDate, Utc};
+
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            // `/cal/2030/03` lists one release on the 15th of that month.
+            let month = request.url().trim_start_matches("/cal/").replace('/', "-");
+            let body = format!(
+                r#"<li><span class="title">Release {month}</span><time>{month}-15</time></li>"#
+            );
+            let _ = request.respond(Response::from_string(body));
+        }
+    });
+
+    let root = tempdir()?;
+    let path = root.path().join("monthly.toml");
+    let config = |iterate: &str| {
+        format!(
+            r#"[source]
+key = "test.monthly"
+name = "Monthly"
+domain = "economics"
+timezone = "UTC"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/cal/{{{{year}}}}/{{{{month}}}}"
+retry_attempts = 1
+
+[fetch.iterate]
+{iterate}
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        )
+    };
+    fs::write(
+        &path,
+        config("unit = \"month\"\nfrom = \"-1 month\"\nto = 1"),
+    )?;
+    let source = rics::config::load_source_file(&path)?;
+    let docs = rics::fetch::fetch_source_documents(&source)?;
+
+    let this_month = Utc::now().date_naive().with_day(1).expect("first of month");
+    let expected = [
+        this_month - Months::new(1),
+        this_month,
+        this_month + Months::new(1),
+    ]
+    .map(|month| format!("http://127.0.0.1:{port}/cal/{}", month.format("%Y/%m")));
+    assert_eq!(
+        docs.iter()
+            .map(|doc| doc.source_url.as_str())
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert_eq!(
+        docs.iter().map(|doc| doc.page_index).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    assert_eq!(rics::parser::parse_source_events(&source, &docs)?.len(), 3);
+
+    // Offsets in another unit land on the period that contains them.
+    let weekly = rics::config::parse_source_config(&config(
+        "unit = \"week\"\nfrom = \"-1 month\"\nto = \"+1 week\"",
+    ))?;
+    let periods = weekly
+        .fetch
+        .iterate
+        .expect("iterate")
+        .periods(NaiveDate::from_ymd_opt(2030, 3, 14).expect("date"));
+    assert_eq!(
+        periods.first().map(ToString::to_string).as_deref(),
+        Some("2030-02-11")
+    );
+    assert_eq!(
+        periods.last().map(ToString::to_string).as_deref(),
+        Some("2030-03-18")
+    );
+    assert_eq!(periods.len(), 6);
+
+    // A year of months includes a December, whose next month is in the
+    // next year.
+    fs::write(
+        &path,
+        config("unit = \"month\"\nfrom = \"-11 months\"\nto = 0").replace(
+            "/cal/{{year}}/{{month}}",
+            "/cal/{{year_month+1}}",
+        ),
+    )?;
+    let source = rics::config::load_source_file(&path)?;
+    let docs = rics::fetch::fetch_source_documents(&source)?;
+    let expected = (0..12)
+        .rev()
+        .map(|ago| this_month - Months::new(ago) + Months::new(1))
+        .map(|month| format!("http://127.0.0.1:{port}/cal/{}", month.format("%Y-%m")))
+        .collect::<Vec<_>>();
+    assert!(expected.iter().any(|url| url.ends_with("-01")));
+    assert_eq!(
+        docs.iter()
+            .map(|doc| doc.source_url.as_str())
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    for (iterate, message) in [
+        ("unit = \"month\"\nfrom = 2\nto = 1", "must not be after"),
+        (
+            "unit = \"month\"\nto = \"+2 fortnights\"",
+            "unknown period unit",
+        ),
+        ("unit = \"day\"\nto = \"+3 years\"", "at most 400"),
+    ] {
+        let err = rics::config::parse_source_config(&config(iterate)).unwrap_err();
+        assert!(format!("{err:#}").contains(message), "{iterate}: {err:#}");
+    }
+    Ok(())
+}
+
+#[test]
+fn min_interval_skips_recently_fetched_sources_unless_forced() -> Result<()> {
+    use rics::config::{FetchInterval, parse_source_config};
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for (key, interval) in [
+        ("test.hourly", "\nmin_interval = \"6h\""),
+        ("test.always", ""),
+    ] {
+        fs::write(
+            config_dir.join(format!("{key}.toml")),
+            format!(
+                r#"[source]
+key = "{key}"
+name = "{key}"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": [{{"title": "Board meeting", "date": "2099-05-01"}}]}}"""{interval}
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+"#
+            ),
+        )?;
+    }
+    let options = |force| SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        force,
+        ..SyncOptions::default()
+    };
+    let synced = |force| -> Result<Vec<String>> {
+        let mut keys = sync_sources(&options(force))?
+            .into_iter()
+            .map(|report| report.source_key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        Ok(keys)
+    };
+
+    assert_eq!(synced(false)?, ["test.always", "test.hourly"]);
+    assert_eq!(synced(false)?, ["test.always"]);
+    assert_eq!(synced(true)?, ["test.always", "test.hourly"]);
+
+    let parsed = |raw: &str| raw.parse::<FetchInterval>().map(|i| i.seconds);
+    assert_eq!(parsed("90s")?, 90);
+    assert_eq!(parsed("30m")?, 1_800);
+    assert_eq!(parsed("1d")?, 86_400);
+    assert_eq!(parsed("2w")?, 1_209_600);
+    assert_eq!(parsed("45")?, 45);
+    assert!(parsed("6 fortnights").is_err());
+    let config = fs::read_to_string(config_dir.join("test.always.toml"))?
+        .replace("[extract]", "min_interval = 3600\n\n[extract]");
+    assert_eq!(
+        parse_source_config(&config)?.fetch.min_interval,
+        Some(FetchInterval { seconds: 3_600 })
+    );
+    Ok(())
+}
+
+#[test]
+fn network_policy_refuses_hosts_private_addresses_and_redirects() -> Result<()> {
+    use rics::config::{NetworkConfig, load_source_file};
+    use rics::fetch::fetch_source_documents;
+    use rics::network::{check_url, is_public};
+
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let requested = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&requested);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let url = request.url().to_string();
+            seen.lock().unwrap().push(url.clone());
+            let response = match url.as_str() {
+                "/moved" => Response::from_string("").with_status_code(302).with_header(
+                    tiny_http::Header::from_bytes(
+                        "Location",
+                        format!("http://localhost:{port}/events"),
+                    )
+                    .unwrap(),
+                ),
+                _ => Response::from_string(
+                    r#"<li><span class="title">Board meeting</span><time>2030-03-01</time></li>"#,
+                ),
+            };
+            let _ = request.respond(response);
+        }
+    });
+
+    let fetch = |host: &str, path: &str, network: &str| -> Result<usize> {
+        let root = tempdir()?;
+        let config_dir = root.path().join("sources");
+        fs::create_dir_all(&config_dir)?;
+        fs::write(root.path().join("network.toml"), network)?;
+        let source_path = config_dir.join("guarded.toml");
+        fs::write(
+            &source_path,
+            format!(
+                r#"[source]
+key = "test.guarded"
+name = "Guarded"
+domain = "government"
+
+[fetch]
+base_url = "http://{host}:{port}{path}"
+retry_attempts = 1
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+            ),
+        )?;
+        Ok(fetch_source_documents(&load_source_file(&source_path)?)?.len())
+    };
+    let refusal = |result: Result<usize>| format!("{:#}", result.unwrap_err());
+
+    assert_eq!(fetch("127.0.0.1", "/events", "")?, 1);
+    assert_eq!(fetch("127.0.0.1", "/moved", "")?, 1);
+    assert_eq!(requested.lock().unwrap().len(), 3);
+
+    let err = refusal(fetch(
+        "127.0.0.1",
+        "/events",
+        "allow_hosts = [\"*.example.org\"]",
+    ));
+    assert!(err.contains("host is not allowed"), "{err}");
+    let err = refusal(fetch("127.0.0.1", "/events", "block_private_ips = true"));
+    assert!(err.contains("127.0.0.1 is not a public address"), "{err}");
+    // A name that resolves inward is refused when connecting.
+    let err = refusal(fetch("localhost", "/events", "block_private_ips = true"));
+    assert!(err.contains("non-public addresses"), "{err}");
+    assert_eq!(requested.lock().unwrap().len(), 3);
+
+    // Redirects are checked hop by hop.
+    let err = refusal(fetch("127.0.0.1", "/moved", "deny_hosts = [\"localhost\"]"));
+    assert!(err.contains("host is not allowed"), "{err}");
+    assert_eq!(*requested.lock().unwrap().last().unwrap(), "/moved");
+
+    let network = NetworkConfig {
+        allow_hosts: vec!["*.example.org".to_string(), "calendar.gov".to_string()],
+        deny_hosts: vec!["internal.example.org".to_string()],
+        block_private_ips: false,
+    };
+    assert!(network.host_allowed("events.example.org"));
+    assert!(network.host_allowed("CALENDAR.gov."));
+    assert!(!network.host_allowed("example.org"));
+    assert!(!network.host_allowed("badexample.org"));
+    assert!(!network.host_allowed("internal.example.org"));
+    // An IPv6 entry matches the URL host and a bare ftp/sftp/imap host alike.
+    let v6 = NetworkConfig {
+        allow_hosts: vec!["2001:DB8:0::1".to_string()],
+        ..NetworkConfig::default()
+    };
+    assert!(check_url(&v6, "http://[2001:db8::1]:8080/events").is_ok());
+    assert!(v6.host_allowed("2001:db8:0:0::1"));
+    assert!(!v6.host_allowed("2001:db8::2"));
+    for private in [
+        "10.1.2.3",
+        "169.254.169.254",
+        "100.64.0.1",
+        "::1",
+        "fd00::1",
+        "::ffff:192.168.0.1",
+        "192.0.0.8",
+        "198.18.0.1",
+        "198.19.255.254",
+        "64:ff9b::a9fe:a9fe",
+        "2001:db8::1",
+        "2002:c0a8:1::1",
+        "::10.0.0.1",
+        "2001:0:4136:e378:8000:63bf:3fff:fdd2",
+        "fec0::1",
+    ] {
+        assert!(!is_public(private.parse()?), "{private}");
+    }
+    assert!(is_public("93.184.216.34".parse()?));
+    assert!(is_public("2606:4700::1111".parse()?));
+    assert!(is_public("64:ff9b::5db8:d822".parse()?));
+    assert!(is_public("2002:5db8:d822::1".parse()?));
+    assert!(is_public("::93.184.216.34".parse()?));
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use anyhow::Result;
+use rics::config::load_source_file;
+use rics::fetch::fetch_source_documents;
+use rics::parser::preview_source_events;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use tempfile::tempdir;
+
+const CONFIG: &str = r#"[source]
+key = "test.ftp"
+name = "FTP Fixture"
+domain = "government"
+
+[fetch]
+mode = "ftp"
+
+[fetch.ftp]
+host = "127.0.0.1"
+port = PORT
+path = "/pub/agendas/agenda-*.html"
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#;
+
+#[test]
+fn ftp_mode_downloads_every_file_matching_the_path_glob() -> Result<()> {
+    let control = TcpListener::bind("127.0.0.1:0")?;
+    let data = TcpListener::bind("127.0.0.1:0")?;
+    let port = control.local_addr()?.port();
+    let data_port = data.local_addr()?.port();
+    let server = std::thread::spawn(move || -> Result<Vec<String>> {
+        let (stream, _) = control.accept()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let mut commands = Vec::new();
+        writer.write_all(b"220-fake ftp\r\n220 ready\r\n")?;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let command = line.trim_end().to_string();
+            commands.push(command.clone());
+            let (verb, arg) = command.split_once(' ').unwrap_or((&command, ""));
+            let body = match (verb, arg) {
+                ("NLST", _) => Some(
+                    "agenda-2030.html\r\nnotes.txt\r\n/pub/agendas/agenda-2031.html\r\n"
+                        .to_string(),
+                ),
+                ("RETR", path) => path.strip_prefix("/pub/agendas/agenda-").map(|name| {
+                    let year = name.trim_end_matches(".html");
+                    format!(
+                        "<ul><li><span class=\"title\">Board {year}</span><time>{year}-03-01</time></li></ul>"
+                    )
+                }),
+                _ => None,
+            };
+            if let Some(body) = body {
+                writer.write_all(b"150 opening data connection\r\n")?;
+                let (mut conn, _) = data.accept()?;
+                conn.write_all(body.as_bytes())?;
+                drop(conn);
+                writer.write_all(b"226 transfer complete\r\n")?;
+                continue;
+            }
+            // Only the first data connection is offered over EPSV, so the
+            // PASV fallback is exercised too.
+            let reply = match verb {
+                "USER" => "331 password required".to_string(),
+                "PASS" => "230 logged in".to_string(),
+                "TYPE" => "200 binary".to_string(),
+                "EPSV" if commands.iter().filter(|c| *c == "EPSV").count() == 1 => {
+                    format!("229 Entering Extended Passive Mode (|||{data_port}|)")
+                }
+                "EPSV" => "502 not implemented".to_string(),
+                "PASV" => format!(
+                    "227 Entering Passive Mode (10,0,0,9,{},{})",
+                    data_port >> 8,
+                    data_port & 0xff
+                ),
+                "QUIT" => "221 bye".to_string(),
+                _ => "500 unknown".to_string(),
+            };
+            write!(writer, "{reply}\r\n")?;
+            if verb == "QUIT" {
+                break;
+            }
+        }
+        Ok(commands)
+    });
+
+    let root = tempdir()?;
+    let config = CONFIG.replace("PORT", &port.to_string());
+    let path = root.path().join("ftp.toml");
+    fs::write(&path, &config)?;
+    // SAFETY: no other test in this binary reads or writes the environment.
+    unsafe { std::env::set_var("RICS_CONTACT_EMAIL", "ops@example.org") };
+
+    let docs = fetch_source_documents(&load_source_file(&path)?)?;
+    let urls = docs
+        .iter()
+        .map(|doc| doc.source_url.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            format!("ftp://127.0.0.1:{port}/pub/agendas/agenda-2030.html"),
+            format!("ftp://127.0.0.1:{port}/pub/agendas/agenda-2031.html"),
+        ]
+    );
+    let events = preview_source_events(&config, &docs)?;
+    let titles = events
+        .iter()
+        .map(|event| event.title.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(titles, ["Board 2030", "Board 2031"]);
+
+    let commands = server.join().expect("server thread")?;
+    assert_eq!(commands[0], "USER anonymous");
+    assert_eq!(commands[1], "PASS ops@example.org");
+    assert_eq!(commands[3], "EPSV");
+    assert_eq!(commands[4], "NLST /pub/agendas");
+    assert!(commands.contains(&"PASV".to_string()));
+    assert!(commands.contains(&"RETR /pub/agendas/agenda-2031.html".to_string()));
+    Ok(())
+}
+
+#[test]
+fn ftp_mode_refuses_servers_network_toml_blocks() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let path = config_dir.join("ftp.toml");
+    fs::write(&path, CONFIG.replace("PORT", &port.to_string()))?;
+
+    fs::write(root.path().join("network.toml"), "block_private_ips = true")?;
+    let err = fetch_source_documents(&load_source_file(&path)?).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("non-public addresses"),
+        "{err:#}"
+    );
+    fs::write(
+        root.path().join("network.toml"),
+        "allow_hosts = [\"ftp.example.org\"]",
+    )?;
+    let err = fetch_source_documents(&load_source_file(&path)?).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("host is not allowed"),
+        "{err:#}"
+    );
+    Ok(())
+}
+
+const SFTP_CONFIG: &str = r#"[source]
+key = "test.sftp"
+name = "SFTP Fixture"
+domain = "government"
+
+[fetch]
+mode = "sftp"
+timeout_secs = 5
+
+[fetch.sftp]
+host = "127.0.0.1"
+port = PORT
+username = "releases"
+password_env = "RICS_TEST_SFTP_PASSWORD"
+path = "/pub/releases/*.csv"
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#;
+
+#[test]
+fn sftp_mode_validates_its_config_and_speaks_ssh() -> Result<()> {
+    use rics::config::parse_source_config;
+
+    let config = SFTP_CONFIG.replace("PORT", "22");
+    let err =
+        parse_source_config(&config.replace("password_env = \"RICS_TEST_SFTP_PASSWORD\"\n", ""))
+            .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("password_env or private_key"),
+        "{err:#}"
+    );
+    let err =
+        parse_source_config(&config.replace("username =", "host_key_sha256 = \"abc\"\nusername ="))
+            .unwrap_err();
+    assert!(format!("{err:#}").contains("64 hex digits"), "{err:#}");
+    let err = parse_source_config(&config.replace("/pub/releases/", "/pub/*/")).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("only use a glob in its file name"),
+        "{err:#}"
+    );
+
+    // A server that does not answer with an SSH banner fails the handshake.
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let server = std::thread::spawn(move || -> Result<()> {
+        let (mut stream, _) = listener.accept()?;
+        stream.write_all(b"220 not an ssh server\r\n")?;
+        Ok(())
+    });
+    let root = tempdir()?;
+    let path = root.path().join("sftp.toml");
+    fs::write(&path, SFTP_CONFIG.replace("PORT", &port.to_string()))?;
+    let err = fetch_source_documents(&load_source_file(&path)?).unwrap_err();
+    if cfg!(feature = "sftp") {
+        assert!(format!("{err:#}").contains("ssh handshake"), "{err:#}");
+        server.join().expect("server thread")?;
+    } else {
+        assert!(format!("{err:#}").contains("`sftp` feature"), "{err:#}");
+    }
+    Ok(())
+}
@@ -1,6 +1,10 @@
 use anyhow::Result;
 use rics::harness::{HarnessOptions, run_harness};
-use rics::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use rics::model::{Lifecycle, ParseWarningKind};
+use rics::pipeline::{
+    BuildOptions, SyncOptions, build_calendars, load_state_for_read, render_calendar_to,
+    sync_sources,
+};
 use std::fs;
 use std::path::Path;
 use tempfile::tempdir;
@@ -13,12 +17,16 @@ fn sync_builds_yearly_ics_files() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports.len(), 1);
     assert_eq!(reports[0].inserted, 2);
+    assert!(reports[0].bytes_downloaded > 0);
+
+    let json = serde_json::to_value(&reports[0])?;
+    assert_eq!(json["source_key"], "test.oecd.fixture");
+    assert!(json["skipped_records"].is_array());
 
     let y2026 = env
         .out_dir
@@ -38,6 +46,18 @@ fn sync_builds_yearly_ics_files() -> Result<()> {
     assert!(content.contains("SUMMARY:OECD Sample Report A"));
     assert!(content.contains("X-RICS-SOURCE-KEY:test.oecd.fixture"));
 
+    let state = load_state_for_read(&env.state_path)?;
+    let provenance = state
+        .events
+        .values()
+        .find(|event| event.title == "OECD Sample Report B")
+        .and_then(|event| event.provenance.clone())
+        .expect("provenance recorded");
+    assert_eq!(provenance.parser, "declarative:html");
+    assert_eq!(provenance.selector.as_deref(), Some("article.pub-card"));
+    assert_eq!(provenance.record_index, Some(1));
+    assert!(provenance.fetched_at.is_some());
+
     Ok(())
 }
 
@@ -49,8 +69,7 @@ fn sync_updates_existing_future_events() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     let fixture_html = env.data_dir.join("oecd_fixture.html");
@@ -64,8 +83,7 @@ fn sync_updates_existing_future_events() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports[0].updated, 1);
@@ -84,6 +102,12 @@ fn sync_updates_existing_future_events() -> Result<()> {
 
     assert_eq!(record.sequence, 1);
     assert!(record.title.contains("Revised"));
+    assert_eq!(record.history.len(), 1);
+    assert_eq!(record.history[0].title, "OECD Sample Report A");
+    assert_eq!(
+        record.rescheduled_from(),
+        chrono::NaiveDate::from_ymd_opt(2026, 5, 1)
+    );
 
     let content = fs::read_to_string(
         env.out_dir
@@ -96,6 +120,170 @@ fn sync_updates_existing_future_events() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn sync_links_rescheduled_event_to_cancelled_predecessor() -> Result<()> {
+    let env = setup_fixture_env()?;
+    let options = SyncOptions {
+        config_dir: env.config_dir.clone(),
+        state_path: env.state_path.clone(),
+        out_dir: env.out_dir.clone(),
+        ..SyncOptions::default()
+    };
+
+    sync_sources(&options)?;
+
+    let fixture_html = env.data_dir.join("oecd_fixture.html");
+    let html = fs::read_to_string(&fixture_html)?;
+    let updated = html
+        .replace("sample-report-b_456", "sample-report-b_789")
+        .replace("2027-01-15", "2027-02-10");
+    fs::write(&fixture_html, updated)?;
+
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].inserted, 1);
+    assert_eq!(reports[0].cancelled, 1);
+
+    let state = load_state_for_read(&env.state_path)?;
+    let old = state
+        .events
+        .values()
+        .find(|event| event.status == "cancelled")
+        .expect("cancelled predecessor must exist");
+    let new = state
+        .events
+        .values()
+        .find(|event| event.supersedes.is_some())
+        .expect("replacement must reference its predecessor");
+
+    assert_eq!(new.supersedes.as_deref(), Some(old.uid.as_str()));
+    assert_eq!(old.lifecycle, Lifecycle::Cancelled);
+    assert!(old.cancelled_at.is_some());
+    assert!(old.related_uids.contains(&new.uid));
+
+    let content = fs::read_to_string(
+        env.out_dir
+            .join("sources")
+            .join("test-oecd-fixture")
+            .join("test-oecd-fixture-2027.ics"),
+    )?;
+    assert!(content.contains(&format!("RELATED-TO;RELTYPE=X-RICS-SUPERSEDES:{}", old.uid)));
+
+    Ok(())
+}
+
+#[test]
+fn sync_expires_past_events_and_purges_after_delete_window() -> Result<()> {
+    let env = setup_fixture_env()?;
+    let options = SyncOptions {
+        config_dir: env.config_dir.clone(),
+        state_path: env.state_path.clone(),
+        out_dir: env.out_dir.clone(),
+        ..SyncOptions::default()
+    };
+
+    sync_sources(&options)?;
+
+    let fixture_html = env.data_dir.join("oecd_fixture.html");
+    let html = fs::read_to_string(&fixture_html)?;
+    fs::write(
+        &fixture_html,
+        html.replace("OECD Sample Report A", "")
+            .replace("sample-report-a_123", "gone"),
+    )?;
+
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].expired, 1);
+    assert_eq!(reports[0].purged, 0);
+
+    let state = load_state_for_read(&env.state_path)?;
+    assert!(
+        state
+            .events
+            .values()
+            .any(|event| event.lifecycle == Lifecycle::Expired)
+    );
+
+    let config_path = env.config_dir.join("oecd_fixture.toml");
+    let mut config = fs::read_to_string(&config_path)?;
+    config.push_str("\n[lifecycle]\ndelete_after_days = 0\n");
+    fs::write(&config_path, config)?;
+
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].purged, 1);
+    let state = load_state_for_read(&env.state_path)?;
+    assert_eq!(state.events.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn rebuild_leaves_identical_calendar_files_untouched() -> Result<()> {
+    let env = setup_fixture_env()?;
+
+    sync_sources(&SyncOptions {
+        config_dir: env.config_dir.clone(),
+        state_path: env.state_path.clone(),
+        out_dir: env.out_dir.clone(),
+        ..SyncOptions::default()
+    })?;
+
+    let path = env
+        .out_dir
+        .join("sources")
+        .join("test-oecd-fixture")
+        .join("test-oecd-fixture-2026.ics");
+    let before = fs::metadata(&path)?.modified()?;
+    std::thread::sleep(std::time::Duration::from_millis(20));
+
+    build_calendars(&BuildOptions {
+        config_dir: env.config_dir.clone(),
+        state_path: env.state_path.clone(),
+        out_dir: env.out_dir.clone(),
+        source: None,
+        year: None,
+    })?;
+
+    assert_eq!(fs::metadata(&path)?.modified()?, before);
+
+    Ok(())
+}
+
+#[test]
+fn render_to_writer_matches_built_file_without_writing() -> Result<()> {
+    let env = setup_fixture_env()?;
+    sync_sources(&SyncOptions {
+        config_dir: env.config_dir.clone(),
+        state_path: env.state_path.clone(),
+        out_dir: env.out_dir.clone(),
+        ..SyncOptions::default()
+    })?;
+
+    let scratch_out = env.out_dir.with_file_name("scratch-out");
+    let mut options = BuildOptions {
+        config_dir: env.config_dir.clone(),
+        state_path: env.state_path.clone(),
+        out_dir: scratch_out.clone(),
+        source: Some("test.oecd.fixture".to_string()),
+        year: Some(2026),
+    };
+    let mut rendered = Vec::new();
+    let events = render_calendar_to(&options, &mut rendered)?;
+    assert!(events > 0);
+    assert_eq!(
+        String::from_utf8(rendered)?,
+        fs::read_to_string(
+            env.out_dir
+                .join("sources/test-oecd-fixture/test-oecd-fixture-2026.ics")
+        )?
+    );
+    assert!(!scratch_out.exists());
+
+    options.year = None;
+    assert!(render_calendar_to(&options, &mut Vec::new()).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn harness_reports_stability_metrics() -> Result<()> {
     let env = setup_fixture_env()?;
@@ -114,6 +302,44 @@ fn harness_reports_stability_metrics() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn sync_reports_field_coverage_and_warns_on_sharp_drop() -> Result<()> {
+    let env = setup_fixture_env()?;
+    let options = SyncOptions {
+        config_dir: env.config_dir.clone(),
+        state_path: env.state_path.clone(),
+        out_dir: env.out_dir.clone(),
+        ..SyncOptions::default()
+    };
+
+    let first = sync_sources(&options)?;
+    assert_eq!(first[0].field_coverage["title"], 1.0);
+    assert_eq!(first[0].field_coverage["description"], 1.0);
+    assert_eq!(first[0].field_coverage["importance"], 1.0);
+    assert!(
+        first[0]
+            .warnings
+            .iter()
+            .all(|w| w.kind != ParseWarningKind::CoverageDrop)
+    );
+
+    let fixture_html = env.data_dir.join("oecd_fixture.html");
+    let html = fs::read_to_string(&fixture_html)?;
+    fs::write(&fixture_html, html.replace("class=\"summary\"", "class=\"abstract\""))?;
+
+    let second = sync_sources(&options)?;
+    assert_eq!(second[0].field_coverage["description"], 0.0);
+    let drop = second[0]
+        .warnings
+        .iter()
+        .find(|w| w.kind == ParseWarningKind::CoverageDrop)
+        .expect("coverage drop warning");
+    assert_eq!(drop.field.as_deref(), Some("description"));
+    assert_eq!(drop.message, "description filled in 0% of records, down from 100%");
+
+    Ok(())
+}
+
 struct FixtureEnv {
     config_dir: std::path::PathBuf,
     data_dir: std::path::PathBuf,
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use rics::fetch::FetchedDocument;
+use rics::holidays::{HolidayCalendar, easter_sunday};
+use rics::ics::render_named_year_calendar;
+use rics::parser::preview_source_events;
+use rics::pipeline::preview_records;
+use std::collections::BTreeMap;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn national_calendars_apply_easter_and_weekend_substitution() {
+    assert_eq!(easter_sunday(2030), Some(date(2030, 4, 21)));
+    assert_eq!(easter_sunday(2024), Some(date(2024, 3, 31)));
+
+    let us = HolidayCalendar::new("us", BTreeMap::new()).unwrap();
+    assert_eq!(
+        us.holiday(date(2027, 7, 5)).as_deref(),
+        Some("Independence Day (observed)")
+    );
+    assert_eq!(
+        us.holiday(date(2021, 12, 31)).as_deref(),
+        Some("New Year's Day (observed)")
+    );
+    assert_eq!(
+        us.holiday(date(2030, 11, 28)).as_deref(),
+        Some("Thanksgiving Day")
+    );
+    assert!(us.is_business_day(date(2030, 11, 29)));
+
+    let gb = HolidayCalendar::new("GB", BTreeMap::new()).unwrap();
+    assert_eq!(
+        gb.holiday(date(2027, 12, 27)).as_deref(),
+        Some("Christmas Day (observed)")
+    );
+    assert_eq!(
+        gb.holiday(date(2027, 12, 28)).as_deref(),
+        Some("Boxing Day (observed)")
+    );
+    assert_eq!(
+        gb.holiday(date(2030, 4, 19)).as_deref(),
+        Some("Good Friday")
+    );
+
+    let de = HolidayCalendar::new("DE", BTreeMap::new()).unwrap();
+    assert_eq!(
+        de.holiday(date(2030, 5, 30)).as_deref(),
+        Some("Christi Himmelfahrt")
+    );
+
+    assert!(HolidayCalendar::new("ZZ", BTreeMap::new()).is_none());
+    let custom = HolidayCalendar::new(
+        "ZZ",
+        BTreeMap::from([(date(2030, 3, 1), "Closure".to_string())]),
+    )
+    .unwrap();
+    assert!(!custom.is_business_day(date(2030, 3, 1)));
+}
+
+#[test]
+fn events_on_holidays_and_weekends_are_annotated() -> Result<()> {
+    let config = r#"[source]
+key = "holidays.releases"
+name = "Holiday Releases"
+domain = "economics"
+default_country = "US"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[holidays]
+annotate = true
+
+[holidays.extra]
+"2030-07-08" = "Agency closure"
+
+[publish]
+describe_holidays = true
+"#;
+    let body = serde_json::json!({"rows": [
+        {"id": "a", "title": "Jobs report", "date": "2030-07-04"},
+        {"id": "b", "title": "Survey", "date": "2030-07-06"},
+        {"id": "c", "title": "Claims", "date": "2030-07-08"},
+        {"id": "d", "title": "Trade", "date": "2030-07-09"}
+    ]});
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://holidays".to_string(),
+            body: serde_json::to_vec(&body)?.into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    let flags = candidates
+        .iter()
+        .map(|event| {
+            (
+                event.metadata.get("holiday").map(String::as_str),
+                event.metadata.get("weekend").map(String::as_str),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flags,
+        vec![
+            (Some("Independence Day (US)"), None),
+            (None, Some("Saturday")),
+            (Some("Agency closure (US)"), None),
+            (None, None),
+        ]
+    );
+
+    let publish = rics::config::parse_source_config(config)?.publish;
+    let records = preview_records(candidates)?;
+    let ics = render_named_year_calendar(
+        "Holiday Releases",
+        2030,
+        &records.iter().collect::<Vec<_>>(),
+        &publish,
+    );
+    assert!(ics.contains("X-RICS-HOLIDAY:Independence Day (US)"));
+    assert!(
+        ics.contains("DESCRIPTION:Warning: scheduled on a public holiday\\, Independence Day (US)")
+    );
+    assert!(ics.contains("DESCRIPTION:Warning: scheduled on a weekend (Saturday)"));
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{NaiveDate, TimeZone, Utc};
+use common::text_source;
+use rics::config::parse_source_config;
+use rics::ics::{
+    render_source_year_calendar, stream_source_year_calendar, write_source_year_calendar,
+};
+use rics::model::{EventRecord, EventTimeSpec};
+use std::fs;
+
+fn record(n: u32) -> EventRecord {
+    let stamp = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+    EventRecord {
+        uid: format!("ics-{n}"),
+        source_key: "ics.hearings".to_string(),
+        source_name: "Hearings".to_string(),
+        title: format!("Hearing {n}"),
+        description: Some(format!(
+            "Agenda item {n}: a description long enough that it has to be folded \
+             across several lines of the calendar file."
+        )),
+        time: EventTimeSpec::Date {
+            start: NaiveDate::from_yo_opt(2030, n % 365 + 1).expect("valid date"),
+            end: None,
+        },
+        status: "scheduled".to_string(),
+        event_type: "hearing".to_string(),
+        created_at: stamp,
+        last_modified: stamp,
+        last_seen_at: stamp,
+        ..EventRecord::default()
+    }
+}
+
+#[test]
+fn streamed_calendars_match_the_rendered_one_and_skip_unchanged_writes() -> Result<()> {
+    let source = parse_source_config(&text_source("ics.hearings", "Hearings"))?;
+    let records = (0..5_000).map(record).collect::<Vec<_>>();
+    let events = records.iter().collect::<Vec<_>>();
+
+    let rendered = render_source_year_calendar(&source, 2030, &events);
+    assert_eq!(rendered.matches("BEGIN:VEVENT").count(), 5_000);
+    assert!(rendered.ends_with("END:VCALENDAR\r\n"));
+    assert!(rendered.split("\r\n").all(|line| line.len() <= 75));
+
+    let mut streamed = Vec::new();
+    stream_source_year_calendar(&source, 2030, &events, &mut streamed)?;
+    assert_eq!(String::from_utf8(streamed)?, rendered);
+
+    let root = tempfile::tempdir()?;
+    let path = root.path().join("out/hearings-2030.ics");
+    assert!(write_source_year_calendar(&source, 2030, &events, &path)?);
+    assert_eq!(fs::read_to_string(&path)?, rendered);
+    let written_at = fs::metadata(&path)?.modified()?;
+
+    // Same content: the file, and so its mtime, is left alone.
+    assert!(!write_source_year_calendar(&source, 2030, &events, &path)?);
+    assert_eq!(fs::metadata(&path)?.modified()?, written_at);
+    assert!(!root.path().join("out/hearings-2030.ics.tmp").exists());
+
+    let fewer = &events[..10];
+    assert!(write_source_year_calendar(&source, 2030, fewer, &path)?);
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        render_source_year_calendar(&source, 2030, fewer)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "http")]
+
+use anyhow::Result;
+use rics::pipeline::{SyncOptions, sync_sources};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+use tiny_http::{Response, Server};
+
+const BODY: &str = r#"<html><body>
+<article class="item"><span class="title">Quarterly bulletin</span><time>2030-03-01</time></article>
+</body></html>"#;
+
+#[test]
+fn identity_policy_rotates_user_agent_and_sends_contact_headers() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let header = |name: &'static str| {
+                request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv(name))
+                    .map(|h| h.value.as_str().to_string())
+                    .unwrap_or_default()
+            };
+            let entry = (header("User-Agent"), header("From"), header("X-Contact"));
+            log.lock().unwrap().push(entry);
+            let _ = request.respond(Response::from_string(BODY));
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("bulletin.toml"),
+        format!(
+            r#"[source]
+key = "test.identity"
+name = "Identity Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/bulletin.html"
+retry_attempts = 1
+
+[fetch.identity]
+rotate = ["calbot/{{{{version}}}} (mailto:{{{{contact}}}})", "calbot-alt/1"]
+contact = "ops@example.org"
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        dry_run: true,
+        ..SyncOptions::default()
+    };
+    sync_sources(&options)?;
+    sync_sources(&options)?;
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    let agents = seen
+        .iter()
+        .map(|(ua, _, _)| ua.as_str())
+        .collect::<Vec<_>>();
+    assert!(agents.contains(&"calbot-alt/1"));
+    assert!(
+        agents.contains(
+            &format!(
+                "calbot/{} (mailto:ops@example.org)",
+                env!("CARGO_PKG_VERSION")
+            )
+            .as_str()
+        )
+    );
+    for (_, from, contact) in seen.iter() {
+        assert_eq!(from, "ops@example.org");
+        assert_eq!(contact, "ops@example.org");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fetch_auth_does_not_skip_rotated_user_agents() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let agent = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("User-Agent"))
+                .map(|h| h.value.as_str().to_string())
+                .unwrap_or_default();
+            log.lock().unwrap().push(agent);
+            let _ = request.respond(Response::from_string(BODY));
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("bulletin.toml"),
+        format!(
+            r#"[source]
+key = "test.identity_auth"
+name = "Identity Auth Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/bulletin.html"
+retry_attempts = 1
+
+[fetch.auth]
+mode = "bearer"
+secret_env = "RICS_TEST_IDENTITY_TOKEN"
+
+[fetch.identity]
+rotate = ["calbot-a/1", "calbot-b/1", "calbot-c/1"]
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+    // SAFETY: no other test in this binary reads or writes the environment.
+    unsafe { std::env::set_var("RICS_TEST_IDENTITY_TOKEN", "t0k3n") };
+
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        dry_run: true,
+        ..SyncOptions::default()
+    };
+    sync_sources(&options)?;
+    sync_sources(&options)?;
+
+    assert_eq!(*seen.lock().unwrap(), ["calbot-a/1", "calbot-b/1"]);
+
+    Ok(())
+}
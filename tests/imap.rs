@@ -0,0 +1,186 @@
+#![cfg(feature = "imap")]
+
+use anyhow::Result;
+use rics::config::load_source_file;
+use rics::fetch::fetch_source_documents;
+use rics::parser::preview_source_events;
+use rics::pipeline::{SyncOptions, sync_sources};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+use tempfile::tempdir;
+
+const MESSAGE: &str = "From: Bulletin <news@example.invalid>\r\n\
+Subject: Schedule update\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: text/html; charset=utf-8\r\n\
+\r\n\
+<ul><li class=\"event\"><span class=\"title\">Board meeting</span><time>2030-04-02</time></li></ul>\r\n";
+
+/// Fake IMAP server answering every search with all of `messages` and
+/// returning the commands it received.
+fn serve_imap(messages: Vec<(u32, String)>) -> Result<(u16, JoinHandle<Result<Vec<String>>>)> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let server = std::thread::spawn(move || -> Result<Vec<String>> {
+        let (stream, _) = listener.accept()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let mut commands = Vec::new();
+        writer.write_all(b"* OK fake imap ready\r\n")?;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let (tag, command) = line.trim_end().split_once(' ').expect("tagged command");
+            commands.push(command.to_string());
+            if command.starts_with("UID SEARCH") {
+                let uids = messages
+                    .iter()
+                    .map(|(uid, _)| uid.to_string())
+                    .collect::<Vec<_>>();
+                write!(writer, "* SEARCH {}\r\n", uids.join(" "))?;
+            } else if let Some(rest) = command.strip_prefix("UID FETCH ")
+                && let Some((uid, message)) = messages
+                    .iter()
+                    .find(|(uid, _)| rest.starts_with(&format!("{uid} ")))
+            {
+                write!(
+                    writer,
+                    "* 1 FETCH (UID {uid} BODY[] {{{}}}\r\n{message})\r\n",
+                    message.len()
+                )?;
+            }
+            write!(writer, "{tag} OK done\r\n")?;
+            if command == "LOGOUT" {
+                break;
+            }
+        }
+        Ok(commands)
+    });
+    Ok((port, server))
+}
+
+#[test]
+fn imap_mode_reads_html_bodies_from_matching_messages() -> Result<()> {
+    let (port, server) = serve_imap(vec![(7, MESSAGE.to_string())])?;
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("newsletter.toml"),
+        format!(
+            r#"[source]
+key = "test.imap"
+name = "IMAP Fixture"
+domain = "publishing"
+
+[fetch]
+mode = "imap"
+
+[fetch.imap]
+host = "127.0.0.1"
+port = {port}
+tls = false
+username = "reader"
+password_env = "RICS_TEST_IMAP_PASSWORD"
+from = "example.invalid"
+subject = "Schedule"
+since = "2026-01-01"
+
+[extract]
+root_selector = "li.event"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+    // SAFETY: the other tests in this binary only set the same value.
+    unsafe { std::env::set_var("RICS_TEST_IMAP_PASSWORD", "secret") };
+
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        dry_run: true,
+        ..SyncOptions::default()
+    })?;
+    assert_eq!(reports[0].inserted, 1);
+
+    let commands = server.join().expect("server thread")?;
+    assert_eq!(commands[0], r#"LOGIN "reader" "secret""#);
+    assert_eq!(commands[1], r#"EXAMINE "INBOX""#);
+    assert_eq!(
+        commands[2],
+        r#"UID SEARCH SINCE 1-Jan-2026 FROM "example.invalid" SUBJECT "Schedule""#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn imap_mode_skips_messages_from_other_senders() -> Result<()> {
+    // A lax server returns every message whatever the SEARCH criteria.
+    let other = MESSAGE
+        .replace(
+            "Bulletin <news@example.invalid>",
+            "Promo <deals@elsewhere.invalid>",
+        )
+        .replace("Board meeting", "Flash sale");
+    let (port, server) = serve_imap(vec![(7, MESSAGE.to_string()), (8, other)])?;
+
+    let root = tempdir()?;
+    let path = root.path().join("newsletter.toml");
+    fs::write(
+        &path,
+        format!(
+            r#"[source]
+key = "test.imap_from"
+name = "IMAP Sender Fixture"
+domain = "publishing"
+
+[fetch]
+mode = "imap"
+
+[fetch.imap]
+host = "127.0.0.1"
+port = {port}
+tls = false
+username = "reader"
+password_env = "RICS_TEST_IMAP_PASSWORD"
+from = "NEWS@example.invalid"
+
+[extract]
+root_selector = "li.event"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+    // SAFETY: the other tests in this binary only set the same value.
+    unsafe { std::env::set_var("RICS_TEST_IMAP_PASSWORD", "secret") };
+
+    let source = load_source_file(&path)?;
+    let documents = fetch_source_documents(&source)?;
+    assert_eq!(documents.len(), 1);
+    assert!(documents[0].source_url.ends_with(";UID=7"));
+    let events = preview_source_events(&fs::read_to_string(&path)?, &documents)?;
+    let titles = events.iter().map(|e| e.title.as_str()).collect::<Vec<_>>();
+    assert_eq!(titles, ["Board meeting"]);
+
+    let commands = server.join().expect("server thread")?;
+    assert!(commands.contains(&"UID FETCH 8 BODY.PEEK[]".to_string()));
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use anyhow::Result;
+use common::{sync_options, write_json_source};
+use rics::config::load_source_file;
+use rics::invites::{InviteMethod, invite_message};
+use rics::pipeline::{load_state_for_read, sync_sources};
+use std::fs;
+use std::path::Path;
+
+const INVITES: &str = r#"[map.importance]
+from = "json:/importance"
+
+[invites]
+recipients = ["board@example.invalid"]
+min_importance = 80
+"#;
+
+fn write_smtp(root: &Path, port: u16) -> Result<()> {
+    fs::write(
+        root.join("smtp.toml"),
+        format!(
+            r#"host = "127.0.0.1"
+port = {port}
+tls = false
+from = "calendar@example.invalid"
+timeout_secs = 2
+"#
+        ),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn invites_follow_inserts_updates_and_cancellations() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    // Nothing listens here; sending fails and is only logged.
+    let unused = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let port = unused.local_addr()?.port();
+    drop(unused);
+    write_smtp(root.path(), port)?;
+    let options = sync_options(root.path());
+
+    write_json_source(
+        &config_dir.join("board.toml"),
+        "invites.board",
+        "Board",
+        "government",
+        r#"[{"id": "vote", "title": "Budget vote", "date": "2030-05-01", "importance": "90"},
+            {"id": "minor", "title": "Committee tour", "date": "2030-05-02", "importance": "20"}]"#,
+        INVITES,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].invites, 1);
+    // Unchanged events are not sent again.
+    assert_eq!(sync_sources(&options)?[0].invites, 0);
+
+    write_json_source(
+        &config_dir.join("board.toml"),
+        "invites.board",
+        "Board",
+        "government",
+        r#"[{"id": "vote", "title": "Budget vote (final)", "date": "2030-05-01", "importance": "90"},
+            {"id": "minor", "title": "Committee tour", "date": "2030-05-03", "importance": "20"}]"#,
+        INVITES,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].invites, 1);
+
+    write_json_source(
+        &config_dir.join("board.toml"),
+        "invites.board",
+        "Board",
+        "government",
+        r#"[{"id": "minor", "title": "Committee tour", "date": "2030-05-03", "importance": "20"}]"#,
+        INVITES,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].invites, 1);
+
+    let state = load_state_for_read(&options.state_path)?;
+    let vote = state
+        .events
+        .values()
+        .find(|event| event.title.starts_with("Budget vote"))
+        .expect("vote stored");
+    let source = load_source_file(&config_dir.join("board.toml"))?;
+    let message = invite_message(
+        vote,
+        InviteMethod::Cancel,
+        "calendar@example.invalid",
+        &source.config.invites.recipients,
+        &source.config,
+    );
+    assert!(message.contains("Subject: Cancelled: Budget vote (final)\r\n"));
+    assert!(message.contains("Content-Type: text/calendar; charset=UTF-8; method=CANCEL\r\n"));
+    assert!(message.contains("METHOD:CANCEL\r\n"));
+    assert!(message.contains("STATUS:CANCELLED\r\n"));
+    assert!(message.contains("ORGANIZER:mailto:calendar@example.invalid\r\n"));
+    assert!(message.contains("mailto:board@example.invalid\r\nEND:VEVENT\r\n"));
+
+    Ok(())
+}
+
+#[cfg(feature = "email")]
+#[test]
+fn invites_are_delivered_over_smtp() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    let server = std::thread::spawn(move || -> Result<Vec<String>> {
+        let (stream, _) = listener.accept()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let mut lines = Vec::new();
+        let mut in_data = false;
+        writer.write_all(b"220 fake smtp\r\n")?;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let line = line.trim_end().to_string();
+            lines.push(line.clone());
+            if in_data {
+                if line == "." {
+                    in_data = false;
+                    writer.write_all(b"250 queued\r\n")?;
+                }
+                continue;
+            }
+            match line.split(' ').next().unwrap_or_default() {
+                "EHLO" => writer.write_all(b"250-fake\r\n250 8BITMIME\r\n")?,
+                "DATA" => {
+                    in_data = true;
+                    writer.write_all(b"354 go ahead\r\n")?;
+                }
+                "QUIT" => {
+                    writer.write_all(b"221 bye\r\n")?;
+                    break;
+                }
+                _ => writer.write_all(b"250 ok\r\n")?,
+            }
+        }
+        Ok(lines)
+    });
+
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    write_smtp(root.path(), port)?;
+    write_json_source(
+        &config_dir.join("board.toml"),
+        "invites.board",
+        "Board",
+        "government",
+        r#"[{"id": "vote", "title": "Budget vote", "date": "2030-05-01", "importance": "90"}]"#,
+        INVITES,
+    )?;
+    sync_sources(&sync_options(root.path()))?;
+
+    let lines = server.join().expect("server thread")?;
+    assert!(lines.contains(&"MAIL FROM:<calendar@example.invalid>".to_string()));
+    assert!(lines.contains(&"RCPT TO:<board@example.invalid>".to_string()));
+    assert!(lines.contains(&"METHOD:REQUEST".to_string()));
+    assert!(lines.contains(&"SUMMARY:Budget vote".to_string()));
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use anyhow::Result;
+use common::{sync_options, write_json_source};
+use rics::model::Lifecycle;
+use rics::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use std::fs;
+
+fn lifecycle_of(options: &SyncOptions, title: &str) -> Result<Lifecycle> {
+    let state = load_state_for_read(&options.state_path)?;
+    Ok(state
+        .events
+        .values()
+        .find(|event| event.title == title)
+        .expect("stored event")
+        .lifecycle)
+}
+
+#[test]
+fn unlisted_tbd_events_get_a_grace_period_before_expiring() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let options = sync_options(root.path());
+    fs::create_dir_all(&options.config_dir)?;
+    let lifecycle = "[lifecycle]\ntbd_missed_syncs = 2";
+
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "lifecycle.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP", "date": "2099-04-25"}, {"id": "ppi", "title": "PPI"}]"#,
+        lifecycle,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].inserted, 2);
+
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "lifecycle.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP", "date": "2099-04-25"}]"#,
+        lifecycle,
+    )?;
+    let report = &sync_sources(&options)?[0];
+    assert_eq!((report.cancelled, report.expired), (0, 0));
+    assert_eq!(lifecycle_of(&options, "PPI")?, Lifecycle::Active);
+
+    let report = &sync_sources(&options)?[0];
+    assert_eq!((report.cancelled, report.expired), (0, 1));
+    assert_eq!(lifecycle_of(&options, "PPI")?, Lifecycle::Expired);
+
+    Ok(())
+}
+
+#[test]
+fn old_tbd_placeholders_retire_until_they_get_a_date() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let options = sync_options(root.path());
+    fs::create_dir_all(&options.config_dir)?;
+    let lifecycle = "[lifecycle]\ntbd_max_age_days = 0\ntbd_action = \"cancel\"";
+
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "lifecycle.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "ppi", "title": "PPI"}]"#,
+        lifecycle,
+    )?;
+    let report = &sync_sources(&options)?[0];
+    assert_eq!((report.inserted, report.cancelled), (1, 1));
+    assert_eq!(lifecycle_of(&options, "PPI")?, Lifecycle::Cancelled);
+
+    // Still listed as TBD: stays retired without churning revisions.
+    let report = &sync_sources(&options)?[0];
+    assert_eq!((report.unchanged, report.updated), (1, 0));
+
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "lifecycle.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "ppi", "title": "PPI", "date": "2099-05-14"}]"#,
+        lifecycle,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].updated, 1);
+    assert_eq!(lifecycle_of(&options, "PPI")?, Lifecycle::Active);
+
+    Ok(())
+}
+
+#[test]
+fn events_beyond_the_cancellation_horizon_are_not_cancelled_when_unlisted() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let options = sync_options(root.path());
+    fs::create_dir_all(&options.config_dir)?;
+    let horizon = "[merge]\ncancellation_horizon = \"90d\"";
+    let soon = (chrono::Utc::now().date_naive() + chrono::Duration::days(10)).to_string();
+
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "lifecycle.releases",
+        "Releases",
+        "economics",
+        &format!(
+            r#"[{{"id": "cpi", "title": "CPI", "date": "{soon}"}}, {{"id": "gdp", "title": "GDP", "date": "2099-04-25"}}, {{"id": "ppi", "title": "PPI", "date": "{soon}"}}]"#
+        ),
+        horizon,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].inserted, 3);
+
+    // The feed now shows only its next three months, and dropped PPI.
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "lifecycle.releases",
+        "Releases",
+        "economics",
+        &format!(r#"[{{"id": "cpi", "title": "CPI", "date": "{soon}"}}]"#),
+        horizon,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].cancelled, 1);
+    assert_eq!(lifecycle_of(&options, "PPI")?, Lifecycle::Cancelled);
+    assert_eq!(lifecycle_of(&options, "GDP")?, Lifecycle::Active);
+
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "lifecycle.releases",
+        "Releases",
+        "economics",
+        "[]",
+        "[merge]\ncancellation_horizon = \"0w\"",
+    )?;
+    let err = sync_sources(&options).unwrap_err();
+    assert!(format!("{err:#}").contains("at least 1 day"), "{err:#}");
+
+    Ok(())
+}
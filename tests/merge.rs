@@ -0,0 +1,187 @@
+mod common;
+
+use anyhow::Result;
+use common::{sync_options, write_json_source};
+use rics::model::ParseWarningKind;
+use rics::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use std::fs;
+
+/// Mappings and field policies of the `merge.releases` source.
+const MERGE_FIELDS: &str = r#"[map.description]
+from = "json:/summary"
+
+[map.venue]
+from = "json:/venue"
+
+[merge.fields]
+description = "longest"
+"metadata.venue" = "prefer_non_empty"
+title = "prefer_existing"
+"#;
+
+#[test]
+fn merge_policies_keep_better_stored_values() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let options = sync_options(root.path());
+
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "merge.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP advance estimate", "summary": "Advance estimate of Q1 output and income.", "venue": "Suitland", "date": "2030-04-25"}]"#,
+        MERGE_FIELDS,
+    )?;
+    sync_sources(&options)?;
+
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "merge.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP (advance)", "summary": "Advance estimate of", "date": "2030-04-25"}]"#,
+        MERGE_FIELDS,
+    )?;
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].updated, 0);
+    assert_eq!(reports[0].unchanged, 1);
+
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "merge.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP (advance)", "summary": "Advance estimate of Q1 output and income, revised schedule.", "date": "2030-04-26"}]"#,
+        MERGE_FIELDS,
+    )?;
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].updated, 1);
+
+    let state = load_state_for_read(&options.state_path)?;
+    let event = state.events.values().next().expect("one event");
+    assert_eq!(event.title, "GDP advance estimate");
+    assert_eq!(
+        event.description.as_deref(),
+        Some("Advance estimate of Q1 output and income, revised schedule.")
+    );
+    assert_eq!(
+        event.metadata.get("venue").map(String::as_str),
+        Some("Suitland")
+    );
+    assert_eq!(
+        event.time.start_date().map(|d| d.to_string()).as_deref(),
+        Some("2030-04-26")
+    );
+
+    let bad = fs::read_to_string(config_dir.join("releases.toml"))?
+        .replace("title = \"prefer_existing\"", "location = \"longest\"");
+    assert!(rics::config::parse_source_config(&bad).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn concurrent_sync_matches_serial_sync() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    for n in 0..6 {
+        write_json_source(
+            &config_dir.join(format!("board-{n}.toml")),
+            &format!("merge.board{n}"),
+            &format!("Board {n}"),
+            "government",
+            &format!(
+                r#"[{{"id": "m", "title": "Board {n} meeting", "date": "2030-06-0{}"}}]"#,
+                n + 1
+            ),
+            "",
+        )?;
+    }
+
+    let sync = |name: &str, jobs: usize| {
+        let options = SyncOptions {
+            config_dir: config_dir.clone(),
+            state_path: root.path().join(name).join("events.json"),
+            out_dir: root.path().join(name).join("out"),
+            jobs,
+            ..SyncOptions::default()
+        };
+        let reports = sync_sources(&options)?;
+        let state = load_state_for_read(&options.state_path)?;
+        anyhow::Ok((reports, state))
+    };
+    let (serial_reports, serial_state) = sync("serial", 1)?;
+    let (concurrent_reports, concurrent_state) = sync("concurrent", 4)?;
+
+    let keys = |reports: &[rics::model::SourceRunReport]| {
+        reports
+            .iter()
+            .map(|report| (report.source_key.clone(), report.inserted))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(&concurrent_reports), keys(&serial_reports));
+    assert_eq!(concurrent_reports.len(), 6);
+    assert_eq!(
+        concurrent_state.events.keys().collect::<Vec<_>>(),
+        serial_state.events.keys().collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn colliding_uids_are_suffixed_in_parse_order_and_reported() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let options = sync_options(root.path());
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "merge.releases",
+        "Releases",
+        "economics",
+        r#"[
+            {"id": "meeting", "title": "Board meeting", "date": "2030-03-01"},
+            {"id": "meeting", "title": "Board meeting", "date": "2030-03-01"},
+            {"id": "meeting", "title": "Board meeting", "date": "2030-04-05"},
+            {"id": "meeting", "title": "Board meeting", "date": "2030-05-03"}
+        ]"#,
+        MERGE_FIELDS,
+    )?;
+
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].inserted, 3);
+    let collisions = reports[0]
+        .warnings
+        .iter()
+        .filter(|warning| warning.kind == ParseWarningKind::UidCollision)
+        .collect::<Vec<_>>();
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].count, 3);
+
+    let state = load_state_for_read(&options.state_path)?;
+    let mut by_date = state
+        .events
+        .values()
+        .map(|event| {
+            (
+                event.time.start_date().map(|d| d.to_string()),
+                event.uid.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    by_date.sort();
+    let base = by_date[0].1.clone();
+    assert!(!base.contains('-'));
+    assert_eq!(by_date[1].1, base.replace('@', "-2@"));
+    assert_eq!(by_date[2].1, base.replace('@', "-3@"));
+
+    let reports = sync_sources(&options)?;
+    assert_eq!(reports[0].inserted, 0);
+    assert_eq!(reports[0].updated, 0);
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use anyhow::Result;
+use common::write_json_source;
+use rics::pipeline::{MonitorOptions, SyncOptions, monitor_sources, sync_sources};
+use rics::store::StateLock;
+use std::fs;
+
+#[test]
+fn monitor_reports_differences_without_touching_state() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let state_path = root.path().join("state/events.json");
+    write_json_source(
+        &config_dir.join("council.toml"),
+        "monitor.council",
+        "Council",
+        "government",
+        r#"[{"id": "a", "title": "Budget hearing", "date": "2030-05-01"},
+            {"id": "b", "title": "Zoning session", "date": "2030-05-02"}]"#,
+        "",
+    )?;
+    sync_sources(&SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: state_path.clone(),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    })?;
+    let options = MonitorOptions {
+        config_dir: config_dir.clone(),
+        state_path: state_path.clone(),
+        source: None,
+        jobs: 4,
+        tenant: Default::default(),
+    };
+
+    let reports = monitor_sources(&options)?;
+    assert_eq!(reports.len(), 1);
+    assert!(!reports[0].differs());
+    assert_eq!(reports[0].records_parsed, 2);
+
+    write_json_source(
+        &config_dir.join("council.toml"),
+        "monitor.council",
+        "Council",
+        "government",
+        r#"[{"id": "a", "title": "Budget hearing", "date": "2030-05-08"},
+            {"id": "c", "title": "Parks forum", "date": "2030-05-03"}]"#,
+        "",
+    )?;
+    let state_before = fs::read(&state_path)?;
+    let reports = monitor_sources(&options)?;
+    let mut changes = reports[0]
+        .changes
+        .iter()
+        .map(|change| (change.kind.as_str(), change.title.as_str()))
+        .collect::<Vec<_>>();
+    changes.sort();
+    assert_eq!(
+        changes,
+        vec![
+            ("event.cancelled", "Zoning session"),
+            ("event.created", "Parks forum"),
+            ("event.updated", "Budget hearing"),
+        ]
+    );
+    assert_eq!(fs::read(&state_path)?, state_before);
+
+    // It waits for a sync holding the state lock, since both save the cache.
+    let held = StateLock::acquire(&state_path)?;
+    let monitor = std::thread::spawn({
+        let options = options.clone();
+        move || monitor_sources(&options).map(|reports| reports.len())
+    });
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert!(!monitor.is_finished());
+    drop(held);
+    assert_eq!(monitor.join().expect("monitor thread")?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn monitor_reports_a_failing_source_and_checks_the_others() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    write_json_source(
+        &config_dir.join("council.toml"),
+        "monitor.council",
+        "Council",
+        "government",
+        r#"[{"id": "a", "title": "Budget hearing", "date": "2030-05-01"}]"#,
+        "",
+    )?;
+    fs::write(
+        config_dir.join("archive.toml"),
+        r#"[source]
+key = "monitor.archive"
+name = "Archive"
+domain = "government"
+
+[fetch]
+mode = "file"
+file_path = "missing.txt"
+
+[extract]
+format = "text"
+"#,
+    )?;
+
+    let reports = monitor_sources(&MonitorOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        source: None,
+        jobs: 2,
+        tenant: Default::default(),
+    })?;
+    let report = |key: &str| {
+        reports
+            .iter()
+            .find(|report| report.source_key == key)
+            .expect("source report")
+    };
+    let failed = report("monitor.archive");
+    assert!(failed.error.is_some());
+    assert!(!failed.differs());
+    let council = report("monitor.council");
+    assert_eq!(council.error, None);
+    assert_eq!(council.changes.len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+#![cfg(feature = "http")]
+
+use anyhow::Result;
+use rics::http_cache::HttpCache;
+use rics::pipeline::{SyncOptions, sync_sources};
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tempfile::tempdir;
+use tiny_http::{Header, Method, Response, Server};
+
+const BODY: &str = r#"<html><body>
+<article class="item"><span class="title">Quarterly bulletin</span><time>2030-03-01</time></article>
+</body></html>"#;
+
+#[test]
+fn preflight_skips_fetch_when_validators_match_previous_run() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let full_gets = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&full_gets);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            if *request.method() == Method::Get {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+            let last_modified =
+                Header::from_bytes("Last-Modified", "Wed, 01 Jan 2030 00:00:00 GMT").unwrap();
+            let _ = request.respond(Response::from_string(BODY).with_header(last_modified));
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("bulletin.toml"),
+        format!(
+            r#"[source]
+key = "test.preflight"
+name = "Preflight Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/bulletin.html"
+preflight = true
+retry_attempts = 1
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+
+    let first = sync_sources(&options)?;
+    assert!(!first[0].not_modified);
+    assert_eq!(first[0].inserted, 1);
+
+    let second = sync_sources(&options)?;
+    assert!(second[0].not_modified);
+    assert_eq!(second[0].cancelled, 0);
+    assert_eq!(full_gets.load(Ordering::SeqCst), 1);
+    assert!(!second[0].config_changed);
+
+    // A config edit must be applied even though the document is unchanged.
+    let config_path = options.config_dir.join("bulletin.toml");
+    let edited = fs::read_to_string(&config_path)?.replace(
+        "[fetch]",
+        "[event]\ncategories = [\"bulletins\"]\n\n[fetch]",
+    );
+    fs::write(&config_path, edited)?;
+    let third = sync_sources(&options)?;
+    assert!(!third[0].not_modified);
+    assert!(third[0].config_changed);
+    assert_eq!(third[0].updated, 1);
+    assert_eq!(full_gets.load(Ordering::SeqCst), 2);
+
+    let fourth = sync_sources(&options)?;
+    assert!(fourth[0].not_modified);
+
+    Ok(())
+}
+
+#[test]
+fn conditional_requests_reuse_cached_body_on_304() -> Result<()> {
+    let server = Server::http("127.0.0.1:0").map_err(|err| anyhow::anyhow!(err))?;
+    let port = server.server_addr().to_ip().expect("ip listener").port();
+    let full_responses = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&full_responses);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let revalidated = request
+                .headers()
+                .iter()
+                .any(|header| header.field.equiv("If-None-Match") && header.value == "\"v1\"");
+            let etag = Header::from_bytes("ETag", "\"v1\"").unwrap();
+            let response = if revalidated {
+                Response::from_string("").with_status_code(304)
+            } else {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Response::from_string(BODY)
+            };
+            let _ = request.respond(response.with_header(etag));
+        }
+    });
+
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("bulletin.toml"),
+        format!(
+            r#"[source]
+key = "test.http_cache"
+name = "HTTP Cache Fixture"
+domain = "publishing"
+
+[fetch]
+base_url = "http://127.0.0.1:{port}/bulletin.html"
+retry_attempts = 1
+
+[extract]
+root_selector = "article.item"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        ),
+    )?;
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+
+    let first = sync_sources(&options)?;
+    assert_eq!(first[0].inserted, 1);
+    assert!(
+        root.path()
+            .join("state/events.json.http-cache/index.json")
+            .exists()
+    );
+
+    let second = sync_sources(&options)?;
+    assert_eq!(full_responses.load(Ordering::SeqCst), 1);
+    assert_eq!(second[0].records_parsed, 1);
+    assert_eq!(second[0].unchanged, 1);
+    assert_eq!(second[0].cancelled, 0);
+
+    Ok(())
+}
+
+#[test]
+fn state_files_in_one_directory_keep_separate_http_caches() -> Result<()> {
+    let root = tempdir()?;
+    let save = |state: &str, url: &str, body: &[u8]| -> Result<()> {
+        let cache = HttpCache::open(&HttpCache::dir_for_state(&root.path().join(state)))?;
+        cache.store(url, Some("\"v1\"".to_string()), None, &body.to_vec().into());
+        cache.save()
+    };
+    save("a.json", "https://example.org/a", b"first")?;
+    save("b.json", "https://example.org/b", b"second")?;
+
+    let cache = HttpCache::open(&HttpCache::dir_for_state(&root.path().join("a.json")))?;
+    assert!(cache.validators("https://example.org/a").is_some());
+    assert_eq!(cache.body("https://example.org/a")?.as_slice(), b"first");
+    assert!(cache.validators("https://example.org/b").is_none());
+    Ok(())
+}
+
+#[test]
+fn runs_that_overlap_keep_each_others_http_cache_entries() -> Result<()> {
+    let root = tempdir()?;
+    let dir = HttpCache::dir_for_state(&root.path().join("events.json"));
+    let first = HttpCache::open(&dir)?;
+    let second = HttpCache::open(&dir)?;
+    first.store(
+        "https://example.org/a",
+        Some("\"a\"".to_string()),
+        None,
+        &b"first".to_vec().into(),
+    );
+    second.store(
+        "https://example.org/b",
+        Some("\"b\"".to_string()),
+        None,
+        &b"second".to_vec().into(),
+    );
+    first.save()?;
+    second.save()?;
+
+    let cache = HttpCache::open(&dir)?;
+    assert_eq!(cache.body("https://example.org/a")?.as_slice(), b"first");
+    assert_eq!(cache.body("https://example.org/b")?.as_slice(), b"second");
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use anyhow::Result;
+use rics::body::Body;
+use rics::config::{LoadedSource, parse_source_config};
+use rics::fetch::FetchedDocument;
+use rics::parser::preview_source_events;
+use rics::preprocess::preprocess_documents;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+fn document(url: &str, body: impl Into<Vec<u8>>) -> FetchedDocument {
+    FetchedDocument {
+        source_url: url.to_string(),
+        body: Body::from(body.into()),
+        page_index: 0,
+        fetched_at: None,
+        headers: BTreeMap::new(),
+    }
+}
+
+fn loaded(config: &str) -> Result<LoadedSource> {
+    Ok(LoadedSource {
+        path: PathBuf::from("preprocess.toml"),
+        config: parse_source_config(config)?,
+        tenant: Default::default(),
+    })
+}
+
+const PAGE: &str = r#"<html><head><script>var promo = "<li>2099-01-01</li>";</script></head><body>
+<ul class="nav"><li><a href="/">Home</a></li><li><a href="/about">About the board and its members</a></li></ul>
+<div class="banner"><ul><li><span class="title">Subscribe today</span><time>2099-01-01</time></li></ul></div>
+<div id="content">
+  <h1>Meetings</h1>
+  <ul>
+    <li><span class="title">Board meeting</span> held at city hall <time>2099-05-01</time></li>
+    <li><span class="title">Budget hearing</span> held at the annex <time>2099-06-01</time></li>
+  </ul>
+</div>
+</body></html>"#;
+
+#[test]
+fn strip_tags_and_readability_leave_only_the_main_content() -> Result<()> {
+    let config = |steps: &str| {
+        format!(
+            r#"[source]
+key = "test.preprocess"
+name = "Preprocess"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+{steps}
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        )
+    };
+    let docs = [document("https://example.org/meetings", PAGE)];
+    let titles = |config: &str| -> Result<Vec<String>> {
+        Ok(preview_source_events(config, &docs)?
+            .into_iter()
+            .map(|event| event.title)
+            .collect())
+    };
+
+    // Navigation items borrow the banner's fields.
+    assert_eq!(
+        titles(&config(""))?,
+        [
+            "Subscribe today",
+            "Subscribe today",
+            "Subscribe today",
+            "Board meeting",
+            "Budget hearing"
+        ]
+    );
+
+    let steps = r#"
+[[preprocess]]
+kind = "strip_tags"
+tags = ["script", ".banner"]
+
+[[preprocess]]
+kind = "readability"
+"#;
+    assert_eq!(titles(&config(steps))?, ["Board meeting", "Budget hearing"]);
+
+    let source = loaded(&config(steps))?;
+    let processed = preprocess_documents(&source, &docs)?;
+    let body = processed[0].text();
+    assert!(
+        body.starts_with("<html><body><div id=\"content\">"),
+        "{body}"
+    );
+    assert!(!body.contains("promo") && !body.contains("nav"), "{body}");
+    assert_eq!(
+        processed[0].headers.get("content-type").map(String::as_str),
+        Some("text/html; charset=utf-8")
+    );
+
+    let err = parse_source_config(&config(
+        "\n[[preprocess]]\nkind = \"strip_tags\"\ntags = [\"div[\"]\n",
+    ))
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("not valid CSS selectors"),
+        "{err:#}"
+    );
+
+    #[cfg(not(feature = "archives"))]
+    {
+        let source = loaded(&config("\n[[preprocess]]\nkind = \"unpack\"\n"))?;
+        let err = preprocess_documents(&source, &docs).unwrap_err();
+        assert!(format!("{err:#}").contains("`archives` feature"), "{err:#}");
+    }
+    Ok(())
+}
+
+#[cfg(any(feature = "archives", feature = "spreadsheet"))]
+fn zip_archive(members: &[(&str, &str)]) -> Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, body) in members {
+        writer.start_file(*name, zip::write::SimpleFileOptions::default())?;
+        writer.write_all(body.as_bytes())?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+#[cfg(feature = "archives")]
+fn tar_gz_archive(members: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(gzip);
+    for (name, body) in members {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(body.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, body.as_bytes())?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+#[cfg(feature = "archives")]
+#[test]
+fn unpack_turns_each_matching_archive_member_into_a_document() -> Result<()> {
+    let config = |archive: &str| {
+        format!(
+            r#"[source]
+key = "test.unpack"
+name = "Unpack"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[[preprocess]]
+kind = "unpack"
+files = "agendas/*.html"{archive}
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+"#
+        )
+    };
+    let agenda = |title: &str, date: &str| {
+        format!(r#"<ul><li><span class="title">{title}</span><time>{date}</time></li></ul>"#)
+    };
+    let zip = zip_archive(&[
+        ("readme.txt", "not an agenda"),
+        ("agendas/may.html", &agenda("May board", "2099-05-01")),
+        ("agendas/june.html", &agenda("June board", "2099-06-01")),
+    ])?;
+    let tar_gz = tar_gz_archive(&[
+        ("agendas/july.html", &agenda("July board", "2099-07-01")),
+        ("agendas/notes.md", "not an agenda either"),
+    ])?;
+    // Archives are recognized by their bytes; other documents pass through.
+    let docs = [
+        document("https://example.org/agendas", zip),
+        document("https://example.org/2099.tgz", tar_gz),
+        document(
+            "https://example.org/august",
+            agenda("August board", "2099-08-01"),
+        ),
+    ];
+
+    let source = loaded(&config(""))?;
+    let processed = preprocess_documents(&source, &docs)?;
+    let urls = processed
+        .iter()
+        .map(|doc| doc.source_url.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            "https://example.org/agendas#agendas/may.html",
+            "https://example.org/agendas#agendas/june.html",
+            "https://example.org/2099.tgz#agendas/july.html",
+            "https://example.org/august",
+        ]
+    );
+    let titles = preview_source_events(&config(""), &docs)?
+        .into_iter()
+        .map(|event| event.title)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        titles,
+        ["May board", "June board", "July board", "August board"]
+    );
+
+    // A declared content type or format is trusted.
+    let mut mislabeled = document("https://example.org/a.zip", "nope");
+    mislabeled
+        .headers
+        .insert("content-type".to_string(), "application/zip".to_string());
+    let err = preprocess_documents(&source, &[mislabeled]).unwrap_err();
+    assert!(format!("{err:#}").contains("not a zip archive"), "{err:#}");
+    let forced = loaded(&config("\narchive = \"tar.gz\""))?;
+    let err = preprocess_documents(&forced, &docs[2..]).unwrap_err();
+    assert!(format!("{err:#}").contains("not a tar archive"), "{err:#}");
+    Ok(())
+}
+
+#[cfg(feature = "spreadsheet")]
+#[test]
+fn spreadsheet_to_csv_converts_the_named_sheet() -> Result<()> {
+    const NS: &str = "http://schemas.openxmlformats.org";
+    let cell = |reference: &str, text: &str| {
+        format!(r#"<c r="{reference}" t="inlineStr"><is><t>{text}</t></is></c>"#)
+    };
+    let sheet = format!(
+        r#"<worksheet xmlns="{NS}/spreadsheetml/2006/main"><sheetData>
+<row r="1">{}{}</row>
+<row r="2"><c r="A2" s="1"><v>73000</v></c>{}</row>
+<row r="3">{}{}</row>
+</sheetData></worksheet>"#,
+        cell("A1", "Date"),
+        cell("B1", "Title"),
+        cell("B2", "Board meeting"),
+        cell("A3", "2099-12-01"),
+        cell("B3", "Hearing, \"final\""),
+    );
+    let rels = |relations: &str| {
+        format!(
+            r#"<Relationships xmlns="{NS}/package/2006/relationships">{relations}</Relationships>"#
+        )
+    };
+    let workbook = zip_archive(&[
+        (
+            "[Content_Types].xml",
+            &format!(
+                r#"<Types xmlns="{NS}/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/></Types>"#
+            ),
+        ),
+        (
+            "_rels/.rels",
+            &rels(&format!(
+                r#"<Relationship Id="rId1" Type="{NS}/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#
+            )),
+        ),
+        (
+            "xl/workbook.xml",
+            &format!(
+                r#"<workbook xmlns="{NS}/spreadsheetml/2006/main" xmlns:r="{NS}/officeDocument/2006/relationships"><sheets><sheet name="Notes" sheetId="1" r:id="rId1"/><sheet name="Calendar" sheetId="2" r:id="rId2"/></sheets></workbook>"#
+            ),
+        ),
+        (
+            "xl/_rels/workbook.xml.rels",
+            &rels(&format!(
+                r#"<Relationship Id="rId1" Type="{NS}/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="{NS}/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet2.xml"/><Relationship Id="rId3" Type="{NS}/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#
+            )),
+        ),
+        (
+            "xl/styles.xml",
+            &format!(
+                r#"<styleSheet xmlns="{NS}/spreadsheetml/2006/main"><cellXfs count="2"><xf numFmtId="0"/><xf numFmtId="14"/></cellXfs></styleSheet>"#
+            ),
+        ),
+        (
+            "xl/worksheets/sheet1.xml",
+            &format!(
+                r#"<worksheet xmlns="{NS}/spreadsheetml/2006/main"><sheetData><row r="1">{}</row></sheetData></worksheet>"#,
+                cell("A1", "Nothing to see")
+            ),
+        ),
+        ("xl/worksheets/sheet2.xml", &sheet),
+    ])?;
+
+    let config = r#"[source]
+key = "test.spreadsheet"
+name = "Spreadsheet"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[[preprocess]]
+kind = "spreadsheet_to_csv"
+sheet = "Calendar"
+
+[extract]
+format = "text"
+record_regex = "(?m)^(\\d{4}-\\d{2}-\\d{2},.+)$"
+
+[map.date]
+from = "regex:^([^,]+),"
+
+[map.title]
+from = "regex:^[^,]+,(.+)$"
+"#;
+    let docs = [document("https://example.org/calendar.xlsx", workbook)];
+    let processed = preprocess_documents(&loaded(config)?, &docs)?;
+    assert_eq!(
+        processed[0].text(),
+        "Date,Title\n2099-11-11,Board meeting\n2099-12-01,\"Hearing, \"\"final\"\"\"\n"
+    );
+    let events = preview_source_events(config, &docs)?;
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].title, "Board meeting");
+    Ok(())
+}
@@ -0,0 +1,954 @@
+use anyhow::Result;
+use rics::fetch::FetchedDocument;
+use rics::ics::render_named_year_calendar;
+use rics::model::{EventTimeSpec, ParseWarningKind, RecurrenceDate};
+use rics::parser::preview_source_events;
+use rics::pipeline::preview_records;
+
+#[test]
+fn preview_parses_supplied_body_without_fetching() -> Result<()> {
+    let config = r#"[source]
+key = "preview.html"
+name = "Preview HTML"
+domain = "publishing"
+
+[fetch]
+mode = "http"
+base_url = "https://example.invalid/calendar"
+
+[extract]
+format = "html"
+root_selector = "li.event"
+
+[map.title]
+from = "css:.title"
+trim = true
+
+[map.date]
+from = "css:time@datetime"
+"#;
+    let body = r#"<ul>
+<li class="event"><span class="title">Budget hearing</span><time datetime="2026-03-04"></time></li>
+<li class="event"><span class="title">Annual report</span><time datetime="2027-01-15"></time></li>
+</ul>"#;
+
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "https://example.invalid/calendar".to_string(),
+            body: body.as_bytes().to_vec().into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    assert_eq!(candidates.len(), 2);
+
+    let records = preview_records(candidates)?;
+    let in_2026 = records
+        .iter()
+        .filter(|event| event.year_bucket() == Some(2026))
+        .collect::<Vec<_>>();
+    let ics = render_named_year_calendar("Preview HTML", 2026, &in_2026, &Default::default());
+    assert!(ics.contains("SUMMARY:Budget hearing"));
+    assert!(!ics.contains("SUMMARY:Annual report"));
+
+    Ok(())
+}
+
+#[test]
+fn local_wall_clock_time_round_trips_into_tzid_output() -> Result<()> {
+    let config = r#"[source]
+key = "preview.local"
+name = "Preview Local"
+domain = "economics"
+timezone = "Europe/Paris"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "text"
+
+[date]
+formats = ["%Y-%m-%d %H:%M"]
+
+[publish]
+time_basis = "local"
+"#;
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://preview.local".to_string(),
+            body: b"2026-01-15 14:30 | Rate decision".to_vec().into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    let local = candidates[0].time.local_time().expect("local time kept");
+    assert_eq!(local.tzid, "Europe/Paris");
+    assert_eq!(local.start_label(), "14:30 CET");
+
+    let publish = rics::config::parse_source_config(config)?.publish;
+    let records = preview_records(candidates)?;
+    let ics = render_named_year_calendar(
+        "Preview Local",
+        2026,
+        &records.iter().collect::<Vec<_>>(),
+        &publish,
+    );
+    assert!(ics.contains("DTSTART;TZID=Europe/Paris:20260115T143000"));
+
+    let legacy: rics::model::EventTimeSpec =
+        serde_json::from_str(r#"{"kind":"date_time","start":"2026-01-15T13:30:00Z","end":null}"#)?;
+    assert!(legacy.local_time().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn numeric_values_carry_units_and_decimal_comma_normalization() -> Result<()> {
+    let config = r#"[source]
+key = "preview.indicators"
+name = "Preview Indicators"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "json"
+root_jsonpath = "/releases"
+
+[values.actual]
+decimal_comma = true
+
+[values.forecast]
+decimal_comma = true
+
+[values.previous]
+field = "prior"
+unit = "%"
+decimal_comma = true
+"#;
+    let body = r#"{"releases": [{"title": "CPI flash", "date": "2026-03-02",
+        "actual": "2,4 %", "forecast": "2,1%", "prior": "1.902,5"}]}"#;
+
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://preview.indicators".to_string(),
+            body: body.as_bytes().to_vec().into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    let records = preview_records(candidates)?;
+    let event = &records[0];
+
+    assert_eq!(event.values["actual"].value, 2.4);
+    assert_eq!(event.values["actual"].unit.as_deref(), Some("%"));
+    assert_eq!(event.values["previous"].value, 1902.5);
+    assert_eq!(event.metadata["actual"], "2,4 %");
+    let surprise = event.surprise("actual", "forecast").expect("same unit");
+    assert!((surprise - 0.3).abs() < 1e-9);
+
+    Ok(())
+}
+
+#[test]
+fn parse_warnings_fold_missing_fields_and_unmatched_selectors() -> Result<()> {
+    let config = rics::config::parse_source_config(
+        r#"[source]
+key = "preview.warnings"
+name = "Preview Warnings"
+domain = "publishing"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+root_selector = "li.event"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = 'regex:(\d{4}-\d{2}-\d{2})'
+
+[map.venue]
+from = "css:.venue"
+"#,
+    )?;
+    let source = rics::config::LoadedSource {
+        path: "preview.toml".into(),
+        config,
+        tenant: Default::default(),
+    };
+    let docs = [
+        FetchedDocument {
+            source_url: "inline://preview.warnings/1".to_string(),
+            body: br#"<ul>
+<li class="event"><span class="title">Hearing</span><time>2030-03-04</time></li>
+<li class="event"><span class="title">Vote</span></li>
+<li class="event"><span class="title">Recess</span><time>2030-03-09</time></li>
+</ul>"#
+                .to_vec()
+                .into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        },
+        FetchedDocument {
+            source_url: "inline://preview.warnings/2".to_string(),
+            body: b"<p>moved</p>".to_vec().into(),
+            page_index: 1,
+            fetched_at: None,
+            headers: Default::default(),
+        },
+    ];
+
+    let outcome = rics::parser::parse_source_documents(&source, &docs)?;
+    let kinds = outcome
+        .warnings
+        .iter()
+        .map(|w| (w.kind, w.field.as_deref(), w.count))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            (ParseWarningKind::MissingField, Some("date"), 1),
+            (ParseWarningKind::UnmatchedSelector, Some("venue"), 3),
+            (ParseWarningKind::EmptyNodeSet, None, 1),
+        ]
+    );
+    assert_eq!(
+        outcome.warnings[1].to_string(),
+        "selector css:.venue for venue matched in none of 3 records (inline://preview.warnings/1)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn language_variants_pick_preferred_title_and_keep_the_rest() -> Result<()> {
+    let config = r#"[source]
+key = "preview.i18n"
+name = "Preview I18n"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+root_selector = "li.event"
+
+[map."title.en"]
+from = "css:.en"
+
+[map."title.fr"]
+from = "css:.fr"
+
+[map.date]
+from = "css:time"
+
+[publish]
+language = "fr"
+languages = ["fr", "en"]
+"#;
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://preview.i18n".to_string(),
+            body: r#"<ul><li class="event"><span class="en">Rate decision</span><span class="fr">Décision de taux</span><time>2030-03-04</time></li></ul>"#
+                .as_bytes()
+                .to_vec()
+                .into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    assert_eq!(candidates[0].title, "Décision de taux");
+    assert!(!candidates[0].metadata.contains_key("title.en"));
+
+    let mut publish = rics::config::parse_source_config(config)?.publish;
+    let records = preview_records(candidates)?;
+    assert_eq!(records[0].title_in(Some("en")), "Rate decision");
+
+    let ics = render_named_year_calendar(
+        "Preview I18n",
+        2030,
+        &records.iter().collect::<Vec<_>>(),
+        &publish,
+    );
+    assert!(ics.contains("SUMMARY;LANGUAGE=fr:Décision de taux"));
+    assert!(ics.contains("X-RICS-SUMMARY;LANGUAGE=en:Rate decision"));
+
+    publish.language = Some("en".to_string());
+    let ics = render_named_year_calendar(
+        "Preview I18n",
+        2030,
+        &records.iter().collect::<Vec<_>>(),
+        &publish,
+    );
+    assert!(ics.contains("SUMMARY;LANGUAGE=en:Rate decision"));
+    assert!(ics.contains("X-RICS-SUMMARY;LANGUAGE=fr:Décision de taux"));
+
+    Ok(())
+}
+
+#[test]
+fn extracted_text_is_entity_decoded_and_unicode_normalized() -> Result<()> {
+    let config = r#"[source]
+key = "preview.normalize"
+name = "Preview Normalize"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "json"
+root_jsonpath = "/items"
+
+[map.title]
+from = "json:/title"
+
+[map.description]
+from = "json:/summary"
+
+[map.date]
+from = "json:/date"
+"#;
+    let body = serde_json::json!({
+        "items": [
+            {
+                "title": "Trade&nbsp;&amp;\u{200b} Industry\u{0007}",
+                "summary": "Re\u{301}sume\u{301}&#x20AC; &unknown; &#8212;",
+                "date": "2030-03-04"
+            },
+            {"title": "Trade & Industry", "summary": "Résumé€ &unknown; —", "date": "2030-03-04"}
+        ]
+    });
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://preview.normalize".to_string(),
+            body: serde_json::to_vec(&body)?.into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates[0].title, "Trade & Industry");
+    assert_eq!(candidates[0].title, candidates[1].title);
+    assert_eq!(
+        candidates[0].description.as_deref(),
+        Some("Résumé€ &unknown; —")
+    );
+    assert_eq!(candidates[0].description, candidates[1].description);
+
+    let records = preview_records(candidates)?;
+    assert_eq!(records[0].revision_hash, records[1].revision_hash);
+
+    Ok(())
+}
+
+#[test]
+fn oversized_documents_and_node_sets_are_truncated_with_warnings() -> Result<()> {
+    let config = |format: &str, extra: &str| {
+        rics::config::parse_source_config(&format!(
+            r#"[source]
+key = "preview.limits"
+name = "Preview Limits"
+domain = "publishing"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "{format}"
+root_selector = "div"
+root_jsonpath = "/rows"
+{extra}
+[map.title]
+from = "{title}"
+
+[map.date]
+const = "2030-03-04"
+"#,
+            title = if format == "json" {
+                "json:/title"
+            } else {
+                "css:span"
+            },
+        ))
+        .map(|config| rics::config::LoadedSource {
+            path: "limits.toml".into(),
+            config,
+            tenant: Default::default(),
+        })
+    };
+    let html = (0..30)
+        .map(|i| format!("<div><span>Item {i}</span></div>"))
+        .collect::<String>();
+    let doc = |body: &[u8]| FetchedDocument {
+        source_url: "inline://preview.limits".to_string(),
+        body: body.to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    };
+
+    let outcome = rics::parser::parse_source_documents(
+        &config("html", "max_nodes = 10")?,
+        &[doc(html.as_bytes())],
+    )?;
+    assert_eq!(outcome.events.len(), 10);
+    assert_eq!(outcome.events[9].title, "Item 9");
+    assert_eq!(outcome.warnings.len(), 1);
+    assert_eq!(outcome.warnings[0].kind, ParseWarningKind::Truncated);
+    assert_eq!(outcome.warnings[0].count, 20);
+
+    // HTML is cut at the byte limit and the rest parsed.
+    let cut = "<div><span>Item 0</span></div>".len() * 5;
+    let outcome = rics::parser::parse_source_documents(
+        &config("html", &format!("max_document_bytes = {cut}"))?,
+        &[doc(html.as_bytes())],
+    )?;
+    assert_eq!(outcome.events.len(), 5);
+    assert_eq!(outcome.warnings[0].kind, ParseWarningKind::Truncated);
+
+    // JSON cannot be parsed in part, so the document is skipped.
+    let outcome = rics::parser::parse_source_documents(
+        &config("json", "max_document_bytes = 16")?,
+        &[doc(br#"{"rows": [{"title": "Hearing"}]}"#)],
+    )?;
+    assert!(outcome.events.is_empty());
+    assert!(outcome.warnings[0].message.ends_with("skipped"));
+
+    Ok(())
+}
+
+#[test]
+fn x_properties_rename_omit_and_stop_passing_through_metadata() -> Result<()> {
+    let config = r#"[source]
+key = "preview.xprops"
+name = "Preview X Properties"
+domain = "sports"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+root_selector = "li.event"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.venue]
+from = "css:.venue"
+
+[map.raw_tags]
+from = "css:.tags"
+
+[map.league]
+from = "css:.league"
+
+[publish.x_properties]
+omit = ["raw_*"]
+
+[publish.x_properties.map]
+venue = "X-Venue"
+"#;
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://preview.xprops".to_string(),
+            body: r#"<ul><li class="event"><span class="title">Final</span><time>2030-06-01</time><span class="venue">Main Stadium</span><span class="tags">a,b,c</span><span class="league">Premier</span></li></ul>"#
+                .as_bytes()
+                .to_vec()
+                .into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    let records = preview_records(candidates)?;
+    let render = |publish: &rics::config::PublishConfig| {
+        render_named_year_calendar(
+            "Preview X Properties",
+            2030,
+            &records.iter().collect::<Vec<_>>(),
+            publish,
+        )
+    };
+
+    let mut publish = rics::config::parse_source_config(config)?.publish;
+    let ics = render(&publish);
+    assert!(ics.contains("X-VENUE:Main Stadium"));
+    assert!(!ics.contains("X-RICS-VENUE"));
+    assert!(!ics.contains("a,b,c") && !ics.contains("a\\,b\\,c"));
+    assert!(ics.contains("X-RICS-LEAGUE:Premier"));
+
+    publish.x_properties.passthrough = false;
+    let ics = render(&publish);
+    assert!(ics.contains("X-VENUE:Main Stadium"));
+    assert!(!ics.contains("Premier"));
+
+    let invalid = config.replace("\"X-Venue\"", "\"VENUE\"");
+    let err = rics::config::parse_source_config(&invalid).unwrap_err();
+    assert!(format!("{err:#}").contains("must be an X- name"));
+
+    Ok(())
+}
+
+#[test]
+fn redact_rules_rewrite_published_text_but_not_records() -> Result<()> {
+    let config = r#"[source]
+key = "preview.redact"
+name = "Preview Redact"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+root_selector = "li.event"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.description]
+from = "css:.body"
+
+[[publish.redact]]
+builtin = "email"
+
+[[publish.redact]]
+builtin = "phone"
+replacement = "[phone]"
+
+[[publish.redact]]
+pattern = "(?i)project falcon"
+replacement = "an internal project"
+"#;
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://preview.redact".to_string(),
+            body: r#"<ul><li class="event"><span class="title">Project Falcon review</span><time>2030-06-01</time><span class="body">Ask jo.doe@agency.example or call (555) 123-4567 before 2030-05-30.</span></li></ul>"#
+                .as_bytes()
+                .to_vec()
+                .into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    let records = preview_records(candidates)?;
+    let publish = rics::config::parse_source_config(config)?.publish;
+    let ics = render_named_year_calendar(
+        "Preview Redact",
+        2030,
+        &records.iter().collect::<Vec<_>>(),
+        &publish,
+    );
+
+    assert!(ics.contains("SUMMARY:an internal project review"));
+    assert!(
+        ics.contains("DESCRIPTION:Ask [redacted] or call [phone] before 2030-05-30."),
+        "{ics}"
+    );
+    assert_eq!(records[0].title, "Project Falcon review");
+
+    let invalid = config.replace(
+        "builtin = \"phone\"",
+        "builtin = \"phone\"\npattern = \"x\"",
+    );
+    let err = rics::config::parse_source_config(&invalid).unwrap_err();
+    assert!(format!("{err:#}").contains("exactly one of builtin or pattern"));
+
+    Ok(())
+}
+
+#[test]
+fn default_start_time_upgrades_single_day_dates_to_timed_events() -> Result<()> {
+    let config = |event: &str| {
+        format!(
+            r#"[source]
+key = "preview.release"
+name = "Preview Release"
+domain = "economics"
+timezone = "America/New_York"
+
+[fetch]
+base_url = "https://stats.example/releases"
+
+[extract]
+root_selector = "li"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:time"
+
+[map.end]
+from = "css:time.end"
+
+[event]
+{event}
+"#
+        )
+    };
+    let body = r#"<ul>
+<li><span class="title">Employment Situation</span><time>2030-03-01</time><time class="end"></time></li>
+<li><span class="title">Data users conference</span><time>2030-03-04</time><time class="end">2030-03-06</time></li>
+</ul>"#;
+    let docs = [FetchedDocument {
+        source_url: "https://stats.example/releases".to_string(),
+        body: body.as_bytes().to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    }];
+
+    let candidates = preview_source_events(&config("default_start_time = \"08:30\""), &docs)?;
+    let EventTimeSpec::DateTime { start, end, local } = &candidates[0].time else {
+        panic!("expected a timed event, got {:?}", candidates[0].time);
+    };
+    assert_eq!(start.to_rfc3339(), "2030-03-01T13:30:00+00:00");
+    assert_eq!(*end, None);
+    let local = local.as_ref().expect("local time");
+    assert_eq!(local.tzid, "America/New_York");
+    assert_eq!(local.start.to_string(), "2030-03-01 08:30:00");
+    assert!(matches!(candidates[1].time, EventTimeSpec::Date { .. }));
+
+    let candidates = preview_source_events(
+        &config("default_start_time = \"08:30\"\ndefault_start_timezone = \"Europe/London\""),
+        &docs,
+    )?;
+    let EventTimeSpec::DateTime { start, .. } = &candidates[0].time else {
+        panic!("expected a timed event");
+    };
+    assert_eq!(start.to_rfc3339(), "2030-03-01T08:30:00+00:00");
+
+    let err = preview_source_events(&config("default_start_time = \"8.30am\""), &docs).unwrap_err();
+    assert!(format!("{err:#}").contains("must be HH:MM"), "{err:#}");
+    Ok(())
+}
+
+#[test]
+fn item_selector_yields_one_record_per_item_inheriting_the_day() -> Result<()> {
+    let config = r#"[source]
+key = "preview.days"
+name = "Preview Days"
+domain = "economics"
+
+[fetch]
+base_url = "https://stats.example/calendar"
+
+[extract]
+root_selector = "section.day"
+item_selector = "li.release"
+
+[map.title]
+from = "css:.title"
+
+[map.date]
+from = "css:h2@data-date"
+
+[map.description]
+from = "css:.note"
+optional = true
+"#;
+    let body = r#"<section class="day"><h2 data-date="2030-03-01">Friday</h2>
+  <ul>
+    <li class="release"><span class="title">Employment Situation</span><span class="note">Revised</span></li>
+    <li class="release"><span class="title">Consumer Credit</span></li>
+  </ul>
+</section>
+<section class="day"><h2 data-date="2030-03-04">Monday</h2>
+  <ul><li class="release"><span class="title">Construction Spending</span></li></ul>
+</section>
+<section class="day"><h2 data-date="2030-03-05">Tuesday</h2><p>No releases.</p></section>"#;
+
+    let candidates = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "https://stats.example/calendar".to_string(),
+            body: body.as_bytes().to_vec().into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    let rows = candidates
+        .iter()
+        .map(|event| {
+            let EventTimeSpec::Date { start, .. } = event.time else {
+                panic!("expected a date, got {:?}", event.time);
+            };
+            (
+                event.title.as_str(),
+                start.to_string(),
+                event.description.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            (
+                "Employment Situation",
+                "2030-03-01".to_string(),
+                Some("Revised")
+            ),
+            // The note of a sibling item is not inherited.
+            ("Consumer Credit", "2030-03-01".to_string(), None),
+            ("Construction Spending", "2030-03-04".to_string(), None),
+        ]
+    );
+    let provenance = candidates[2].provenance.as_ref().expect("provenance");
+    assert_eq!(
+        provenance.selector.as_deref(),
+        Some("section.day li.release")
+    );
+    assert_eq!(provenance.record_index, Some(2));
+    Ok(())
+}
+
+#[test]
+fn header_rules_read_response_headers_of_the_document() -> Result<()> {
+    let config = r#"[source]
+key = "preview.headers"
+name = "Preview Headers"
+domain = "publishing"
+
+[fetch]
+base_url = "https://stats.example/latest"
+
+[extract]
+root_selector = "body"
+
+[map.title]
+from = "header:content-disposition@filename"
+regex = "^(.+)\\.pdf$"
+
+[map.date]
+from = "header:Last-Modified"
+
+[map.description]
+from = "header:x-missing"
+optional = true
+"#;
+    let doc = |disposition: &str| FetchedDocument {
+        source_url: "https://stats.example/latest".to_string(),
+        body: b"<body><p>See attachment.</p></body>".to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: [
+            ("content-disposition", disposition),
+            ("last-modified", "Fri, 01 Mar 2030 13:30:00 GMT"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect(),
+    };
+
+    let candidates = preview_source_events(
+        config,
+        &[doc(r#"attachment; filename="Quarterly report.pdf""#)],
+    )?;
+    assert_eq!(candidates[0].title, "Quarterly report");
+    let EventTimeSpec::DateTime { start, .. } = candidates[0].time else {
+        panic!("expected a timed event, got {:?}", candidates[0].time);
+    };
+    assert_eq!(start.to_rfc3339(), "2030-03-01T13:30:00+00:00");
+    assert_eq!(candidates[0].description, None);
+
+    // RFC 5987 `filename*` wins over the ASCII fallback.
+    let candidates = preview_source_events(
+        config,
+        &[doc(
+            "attachment; filename=\"Reunion.pdf\"; filename*=UTF-8''R%C3%A9union%20du%20conseil.pdf",
+        )],
+    )?;
+    assert_eq!(candidates[0].title, "Réunion du conseil");
+    Ok(())
+}
+
+#[test]
+fn floating_time_basis_writes_wall_clock_times_without_zone() -> Result<()> {
+    let config = |timezone: &str| {
+        format!(
+            r#"[source]
+key = "preview.webinar"
+name = "Preview Webinar"
+domain = "publishing"
+{timezone}
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "text"
+
+[date]
+formats = ["%Y-%m-%d %H:%M"]
+
+[publish]
+time_basis = "floating"
+"#
+        )
+    };
+    let docs = [FetchedDocument {
+        source_url: "inline://preview.webinar".to_string(),
+        body: b"2026-01-15 14:30 | Data tools webinar".to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    }];
+
+    for timezone in ["timezone = \"Europe/Paris\"", ""] {
+        let config = config(timezone);
+        let candidates = preview_source_events(&config, &docs)?;
+        let publish = rics::config::parse_source_config(&config)?.publish;
+        let mut records = preview_records(candidates)?;
+        records[0].rrule = Some("FREQ=WEEKLY;COUNT=3".to_string());
+        records[0].exdates = vec![RecurrenceDate::DateTime("2026-01-22T13:30:00Z".parse()?)];
+        let ics = render_named_year_calendar(
+            "Preview Webinar",
+            2026,
+            &records.iter().collect::<Vec<_>>(),
+            &publish,
+        );
+        assert!(ics.contains("\r\nDTSTART:20260115T143000\r\n"), "{ics}");
+        assert!(!ics.contains("TZID="), "{ics}");
+        if timezone.is_empty() {
+            assert!(ics.contains("\r\nEXDATE:20260122T133000\r\n"), "{ics}");
+        } else {
+            assert!(ics.contains("\r\nEXDATE:20260122T143000\r\n"), "{ics}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn custom_parsers_come_from_the_publishers_feature() -> Result<()> {
+    let config = rics::config::parse_source_config(
+        r#"[source]
+key = "preview.custom"
+name = "Preview Custom"
+domain = "publishing"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "text"
+record_regex = '(?m)^(?P<record>.+)$'
+
+[map.title]
+from = 'regex:\|\s*(.+)$'
+
+[map.date]
+from = 'regex:^(\d{4}-\d{2}-\d{2})'
+
+[custom]
+enabled = true
+parser = "rough_text_lines_v1"
+"#,
+    )?;
+    let source = rics::config::LoadedSource {
+        path: "preview.toml".into(),
+        config,
+        tenant: Default::default(),
+    };
+    let docs = [FetchedDocument {
+        source_url: "inline://preview.custom".to_string(),
+        body: b"2030-03-04 | Hearing\n".to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    }];
+
+    let outcome = rics::parser::parse_source_documents(&source, &docs)?;
+    assert_eq!(outcome.events.len(), 1);
+    assert_eq!(outcome.events[0].title, "Hearing");
+    let parser = outcome.events[0].metadata.get("custom_parser");
+    if cfg!(feature = "publishers") {
+        assert_eq!(parser.map(String::as_str), Some("rough_text_lines_v1"));
+        assert!(outcome.warnings.is_empty());
+    } else {
+        assert_eq!(parser, None);
+        assert_eq!(
+            outcome.warnings[0].kind,
+            ParseWarningKind::CustomParserMissing
+        );
+        assert!(
+            outcome.warnings[0]
+                .message
+                .contains("without the `publishers` feature")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "pdf"))]
+#[test]
+fn pdf_text_needs_the_pdf_feature() {
+    let config = r#"[source]
+key = "preview.pdf"
+name = "Preview PDF"
+domain = "publishing"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "pdf_text"
+record_regex = '(?m)^(?P<record>.+)$'
+
+[map.title]
+from = 'regex:(.+)'
+
+[map.date]
+from = 'regex:(\d{4}-\d{2}-\d{2})'
+"#;
+    let err = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://preview.pdf".to_string(),
+            body: b"%PDF-1.4 2030-03-04 Hearing".to_vec().into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("`pdf` feature"), "{err:#}");
+}
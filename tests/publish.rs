@@ -0,0 +1,890 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{Datelike, Utc};
+use common::{sync_options, write_json_source};
+use rics::config::{PublishConfig, YearBound};
+use rics::pipeline::{BuildOptions, SyncOptions, build_calendars, sync_sources};
+use rics::staging::Staging;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+fn ics_files(dir: &Path) -> Result<Vec<String>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .filter(|name: &Result<String>| name.as_ref().map_or(true, |name| name.ends_with(".ics")))
+        .collect::<Result<Vec<_>>>()?;
+    files.sort();
+    Ok(files)
+}
+
+#[test]
+fn publish_year_bounds_limit_calendar_files() -> Result<()> {
+    let current = Utc::now().year();
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let rows = [current - 30, current - 1, current, current + 5]
+        .iter()
+        .map(|year| {
+            format!(r#"{{"id": "e{year}", "title": "Event {year}", "date": "{year}-06-01"}}"#)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    fs::write(
+        config_dir.join("archive.toml"),
+        format!(
+            r#"[source]
+key = "publish.archive"
+name = "Archive"
+domain = "history"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": [{rows}]}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[publish]
+min_year = "current-1"
+max_year = "current + 2"
+"#
+        ),
+    )?;
+    let out_dir = root.path().join("out");
+    let reports = sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: out_dir.clone(),
+        ..SyncOptions::default()
+    })?;
+    assert_eq!(reports[0].inserted, 4);
+
+    let files = ics_files(&out_dir.join("sources/publish-archive"))?;
+    assert_eq!(
+        files,
+        vec![
+            format!("publish-archive-{}.ics", current - 1),
+            format!("publish-archive-{current}.ics"),
+        ]
+    );
+
+    let publish = PublishConfig {
+        min_year: Some(YearBound::Absolute(2020)),
+        ..PublishConfig::default()
+    };
+    assert!(publish.publishes_year(2020, 2030));
+    assert!(!publish.publishes_year(2019, 2030));
+    assert_eq!("current+3".parse::<YearBound>()?, YearBound::Relative(3));
+    assert!("next year".parse::<YearBound>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn cleanup_only_removes_files_listed_in_the_manifest() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    let webroot = root.path().join("webroot");
+    fs::create_dir_all(&config_dir)?;
+    fs::create_dir_all(&webroot)?;
+    fs::write(webroot.join("2029.ics"), "hand-placed")?;
+    fs::write(webroot.join("holidays-2031.ics"), "hand-placed")?;
+
+    let write_source = |rows: &str| {
+        fs::write(
+            config_dir.join("releases.toml"),
+            format!(
+                r#"[source]
+key = "publish.releases"
+name = "Releases"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": {rows}}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[publish]
+mirror_dir = "{}"
+mirror_source_subdir = false
+"#,
+                webroot.display()
+            ),
+        )
+    };
+    let options = SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+
+    write_source(
+        r#"[{"id": "a", "title": "A", "date": "2030-03-01"}, {"id": "b", "title": "B", "date": "2031-03-01"}]"#,
+    )?;
+    sync_sources(&options)?;
+    let mut expected = vec![
+        "2029.ics",
+        "holidays-2031.ics",
+        "publish-releases-2030.ics",
+        "publish-releases-2031.ics",
+    ];
+    assert_eq!(ics_files(&webroot)?, expected);
+
+    // Only 2030 changes; the untouched 2031 file must survive.
+    write_source(
+        r#"[{"id": "a", "title": "A (revised)", "date": "2030-03-01"}, {"id": "b", "title": "B", "date": "2031-03-01"}]"#,
+    )?;
+    sync_sources(&options)?;
+    assert_eq!(ics_files(&webroot)?, expected);
+
+    write_source(r#"[{"id": "a", "title": "A (revised)", "date": "2030-03-01"}]"#)?;
+    sync_sources(&options)?;
+    expected.retain(|name| *name != "publish-releases-2031.ics");
+    assert_eq!(ics_files(&webroot)?, expected);
+    assert_eq!(
+        ics_files(&options.out_dir.join("sources/publish-releases"))?,
+        vec!["publish-releases-2030.ics"]
+    );
+
+    // Every rebuild was committed, so no staged copies are left behind.
+    assert_eq!(
+        fs::read_dir(options.out_dir.join(".staging/sources"))?.count(),
+        0
+    );
+
+    let manifest = rics::manifest::OutputManifest::load(&webroot)?;
+    assert!(manifest.owns("publish-releases-2030.ics"));
+    assert!(!manifest.owns("2029.ics"));
+
+    Ok(())
+}
+
+#[test]
+fn staged_files_reach_the_live_directory_only_on_commit() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let live = root.path().join("live");
+    let staging_dir = root.path().join(".staging/live");
+    fs::create_dir_all(&live)?;
+    fs::write(live.join("cal-2030.ics"), "old")?;
+    fs::write(live.join("cal-2031.ics"), "same")?;
+
+    // An abandoned rebuild leaves the live files alone.
+    let staging = Staging::new(&live, &staging_dir)?;
+    fs::write(staging.path("cal-2030.ics"), "half")?;
+    drop(staging);
+    assert_eq!(fs::read_to_string(live.join("cal-2030.ics"))?, "old");
+    assert!(!staging_dir.exists());
+
+    let staging = Staging::new(&live, &staging_dir)?;
+    fs::write(staging.path("cal-2030.ics"), "new")?;
+    fs::write(staging.path("cal-2031.ics"), "same")?;
+    fs::write(staging.path("cal-2032.ics"), "added")?;
+    assert_eq!(fs::read_to_string(live.join("cal-2030.ics"))?, "old");
+    let committed = staging.commit()?;
+    assert_eq!(
+        committed,
+        vec!["cal-2030.ics", "cal-2031.ics", "cal-2032.ics"]
+    );
+    assert_eq!(fs::read_to_string(live.join("cal-2030.ics"))?, "new");
+    assert_eq!(fs::read_to_string(live.join("cal-2032.ics"))?, "added");
+    assert!(!staging_dir.exists());
+
+    Ok(())
+}
+
+#[test]
+fn aliases_follow_the_current_and_next_year_files() -> Result<()> {
+    let current = Utc::now().year();
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let write_source = |years: &[i32]| {
+        let rows = years
+            .iter()
+            .map(|year| {
+                format!(r#"{{"id": "e{year}", "title": "Event {year}", "date": "{year}-06-01"}}"#)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        fs::write(
+            config_dir.join("meetings.toml"),
+            format!(
+                r#"[source]
+key = "publish.meetings"
+name = "Meetings"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": [{rows}]}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[publish]
+aliases = "symlink"
+"#
+            ),
+        )
+    };
+    let options = SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+    let dir = options.out_dir.join("sources/publish-meetings");
+
+    write_source(&[current - 1, current, current + 1])?;
+    sync_sources(&options)?;
+    assert_eq!(
+        ics_files(&dir)?,
+        vec![
+            format!("publish-meetings-{}.ics", current - 1),
+            format!("publish-meetings-{current}.ics"),
+            format!("publish-meetings-{}.ics", current + 1),
+            "publish-meetings-current.ics".to_string(),
+            "publish-meetings-next.ics".to_string(),
+        ]
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("publish-meetings-current.ics"))?,
+        fs::read_to_string(dir.join(format!("publish-meetings-{current}.ics")))?
+    );
+    assert!(
+        fs::read_to_string(dir.join("publish-meetings-next.ics"))?
+            .contains(&format!("Event {}", current + 1))
+    );
+
+    // Without next year's events the alias goes away with the file.
+    write_source(&[current - 1, current])?;
+    sync_sources(&options)?;
+    assert!(
+        dir.join("publish-meetings-next.ics")
+            .symlink_metadata()
+            .is_err()
+    );
+    assert!(dir.join("publish-meetings-current.ics").exists());
+
+    Ok(())
+}
+
+#[test]
+fn redirect_map_points_stable_paths_at_templated_files() -> Result<()> {
+    let current = Utc::now().year();
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("board.toml"),
+        format!(
+            r#"[source]
+key = "publish.board"
+name = "Board"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": [{{"id": "a", "title": "Meeting", "date": "{current}-06-01"}}]}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[publish]
+file_name_template = "board-meetings-{{{{year}}}}-v2"
+"#
+        ),
+    )?;
+    fs::write(
+        root.path().join("redirects.toml"),
+        r#"format = "netlify"
+base_path = "/cal"
+source_path = "{{source_key}}/{{year}}.ics"
+"#,
+    )?;
+    let out_dir = root.path().join("out");
+    sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: out_dir.clone(),
+        ..SyncOptions::default()
+    })?;
+
+    let target = format!("/cal/sources/publish-board/board-meetings-{current}-v2.ics");
+    assert!(out_dir.join(&target["/cal/".len()..]).exists());
+    let map = fs::read_to_string(out_dir.join("_redirects"))?;
+    assert_eq!(
+        map.lines().skip(1).collect::<Vec<_>>(),
+        vec![
+            format!("/cal/publish.board/{current}.ics {target} 302"),
+            format!("/cal/publish.board/current.ics {target} 302"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn over_budget_calendars_move_events_to_an_overflow_file() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let rows = [(1, 90), (2, 10), (3, 50), (4, 10), (5, 70)]
+        .iter()
+        .map(|(month, importance)| {
+            format!(
+                r#"{{"id": "m{month}", "title": "Meeting {month}", "date": "2030-0{month}-01", "importance": {importance}}}"#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let write_config = |budget: &str| {
+        fs::write(
+            config_dir.join("meetings.toml"),
+            format!(
+                r#"[source]
+key = "publish.budget"
+name = "Budget"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": [{rows}]}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[map.importance]
+from = "json:/importance"
+
+[publish]
+{budget}
+"#
+            ),
+        )
+    };
+    let options = SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+    let dir = root.path().join("out/sources/publish-budget");
+    let titles = |name: &str| -> Result<Vec<String>> {
+        Ok(fs::read_to_string(dir.join(name))?
+            .lines()
+            .filter_map(|line| line.strip_prefix("SUMMARY:"))
+            .map(str::to_string)
+            .collect())
+    };
+
+    write_config("max_events_per_file = 3")?;
+    sync_sources(&options)?;
+    assert_eq!(
+        titles("publish-budget-2030.ics")?,
+        ["Meeting 1", "Meeting 3", "Meeting 5"]
+    );
+    assert_eq!(
+        titles("publish-budget-2030.overflow.ics")?,
+        ["Meeting 2", "Meeting 4"]
+    );
+
+    let full_size = fs::metadata(dir.join("publish-budget-2030.ics"))?.len()
+        + fs::metadata(dir.join("publish-budget-2030.overflow.ics"))?.len();
+    write_config(&format!(
+        "max_file_bytes = {}\ntrim_order = \"oldest\"",
+        full_size / 2
+    ))?;
+    sync_sources(&options)?;
+    let kept = titles("publish-budget-2030.ics")?;
+    assert!(fs::metadata(dir.join("publish-budget-2030.ics"))?.len() <= full_size / 2);
+    assert!(kept.contains(&"Meeting 5".to_string()));
+    assert!(!kept.contains(&"Meeting 1".to_string()));
+    assert_eq!(
+        kept.len() + titles("publish-budget-2030.overflow.ics")?.len(),
+        5
+    );
+
+    write_config("")?;
+    sync_sources(&options)?;
+    assert_eq!(titles("publish-budget-2030.ics")?.len(), 5);
+    assert!(!dir.join("publish-budget-2030.overflow.ics").exists());
+
+    // One byte over the budget moves out only the oldest event.
+    let untrimmed = fs::metadata(dir.join("publish-budget-2030.ics"))?.len();
+    write_config(&format!(
+        "max_file_bytes = {}\ntrim_order = \"oldest\"",
+        untrimmed - 1
+    ))?;
+    sync_sources(&options)?;
+    assert_eq!(titles("publish-budget-2030.overflow.ics")?, ["Meeting 1"]);
+    assert!(fs::metadata(dir.join("publish-budget-2030.ics"))?.len() < untrimmed);
+
+    Ok(())
+}
+
+#[test]
+fn compress_writes_gzip_copies_next_to_each_calendar() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let write_config = |publish: &str| {
+        fs::write(
+            config_dir.join("meetings.toml"),
+            format!(
+                r#"[source]
+key = "publish.gzip"
+name = "Gzip"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": [{{"id": "m1", "title": "Meeting", "date": "2030-03-01"}}]}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[publish]
+{publish}
+"#
+            ),
+        )
+    };
+    let options = SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+    let dir = root.path().join("out/sources/publish-gzip");
+    let gzip = dir.join("publish-gzip-2030.ics.gz");
+
+    write_config("")?;
+    sync_sources(&options)?;
+    assert!(!gzip.exists());
+
+    // Enabling compression covers calendars that did not change.
+    write_config(r#"compress = ["gzip"]"#)?;
+    sync_sources(&options)?;
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(fs::File::open(&gzip)?).read_to_string(&mut decoded)?;
+    assert_eq!(
+        decoded,
+        fs::read_to_string(dir.join("publish-gzip-2030.ics"))?
+    );
+
+    write_config("")?;
+    sync_sources(&options)?;
+    assert!(!gzip.exists());
+    assert!(dir.join("publish-gzip-2030.ics").exists());
+
+    Ok(())
+}
+
+#[test]
+fn publish_profiles_write_filtered_variants_from_the_same_events() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("meetings.toml"),
+        r#"[source]
+key = "publish.profiles"
+name = "Profiles"
+domain = "government"
+
+[fetch]
+mode = "inline"
+inline_data = """{"rows": [
+  {"id": "board", "title": "Board meeting", "date": "2030-03-01", "importance": 80, "contact": "clerk@agency.example", "room": "B2"},
+  {"id": "standup", "title": "Team standup", "date": "2030-03-02", "importance": 10, "contact": "", "room": "A1"},
+  {"id": "retro", "title": "Staff retro", "date": "2030-03-03", "importance": 90, "category": "internal", "contact": "", "room": "A1"}
+]}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[map.importance]
+from = "json:/importance"
+
+[map.categories]
+from = "json:/category"
+optional = true
+
+[map.description]
+from = "json:/contact"
+optional = true
+
+[map.room]
+from = "json:/room"
+
+[publish.profiles.public]
+min_importance = 50
+exclude_categories = ["internal"]
+x_properties = { passthrough = false }
+redact = [{ builtin = "email" }]
+"#,
+    )?;
+    sync_sources(&SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    })?;
+
+    let dir = root.path().join("out/sources/publish-profiles");
+    let internal = fs::read_to_string(dir.join("publish-profiles-2030.ics"))?;
+    let public = fs::read_to_string(dir.join("publish-profiles-2030.public.ics"))?;
+    for title in ["Board meeting", "Team standup", "Staff retro"] {
+        assert!(internal.contains(&format!("SUMMARY:{title}")), "{internal}");
+    }
+    assert!(internal.contains("clerk@agency.example"));
+    assert!(internal.contains("X-RICS-ROOM:B2"));
+
+    assert!(public.contains("SUMMARY:Board meeting"));
+    assert!(!public.contains("Team standup"));
+    assert!(!public.contains("Staff retro"));
+    assert!(public.contains("DESCRIPTION:[redacted]"));
+    assert!(!public.contains("X-RICS-ROOM"));
+
+    Ok(())
+}
+
+#[test]
+fn weekly_digest_folds_minor_events_into_one_all_day_event() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    fs::write(
+        config_dir.join("releases.toml"),
+        r#"[source]
+key = "publish.digest"
+name = "Digest"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = """{"rows": [
+  {"id": "a", "title": "Housing starts", "date": "2030-03-05", "importance": 10},
+  {"id": "b", "title": "Retail sales", "date": "2030-03-06", "importance": 20},
+  {"id": "c", "title": "Jobless claims", "date": "2030-03-07", "importance": 10},
+  {"id": "d", "title": "Rate decision", "date": "2030-03-06", "importance": 90},
+  {"id": "e", "title": "Trade balance", "date": "2030-03-12", "importance": 10}
+]}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[map.importance]
+from = "json:/importance"
+
+[publish.digest]
+period = "weekly"
+max_importance = 30
+"#,
+    )?;
+    let options = SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+    sync_sources(&options)?;
+
+    let path = root
+        .path()
+        .join("out/sources/publish-digest/publish-digest-2030.ics");
+    let ics = fs::read_to_string(&path)?;
+    let summaries = ics
+        .lines()
+        .filter_map(|line| line.strip_prefix("SUMMARY:"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summaries,
+        [
+            "Week of 4 March: 3 events",
+            "Rate decision",
+            "Trade balance"
+        ]
+    );
+    assert!(ics.contains("DTSTART;VALUE=DATE:20300304"));
+    assert!(ics.contains("DESCRIPTION:Tue 5 Mar: Housing starts\\nWed 6 Mar: Retail sales"));
+
+    // Unchanged events give a byte-identical digest.
+    sync_sources(&options)?;
+    assert_eq!(fs::read_to_string(&path)?, ics);
+
+    Ok(())
+}
+
+#[test]
+fn a_failed_publish_rolls_back_only_that_sources_state() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    let webroot = root.path().join("webroot");
+    fs::create_dir_all(&config_dir)?;
+    fs::create_dir_all(&webroot)?;
+    let write_source = |key: &str, title: &str, publish: &str| {
+        fs::write(
+            config_dir.join(format!("{key}.toml")),
+            format!(
+                r#"[source]
+key = "publish.{key}"
+name = "{key}"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = """{{"rows": [{{"id": "a", "title": "{title}", "date": "2030-03-01"}}]}}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.id]
+from = "json:/id"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+
+[publish]
+{publish}
+"#
+            ),
+        )
+    };
+    let mirrored = format!(
+        "mirror_dir = \"{}\"\nmirror_source_subdir = false",
+        webroot.display()
+    );
+    let options = SyncOptions {
+        config_dir: config_dir.clone(),
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..SyncOptions::default()
+    };
+    write_source("mirrored", "Old title", &mirrored)?;
+    write_source("plain", "Old title", "")?;
+    sync_sources(&options)?;
+
+    // The mirror copy fails once the live calendar was already replaced.
+    let mirror_file = webroot.join("publish-mirrored-2030.ics");
+    fs::remove_file(&mirror_file)?;
+    fs::create_dir_all(mirror_file.join("blocker"))?;
+    write_source("mirrored", "New title", &mirrored)?;
+    write_source("plain", "New title", "")?;
+    let reports = sync_sources(&options)?;
+
+    let report = |key: &str| {
+        reports
+            .iter()
+            .find(|report| report.source_key == key)
+            .expect("report")
+    };
+    let failed = report("publish.mirrored");
+    assert!(
+        failed
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("publish-mirrored-2030.ics")),
+        "{failed:?}"
+    );
+    assert!(failed.rolled_back);
+    assert_eq!(failed.updated, 0);
+    assert_eq!(report("publish.plain").updated, 1);
+
+    let state = rics::store::load_state(&options.state_path)?;
+    let title = |key: &str| {
+        state
+            .events
+            .values()
+            .find(|event| event.source_key == key)
+            .map(|event| event.title.clone())
+    };
+    assert_eq!(title("publish.mirrored").as_deref(), Some("Old title"));
+    assert_eq!(title("publish.plain").as_deref(), Some("New title"));
+    // The live calendar was rebuilt from the rolled-back state.
+    let live = fs::read_to_string(
+        options
+            .out_dir
+            .join("sources/publish-mirrored/publish-mirrored-2030.ics"),
+    )?;
+    assert!(live.contains("Old title") && !live.contains("New title"));
+
+    fs::remove_dir_all(&mirror_file)?;
+    let reports = sync_sources(&options)?;
+    assert!(reports.iter().all(|report| report.error.is_none()));
+    assert!(fs::read_to_string(&mirror_file)?.contains("New title"));
+    Ok(())
+}
+
+#[test]
+fn parallel_builds_write_the_same_calendars_as_one_thread() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let options = sync_options(root.path());
+    fs::create_dir_all(&options.config_dir)?;
+    for n in 0..8 {
+        let rows = (2026..=2031)
+            .flat_map(|year| {
+                ["us", "ca"].map(|country| {
+                    format!(
+                        r#"{{"id": "{country}{year}", "title": "Board {n} {country} {year}", "date": "{year}-0{}-01", "country": "{country}"}}"#,
+                        n + 1
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let extra = if n == 0 {
+            r#"[map.country]
+from = "json:/country"
+
+[publish]
+split_by_country = true
+file_name_template = "{{source_dir}}-{{country}}-{{year}}.ics"
+"#
+        } else {
+            ""
+        };
+        write_json_source(
+            &options.config_dir.join(format!("board-{n}.toml")),
+            &format!("build.board{n}"),
+            &format!("Board {n}"),
+            "government",
+            &format!("[{rows}]"),
+            extra,
+        )?;
+    }
+    sync_sources(&options)?;
+
+    let build = |name: &str, threads: usize| -> Result<BTreeMap<String, String>> {
+        let out_dir = root.path().join(name);
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?
+            .install(|| {
+                build_calendars(&BuildOptions {
+                    config_dir: options.config_dir.clone(),
+                    state_path: options.state_path.clone(),
+                    out_dir: out_dir.clone(),
+                    source: None,
+                    year: None,
+                })
+            })?;
+        let mut calendars = BTreeMap::new();
+        for entry in walkdir::WalkDir::new(&out_dir) {
+            let entry = entry?;
+            if entry.path().extension().is_some_and(|ext| ext == "ics") {
+                let relative = entry.path().strip_prefix(&out_dir)?;
+                calendars.insert(
+                    relative.to_string_lossy().into_owned(),
+                    fs::read_to_string(entry.path())?,
+                );
+            }
+        }
+        Ok(calendars)
+    };
+
+    let serial = build("serial", 1)?;
+    let parallel = build("parallel", 8)?;
+
+    assert_eq!(parallel, serial);
+    // 7 sources with a file per year, and one split into two countries.
+    assert_eq!(serial.len(), 7 * 6 + 2 * 6);
+    assert!(serial.contains_key("sources/build-board0/build-board0-ca-2030.ics"));
+    assert!(
+        serial["sources/build-board3/build-board3-2028.ics"].contains("SUMMARY:Board 3 us 2028")
+    );
+
+    Ok(())
+}
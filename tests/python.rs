@@ -0,0 +1,79 @@
+#![cfg(feature = "python")]
+
+mod common;
+
+use anyhow::Result;
+use common::write_json_source;
+use pyo3::prelude::*;
+use pyo3::wrap_pymodule;
+use serde_json::Value;
+use std::fs;
+
+/// `value` as JSON, to compare what Python got without walking dicts.
+fn to_json(value: &Bound<'_, PyAny>) -> Result<Value> {
+    let json = value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract::<String>()?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+#[test]
+fn python_module_syncs_builds_previews_and_loads_events() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    write_json_source(
+        &config_dir.join("board.toml"),
+        "python.board",
+        "Board",
+        "government",
+        r#"[{"id": "b-1", "title": "Board meeting", "date": "2030-03-01"}]"#,
+        "",
+    )?;
+    let state_path = root.path().join("state/events.json");
+    let out_dir = root.path().join("out");
+
+    Python::initialize();
+    Python::attach(|py| -> Result<()> {
+        let module = wrap_pymodule!(rics::python::rics)(py);
+        let module = module.bind(py);
+
+        let reports = module
+            .getattr("sync")?
+            .call1((&config_dir, &state_path, &out_dir))?;
+        let reports = to_json(&reports)?;
+        assert_eq!(reports[0]["source_key"], "python.board");
+        assert_eq!(reports[0]["inserted"], 1);
+
+        let events = to_json(&module.getattr("load_events")?.call1((&state_path,))?)?;
+        let titles = events.as_array().map(|events| {
+            events
+                .iter()
+                .map(|e| e["title"].clone())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(titles, Some(vec![Value::from("Board meeting")]));
+
+        fs::remove_dir_all(&out_dir)?;
+        module
+            .getattr("build")?
+            .call1((&config_dir, &state_path, &out_dir))?;
+        let calendar =
+            fs::read_to_string(out_dir.join("sources/python-board/python-board-2030.ics"))?;
+        assert!(calendar.contains("SUMMARY:Board meeting"));
+
+        let config = fs::read_to_string(config_dir.join("board.toml"))?;
+        let body = r#"{"rows": [{"id": "b-2", "title": "Budget vote", "date": "2030-04-02"}]}"#;
+        let candidates = to_json(&module.getattr("preview")?.call1((config, body))?)?;
+        assert_eq!(candidates[0]["title"], "Budget vote");
+
+        let err = module
+            .getattr("sync")?
+            .call1((root.path().join("missing"), &state_path, &out_dir))
+            .unwrap_err();
+        assert!(err.is_instance_of::<pyo3::exceptions::PyRuntimeError>(py));
+        Ok(())
+    })
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use anyhow::Result;
+use common::{sync_options, write_json_source};
+use rics::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use rics::quarantine::{Quarantine, apply_quarantined, reject_quarantined, title_similarity};
+use std::fs;
+
+const QUARANTINE: &str = r#"[quarantine]
+enabled = true
+max_date_shift_days = 60
+min_title_similarity = 0.5
+"#;
+
+fn stored(options: &SyncOptions) -> Result<(String, String)> {
+    let state = load_state_for_read(&options.state_path)?;
+    let event = state.events.values().next().expect("one event");
+    Ok((
+        event.title.clone(),
+        event.time.start_date().expect("dated").to_string(),
+    ))
+}
+
+#[test]
+fn suspicious_updates_wait_in_quarantine_until_reviewed() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    let options = sync_options(root.path());
+    let queue = || Quarantine::open(&Quarantine::path_for_state(&options.state_path));
+
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "quarantine.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP advance estimate", "date": "2030-04-25"}]"#,
+        QUARANTINE,
+    )?;
+    sync_sources(&options)?;
+
+    // A small move is an ordinary update.
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "quarantine.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP advance estimate", "date": "2030-04-28"}]"#,
+        QUARANTINE,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].updated, 1);
+
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "quarantine.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "GDP advance estimate", "date": "2030-09-30"}]"#,
+        QUARANTINE,
+    )?;
+    let reports = sync_sources(&options)?;
+    assert_eq!((reports[0].updated, reports[0].quarantined), (0, 1));
+    assert_eq!(
+        stored(&options)?,
+        ("GDP advance estimate".to_string(), "2030-04-28".to_string())
+    );
+    let entries = queue()?.entries().to_vec();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].reasons[0].starts_with("date moved 155 days"));
+    // Another state file in the same directory has its own queue.
+    let other = options.state_path.with_file_name("other.json");
+    assert!(
+        Quarantine::open(&Quarantine::path_for_state(&other))?
+            .entries()
+            .is_empty()
+    );
+
+    // Rejected revisions are not queued again.
+    reject_quarantined(&options.state_path, &entries[0].id)?;
+    let reports = sync_sources(&options)?;
+    assert_eq!((reports[0].unchanged, reports[0].quarantined), (1, 0));
+    assert!(queue()?.entries().is_empty());
+
+    write_json_source(
+        &config_dir.join("releases.toml"),
+        "quarantine.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "gdp", "title": "Page not found", "date": "2030-04-28"}]"#,
+        QUARANTINE,
+    )?;
+    assert_eq!(sync_sources(&options)?[0].quarantined, 1);
+    let entries = queue()?.entries().to_vec();
+    assert!(entries[0].reasons[0].starts_with("title similarity"));
+    apply_quarantined(&options.state_path, &entries[0].id)?;
+    assert_eq!(
+        stored(&options)?,
+        ("Page not found".to_string(), "2030-04-28".to_string())
+    );
+    assert!(queue()?.entries().is_empty());
+    assert!(apply_quarantined(&options.state_path, &entries[0].id).is_err());
+
+    assert!(title_similarity("GDP (advance)", "gdp advance") > 0.99);
+    assert!(title_similarity("GDP advance estimate", "Page not found") < 0.5);
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use rics::config::parse_source_config;
+use rics::fetch::FetchedDocument;
+use rics::parser::preview_source_events;
+
+fn text_config(extra: &str) -> String {
+    format!(
+        r#"[source]
+key = "regex.limits"
+name = "Regex Limits"
+domain = "publishing"
+
+[fetch]
+mode = "http"
+base_url = "https://example.invalid/releases.txt"
+
+[extract]
+format = "text"
+record_regex = "(?m)^(\\d{{4}}-\\d{{2}}-\\d{{2}} \\| .+)$"
+{extra}
+"#
+    )
+}
+
+#[test]
+fn oversized_patterns_are_rejected_when_the_config_loads() {
+    let config = format!(
+        "{}\n[map.title]\nfrom = \"regex:(\\\\w{{500}}){{500}}\"\n",
+        text_config("")
+    );
+    let err = parse_source_config(&config).unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("map.title.from is invalid"), "{message}");
+    assert!(message.contains("too complex"), "{message}");
+}
+
+#[test]
+fn regex_over_its_time_budget_fails_the_parse() {
+    let body = "2030-01-01 | Weekly release\n".repeat(50_000);
+    let doc = FetchedDocument {
+        source_url: "https://example.invalid/releases.txt".to_string(),
+        body: body.into_bytes().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    };
+
+    let err = preview_source_events(&text_config("regex_budget_ms = 1"), &[doc]).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("over its 1 ms budget"),
+        "{err:#}"
+    );
+}
@@ -0,0 +1,215 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use rics::config::LoadedSource;
+use rics::fetch::FetchedDocument;
+use rics::holidays::HolidayCalendar;
+use rics::parser::preview_source_events;
+use rics::schedule::{BusinessDayRule, Period, RuleKind};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn business_day_rules_parse_and_respect_holidays() -> Result<()> {
+    let us = HolidayCalendar::new("US", BTreeMap::new());
+
+    let fifth: BusinessDayRule = "5th business day of month".parse()?;
+    assert_eq!(
+        fifth,
+        BusinessDayRule {
+            period: Period::Month,
+            kind: RuleKind::Nth(5)
+        }
+    );
+    assert_eq!(
+        fifth.date_in(date(2030, 5, 1), us.as_ref()),
+        date(2030, 5, 7)
+    );
+    // 4 July 2030 is a Thursday.
+    assert_eq!(
+        fifth.date_in(date(2030, 7, 1), us.as_ref()),
+        date(2030, 7, 8)
+    );
+    assert_eq!(fifth.date_in(date(2030, 7, 1), None), date(2030, 7, 5));
+
+    let after_quarter: BusinessDayRule = "T+2 business days after quarter end".parse()?;
+    assert_eq!(
+        after_quarter.date_in(date(2030, 4, 1), us.as_ref()),
+        date(2030, 7, 2)
+    );
+
+    let last: BusinessDayRule = "Last business day of the month".parse()?;
+    // New Year's Day 2028 is observed on Friday 31 December 2027.
+    assert_eq!(
+        last.date_in(date(2027, 12, 1), us.as_ref()),
+        date(2027, 12, 30)
+    );
+
+    let before: BusinessDayRule = "2 business days before year end".parse()?;
+    assert_eq!(
+        before.date_in(date(2030, 1, 1), us.as_ref()),
+        date(2030, 12, 27)
+    );
+
+    assert!("every other tuesday".parse::<BusinessDayRule>().is_err());
+    assert!(
+        "0th business day of month"
+            .parse::<BusinessDayRule>()
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn date_rules_generate_period_keyed_events() -> Result<()> {
+    let config = r#"[source]
+key = "schedule.rules"
+name = "Rule Releases"
+domain = "economics"
+timezone = "America/New_York"
+default_country = "US"
+
+[fetch]
+mode = "inline"
+inline_data = "unused"
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[[date_rules]]
+title = "Retail Sales"
+rule = "5th business day of month"
+time = "08:30"
+from = "2030-06"
+months = 3
+
+[[date_rules]]
+title = "Quarterly Survey"
+id = "survey"
+rule = "T+2 business days after quarter end"
+from = "2030-06"
+months = 3
+"#;
+    let events = preview_source_events(
+        config,
+        &[FetchedDocument {
+            source_url: "inline://schedule".to_string(),
+            body: br#"{"rows": []}"#.to_vec().into(),
+            page_index: 0,
+            fetched_at: None,
+            headers: Default::default(),
+        }],
+    )?;
+    let summary = events
+        .iter()
+        .map(|event| {
+            (
+                event.source_event_id.clone().unwrap_or_default(),
+                event.time.start_date().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("retail-sales:2030-06".to_string(), date(2030, 6, 7)),
+            ("retail-sales:2030-07".to_string(), date(2030, 7, 8)),
+            ("retail-sales:2030-08".to_string(), date(2030, 8, 7)),
+            ("survey:2030-Q2".to_string(), date(2030, 7, 2)),
+            ("survey:2030-Q3".to_string(), date(2030, 10, 2)),
+        ]
+    );
+    let local = events[0].time.local_time().expect("local time kept");
+    assert_eq!(local.start_label(), "08:30 EDT");
+    assert_eq!(
+        events[0].metadata.get("date_rule").map(String::as_str),
+        Some("5th business day of month")
+    );
+
+    let bad = config.replace("5th business day of month", "fifth monday-ish");
+    assert!(rics::config::parse_source_config(&bad).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn schedule_mode_sources_generate_events_without_fetching() -> Result<()> {
+    let config = r#"[source]
+key = "schedule.fomc"
+name = "Scheduled Releases"
+domain = "economics"
+timezone = "America/New_York"
+default_country = "US"
+
+[fetch]
+mode = "schedule"
+
+[[schedule]]
+title = "FOMC Meeting"
+id = "fomc"
+dates = ["2030-01-29", "2030-03-19"]
+time = "14:00"
+end_time = "14:30"
+
+[[schedule]]
+title = "Employment Situation"
+every = "first friday of month"
+time = "08:30"
+from = "2030-06"
+months = 2
+
+[[schedule]]
+title = "Weekly Claims"
+every = "every thursday"
+from = "2030-06"
+months = 1
+"#;
+    let source = LoadedSource {
+        path: PathBuf::from("schedule.toml"),
+        config: rics::config::parse_source_config(config)?,
+        tenant: Default::default(),
+    };
+    assert!(rics::fetch::fetch_source_documents(&source)?.is_empty());
+    let events = preview_source_events(config, &[])?;
+    let summary = events
+        .iter()
+        .map(|event| {
+            (
+                event.source_event_id.clone().unwrap_or_default(),
+                event.time.start_date().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("fomc:2030-01-29".to_string(), date(2030, 1, 29)),
+            ("fomc:2030-03-19".to_string(), date(2030, 3, 19)),
+            ("employment-situation:2030-06".to_string(), date(2030, 6, 7)),
+            ("employment-situation:2030-07".to_string(), date(2030, 7, 5)),
+            ("weekly-claims:2030-06-06".to_string(), date(2030, 6, 6)),
+            ("weekly-claims:2030-06-13".to_string(), date(2030, 6, 13)),
+            ("weekly-claims:2030-06-20".to_string(), date(2030, 6, 20)),
+            ("weekly-claims:2030-06-27".to_string(), date(2030, 6, 27)),
+        ]
+    );
+    let local = events[0].time.local_time().expect("local time kept");
+    assert_eq!(local.start_label(), "14:00 EST");
+    assert!(local.end.is_some());
+    assert_eq!(
+        events[2].metadata.get("schedule").map(String::as_str),
+        Some("first friday of month")
+    );
+
+    let empty = config.split("[[schedule]]").next().unwrap_or_default();
+    assert!(rics::config::parse_source_config(empty).is_err());
+    let bad = config.replace("first friday of month", "now and then");
+    assert!(rics::config::parse_source_config(&bad).is_err());
+
+    Ok(())
+}
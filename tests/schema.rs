@@ -0,0 +1,137 @@
+use anyhow::Result;
+use rics::fetch::FetchedDocument;
+use rics::parser::preview_source_events;
+use rics::schema::{candidate_event_schema, parse_candidate_events};
+
+#[test]
+fn published_schema_matches_the_candidate_event_type() -> Result<()> {
+    let published: serde_json::Value =
+        serde_json::from_str(include_str!("../schemas/candidate-event.schema.json"))?;
+    assert_eq!(
+        published,
+        candidate_event_schema(),
+        "regenerate with `rics schema > schemas/candidate-event.schema.json`"
+    );
+    Ok(())
+}
+
+#[test]
+fn parser_output_round_trips_through_validation() -> Result<()> {
+    let config = r#"[source]
+key = "schema.roundtrip"
+name = "Schema Roundtrip"
+domain = "publishing"
+
+[fetch]
+mode = "http"
+base_url = "https://example.invalid/releases.txt"
+
+[extract]
+format = "text"
+record_regex = "(?m)^(\\d{4}-\\d{2}-\\d{2} \\| .+)$"
+"#;
+    let doc = FetchedDocument {
+        source_url: "https://example.invalid/releases.txt".to_string(),
+        body: b"2030-01-01 | Annual report\n2030-02-01 | Budget hearing\n"
+            .to_vec()
+            .into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    };
+    let events = preview_source_events(config, &[doc])?;
+    let parsed = parse_candidate_events(&serde_json::to_string(&events)?)?;
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[1].title, events[1].title);
+    Ok(())
+}
+
+#[test]
+fn parsed_events_that_fail_the_checks_fail_the_source() {
+    let config = r#"[source]
+key = "schema.relative"
+name = "Schema Relative"
+domain = "publishing"
+
+[fetch]
+mode = "http"
+base_url = "https://example.invalid/releases"
+
+[extract]
+root_selector = "article"
+
+[map.title]
+from = "css:h2"
+
+[map.date]
+from = "css:time"
+
+[map.url]
+from = "css:a@href"
+"#;
+    let doc = FetchedDocument {
+        source_url: "https://example.invalid/releases".to_string(),
+        body: br#"<article><h2>Annual report</h2><time>2030-01-01</time><a href="/releases/annual">More</a></article>"#.to_vec().into(),
+        page_index: 0,
+        fetched_at: None,
+        headers: Default::default(),
+    };
+    let message = format!("{:#}", preview_source_events(config, &[doc]).unwrap_err());
+    assert!(
+        message.contains("source schema.relative produced invalid events")
+            && message.contains("[0].source_url: /releases/annual is not an absolute URL"),
+        "{message}"
+    );
+}
+
+fn event(time: &str, extra: &str) -> String {
+    format!(
+        r#"{{"source_key": "x", "source_name": "X", "title": "Hearing",
+            "time": {time}, "status": "confirmed", "event_type": "hearing",
+            "categories": [], "metadata": {{}}{extra}}}"#
+    )
+}
+
+#[test]
+fn schema_mismatches_name_the_event_and_field() {
+    let json = format!(
+        "[{}, {}]",
+        event(r#"{"kind": "date", "start": "2030-03-01"}"#, ""),
+        event(
+            r#"{"kind": "date", "start": "2030-03-01"}"#,
+            r#", "importance": "high""#
+        )
+    );
+    let err = parse_candidate_events(&json).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("at [1].importance: invalid type"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn every_semantic_problem_is_reported_at_once() {
+    let json = format!(
+        "[{}, {}]",
+        event(
+            r#"{"kind": "date", "start": "2030-03-02", "end": "2030-03-01"}"#,
+            r#", "source_url": "/events/1", "importance": 140"#
+        ),
+        event(
+            r#"{"kind": "month", "year": 2030, "month": 13}"#,
+            r#", "timezone": "Mars/Olympus", "rrule": "RRULE:FREQ=YEARLY""#
+        )
+    );
+    let message = format!("{:#}", parse_candidate_events(&json).unwrap_err());
+    for expected in [
+        "6 problems",
+        "[0].time.end: is before time.start",
+        "[0].source_url: /events/1 is not an absolute URL",
+        "[0].importance: 140 is not between 0 and 100",
+        "[1].time.month: 13 is not between 1 and 12",
+        "[1].timezone: Mars/Olympus is not an IANA time zone",
+        "[1].rrule: must not include the RRULE: prefix",
+    ] {
+        assert!(message.contains(expected), "{expected}: {message}");
+    }
+}
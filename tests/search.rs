@@ -0,0 +1,93 @@
+mod common;
+
+use anyhow::Result;
+use common::{sync_options, write_json_source};
+use rics::pipeline::{load_state_for_read, sync_sources};
+use rics::search::{SearchIndex, open_search_index, tokenize};
+use rics::store::StateLock;
+use std::fs;
+
+const DESCRIPTION: &str = r#"[map.description]
+from = "json:/description"
+"#;
+
+#[test]
+fn index_answers_text_queries_and_follows_syncs() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    write_json_source(
+        &config_dir.join("search.fed.toml"),
+        "search.fed",
+        "search.fed",
+        "economics",
+        r#"[{"id": "1", "title": "FOMC Rate Decision", "description": "Statement and projections", "date": "2030-03-18"},
+            {"id": "2", "title": "Beige Book", "description": "Regional conditions", "date": "2030-03-04"}]"#,
+        DESCRIPTION,
+    )?;
+    write_json_source(
+        &config_dir.join("search.wire.toml"),
+        "search.wire",
+        "search.wire",
+        "economics",
+        r#"[{"id": "a", "title": "FOMC rate decision!", "description": "Wire copy", "date": "2030-03-18"}]"#,
+        DESCRIPTION,
+    )?;
+    let options = sync_options(root.path());
+    let index_path = SearchIndex::path_for_state(&options.state_path);
+
+    sync_sources(&options)?;
+    // Nothing is indexed until the first query asks for it.
+    assert!(!index_path.exists());
+    let state = load_state_for_read(&options.state_path)?;
+    // A query during a sync answers from memory and leaves the file alone.
+    {
+        let _lock = StateLock::acquire(&options.state_path)?;
+        let index = open_search_index(&options.state_path, &state)?;
+        assert_eq!(index.search("fomc").len(), 2);
+        assert!(!index_path.exists());
+    }
+    let index = open_search_index(&options.state_path, &state)?;
+    assert!(index_path.exists());
+
+    let titles = |keys: Vec<String>| {
+        keys.iter()
+            .map(|key| state.events[key].title.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(titles(index.search("regional")), vec!["Beige Book"]);
+    assert_eq!(titles(index.search("beige bo")), vec!["Beige Book"]);
+    assert_eq!(index.search("fomc").len(), 2);
+    assert!(index.search("fomc beige").is_empty());
+
+    let duplicates = index.duplicates(&state, 0.8);
+    assert_eq!(duplicates.len(), 1);
+    assert_ne!(
+        state.events[&duplicates[0].first].source_key,
+        state.events[&duplicates[0].second].source_key
+    );
+
+    // Later syncs keep the existing index current.
+    write_json_source(
+        &config_dir.join("search.fed.toml"),
+        "search.fed",
+        "search.fed",
+        "economics",
+        r#"[{"id": "2", "title": "Beige Book", "description": "District summaries", "date": "2030-03-04"}]"#,
+        DESCRIPTION,
+    )?;
+    sync_sources(&options)?;
+    let index = SearchIndex::open(&index_path)?;
+    assert!(index.search("regional").is_empty());
+    assert_eq!(index.search("district").len(), 1);
+
+    assert_eq!(
+        tokenize("Non-farm Payrolls, 2030"),
+        ["2030", "farm", "non", "payrolls"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    );
+
+    Ok(())
+}
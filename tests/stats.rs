@@ -0,0 +1,71 @@
+mod common;
+
+use anyhow::Result;
+use common::{sync_options, write_json_source};
+use rics::pipeline::{SyncOptions, load_state_for_read, sync_sources};
+use rics::stats::{event_stats, stats_csv};
+use std::fs;
+
+const EVENT_DEFAULTS: &str = r#"[event]
+categories = ["economy", "prices, monthly"]
+importance = 85
+"#;
+
+#[test]
+fn stats_count_events_by_dimension_and_trend_over_the_latest_run() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    let options = SyncOptions {
+        jobs: 1,
+        ..sync_options(root.path())
+    };
+    fs::create_dir_all(&options.config_dir)?;
+
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "stats.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "cpi", "title": "CPI", "date": "2099-04-10"}, {"id": "ppi", "title": "PPI"}]"#,
+        EVENT_DEFAULTS,
+    )?;
+    sync_sources(&options)?;
+    write_json_source(
+        &options.config_dir.join("releases.toml"),
+        "stats.releases",
+        "Releases",
+        "economics",
+        r#"[{"id": "cpi", "title": "CPI", "date": "2099-04-10"}, {"id": "ppi", "title": "PPI"},
+            {"id": "gdp", "title": "GDP", "date": "2099-05-28"}]"#,
+        EVENT_DEFAULTS,
+    )?;
+    sync_sources(&options)?;
+
+    let state = load_state_for_read(&options.state_path)?;
+    let stats = event_stats(&state, None);
+    assert_eq!(stats.total, 3);
+    assert_eq!(stats.by_source["stats.releases"], 3);
+    assert_eq!(stats.by_year["2099"], 2);
+    assert_eq!(stats.by_year["tbd"], 1);
+    assert_eq!(stats.by_month["2099-04"], 1);
+    assert_eq!(stats.by_month["2099-05"], 1);
+    assert_eq!(stats.by_category["economy"], 3);
+    assert_eq!(stats.by_importance["80-89"], 3);
+    assert_eq!(stats.by_precision["date"], 2);
+    assert_eq!(stats.by_precision["tbd"], 1);
+
+    assert_eq!(stats.trends.len(), 1);
+    let trend = &stats.trends[0];
+    assert_eq!((trend.previous, trend.current, trend.inserted), (2, 3, 1));
+    assert_eq!(trend.change(), 1);
+
+    let csv = stats_csv(&stats);
+    assert!(csv.starts_with("dimension,key,count\ntotal,,3\n"), "{csv}");
+    assert!(csv.contains("\ncategory,\"prices, monthly\",3\n"), "{csv}");
+    assert!(
+        csv.contains("\nchange_since_previous_run,stats.releases,1\n"),
+        "{csv}"
+    );
+
+    assert_eq!(event_stats(&state, Some("other.source")).total, 0);
+    Ok(())
+}
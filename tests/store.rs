@@ -0,0 +1,262 @@
+use chrono::{NaiveDate, Utc};
+use rics::model::{EventRecord, EventTimeSpec, Lifecycle, MAX_RUN_HISTORY, RecurrenceDate, State};
+use rics::store::{EventQuery, EventStore, JsonFileStore, StateLock};
+
+#[test]
+fn event_store_queries_filter_by_source_year_and_status() {
+    let mut state = State::default();
+    state.upsert(record("a", "federal.us.scotus", 2026));
+    state.upsert(record("b", "federal.us.scotus", 2027));
+    state.upsert(record("c", "federal.us.congress", 2026));
+    state.upsert(record("d", "europe.fr", 2026));
+
+    assert_eq!(
+        state
+            .query(&EventQuery::for_source("federal.us.scotus"))
+            .len(),
+        2
+    );
+    assert_eq!(
+        state
+            .query(&EventQuery::for_source("federal.us.scotus").with_year(Some(2027)))
+            .len(),
+        1
+    );
+    assert_eq!(
+        state
+            .query(&EventQuery::for_patterns(&["federal.us.*".to_string()]))
+            .len(),
+        3
+    );
+
+    let now = Utc::now();
+    assert!(state.mark_cancelled("a", now));
+    assert!(!state.mark_cancelled("a", now));
+    assert_eq!(state.get("a").map(|event| event.sequence), Some(1));
+    assert_eq!(
+        state
+            .query(&EventQuery::for_source("federal.us.scotus"))
+            .len(),
+        1
+    );
+    assert_eq!(
+        state
+            .query(&EventQuery::for_source("federal.us.scotus").with_cancelled(true))
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn exception_instances_are_stored_alongside_their_series() {
+    let mut state = State::default();
+    let mut master = record("series", "federal.us.scotus", 2026);
+    master.rrule = Some("FREQ=WEEKLY;COUNT=4".to_string());
+    state.upsert(master);
+
+    let occurrence = NaiveDate::from_ymd_opt(2026, 6, 8).expect("valid date");
+    let mut instance = record("series", "federal.us.scotus", 2026);
+    instance.recurrence_id = Some(RecurrenceDate::Date(occurrence));
+    let instance_key = instance.store_key();
+    state.upsert(instance);
+
+    assert_eq!(state.len(), 2);
+    assert_eq!(instance_key, "series#20260608");
+    assert!(state.mark_cancelled(&instance_key, Utc::now()));
+    assert_eq!(state.get("series").map(|event| event.sequence), Some(0));
+    assert_eq!(
+        state.get(&instance_key).map(|event| event.sequence),
+        Some(1)
+    );
+
+    let restored: State = serde_json::from_str(&serde_json::to_string(&state).expect("serialize"))
+        .expect("deserialize");
+    assert_eq!(
+        restored
+            .get(&instance_key)
+            .and_then(|event| event.recurrence_id.clone()),
+        Some(RecurrenceDate::Date(occurrence))
+    );
+}
+
+#[test]
+fn legacy_cancelled_status_is_backfilled_into_lifecycle() -> anyhow::Result<()> {
+    let mut legacy = serde_json::to_value(record("a", "europe.fr", 2026))?;
+    let event = legacy.as_object_mut().expect("record object");
+    event.remove("lifecycle");
+    event.insert("status".to_string(), "cancelled".into());
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("events.json");
+    std::fs::write(
+        &path,
+        serde_json::json!({ "schema_version": 1, "events": { "a": legacy } }).to_string(),
+    )?;
+
+    let state = rics::store::load_state(&path)?;
+    let event = state.get("a").expect("record loaded");
+    assert_eq!(event.lifecycle, Lifecycle::Cancelled);
+    assert_eq!(event.cancelled_at, Some(event.last_modified));
+    assert!(state.query(&EventQuery::for_source("europe.fr")).is_empty());
+
+    Ok(())
+}
+
+fn record(uid: &str, source_key: &str, year: i32) -> EventRecord {
+    let now = Utc::now();
+    EventRecord {
+        uid: uid.to_string(),
+        source_key: source_key.to_string(),
+        source_name: source_key.to_string(),
+        title: format!("Event {uid}"),
+        time: EventTimeSpec::Date {
+            start: NaiveDate::from_ymd_opt(year, 6, 1).expect("valid date"),
+            end: None,
+        },
+        status: "scheduled".to_string(),
+        event_type: "event".to_string(),
+        created_at: now,
+        last_modified: now,
+        last_seen_at: now,
+        ..EventRecord::default()
+    }
+}
+
+#[test]
+fn sync_runs_are_recorded_and_bounded() -> anyhow::Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    std::fs::create_dir_all(&config_dir)?;
+    std::fs::write(
+        config_dir.join("releases.toml"),
+        r#"[source]
+key = "runs.releases"
+name = "Releases"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = """{"rows": [{"title": "GDP", "date": "2030-04-25"}]}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+"#,
+    )?;
+    let options = rics::pipeline::SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        ..rics::pipeline::SyncOptions::default()
+    };
+    rics::pipeline::sync_sources(&options)?;
+    rics::pipeline::sync_sources(&options)?;
+
+    let mut store = JsonFileStore::open(&options.state_path)?;
+    let runs = &store.state().runs;
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].rics_version, env!("CARGO_PKG_VERSION"));
+    let first = runs[0].source("runs.releases").expect("source summary");
+    let second = runs[1].source("runs.releases").expect("source summary");
+    assert_eq!((first.inserted, second.unchanged), (1, 1));
+    assert_eq!(first.config_hash.as_ref().map(String::len), Some(64));
+    assert_eq!(first.config_hash, second.config_hash);
+
+    let template = runs[0].clone();
+    for _ in 0..MAX_RUN_HISTORY {
+        store.record_run(template.clone());
+    }
+    assert_eq!(store.state().runs.len(), MAX_RUN_HISTORY);
+
+    Ok(())
+}
+
+#[test]
+fn sync_saves_each_source_as_it_goes_and_holds_the_state_lock() -> anyhow::Result<()> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    std::fs::create_dir_all(&config_dir)?;
+    let source = |key: &str, inline_data: &str| {
+        format!(
+            r#"[source]
+key = "{key}"
+name = "{key}"
+domain = "economics"
+
+[fetch]
+mode = "inline"
+inline_data = """{inline_data}"""
+
+[extract]
+format = "json"
+root_jsonpath = "/rows"
+
+[map.title]
+from = "json:/title"
+
+[map.date]
+from = "json:/date"
+"#
+        )
+    };
+    std::fs::write(
+        config_dir.join("a.toml"),
+        source(
+            "partial.a",
+            r#"{"rows": [{"title": "GDP", "date": "2030-04-25"}]}"#,
+        ),
+    )?;
+    std::fs::write(config_dir.join("b.toml"), source("partial.b", "not json"))?;
+    let options = rics::pipeline::SyncOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        jobs: 1,
+        ..rics::pipeline::SyncOptions::default()
+    };
+
+    // A bundle that fails to load aborts the run after every source merged.
+    let bundles = root.path().join("bundles");
+    std::fs::create_dir_all(&bundles)?;
+    std::fs::write(bundles.join("broken.toml"), "[bundle")?;
+
+    // Another run holding the lock keeps this one waiting.
+    let held = StateLock::acquire(&options.state_path)?;
+    assert!(StateLock::try_acquire(&options.state_path)?.is_none());
+    let sync = std::thread::spawn({
+        let options = options.clone();
+        move || rics::pipeline::sync_sources(&options).map(|_| ())
+    });
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert!(!options.state_path.exists());
+    drop(held);
+
+    let err = sync.join().expect("sync thread").unwrap_err();
+    assert!(format!("{err:#}").contains("broken.toml"), "{err:#}");
+    let store = JsonFileStore::open(&options.state_path)?;
+    let events = store.query(&EventQuery::default());
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].source_key, "partial.a");
+    assert!(store.state().runs.is_empty());
+    assert!(!root.path().join("state/events.json.tmp").exists());
+
+    // A source that fails to parse is reported, and the run goes on.
+    std::fs::remove_file(bundles.join("broken.toml"))?;
+    let reports = rics::pipeline::sync_sources(&options)?;
+    assert_eq!(reports.len(), 2);
+    assert!(reports[0].error.is_none());
+    let error = reports[1].error.as_deref().unwrap_or_default();
+    assert!(error.contains("partial.b"), "{error}");
+    assert!(!reports[1].rolled_back);
+    let store = JsonFileStore::open(&options.state_path)?;
+    assert_eq!(store.state().runs.len(), 1);
+    assert_eq!(store.state().runs[0].sources.len(), 2);
+    assert!(StateLock::try_acquire(&options.state_path)?.is_some());
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use chrono_tz::Tz;
+use rics::config::parse_source_config;
+use rics::timezones::{COUNTRIES, SUBDIVISIONS, derive_timezone, timezone_for_code};
+
+#[test]
+fn every_default_zone_is_a_known_tz_name() {
+    for (code, zone) in SUBDIVISIONS.iter().chain(COUNTRIES) {
+        assert!(
+            zone.parse::<Tz>().is_ok(),
+            "{code} maps to unknown zone {zone}"
+        );
+    }
+}
+
+#[test]
+fn codes_resolve_through_their_prefixes_and_fall_back_to_the_country() {
+    assert_eq!(timezone_for_code("US-TX-HOU"), Some("America/Chicago"));
+    assert_eq!(timezone_for_code("us-ca"), Some("America/Los_Angeles"));
+    assert_eq!(timezone_for_code("US-GU"), Some("America/New_York"));
+    assert_eq!(timezone_for_code("INTL"), None);
+    assert_eq!(
+        derive_timezone(Some("INTL"), Some("FR")),
+        Some(("Europe/Paris", "FR"))
+    );
+    assert_eq!(derive_timezone(None, None), None);
+}
+
+#[test]
+fn unset_source_timezone_defaults_from_jurisdiction() -> Result<()> {
+    let config = |source: &str| {
+        parse_source_config(&format!(
+            r#"[source]
+key = "tz.default"
+name = "Timezone Default"
+domain = "government"
+{source}
+
+[fetch]
+mode = "inline"
+inline_data = "2030-05-01 | Council meeting"
+
+[extract]
+format = "text"
+"#
+        ))
+    };
+
+    let derived = config("jurisdiction = \"US-IL-CHI\"\ndefault_country = \"US\"")?;
+    assert_eq!(derived.source.timezone.as_deref(), Some("America/Chicago"));
+
+    let explicit = config("timezone = \"UTC\"\njurisdiction = \"US-IL-CHI\"")?;
+    assert_eq!(explicit.source.timezone.as_deref(), Some("UTC"));
+
+    let unknown = config("jurisdiction = \"INTL\"")?;
+    assert_eq!(unknown.source.timezone, None);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "tui")]
+
+mod common;
+
+use anyhow::Result;
+use common::write_json_source;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::KeyCode;
+use rics::tui::{Action, App, Pane, TuiOptions};
+use std::fs;
+use tempfile::tempdir;
+
+fn screen(app: &mut App) -> Result<String> {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30))?;
+    terminal.draw(|frame| app.render(frame))?;
+    let buffer = terminal.backend().buffer();
+    Ok(buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[test]
+fn tui_browses_sources_and_syncs_the_selected_one() -> Result<()> {
+    let root = tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    write_json_source(
+        &config_dir.join("tui.alpha.toml"),
+        "tui.alpha",
+        "tui.alpha calendar",
+        "government",
+        r#"[{"title": "Alpha hearing", "date": "2099-05-01"}]"#,
+        "",
+    )?;
+    write_json_source(
+        &config_dir.join("tui.beta.toml"),
+        "tui.beta",
+        "tui.beta calendar",
+        "government",
+        r#"[{"title": "Beta budget vote", "date": "2099-05-01"}]"#,
+        "",
+    )?;
+
+    let mut app = App::new(TuiOptions {
+        config_dir,
+        state_path: root.path().join("state/events.json"),
+        out_dir: root.path().join("out"),
+        stale_after: chrono::Duration::days(1),
+    })?;
+    let before = screen(&mut app)?;
+    assert!(before.contains("! tui.alpha"), "{before}");
+    assert!(before.contains("tui.beta"));
+    assert!(!before.contains("Alpha hearing"));
+
+    assert_eq!(app.handle_key(KeyCode::Down), None);
+    assert_eq!(app.selected_source(), Some("tui.beta"));
+    assert_eq!(app.handle_key(KeyCode::Char('s')), Some(Action::Sync));
+    app.sync_selected();
+    assert_eq!(app.message, "synced tui.beta");
+
+    let upcoming = screen(&mut app)?;
+    assert!(upcoming.contains("2099-05-01"), "{upcoming}");
+    assert!(upcoming.contains("Beta budget vote"));
+    assert!(upcoming.contains("✓ tui.beta"));
+    assert!(upcoming.contains("! tui.alpha"));
+
+    app.handle_key(KeyCode::Char('2'));
+    assert_eq!(app.pane, Pane::Changes);
+    let changes = screen(&mut app)?;
+    assert!(changes.contains("new"), "{changes}");
+
+    app.handle_key(KeyCode::Right);
+    assert_eq!(app.pane, Pane::Health);
+    let health = screen(&mut app)?;
+    assert!(health.contains("status: healthy"), "{health}");
+    assert!(health.contains("Runs"));
+
+    // Alpha was never synced.
+    app.handle_key(KeyCode::Up);
+    assert!(screen(&mut app)?.contains("last success: never"));
+
+    assert_eq!(app.handle_key(KeyCode::Char('q')), Some(Action::Quit));
+    Ok(())
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use common::{sync_options, write_json_source};
+use rics::config::parse_source_config;
+use rics::pipeline::{UidOptions, load_state_for_read, predict_uid, sync_sources};
+use std::fs;
+
+const HEARINGS: &str = r#"[
+    {"id": "h-1", "title": "Zoning hearing", "date": "2030-03-01"},
+    {"title": "Budget hearing", "date": "2030-04-02"}
+]"#;
+
+fn sync_and_predict(uid: &str) -> Result<Vec<(String, String)>> {
+    let root = tempfile::tempdir()?;
+    let config_dir = root.path().join("sources");
+    fs::create_dir_all(&config_dir)?;
+    write_json_source(
+        &config_dir.join("hearings.toml"),
+        "uid.hearings",
+        "Hearings",
+        "government",
+        HEARINGS,
+        uid,
+    )?;
+    let options = sync_options(root.path());
+    sync_sources(&options)?;
+
+    let state = load_state_for_read(&options.state_path)?;
+    let mut pairs = Vec::new();
+    for event in state.events.values() {
+        let predicted = predict_uid(&UidOptions {
+            config_dir: config_dir.clone(),
+            source: "uid.hearings".to_string(),
+            title: event.title.clone(),
+            date: event.time.start_date(),
+            id: event.source_event_id.clone(),
+            url: None,
+        })?;
+        pairs.push((event.uid.clone(), predicted));
+    }
+    Ok(pairs)
+}
+
+#[test]
+fn predicted_uids_match_the_synced_ones() -> Result<()> {
+    let pairs = sync_and_predict("")?;
+    assert_eq!(pairs.len(), 2);
+    for (stored, predicted) in pairs {
+        assert_eq!(stored, predicted);
+        assert_eq!(stored.len(), "@rics.local".len() + 24);
+    }
+    Ok(())
+}
+
+#[test]
+fn uid_algorithm_and_length_are_configurable() -> Result<()> {
+    let pairs = sync_and_predict("[uid]\nalgorithm = \"sha512\"\nlength = 128")?;
+    for (stored, predicted) in pairs {
+        assert_eq!(stored, predicted);
+        assert_eq!(stored.len(), "@rics.local".len() + 128);
+    }
+
+    let root = tempfile::tempdir()?;
+    write_json_source(
+        &root.path().join("hearings.toml"),
+        "uid.hearings",
+        "Hearings",
+        "government",
+        HEARINGS,
+        "[uid]\nlength = 65",
+    )?;
+    let config = fs::read_to_string(root.path().join("hearings.toml"))?;
+    let err = parse_source_config(&config).unwrap_err();
+    assert!(format!("{err:#}").contains("uid.length must be between 16 and 64"));
+    Ok(())
+}
+
+#[test]
+fn an_event_url_outweighs_its_title_and_date() -> Result<()> {
+    let root = tempfile::tempdir()?;
+    write_json_source(
+        &root.path().join("hearings.toml"),
+        "uid.hearings",
+        "Hearings",
+        "government",
+        HEARINGS,
+        "",
+    )?;
+    let uid = |title: &str, year: i32| {
+        predict_uid(&UidOptions {
+            config_dir: root.path().to_path_buf(),
+            source: "uid.hearings".to_string(),
+            title: title.to_string(),
+            date: NaiveDate::from_ymd_opt(year, 1, 1),
+            id: None,
+            url: Some("https://example.org/hearings".to_string()),
+        })
+    };
+    assert_eq!(uid("Budget hearing", 2030)?, uid("Zoning hearing", 2031)?);
+    Ok(())
+}
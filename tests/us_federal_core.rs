@@ -33,8 +33,7 @@ fn us_federal_bundle_builds_from_multiple_sources() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports.len(), 2);
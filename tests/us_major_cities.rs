@@ -43,8 +43,7 @@ fn us_major_city_bundle_builds_from_multiple_cities() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports.len(), 2);
@@ -182,16 +181,12 @@ file_name_template = "us-major-cities-civic-{{year}}.ics"
 
     fs::write(
         data_dir.join("nyc.txt"),
-        concat!(
-            "2026 | Council calendar published | subtype=city_council_calendar | office=city_council | source_class=official | source_event_id=test-nyc-calendar | source_url=https://legistar.council.nyc.gov/ | description=Calendar.\\n",
-        ),
+        "2026 | Council calendar published | subtype=city_council_calendar | office=city_council | source_class=official | source_event_id=test-nyc-calendar | source_url=https://legistar.council.nyc.gov/ | description=Calendar.\\n",
     )?;
 
     fs::write(
         data_dir.join("la.txt"),
-        concat!(
-            "2026-05-14 | Planning Commission hearing | subtype=planning_hearing | office=city_planning_commission | source_class=official | source_event_id=test-la-hearing | source_url=https://planning.lacity.gov/about/calendar | description=Hearing.\\n",
-        ),
+        "2026-05-14 | Planning Commission hearing | subtype=planning_hearing | office=city_planning_commission | source_class=official | source_event_id=test-la-hearing | source_url=https://planning.lacity.gov/about/calendar | description=Hearing.\\n",
     )?;
 
     Ok(TempCityEnv {
@@ -43,8 +43,7 @@ fn us_pro_sports_bundle_builds_from_multiple_leagues() -> Result<()> {
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports.len(), 2);
@@ -182,16 +181,12 @@ file_name_template = "us-pro-sports-{{year}}.ics"
 
     fs::write(
         data_dir.join("nfl.txt"),
-        concat!(
-            "2026-05-14 | NFL: Schedule release | subtype=special_event | league=nfl | source_class=official | source_event_id=test-nfl-release | source_url=https://operations.nfl.com/updates/the-game/2026-nfl-schedule-announced/ | description=Schedule release.\\n",
-        ),
+        "2026-05-14 | NFL: Schedule release | subtype=special_event | league=nfl | source_class=official | source_event_id=test-nfl-release | source_url=https://operations.nfl.com/updates/the-game/2026-nfl-schedule-announced/ | description=Schedule release.\\n",
     )?;
 
     fs::write(
         data_dir.join("nba.txt"),
-        concat!(
-            "2026-02-05 | NBA: Trade deadline | subtype=trade_deadline | league=nba | source_class=official | source_event_id=test-nba-deadline | source_url=https://www.nba.com/news/key-dates?os=w | description=Trade deadline.\\n",
-        ),
+        "2026-02-05 | NBA: Trade deadline | subtype=trade_deadline | league=nba | source_class=official | source_event_id=test-nba-deadline | source_url=https://www.nba.com/news/key-dates?os=w | description=Trade deadline.\\n",
     )?;
 
     Ok(TempSportsEnv {
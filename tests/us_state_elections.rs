@@ -34,8 +34,7 @@ fn us_state_shared_feed_filters_events_by_state_and_builds_bundle() -> Result<()
         config_dir: env.config_dir.clone(),
         state_path: env.state_path.clone(),
         out_dir: env.out_dir.clone(),
-        source: None,
-        dry_run: false,
+        ..SyncOptions::default()
     })?;
 
     assert_eq!(reports.len(), 2);
@@ -49,7 +48,7 @@ fn us_state_shared_feed_filters_events_by_state_and_builds_bundle() -> Result<()
     assert!(state
         .events
         .values()
-        .all(|event| event.metadata.get("state").is_some()));
+        .all(|event| event.metadata.contains_key("state")));
     assert!(state
         .events
         .values()